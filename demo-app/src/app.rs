@@ -22,7 +22,7 @@ use iced::widget::Id;
 use iced::widget::operation::focus;
 use iced::{Event, Subscription, Task, Theme, event, window};
 #[cfg(not(target_arch = "wasm32"))]
-use iced_code_editor::LspEvent;
+use iced_code_editor::LspEventHub;
 #[cfg(not(target_arch = "wasm32"))]
 use iced_code_editor::LspOverlayState;
 #[cfg(not(target_arch = "wasm32"))]
@@ -32,8 +32,6 @@ use iced_code_editor::{Language, theme};
 #[cfg(not(target_arch = "wasm32"))]
 use std::collections::HashMap;
 
-#[cfg(not(target_arch = "wasm32"))]
-use std::sync::mpsc;
 #[cfg(not(target_arch = "wasm32"))]
 use std::time::{Duration, Instant};
 
@@ -98,10 +96,9 @@ pub struct DemoApp {
     pub auto_adjust_line_height: bool,
     /// Whether the editor options dropdown panel is expanded
     pub show_editor_options: bool,
+    /// Spawns the LSP clients, tagging their events with the tab they serve.
     #[cfg(not(target_arch = "wasm32"))]
-    lsp_events: Option<mpsc::Receiver<LspEvent>>,
-    #[cfg(not(target_arch = "wasm32"))]
-    lsp_event_sender: Option<mpsc::Sender<LspEvent>>,
+    lsp_hub: LspEventHub<EditorId>,
     /// Aggregated LSP overlay display state (hover + completion).
     #[cfg(not(target_arch = "wasm32"))]
     pub lsp_overlay: LspOverlayState,
@@ -166,10 +163,7 @@ greet("World")
         let next_tab_id = 1;
 
         #[cfg(not(target_arch = "wasm32"))]
-        let (lsp_event_sender, lsp_events) = {
-            let (event_tx, event_rx) = mpsc::channel();
-            (Some(event_tx), Some(event_rx))
-        };
+        let (lsp_hub, lsp_events) = LspEventHub::new();
 
         let app = Self {
             tabs,
@@ -187,9 +181,7 @@ greet("World")
             auto_adjust_line_height: true,
             show_editor_options: false,
             #[cfg(not(target_arch = "wasm32"))]
-            lsp_events,
-            #[cfg(not(target_arch = "wasm32"))]
-            lsp_event_sender,
+            lsp_hub,
             #[cfg(not(target_arch = "wasm32"))]
             lsp_overlay: LspOverlayState::new(),
             #[cfg(not(target_arch = "wasm32"))]
@@ -216,11 +208,14 @@ greet("World")
         // unit tests, and a synchronous spawn would make their behavior
         // depend on whether a language server happens to be on `PATH`.
         #[cfg(not(target_arch = "wasm32"))]
-        let startup_task = Task::done(Message::ToggleEditor(
-            active_tab_id,
-            EditorToggle::Lsp,
-            true,
-        ));
+        let startup_task = Task::batch([
+            lsp_events.map(Message::Lsp),
+            Task::done(Message::ToggleEditor(
+                active_tab_id,
+                EditorToggle::Lsp,
+                true,
+            )),
+        ]);
         #[cfg(target_arch = "wasm32")]
         let startup_task = Task::none();
 
@@ -237,15 +232,9 @@ greet("World")
         self.spinner_frame = (self.spinner_frame + 1) % 8;
 
         #[cfg(not(target_arch = "wasm32"))]
-        let lsp_task = {
-            self.process_lsp_hover_timers();
-            self.drain_lsp_events()
-        };
-        #[cfg(target_arch = "wasm32")]
-        let lsp_task = Task::none();
+        self.process_lsp_hover_timers();
 
         let mut tasks = Vec::new();
-        tasks.push(lsp_task);
 
         for tab in &mut self.tabs {
             let id = tab.id;
@@ -457,6 +446,8 @@ greet("World")
             }
             Message::TextInputClicked => self.handle_text_input_clicked(),
             #[cfg(not(target_arch = "wasm32"))]
+            Message::Lsp(event) => self.handle_lsp_event(event),
+            #[cfg(not(target_arch = "wasm32"))]
             Message::JumpToFile(path, line, col) => {
                 self.handle_jump_to_file(path, line, col)
             }
@@ -494,10 +485,10 @@ greet("World")
                     }
                     LspOverlayMessage::CompletionSelected(index) => {
                         self.lsp_applying_completion = true;
+                        self.lsp_overlay.completion_selected = index;
                         let completion = self
                             .lsp_overlay
-                            .completion_items
-                            .get(index)
+                            .selected_completion_item()
                             .cloned();
                         if let Some(item) = completion {
                             self.apply_completion(&item);
@@ -519,8 +510,8 @@ greet("World")
                             self.lsp_applying_completion = true;
                             let completion = self
                                 .lsp_overlay
-                                .selected_item()
-                                .map(str::to_owned);
+                                .selected_completion_item()
+                                .cloned();
                             if let Some(item) = completion {
                                 self.apply_completion(&item);
                             }
@@ -531,6 +522,9 @@ greet("World")
                         }
                         Task::none()
                     }
+                    LspOverlayMessage::LinkClicked(target) => {
+                        self.handle_lsp_link_clicked(&target)
+                    }
                 }
             }
             // Tab management
@@ -15,23 +15,14 @@ use iced::widget::Id;
 use iced::widget::operation::scroll_to;
 use iced::widget::scrollable;
 use iced_code_editor::{
-    LspDocument, LspEvent, LspLanguage, LspPosition, LspProcessClient,
-    Message as EditorMessage, lsp_language_for_extension,
+    LspCompletionItem, LspDocument, LspEditorEvent, LspEvent, LspLanguage,
+    LspPosition, Message as EditorMessage, lsp_language_for_extension,
     lsp_language_for_path,
 };
 use std::path::{Path, PathBuf};
-use std::sync::mpsc;
 use std::time::{Duration, Instant};
 use url::Url;
 
-/// Maximum LSP events processed in one [`DemoApp::drain_lsp_events`] call.
-///
-/// The event channel is unbounded, so without a per-tick budget a server that
-/// floods it would hold the UI thread in the drain loop for as long as the
-/// backlog took to process. Whatever is left over is picked up on the next
-/// tick, which keeps the frame time bounded instead of the queue.
-const MAX_LSP_EVENTS_PER_TICK: usize = 256;
-
 /// Returns the LSP language for a built-in template (all use Lua).
 fn lsp_language_for_template(template: Template) -> Option<LspLanguage> {
     lsp_language_for_extension(match template {
@@ -95,13 +86,55 @@ fn file_uri_to_path(uri: &str) -> Option<PathBuf> {
     Url::parse(uri).ok()?.to_file_path().ok()
 }
 
+/// Splits a file path written in the buffer into the file to open and the
+/// zero-based `(line, column)` to jump to.
+///
+/// The editor reports paths as written, with an optional one-based `:line`
+/// or `:line:column` suffix. `~/` expands to the home directory, and a
+/// relative path is resolved against `base` — the directory of the file the
+/// link appeared in.
+fn resolve_path_link(
+    target: &str,
+    base: &Path,
+    home: Option<&Path>,
+) -> (PathBuf, usize, usize) {
+    let mut path = target;
+    let mut numbers = Vec::new();
+    while numbers.len() < 2
+        && let Some((head, tail)) = path.rsplit_once(':')
+        && let Ok(number) = tail.parse::<usize>()
+    {
+        numbers.insert(0, number.saturating_sub(1));
+        path = head;
+    }
+
+    let path = match (path.strip_prefix("~/"), home) {
+        (Some(rest), Some(home)) => home.join(rest),
+        _ => base.join(path),
+    };
+    (
+        path,
+        numbers.first().copied().unwrap_or(0),
+        numbers.get(1).copied().unwrap_or(0),
+    )
+}
+
 impl DemoApp {
     /// Applies a completion item by inserting the text at the current cursor position
     /// and replacing the current word being typed
-    pub(super) fn apply_completion(&mut self, completion_text: &str) {
+    ///
+    /// Items the server sent with their own insert text insert that instead
+    /// of the label, through the editor's snippet engine when it is a snippet.
+    pub(super) fn apply_completion(&mut self, item: &LspCompletionItem) {
+        let completion_text = item.text_to_insert();
         if completion_text.is_empty() {
             return;
         }
+        let insertion = if item.is_snippet {
+            EditorMessage::InsertSnippet(completion_text.to_string())
+        } else {
+            EditorMessage::Paste(completion_text.to_string())
+        };
         if let Some(tab) =
             self.tabs.iter_mut().find(|t| t.id == self.active_tab_id)
         {
@@ -116,22 +149,17 @@ impl DemoApp {
             let chars_to_delete = col - word_start_col;
 
             // Delete the current word being typed and insert the completion
-            // as a single `Paste`, which inserts the text verbatim: unlike
-            // `CharacterInput`, it neither runs it through auto-close
-            // (spurious `)`/`"` for labels containing `(`/quotes) nor
-            // re-triggers LSP completion requests mid-insertion.
+            // as a single `Paste` (or `InsertSnippet`), which inserts the text
+            // verbatim: unlike `CharacterInput`, it neither runs it through
+            // auto-close (spurious `)`/`"` for labels containing `(`/quotes)
+            // nor re-triggers LSP completion requests mid-insertion.
             for _ in 0..chars_to_delete {
                 let _ = tab.editor.update(&EditorMessage::Backspace);
             }
-            let _ = tab
-                .editor
-                .update(&EditorMessage::Paste(completion_text.to_string()));
+            let _ = tab.editor.update(&insertion);
 
             tab.is_dirty = tab.editor.is_modified();
-            self.log(
-                "INFO",
-                &format!("Applied completion: {}", completion_text),
-            );
+            self.log("INFO", &format!("Applied completion: {}", item.label));
         }
     }
 
@@ -316,12 +344,6 @@ impl DemoApp {
             return false;
         }
 
-        // Check if we have an event sender for LSP communication
-        let Some(sender) = self.lsp_event_sender.as_ref().cloned() else {
-            self.detach_lsp_for_editor(editor_id);
-            return false;
-        };
-
         // Determine the root URI for the LSP server
         let Some(root_uri) = Self::lsp_root_uri_for_path(root_hint) else {
             self.log("ERROR", "LSP failed: root uri unavailable");
@@ -331,11 +353,7 @@ impl DemoApp {
 
         // Detach any existing LSP and create a new one
         self.detach_lsp_for_editor(editor_id);
-        match LspProcessClient::new_with_server(
-            &root_uri,
-            sender,
-            language.server_key,
-        ) {
+        match self.lsp_hub.spawn(editor_id, &root_uri, language.server_key) {
             Ok(client) => {
                 let Some(editor) = self.get_editor(editor_id) else {
                     self.log("ERROR", "Editor not found for LSP attach");
@@ -512,138 +530,158 @@ impl DemoApp {
         }
     }
 
-    /// Drains and processes pending LSP events from the event channel.
+    /// Follows a link clicked in a hover, documentation, or signature popup.
     ///
-    /// Handles hover responses and completion items from the LSP server, up to
-    /// [`MAX_LSP_EVENTS_PER_TICK`] per call. Anything still queued is picked up
-    /// on the next tick: the channel is unbounded, so a server that floods it
-    /// (rust-analyzer emits substantial output on a large workspace) would
-    /// otherwise hold the UI thread here for as long as the backlog took to
-    /// process.
-    pub(super) fn drain_lsp_events(&mut self) -> Task<Message> {
-        let Some(receiver) = self.lsp_events.take() else {
-            return Task::none();
-        };
-        let receiver = receiver;
-        let mut messages = Vec::new();
-        let mut disconnected = false;
-
-        for _ in 0..MAX_LSP_EVENTS_PER_TICK {
-            match receiver.try_recv() {
-                Ok(event) => match event {
-                    // Handle hover response from LSP server
-                    LspEvent::Hover { text } => {
-                        if text.trim().is_empty() {
-                            self.clear_lsp_hover();
-                        } else {
-                            self.lsp_overlay.show_hover(text);
-                            self.lsp_hover_hide_deadline = None;
-                            if self.lsp_overlay_editor.is_none() {
-                                self.lsp_overlay_editor =
-                                    Some(self.active_tab_id);
-                            }
-                        }
-                    }
-                    // Handle completion response from LSP server
-                    LspEvent::Completion { items } => {
-                        // Record cursor position for menu placement
-                        let position = self
-                            .tabs
-                            .iter()
-                            .find(|t| t.id == self.active_tab_id)
-                            .and_then(|tab| tab.editor.cursor_screen_position())
-                            .unwrap_or(iced::Point::new(4.0, 4.0));
-
-                        self.lsp_overlay.set_completions(items, position);
-
-                        if self.lsp_overlay_editor.is_none()
-                            && self.lsp_overlay.completion_visible
-                        {
-                            self.lsp_overlay_editor = Some(self.active_tab_id);
-                        }
+    /// `file://` targets open in the editor, honoring a `#L<line>` fragment
+    /// (one-based, as language servers write it); anything else is handed to
+    /// the system's default handler.
+    pub(super) fn handle_lsp_link_clicked(
+        &mut self,
+        target: &str,
+    ) -> Task<Message> {
+        if let Some(path) = file_uri_to_path(target) {
+            let line = Url::parse(target)
+                .ok()
+                .and_then(|url| {
+                    url.fragment()?.strip_prefix('L')?.parse::<usize>().ok()
+                })
+                .map_or(0, |line| line.saturating_sub(1));
+            return Task::done(Message::JumpToFile(path, line, 0));
+        }
+        match opener::open(target) {
+            Ok(()) => self.log("INFO", &format!("Opened link: {target}")),
+            Err(error) => self.log(
+                "ERROR",
+                &format!("Failed to open link {target}: {error}"),
+            ),
+        }
+        Task::none()
+    }
+
+    /// Follows a URL or file path Ctrl+Clicked in the editor `editor_id`.
+    ///
+    /// URLs take the same route as links clicked in LSP popups; a path opens
+    /// in the editor, at the line and column it names.
+    pub(super) fn handle_editor_link_activated(
+        &mut self,
+        editor_id: EditorId,
+        target: &str,
+    ) -> Task<Message> {
+        if target.contains("://") {
+            return self.handle_lsp_link_clicked(target);
+        }
+        let base = self
+            .tabs
+            .iter()
+            .find(|t| t.id == editor_id)
+            .and_then(|t| {
+                t.file_path.as_deref()?.parent().map(Path::to_path_buf)
+            })
+            .or_else(|| std::env::current_dir().ok())
+            .unwrap_or_default();
+        let home = std::env::var_os("HOME").map(PathBuf::from);
+        let (path, line, col) =
+            resolve_path_link(target, &base, home.as_deref());
+        Task::done(Message::JumpToFile(path, line, col))
+    }
+
+    /// Handles an event emitted by the LSP client of one editor.
+    ///
+    /// Replies the editor consumes itself (highlights, selection and linked
+    /// editing ranges, links, colors) go straight back to it through
+    /// [`CodeEditor::apply_lsp_event`](iced_code_editor::CodeEditor::apply_lsp_event);
+    /// the rest drives the overlay, navigation, progress spinner and log.
+    pub(super) fn handle_lsp_event(
+        &mut self,
+        event: LspEditorEvent<EditorId>,
+    ) -> Task<Message> {
+        let LspEditorEvent { editor: editor_id, event } = event;
+        if let Some(task) = self
+            .get_editor(editor_id)
+            .and_then(|editor| editor.apply_lsp_event(&event))
+        {
+            return task.map(move |e| Message::EditorEvent(editor_id, e));
+        }
+
+        match event {
+            LspEvent::Hover { text } => {
+                if text.trim().is_empty() {
+                    self.clear_lsp_hover();
+                } else {
+                    self.lsp_overlay.show_hover(text);
+                    self.lsp_hover_hide_deadline = None;
+                    if self.lsp_overlay_editor.is_none() {
+                        self.lsp_overlay_editor = Some(editor_id);
                     }
-                    // Handle definition response from LSP server
-                    LspEvent::Definition { uri, range } => {
-                        if let Some(path) = file_uri_to_path(&uri) {
-                            messages.push(Message::JumpToFile(
-                                path,
-                                range.start.line as usize,
-                                range.start.character as usize,
-                            ));
+                }
+            }
+            LspEvent::Completion { items } => {
+                // Record cursor position for menu placement
+                let position = self
+                    .get_editor(editor_id)
+                    .and_then(|editor| editor.cursor_screen_position())
+                    .unwrap_or(iced::Point::new(4.0, 4.0));
+
+                self.lsp_overlay.set_completions(items, position);
+
+                if self.lsp_overlay_editor.is_none()
+                    && self.lsp_overlay.completion_visible
+                {
+                    self.lsp_overlay_editor = Some(editor_id);
+                }
+            }
+            LspEvent::SignatureHelp { help } => {
+                let position = self
+                    .get_editor(editor_id)
+                    .and_then(|editor| editor.cursor_screen_position())
+                    .unwrap_or(iced::Point::new(4.0, 4.0));
+                self.lsp_overlay.show_signature_help(help, position);
+            }
+            LspEvent::Definition { uri, range } => {
+                if let Some(path) = file_uri_to_path(&uri) {
+                    return Task::done(Message::JumpToFile(
+                        path,
+                        range.start.line as usize,
+                        range.start.character as usize,
+                    ));
+                }
+            }
+            LspEvent::Progress {
+                token,
+                server_key,
+                title,
+                message,
+                percentage,
+                done,
+            } => {
+                if done {
+                    if let Some(map) = self.lsp_progress.get_mut(&server_key) {
+                        map.remove(&token);
+                        if map.is_empty() {
+                            self.lsp_progress.remove(&server_key);
                         }
                     }
-                    // Handle progress notification from LSP server
-                    LspEvent::Progress {
+                } else {
+                    self.lsp_progress.entry(server_key).or_default().insert(
                         token,
-                        server_key,
-                        title,
-                        message,
-                        percentage,
-                        done,
-                    } => {
-                        if done {
-                            if let Some(map) =
-                                self.lsp_progress.get_mut(&server_key)
-                            {
-                                map.remove(&token);
-                                if map.is_empty() {
-                                    self.lsp_progress.remove(&server_key);
-                                }
-                            }
-                        } else {
-                            self.lsp_progress
-                                .entry(server_key)
-                                .or_default()
-                                .insert(
-                                    token,
-                                    LspProgress { title, message, percentage },
-                                );
-                        }
-                    }
-                    LspEvent::Log { server_key, message } => {
-                        self.log(
-                            "LSP",
-                            &format!("[{}] {}", server_key, message),
-                        );
-                    }
-                },
-                // No more events available right now
-                Err(mpsc::TryRecvError::Empty) => break,
-                // LSP process has disconnected
-                Err(mpsc::TryRecvError::Disconnected) => {
-                    disconnected = true;
-                    break;
+                        LspProgress { title, message, percentage },
+                    );
                 }
             }
+            LspEvent::Log { server_key, message } => {
+                self.log("LSP", &format!("[{}] {}", server_key, message));
+            }
+            // A reply for an editor whose tab has been closed since.
+            _ => {}
         }
-
-        // Keep the receiver unless the server is gone — including when the
-        // per-tick budget ran out with events still queued, which is the case
-        // the loop exits without taking either branch above.
-        if !disconnected {
-            self.lsp_events = Some(receiver);
-        }
-
-        if messages.is_empty() {
-            Task::none()
-        } else {
-            Task::batch(
-                messages
-                    .into_iter()
-                    .map(|msg| Task::perform(async move { msg }, |m| m)),
-            )
-        }
+        Task::none()
     }
 }
 
 #[cfg(test)]
 mod tests {
-    // A handful of tests below carry an `#[allow(clippy::unwrap_used)]` on
-    // `mpsc::Sender::send(..).unwrap()`. The channel is created and drained
-    // within the same test, so a failed send means the test setup itself is
-    // broken — a panic is the right failure report there, matching the
-    // existing per-test allows in `history.rs`/`command.rs`.
     use super::*;
+    use iced_code_editor::LspCompletionItem;
     use std::path::{Path, PathBuf};
 
     #[test]
@@ -677,6 +715,29 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_resolve_path_link_reads_line_and_column_suffixes() {
+        let base = Path::new("/work/src");
+        let home = Some(Path::new("/home/me"));
+
+        assert_eq!(
+            resolve_path_link("./main.rs:12:5", base, home),
+            (PathBuf::from("/work/src/./main.rs"), 11, 4)
+        );
+        assert_eq!(
+            resolve_path_link("../README.md:3", base, home),
+            (PathBuf::from("/work/src/../README.md"), 2, 0)
+        );
+        assert_eq!(
+            resolve_path_link("~/notes.txt", base, home),
+            (PathBuf::from("/home/me/notes.txt"), 0, 0)
+        );
+        assert_eq!(
+            resolve_path_link("/etc/hosts", base, home),
+            (PathBuf::from("/etc/hosts"), 0, 0)
+        );
+    }
+
     #[test]
     fn test_file_uri_to_path_rejects_other_schemes() {
         // Template buffers use `untitled://`; they have no filesystem path.
@@ -726,7 +787,7 @@ mod tests {
             let _ = tab.editor.set_cursor(0, 2);
         }
 
-        app.apply_completion("foobar");
+        app.apply_completion(&LspCompletionItem::from("foobar"));
 
         // `.map()` instead of `if let` for the assertion: a missing tab must
         // fail the comparison against `Some(...)` rather than silently skip it.
@@ -734,6 +795,55 @@ mod tests {
         assert_eq!(content.as_deref(), Some("foobar"));
     }
 
+    #[test]
+    fn test_apply_completion_expands_snippet_items() {
+        let (mut app, _) = DemoApp::new();
+        if let Some(tab) = app.get_active_tab() {
+            let _ = tab.editor.reset("pu");
+            let _ = tab.editor.set_cursor(0, 2);
+        }
+        let push = LspCompletionItem {
+            insert_text: Some("push(${1:value})".to_string()),
+            is_snippet: true,
+            ..LspCompletionItem::from("push")
+        };
+        app.apply_completion(&push);
+
+        let content = app.get_active_tab().map(|tab| tab.editor.content());
+        assert_eq!(content.as_deref(), Some("push(value)"));
+        // The placeholder is selected, ready to be typed over.
+        let active =
+            app.get_active_tab().map(|tab| tab.editor.is_snippet_active());
+        assert_eq!(active, Some(true));
+    }
+
+    #[test]
+    fn test_confirming_a_completion_inserts_the_selected_overload() {
+        let (mut app, _) = DemoApp::new();
+        if let Some(tab) = app.get_active_tab() {
+            let _ = tab.editor.reset("ne");
+            let _ = tab.editor.set_cursor(0, 2);
+        }
+        let overload = |insert_text: &str| LspCompletionItem {
+            insert_text: Some(insert_text.to_string()),
+            is_snippet: true,
+            ..LspCompletionItem::from("new")
+        };
+        app.lsp_overlay.set_completions(
+            vec![overload("new()"), overload("new(${1:capacity})")],
+            iced::Point::ORIGIN,
+        );
+        app.lsp_overlay.completion_visible = true;
+        app.lsp_overlay.navigate(1);
+
+        let _ = app.update(Message::LspOverlay(
+            iced_code_editor::LspOverlayMessage::CompletionConfirm,
+        ));
+
+        let content = app.get_active_tab().map(|tab| tab.editor.content());
+        assert_eq!(content.as_deref(), Some("new(capacity)"));
+    }
+
     #[test]
     fn test_apply_completion_does_not_trigger_auto_close() {
         // Regression: inserting a completion label containing '(' through
@@ -745,7 +855,7 @@ mod tests {
             let _ = tab.editor.set_cursor(0, 1);
         }
 
-        app.apply_completion("foo()");
+        app.apply_completion(&LspCompletionItem::from("foo()"));
 
         let content = app.get_active_tab().map(|tab| tab.editor.content());
         assert_eq!(content.as_deref(), Some("foo()"));
@@ -758,7 +868,7 @@ mod tests {
             let _ = tab.editor.reset("");
         }
 
-        app.apply_completion("value");
+        app.apply_completion(&LspCompletionItem::from("value"));
 
         let cursor =
             app.get_active_tab().map(|tab| tab.editor.cursor_position());
@@ -777,7 +887,7 @@ mod tests {
             let _ = tab.editor.set_cursor(0, 3);
         }
 
-        app.apply_completion("foobar");
+        app.apply_completion(&LspCompletionItem::from("foobar"));
 
         let after_completion =
             app.get_active_tab().map(|tab| tab.editor.content());
@@ -797,65 +907,116 @@ mod tests {
             let _ = tab.editor.reset("word");
         }
 
-        app.apply_completion("");
+        app.apply_completion(&LspCompletionItem::from(""));
 
         let content = app.get_active_tab().map(|tab| tab.editor.content());
         assert_eq!(content.as_deref(), Some("word"));
     }
 
-    // ---- drain_lsp_events ----
+    // ---- handle_lsp_event ----
+
+    /// Feeds `event` to the app as if the active tab's client emitted it.
+    fn active_tab_event(app: &mut DemoApp, event: LspEvent) -> Task<Message> {
+        let editor = app.active_tab_id;
+        app.handle_lsp_event(LspEditorEvent { editor, event })
+    }
 
     #[test]
-    #[allow(clippy::unwrap_used)]
-    fn test_drain_lsp_events_shows_hover_text() {
+    fn test_handle_lsp_event_shows_hover_text() {
         let (mut app, _) = DemoApp::new();
-        let (tx, rx) = mpsc::channel();
-        app.lsp_events = Some(rx);
-        tx.send(LspEvent::Hover { text: "docs".to_string() }).unwrap();
 
-        let _ = app.drain_lsp_events();
+        let _ = active_tab_event(
+            &mut app,
+            LspEvent::Hover { text: "docs".to_string() },
+        );
 
         assert!(app.lsp_overlay.hover_visible);
         assert_eq!(app.lsp_overlay_editor, Some(app.active_tab_id));
     }
 
     #[test]
-    #[allow(clippy::unwrap_used)]
-    fn test_drain_lsp_events_empty_hover_clears_overlay() {
+    fn test_handle_lsp_event_attaches_overlay_to_the_originating_editor() {
+        let (mut app, _) = DemoApp::new();
+        let first = app.active_tab_id;
+        let _ = app.update(Message::NewTab);
+        assert_ne!(app.active_tab_id, first);
+
+        // The reply answers the first tab's request, whichever tab is active
+        // by the time it arrives.
+        let _ = app.handle_lsp_event(LspEditorEvent {
+            editor: first,
+            event: LspEvent::Hover { text: "docs".to_string() },
+        });
+
+        assert_eq!(app.lsp_overlay_editor, Some(first));
+    }
+
+    #[test]
+    fn test_handle_lsp_event_empty_hover_clears_overlay() {
         let (mut app, _) = DemoApp::new();
         app.lsp_overlay.show_hover("stale".to_string());
         app.lsp_hover_anchor =
             Some((app.active_tab_id, LspPosition { line: 0, character: 0 }));
-        let (tx, rx) = mpsc::channel();
-        app.lsp_events = Some(rx);
-        tx.send(LspEvent::Hover { text: String::new() }).unwrap();
 
-        let _ = app.drain_lsp_events();
+        let _ =
+            active_tab_event(&mut app, LspEvent::Hover { text: String::new() });
 
         assert!(!app.lsp_overlay.hover_visible);
         assert!(app.lsp_hover_anchor.is_none());
     }
 
     #[test]
-    #[allow(clippy::unwrap_used)]
-    fn test_drain_lsp_events_completion_shows_overlay() {
+    fn test_handle_lsp_event_completion_shows_overlay() {
         let (mut app, _) = DemoApp::new();
-        let (tx, rx) = mpsc::channel();
-        app.lsp_events = Some(rx);
-        tx.send(LspEvent::Completion {
-            items: vec!["foo".to_string(), "bar".to_string()],
-        })
-        .unwrap();
 
-        let _ = app.drain_lsp_events();
+        let _ = active_tab_event(
+            &mut app,
+            LspEvent::Completion { items: vec!["foo".into(), "bar".into()] },
+        );
 
         assert!(app.lsp_overlay.completion_visible);
         assert_eq!(app.lsp_overlay_editor, Some(app.active_tab_id));
     }
 
     #[test]
-    #[allow(clippy::unwrap_used)]
-    fn test_drain_lsp_events_progress_done_removes_entry() {
+    fn test_handle_lsp_event_signature_help_shows_and_closes_popup() {
+        let (mut app, _) = DemoApp::new();
+
+        let _ = active_tab_event(
+            &mut app,
+            LspEvent::SignatureHelp {
+                help: Some(iced_code_editor::LspSignatureHelp {
+                    label: "fn add(a: i32, b: i32)".to_string(),
+                    documentation: None,
+                    active_parameter: Some((7, 13)),
+                }),
+            },
+        );
+        assert!(app.lsp_overlay.signature_help.is_some());
+
+        let _ =
+            active_tab_event(&mut app, LspEvent::SignatureHelp { help: None });
+        assert!(app.lsp_overlay.signature_help.is_none());
+    }
+
+    #[test]
+    fn test_handle_lsp_link_clicked_opens_file_links_in_the_editor() {
+        let (mut app, _) = DemoApp::new();
+        let target = if cfg!(windows) {
+            "file:///C:/tmp/lib.rs#L12"
+        } else {
+            "file:///tmp/lib.rs#L12"
+        };
+
+        // A file link becomes an in-editor jump rather than an external open,
+        // so nothing is logged.
+        let log_len = app.log_messages.len();
+        let _ = app.handle_lsp_link_clicked(target);
+        assert_eq!(app.log_messages.len(), log_len);
+    }
+
+    #[test]
+    fn test_handle_lsp_event_progress_done_removes_entry() {
         let (mut app, _) = DemoApp::new();
         app.lsp_progress
             .entry("rust-analyzer".to_string())
@@ -868,36 +1029,33 @@ mod tests {
                     percentage: Some(50),
                 },
             );
-        let (tx, rx) = mpsc::channel();
-        app.lsp_events = Some(rx);
-        tx.send(LspEvent::Progress {
-            token: "token-1".to_string(),
-            server_key: "rust-analyzer".to_string(),
-            title: "Indexing".to_string(),
-            message: None,
-            percentage: None,
-            done: true,
-        })
-        .unwrap();
-
-        let _ = app.drain_lsp_events();
+
+        let _ = active_tab_event(
+            &mut app,
+            LspEvent::Progress {
+                token: "token-1".to_string(),
+                server_key: "rust-analyzer".to_string(),
+                title: "Indexing".to_string(),
+                message: None,
+                percentage: None,
+                done: true,
+            },
+        );
 
         assert!(!app.lsp_progress.contains_key("rust-analyzer"));
     }
 
     #[test]
-    #[allow(clippy::unwrap_used)]
-    fn test_drain_lsp_events_log_appends_message() {
+    fn test_handle_lsp_event_log_appends_message() {
         let (mut app, _) = DemoApp::new();
-        let (tx, rx) = mpsc::channel();
-        app.lsp_events = Some(rx);
-        tx.send(LspEvent::Log {
-            server_key: "gopls".to_string(),
-            message: "started".to_string(),
-        })
-        .unwrap();
 
-        let _ = app.drain_lsp_events();
+        let _ = active_tab_event(
+            &mut app,
+            LspEvent::Log {
+                server_key: "gopls".to_string(),
+                message: "started".to_string(),
+            },
+        );
 
         assert_eq!(
             app.log_messages.last().map(String::as_str),
@@ -906,54 +1064,16 @@ mod tests {
     }
 
     #[test]
-    fn test_drain_lsp_events_disconnected_sender_clears_receiver() {
+    fn test_handle_lsp_event_ignores_replies_for_closed_tabs() {
         let (mut app, _) = DemoApp::new();
-        let (tx, rx) = mpsc::channel::<LspEvent>();
-        app.lsp_events = Some(rx);
-        drop(tx);
-
-        let _ = app.drain_lsp_events();
-
-        assert!(app.lsp_events.is_none());
-    }
-
-    #[test]
-    #[allow(clippy::unwrap_used)]
-    fn test_drain_lsp_events_stops_at_the_per_tick_budget() {
-        let (mut app, _) = DemoApp::new();
-        let (tx, rx) = mpsc::channel();
-        app.lsp_events = Some(rx);
-
-        let queued = MAX_LSP_EVENTS_PER_TICK + 20;
-        for index in 0..queued {
-            tx.send(LspEvent::Log {
-                server_key: "gopls".to_string(),
-                message: index.to_string(),
-            })
-            .unwrap();
-        }
         let logs_before = app.log_messages.len();
 
-        let _ = app.drain_lsp_events();
-
-        // One tick handles at most the budget, so a flooding server cannot
-        // hold the UI thread here until the whole backlog is drained.
-        assert_eq!(
-            app.log_messages.len() - logs_before,
-            MAX_LSP_EVENTS_PER_TICK
-        );
-
-        // The receiver must survive a budget-limited exit, or the remaining
-        // events — and every later one — would be lost with it.
-        assert!(app.lsp_events.is_some());
+        let _ = app.handle_lsp_event(LspEditorEvent {
+            editor: EditorId(99),
+            event: LspEvent::DocumentLinks { links: Vec::new() },
+        });
 
-        // The leftovers arrive on the following ticks, in order.
-        let _ = app.drain_lsp_events();
-        assert_eq!(app.log_messages.len() - logs_before, queued);
-        assert_eq!(
-            app.log_messages.last().map(String::as_str),
-            Some(format!("[LSP] [gopls] {}", queued - 1).as_str())
-        );
+        assert_eq!(app.log_messages.len(), logs_before);
     }
 
     // ---- process_lsp_hover_timers ----
@@ -71,6 +71,9 @@ pub enum Message {
     SelectTab(EditorId),
     /// New empty tab
     NewTab,
+    /// Event from the LSP client of an editor
+    #[cfg(not(target_arch = "wasm32"))]
+    Lsp(iced_code_editor::LspEditorEvent<EditorId>),
     #[cfg(not(target_arch = "wasm32"))]
     LspOverlay(iced_code_editor::LspOverlayMessage),
     #[cfg(not(target_arch = "wasm32"))]
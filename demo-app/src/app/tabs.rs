@@ -238,6 +238,11 @@ impl DemoApp {
 
         #[cfg(not(target_arch = "wasm32"))]
         {
+            // Escape dismisses parameter hints along with everything else
+            if matches!(event, EditorMessage::CloseSearch) {
+                self.lsp_overlay.clear_signature_help();
+            }
+
             // Intercept Escape to close completion menu
             if matches!(event, EditorMessage::CloseSearch)
                 && self.lsp_overlay.completion_visible
@@ -315,9 +320,20 @@ impl DemoApp {
             tab.editor.lsp_request_definition_at(*point);
         }
         #[cfg(not(target_arch = "wasm32"))]
+        if let EditorMessage::LinkActivated { target } = event {
+            return Task::batch([
+                task,
+                self.handle_editor_link_activated(editor_id, target),
+            ]);
+        }
+        #[cfg(not(target_arch = "wasm32"))]
         if let EditorMessage::CharacterInput(ch) = event
             && !self.lsp_applying_completion
         {
+            // Closing the call ends its parameter hints
+            if *ch == ')' {
+                self.lsp_overlay.clear_signature_help();
+            }
             // If input is not a word character, clear completion state
             if !ch.is_alphanumeric() && *ch != '_' {
                 self.lsp_overlay.clear_completions();
@@ -483,6 +483,7 @@ impl CodeEditor {
             self.cursors.primary_position()
         };
         self.cursors.set_single(position);
+        self.column_selection = None;
         self.is_dragging = false;
         self.overlay_cache.clear();
     }
@@ -791,8 +792,10 @@ impl CodeEditor {
     /// An inline color preview is a small square drawn just after a color
     /// literal — `#1e1e2e`, `0xFF6B6B`, `rgb(58, 123, 213)` — filled with the
     /// color the literal denotes, so a palette can be read without decoding
-    /// hexadecimal by eye. Toggling this setting clears the content cache to
-    /// trigger an immediate redraw.
+    /// hexadecimal by eye. Clicking a swatch opens a color picker that edits
+    /// the literal in place. With a language server attached, the colors it
+    /// reports (`textDocument/documentColor`) get a swatch as well. Toggling
+    /// this setting clears the content cache to trigger an immediate redraw.
     ///
     /// # Arguments
     ///
@@ -809,6 +812,8 @@ impl CodeEditor {
     pub fn set_show_color_previews(&mut self, enabled: bool) {
         if self.show_color_previews != enabled {
             self.show_color_previews = enabled;
+            self.document_color_state.clear();
+            self.commit_color_picker();
             self.content_cache.clear();
         }
     }
@@ -955,6 +960,152 @@ impl CodeEditor {
         self.bracket_pair_colorization_enabled
     }
 
+    /// Enables or disables highlighting the occurrences of the symbol under
+    /// the cursor.
+    ///
+    /// When enabled, resting the cursor on an identifier paints its other
+    /// occurrences — as reported by the attached language server, or found
+    /// as whole-word matches when there is none — and `F7`/`Shift+F7` step
+    /// between them. Disabling drops any highlight currently shown.
+    ///
+    /// # Arguments
+    ///
+    /// * `enabled` - Whether to highlight occurrences of the symbol under the
+    ///   cursor
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use iced_code_editor::CodeEditor;
+    ///
+    /// let mut editor = CodeEditor::new("fn main() {}", "rs");
+    /// editor.set_document_highlight_enabled(false); // Disable
+    /// ```
+    pub fn set_document_highlight_enabled(&mut self, enabled: bool) {
+        if self.document_highlight_enabled != enabled {
+            self.document_highlight_enabled = enabled;
+            self.document_highlight_state.clear();
+            self.overlay_cache.clear();
+        }
+    }
+
+    /// Returns whether occurrences of the symbol under the cursor are
+    /// highlighted.
+    ///
+    /// # Returns
+    ///
+    /// `true` if document highlights are enabled, `false` otherwise
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use iced_code_editor::CodeEditor;
+    ///
+    /// let mut editor = CodeEditor::new("fn main() {}", "rs");
+    /// // Enabled by default.
+    /// assert!(editor.document_highlight_enabled());
+    ///
+    /// editor.set_document_highlight_enabled(false);
+    /// assert!(!editor.document_highlight_enabled());
+    /// ```
+    pub fn document_highlight_enabled(&self) -> bool {
+        self.document_highlight_enabled
+    }
+
+    /// Enables or disables linked editing.
+    ///
+    /// When enabled, editing an HTML or XML tag name renames its partner
+    /// tag as you type — paired by the attached language server's
+    /// `textDocument/linkedEditingRange` answer, or by a lexical tag scan in
+    /// markup files when there is none.
+    ///
+    /// # Arguments
+    ///
+    /// * `enabled` - Whether to mirror edits into linked ranges
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use iced_code_editor::CodeEditor;
+    ///
+    /// let mut editor = CodeEditor::new("<div></div>", "html");
+    /// editor.set_linked_editing_enabled(false); // Disable
+    /// ```
+    pub fn set_linked_editing_enabled(&mut self, enabled: bool) {
+        self.linked_editing_enabled = enabled;
+        self.linked_editing_state.ranges.clear();
+    }
+
+    /// Returns whether edits are mirrored into linked ranges.
+    ///
+    /// # Returns
+    ///
+    /// `true` if linked editing is enabled, `false` otherwise
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use iced_code_editor::CodeEditor;
+    ///
+    /// let mut editor = CodeEditor::new("<div></div>", "html");
+    /// // Enabled by default.
+    /// assert!(editor.linked_editing_enabled());
+    ///
+    /// editor.set_linked_editing_enabled(false);
+    /// assert!(!editor.linked_editing_enabled());
+    /// ```
+    pub fn linked_editing_enabled(&self) -> bool {
+        self.linked_editing_enabled
+    }
+
+    /// Enables or disables document links.
+    ///
+    /// When enabled, holding Ctrl (Cmd on macOS) underlines the URL or file
+    /// path under the mouse, and clicking it publishes
+    /// [`Message::LinkActivated`](crate::Message::LinkActivated) instead of
+    /// [`Message::JumpClick`](crate::Message::JumpClick). Links are detected
+    /// lexically, and also requested from the attached language server
+    /// through `textDocument/documentLink`.
+    ///
+    /// # Arguments
+    ///
+    /// * `enabled` - Whether URLs and file paths can be Ctrl+Clicked
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use iced_code_editor::CodeEditor;
+    ///
+    /// let mut editor = CodeEditor::new("// https://example.com", "rs");
+    /// editor.set_document_links_enabled(false); // Disable
+    /// ```
+    pub fn set_document_links_enabled(&mut self, enabled: bool) {
+        self.document_links_enabled = enabled;
+        self.document_link_state.clear();
+    }
+
+    /// Returns whether URLs and file paths can be Ctrl+Clicked.
+    ///
+    /// # Returns
+    ///
+    /// `true` if document links are enabled, `false` otherwise
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use iced_code_editor::CodeEditor;
+    ///
+    /// let mut editor = CodeEditor::new("// https://example.com", "rs");
+    /// // Enabled by default.
+    /// assert!(editor.document_links_enabled());
+    ///
+    /// editor.set_document_links_enabled(false);
+    /// assert!(!editor.document_links_enabled());
+    /// ```
+    pub fn document_links_enabled(&self) -> bool {
+        self.document_links_enabled
+    }
+
     /// Enables or disables code folding (collapse/expand blocks).
     ///
     /// When disabled, no fold chevrons are drawn and all lines are shown
@@ -1002,11 +1153,63 @@ impl CodeEditor {
         self.folding_enabled
     }
 
+    /// Shows or hides the breakpoint margin.
+    ///
+    /// When enabled, a narrow margin at the left edge of the gutter shows
+    /// breakpoints and the execution arrow; clicking it, or pressing `F9`,
+    /// toggles a breakpoint on that line. Breakpoints set through the API are
+    /// kept either way.
+    ///
+    /// # Arguments
+    ///
+    /// * `enabled` - Whether to show the breakpoint margin
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use iced_code_editor::CodeEditor;
+    ///
+    /// let mut editor = CodeEditor::new("fn main() {}", "rs");
+    /// editor.set_breakpoints_enabled(true);
+    /// ```
+    pub fn set_breakpoints_enabled(&mut self, enabled: bool) {
+        if self.breakpoints_enabled != enabled {
+            self.breakpoints_enabled = enabled;
+            self.content_cache.clear();
+            self.overlay_cache.clear();
+        }
+    }
+
+    /// Returns whether the breakpoint margin is shown.
+    ///
+    /// # Returns
+    ///
+    /// `true` if the breakpoint margin is shown, `false` otherwise
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use iced_code_editor::CodeEditor;
+    ///
+    /// let mut editor = CodeEditor::new("fn main() {}", "rs");
+    /// // Disabled by default.
+    /// assert!(!editor.breakpoints_enabled());
+    ///
+    /// editor.set_breakpoints_enabled(true);
+    /// assert!(editor.breakpoints_enabled());
+    /// ```
+    pub fn breakpoints_enabled(&self) -> bool {
+        self.breakpoints_enabled
+    }
+
     /// Enables or disables automatic indentation on Enter.
     ///
     /// When enabled, pressing Enter copies the leading whitespace of the
-    /// current line to the new line. When disabled, the cursor is placed
-    /// at column 0 on the new line.
+    /// current line to the new line, or follows the syntax's indentation
+    /// rules when it has some (see [`CodeEditor::indent_rules`]), which
+    /// also outdent a line when a closing token such as `}` is typed at its
+    /// start. When disabled, the cursor is placed at column 0 on the new
+    /// line.
     ///
     /// # Arguments
     ///
@@ -1044,6 +1247,97 @@ impl CodeEditor {
         self.auto_indent_enabled
     }
 
+    /// Enables or disables re-indentation of pasted text.
+    ///
+    /// When enabled, pasting text spanning several lines with a single
+    /// cursor re-indents the pasted lines with the syntax's indentation
+    /// rules (see [`CodeEditor::indent_rules`]), in the same undo step as
+    /// the paste. Syntaxes without rules paste as is.
+    ///
+    /// # Arguments
+    ///
+    /// * `enabled` - `true` to re-indent pasted text, `false` to paste it
+    ///   verbatim
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use iced_code_editor::CodeEditor;
+    ///
+    /// let mut editor = CodeEditor::new("fn main() {}", "rs");
+    /// editor.set_reindent_on_paste(true);
+    /// assert!(editor.reindent_on_paste());
+    /// ```
+    pub fn set_reindent_on_paste(&mut self, enabled: bool) {
+        self.reindent_on_paste = enabled;
+    }
+
+    /// Returns whether pasted text is re-indented.
+    ///
+    /// # Returns
+    ///
+    /// `true` if re-indentation on paste is enabled, `false` otherwise
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use iced_code_editor::CodeEditor;
+    ///
+    /// let editor = CodeEditor::new("fn main() {}", "rs");
+    /// // Disabled by default.
+    /// assert!(!editor.reindent_on_paste());
+    /// ```
+    pub fn reindent_on_paste(&self) -> bool {
+        self.reindent_on_paste
+    }
+
+    /// Sets the characters that end a word for word-wise moves and
+    /// deletions (`Ctrl+Left`/`Right`, `Ctrl+Backspace`/`Delete`).
+    ///
+    /// Whitespace always separates words. A run of separators counts as a
+    /// word of its own, so `Ctrl+Right` steps over `->` in one move. The
+    /// default is every ASCII punctuation character except `_`; Vim word
+    /// motions always use it.
+    ///
+    /// # Arguments
+    ///
+    /// * `separators` - The characters that separate words
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use iced_code_editor::CodeEditor;
+    ///
+    /// let mut editor = CodeEditor::new("(kebab-case)", "css");
+    /// // Treat `-` as part of a word.
+    /// editor.set_word_separators("()[]{};:,.");
+    /// assert_eq!(editor.word_separators(), "()[]{};:,.");
+    /// ```
+    pub fn set_word_separators(&mut self, separators: &str) {
+        self.word_separators = separators.to_string();
+    }
+
+    /// Returns the characters that end a word for word-wise moves and
+    /// deletions.
+    ///
+    /// # Returns
+    ///
+    /// The separator characters, whitespace excluded
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use iced_code_editor::CodeEditor;
+    ///
+    /// let editor = CodeEditor::new("fn main() {}", "rs");
+    /// // ASCII punctuation except `_` by default.
+    /// assert!(editor.word_separators().contains('-'));
+    /// assert!(!editor.word_separators().contains('_'));
+    /// ```
+    pub fn word_separators(&self) -> &str {
+        &self.word_separators
+    }
+
     /// Enables or disables auto-closing of brackets and quotes.
     ///
     /// When enabled, typing an opening bracket/quote (`(`, `[`, `{`, `"`,
@@ -1131,6 +1425,48 @@ impl CodeEditor {
         self.indent_style
     }
 
+    /// Enables or disables picking the indentation style from the content.
+    ///
+    /// When enabled (the default), [`CodeEditor::new`] and
+    /// [`CodeEditor::reset`] sample the leading whitespace of the text and
+    /// switch to the [`IndentStyle`] it uses. Text without indentation keeps
+    /// the current style.
+    ///
+    /// # Arguments
+    ///
+    /// * `enabled` - Whether to detect the indentation style
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use iced_code_editor::{CodeEditor, IndentStyle};
+    ///
+    /// let mut editor = CodeEditor::new("fn main() {\n\tlet x = 1;\n}", "rs");
+    /// assert_eq!(editor.indent_style(), IndentStyle::Tab);
+    ///
+    /// editor.set_detect_indentation(false);
+    /// editor.set_indent_style(IndentStyle::Spaces(2));
+    /// let _ = editor.reset("fn main() {\n\tlet x = 1;\n}");
+    /// assert_eq!(editor.indent_style(), IndentStyle::Spaces(2));
+    /// ```
+    pub fn set_detect_indentation(&mut self, enabled: bool) {
+        self.detect_indentation = enabled;
+    }
+
+    /// Returns whether the indentation style is picked from the content.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use iced_code_editor::CodeEditor;
+    ///
+    /// let editor = CodeEditor::new("", "txt");
+    /// assert!(editor.detect_indentation());
+    /// ```
+    pub fn detect_indentation(&self) -> bool {
+        self.detect_indentation
+    }
+
     /// Enables or disables the search/replace functionality.
     ///
     /// When disabled, search/replace keyboard shortcuts (Ctrl+F, Ctrl+H, F3)
@@ -1236,6 +1572,26 @@ impl CodeEditor {
         self
     }
 
+    /// Shows or hides the breakpoint margin using the builder pattern.
+    ///
+    /// # Arguments
+    ///
+    /// * `enabled` - Whether to show the breakpoint margin
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use iced_code_editor::CodeEditor;
+    ///
+    /// let editor = CodeEditor::new("fn main() {}", "rs")
+    ///     .with_breakpoints_enabled(true);
+    /// ```
+    #[must_use]
+    pub fn with_breakpoints_enabled(mut self, enabled: bool) -> Self {
+        self.breakpoints_enabled = enabled;
+        self
+    }
+
     /// Sets the wrap column (fixed width wrapping).
     ///
     /// When set to `Some(n)`, lines will wrap at column `n`.
@@ -0,0 +1,409 @@
+//! Minimal DAP (Debug Adapter Protocol) types used by the editor.
+//!
+//! These models are transport-agnostic: the `dap-process` feature adds a
+//! client that fills them from a debug adapter speaking DAP over stdio, but a
+//! host with its own transport can build them just as well.
+
+#[cfg(all(feature = "dap-process", not(target_arch = "wasm32")))]
+pub mod process;
+
+use std::collections::HashMap;
+
+use crate::canvas_editor::CodeEditor;
+
+/// A breakpoint as sent to the debug adapter in a `setBreakpoints` request.
+///
+/// Lines are one-based, as DAP clients announce by default.
+///
+/// # Example
+///
+/// ```
+/// use iced_code_editor::{Breakpoint, CodeEditor};
+///
+/// let mut editor = CodeEditor::new("let x = 1;\nlet y = x;\n", "rs");
+/// editor.set_breakpoint(1, Breakpoint::conditional("x > 0"));
+///
+/// let breakpoints = editor.dap_source_breakpoints();
+/// assert_eq!(breakpoints[0].line, 2);
+/// assert_eq!(breakpoints[0].condition.as_deref(), Some("x > 0"));
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DapSourceBreakpoint {
+    /// One-based line number.
+    pub line: u32,
+    /// Expression that must hold for execution to stop.
+    pub condition: Option<String>,
+    /// Message to log instead of stopping.
+    pub log_message: Option<String>,
+}
+
+/// The adapter's verdict on one breakpoint of a `setBreakpoints` request.
+///
+/// The adapter answers in request order, which is line order for the
+/// breakpoints from [`CodeEditor::dap_source_breakpoints`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DapBreakpointStatus {
+    /// Whether the breakpoint could be set.
+    pub verified: bool,
+    /// One-based line the adapter actually placed the breakpoint on.
+    pub line: Option<u32>,
+    /// Why the breakpoint is unverified, if the adapter says.
+    pub message: Option<String>,
+}
+
+/// A thread of the debuggee.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DapThread {
+    /// Adapter-assigned thread id.
+    pub id: i64,
+    /// Thread name.
+    pub name: String,
+}
+
+/// A frame of a stopped thread's call stack.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DapStackFrame {
+    /// Adapter-assigned frame id, used to request its scopes.
+    pub id: i64,
+    /// Function or method name.
+    pub name: String,
+    /// Path of the frame's source file, when it has one.
+    pub source_path: Option<String>,
+    /// One-based line number.
+    pub line: u32,
+    /// One-based column number.
+    pub column: u32,
+}
+
+/// A scope of a stack frame, such as locals, arguments or globals.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DapScope {
+    /// Scope name, e.g. `Locals`.
+    pub name: String,
+    /// Reference used to request the scope's variables.
+    pub variables_reference: i64,
+    /// Whether fetching the variables is expensive, so a UI should wait for
+    /// the user to expand the scope.
+    pub expensive: bool,
+}
+
+/// A variable of a scope, or a child of a structured variable.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DapVariable {
+    /// Variable name.
+    pub name: String,
+    /// Value formatted by the adapter.
+    pub value: String,
+    /// Type name, when the adapter reports one.
+    pub type_name: Option<String>,
+    /// Reference used to request the children of a structured value, `0`
+    /// for a leaf.
+    pub variables_reference: i64,
+}
+
+/// What the host knows about a debug session: threads, where execution is
+/// stopped, and the stack, scopes and variables fetched there.
+///
+/// Stack, scopes and variables are only valid while stopped, so they are
+/// dropped when execution continues. With the `dap-process` feature,
+/// `DapSession::apply` folds a client's events in.
+///
+/// # Example
+///
+/// ```
+/// use iced_code_editor::{CodeEditor, DapSession, DapStackFrame};
+///
+/// let mut session = DapSession::default();
+/// session.stopped(Some(1), "breakpoint");
+/// session.set_stack_trace(
+///     1,
+///     vec![DapStackFrame {
+///         id: 1000,
+///         name: "main".to_string(),
+///         source_path: Some("/src/main.rs".to_string()),
+///         line: 2,
+///         column: 1,
+///     }],
+/// );
+///
+/// let mut editor = CodeEditor::new("fn main() {\n    run();\n}", "rs");
+/// editor.set_execution_line(session.execution_line("/src/main.rs"));
+/// assert_eq!(editor.execution_line(), Some(1));
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct DapSession {
+    /// Threads from the last `threads` response.
+    threads: Vec<DapThread>,
+    /// Thread that stopped, while stopped.
+    stopped_thread: Option<i64>,
+    /// Why execution stopped, while stopped.
+    stop_reason: Option<String>,
+    /// Call stack of the stopped thread, innermost frame first.
+    frames: Vec<DapStackFrame>,
+    /// Scopes by frame id.
+    scopes: HashMap<i64, Vec<DapScope>>,
+    /// Variables by variables reference.
+    variables: HashMap<i64, Vec<DapVariable>>,
+}
+
+impl DapSession {
+    /// Records that execution stopped, dropping the previous stop's stack,
+    /// scopes and variables.
+    ///
+    /// # Arguments
+    ///
+    /// * `thread_id` - The thread that stopped, if the adapter says
+    /// * `reason` - Why it stopped, e.g. `breakpoint` or `step`
+    pub fn stopped(&mut self, thread_id: Option<i64>, reason: &str) {
+        self.clear_stop();
+        self.stopped_thread = thread_id;
+        self.stop_reason = Some(reason.to_string());
+    }
+
+    /// Records that execution continued, dropping the stack, scopes and
+    /// variables.
+    pub fn continued(&mut self) {
+        self.clear_stop();
+    }
+
+    /// Forgets everything, for when the debuggee exits.
+    pub fn end(&mut self) {
+        *self = Self::default();
+    }
+
+    /// Replaces the thread list.
+    pub fn set_threads(&mut self, threads: Vec<DapThread>) {
+        self.threads = threads;
+    }
+
+    /// Stores the call stack of `thread_id`.
+    ///
+    /// Ignored unless that thread is the stopped one, so a late reply from a
+    /// previous stop cannot overwrite the current stack. Adapters that do not
+    /// name the stopped thread accept any.
+    pub fn set_stack_trace(
+        &mut self,
+        thread_id: i64,
+        frames: Vec<DapStackFrame>,
+    ) {
+        if self.stop_reason.is_some()
+            && self.stopped_thread.is_none_or(|id| id == thread_id)
+        {
+            self.stopped_thread = Some(thread_id);
+            self.frames = frames;
+        }
+    }
+
+    /// Stores the scopes of the frame `frame_id`.
+    pub fn set_scopes(&mut self, frame_id: i64, scopes: Vec<DapScope>) {
+        if self.frames.iter().any(|frame| frame.id == frame_id) {
+            self.scopes.insert(frame_id, scopes);
+        }
+    }
+
+    /// Stores the variables fetched for `variables_reference`.
+    pub fn set_variables(
+        &mut self,
+        variables_reference: i64,
+        variables: Vec<DapVariable>,
+    ) {
+        if self.stop_reason.is_some() {
+            self.variables.insert(variables_reference, variables);
+        }
+    }
+
+    /// Returns whether execution is stopped.
+    pub fn is_stopped(&self) -> bool {
+        self.stop_reason.is_some()
+    }
+
+    /// Returns the thread that stopped.
+    pub fn stopped_thread(&self) -> Option<i64> {
+        self.stopped_thread
+    }
+
+    /// Returns why execution stopped.
+    pub fn stop_reason(&self) -> Option<&str> {
+        self.stop_reason.as_deref()
+    }
+
+    /// Returns the known threads.
+    pub fn threads(&self) -> &[DapThread] {
+        &self.threads
+    }
+
+    /// Returns the stopped thread's call stack, innermost frame first.
+    pub fn frames(&self) -> &[DapStackFrame] {
+        &self.frames
+    }
+
+    /// Returns the scopes of the frame `frame_id`, once fetched.
+    pub fn scopes(&self, frame_id: i64) -> Option<&[DapScope]> {
+        self.scopes.get(&frame_id).map(Vec::as_slice)
+    }
+
+    /// Returns the variables of `variables_reference`, once fetched.
+    pub fn variables(
+        &self,
+        variables_reference: i64,
+    ) -> Option<&[DapVariable]> {
+        self.variables.get(&variables_reference).map(Vec::as_slice)
+    }
+
+    /// Returns the zero-based line where execution is stopped in the file at
+    /// `source_path`, for [`CodeEditor::set_execution_line`].
+    ///
+    /// That is the line of the innermost frame, when the frame is in that
+    /// file.
+    pub fn execution_line(&self, source_path: &str) -> Option<usize> {
+        let frame = self.frames.first()?;
+        (frame.source_path.as_deref() == Some(source_path))
+            .then(|| (frame.line as usize).saturating_sub(1))
+    }
+
+    /// Drops the state that is only valid while stopped.
+    fn clear_stop(&mut self) {
+        self.stopped_thread = None;
+        self.stop_reason = None;
+        self.frames.clear();
+        self.scopes.clear();
+        self.variables.clear();
+    }
+}
+
+impl CodeEditor {
+    /// Returns the editor's breakpoints as the body of a `setBreakpoints`
+    /// request, in line order.
+    pub fn dap_source_breakpoints(&self) -> Vec<DapSourceBreakpoint> {
+        self.breakpoints()
+            .map(|(line, breakpoint)| DapSourceBreakpoint {
+                line: u32::try_from(line + 1).unwrap_or(u32::MAX),
+                condition: breakpoint.condition.clone(),
+                log_message: breakpoint.log_message.clone(),
+            })
+            .collect()
+    }
+
+    /// Marks breakpoints verified or not from a `setBreakpoints` response.
+    ///
+    /// `statuses` pairs with [`Self::dap_source_breakpoints`] by position, so
+    /// apply it only if [`Self::breakpoints_revision`] has not changed since
+    /// the request was sent; otherwise send the breakpoints again.
+    ///
+    /// # Arguments
+    ///
+    /// * `statuses` - The adapter's answer, in request order
+    pub fn set_dap_breakpoint_statuses(
+        &mut self,
+        statuses: &[DapBreakpointStatus],
+    ) {
+        let lines: Vec<usize> =
+            self.breakpoints().map(|(line, _)| line).collect();
+        for (line, status) in lines.into_iter().zip(statuses) {
+            self.set_breakpoint_verified(line, status.verified);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::canvas_editor::features::breakpoints::Breakpoint;
+
+    fn frame(id: i64, path: &str, line: u32) -> DapStackFrame {
+        DapStackFrame {
+            id,
+            name: format!("frame{id}"),
+            source_path: Some(path.to_string()),
+            line,
+            column: 1,
+        }
+    }
+
+    fn scope(reference: i64) -> DapScope {
+        DapScope {
+            name: "Locals".to_string(),
+            variables_reference: reference,
+            expensive: false,
+        }
+    }
+
+    #[test]
+    fn test_source_breakpoints_are_one_based_and_carry_conditions() {
+        let mut editor = CodeEditor::new("a\nb\nc", "txt");
+        editor.set_breakpoint(2, Breakpoint::logpoint("at c"));
+        editor.toggle_breakpoint(0);
+
+        assert_eq!(
+            editor.dap_source_breakpoints(),
+            [
+                DapSourceBreakpoint {
+                    line: 1,
+                    condition: None,
+                    log_message: None,
+                },
+                DapSourceBreakpoint {
+                    line: 3,
+                    condition: None,
+                    log_message: Some("at c".to_string()),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_breakpoint_statuses_pair_with_breakpoints_in_line_order() {
+        let mut editor = CodeEditor::new("a\nb\nc", "txt");
+        editor.toggle_breakpoint(0);
+        editor.toggle_breakpoint(2);
+
+        let status = |verified| DapBreakpointStatus {
+            verified,
+            line: None,
+            message: None,
+        };
+        editor.set_dap_breakpoint_statuses(&[status(true), status(false)]);
+
+        assert_eq!(editor.breakpoint(0).map(|b| b.verified), Some(true));
+        assert_eq!(editor.breakpoint(2).map(|b| b.verified), Some(false));
+    }
+
+    #[test]
+    fn test_session_drops_stop_state_when_continuing() {
+        let mut session = DapSession::default();
+        session.stopped(Some(1), "step");
+        session.set_stack_trace(1, vec![frame(10, "/a.rs", 3)]);
+        session.set_scopes(10, vec![scope(20)]);
+        session.set_variables(20, Vec::new());
+
+        assert_eq!(session.execution_line("/a.rs"), Some(2));
+        assert_eq!(session.execution_line("/b.rs"), None);
+        assert!(session.scopes(10).is_some());
+        assert!(session.variables(20).is_some());
+
+        session.continued();
+        assert!(!session.is_stopped());
+        assert!(session.frames().is_empty());
+        assert!(session.scopes(10).is_none());
+        assert!(session.variables(20).is_none());
+    }
+
+    #[test]
+    fn test_session_ignores_replies_for_other_stops() {
+        let mut session = DapSession::default();
+
+        // Not stopped: nothing to attach a stack to.
+        session.set_stack_trace(1, vec![frame(10, "/a.rs", 3)]);
+        assert!(session.frames().is_empty());
+
+        session.stopped(Some(2), "breakpoint");
+        session.set_stack_trace(1, vec![frame(10, "/a.rs", 3)]);
+        assert!(session.frames().is_empty());
+
+        // Scopes of a frame that is not on the stack are dropped too.
+        session.set_stack_trace(2, vec![frame(11, "/a.rs", 4)]);
+        session.set_scopes(10, vec![scope(20)]);
+        assert!(session.scopes(10).is_none());
+        assert_eq!(session.stopped_thread(), Some(2));
+    }
+}
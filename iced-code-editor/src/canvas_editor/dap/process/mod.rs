@@ -0,0 +1,851 @@
+//! DAP (Debug Adapter Protocol) Process Client implementation.
+//!
+//! This module provides a client for debug adapters such as `codelldb`,
+//! `lldb-dap` or `debugpy` speaking DAP over stdio. It sends breakpoints,
+//! drives execution (continue, step, pause) and fetches the stack, scopes and
+//! variables while stopped.
+//!
+//! Enable with the `dap-process` Cargo feature. Not available on WASM targets.
+//!
+//! Like the LSP client, requests are fire-and-forget: replies and adapter
+//! events arrive as [`DapEvent`]s on the channel given at construction.
+//! [`protocol`] holds message dispatch and body parsing; what remains here is
+//! the process lifecycle and the request methods.
+
+mod protocol;
+
+use std::collections::HashMap;
+use std::io::{BufReader, Read, Write};
+use std::process::{Child, Command, Stdio};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, mpsc};
+use std::thread;
+
+use serde_json::{Value, json};
+
+use self::protocol::{DapRequestKind, handle_adapter_message};
+use crate::canvas_editor::dap::{
+    DapBreakpointStatus, DapScope, DapSession, DapSourceBreakpoint,
+    DapStackFrame, DapThread, DapVariable,
+};
+use crate::canvas_editor::framing::{
+    frame_message, read_log_line, read_message,
+};
+
+// =============================================================================
+// DAP Events - Events sent back to the main application
+// =============================================================================
+
+/// Events that can be sent from the DAP client to the application.
+///
+/// Replies to requests and events pushed by the adapter both arrive here.
+/// Execution control shows up as [`DapEvent::Stopped`] and
+/// [`DapEvent::Continued`]: the latter is emitted both for the adapter's own
+/// `continued` event and for an accepted continue or step request.
+///
+/// # Examples
+///
+/// ```
+/// use std::sync::mpsc;
+///
+/// use iced_code_editor::{CodeEditor, DapEvent, DapSession};
+///
+/// let (tx, rx) = mpsc::channel::<DapEvent>();
+/// tx.send(DapEvent::Stopped {
+///     reason: "breakpoint".to_string(),
+///     thread_id: Some(1),
+///     description: None,
+///     all_threads_stopped: true,
+/// })
+/// .expect("the receiver is still alive");
+///
+/// let mut session = DapSession::default();
+/// while let Ok(event) = rx.try_recv() {
+///     session.apply(&event);
+/// }
+/// assert_eq!(session.stopped_thread(), Some(1));
+/// ```
+#[derive(Debug, Clone)]
+pub enum DapEvent {
+    /// The adapter is ready for configuration: send breakpoints, then
+    /// `configurationDone`.
+    Initialized,
+    /// Execution stopped; request the thread's stack trace to see where.
+    Stopped {
+        /// Why execution stopped, e.g. `breakpoint`, `step` or `exception`.
+        reason: String,
+        /// The thread that stopped, if the adapter says.
+        thread_id: Option<i64>,
+        /// Human-readable detail, e.g. the exception message.
+        description: Option<String>,
+        /// Whether every thread stopped.
+        all_threads_stopped: bool,
+    },
+    /// Execution continued after a continue or step request, or on the
+    /// adapter's own initiative.
+    Continued {
+        /// The thread that continued, if known.
+        thread_id: Option<i64>,
+        /// Whether every thread continued.
+        all_threads_continued: bool,
+    },
+    /// The debuggee exited.
+    Exited {
+        /// The debuggee's exit code.
+        exit_code: i64,
+    },
+    /// The debug session ended.
+    Terminated,
+    /// Output of the debuggee or the adapter.
+    Output {
+        /// `console`, `stdout`, `stderr`, … when the adapter says.
+        category: Option<String>,
+        /// The output text, usually ending with a newline.
+        output: String,
+    },
+    /// The adapter's answer to [`DapProcessClient::set_breakpoints`]; pass
+    /// it to
+    /// [`CodeEditor::set_dap_breakpoint_statuses`](crate::CodeEditor::set_dap_breakpoint_statuses)
+    /// for the editor showing `source_path`.
+    Breakpoints {
+        /// Path the breakpoints were set in.
+        source_path: String,
+        /// One status per requested breakpoint, in request order.
+        breakpoints: Vec<DapBreakpointStatus>,
+    },
+    /// The debuggee's threads.
+    Threads {
+        /// Threads, in adapter order.
+        threads: Vec<DapThread>,
+    },
+    /// The call stack of a stopped thread.
+    StackTrace {
+        /// The thread whose stack this is.
+        thread_id: i64,
+        /// Frames, innermost first.
+        frames: Vec<DapStackFrame>,
+    },
+    /// The scopes of a stack frame.
+    Scopes {
+        /// The frame these scopes belong to.
+        frame_id: i64,
+        /// Scopes, in adapter order.
+        scopes: Vec<DapScope>,
+    },
+    /// The variables of a scope or structured variable.
+    Variables {
+        /// The reference these variables were requested for.
+        variables_reference: i64,
+        /// Variables, in adapter order.
+        variables: Vec<DapVariable>,
+    },
+    /// A request failed.
+    Error {
+        /// The command of the failed request.
+        command: String,
+        /// The adapter's explanation.
+        message: String,
+    },
+    /// Log line from the adapter's stderr.
+    Log {
+        /// The log line.
+        message: String,
+    },
+}
+
+impl DapSession {
+    /// Folds a [`DapEvent`] into the session.
+    ///
+    /// Stops, continues, exits, threads, stack traces, scopes and variables
+    /// update the model; other events leave it unchanged.
+    ///
+    /// # Arguments
+    ///
+    /// * `event` - An event emitted by the session's client
+    pub fn apply(&mut self, event: &DapEvent) {
+        match event {
+            DapEvent::Stopped { reason, thread_id, .. } => {
+                self.stopped(*thread_id, reason);
+            }
+            DapEvent::Continued { .. } => self.continued(),
+            DapEvent::Exited { .. } | DapEvent::Terminated => self.end(),
+            DapEvent::Threads { threads } => self.set_threads(threads.clone()),
+            DapEvent::StackTrace { thread_id, frames } => {
+                self.set_stack_trace(*thread_id, frames.clone());
+            }
+            DapEvent::Scopes { frame_id, scopes } => {
+                self.set_scopes(*frame_id, scopes.clone());
+            }
+            DapEvent::Variables { variables_reference, variables } => {
+                self.set_variables(*variables_reference, variables.clone());
+            }
+            DapEvent::Initialized
+            | DapEvent::Output { .. }
+            | DapEvent::Breakpoints { .. }
+            | DapEvent::Error { .. }
+            | DapEvent::Log { .. } => {}
+        }
+    }
+}
+
+// =============================================================================
+// DAP Process Client - Main client implementation
+// =============================================================================
+
+/// Client for communicating with a debug adapter.
+///
+/// Typical flow: [`Self::initialize`], [`Self::launch`] (or
+/// [`Self::attach`]); on [`DapEvent::Initialized`] send
+/// [`Self::set_breakpoints`] for each file and [`Self::configuration_done`];
+/// on [`DapEvent::Stopped`] request [`Self::stack_trace`], then
+/// [`Self::scopes`] and [`Self::variables`] as the user drills in.
+///
+/// # Examples
+///
+/// ```no_run
+/// use std::sync::mpsc;
+/// use iced_code_editor::{DapEvent, DapProcessClient};
+///
+/// let (tx, rx) = mpsc::channel::<DapEvent>();
+/// let client = DapProcessClient::spawn(
+///     "python3",
+///     &["-m".to_string(), "debugpy.adapter".to_string()],
+///     tx,
+/// );
+/// ```
+pub struct DapProcessClient {
+    /// The adapter process, when the client spawned it
+    child: Option<Child>,
+    /// Channel for sending messages to the writer thread
+    writer: mpsc::Sender<Vec<u8>>,
+    /// Sequence number of the next outgoing message, shared with the reader
+    /// thread, which answers reverse requests
+    seq: Arc<AtomicU64>,
+    /// Map of pending request sequence numbers to what they asked for
+    pending_requests: Arc<Mutex<HashMap<u64, DapRequestKind>>>,
+    /// Handle to the writer thread (kept alive for the client's lifetime)
+    _writer_thread: thread::JoinHandle<()>,
+    /// Handle to the reader thread (kept alive for the client's lifetime)
+    _reader_thread: thread::JoinHandle<()>,
+    /// Handle to the stderr thread, when the client spawned the adapter
+    _stderr_thread: Option<thread::JoinHandle<()>>,
+}
+
+impl DapProcessClient {
+    /// Spawns a debug adapter and connects to its stdio.
+    ///
+    /// # Arguments
+    ///
+    /// * `program` — the adapter executable (e.g. `"codelldb"`)
+    /// * `args` — its arguments
+    /// * `events` — channel to send [`DapEvent`]s back to the application
+    ///
+    /// # Errors
+    ///
+    /// Returns an error string when the program cannot be found or spawned,
+    /// or when its stdio handles cannot be acquired.
+    pub fn spawn(
+        program: &str,
+        args: &[String],
+        events: mpsc::Sender<DapEvent>,
+    ) -> Result<Self, String> {
+        let mut child = Command::new(program)
+            .args(args)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .map_err(|e| {
+                if e.kind() == std::io::ErrorKind::NotFound {
+                    format!("Debug adapter program {program} not found")
+                } else {
+                    e.to_string()
+                }
+            })?;
+
+        let stdin = child.stdin.take().ok_or("stdin unavailable")?;
+        let stdout = child.stdout.take().ok_or("stdout unavailable")?;
+        let stderr = child.stderr.take().ok_or("stderr unavailable")?;
+
+        let events_log = events.clone();
+        let stderr_thread = thread::spawn(move || {
+            let mut reader = BufReader::new(stderr);
+            while let Some(line) = read_log_line(&mut reader) {
+                let line = line.trim();
+                if !line.is_empty() {
+                    let _ = events_log
+                        .send(DapEvent::Log { message: line.to_string() });
+                }
+            }
+        });
+
+        let mut client = Self::from_streams(stdout, stdin, events);
+        client.child = Some(child);
+        client._stderr_thread = Some(stderr_thread);
+        Ok(client)
+    }
+
+    /// Connects to an adapter over an existing pair of streams.
+    ///
+    /// Use it for adapters reached another way than a child's stdio, such as
+    /// a TCP socket (pass a clone of the stream as each half).
+    ///
+    /// # Arguments
+    ///
+    /// * `reader` — stream the adapter writes to
+    /// * `writer` — stream the adapter reads from
+    /// * `events` — channel to send [`DapEvent`]s back to the application
+    pub fn from_streams(
+        reader: impl Read + Send + 'static,
+        writer: impl Write + Send + 'static,
+        events: mpsc::Sender<DapEvent>,
+    ) -> Self {
+        let (tx, rx) = mpsc::channel::<Vec<u8>>();
+        let seq = Arc::new(AtomicU64::new(1));
+        let pending_requests = Arc::new(Mutex::new(HashMap::new()));
+        let pending_reader = pending_requests.clone();
+        let seq_reader = seq.clone();
+        let tx_reader = tx.clone();
+
+        let writer_thread = thread::spawn(move || {
+            let mut output = writer;
+            for bytes in rx {
+                if output.write_all(&bytes).is_err() {
+                    break;
+                }
+                let _ = output.flush();
+            }
+        });
+
+        let reader_thread = thread::spawn(move || {
+            let mut reader = BufReader::new(reader);
+            while let Some(buf) = read_message(&mut reader) {
+                if let Ok(value) = serde_json::from_slice::<Value>(&buf) {
+                    handle_adapter_message(
+                        &value,
+                        &pending_reader,
+                        &events,
+                        &tx_reader,
+                        &seq_reader,
+                    );
+                }
+            }
+        });
+
+        Self {
+            child: None,
+            writer: tx,
+            seq,
+            pending_requests,
+            _writer_thread: writer_thread,
+            _reader_thread: reader_thread,
+            _stderr_thread: None,
+        }
+    }
+
+    /// Sends `initialize`, announcing one-based lines and columns and plain
+    /// file paths.
+    ///
+    /// # Arguments
+    ///
+    /// * `adapter_id` — the adapter's id, e.g. `"lldb"` or `"python"`
+    pub fn initialize(&self, adapter_id: &str) {
+        self.send_request(
+            "initialize",
+            &json!({
+                "clientID": "iced-code-editor",
+                "clientName": "iced-code-editor",
+                "adapterID": adapter_id,
+                "linesStartAt1": true,
+                "columnsStartAt1": true,
+                "pathFormat": "path",
+                "supportsVariableType": true,
+                "supportsRunInTerminalRequest": false
+            }),
+            DapRequestKind::Other,
+        );
+    }
+
+    /// Sends `launch` with adapter-specific `arguments` (program, args, cwd,
+    /// …).
+    pub fn launch(&self, arguments: &Value) {
+        self.send_request("launch", arguments, DapRequestKind::Other);
+    }
+
+    /// Sends `attach` with adapter-specific `arguments` (pid, host, …).
+    pub fn attach(&self, arguments: &Value) {
+        self.send_request("attach", arguments, DapRequestKind::Other);
+    }
+
+    /// Replaces the breakpoints of the file at `source_path`.
+    ///
+    /// Answered by [`DapEvent::Breakpoints`].
+    ///
+    /// # Arguments
+    ///
+    /// * `source_path` — path of the file
+    /// * `breakpoints` — all of its breakpoints, e.g. from
+    ///   [`CodeEditor::dap_source_breakpoints`](crate::CodeEditor::dap_source_breakpoints)
+    pub fn set_breakpoints(
+        &self,
+        source_path: &str,
+        breakpoints: &[DapSourceBreakpoint],
+    ) {
+        let breakpoints: Vec<Value> = breakpoints
+            .iter()
+            .map(|breakpoint| {
+                let mut value = json!({ "line": breakpoint.line });
+                if let Some(condition) = &breakpoint.condition {
+                    value["condition"] = json!(condition);
+                }
+                if let Some(log_message) = &breakpoint.log_message {
+                    value["logMessage"] = json!(log_message);
+                }
+                value
+            })
+            .collect();
+        self.send_request(
+            "setBreakpoints",
+            &json!({
+                "source": { "path": source_path },
+                "breakpoints": breakpoints
+            }),
+            DapRequestKind::SetBreakpoints {
+                source_path: source_path.to_string(),
+            },
+        );
+    }
+
+    /// Sends `configurationDone`, after which the debuggee starts running.
+    pub fn configuration_done(&self) {
+        self.send_request(
+            "configurationDone",
+            &json!({}),
+            DapRequestKind::Other,
+        );
+    }
+
+    /// Requests the debuggee's threads, answered by [`DapEvent::Threads`].
+    pub fn threads(&self) {
+        self.send_request("threads", &json!({}), DapRequestKind::Threads);
+    }
+
+    /// Resumes `thread_id` (and, with most adapters, every other thread).
+    pub fn continue_thread(&self, thread_id: i64) {
+        self.resume("continue", thread_id, true);
+    }
+
+    /// Steps over the current line of `thread_id`.
+    pub fn next(&self, thread_id: i64) {
+        self.resume("next", thread_id, false);
+    }
+
+    /// Steps into the call on the current line of `thread_id`.
+    pub fn step_in(&self, thread_id: i64) {
+        self.resume("stepIn", thread_id, false);
+    }
+
+    /// Runs `thread_id` until the current function returns.
+    pub fn step_out(&self, thread_id: i64) {
+        self.resume("stepOut", thread_id, false);
+    }
+
+    /// Suspends `thread_id`; the adapter then sends [`DapEvent::Stopped`].
+    pub fn pause(&self, thread_id: i64) {
+        self.send_request(
+            "pause",
+            &json!({ "threadId": thread_id }),
+            DapRequestKind::Other,
+        );
+    }
+
+    /// Requests the call stack of the stopped `thread_id`, answered by
+    /// [`DapEvent::StackTrace`].
+    pub fn stack_trace(&self, thread_id: i64) {
+        self.send_request(
+            "stackTrace",
+            &json!({ "threadId": thread_id }),
+            DapRequestKind::StackTrace { thread_id },
+        );
+    }
+
+    /// Requests the scopes of `frame_id`, answered by [`DapEvent::Scopes`].
+    pub fn scopes(&self, frame_id: i64) {
+        self.send_request(
+            "scopes",
+            &json!({ "frameId": frame_id }),
+            DapRequestKind::Scopes { frame_id },
+        );
+    }
+
+    /// Requests the variables of a scope or structured variable, answered by
+    /// [`DapEvent::Variables`].
+    pub fn variables(&self, variables_reference: i64) {
+        self.send_request(
+            "variables",
+            &json!({ "variablesReference": variables_reference }),
+            DapRequestKind::Variables { variables_reference },
+        );
+    }
+
+    /// Ends the session, terminating a launched debuggee.
+    pub fn disconnect(&self) {
+        self.send_request(
+            "disconnect",
+            &json!({ "terminateDebuggee": true }),
+            DapRequestKind::Other,
+        );
+    }
+
+    /// Sends one of the requests that resume a thread, reported as
+    /// [`DapEvent::Continued`] once accepted.
+    fn resume(&self, command: &str, thread_id: i64, all_threads: bool) {
+        self.send_request(
+            command,
+            &json!({ "threadId": thread_id }),
+            DapRequestKind::Resume {
+                thread_id,
+                all_threads_by_default: all_threads,
+            },
+        );
+    }
+
+    /// Registers `kind` as pending and sends the request.
+    fn send_request(
+        &self,
+        command: &str,
+        arguments: &Value,
+        kind: DapRequestKind,
+    ) {
+        let seq = self.seq.fetch_add(1, Ordering::Relaxed);
+        self.pending_requests
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .insert(seq, kind);
+        let request = json!({
+            "seq": seq,
+            "type": "request",
+            "command": command,
+            "arguments": arguments
+        });
+        if let Some(bytes) = frame_message(&request) {
+            let _ = self.writer.send(bytes);
+        }
+    }
+}
+
+/// Kills a spawned adapter that is still running on drop.
+///
+/// Call [`DapProcessClient::disconnect`] first to let the adapter end the
+/// session cleanly.
+impl Drop for DapProcessClient {
+    fn drop(&mut self) {
+        if let Some(child) = &mut self.child
+            && child.try_wait().ok().flatten().is_none()
+        {
+            let _ = child.kill();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io;
+    use std::time::Duration;
+
+    use super::*;
+    use crate::canvas_editor::CodeEditor;
+    use crate::canvas_editor::features::breakpoints::Breakpoint;
+
+    /// Starts a scripted fake adapter on in-process pipes and connects a
+    /// client to it.
+    ///
+    /// `script` is called with every message the client sends, and returns
+    /// the messages the adapter writes back.
+    #[allow(clippy::expect_used)]
+    fn fake_adapter(
+        script: impl Fn(&Value) -> Vec<Value> + Send + 'static,
+    ) -> (DapProcessClient, mpsc::Receiver<DapEvent>) {
+        let (client_reader, mut adapter_writer) =
+            io::pipe().expect("create pipe");
+        let (adapter_reader, client_writer) = io::pipe().expect("create pipe");
+
+        thread::spawn(move || {
+            let mut reader = BufReader::new(adapter_reader);
+            let mut seq = 1000;
+            while let Some(buf) = read_message(&mut reader) {
+                let Ok(message) = serde_json::from_slice::<Value>(&buf) else {
+                    continue;
+                };
+                for mut reply in script(&message) {
+                    reply["seq"] = json!(seq);
+                    seq += 1;
+                    let Some(bytes) = frame_message(&reply) else { continue };
+                    if adapter_writer.write_all(&bytes).is_err() {
+                        return;
+                    }
+                }
+            }
+        });
+
+        let (events_tx, events_rx) = mpsc::channel();
+        let client = DapProcessClient::from_streams(
+            client_reader,
+            client_writer,
+            events_tx,
+        );
+        (client, events_rx)
+    }
+
+    /// Returns a successful response to `request` carrying `body`.
+    fn response(request: &Value, body: Value) -> Value {
+        let mut response = json!({
+            "type": "response",
+            "request_seq": request["seq"],
+            "success": true,
+            "command": request["command"]
+        });
+        response["body"] = body;
+        response
+    }
+
+    /// Returns an adapter event.
+    fn event(name: &str, body: Value) -> Value {
+        let mut event = json!({ "type": "event", "event": name });
+        event["body"] = body;
+        event
+    }
+
+    /// Waits for the next event from the client.
+    #[allow(clippy::expect_used)]
+    fn next_event(events: &mpsc::Receiver<DapEvent>) -> DapEvent {
+        events
+            .recv_timeout(Duration::from_secs(5))
+            .expect("the fake adapter answers")
+    }
+
+    /// A debuggee with one breakpoint at line 2 of `/src/main.rs`, where it
+    /// stops right after configuration.
+    fn scripted_session(request: &Value) -> Vec<Value> {
+        let command = request["command"].as_str().unwrap_or("");
+        match command {
+            "initialize" => vec![
+                response(
+                    request,
+                    json!({ "supportsConditionalBreakpoints": true }),
+                ),
+                event("initialized", json!({})),
+            ],
+            "setBreakpoints" => {
+                let breakpoints: Vec<Value> =
+                    request["arguments"]["breakpoints"]
+                        .as_array()
+                        .map(|breakpoints| {
+                            breakpoints
+                            .iter()
+                            .map(|b| {
+                                json!({
+                                    // The fake rejects conditional ones.
+                                    "verified": b.get("condition").is_none(),
+                                    "line": b["line"]
+                                })
+                            })
+                            .collect()
+                        })
+                        .unwrap_or_default();
+                vec![response(request, json!({ "breakpoints": breakpoints }))]
+            }
+            "configurationDone" => vec![
+                response(request, json!({})),
+                event(
+                    "stopped",
+                    json!({ "reason": "breakpoint", "threadId": 1 }),
+                ),
+            ],
+            "stackTrace" => vec![response(
+                request,
+                json!({
+                    "stackFrames": [
+                        {
+                            "id": 100,
+                            "name": "main",
+                            "source": { "path": "/src/main.rs" },
+                            "line": 2,
+                            "column": 5
+                        }
+                    ]
+                }),
+            )],
+            "scopes" => vec![response(
+                request,
+                json!({
+                    "scopes": [
+                        { "name": "Locals", "variablesReference": 200,
+                          "expensive": false }
+                    ]
+                }),
+            )],
+            "variables" => vec![response(
+                request,
+                json!({
+                    "variables": [
+                        { "name": "count", "value": "3", "type": "i32",
+                          "variablesReference": 0 }
+                    ]
+                }),
+            )],
+            "next" => vec![
+                response(request, json!({})),
+                event("stopped", json!({ "reason": "step", "threadId": 1 })),
+            ],
+            "continue" => vec![
+                response(request, json!({ "allThreadsContinued": true })),
+                event("exited", json!({ "exitCode": 0 })),
+                event("terminated", json!({})),
+            ],
+            _ => vec![json!({
+                "type": "response",
+                "request_seq": request["seq"],
+                "success": false,
+                "command": command,
+                "message": "unsupported"
+            })],
+        }
+    }
+
+    #[test]
+    #[allow(clippy::panic)]
+    fn test_breakpoints_round_trip_through_the_adapter() {
+        let (client, events) = fake_adapter(scripted_session);
+        let mut editor = CodeEditor::new("fn main() {\n    run();\n}\n", "rs");
+        editor.toggle_breakpoint(1);
+        editor.set_breakpoint(2, Breakpoint::conditional("false"));
+
+        client.initialize("lldb");
+        assert!(matches!(next_event(&events), DapEvent::Initialized));
+
+        client
+            .set_breakpoints("/src/main.rs", &editor.dap_source_breakpoints());
+        let DapEvent::Breakpoints { source_path, breakpoints } =
+            next_event(&events)
+        else {
+            panic!("expected the setBreakpoints answer");
+        };
+        assert_eq!(source_path, "/src/main.rs");
+        assert_eq!(breakpoints.len(), 2);
+        assert_eq!(breakpoints[0].line, Some(2));
+
+        editor.set_dap_breakpoint_statuses(&breakpoints);
+        assert_eq!(editor.breakpoint(1).map(|b| b.verified), Some(true));
+        assert_eq!(editor.breakpoint(2).map(|b| b.verified), Some(false));
+    }
+
+    #[test]
+    fn test_stop_stack_scopes_variables_step_and_exit() {
+        let (client, events) = fake_adapter(scripted_session);
+        let mut session = DapSession::default();
+        let mut editor = CodeEditor::new("fn main() {\n    run();\n}\n", "rs");
+
+        client.configuration_done();
+        let stopped = next_event(&events);
+        assert!(matches!(
+            &stopped,
+            DapEvent::Stopped { reason, thread_id: Some(1), .. }
+                if reason == "breakpoint"
+        ));
+        session.apply(&stopped);
+
+        client.stack_trace(1);
+        session.apply(&next_event(&events));
+        editor.set_execution_line(session.execution_line("/src/main.rs"));
+        assert_eq!(editor.execution_line(), Some(1));
+
+        client.scopes(session.frames()[0].id);
+        session.apply(&next_event(&events));
+        let locals = session.scopes(100).map(|scopes| scopes[0].clone());
+        assert_eq!(locals.as_ref().map(|s| s.variables_reference), Some(200));
+
+        client.variables(200);
+        session.apply(&next_event(&events));
+        assert_eq!(
+            session.variables(200).map(|v| v[0].value.as_str()),
+            Some("3")
+        );
+
+        // A step resumes the thread, then stops it again.
+        client.next(1);
+        let continued = next_event(&events);
+        assert!(matches!(
+            continued,
+            DapEvent::Continued {
+                thread_id: Some(1),
+                all_threads_continued: false
+            }
+        ));
+        session.apply(&continued);
+        assert!(session.frames().is_empty());
+        assert!(matches!(
+            next_event(&events),
+            DapEvent::Stopped { reason, .. } if reason == "step"
+        ));
+
+        client.continue_thread(1);
+        assert!(matches!(
+            next_event(&events),
+            DapEvent::Continued { all_threads_continued: true, .. }
+        ));
+        assert!(matches!(
+            next_event(&events),
+            DapEvent::Exited { exit_code: 0 }
+        ));
+        session.apply(&next_event(&events));
+        assert!(!session.is_stopped());
+    }
+
+    #[test]
+    fn test_failed_requests_are_reported() {
+        let (client, events) = fake_adapter(scripted_session);
+
+        client.pause(1);
+
+        assert!(matches!(
+            next_event(&events),
+            DapEvent::Error { command, message }
+                if command == "pause" && message == "unsupported"
+        ));
+    }
+
+    #[test]
+    fn test_reverse_requests_are_declined() {
+        // The adapter asks the client to run the debuggee in a terminal, and
+        // reports the client's answer as output.
+        let (client, events) =
+            fake_adapter(|message| match message["type"].as_str() {
+                Some("request") => vec![json!({
+                    "type": "request",
+                    "command": "runInTerminal",
+                    "arguments": { "args": ["./app"] }
+                })],
+                Some("response") => vec![event(
+                    "output",
+                    json!({
+                        "category": "console",
+                        "output": format!(
+                            "{} {}",
+                            message["command"], message["success"]
+                        )
+                    }),
+                )],
+                _ => Vec::new(),
+            });
+
+        client.launch(&json!({ "program": "./app" }));
+
+        assert!(matches!(
+            next_event(&events),
+            DapEvent::Output { category: Some(category), output }
+                if category == "console"
+                    && output == "\"runInTerminal\" false"
+        ));
+    }
+}
@@ -0,0 +1,378 @@
+//! Wire format for the DAP stdio transport.
+//!
+//! Everything between a framed message body and the host-facing
+//! [`DapEvent`](super::DapEvent): dispatch of the adapter's responses, events
+//! and reverse requests, and parsing of the bodies the client asks for. The
+//! `Content-Length` framing itself is shared with the LSP client.
+//!
+//! Like their LSP counterparts these are free functions with no client state:
+//! the reader thread owns the stream and calls into here.
+
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, mpsc};
+
+use serde_json::{Value, json};
+
+use super::DapEvent;
+use crate::canvas_editor::dap::{
+    DapBreakpointStatus, DapScope, DapStackFrame, DapThread, DapVariable,
+};
+use crate::canvas_editor::framing::frame_message;
+
+/// What a pending request asked for, used to route its response.
+pub(super) enum DapRequestKind {
+    /// `setBreakpoints` for the file at `source_path`.
+    SetBreakpoints {
+        /// Path of the file the breakpoints belong to.
+        source_path: String,
+    },
+    /// `threads`.
+    Threads,
+    /// `stackTrace` of a thread.
+    StackTrace {
+        /// The thread whose stack was requested.
+        thread_id: i64,
+    },
+    /// `scopes` of a frame.
+    Scopes {
+        /// The frame whose scopes were requested.
+        frame_id: i64,
+    },
+    /// `variables` of a scope or structured variable.
+    Variables {
+        /// The reference whose variables were requested.
+        variables_reference: i64,
+    },
+    /// `continue`, `next`, `stepIn` or `stepOut`, which resume the thread.
+    Resume {
+        /// The thread that was resumed.
+        thread_id: i64,
+        /// Whether the adapter resumes every thread unless it says otherwise.
+        all_threads_by_default: bool,
+    },
+    /// Any other request; only a failure is reported.
+    Other,
+}
+
+/// Dispatches one message from the adapter.
+///
+/// Responses are routed through `pending`, events are translated to
+/// [`DapEvent`]s, and reverse requests are declined (see
+/// [`handle_reverse_request`]). Anything else is ignored.
+pub(super) fn handle_adapter_message(
+    value: &Value,
+    pending: &Arc<Mutex<HashMap<u64, DapRequestKind>>>,
+    events: &mpsc::Sender<DapEvent>,
+    writer: &mpsc::Sender<Vec<u8>>,
+    seq: &AtomicU64,
+) {
+    match value.get("type").and_then(Value::as_str) {
+        Some("response") => handle_response(value, pending, events),
+        Some("event") => handle_event(value, events),
+        Some("request") => handle_reverse_request(value, writer, seq),
+        _ => {}
+    }
+}
+
+/// Routes a response to the event matching the request it answers.
+///
+/// A failed request yields [`DapEvent::Error`], whatever it asked for.
+fn handle_response(
+    value: &Value,
+    pending: &Arc<Mutex<HashMap<u64, DapRequestKind>>>,
+    events: &mpsc::Sender<DapEvent>,
+) {
+    let Some(request_seq) = value.get("request_seq").and_then(Value::as_u64)
+    else {
+        return;
+    };
+    let kind = pending
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .remove(&request_seq)
+        .unwrap_or(DapRequestKind::Other);
+
+    if !value.get("success").and_then(Value::as_bool).unwrap_or(false) {
+        let command = string_field(value, "command").unwrap_or_default();
+        let message = value
+            .pointer("/body/error/format")
+            .and_then(Value::as_str)
+            .or_else(|| value.get("message").and_then(Value::as_str))
+            .unwrap_or("request failed")
+            .to_string();
+        let _ = events.send(DapEvent::Error { command, message });
+        return;
+    }
+
+    let body = value.get("body").unwrap_or(&Value::Null);
+    let event = match kind {
+        DapRequestKind::SetBreakpoints { source_path } => {
+            DapEvent::Breakpoints {
+                source_path,
+                breakpoints: parse_array(body, "breakpoints", parse_breakpoint),
+            }
+        }
+        DapRequestKind::Threads => DapEvent::Threads {
+            threads: parse_array(body, "threads", parse_thread),
+        },
+        DapRequestKind::StackTrace { thread_id } => DapEvent::StackTrace {
+            thread_id,
+            frames: parse_array(body, "stackFrames", parse_stack_frame),
+        },
+        DapRequestKind::Scopes { frame_id } => DapEvent::Scopes {
+            frame_id,
+            scopes: parse_array(body, "scopes", parse_scope),
+        },
+        DapRequestKind::Variables { variables_reference } => {
+            DapEvent::Variables {
+                variables_reference,
+                variables: parse_array(body, "variables", parse_variable),
+            }
+        }
+        DapRequestKind::Resume { thread_id, all_threads_by_default } => {
+            DapEvent::Continued {
+                thread_id: Some(thread_id),
+                all_threads_continued: body
+                    .get("allThreadsContinued")
+                    .and_then(Value::as_bool)
+                    .unwrap_or(all_threads_by_default),
+            }
+        }
+        DapRequestKind::Other => return,
+    };
+    let _ = events.send(event);
+}
+
+/// Translates an adapter event to a [`DapEvent`].
+///
+/// Events the editor has no use for (`module`, `loadedSource`, `process`,
+/// `capabilities`, …) are ignored.
+fn handle_event(value: &Value, events: &mpsc::Sender<DapEvent>) {
+    let body = value.get("body").unwrap_or(&Value::Null);
+    let event = match value.get("event").and_then(Value::as_str) {
+        Some("initialized") => DapEvent::Initialized,
+        Some("stopped") => DapEvent::Stopped {
+            reason: string_field(body, "reason").unwrap_or_default(),
+            thread_id: body.get("threadId").and_then(Value::as_i64),
+            description: string_field(body, "description"),
+            all_threads_stopped: body
+                .get("allThreadsStopped")
+                .and_then(Value::as_bool)
+                .unwrap_or(false),
+        },
+        Some("continued") => DapEvent::Continued {
+            thread_id: body.get("threadId").and_then(Value::as_i64),
+            all_threads_continued: body
+                .get("allThreadsContinued")
+                .and_then(Value::as_bool)
+                .unwrap_or(true),
+        },
+        Some("exited") => DapEvent::Exited {
+            exit_code: body
+                .get("exitCode")
+                .and_then(Value::as_i64)
+                .unwrap_or(0),
+        },
+        Some("terminated") => DapEvent::Terminated,
+        Some("output") => match string_field(body, "output") {
+            Some(output) => DapEvent::Output {
+                category: string_field(body, "category"),
+                output,
+            },
+            None => return,
+        },
+        _ => return,
+    };
+    let _ = events.send(event);
+}
+
+/// Declines a reverse request from the adapter, such as `runInTerminal` or
+/// `startDebugging`.
+///
+/// The client announces no support for them in `initialize`, but an adapter
+/// that sends one anyway waits for an answer; an unsuccessful response lets it
+/// fall back instead of hanging.
+fn handle_reverse_request(
+    value: &Value,
+    writer: &mpsc::Sender<Vec<u8>>,
+    seq: &AtomicU64,
+) {
+    let Some(request_seq) = value.get("seq").and_then(Value::as_u64) else {
+        return;
+    };
+    let command = value.get("command").and_then(Value::as_str).unwrap_or("");
+    let response = json!({
+        "seq": seq.fetch_add(1, Ordering::Relaxed),
+        "type": "response",
+        "request_seq": request_seq,
+        "success": false,
+        "command": command,
+        "message": format!("{command} is not supported by this client"),
+    });
+    if let Some(bytes) = frame_message(&response) {
+        let _ = writer.send(bytes);
+    }
+}
+
+/// Returns the string at `key` in `value`, if any.
+fn string_field(value: &Value, key: &str) -> Option<String> {
+    value.get(key).and_then(Value::as_str).map(str::to_string)
+}
+
+/// Parses the array at `key` in `body` with `parse`, skipping malformed
+/// entries.
+fn parse_array<T>(
+    body: &Value,
+    key: &str,
+    parse: fn(&Value) -> Option<T>,
+) -> Vec<T> {
+    body.get(key)
+        .and_then(Value::as_array)
+        .map(|items| items.iter().filter_map(parse).collect())
+        .unwrap_or_default()
+}
+
+/// Parses a one-based line or column, which DAP sends as a number.
+fn position_field(value: &Value, key: &str) -> Option<u32> {
+    value.get(key).and_then(Value::as_u64).and_then(|n| u32::try_from(n).ok())
+}
+
+/// Parses a `Breakpoint` of a `setBreakpoints` response.
+fn parse_breakpoint(value: &Value) -> Option<DapBreakpointStatus> {
+    Some(DapBreakpointStatus {
+        verified: value.get("verified")?.as_bool()?,
+        line: position_field(value, "line"),
+        message: string_field(value, "message"),
+    })
+}
+
+/// Parses a `Thread`.
+fn parse_thread(value: &Value) -> Option<DapThread> {
+    Some(DapThread {
+        id: value.get("id")?.as_i64()?,
+        name: string_field(value, "name").unwrap_or_default(),
+    })
+}
+
+/// Parses a `StackFrame`; the source path is kept only for frames backed by
+/// a file.
+fn parse_stack_frame(value: &Value) -> Option<DapStackFrame> {
+    Some(DapStackFrame {
+        id: value.get("id")?.as_i64()?,
+        name: string_field(value, "name").unwrap_or_default(),
+        source_path: value
+            .pointer("/source/path")
+            .and_then(Value::as_str)
+            .map(str::to_string),
+        line: position_field(value, "line")?,
+        column: position_field(value, "column").unwrap_or(1),
+    })
+}
+
+/// Parses a `Scope`.
+fn parse_scope(value: &Value) -> Option<DapScope> {
+    Some(DapScope {
+        name: string_field(value, "name")?,
+        variables_reference: value.get("variablesReference")?.as_i64()?,
+        expensive: value
+            .get("expensive")
+            .and_then(Value::as_bool)
+            .unwrap_or(false),
+    })
+}
+
+/// Parses a `Variable`.
+fn parse_variable(value: &Value) -> Option<DapVariable> {
+    Some(DapVariable {
+        name: string_field(value, "name")?,
+        value: string_field(value, "value").unwrap_or_default(),
+        type_name: string_field(value, "type"),
+        variables_reference: value
+            .get("variablesReference")
+            .and_then(Value::as_i64)
+            .unwrap_or(0),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_stack_frame_keeps_file_sources_only() {
+        let frame = parse_stack_frame(&json!({
+            "id": 7,
+            "name": "main",
+            "source": { "path": "/src/main.rs" },
+            "line": 12,
+            "column": 5
+        }));
+        assert_eq!(
+            frame.and_then(|frame| frame.source_path),
+            Some("/src/main.rs".to_string())
+        );
+
+        let frame = parse_stack_frame(&json!({
+            "id": 8,
+            "name": "<native>",
+            "source": { "sourceReference": 3 },
+            "line": 0
+        }));
+        assert_eq!(frame.map(|frame| frame.source_path), Some(None));
+
+        // A frame without a line is malformed.
+        assert!(parse_stack_frame(&json!({ "id": 9, "name": "x" })).is_none());
+    }
+
+    #[test]
+    fn test_parse_array_skips_malformed_entries() {
+        let body = json!({
+            "variables": [
+                { "name": "x", "value": "1", "type": "i32",
+                  "variablesReference": 0 },
+                { "value": "nameless" },
+                { "name": "v", "value": "Vec(2)", "variablesReference": 4 }
+            ]
+        });
+        let variables = parse_array(&body, "variables", parse_variable);
+        let names: Vec<&str> =
+            variables.iter().map(|variable| variable.name.as_str()).collect();
+        assert_eq!(names, ["x", "v"]);
+        assert_eq!(variables[0].type_name.as_deref(), Some("i32"));
+        assert_eq!(variables[1].variables_reference, 4);
+
+        assert!(
+            parse_array(&json!({}), "variables", parse_variable).is_empty()
+        );
+    }
+
+    #[test]
+    fn test_failed_response_reports_the_adapter_message() {
+        let pending = Arc::new(Mutex::new(HashMap::new()));
+        pending
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .insert(3, DapRequestKind::StackTrace { thread_id: 1 });
+        let (events, events_rx) = mpsc::channel();
+
+        handle_response(
+            &json!({
+                "type": "response",
+                "request_seq": 3,
+                "success": false,
+                "command": "stackTrace",
+                "message": "notStopped"
+            }),
+            &pending,
+            &events,
+        );
+
+        assert!(matches!(
+            events_rx.try_recv(),
+            Ok(DapEvent::Error { command, message })
+                if command == "stackTrace" && message == "notStopped"
+        ));
+        assert!(pending.lock().unwrap_or_else(|e| e.into_inner()).is_empty());
+    }
+}
@@ -80,16 +80,45 @@ fn text_deltas(text: &str) -> (usize, usize) {
     (line_delta, col_delta)
 }
 
+/// Splits clipboard text into one segment per cursor, when it has exactly
+/// `cursor_count` lines.
+///
+/// A single trailing line break is not counted as an extra, empty line, and
+/// `\r\n` line endings count as one break: text copied from another
+/// application or platform often carries either, and should still spread one
+/// line per cursor.
+///
+/// Returns `None` when the line count does not match, in which case the full
+/// text goes to every cursor.
+fn per_cursor_segments(text: &str, cursor_count: usize) -> Option<Vec<&str>> {
+    fn split(body: &str) -> Vec<&str> {
+        body.split('\n')
+            .map(|line| line.strip_suffix('\r').unwrap_or(line))
+            .collect()
+    }
+
+    let lines = split(text);
+    if lines.len() == cursor_count {
+        return Some(lines);
+    }
+    let trimmed = text.strip_suffix('\n')?;
+    let lines = split(trimmed);
+    (lines.len() == cursor_count).then_some(lines)
+}
+
 // =========================================================================
 // CodeEditor impl
 // =========================================================================
 
 impl CodeEditor {
-    /// Copies selected text to clipboard.
+    /// Copies selected text to clipboard and records it in the clipboard
+    /// history.
     ///
     /// With multiple cursors that each have a selection, all selected texts
     /// are joined with `\n` and written to the clipboard as one string —
     /// matching the multi-cursor copy convention used by VS Code/Sublime Text.
+    /// Pasting that string back with the same number of cursors gives each
+    /// cursor its own text again (see [`Self::paste_text`]).
     ///
     /// # Example
     ///
@@ -97,7 +126,7 @@ impl CodeEditor {
     /// // cursor 0 selects "foo", cursor 1 selects "bar"
     /// // → clipboard contains "foo\nbar"
     /// ```
-    pub(crate) fn copy_selection(&self) -> Task<Message> {
+    pub(crate) fn copy_selection(&mut self) -> Task<Message> {
         let texts: Vec<String> = self
             .cursors
             .iter()
@@ -110,7 +139,9 @@ impl CodeEditor {
         if texts.is_empty() {
             Task::none()
         } else {
-            iced::clipboard::write(texts.join("\n"))
+            let text = texts.join("\n");
+            self.clipboard_history.push(&text);
+            iced::clipboard::write(text)
         }
     }
 
@@ -162,9 +193,10 @@ impl CodeEditor {
     /// Pastes text from clipboard at all cursor positions.
     ///
     /// **Multi-cursor behaviour**:
-    /// - If the clipboard text has exactly as many `\n`-separated segments as
-    ///   there are cursors, each segment is pasted at the corresponding cursor
-    ///   in ascending document order (segment *i* → cursor *i*).
+    /// - If the clipboard text has exactly as many lines as there are
+    ///   cursors, each line is pasted at the corresponding cursor in
+    ///   ascending document order (line *i* → cursor *i*). A trailing line
+    ///   break and `\r\n` endings are tolerated, see [`per_cursor_segments`].
     /// - Otherwise the full text is pasted at every cursor.
     ///
     /// Any active selection is deleted before the paste.
@@ -198,9 +230,7 @@ impl CodeEditor {
         }
 
         // Multi-cursor path.
-        // Split the clipboard on '\n' to detect per-cursor mode.
-        let clip_lines: Vec<&str> = text.split('\n').collect();
-        let per_cursor = clip_lines.len() == cursor_count;
+        let segments = per_cursor_segments(text, cursor_count);
 
         // Build a descending-order index list (highest position first).
         let order = self.cursors.descending_order();
@@ -208,11 +238,9 @@ impl CodeEditor {
         for (rank, &idx) in order.iter().enumerate() {
             // `rank` 0 = highest position, `cursor_count - 1` = lowest.
             // For per-cursor paste: ascending document order → ascending line index.
-            let paste_str: &str = if per_cursor {
-                let asc_rank = cursor_count - 1 - rank;
-                clip_lines[asc_rank]
-            } else {
-                text
+            let paste_str: &str = match &segments {
+                Some(lines) => lines[cursor_count - 1 - rank],
+                None => text,
             };
 
             let pos = self.cursors.as_slice()[idx].position;
@@ -413,4 +441,42 @@ mod tests {
         assert_eq!(editor.buffer.line(0), "abfoo");
         assert_eq!(editor.buffer.line(1), "cdbar");
     }
+
+    #[test]
+    fn test_paste_text_per_cursor_ignores_trailing_newline_and_crlf() {
+        let mut editor = CodeEditor::new("ab\ncd", "py");
+        editor.cursors.set_single((0, 2));
+        editor.cursors.add_cursor((1, 2));
+
+        editor.paste_text("foo\r\nbar\r\n");
+
+        assert_eq!(editor.buffer.line(0), "abfoo");
+        assert_eq!(editor.buffer.line(1), "cdbar");
+    }
+
+    #[test]
+    fn test_per_cursor_segments_prefers_the_exact_line_count() {
+        // Three cursors and "a\nb\n": the empty third line is a real segment.
+        assert_eq!(per_cursor_segments("a\nb\n", 3), Some(vec!["a", "b", ""]));
+        assert_eq!(per_cursor_segments("a\nb\n", 2), Some(vec!["a", "b"]));
+        assert_eq!(per_cursor_segments("a\nb\n", 4), None);
+    }
+
+    #[test]
+    fn test_copy_then_paste_round_trips_across_cursors() {
+        let mut editor = CodeEditor::new("one x\ntwo x", "txt");
+        editor.cursors.set_single((0, 3));
+        editor.cursors.primary_mut().anchor = Some((0, 0));
+        editor.cursors.add_cursor((1, 3));
+        editor.cursors.as_mut_slice()[1].anchor = Some((1, 0));
+        let _ = editor.copy_selection();
+        let copied = editor.clipboard_history()[0].clone();
+        assert_eq!(copied, "one\ntwo");
+
+        editor.cursors.set_single((0, 5));
+        editor.cursors.add_cursor((1, 5));
+        editor.paste_text(&copied);
+
+        assert_eq!(editor.content(), "one xone\ntwo xtwo");
+    }
 }
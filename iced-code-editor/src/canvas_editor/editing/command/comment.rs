@@ -1,35 +1,10 @@
-//! Line-comment toggling: mapping a syntax to its comment token and the
-//! indentation-aware command that comments/uncomments a line range.
+//! Comment toggling: the indentation-aware command that comments/uncomments a
+//! line range, and the command wrapping a range in block-comment tokens.
 
 use crate::buffer::TextBuffer;
 
 use super::Command;
 
-/// Returns the line-comment token for a syntax identifier, or `None` if the
-/// language has no line comment (e.g. HTML, CSS, Markdown).
-///
-/// Accepts both file extensions (`"rs"`) and language names (`"rust"`), matching
-/// the aliases normalised elsewhere in the editor.
-///
-/// # Examples
-///
-/// ```text
-/// assert_eq!(line_comment_token("rs"), Some("//"));
-/// assert_eq!(line_comment_token("python"), Some("#"));
-/// assert_eq!(line_comment_token("html"), None);
-/// ```
-pub(crate) fn line_comment_token(syntax: &str) -> Option<&'static str> {
-    match syntax {
-        "rs" | "rust" | "js" | "javascript" | "ts" | "typescript" | "jsx"
-        | "tsx" | "go" | "c" | "h" | "cpp" | "cc" | "cxx" | "hpp" | "c++"
-        | "java" | "cs" | "csharp" => Some("//"),
-        "py" | "python" | "sh" | "bash" | "shell" | "zsh" | "rb" | "ruby"
-        | "toml" | "yaml" | "yml" => Some("#"),
-        "lua" => Some("--"),
-        _ => None,
-    }
-}
-
 /// Computes the character count of the leading whitespace of a line.
 fn indent_char_count(line: &str) -> usize {
     let trimmed = line.trim_start();
@@ -185,34 +160,269 @@ impl Command for ToggleCommentCommand {
     }
 }
 
+/// One single-line replacement made by a [`ToggleBlockCommentCommand`].
+#[derive(Debug, Clone)]
+struct Splice {
+    /// Where the replaced text starts, before the command runs.
+    pos: (usize, usize),
+    old: String,
+    new: String,
+}
+
+impl Splice {
+    /// Creates an insertion of `text` at `pos`.
+    fn insert(pos: (usize, usize), text: String) -> Self {
+        Self { pos, old: String::new(), new: text }
+    }
+
+    /// Moves a position that sits on the spliced line, past the replaced
+    /// text, by the change in length; positions inside the replaced text are
+    /// clamped to the replacement.
+    fn shift(&self, pos: (usize, usize)) -> (usize, usize) {
+        if pos.0 != self.pos.0 || pos.1 < self.pos.1 {
+            return pos;
+        }
+        let old_len = self.old.chars().count();
+        let new_len = self.new.chars().count();
+        let offset = pos.1 - self.pos.1;
+        if offset >= old_len {
+            (pos.0, pos.1 - old_len + new_len)
+        } else {
+            (pos.0, self.pos.1 + offset.min(new_len))
+        }
+    }
+}
+
+/// Returns the characters of `line` in `from..to`.
+fn char_slice(line: &str, from: usize, to: usize) -> String {
+    line.chars().skip(from).take(to.saturating_sub(from)).collect()
+}
+
+/// Narrows `start..end` to its first and last non-whitespace characters.
+///
+/// # Returns
+///
+/// The position of the first non-whitespace character and the position just
+/// past the last one, or `None` when the range holds only whitespace
+fn trim_range(
+    buffer: &TextBuffer,
+    start: (usize, usize),
+    end: (usize, usize),
+) -> Option<((usize, usize), (usize, usize))> {
+    let mut first = None;
+    let mut last = None;
+    for line_idx in start.0..=end.0 {
+        let from = if line_idx == start.0 { start.1 } else { 0 };
+        let to = if line_idx == end.0 { end.1 } else { usize::MAX };
+        let chars = buffer.line(line_idx).chars().enumerate();
+        for (col, ch) in chars.skip(from).take_while(|(col, _)| *col < to) {
+            if !ch.is_whitespace() {
+                first.get_or_insert((line_idx, col));
+                last = Some((line_idx, col + 1));
+            }
+        }
+    }
+    Some((first?, last?))
+}
+
+/// Command for wrapping a range in block-comment tokens, or unwrapping it.
+///
+/// The text to toggle is the range with its surrounding whitespace left out.
+/// When it starts with the opening token and ends with the closing one, or
+/// when the tokens sit just outside it, the tokens are removed together with
+/// one space next to each; otherwise `open ` and ` close` are inserted
+/// around it. A blank range gets an empty comment with the cursor inside.
+///
+/// Only the two tokens are inserted or removed, so the command works on any
+/// number of lines and leaves the text in between untouched.
+#[derive(Debug, Clone)]
+pub struct ToggleBlockCommentCommand {
+    open: Splice,
+    close: Splice,
+    cursor_before: (usize, usize),
+    cursor_after: (usize, usize),
+}
+
+impl ToggleBlockCommentCommand {
+    /// Creates a new toggle-block-comment command.
+    ///
+    /// # Arguments
+    ///
+    /// * `buffer` - The text buffer (read to find existing tokens)
+    /// * `start` - Start of the range to toggle
+    /// * `end` - End of the range to toggle (exclusive)
+    /// * `open` - The opening token, e.g. `"/*"`
+    /// * `close` - The closing token, e.g. `"*/"`
+    /// * `cursor` - Current cursor position
+    pub fn new(
+        buffer: &TextBuffer,
+        start: (usize, usize),
+        end: (usize, usize),
+        open: &str,
+        close: &str,
+        cursor: (usize, usize),
+    ) -> Self {
+        let Some((first, last)) = trim_range(buffer, start, end) else {
+            let cmd = Self {
+                open: Splice::insert(start, format!("{open} ")),
+                close: Splice::insert(start, format!(" {close}")),
+                cursor_before: cursor,
+                cursor_after: cursor,
+            };
+            let cursor_after = (start.0, start.1 + open.chars().count() + 1);
+            return Self { cursor_after, ..cmd };
+        };
+
+        let (open_splice, close_splice) =
+            Self::unwrap_inside(buffer, first, last, open, close)
+                .or_else(|| {
+                    Self::unwrap_outside(buffer, first, last, open, close)
+                })
+                .unwrap_or_else(|| {
+                    (
+                        Splice::insert(first, format!("{open} ")),
+                        Splice::insert(last, format!(" {close}")),
+                    )
+                });
+        let mut cmd = Self {
+            open: open_splice,
+            close: close_splice,
+            cursor_before: cursor,
+            cursor_after: cursor,
+        };
+        cmd.cursor_after = cmd.adjust_position(cursor);
+        cmd
+    }
+
+    /// Returns the splices removing tokens that open and close
+    /// `first..last` from the inside.
+    fn unwrap_inside(
+        buffer: &TextBuffer,
+        first: (usize, usize),
+        last: (usize, usize),
+        open: &str,
+        close: &str,
+    ) -> Option<(Splice, Splice)> {
+        let open_len = open.chars().count();
+        let close_len = close.chars().count();
+        let head: String = buffer.line(first.0).chars().skip(first.1).collect();
+        let tail = char_slice(buffer.line(last.0), 0, last.1);
+        if !head.starts_with(open) || !tail.ends_with(close) {
+            return None;
+        }
+        let close_start = last.1.checked_sub(close_len)?;
+        let inner_start = first.1 + open_len;
+        if first.0 == last.0 && inner_start > close_start {
+            return None;
+        }
+
+        let mut open_old = open.to_string();
+        let room = first.0 != last.0 || inner_start < close_start;
+        if room && head[open.len()..].starts_with(' ') {
+            open_old.push(' ');
+        }
+        let mut close_pos = (last.0, close_start);
+        let mut close_old = close.to_string();
+        let open_end = first.1 + open_old.chars().count();
+        if tail[..tail.len() - close.len()].ends_with(' ')
+            && (first.0 != last.0 || close_start > open_end)
+        {
+            close_pos.1 -= 1;
+            close_old.insert(0, ' ');
+        }
+        Some((
+            Splice { pos: first, old: open_old, new: String::new() },
+            Splice { pos: close_pos, old: close_old, new: String::new() },
+        ))
+    }
+
+    /// Returns the splices removing tokens that sit just outside
+    /// `first..last`, separated from it by whitespace only.
+    fn unwrap_outside(
+        buffer: &TextBuffer,
+        first: (usize, usize),
+        last: (usize, usize),
+        open: &str,
+        close: &str,
+    ) -> Option<(Splice, Splice)> {
+        let before = char_slice(buffer.line(first.0), 0, first.1);
+        let after: String = buffer.line(last.0).chars().skip(last.1).collect();
+        let before_code = before.trim_end();
+        let after_code = after.trim_start();
+        if !before_code.ends_with(open) || !after_code.starts_with(close) {
+            return None;
+        }
+
+        let open_col =
+            before_code.chars().count().checked_sub(open.chars().count())?;
+        let mut open_old = open.to_string();
+        if before[before_code.len()..].starts_with(' ') {
+            open_old.push(' ');
+        }
+        let gap = after.len() - after_code.len();
+        let mut close_col = last.1 + after[..gap].chars().count();
+        let mut close_old = close.to_string();
+        if after[..gap].ends_with(' ') {
+            close_col -= 1;
+            close_old.insert(0, ' ');
+        }
+        Some((
+            Splice {
+                pos: (first.0, open_col),
+                old: open_old,
+                new: String::new(),
+            },
+            Splice {
+                pos: (last.0, close_col),
+                old: close_old,
+                new: String::new(),
+            },
+        ))
+    }
+
+    /// Adjusts a position (such as a selection anchor or another cursor) to
+    /// the toggled text.
+    pub fn adjust_position(&self, pos: (usize, usize)) -> (usize, usize) {
+        self.open.shift(self.close.shift(pos))
+    }
+
+    /// Returns the range the toggled text covers after the command: the
+    /// wrapped text with its tokens, or the unwrapped text without them.
+    pub fn range_after(&self) -> ((usize, usize), (usize, usize)) {
+        let close = &self.close;
+        let close_end = (close.pos.0, close.pos.1 + close.old.chars().count());
+        (self.open.pos, self.adjust_position(close_end))
+    }
+}
+
+impl Command for ToggleBlockCommentCommand {
+    fn execute(
+        &mut self,
+        buffer: &mut TextBuffer,
+        cursor: &mut (usize, usize),
+    ) {
+        // The closing token comes after the opening one, so replacing it
+        // first keeps the opening token's position valid.
+        for splice in [&self.close, &self.open] {
+            let len = splice.old.chars().count();
+            buffer.replace_range(splice.pos.0, splice.pos.1, len, &splice.new);
+        }
+        *cursor = self.cursor_after;
+    }
+
+    fn undo(&mut self, buffer: &mut TextBuffer, cursor: &mut (usize, usize)) {
+        for splice in [&self.open, &self.close] {
+            let len = splice.new.chars().count();
+            buffer.replace_range(splice.pos.0, splice.pos.1, len, &splice.old);
+        }
+        *cursor = self.cursor_before;
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
-    #[test]
-    fn test_line_comment_token() {
-        assert_eq!(line_comment_token("rs"), Some("//"));
-        assert_eq!(line_comment_token("rust"), Some("//"));
-        assert_eq!(line_comment_token("ts"), Some("//"));
-        assert_eq!(line_comment_token("go"), Some("//"));
-        assert_eq!(line_comment_token("py"), Some("#"));
-        assert_eq!(line_comment_token("python"), Some("#"));
-        assert_eq!(line_comment_token("lua"), Some("--"));
-        assert_eq!(line_comment_token("c"), Some("//"));
-        assert_eq!(line_comment_token("cpp"), Some("//"));
-        assert_eq!(line_comment_token("java"), Some("//"));
-        assert_eq!(line_comment_token("cs"), Some("//"));
-        assert_eq!(line_comment_token("sh"), Some("#"));
-        assert_eq!(line_comment_token("bash"), Some("#"));
-        assert_eq!(line_comment_token("rb"), Some("#"));
-        assert_eq!(line_comment_token("ruby"), Some("#"));
-        assert_eq!(line_comment_token("toml"), Some("#"));
-        assert_eq!(line_comment_token("yaml"), Some("#"));
-        assert_eq!(line_comment_token("yml"), Some("#"));
-        assert_eq!(line_comment_token("html"), None);
-        assert_eq!(line_comment_token("txt"), None);
-    }
-
     #[test]
     fn test_toggle_comment_single_line() {
         let mut buffer = TextBuffer::new("let x = 1;");
@@ -297,4 +507,87 @@ mod tests {
         let cmd = ToggleCommentCommand::new(&buffer, 0, 2, "//", (0, 0));
         assert!(cmd.is_noop());
     }
+
+    fn toggle_block(
+        text: &str,
+        start: (usize, usize),
+        end: (usize, usize),
+        cursor: (usize, usize),
+    ) -> (String, (usize, usize), ToggleBlockCommentCommand) {
+        let mut buffer = TextBuffer::new(text);
+        let mut cursor = cursor;
+        let mut cmd = ToggleBlockCommentCommand::new(
+            &buffer, start, end, "/*", "*/", cursor,
+        );
+        cmd.execute(&mut buffer, &mut cursor);
+        let result = buffer.to_string();
+        cmd.undo(&mut buffer, &mut cursor);
+        assert_eq!(buffer.to_string(), text);
+        (result, cursor, cmd)
+    }
+
+    #[test]
+    fn test_toggle_block_comment_wraps_and_unwraps() {
+        let (text, _, cmd) = toggle_block("a = b + c;", (0, 4), (0, 9), (0, 9));
+        assert_eq!(text, "a = /* b + c */;");
+        assert_eq!(cmd.range_after(), ((0, 4), (0, 15)));
+        assert_eq!(cmd.adjust_position((0, 9)), (0, 15));
+
+        let (text, _, cmd) =
+            toggle_block("a = /* b + c */;", (0, 4), (0, 15), (0, 4));
+        assert_eq!(text, "a = b + c;");
+        assert_eq!(cmd.range_after(), ((0, 4), (0, 9)));
+    }
+
+    #[test]
+    fn test_toggle_block_comment_ignores_surrounding_whitespace() {
+        let (text, _, _) = toggle_block("  x  ", (0, 0), (0, 5), (0, 0));
+        assert_eq!(text, "  /* x */  ");
+        let (text, _, _) = toggle_block("  /* x */  ", (0, 0), (0, 11), (0, 0));
+        assert_eq!(text, "  x  ");
+    }
+
+    #[test]
+    fn test_toggle_block_comment_unwraps_tokens_outside_the_range() {
+        let (text, _, cmd) = toggle_block("f(/* x */)", (0, 5), (0, 6), (0, 6));
+        assert_eq!(text, "f(x)");
+        assert_eq!(cmd.range_after(), ((0, 2), (0, 3)));
+        assert_eq!(cmd.adjust_position((0, 6)), (0, 3));
+    }
+
+    #[test]
+    fn test_toggle_block_comment_multiline() {
+        let (text, _, cmd) = toggle_block("a\nb\nc", (0, 0), (2, 1), (2, 1));
+        assert_eq!(text, "/* a\nb\nc */");
+        assert_eq!(cmd.range_after(), ((0, 0), (2, 4)));
+
+        let (text, _, _) =
+            toggle_block("/* a\nb\nc */", (0, 0), (2, 4), (0, 0));
+        assert_eq!(text, "a\nb\nc");
+    }
+
+    #[test]
+    fn test_toggle_block_comment_on_blank_range_inserts_empty_comment() {
+        let mut buffer = TextBuffer::new("    ");
+        let mut cursor = (0, 4);
+        let mut cmd = ToggleBlockCommentCommand::new(
+            &buffer,
+            (0, 4),
+            (0, 4),
+            "<!--",
+            "-->",
+            cursor,
+        );
+        cmd.execute(&mut buffer, &mut cursor);
+        assert_eq!(buffer.line(0), "    <!--  -->");
+        assert_eq!(cursor, (0, 9));
+    }
+
+    #[test]
+    fn test_toggle_block_comment_keeps_a_lone_space() {
+        let (text, _, _) = toggle_block("/* */", (0, 0), (0, 5), (0, 0));
+        assert_eq!(text, "");
+        let (text, _, _) = toggle_block("/**/", (0, 0), (0, 4), (0, 0));
+        assert_eq!(text, "");
+    }
 }
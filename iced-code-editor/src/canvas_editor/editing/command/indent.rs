@@ -0,0 +1,146 @@
+//! Command replacing the indentation of a contiguous range of lines.
+
+use super::Command;
+use crate::buffer::TextBuffer;
+
+/// Returns the number of leading whitespace characters of `line`.
+fn indent_len(line: &str) -> usize {
+    line.chars().take_while(|ch| *ch == ' ' || *ch == '\t').count()
+}
+
+/// Command for re-indenting a contiguous range of lines.
+///
+/// Only the leading whitespace of each line is replaced; the rest of the line
+/// is kept as is. Positions inside the new indentation are clamped to its
+/// end, positions after it move with the text.
+#[derive(Debug, Clone)]
+pub struct ReindentLinesCommand {
+    start: usize,
+    old_lines: Vec<String>,
+    new_lines: Vec<String>,
+    cursor_before: (usize, usize),
+    cursor_after: (usize, usize),
+}
+
+impl ReindentLinesCommand {
+    /// Creates a new re-indent command.
+    ///
+    /// # Arguments
+    ///
+    /// * `buffer` - The text buffer (read to capture original lines)
+    /// * `start` - First line of the range
+    /// * `indents` - The new indentation of each line from `start`, one
+    ///   entry per line
+    /// * `cursor` - Current cursor position
+    pub fn new(
+        buffer: &TextBuffer,
+        start: usize,
+        indents: &[String],
+        cursor: (usize, usize),
+    ) -> Self {
+        let old_lines: Vec<String> = (start..start + indents.len())
+            .map(|line| buffer.line(line).to_string())
+            .collect();
+        let new_lines = old_lines
+            .iter()
+            .zip(indents)
+            .map(|(line, indent)| {
+                let text: String =
+                    line.chars().skip(indent_len(line)).collect();
+                format!("{indent}{text}")
+            })
+            .collect();
+
+        let mut command = Self {
+            start,
+            old_lines,
+            new_lines,
+            cursor_before: cursor,
+            cursor_after: cursor,
+        };
+        command.cursor_after = command.adjust_position(cursor);
+        command
+    }
+
+    /// Returns `true` when every line already has its new indentation, so
+    /// the caller can skip recording history.
+    pub fn is_noop(&self) -> bool {
+        self.old_lines == self.new_lines
+    }
+
+    /// Adjusts a position (such as a selection anchor or another cursor) to
+    /// the re-indented text.
+    pub fn adjust_position(&self, pos: (usize, usize)) -> (usize, usize) {
+        let Some(index) = pos.0.checked_sub(self.start) else {
+            return pos;
+        };
+        let (Some(old), Some(new)) =
+            (self.old_lines.get(index), self.new_lines.get(index))
+        else {
+            return pos;
+        };
+        let old_indent = indent_len(old);
+        let new_indent = indent_len(new);
+        if pos.1 >= old_indent {
+            (pos.0, pos.1 - old_indent + new_indent)
+        } else {
+            (pos.0, pos.1.min(new_indent))
+        }
+    }
+}
+
+impl Command for ReindentLinesCommand {
+    fn execute(
+        &mut self,
+        buffer: &mut TextBuffer,
+        cursor: &mut (usize, usize),
+    ) {
+        for (offset, content) in self.new_lines.iter().enumerate() {
+            let line_idx = self.start + offset;
+            let len = buffer.line_len(line_idx);
+            buffer.replace_range(line_idx, 0, len, content);
+        }
+        *cursor = self.cursor_after;
+    }
+
+    fn undo(&mut self, buffer: &mut TextBuffer, cursor: &mut (usize, usize)) {
+        for (offset, content) in self.old_lines.iter().enumerate() {
+            let line_idx = self.start + offset;
+            let len = buffer.line_len(line_idx);
+            buffer.replace_range(line_idx, 0, len, content);
+        }
+        *cursor = self.cursor_before;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_reindent_replaces_only_leading_whitespace() {
+        let mut buffer = TextBuffer::new("a\n\t  b\n c");
+        let mut cursor = (1, 4);
+        let indents = ["    ".to_string(), String::new()];
+        let mut cmd = ReindentLinesCommand::new(&buffer, 1, &indents, cursor);
+
+        cmd.execute(&mut buffer, &mut cursor);
+        assert_eq!(buffer.to_string(), "a\n    b\nc");
+        assert_eq!(cursor, (1, 5));
+
+        cmd.undo(&mut buffer, &mut cursor);
+        assert_eq!(buffer.to_string(), "a\n\t  b\n c");
+        assert_eq!(cursor, (1, 4));
+    }
+
+    #[test]
+    fn test_reindent_clamps_positions_inside_the_indentation() {
+        let buffer = TextBuffer::new("        x");
+        let cmd =
+            ReindentLinesCommand::new(&buffer, 0, &["  ".to_string()], (0, 6));
+        assert_eq!(cmd.adjust_position((0, 6)), (0, 2));
+        assert_eq!(cmd.adjust_position((0, 1)), (0, 1));
+        assert_eq!(cmd.adjust_position((0, 9)), (0, 3));
+        assert!(!cmd.is_noop());
+    }
+}
@@ -1,4 +1,5 @@
-//! Line-level commands: moving and duplicating contiguous ranges of lines.
+//! Line-level commands: moving, duplicating and rewriting contiguous ranges
+//! of lines.
 
 use crate::buffer::TextBuffer;
 
@@ -142,10 +143,140 @@ impl Command for DuplicateLinesCommand {
     }
 }
 
+/// Command for replacing a contiguous range of lines with new lines.
+///
+/// The replacement may hold a different number of lines than it replaces,
+/// which is how de-duplicating and joining lines shrink the buffer. Backs the
+/// line and text transformations (sort, case conversion, ...).
+#[derive(Debug, Clone)]
+pub struct ReplaceLinesCommand {
+    start: usize,
+    old_lines: Vec<String>,
+    new_lines: Vec<String>,
+    cursor_before: (usize, usize),
+    cursor_after: (usize, usize),
+}
+
+impl ReplaceLinesCommand {
+    /// Creates a new replace-lines command.
+    ///
+    /// # Arguments
+    ///
+    /// * `buffer` - The text buffer (read to capture the original lines)
+    /// * `start` - First line of the range
+    /// * `old_len` - Number of lines replaced, at least one
+    /// * `new_lines` - The lines put in their place, at least one
+    /// * `cursor_before` - Cursor position restored by undo
+    /// * `cursor_after` - Cursor position set by execute
+    pub fn new(
+        buffer: &TextBuffer,
+        start: usize,
+        old_len: usize,
+        new_lines: Vec<String>,
+        cursor_before: (usize, usize),
+        cursor_after: (usize, usize),
+    ) -> Self {
+        let old_lines = (start..start + old_len)
+            .map(|line| buffer.line(line).to_string())
+            .collect();
+        Self { start, old_lines, new_lines, cursor_before, cursor_after }
+    }
+
+    /// Returns `true` when the replacement equals the original lines, so the
+    /// caller can skip recording history.
+    pub fn is_noop(&self) -> bool {
+        self.old_lines == self.new_lines
+    }
+
+    /// Swaps `from` for `to` in the buffer, line by line where both have a
+    /// line and by inserting or removing whole lines past that.
+    fn replace(
+        buffer: &mut TextBuffer,
+        start: usize,
+        from: &[String],
+        to: &[String],
+    ) {
+        let shared = from.len().min(to.len());
+        for (offset, content) in to.iter().take(shared).enumerate() {
+            let line = start + offset;
+            let len = buffer.line_len(line);
+            buffer.replace_range(line, 0, len, content);
+        }
+        for _ in shared..from.len() {
+            buffer.remove_line(start + shared);
+        }
+        for (offset, content) in to.iter().enumerate().skip(shared) {
+            buffer.insert_line(start + offset, content.clone());
+        }
+    }
+}
+
+impl Command for ReplaceLinesCommand {
+    fn execute(
+        &mut self,
+        buffer: &mut TextBuffer,
+        cursor: &mut (usize, usize),
+    ) {
+        Self::replace(buffer, self.start, &self.old_lines, &self.new_lines);
+        *cursor = self.cursor_after;
+    }
+
+    fn undo(&mut self, buffer: &mut TextBuffer, cursor: &mut (usize, usize)) {
+        Self::replace(buffer, self.start, &self.new_lines, &self.old_lines);
+        *cursor = self.cursor_before;
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    fn lines(text: &str) -> Vec<String> {
+        text.split('\n').map(str::to_string).collect()
+    }
+
+    #[test]
+    fn test_replace_lines_command_shrinks_and_restores() {
+        let mut buffer = TextBuffer::new("a\nb\nc\nd");
+        let mut cursor = (2, 1);
+        let mut cmd = ReplaceLinesCommand::new(
+            &buffer,
+            1,
+            2,
+            lines("b c"),
+            cursor,
+            (1, 3),
+        );
+
+        cmd.execute(&mut buffer, &mut cursor);
+        assert_eq!(buffer.to_string(), "a\nb c\nd");
+        assert_eq!(cursor, (1, 3));
+
+        cmd.undo(&mut buffer, &mut cursor);
+        assert_eq!(buffer.to_string(), "a\nb\nc\nd");
+        assert_eq!(cursor, (2, 1));
+    }
+
+    #[test]
+    fn test_replace_lines_command_grows_at_the_end_of_the_buffer() {
+        let mut buffer = TextBuffer::new("x");
+        let mut cursor = (0, 0);
+        let mut cmd = ReplaceLinesCommand::new(
+            &buffer,
+            0,
+            1,
+            lines("y\nz"),
+            cursor,
+            cursor,
+        );
+
+        cmd.execute(&mut buffer, &mut cursor);
+        assert_eq!(buffer.to_string(), "y\nz");
+
+        cmd.undo(&mut buffer, &mut cursor);
+        assert_eq!(buffer.to_string(), "x");
+    }
+
     #[test]
     fn test_move_lines_command_down() {
         let mut buffer = TextBuffer::new("a\nb\nc");
@@ -5,24 +5,25 @@
 //!
 //! Concrete commands are grouped by family into submodules: [`edit`] for
 //! single-buffer editing, [`composite`] for grouping/replace commands,
-//! [`lines`] for line-level moves/duplication, and [`comment`] for line-
-//! comment toggling.
+//! [`lines`] for line-level moves, duplication and replacement, [`comment`] for line and
+//! block comment toggling, and [`indent`] for re-indentation.
 
 use crate::buffer::TextBuffer;
 
 mod comment;
 mod composite;
 mod edit;
+mod indent;
 mod lines;
 
-pub use comment::ToggleCommentCommand;
-pub(crate) use comment::line_comment_token;
+pub use comment::{ToggleBlockCommentCommand, ToggleCommentCommand};
 pub use composite::{CompositeCommand, ReplaceTextCommand};
 pub use edit::{
     DeleteCharCommand, DeleteForwardCommand, DeleteRangeCommand,
     InsertCharCommand, InsertNewlineCommand, InsertTextCommand,
 };
-pub use lines::{DuplicateLinesCommand, MoveLinesCommand};
+pub use indent::ReindentLinesCommand;
+pub use lines::{DuplicateLinesCommand, MoveLinesCommand, ReplaceLinesCommand};
 
 /// Trait for reversible editor commands.
 ///
@@ -14,9 +14,59 @@ use crate::buffer::TextBuffer;
 use crate::canvas_editor::features::vim::VimMotion;
 use crate::canvas_editor::render::wrapping::{VisualLine, WrappingCalculator};
 use crate::canvas_editor::{
-    ArrowDirection, CodeEditor, Message, measure_char_width, measure_text_width,
+    ArrowDirection, CodeEditor, Message, WordUnit, measure_char_width,
+    measure_text_width,
 };
 
+/// Characters that separate words by default: ASCII punctuation except `_`.
+pub(crate) const DEFAULT_WORD_SEPARATORS: &str =
+    "`~!@#$%^&*()-=+[{]}\\|;:'\",.<>/?";
+
+/// The classes of characters word motions tell apart; a word is a run of
+/// characters of one class other than [`WordClass::Space`].
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub(crate) enum WordClass {
+    Space,
+    Word,
+    Punctuation,
+}
+
+/// Classifies a character for word motions, in and outside Vim mode.
+///
+/// Whitespace (including the synthetic `'\n'` of a character index) is
+/// [`WordClass::Space`] and characters in `separators` are
+/// [`WordClass::Punctuation`]. Other letters, digits and ASCII characters
+/// are [`WordClass::Word`], and the remaining symbols punctuation.
+pub(crate) fn word_class(ch: char, separators: &str) -> WordClass {
+    if ch.is_whitespace() {
+        WordClass::Space
+    } else if separators.contains(ch) {
+        WordClass::Punctuation
+    } else if ch.is_alphanumeric() || ch.is_ascii() {
+        WordClass::Word
+    } else {
+        WordClass::Punctuation
+    }
+}
+
+/// Returns `true` when a sub-word starts at `index` of a run of word
+/// characters: after underscores (`foo_|bar`), at a lower-to-upper case
+/// change (`foo|Bar`), and before the last capital of an acronym followed
+/// by a lowercase letter (`HTML|Parser`).
+fn starts_sub_word(chars: &[((usize, usize), char)], index: usize) -> bool {
+    let Some(prev) = index.checked_sub(1).map(|i| chars[i].1) else {
+        return false;
+    };
+    let cur = chars[index].1;
+    let next = chars.get(index + 1).map(|(_, ch)| *ch);
+    (prev == '_' && cur != '_')
+        || ((prev.is_lowercase() || prev.is_ascii_digit())
+            && cur.is_uppercase())
+        || (prev.is_uppercase()
+            && cur.is_uppercase()
+            && next.is_some_and(char::is_lowercase))
+}
+
 /// Computes the next logical `(line, col)` position for a cursor at `pos` moving in `direction`.
 ///
 /// Returns `None` if the cursor is already at the boundary and cannot move further.
@@ -192,10 +242,20 @@ impl CodeEditor {
             }
             VimMotion::WordForward
             | VimMotion::WordBackward
-            | VimMotion::WordEnd => {
-                let chars = self.vim_char_index();
+            | VimMotion::WordEnd
+            | VimMotion::WordEndBackward
+            | VimMotion::BigWordForward
+            | VimMotion::BigWordBackward
+            | VimMotion::BigWordEnd
+            | VimMotion::BigWordEndBackward => {
+                let chars = self.char_index();
                 for _ in 0..count {
-                    position = Self::vim_word_motion(&chars, position, motion);
+                    position = Self::vim_word_motion(
+                        &chars,
+                        position,
+                        motion,
+                        &self.word_separators,
+                    );
                 }
             }
             VimMotion::LineStart => position.1 = 0,
@@ -226,6 +286,31 @@ impl CodeEditor {
                 };
                 position = (line, 0);
             }
+            VimMotion::FindChar { find, repeated } => {
+                position = self
+                    .vim_find_target(position, find, repeated, count)
+                    .unwrap_or(position);
+            }
+            VimMotion::MatchPair => {
+                position =
+                    self.vim_match_pair_target(position, count, explicit_count);
+            }
+            VimMotion::ParagraphForward | VimMotion::ParagraphBackward => {
+                position = self.vim_paragraph_target(
+                    position,
+                    motion == VimMotion::ParagraphForward,
+                    count,
+                );
+            }
+            VimMotion::ScreenTop
+            | VimMotion::ScreenMiddle
+            | VimMotion::ScreenBottom => {
+                position = self.vim_screen_line_target(motion, count);
+            }
+            VimMotion::SearchWord { forward } => {
+                position =
+                    self.vim_search_word_target(position, forward, count);
+            }
         }
 
         self.vim_normal_position(position)
@@ -236,7 +321,7 @@ impl CodeEditor {
     /// word motions can cross line breaks. Rebuilding this once per keystroke
     /// (rather than once per counted repetition) keeps counted motions
     /// (`5w`) linear in document size instead of `O(document_size * count)`.
-    fn vim_char_index(&self) -> Vec<((usize, usize), char)> {
+    pub(crate) fn char_index(&self) -> Vec<((usize, usize), char)> {
         let mut chars = Vec::new();
         for line in 0..self.buffer.line_count() {
             chars.extend(
@@ -253,29 +338,28 @@ impl CodeEditor {
         chars
     }
 
-    /// Resolves a single word-wise Vim motion (`w`/`b`/`e`) against a
-    /// prebuilt character index (see [`Self::vim_char_index`]).
+    /// Resolves a single word-wise Vim motion (`w`/`b`/`e`/`ge`, or their
+    /// WORD forms `W`/`B`/`E`/`gE`, for which every non-blank character
+    /// belongs to the same class) against a prebuilt character index (see
+    /// [`Self::char_index`]).
     fn vim_word_motion(
         chars: &[((usize, usize), char)],
         start: (usize, usize),
         motion: VimMotion,
+        separators: &str,
     ) -> (usize, usize) {
-        #[derive(Clone, Copy, PartialEq, Eq)]
-        enum Class {
-            Space,
-            Word,
-            Punctuation,
-        }
-
-        fn class(ch: char) -> Class {
-            if ch.is_whitespace() {
-                Class::Space
-            } else if ch.is_alphanumeric() || ch == '_' {
-                Class::Word
-            } else {
-                Class::Punctuation
-            }
-        }
+        use WordClass as Class;
+        let big_word = matches!(
+            motion,
+            VimMotion::BigWordForward
+                | VimMotion::BigWordBackward
+                | VimMotion::BigWordEnd
+                | VimMotion::BigWordEndBackward
+        );
+        let class = |ch| match word_class(ch, separators) {
+            Class::Punctuation if big_word => Class::Word,
+            class => class,
+        };
 
         if chars.is_empty() {
             return (0, 0);
@@ -286,7 +370,7 @@ impl CodeEditor {
         let exact = insertion < chars.len() && chars[insertion].0 == start;
 
         match motion {
-            VimMotion::WordForward => {
+            VimMotion::WordForward | VimMotion::BigWordForward => {
                 let mut index = insertion;
                 if exact {
                     let current_class = class(chars[index].1);
@@ -311,7 +395,7 @@ impl CodeEditor {
                 }
                 chars[index.min(chars.len() - 1)].0
             }
-            VimMotion::WordBackward => {
+            VimMotion::WordBackward | VimMotion::BigWordBackward => {
                 let mut index = insertion.saturating_sub(1);
                 while index > 0 && class(chars[index].1) == Class::Space {
                     index -= 1;
@@ -322,7 +406,7 @@ impl CodeEditor {
                 }
                 chars[index].0
             }
-            VimMotion::WordEnd => {
+            VimMotion::WordEnd | VimMotion::BigWordEnd => {
                 let mut index = insertion.min(chars.len() - 1);
                 if exact {
                     let current_class = class(chars[index].1);
@@ -352,10 +436,118 @@ impl CodeEditor {
                 }
                 chars[index].0
             }
+            VimMotion::WordEndBackward | VimMotion::BigWordEndBackward => {
+                let mut index = insertion.min(chars.len() - 1);
+                let current_class = class(chars[index].1);
+                if current_class != Class::Space {
+                    while index > 0
+                        && class(chars[index - 1].1) == current_class
+                    {
+                        index -= 1;
+                    }
+                }
+                if index == 0 {
+                    return chars[0].0;
+                }
+                index -= 1;
+                while index > 0 && class(chars[index].1) == Class::Space {
+                    index -= 1;
+                }
+                chars[index].0
+            }
             _ => start,
         }
     }
 
+    /// Resolves a word-wise move outside Vim mode (`Ctrl+Left`/`Right`)
+    /// against a prebuilt character index (see [`Self::char_index`]).
+    ///
+    /// Whitespace and line breaks are skipped, then the run of characters
+    /// of the class found next: moving left lands on the start of a word,
+    /// moving right just past its end. With [`WordUnit::SubWord`] the run
+    /// also stops where a sub-word starts (see [`starts_sub_word`]).
+    ///
+    /// # Returns
+    ///
+    /// The target position; the start or the end of the document when no
+    /// word is left in that direction
+    fn word_boundary(
+        chars: &[((usize, usize), char)],
+        start: (usize, usize),
+        forward: bool,
+        unit: WordUnit,
+        separators: &str,
+    ) -> (usize, usize) {
+        let class = |index: usize| word_class(chars[index].1, separators);
+        let splits = |index: usize| {
+            unit == WordUnit::SubWord
+                && class(index) == WordClass::Word
+                && starts_sub_word(chars, index)
+        };
+        let mut index = chars.partition_point(|(pos, _)| *pos < start);
+
+        if forward {
+            while index < chars.len() && class(index) == WordClass::Space {
+                index += 1;
+            }
+            if let Some(run) = (index < chars.len()).then(|| class(index)) {
+                index += 1;
+                while index < chars.len()
+                    && class(index) == run
+                    && !splits(index)
+                {
+                    index += 1;
+                }
+            }
+            return match chars.get(index) {
+                Some((pos, _)) => *pos,
+                None => match chars.last() {
+                    Some(((line, _), '\n')) => (line + 1, 0),
+                    Some(((line, col), _)) => (*line, col + 1),
+                    None => (0, 0),
+                },
+            };
+        }
+
+        while index > 0 && class(index - 1) == WordClass::Space {
+            index -= 1;
+        }
+        if let Some(run) = index.checked_sub(1).map(class) {
+            index -= 1;
+            while index > 0 && class(index - 1) == run && !splits(index) {
+                index -= 1;
+            }
+        }
+        chars.get(index).map_or(start, |(pos, _)| *pos)
+    }
+
+    /// Returns where a word-wise move lands for each cursor, in cursor
+    /// order.
+    ///
+    /// # Arguments
+    ///
+    /// * `forward` - `true` to move right, `false` to move left
+    /// * `unit` - Whether to stop at sub-word boundaries
+    pub(crate) fn word_targets(
+        &self,
+        forward: bool,
+        unit: WordUnit,
+    ) -> Vec<(usize, usize)> {
+        let chars = self.char_index();
+        self.cursors
+            .iter()
+            .map(|cursor| {
+                Self::word_boundary(
+                    &chars,
+                    cursor.position,
+                    forward,
+                    unit,
+                    &self.word_separators,
+                )
+            })
+            .collect()
+    }
+
     /// Sets the cursor position to the specified line and column.
     ///
     /// This method ensures the new position is within the bounds of the text buffer.
@@ -829,26 +1021,109 @@ mod tests {
     #[test]
     fn vim_word_motion_crosses_line_boundary_via_prebuilt_index() {
         let editor = CodeEditor::new("one two\nthree four", "txt");
-        let chars = editor.vim_char_index();
+        let chars = editor.char_index();
+        let seps = DEFAULT_WORD_SEPARATORS;
 
         assert_eq!(
-            CodeEditor::vim_word_motion(&chars, (0, 4), VimMotion::WordForward),
+            CodeEditor::vim_word_motion(
+                &chars,
+                (0, 4),
+                VimMotion::WordForward,
+                seps
+            ),
             (1, 0)
         );
         assert_eq!(
             CodeEditor::vim_word_motion(
                 &chars,
                 (1, 0),
-                VimMotion::WordBackward
+                VimMotion::WordBackward,
+                seps
             ),
             (0, 4)
         );
         assert_eq!(
-            CodeEditor::vim_word_motion(&chars, (0, 4), VimMotion::WordEnd),
+            CodeEditor::vim_word_motion(
+                &chars,
+                (0, 4),
+                VimMotion::WordEnd,
+                seps
+            ),
             (0, 6)
         );
     }
 
+    #[test]
+    fn word_class_honours_separators() {
+        let seps = DEFAULT_WORD_SEPARATORS;
+        assert_eq!(word_class('a', seps), WordClass::Word);
+        assert_eq!(word_class('_', seps), WordClass::Word);
+        assert_eq!(word_class('-', seps), WordClass::Punctuation);
+        assert_eq!(word_class('\n', seps), WordClass::Space);
+        assert_eq!(word_class('-', ""), WordClass::Word);
+    }
+
+    fn word_target(
+        editor: &CodeEditor,
+        start: (usize, usize),
+        forward: bool,
+        unit: WordUnit,
+    ) -> (usize, usize) {
+        let chars = editor.char_index();
+        let seps = editor.word_separators();
+        CodeEditor::word_boundary(&chars, start, forward, unit, seps)
+    }
+
+    #[test]
+    fn word_boundary_skips_whitespace_and_punctuation_runs() {
+        let editor = CodeEditor::new("let foo = bar.baz();\nnext", "txt");
+
+        let right = |pos| word_target(&editor, pos, true, WordUnit::Word);
+        assert_eq!(right((0, 0)), (0, 3));
+        assert_eq!(right((0, 3)), (0, 7));
+        assert_eq!(right((0, 7)), (0, 9));
+        assert_eq!(right((0, 13)), (0, 14));
+        assert_eq!(right((0, 17)), (0, 20));
+        assert_eq!(right((0, 20)), (1, 4));
+        assert_eq!(right((1, 4)), (1, 4));
+
+        let left = |pos| word_target(&editor, pos, false, WordUnit::Word);
+        assert_eq!(left((1, 0)), (0, 17));
+        assert_eq!(left((0, 13)), (0, 10));
+        assert_eq!(left((0, 4)), (0, 0));
+        assert_eq!(left((0, 0)), (0, 0));
+    }
+
+    #[test]
+    fn word_boundary_stops_at_sub_words() {
+        let editor = CodeEditor::new("parseHTMLString snake_case_name", "txt");
+
+        let right = |pos| word_target(&editor, pos, true, WordUnit::SubWord);
+        assert_eq!(right((0, 0)), (0, 5));
+        assert_eq!(right((0, 5)), (0, 9));
+        assert_eq!(right((0, 9)), (0, 15));
+        assert_eq!(right((0, 15)), (0, 22));
+        assert_eq!(right((0, 22)), (0, 27));
+
+        let left = |pos| word_target(&editor, pos, false, WordUnit::SubWord);
+        assert_eq!(left((0, 31)), (0, 27));
+        assert_eq!(left((0, 27)), (0, 22));
+        assert_eq!(left((0, 15)), (0, 9));
+        assert_eq!(left((0, 9)), (0, 5));
+    }
+
+    #[test]
+    fn word_boundary_uses_configured_separators() {
+        let mut editor = CodeEditor::new("kebab-case-name", "txt");
+        let right = |editor: &CodeEditor| {
+            word_target(editor, (0, 0), true, WordUnit::Word)
+        };
+        assert_eq!(right(&editor), (0, 5));
+
+        editor.set_word_separators("");
+        assert_eq!(right(&editor), (0, 15));
+    }
+
     #[test]
     fn test_cursor_movement() {
         let mut editor = CodeEditor::new("line1\nline2", "py");
@@ -0,0 +1,198 @@
+//! Pure indentation helpers behind block indent/outdent, indentation
+//! conversion and indentation detection.
+//!
+//! Like [`transform`](super::transform), everything here works on plain
+//! strings; the handlers in `input/update/indentation.rs` apply the results
+//! to the buffer as one undoable edit.
+
+use crate::canvas_editor::features::indent_rules::leading_width;
+use crate::canvas_editor::{IndentStyle, TAB_WIDTH};
+
+/// Most lines [`detect_indent_style`] looks at.
+const DETECTION_SAMPLE_LINES: usize = 10_000;
+
+/// Space widths [`detect_indent_style`] accepts as an indentation level, in
+/// order of preference when two are equally common.
+const DETECTABLE_WIDTHS: [usize; 4] = [4, 2, 8, 3];
+
+/// Returns the text of one indentation level in `style`.
+pub(crate) fn indent_unit(style: IndentStyle) -> String {
+    match style {
+        IndentStyle::Spaces(n) => " ".repeat(usize::from(n)),
+        IndentStyle::Tab => "\t".to_string(),
+    }
+}
+
+/// Returns the number of leading space and tab characters of `line`.
+pub(crate) fn leading_len(line: &str) -> usize {
+    line.chars().take_while(|ch| *ch == ' ' || *ch == '\t').count()
+}
+
+/// Adds one level of `style` indentation in front of `line`.
+///
+/// Empty lines are left alone, so indenting a block does not leave
+/// whitespace-only lines behind.
+///
+/// # Returns
+///
+/// The new line and how many characters were added
+pub(crate) fn indent_line(line: &str, style: IndentStyle) -> (String, usize) {
+    if line.is_empty() {
+        return (String::new(), 0);
+    }
+    let mut indented = indent_unit(style);
+    let added = indented.chars().count();
+    indented.push_str(line);
+    (indented, added)
+}
+
+/// Removes one level of indentation from the front of `line`: a leading tab,
+/// or up to one level of `style` spaces.
+///
+/// # Returns
+///
+/// The new line and how many characters were removed
+pub(crate) fn outdent_line(line: &str, style: IndentStyle) -> (String, usize) {
+    let width = match style {
+        IndentStyle::Spaces(n) => usize::from(n),
+        IndentStyle::Tab => TAB_WIDTH,
+    };
+    let removed = if line.starts_with('\t') {
+        1
+    } else {
+        line.chars().take(width).take_while(|ch| *ch == ' ').count()
+    };
+    (line.chars().skip(removed).collect(), removed)
+}
+
+/// Rewrites the leading whitespace of `line` in `style`, keeping its width.
+///
+/// Converting to tabs keeps a remainder narrower than a tab as spaces, so
+/// hanging alignments survive.
+///
+/// # Returns
+///
+/// The new line, the length of the old leading whitespace and the length of
+/// the new one
+pub(crate) fn convert_indentation(
+    line: &str,
+    style: IndentStyle,
+) -> (String, usize, usize) {
+    let old_len = leading_len(line);
+    let width = leading_width(line);
+    let indent = match style {
+        IndentStyle::Spaces(_) => " ".repeat(width),
+        IndentStyle::Tab => {
+            let mut indent = "\t".repeat(width / TAB_WIDTH);
+            indent.push_str(&" ".repeat(width % TAB_WIDTH));
+            indent
+        }
+    };
+    let new_len = indent.chars().count();
+    let mut converted = indent;
+    converted.extend(line.chars().skip(old_len));
+    (converted, old_len, new_len)
+}
+
+/// Guesses the indentation style of `text` from its leading whitespace.
+///
+/// Lines indented with a tab vote for [`IndentStyle::Tab`]; lines indented
+/// with spaces vote for spaces, and the width is the most common step by
+/// which the indentation grows from one line to the next. Blank lines are
+/// skipped, as are steps of a single space, which are usually alignment
+/// (a `*` continuing a block comment) rather than indentation.
+///
+/// # Returns
+///
+/// The detected style, or `None` when no line is indented by a recognizable
+/// amount
+pub(crate) fn detect_indent_style(text: &str) -> Option<IndentStyle> {
+    let mut tab_lines = 0usize;
+    let mut space_lines = 0usize;
+    let mut steps = [0usize; 9];
+    let mut previous = 0usize;
+    for line in text.lines().take(DETECTION_SAMPLE_LINES) {
+        if line.trim().is_empty() {
+            continue;
+        }
+        if line.starts_with('\t') {
+            tab_lines += 1;
+            previous = 0;
+            continue;
+        }
+        let width = line.chars().take_while(|ch| *ch == ' ').count();
+        if width > 0 {
+            space_lines += 1;
+        }
+        if let Some(step) = width.checked_sub(previous)
+            && let Some(count) = steps.get_mut(step)
+        {
+            *count += 1;
+        }
+        previous = width;
+    }
+
+    if tab_lines > space_lines {
+        return Some(IndentStyle::Tab);
+    }
+    let most = DETECTABLE_WIDTHS.iter().map(|width| steps[*width]).max()?;
+    if most == 0 {
+        return None;
+    }
+    let width =
+        DETECTABLE_WIDTHS.into_iter().find(|width| steps[*width] == most)?;
+    Some(IndentStyle::Spaces(width as u8))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_indent_and_outdent_one_level() {
+        let spaces = IndentStyle::Spaces(4);
+        assert_eq!(indent_line("x", spaces), ("    x".to_string(), 4));
+        assert_eq!(indent_line("", spaces), (String::new(), 0));
+        assert_eq!(indent_line("x", IndentStyle::Tab), ("\tx".to_string(), 1));
+
+        assert_eq!(outdent_line("      x", spaces), ("  x".to_string(), 4));
+        assert_eq!(outdent_line("  x", spaces), ("x".to_string(), 2));
+        assert_eq!(outdent_line("\t\tx", spaces), ("\tx".to_string(), 1));
+        assert_eq!(outdent_line("x", spaces), ("x".to_string(), 0));
+    }
+
+    #[test]
+    fn test_convert_indentation_keeps_the_width() {
+        assert_eq!(
+            convert_indentation("\t  x", IndentStyle::Spaces(4)),
+            ("      x".to_string(), 3, 6)
+        );
+        assert_eq!(
+            convert_indentation("      x", IndentStyle::Tab),
+            ("\t  x".to_string(), 6, 3)
+        );
+        assert_eq!(
+            convert_indentation("x", IndentStyle::Tab),
+            ("x".to_string(), 0, 0)
+        );
+    }
+
+    #[test]
+    fn test_detect_indent_style() {
+        assert_eq!(
+            detect_indent_style("fn a() {\n\tb();\n\tif c {\n\t\td();\n\t}\n}"),
+            Some(IndentStyle::Tab)
+        );
+        assert_eq!(
+            detect_indent_style("a:\n  b:\n    c: 1\n  d: 2\n"),
+            Some(IndentStyle::Spaces(2))
+        );
+        assert_eq!(
+            detect_indent_style(
+                "/**\n * doc\n */\nfn a() {\n    b();\n    if c {\n        d();\n    }\n}"
+            ),
+            Some(IndentStyle::Spaces(4))
+        );
+        assert_eq!(detect_indent_style("a\nb\n\nc"), None);
+    }
+}
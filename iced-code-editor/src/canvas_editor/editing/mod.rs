@@ -3,7 +3,9 @@
 
 mod clipboard;
 pub mod command;
-mod cursor;
+pub(crate) mod cursor;
 pub(crate) mod cursor_set;
 pub mod history;
+pub(crate) mod indentation;
 mod selection;
+pub(crate) mod transform;
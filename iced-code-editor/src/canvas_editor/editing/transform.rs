@@ -0,0 +1,501 @@
+//! Pure text transformations behind the line and text commands.
+//!
+//! Everything here works on plain strings and character columns; the
+//! handlers in `input/update/transform.rs` decide which lines and ranges each
+//! cursor covers, and turn the results into one undoable edit.
+
+use std::cmp::Ordering;
+
+use crate::canvas_editor::{SortOrder, TextCase};
+
+/// Returns `lines` sorted in `order`.
+///
+/// The sort is stable, so equal lines keep their relative order.
+pub(crate) fn sort_lines(lines: &[String], order: SortOrder) -> Vec<String> {
+    let mut sorted = lines.to_vec();
+    match order {
+        SortOrder::Ascending => sorted.sort(),
+        SortOrder::Descending => sorted.sort_by(|a, b| b.cmp(a)),
+        SortOrder::Natural => sorted.sort_by(|a, b| natural_cmp(a, b)),
+    }
+    sorted
+}
+
+/// Compares two strings the way people count: runs of digits compare by
+/// their numeric value (`item2` before `item10`) and letters compare without
+/// regard to case. Strings equal under those rules fall back to a plain
+/// comparison, so the order is total.
+pub(crate) fn natural_cmp(a: &str, b: &str) -> Ordering {
+    let mut left = a.chars().peekable();
+    let mut right = b.chars().peekable();
+    loop {
+        match (left.peek().copied(), right.peek().copied()) {
+            (None, None) => return a.cmp(b),
+            (None, Some(_)) => return Ordering::Less,
+            (Some(_), None) => return Ordering::Greater,
+            (Some(l), Some(r)) if l.is_ascii_digit() && r.is_ascii_digit() => {
+                let l_digits = take_digits(&mut left);
+                let r_digits = take_digits(&mut right);
+                let l_value = l_digits.trim_start_matches('0');
+                let r_value = r_digits.trim_start_matches('0');
+                let ordering = l_value
+                    .len()
+                    .cmp(&r_value.len())
+                    .then_with(|| l_value.cmp(r_value));
+                if ordering != Ordering::Equal {
+                    return ordering;
+                }
+            }
+            (Some(l), Some(r)) => {
+                let ordering = l.to_lowercase().cmp(r.to_lowercase());
+                if ordering != Ordering::Equal {
+                    return ordering;
+                }
+                left.next();
+                right.next();
+            }
+        }
+    }
+}
+
+/// Consumes and returns the run of ASCII digits at the front of `chars`.
+fn take_digits(
+    chars: &mut std::iter::Peekable<impl Iterator<Item = char>>,
+) -> String {
+    let mut digits = String::new();
+    while let Some(ch) = chars.next_if(char::is_ascii_digit) {
+        digits.push(ch);
+    }
+    digits
+}
+
+/// Returns `lines` without repeats, keeping the first occurrence of each.
+pub(crate) fn remove_duplicate_lines(lines: &[String]) -> Vec<String> {
+    let mut seen = std::collections::HashSet::new();
+    lines.iter().filter(|line| seen.insert(line.as_str())).cloned().collect()
+}
+
+/// Joins `lines` into one, the way Vim's `J` does.
+///
+/// Trailing whitespace before each join and leading whitespace after it are
+/// dropped, and a single space separates the two sides unless one of them is
+/// empty.
+///
+/// # Returns
+///
+/// The joined line, and for each input line the column its kept text starts
+/// at in the result together with the number of leading characters dropped
+/// from it, so positions can be carried across the join
+pub(crate) fn join_lines(lines: &[String]) -> (String, Vec<(usize, usize)>) {
+    let mut joined = String::new();
+    let mut layout = Vec::with_capacity(lines.len());
+    for (index, line) in lines.iter().enumerate() {
+        if index == 0 {
+            layout.push((0, 0));
+            joined.push_str(line);
+            continue;
+        }
+        joined.truncate(joined.trim_end().len());
+        let text = line.trim_start();
+        let dropped = line.chars().count() - text.chars().count();
+        if !joined.is_empty() && !text.is_empty() {
+            joined.push(' ');
+        }
+        layout.push((joined.chars().count(), dropped));
+        joined.push_str(text);
+    }
+    (joined, layout)
+}
+
+/// Swaps the characters around `col`, moving the cursor past them.
+///
+/// At the end of a line the two characters before the cursor are swapped
+/// instead and the cursor stays put, so repeating the command toggles them.
+///
+/// # Returns
+///
+/// The new line and cursor column, or `None` when there is nothing to swap
+/// (the cursor is at the start of the line, or the line is shorter than two
+/// characters)
+pub(crate) fn transpose(line: &str, col: usize) -> Option<(String, usize)> {
+    let mut chars: Vec<char> = line.chars().collect();
+    if col == 0 || chars.len() < 2 || col > chars.len() {
+        return None;
+    }
+    if col == chars.len() {
+        chars.swap(col - 2, col - 1);
+        return Some((chars.into_iter().collect(), col));
+    }
+    chars.swap(col - 1, col);
+    Some((chars.into_iter().collect(), col + 1))
+}
+
+/// Converts `text` to `case`.
+///
+/// Upper and lower case apply to every character. Title case capitalizes the
+/// first letter of each whitespace-separated word and leaves the rest alone.
+/// The identifier cases rewrite every run of letters, digits, `_` and `-`,
+/// splitting it into words at those separators and at case changes
+/// (`parseHTMLString` → `parse`, `HTML`, `String`); other characters,
+/// including spaces and line breaks, are kept.
+pub(crate) fn convert_case(text: &str, case: TextCase) -> String {
+    match case {
+        TextCase::Upper => text.to_uppercase(),
+        TextCase::Lower => text.to_lowercase(),
+        TextCase::Title => title_case(text),
+        TextCase::Snake | TextCase::Camel | TextCase::Kebab => {
+            let mut result = String::with_capacity(text.len());
+            let mut run = String::new();
+            for ch in text.chars() {
+                if is_identifier_char(ch) {
+                    run.push(ch);
+                } else {
+                    result.push_str(&identifier_case(&run, case));
+                    run.clear();
+                    result.push(ch);
+                }
+            }
+            result.push_str(&identifier_case(&run, case));
+            result
+        }
+    }
+}
+
+/// Returns `true` for the characters the identifier cases rewrite.
+fn is_identifier_char(ch: char) -> bool {
+    ch.is_alphanumeric() || ch == '_' || ch == '-'
+}
+
+/// Capitalizes the first letter of every whitespace-separated word.
+fn title_case(text: &str) -> String {
+    let mut result = String::with_capacity(text.len());
+    let mut at_word_start = true;
+    for ch in text.chars() {
+        if at_word_start && ch.is_alphabetic() {
+            result.extend(ch.to_uppercase());
+        } else {
+            result.push(ch);
+        }
+        at_word_start = ch.is_whitespace();
+    }
+    result
+}
+
+/// Rewrites one identifier run in `case`, keeping separators at its edges
+/// (`_private` stays private).
+fn identifier_case(run: &str, case: TextCase) -> String {
+    let core = run.trim_matches(['_', '-']);
+    if core.is_empty() {
+        return run.to_string();
+    }
+    let leading = &run[..run.len() - run.trim_start_matches(['_', '-']).len()];
+    let trailing = &run[run.trim_end_matches(['_', '-']).len()..];
+    let words = split_words(core);
+    let body = match case {
+        TextCase::Snake => words
+            .iter()
+            .map(|word| word.to_lowercase())
+            .collect::<Vec<_>>()
+            .join("_"),
+        TextCase::Kebab => words
+            .iter()
+            .map(|word| word.to_lowercase())
+            .collect::<Vec<_>>()
+            .join("-"),
+        _ => words
+            .iter()
+            .enumerate()
+            .map(|(index, word)| {
+                let lower = word.to_lowercase();
+                if index == 0 {
+                    return lower;
+                }
+                let mut chars = lower.chars();
+                chars.next().map_or_else(String::new, |first| {
+                    first.to_uppercase().chain(chars).collect()
+                })
+            })
+            .collect(),
+    };
+    format!("{leading}{body}{trailing}")
+}
+
+/// Splits an identifier into words at `_`, `-` and case changes.
+fn split_words(identifier: &str) -> Vec<String> {
+    let chars: Vec<char> = identifier.chars().collect();
+    let mut words = Vec::new();
+    let mut word = String::new();
+    for (index, &ch) in chars.iter().enumerate() {
+        if ch == '_' || ch == '-' {
+            if !word.is_empty() {
+                words.push(std::mem::take(&mut word));
+            }
+            continue;
+        }
+        let prev = index.checked_sub(1).map(|i| chars[i]);
+        let next = chars.get(index + 1).copied();
+        let boundary = prev.is_some_and(|prev| {
+            ((prev.is_lowercase() || prev.is_ascii_digit())
+                && ch.is_uppercase())
+                || (prev.is_uppercase()
+                    && ch.is_uppercase()
+                    && next.is_some_and(char::is_lowercase))
+        });
+        if boundary && !word.is_empty() {
+            words.push(std::mem::take(&mut word));
+        }
+        word.push(ch);
+    }
+    if !word.is_empty() {
+        words.push(word);
+    }
+    words
+}
+
+/// Adds `delta` to the number under or after `col` on `line`.
+///
+/// The number is the one containing `col`, else the one ending right at it
+/// (where the previous increment left the cursor), else the first one after
+/// it. Numbers are decimal, where a `-` directly in front makes them
+/// negative and leading zeros keep their width (`007` → `008`), or `0x`
+/// hexadecimal and `0b` binary, which are unsigned, keep their width and
+/// the case of their prefix and digits (`0xff` → `0x100`, `0x0F` → `0x10`).
+///
+/// # Returns
+///
+/// The start and end columns of the old number and its replacement, or
+/// `None` when the line holds no number there or the result overflows
+pub(crate) fn increment_number(
+    line: &str,
+    col: usize,
+    delta: i64,
+) -> Option<(usize, usize, String)> {
+    let chars: Vec<char> = line.chars().collect();
+    let numbers = number_spans(&chars);
+    let (start, end, radix) = numbers
+        .iter()
+        .find(|(start, end, _)| *start <= col && col < *end)
+        .or_else(|| numbers.iter().find(|(_, end, _)| *end == col))
+        .or_else(|| numbers.iter().find(|(start, ..)| *start >= col))
+        .copied()?;
+
+    if radix != 10 {
+        let (prefix, digits) = chars[start..end].split_at(2);
+        let digits: String = digits.iter().collect();
+        let value = u128::from_str_radix(&digits, radix).ok()?;
+        let result = if delta < 0 {
+            value.checked_sub(u128::from(delta.unsigned_abs()))?
+        } else {
+            value.checked_add(u128::from(delta.unsigned_abs()))?
+        };
+        let width = digits.len();
+        let mut formatted = if radix == 16 {
+            format!("{result:0width$x}")
+        } else {
+            format!("{result:0width$b}")
+        };
+        if digits.chars().any(|ch| ch.is_ascii_uppercase()) {
+            formatted.make_ascii_uppercase();
+        }
+        let prefix: String = prefix.iter().collect();
+        return Some((start, end, format!("{prefix}{formatted}")));
+    }
+
+    let negative = start > 0 && chars[start - 1] == '-';
+    let digits: String = chars[start..end].iter().collect();
+    let magnitude: i128 = digits.parse().ok()?;
+    let value = if negative { -magnitude } else { magnitude };
+    let result = value.checked_add(i128::from(delta))?;
+
+    let width = if digits.len() > 1 && digits.starts_with('0') {
+        digits.len()
+    } else {
+        0
+    };
+    let sign = if result < 0 { "-" } else { "" };
+    let replacement =
+        format!("{sign}{:0width$}", result.unsigned_abs(), width = width);
+    let start = if negative { start - 1 } else { start };
+    Some((start, end, replacement))
+}
+
+/// Returns the `[start, end)` columns and radix of every number: `0x`
+/// followed by hexadecimal digits, `0b` followed by binary ones, or a run
+/// of decimal digits. A hexadecimal or binary span includes its prefix.
+fn number_spans(chars: &[char]) -> Vec<(usize, usize, u32)> {
+    let run_end = |from: usize, radix: u32| {
+        chars[from..]
+            .iter()
+            .position(|ch| !ch.is_digit(radix))
+            .map_or(chars.len(), |len| from + len)
+    };
+    let mut spans = Vec::new();
+    let mut index = 0;
+    while index < chars.len() {
+        if !chars[index].is_ascii_digit() {
+            index += 1;
+            continue;
+        }
+        let start = index;
+        let radix = match chars[start..] {
+            ['0', 'x' | 'X', digit, ..] if digit.is_ascii_hexdigit() => 16,
+            ['0', 'b' | 'B', '0' | '1', ..] => 2,
+            _ => 10,
+        };
+        index = if radix == 10 {
+            run_end(start, radix)
+        } else {
+            run_end(start + 2, radix)
+        };
+        spans.push((start, index, radix));
+    }
+    spans
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lines(text: &str) -> Vec<String> {
+        text.split('\n').map(str::to_string).collect()
+    }
+
+    #[test]
+    fn test_sort_lines_in_each_order() {
+        let input = lines("item10\nItem2\nitem1");
+
+        assert_eq!(
+            sort_lines(&input, SortOrder::Ascending),
+            lines("Item2\nitem1\nitem10")
+        );
+        assert_eq!(
+            sort_lines(&input, SortOrder::Descending),
+            lines("item10\nitem1\nItem2")
+        );
+        assert_eq!(
+            sort_lines(&input, SortOrder::Natural),
+            lines("item1\nItem2\nitem10")
+        );
+    }
+
+    #[test]
+    fn test_natural_cmp_compares_numbers_by_value() {
+        assert_eq!(natural_cmp("v1.9", "v1.10"), Ordering::Less);
+        assert_eq!(natural_cmp("a007", "a7"), Ordering::Less);
+        assert_eq!(natural_cmp("b", "A"), Ordering::Greater);
+        assert_eq!(natural_cmp("x", "x"), Ordering::Equal);
+    }
+
+    #[test]
+    fn test_remove_duplicate_lines_keeps_the_first_occurrence() {
+        assert_eq!(
+            remove_duplicate_lines(&lines("b\na\nb\n\na\n")),
+            lines("b\na\n")
+        );
+    }
+
+    #[test]
+    fn test_join_lines_trims_the_seams() {
+        let (joined, layout) =
+            join_lines(&lines("fn main() {  \n    run();\n\n}"));
+
+        assert_eq!(joined, "fn main() { run(); }");
+        assert_eq!(layout, vec![(0, 0), (12, 4), (18, 0), (19, 0)]);
+    }
+
+    #[test]
+    fn test_transpose_swaps_around_the_cursor() {
+        assert_eq!(transpose("abc", 1), Some(("bac".to_string(), 2)));
+        assert_eq!(transpose("abc", 3), Some(("acb".to_string(), 3)));
+        assert_eq!(transpose("abc", 0), None);
+        assert_eq!(transpose("a", 1), None);
+    }
+
+    #[test]
+    fn test_convert_case_identifier_cases() {
+        let text = "parseHTMLString(user_id, max-width)";
+
+        assert_eq!(
+            convert_case(text, TextCase::Snake),
+            "parse_html_string(user_id, max_width)"
+        );
+        assert_eq!(
+            convert_case(text, TextCase::Camel),
+            "parseHtmlString(userId, maxWidth)"
+        );
+        assert_eq!(
+            convert_case(text, TextCase::Kebab),
+            "parse-html-string(user-id, max-width)"
+        );
+        assert_eq!(
+            convert_case("_private_name", TextCase::Camel),
+            "_privateName"
+        );
+    }
+
+    #[test]
+    fn test_convert_case_letter_cases() {
+        assert_eq!(convert_case("Grüße", TextCase::Upper), "GRÜSSE");
+        assert_eq!(convert_case("ÉCOLE", TextCase::Lower), "école");
+        assert_eq!(
+            convert_case("hello wORLD\nagain", TextCase::Title),
+            "Hello WORLD\nAgain"
+        );
+    }
+
+    #[test]
+    fn test_increment_number_finds_the_number() {
+        assert_eq!(
+            increment_number("x = 41;", 0, 1),
+            Some((4, 6, "42".to_string()))
+        );
+        assert_eq!(
+            increment_number("x = 41;", 6, 1),
+            Some((4, 6, "42".to_string()))
+        );
+        assert_eq!(
+            increment_number("-1 apples", 1, 3),
+            Some((0, 2, "2".to_string()))
+        );
+        assert_eq!(
+            increment_number("id 007", 0, 1),
+            Some((3, 6, "008".to_string()))
+        );
+        assert_eq!(
+            increment_number("n = 0", 4, -1),
+            Some((4, 5, "-1".to_string()))
+        );
+        assert_eq!(
+            increment_number("a1 b2", 2, 1),
+            Some((1, 2, "2".to_string()))
+        );
+        assert_eq!(increment_number("none", 0, 1), None);
+    }
+
+    #[test]
+    fn test_increment_number_reads_hex_and_binary() {
+        assert_eq!(
+            increment_number("x = 0xff;", 0, 1),
+            Some((4, 8, "0x100".to_string()))
+        );
+        // The cursor on the prefix still changes the whole number.
+        assert_eq!(
+            increment_number("0x0F", 1, 1),
+            Some((0, 4, "0x10".to_string()))
+        );
+        assert_eq!(
+            increment_number("0x00ff", 5, -0xff),
+            Some((0, 6, "0x0000".to_string()))
+        );
+        assert_eq!(
+            increment_number("mask 0b0111", 0, 1),
+            Some((5, 11, "0b1000".to_string()))
+        );
+        assert_eq!(increment_number("0x0", 0, -1), None);
+        // Without digits after it, the prefix is not one.
+        assert_eq!(
+            increment_number("0xg", 0, 1),
+            Some((0, 1, "1".to_string()))
+        );
+    }
+}
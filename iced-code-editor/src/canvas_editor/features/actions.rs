@@ -87,6 +87,11 @@ pub(crate) const PASTE_SHORTCUT: &str = "⌘V";
 #[cfg(not(target_os = "macos"))]
 pub(crate) const PASTE_SHORTCUT: &str = "Ctrl+V";
 
+#[cfg(target_os = "macos")]
+pub(crate) const PASTE_FROM_HISTORY_SHORTCUT: &str = "⇧⌘V";
+#[cfg(not(target_os = "macos"))]
+pub(crate) const PASTE_FROM_HISTORY_SHORTCUT: &str = "Ctrl+Shift+V";
+
 #[cfg(target_os = "macos")]
 pub(crate) const SELECT_ALL_SHORTCUT: &str = "⌘A";
 #[cfg(not(target_os = "macos"))]
@@ -112,11 +117,26 @@ pub(crate) const GOTO_LINE_SHORTCUT: &str = "⌘G";
 #[cfg(not(target_os = "macos"))]
 pub(crate) const GOTO_LINE_SHORTCUT: &str = "Ctrl+G";
 
+#[cfg(target_os = "macos")]
+pub(crate) const NAVIGATE_BACK_SHORTCUT: &str = "⌥←";
+#[cfg(not(target_os = "macos"))]
+pub(crate) const NAVIGATE_BACK_SHORTCUT: &str = "Alt+←";
+
+#[cfg(target_os = "macos")]
+pub(crate) const NAVIGATE_FORWARD_SHORTCUT: &str = "⌥→";
+#[cfg(not(target_os = "macos"))]
+pub(crate) const NAVIGATE_FORWARD_SHORTCUT: &str = "Alt+→";
+
 #[cfg(target_os = "macos")]
 pub(crate) const TOGGLE_COMMENT_SHORTCUT: &str = "⌘/";
 #[cfg(not(target_os = "macos"))]
 pub(crate) const TOGGLE_COMMENT_SHORTCUT: &str = "Ctrl+/";
 
+#[cfg(target_os = "macos")]
+pub(crate) const TOGGLE_BLOCK_COMMENT_SHORTCUT: &str = "⇧⌥A";
+#[cfg(not(target_os = "macos"))]
+pub(crate) const TOGGLE_BLOCK_COMMENT_SHORTCUT: &str = "Shift+Alt+A";
+
 #[cfg(target_os = "macos")]
 pub(crate) const MOVE_LINE_UP_SHORTCUT: &str = "⌥↑";
 #[cfg(not(target_os = "macos"))]
@@ -169,6 +189,24 @@ pub(crate) const SELECT_NEXT_OCCURRENCE_SHORTCUT: &str = "⌘D";
 #[cfg(not(target_os = "macos"))]
 pub(crate) const SELECT_NEXT_OCCURRENCE_SHORTCUT: &str = "Ctrl+D";
 
+// Function keys carry no platform modifier, so only Shift differs in spelling.
+pub(crate) const NEXT_HIGHLIGHT_SHORTCUT: &str = "F7";
+
+#[cfg(target_os = "macos")]
+pub(crate) const PREVIOUS_HIGHLIGHT_SHORTCUT: &str = "⇧F7";
+#[cfg(not(target_os = "macos"))]
+pub(crate) const PREVIOUS_HIGHLIGHT_SHORTCUT: &str = "Shift+F7";
+
+#[cfg(target_os = "macos")]
+pub(crate) const EXPAND_SELECTION_SHORTCUT: &str = "⇧⌥→";
+#[cfg(not(target_os = "macos"))]
+pub(crate) const EXPAND_SELECTION_SHORTCUT: &str = "Shift+Alt+→";
+
+#[cfg(target_os = "macos")]
+pub(crate) const SHRINK_SELECTION_SHORTCUT: &str = "⇧⌥←";
+#[cfg(not(target_os = "macos"))]
+pub(crate) const SHRINK_SELECTION_SHORTCUT: &str = "Shift+Alt+←";
+
 #[cfg(target_os = "macos")]
 pub(crate) const TOGGLE_VIM_MODE_SHORTCUT: &str = "⌥⌘V";
 #[cfg(not(target_os = "macos"))]
@@ -204,6 +204,69 @@ pub(crate) fn find_matching_pair(
     Some((target_pos, match_pos))
 }
 
+/// Finds the innermost bracket pair that encloses the range `start..end`.
+///
+/// Scans backward from `start` for an opening bracket that is not closed
+/// before `start`, and accepts it when its match sits at or after `end`.
+/// Like the other scans in this module it is purely textual and gives up
+/// after [`MAX_SCAN_LINES`] lines.
+///
+/// Returns `Some((open_pos, close_pos))`, or `None` when no pair encloses
+/// the range.
+///
+/// # Examples
+///
+/// ```text
+/// // buffer contains "f(a, [b])"
+/// let pair = enclosing_pair(&buffer, (0, 6), (0, 7)); // `b` selected
+/// assert_eq!(pair, Some(((0, 5), (0, 7))));
+/// ```
+pub(crate) fn enclosing_pair(
+    buffer: &TextBuffer,
+    start: (usize, usize),
+    end: (usize, usize),
+) -> Option<((usize, usize), (usize, usize))> {
+    // Closing brackets seen so far, per family, still waiting for their
+    // opener further back.
+    let mut unclosed = [0usize; 3];
+    let family = |ch: char| match ch {
+        '(' | ')' => 0,
+        '[' | ']' => 1,
+        _ => 2,
+    };
+    let mut l = start.0;
+    let scan_floor = start.0.saturating_sub(MAX_SCAN_LINES);
+
+    loop {
+        let text = buffer.line(l);
+        let end_col = if l == start.0 { start.1 } else { text.chars().count() };
+        let chars: Vec<char> = text.chars().take(end_col).collect();
+
+        for (c, &ch) in chars.iter().enumerate().rev() {
+            if is_closing_bracket(ch) {
+                unclosed[family(ch)] += 1;
+            } else if is_opening_bracket(ch) {
+                let slot = &mut unclosed[family(ch)];
+                if *slot > 0 {
+                    *slot -= 1;
+                    continue;
+                }
+                let close = bracket_pair(ch)?;
+                if let Some(close_pos) = scan_forward(buffer, ch, close, l, c)
+                    && close_pos >= end
+                {
+                    return Some(((l, c), close_pos));
+                }
+            }
+        }
+
+        if l == scan_floor {
+            return None;
+        }
+        l -= 1;
+    }
+}
+
 /// Returns the nesting depth after scanning all of `line`, starting from
 /// `start_depth`.
 ///
@@ -277,6 +340,30 @@ mod tests {
         TextBuffer::new(&lines.join("\n"))
     }
 
+    #[test]
+    fn enclosing_pair_finds_innermost_pair_around_range() {
+        let buffer = buffer_from(&["f(a, [b])"]);
+        assert_eq!(
+            enclosing_pair(&buffer, (0, 6), (0, 7)),
+            Some(((0, 5), (0, 7)))
+        );
+        // The whole `[b]` is enclosed by the parentheses.
+        assert_eq!(
+            enclosing_pair(&buffer, (0, 5), (0, 8)),
+            Some(((0, 1), (0, 8)))
+        );
+    }
+
+    #[test]
+    fn enclosing_pair_skips_pairs_closed_before_the_range() {
+        let buffer = buffer_from(&["{", "  g(x);", "  y", "}"]);
+        assert_eq!(
+            enclosing_pair(&buffer, (2, 2), (2, 3)),
+            Some(((0, 0), (3, 0)))
+        );
+        assert_eq!(enclosing_pair(&buffer, (0, 0), (3, 1)), None);
+    }
+
     #[test]
     fn cursor_before_opening_bracket_matches_closing() {
         let buffer = buffer_from(&["foo(bar)"]);
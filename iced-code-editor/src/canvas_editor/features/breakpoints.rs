@@ -0,0 +1,507 @@
+//! Breakpoints and the current execution line.
+//!
+//! With [`CodeEditor::set_breakpoints_enabled`] a breakpoint margin appears at
+//! the left edge of the gutter: clicking it, or pressing `F9`, toggles a
+//! breakpoint on that line. Breakpoints may carry a condition or a log message
+//! (a logpoint), set through [`CodeEditor::set_breakpoint`]. They follow the
+//! text as lines are inserted or removed above them, and every change bumps
+//! [`CodeEditor::breakpoints_revision`] so the host knows when to send the
+//! debug adapter a fresh `setBreakpoints`.
+//!
+//! The editor does not talk to a debugger itself: the host owns the session
+//! (see the `dap-process` feature) and marks where execution stopped with
+//! [`CodeEditor::set_execution_line`].
+
+use std::collections::BTreeMap;
+
+use iced::Point;
+use iced::widget::canvas::{self, Path, Stroke};
+
+use crate::canvas_editor::CodeEditor;
+use crate::canvas_editor::metrics::BREAKPOINT_MARGIN_WIDTH;
+
+/// A breakpoint set on one line of the buffer.
+///
+/// # Examples
+///
+/// ```
+/// use iced_code_editor::{Breakpoint, CodeEditor};
+///
+/// let mut editor = CodeEditor::new("let mut i = 0;\ni += 1;\n", "rs");
+/// editor.set_breakpoint(1, Breakpoint::conditional("i > 10"));
+///
+/// let breakpoint = editor.breakpoint(1);
+/// assert_eq!(
+///     breakpoint.and_then(|b| b.condition.as_deref()),
+///     Some("i > 10")
+/// );
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Breakpoint {
+    /// Expression that must hold for execution to stop.
+    pub condition: Option<String>,
+    /// Message logged instead of stopping; `{expression}` parts are
+    /// interpolated by the debug adapter.
+    pub log_message: Option<String>,
+    /// Whether the debug adapter accepted the breakpoint. Unverified
+    /// breakpoints are drawn hollow.
+    pub verified: bool,
+}
+
+impl Breakpoint {
+    /// Creates a plain breakpoint.
+    pub fn new() -> Self {
+        Self { condition: None, log_message: None, verified: true }
+    }
+
+    /// Creates a breakpoint that stops only when `condition` holds.
+    pub fn conditional(condition: impl Into<String>) -> Self {
+        Self { condition: Some(condition.into()), ..Self::new() }
+    }
+
+    /// Creates a logpoint, which logs `message` instead of stopping.
+    pub fn logpoint(message: impl Into<String>) -> Self {
+        Self { log_message: Some(message.into()), ..Self::new() }
+    }
+}
+
+impl Default for Breakpoint {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Breakpoint state owned by the editor.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct BreakpointState {
+    /// Breakpoints by logical line.
+    pub(crate) breakpoints: BTreeMap<usize, Breakpoint>,
+    /// Logical line where execution is stopped, if any.
+    pub(crate) execution_line: Option<usize>,
+    /// Bumped whenever the set of breakpoints changes.
+    pub(crate) revision: u64,
+    /// Buffer line count the positions above were last reconciled with.
+    line_count: usize,
+}
+
+/// Returns where `line` lands after an edit on `edit_line` changed the line
+/// count from `old_count` to `new_count`.
+///
+/// Lines below the edited one shift by the change in line count; lines that
+/// were removed land on `edit_line`, the line they were merged into.
+pub(crate) fn shift_line_after_edit(
+    line: usize,
+    edit_line: usize,
+    old_count: usize,
+    new_count: usize,
+) -> usize {
+    if line <= edit_line {
+        line
+    } else if new_count > old_count {
+        line + (new_count - old_count)
+    } else {
+        let removed = old_count - new_count;
+        if line <= edit_line + removed { edit_line } else { line - removed }
+    }
+}
+
+impl CodeEditor {
+    /// Returns the breakpoint on `line`, if any.
+    ///
+    /// # Arguments
+    ///
+    /// * `line` - Logical line index (zero-based)
+    pub fn breakpoint(&self, line: usize) -> Option<&Breakpoint> {
+        self.breakpoint_state.breakpoints.get(&line)
+    }
+
+    /// Returns every breakpoint with its logical line, in line order.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use iced_code_editor::CodeEditor;
+    ///
+    /// let mut editor = CodeEditor::new("a\nb\nc", "txt");
+    /// editor.toggle_breakpoint(2);
+    /// editor.toggle_breakpoint(0);
+    ///
+    /// let lines: Vec<usize> =
+    ///     editor.breakpoints().map(|(line, _)| line).collect();
+    /// assert_eq!(lines, [0, 2]);
+    /// ```
+    pub fn breakpoints(&self) -> impl Iterator<Item = (usize, &Breakpoint)> {
+        self.breakpoint_state
+            .breakpoints
+            .iter()
+            .map(|(line, breakpoint)| (*line, breakpoint))
+    }
+
+    /// Returns a counter that changes whenever the breakpoints change.
+    ///
+    /// Compare it with the value seen when breakpoints were last sent to the
+    /// debug adapter to know when to send them again. Marking breakpoints
+    /// verified does not change it.
+    pub fn breakpoints_revision(&self) -> u64 {
+        self.breakpoint_state.revision
+    }
+
+    /// Adds a plain breakpoint on `line`, or removes the one already there.
+    ///
+    /// Lines past the end of the buffer are ignored.
+    ///
+    /// # Arguments
+    ///
+    /// * `line` - Logical line index (zero-based)
+    pub fn toggle_breakpoint(&mut self, line: usize) {
+        if self.breakpoint_state.breakpoints.contains_key(&line) {
+            self.remove_breakpoint(line);
+        } else {
+            self.set_breakpoint(line, Breakpoint::new());
+        }
+    }
+
+    /// Sets the breakpoint on `line`, replacing any already there.
+    ///
+    /// Lines past the end of the buffer are ignored.
+    ///
+    /// # Arguments
+    ///
+    /// * `line` - Logical line index (zero-based)
+    /// * `breakpoint` - The breakpoint to set
+    pub fn set_breakpoint(&mut self, line: usize, breakpoint: Breakpoint) {
+        if line >= self.buffer.line_count() {
+            return;
+        }
+        self.breakpoint_state.line_count = self.buffer.line_count();
+        self.breakpoint_state.breakpoints.insert(line, breakpoint);
+        self.breakpoints_changed();
+    }
+
+    /// Removes the breakpoint on `line`, returning it.
+    ///
+    /// # Arguments
+    ///
+    /// * `line` - Logical line index (zero-based)
+    pub fn remove_breakpoint(&mut self, line: usize) -> Option<Breakpoint> {
+        let removed = self.breakpoint_state.breakpoints.remove(&line);
+        if removed.is_some() {
+            self.breakpoints_changed();
+        }
+        removed
+    }
+
+    /// Removes every breakpoint.
+    pub fn clear_breakpoints(&mut self) {
+        if !self.breakpoint_state.breakpoints.is_empty() {
+            self.breakpoint_state.breakpoints.clear();
+            self.breakpoints_changed();
+        }
+    }
+
+    /// Records whether the debug adapter accepted the breakpoint on `line`.
+    ///
+    /// Feed this the `verified` flags of a `setBreakpoints` response. It
+    /// changes only how the breakpoint is drawn, not
+    /// [`Self::breakpoints_revision`].
+    ///
+    /// # Arguments
+    ///
+    /// * `line` - Logical line index (zero-based)
+    /// * `verified` - Whether the adapter accepted the breakpoint
+    pub fn set_breakpoint_verified(&mut self, line: usize, verified: bool) {
+        if let Some(breakpoint) =
+            self.breakpoint_state.breakpoints.get_mut(&line)
+            && breakpoint.verified != verified
+        {
+            breakpoint.verified = verified;
+            self.content_cache.clear();
+        }
+    }
+
+    /// Marks `line` as the one where execution is stopped, or clears the
+    /// mark with `None`.
+    ///
+    /// The line is highlighted and an arrow is drawn in the breakpoint
+    /// margin. Call it with the top stack frame's line when the debuggee
+    /// stops, and with `None` when it continues or exits.
+    ///
+    /// # Arguments
+    ///
+    /// * `line` - Logical line index (zero-based), or `None`
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use iced_code_editor::CodeEditor;
+    ///
+    /// let mut editor = CodeEditor::new("fn main() {\n    run();\n}", "rs");
+    /// editor.set_execution_line(Some(1));
+    /// assert_eq!(editor.execution_line(), Some(1));
+    /// ```
+    pub fn set_execution_line(&mut self, line: Option<usize>) {
+        let line = line.filter(|line| *line < self.buffer.line_count());
+        if self.breakpoint_state.execution_line != line {
+            self.breakpoint_state.line_count = self.buffer.line_count();
+            self.breakpoint_state.execution_line = line;
+            self.content_cache.clear();
+            self.overlay_cache.clear();
+        }
+    }
+
+    /// Returns the line marked by [`Self::set_execution_line`].
+    pub fn execution_line(&self) -> Option<usize> {
+        self.breakpoint_state.execution_line
+    }
+
+    /// Toggles the breakpoint on the primary cursor's line, for `F9`.
+    pub(crate) fn toggle_breakpoint_at_cursor(&mut self) {
+        if self.breakpoints_enabled {
+            self.toggle_breakpoint(self.cursors.primary_position().0);
+        }
+    }
+
+    /// Bumps the revision and redraws the gutter.
+    fn breakpoints_changed(&mut self) {
+        let state = &mut self.breakpoint_state;
+        state.revision = state.revision.wrapping_add(1);
+        self.content_cache.clear();
+    }
+
+    /// Moves breakpoints and the execution line with the lines an edit
+    /// inserted or removed.
+    ///
+    /// Lines below the edited one shift by the change in line count. When
+    /// lines are removed, a breakpoint on one of them lands on the line they
+    /// were merged into, unless that line already has its own.
+    pub(crate) fn shift_breakpoints_after_edit(&mut self) {
+        let line_count = self.buffer.line_count();
+        let state = &mut self.breakpoint_state;
+        let old_count = std::mem::replace(&mut state.line_count, line_count);
+        if old_count == line_count
+            || (state.breakpoints.is_empty() && state.execution_line.is_none())
+        {
+            return;
+        }
+
+        let edit_line = self.pre_edit_line;
+        let shift = |line| {
+            shift_line_after_edit(line, edit_line, old_count, line_count)
+        };
+
+        let mut shifted = BTreeMap::new();
+        for (line, breakpoint) in std::mem::take(&mut state.breakpoints) {
+            let line = shift(line);
+            if line < line_count {
+                shifted.entry(line).or_insert(breakpoint);
+            }
+        }
+        let moved = shifted.keys().ne(state.breakpoints.keys());
+        state.breakpoints = shifted;
+        state.execution_line =
+            state.execution_line.map(shift).filter(|line| *line < line_count);
+        if moved {
+            state.revision = state.revision.wrapping_add(1);
+        }
+    }
+
+    /// Returns the logical line whose breakpoint margin is at `point`, if
+    /// any.
+    ///
+    /// Returns `None` when breakpoints are disabled or the point is outside
+    /// the margin or below the last line.
+    ///
+    /// # Arguments
+    ///
+    /// * `point` - The click position in canvas coordinates
+    pub(crate) fn breakpoint_line_at_point(
+        &self,
+        point: Point,
+    ) -> Option<usize> {
+        if !self.breakpoints_enabled || point.x >= BREAKPOINT_MARGIN_WIDTH {
+            return None;
+        }
+
+        let visual_line_idx = (point.y / self.line_height) as usize;
+        let visual_lines = self.visual_lines_cached(self.viewport_width);
+        visual_lines
+            .get(visual_line_idx)
+            .map(|visual_line| visual_line.logical_line)
+    }
+
+    /// Draws the breakpoint and execution-arrow markers of `line` in the
+    /// breakpoint margin.
+    ///
+    /// A plain breakpoint is a filled dot, a conditional one carries a bar,
+    /// and a logpoint is a diamond; an unverified one is drawn as an outline.
+    ///
+    /// # Arguments
+    ///
+    /// * `frame` - The canvas frame to draw on
+    /// * `line` - Logical line of the visual line's first segment
+    /// * `y` - Y position of the visual line
+    pub(crate) fn draw_breakpoint_marker(
+        &self,
+        frame: &mut canvas::Frame,
+        line: usize,
+        y: f32,
+    ) {
+        let center = Point::new(
+            BREAKPOINT_MARGIN_WIDTH / 2.0,
+            y + self.line_height / 2.0,
+        );
+        let radius = (BREAKPOINT_MARGIN_WIDTH / 2.0 - 2.5)
+            .min(self.line_height / 2.0 - 2.0)
+            .max(1.0);
+        let color = self.style.breakpoint_color;
+
+        if let Some(breakpoint) = self.breakpoint_state.breakpoints.get(&line) {
+            let shape = if breakpoint.log_message.is_some() {
+                Path::new(|builder| {
+                    builder.move_to(Point::new(center.x, center.y - radius));
+                    builder.line_to(Point::new(center.x + radius, center.y));
+                    builder.line_to(Point::new(center.x, center.y + radius));
+                    builder.line_to(Point::new(center.x - radius, center.y));
+                    builder.close();
+                })
+            } else {
+                Path::circle(center, radius)
+            };
+
+            if breakpoint.verified {
+                frame.fill(&shape, color);
+            } else {
+                frame.stroke(
+                    &shape,
+                    Stroke::default().with_color(color).with_width(1.5),
+                );
+            }
+
+            if breakpoint.condition.is_some()
+                && breakpoint.log_message.is_none()
+            {
+                let bar = Path::line(
+                    Point::new(center.x - radius / 2.0, center.y),
+                    Point::new(center.x + radius / 2.0, center.y),
+                );
+                frame.stroke(
+                    &bar,
+                    Stroke::default()
+                        .with_color(self.style.gutter_background)
+                        .with_width(1.5),
+                );
+            }
+        }
+
+        if self.breakpoint_state.execution_line == Some(line) {
+            let arrow = Path::new(|builder| {
+                builder
+                    .move_to(Point::new(center.x - radius, center.y - radius));
+                builder.line_to(Point::new(center.x + radius, center.y));
+                builder
+                    .line_to(Point::new(center.x - radius, center.y + radius));
+                builder.close();
+            });
+            frame.fill(&arrow, self.style.execution_arrow_color);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use iced::Point;
+
+    use super::Breakpoint;
+    use crate::canvas_editor::metrics::BREAKPOINT_MARGIN_WIDTH;
+    use crate::canvas_editor::{CodeEditor, Message};
+
+    /// Returns the lines that carry a breakpoint.
+    fn breakpoint_lines(editor: &CodeEditor) -> Vec<usize> {
+        editor.breakpoints().map(|(line, _)| line).collect()
+    }
+
+    #[test]
+    fn test_toggling_adds_and_removes_and_bumps_the_revision() {
+        let mut editor = CodeEditor::new("a\nb\nc", "txt");
+        let revision = editor.breakpoints_revision();
+
+        editor.toggle_breakpoint(1);
+        assert_eq!(breakpoint_lines(&editor), [1]);
+        assert_ne!(editor.breakpoints_revision(), revision);
+
+        editor.toggle_breakpoint(1);
+        assert!(breakpoint_lines(&editor).is_empty());
+
+        // Past the end of the buffer: nothing to break on.
+        editor.toggle_breakpoint(3);
+        assert!(breakpoint_lines(&editor).is_empty());
+    }
+
+    #[test]
+    fn test_verifying_does_not_bump_the_revision() {
+        let mut editor = CodeEditor::new("a\nb", "txt");
+        editor.set_breakpoint(0, Breakpoint::logpoint("x = {x}"));
+        let revision = editor.breakpoints_revision();
+
+        editor.set_breakpoint_verified(0, false);
+
+        assert_eq!(editor.breakpoints_revision(), revision);
+        assert_eq!(editor.breakpoint(0).map(|b| b.verified), Some(false));
+    }
+
+    #[test]
+    fn test_breakpoints_follow_inserted_and_removed_lines() {
+        let mut editor = CodeEditor::new("a\nb\nc\nd", "txt");
+        editor.toggle_breakpoint(0);
+        editor.toggle_breakpoint(2);
+        editor.set_execution_line(Some(3));
+
+        // Two lines inserted after line 0.
+        editor.cursors.primary_mut().position = (0, 1);
+        let _ = editor.update(&Message::Paste("\nx\ny".to_string()));
+        assert_eq!(breakpoint_lines(&editor), [0, 4]);
+        assert_eq!(editor.execution_line(), Some(5));
+
+        // Lines 1..=4 removed by deleting from the end of "a" to the start
+        // of "c": the breakpoint on "c" lands on line 0, which already has
+        // one.
+        editor.cursors.primary_mut().anchor = Some((0, 1));
+        editor.cursors.primary_mut().position = (4, 0);
+        let revision = editor.breakpoints_revision();
+        let _ = editor.update(&Message::Delete);
+        assert_eq!(editor.content(), "ac\nd");
+        assert_eq!(breakpoint_lines(&editor), [0]);
+        assert_eq!(editor.execution_line(), Some(1));
+        assert_ne!(editor.breakpoints_revision(), revision);
+    }
+
+    #[test]
+    fn test_breakpoint_margin_is_only_hit_when_enabled() {
+        let mut editor = CodeEditor::new("a\nb", "txt");
+        let point = Point::new(BREAKPOINT_MARGIN_WIDTH / 2.0, 1.5 * 20.0);
+        assert_eq!(editor.breakpoint_line_at_point(point), None);
+
+        editor.set_breakpoints_enabled(true);
+        assert_eq!(editor.breakpoint_line_at_point(point), Some(1));
+        assert_eq!(
+            editor.breakpoint_line_at_point(Point::new(
+                BREAKPOINT_MARGIN_WIDTH + 1.0,
+                point.y
+            )),
+            None
+        );
+    }
+
+    #[test]
+    fn test_f9_toggles_the_breakpoint_on_the_cursor_line() {
+        let mut editor =
+            CodeEditor::new("a\nb", "txt").with_breakpoints_enabled(true);
+        editor.cursors.primary_mut().position = (1, 0);
+
+        let _ = editor.update(&Message::ToggleBreakpointAtCursor);
+        assert_eq!(breakpoint_lines(&editor), [1]);
+
+        let _ = editor.update(&Message::ToggleBreakpoint(0));
+        assert_eq!(breakpoint_lines(&editor), [0, 1]);
+    }
+}
@@ -0,0 +1,208 @@
+//! Clipboard history: the recent copies and cuts of one editor.
+//!
+//! Every [`Message::Copy`](crate::Message::Copy) and
+//! [`Message::Cut`](crate::Message::Cut) records the text it wrote to the
+//! system clipboard here, newest first. `Ctrl/Cmd+Shift+V` lists the ring in
+//! the command palette; picking an entry pastes it through the regular paste
+//! path, so multi-cursor distribution and reindent-on-paste apply as usual.
+//!
+//! The ring belongs to the editor rather than the system clipboard: it only
+//! sees what was copied from this editor, and it never reads the clipboard.
+
+use crate::canvas_editor::CodeEditor;
+
+/// Number of entries kept by a new editor.
+pub(crate) const DEFAULT_CLIPBOARD_HISTORY_LIMIT: usize = 20;
+
+/// Longest palette label built from an entry, in characters.
+const PREVIEW_CHARS: usize = 72;
+
+/// Recent clipboard texts, newest first, at most `limit` of them.
+#[derive(Debug, Clone)]
+pub(crate) struct ClipboardHistory {
+    entries: Vec<String>,
+    limit: usize,
+}
+
+impl Default for ClipboardHistory {
+    fn default() -> Self {
+        Self { entries: Vec::new(), limit: DEFAULT_CLIPBOARD_HISTORY_LIMIT }
+    }
+}
+
+impl ClipboardHistory {
+    /// Records `text` as the newest entry.
+    ///
+    /// Copying the same text twice moves it back to the front instead of
+    /// listing it twice. Empty text is ignored: pasting it would ask the
+    /// editor to read the system clipboard instead.
+    pub(crate) fn push(&mut self, text: &str) {
+        if text.is_empty() || self.limit == 0 {
+            return;
+        }
+        self.entries.retain(|entry| entry != text);
+        self.entries.insert(0, text.to_string());
+        self.entries.truncate(self.limit);
+    }
+
+    /// Returns the entries, newest first.
+    pub(crate) fn entries(&self) -> &[String] {
+        &self.entries
+    }
+
+    /// Forgets every entry.
+    pub(crate) fn clear(&mut self) {
+        self.entries.clear();
+    }
+
+    /// Returns the maximum number of entries kept.
+    pub(crate) fn limit(&self) -> usize {
+        self.limit
+    }
+
+    /// Changes the maximum number of entries, dropping the oldest ones that
+    /// no longer fit.
+    pub(crate) fn set_limit(&mut self, limit: usize) {
+        self.limit = limit;
+        self.entries.truncate(limit);
+    }
+}
+
+/// Builds the one-line palette label for an entry.
+///
+/// Line breaks become `⏎` and runs of indentation collapse to one space, so a
+/// multi-line block reads as a single row; long entries are cut at
+/// [`PREVIEW_CHARS`] with an ellipsis.
+pub(crate) fn preview(text: &str) -> String {
+    let joined = text
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .collect::<Vec<_>>()
+        .join(" ⏎ ");
+    if joined.chars().count() <= PREVIEW_CHARS {
+        return joined;
+    }
+    let mut cut: String = joined.chars().take(PREVIEW_CHARS - 1).collect();
+    cut.push('…');
+    cut
+}
+
+impl CodeEditor {
+    /// Returns the recent copies and cuts, newest first.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use iced_code_editor::{CodeEditor, Message};
+    ///
+    /// let mut editor = CodeEditor::new("alpha beta", "txt");
+    /// let _ = editor.update(&Message::SelectAll);
+    /// let _ = editor.update(&Message::Copy);
+    ///
+    /// assert_eq!(editor.clipboard_history(), ["alpha beta"]);
+    /// ```
+    pub fn clipboard_history(&self) -> &[String] {
+        self.clipboard_history.entries()
+    }
+
+    /// Forgets every recorded copy and cut.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use iced_code_editor::{CodeEditor, Message};
+    ///
+    /// let mut editor = CodeEditor::new("alpha", "txt");
+    /// let _ = editor.update(&Message::SelectAll);
+    /// let _ = editor.update(&Message::Copy);
+    /// editor.clear_clipboard_history();
+    ///
+    /// assert!(editor.clipboard_history().is_empty());
+    /// ```
+    pub fn clear_clipboard_history(&mut self) {
+        self.clipboard_history.clear();
+    }
+
+    /// Sets how many copies and cuts the clipboard history keeps.
+    ///
+    /// Lowering the limit drops the oldest entries; `0` turns recording off.
+    ///
+    /// # Arguments
+    ///
+    /// * `limit` - Maximum number of entries (default 20)
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use iced_code_editor::CodeEditor;
+    ///
+    /// let mut editor = CodeEditor::new("", "txt");
+    /// editor.set_clipboard_history_limit(5);
+    /// assert_eq!(editor.clipboard_history_limit(), 5);
+    /// ```
+    pub fn set_clipboard_history_limit(&mut self, limit: usize) {
+        self.clipboard_history.set_limit(limit);
+    }
+
+    /// Returns how many copies and cuts the clipboard history keeps.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use iced_code_editor::CodeEditor;
+    ///
+    /// let editor = CodeEditor::new("", "txt");
+    /// assert_eq!(editor.clipboard_history_limit(), 20);
+    /// ```
+    pub fn clipboard_history_limit(&self) -> usize {
+        self.clipboard_history.limit()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_push_keeps_newest_first_without_duplicates() {
+        let mut history = ClipboardHistory::default();
+        history.push("one");
+        history.push("two");
+        history.push("one");
+        history.push("");
+
+        assert_eq!(history.entries(), ["one", "two"]);
+    }
+
+    #[test]
+    fn test_limit_drops_the_oldest_entries() {
+        let mut history = ClipboardHistory::default();
+        for text in ["a", "b", "c"] {
+            history.push(text);
+        }
+
+        history.set_limit(2);
+        assert_eq!(history.entries(), ["c", "b"]);
+
+        history.push("d");
+        assert_eq!(history.entries(), ["d", "c"]);
+
+        history.set_limit(0);
+        history.push("e");
+        assert!(history.entries().is_empty());
+    }
+
+    #[test]
+    fn test_preview_folds_lines_and_truncates() {
+        assert_eq!(
+            preview("fn main() {\n    run();\n}\n"),
+            "fn main() { ⏎ run(); ⏎ }"
+        );
+
+        let long = "x".repeat(PREVIEW_CHARS + 10);
+        let label = preview(&long);
+        assert_eq!(label.chars().count(), PREVIEW_CHARS);
+        assert!(label.ends_with('…'));
+    }
+}
@@ -0,0 +1,391 @@
+//! Color picker UI: a saturation/value square, hue and alpha bars, a hex
+//! input, and the language server's presentations.
+
+use iced::mouse;
+use iced::widget::canvas::{self, Canvas, Path, Stroke, gradient};
+use iced::widget::{
+    Column, Space, Stack, button, column, container, mouse_area, row, text,
+    text_input,
+};
+use iced::{
+    Color, Element, Event, Length, Point, Rectangle, Renderer, Size, Theme,
+    keyboard,
+};
+use iced_font_awesome::fa_icon_solid;
+
+use super::{ColorPickerState, hsv_to_color};
+use crate::canvas_editor::Message;
+
+/// Width in pixels of the picker, padding included.
+pub(crate) const PICKER_WIDTH: f32 = 216.0;
+
+/// Height in pixels of the picker without presentations, padding included;
+/// used to decide whether it fits below the swatch.
+pub(crate) const PICKER_HEIGHT: f32 = 228.0;
+
+/// Width in pixels of the color controls.
+const AREA_WIDTH: f32 = 200.0;
+
+/// Height in pixels of the saturation/value square.
+const SQUARE_HEIGHT: f32 = 120.0;
+
+/// Height in pixels of the hue and alpha bars.
+const BAR_HEIGHT: f32 = 12.0;
+
+/// Side in pixels of one checkerboard cell behind translucent colors.
+const CHECKER_CELL: f32 = 6.0;
+
+/// Hues at the stops of the hue bar's gradient, in degrees.
+const HUE_STOPS: [f32; 7] = [0.0, 60.0, 120.0, 180.0, 240.0, 300.0, 360.0];
+
+/// Which of the picker's draggable controls a [`ColorArea`] draws.
+#[derive(Debug, Clone, Copy)]
+enum ColorAreaKind {
+    /// Saturation left to right, value top to bottom.
+    SaturationValue,
+    /// Hue left to right.
+    Hue,
+    /// Opacity left to right.
+    Alpha,
+}
+
+/// A draggable color control; its state tracks whether a drag started on it.
+struct ColorArea {
+    /// Which control this is.
+    kind: ColorAreaKind,
+    /// The picker's current hue, saturation, value and alpha.
+    hsva: [f32; 4],
+}
+
+impl ColorArea {
+    /// Returns the message setting this control to `position`, clamped to
+    /// `bounds`.
+    fn message_at(&self, position: Point, bounds: Rectangle) -> Message {
+        let x = (position.x / bounds.width).clamp(0.0, 1.0);
+        let y = (position.y / bounds.height).clamp(0.0, 1.0);
+
+        match self.kind {
+            ColorAreaKind::SaturationValue => {
+                Message::ColorPickerSaturationValue(x, 1.0 - y)
+            }
+            ColorAreaKind::Hue => Message::ColorPickerHue(x * 360.0),
+            ColorAreaKind::Alpha => Message::ColorPickerAlpha(x),
+        }
+    }
+}
+
+impl canvas::Program<Message> for ColorArea {
+    type State = bool;
+
+    fn update(
+        &self,
+        dragging: &mut Self::State,
+        event: &Event,
+        bounds: Rectangle,
+        cursor: mouse::Cursor,
+    ) -> Option<canvas::Action<Message>> {
+        match event {
+            Event::Mouse(mouse::Event::ButtonPressed(mouse::Button::Left)) => {
+                let position = cursor.position_in(bounds)?;
+                *dragging = true;
+                Some(
+                    canvas::Action::publish(self.message_at(position, bounds))
+                        .and_capture(),
+                )
+            }
+            Event::Mouse(mouse::Event::CursorMoved { .. }) if *dragging => {
+                let position = cursor.position_from(bounds.position())?;
+                Some(
+                    canvas::Action::publish(self.message_at(position, bounds))
+                        .and_capture(),
+                )
+            }
+            Event::Mouse(mouse::Event::ButtonReleased(mouse::Button::Left))
+                if *dragging =>
+            {
+                *dragging = false;
+                Some(canvas::Action::capture())
+            }
+            _ => None,
+        }
+    }
+
+    fn draw(
+        &self,
+        _dragging: &Self::State,
+        renderer: &Renderer,
+        _theme: &Theme,
+        bounds: Rectangle,
+        _cursor: mouse::Cursor,
+    ) -> Vec<canvas::Geometry> {
+        let mut frame = canvas::Frame::new(renderer, bounds.size());
+        let size = bounds.size();
+        let [hue, saturation, value, alpha] = self.hsva;
+
+        let marker = match self.kind {
+            ColorAreaKind::SaturationValue => {
+                frame.fill_rectangle(
+                    Point::ORIGIN,
+                    size,
+                    hsv_to_color(hue, 1.0, 1.0, 1.0),
+                );
+                frame.fill_rectangle(
+                    Point::ORIGIN,
+                    size,
+                    gradient::Linear::new(
+                        Point::ORIGIN,
+                        Point::new(size.width, 0.0),
+                    )
+                    .add_stop(0.0, Color::WHITE)
+                    .add_stop(1.0, Color::TRANSPARENT),
+                );
+                frame.fill_rectangle(
+                    Point::ORIGIN,
+                    size,
+                    gradient::Linear::new(
+                        Point::ORIGIN,
+                        Point::new(0.0, size.height),
+                    )
+                    .add_stop(0.0, Color::TRANSPARENT)
+                    .add_stop(1.0, Color::BLACK),
+                );
+                Point::new(saturation * size.width, (1.0 - value) * size.height)
+            }
+            ColorAreaKind::Hue => {
+                let mut hues = gradient::Linear::new(
+                    Point::ORIGIN,
+                    Point::new(size.width, 0.0),
+                );
+                for stop in HUE_STOPS {
+                    hues = hues.add_stop(
+                        stop / 360.0,
+                        hsv_to_color(stop, 1.0, 1.0, 1.0),
+                    );
+                }
+                frame.fill_rectangle(Point::ORIGIN, size, hues);
+                Point::new(hue / 360.0 * size.width, size.height / 2.0)
+            }
+            ColorAreaKind::Alpha => {
+                draw_checkerboard(&mut frame, size);
+                let opaque = hsv_to_color(hue, saturation, value, 1.0);
+                frame.fill_rectangle(
+                    Point::ORIGIN,
+                    size,
+                    gradient::Linear::new(
+                        Point::ORIGIN,
+                        Point::new(size.width, 0.0),
+                    )
+                    .add_stop(0.0, Color { a: 0.0, ..opaque })
+                    .add_stop(1.0, opaque),
+                );
+                Point::new(alpha * size.width, size.height / 2.0)
+            }
+        };
+
+        let radius = match self.kind {
+            ColorAreaKind::SaturationValue => 5.0,
+            ColorAreaKind::Hue | ColorAreaKind::Alpha => size.height / 2.0,
+        };
+        let ring = Path::circle(marker, radius);
+        frame.stroke(
+            &ring,
+            Stroke::default().with_color(Color::BLACK).with_width(3.0),
+        );
+        frame.stroke(
+            &ring,
+            Stroke::default().with_color(Color::WHITE).with_width(1.5),
+        );
+
+        vec![frame.into_geometry()]
+    }
+
+    fn mouse_interaction(
+        &self,
+        dragging: &Self::State,
+        bounds: Rectangle,
+        cursor: mouse::Cursor,
+    ) -> mouse::Interaction {
+        if *dragging || cursor.is_over(bounds) {
+            match self.kind {
+                ColorAreaKind::SaturationValue => mouse::Interaction::Crosshair,
+                ColorAreaKind::Hue | ColorAreaKind::Alpha => {
+                    mouse::Interaction::Pointer
+                }
+            }
+        } else {
+            mouse::Interaction::default()
+        }
+    }
+}
+
+/// Fills `size` with a light/dark checkerboard, so translucency shows.
+fn draw_checkerboard(frame: &mut canvas::Frame, size: Size) {
+    frame.fill_rectangle(
+        Point::ORIGIN,
+        size,
+        Color::from_rgb8(0xcc, 0xcc, 0xcc),
+    );
+
+    let columns = (size.width / CHECKER_CELL).ceil() as usize;
+    let rows = (size.height / CHECKER_CELL).ceil() as usize;
+    for row in 0..rows {
+        for column in (row % 2..columns).step_by(2) {
+            frame.fill_rectangle(
+                Point::new(
+                    column as f32 * CHECKER_CELL,
+                    row as f32 * CHECKER_CELL,
+                ),
+                Size::new(CHECKER_CELL, CHECKER_CELL),
+                Color::WHITE,
+            );
+        }
+    }
+}
+
+/// A flat preview of the picked color over a checkerboard.
+struct ColorPreview(Color);
+
+impl canvas::Program<Message> for ColorPreview {
+    type State = ();
+
+    fn draw(
+        &self,
+        _state: &Self::State,
+        renderer: &Renderer,
+        _theme: &Theme,
+        bounds: Rectangle,
+        _cursor: mouse::Cursor,
+    ) -> Vec<canvas::Geometry> {
+        let mut frame = canvas::Frame::new(renderer, bounds.size());
+        draw_checkerboard(&mut frame, bounds.size());
+        frame.fill_rectangle(Point::ORIGIN, bounds.size(), self.0);
+        vec![frame.into_geometry()]
+    }
+}
+
+/// Transparent top layer that lets Escape close the picker before the
+/// focused hex input consumes the key to merely unfocus itself.
+struct EscapeListener;
+
+impl canvas::Program<Message> for EscapeListener {
+    type State = ();
+
+    fn update(
+        &self,
+        _state: &mut Self::State,
+        event: &Event,
+        _bounds: Rectangle,
+        _cursor: mouse::Cursor,
+    ) -> Option<canvas::Action<Message>> {
+        if matches!(
+            event,
+            Event::Keyboard(keyboard::Event::KeyPressed {
+                key: keyboard::Key::Named(keyboard::key::Named::Escape),
+                ..
+            })
+        ) {
+            Some(
+                canvas::Action::publish(Message::CloseColorPicker)
+                    .and_capture(),
+            )
+        } else {
+            None
+        }
+    }
+
+    fn draw(
+        &self,
+        _state: &Self::State,
+        _renderer: &Renderer,
+        _theme: &Theme,
+        _bounds: Rectangle,
+        _cursor: mouse::Cursor,
+    ) -> Vec<canvas::Geometry> {
+        Vec::new()
+    }
+}
+
+/// Builds the color picker shown next to the swatch it was opened from.
+pub(crate) fn view(state: &ColorPickerState) -> Element<'_, Message> {
+    if !state.is_open() {
+        return Space::new().into();
+    }
+
+    let hsva = [state.hue, state.saturation, state.value, state.alpha];
+    let area = |kind, height| {
+        Canvas::new(ColorArea { kind, hsva })
+            .width(Length::Fixed(AREA_WIDTH))
+            .height(Length::Fixed(height))
+    };
+
+    let hex_input = text_input("#rrggbb", &state.hex)
+        .id(state.hex_input_id.clone())
+        .on_input(Message::ColorPickerHexChanged)
+        .on_submit(Message::CloseColorPicker)
+        .padding(4)
+        .size(12)
+        .width(Length::Fill);
+
+    let close_button = button(fa_icon_solid("xmark").size(10.0))
+        .on_press(Message::CloseColorPicker)
+        .padding(4);
+
+    let mut content = column![
+        area(ColorAreaKind::SaturationValue, SQUARE_HEIGHT),
+        area(ColorAreaKind::Hue, BAR_HEIGHT),
+        area(ColorAreaKind::Alpha, BAR_HEIGHT),
+        row![
+            Canvas::new(ColorPreview(state.color()))
+                .width(Length::Fixed(24.0))
+                .height(Length::Fixed(24.0)),
+            hex_input,
+            close_button,
+        ]
+        .spacing(6)
+        .align_y(iced::Alignment::Center),
+    ]
+    .spacing(8)
+    .width(Length::Fixed(AREA_WIDTH));
+
+    if !state.presentations.is_empty() {
+        let presentations = state.presentations.iter().enumerate().fold(
+            Column::new().spacing(2),
+            |presentations, (index, presentation)| {
+                presentations.push(
+                    button(text(presentation.label.as_str()).size(12))
+                        .on_press(Message::ColorPickerPresentation(index))
+                        .padding([2, 6])
+                        .width(Length::Fill)
+                        .style(button::text),
+                )
+            },
+        );
+        content = content.push(presentations);
+    }
+
+    let dialog = container(content).padding(8).style(|theme| {
+        let base = container::rounded_box(theme);
+        container::Style {
+            background: base.background.map(|background| match background {
+                iced::Background::Color(color) => {
+                    iced::Background::Color(iced::Color { a: 0.95, ..color })
+                }
+                _ => background,
+            }),
+            ..base
+        }
+    });
+
+    // Clicks on the dialog's padding must not fall through to the editor,
+    // where they would move the caret and close the picker.
+    let dialog = mouse_area(dialog).interaction(mouse::Interaction::Idle);
+
+    Stack::new()
+        .push(dialog)
+        .push(
+            Canvas::new(EscapeListener)
+                .width(Length::Fill)
+                .height(Length::Fill),
+        )
+        .into()
+}
@@ -0,0 +1,432 @@
+//! Color picker opened by clicking an inline color-preview swatch.
+//!
+//! The picker edits the color literal it was opened on in place: every drag
+//! of the saturation/value square, the hue bar or the alpha bar, and every
+//! valid hex entry, rewrites the literal immediately so the result can be
+//! judged in context. The literal keeps its notation — see
+//! [`color_preview::format_color_like`] — and closing the picker folds all of
+//! those intermediate writes into one undoable edit.
+//!
+//! Colors reported by a language server (see
+//! [`document_color`](super::document_color)) are written through
+//! `textDocument/colorPresentation` instead when the editor cannot format
+//! them itself, and the server's presentations are offered as buttons so the
+//! user can switch notation.
+
+pub(crate) mod dialog;
+mod update;
+
+use iced::widget::Id;
+use iced::{Color, Point};
+
+use super::color_preview::{self, LineLiterals};
+use super::document_color::PlacedColor;
+use crate::canvas_editor::lsp::LspColorPresentation;
+use crate::canvas_editor::render::wrapping::WrappingCalculator;
+use crate::canvas_editor::{CodeEditor, Message};
+
+/// Gap in pixels between a swatch and the picker opened from it.
+const PICKER_OFFSET: f32 = 4.0;
+
+/// The color literal a picker session edits.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct PickerTarget {
+    /// Logical line of the literal.
+    pub(crate) line: usize,
+    /// Column of the literal's first character.
+    pub(crate) start_col: usize,
+    /// Column one past the literal's last character, as currently written.
+    pub(crate) end_col: usize,
+    /// The literal's text when the picker opened, restored before the
+    /// session is committed as a single edit.
+    pub(crate) original: String,
+    /// Whether the language server reported the literal.
+    pub(crate) from_server: bool,
+}
+
+/// State owned by the color picker.
+#[derive(Debug, Clone)]
+pub(crate) struct ColorPickerState {
+    /// The literal being edited; `None` while the picker is closed.
+    pub(crate) target: Option<PickerTarget>,
+    /// Hue, in degrees (`0.0..360.0`).
+    pub(crate) hue: f32,
+    /// Saturation (`0.0..=1.0`).
+    pub(crate) saturation: f32,
+    /// Value, or brightness (`0.0..=1.0`).
+    pub(crate) value: f32,
+    /// Opacity (`0.0..=1.0`).
+    pub(crate) alpha: f32,
+    /// Text of the hex input, which may hold a partial entry.
+    pub(crate) hex: String,
+    /// Notations offered by the language server for the current color.
+    pub(crate) presentations: Vec<LspColorPresentation>,
+    /// Whether the literal is waiting for a presentation to be written with,
+    /// because the editor cannot format it itself.
+    pub(crate) awaiting_presentation: bool,
+    /// Stable input ID of the hex field.
+    pub(crate) hex_input_id: Id,
+}
+
+impl Default for ColorPickerState {
+    fn default() -> Self {
+        Self {
+            target: None,
+            hue: 0.0,
+            saturation: 0.0,
+            value: 0.0,
+            alpha: 1.0,
+            hex: String::new(),
+            presentations: Vec::new(),
+            awaiting_presentation: false,
+            hex_input_id: Id::unique(),
+        }
+    }
+}
+
+impl ColorPickerState {
+    /// Returns whether the picker is shown.
+    pub(crate) fn is_open(&self) -> bool {
+        self.target.is_some()
+    }
+
+    /// Returns the color currently picked.
+    pub(crate) fn color(&self) -> Color {
+        hsv_to_color(self.hue, self.saturation, self.value, self.alpha)
+    }
+
+    /// Moves every control to `color` and rewrites the hex input.
+    ///
+    /// The hue is kept when `color` is a gray, where it is undefined, so
+    /// dragging through black or white does not snap the hue bar back to red.
+    pub(crate) fn set_color(&mut self, color: Color) {
+        self.set_color_keeping_hex(color);
+        self.hex = hex_string(color);
+    }
+
+    /// Moves every control to `color`, leaving the hex input as typed.
+    pub(crate) fn set_color_keeping_hex(&mut self, color: Color) {
+        let (hue, saturation, value) = color_to_hsv(color);
+        if saturation > 0.0 && value > 0.0 {
+            self.hue = hue;
+        }
+        self.saturation = saturation;
+        self.value = value;
+        self.alpha = color.a;
+    }
+}
+
+/// Returns whether `message` leaves an open color picker open.
+///
+/// Anything else — typing, clicking into the text, undo, opening another
+/// dialog — first commits the picker's edit and closes it.
+pub(crate) fn keeps_color_picker_open(message: &Message) -> bool {
+    matches!(
+        message,
+        Message::OpenColorPicker(..)
+            | Message::ColorPickerSaturationValue(..)
+            | Message::ColorPickerHue(_)
+            | Message::ColorPickerAlpha(_)
+            | Message::ColorPickerHexChanged(_)
+            | Message::ColorPickerPresentation(_)
+            | Message::Tick
+            | Message::MouseHover(_)
+            | Message::MouseRelease
+            | Message::Scrolled(_)
+            | Message::HorizontalScrolled(_)
+            | Message::CanvasFocusGained
+            | Message::CanvasFocusLost
+            | Message::ImeOpened
+            | Message::ImeClosed
+    )
+}
+
+/// Converts hue (degrees), saturation, value and alpha to a color.
+pub(crate) fn hsv_to_color(
+    hue: f32,
+    saturation: f32,
+    value: f32,
+    alpha: f32,
+) -> Color {
+    let hue = hue.rem_euclid(360.0) / 60.0;
+    let chroma = value * saturation;
+    let second = chroma * (1.0 - (hue % 2.0 - 1.0).abs());
+    let (red, green, blue) = match hue as u8 {
+        0 => (chroma, second, 0.0),
+        1 => (second, chroma, 0.0),
+        2 => (0.0, chroma, second),
+        3 => (0.0, second, chroma),
+        4 => (second, 0.0, chroma),
+        _ => (chroma, 0.0, second),
+    };
+    let lightness = value - chroma;
+
+    Color::from_rgba(
+        red + lightness,
+        green + lightness,
+        blue + lightness,
+        alpha,
+    )
+}
+
+/// Converts a color to hue (degrees), saturation and value.
+pub(crate) fn color_to_hsv(color: Color) -> (f32, f32, f32) {
+    let max = color.r.max(color.g).max(color.b);
+    let min = color.r.min(color.g).min(color.b);
+    let chroma = max - min;
+
+    let hue = if chroma <= f32::EPSILON {
+        0.0
+    } else if max - color.r <= f32::EPSILON {
+        60.0 * ((color.g - color.b) / chroma).rem_euclid(6.0)
+    } else if max - color.g <= f32::EPSILON {
+        60.0 * ((color.b - color.r) / chroma + 2.0)
+    } else {
+        60.0 * ((color.r - color.g) / chroma + 4.0)
+    };
+    let saturation = if max <= f32::EPSILON { 0.0 } else { chroma / max };
+
+    (hue, saturation, max)
+}
+
+/// Formats `color` as `#rrggbb`, or `#rrggbbaa` when it is translucent.
+pub(crate) fn hex_string(color: Color) -> String {
+    color_preview::format_color_like(
+        if color.a < 1.0 { "#00000000" } else { "#000000" },
+        color,
+    )
+    .unwrap_or_default()
+}
+
+/// Parses the hex input, with or without its leading `#`.
+///
+/// # Returns
+///
+/// The color, or `None` while the entry is not a complete hex color.
+pub(crate) fn parse_hex_input(input: &str) -> Option<Color> {
+    let literal = format!("#{}", input.trim().trim_start_matches('#'));
+    color_preview::color_literals(&literal)
+        .into_iter()
+        .find(|found| {
+            found.start_col == 0 && found.end_col == literal.chars().count()
+        })
+        .map(|found| found.color)
+}
+
+impl CodeEditor {
+    /// Returns the `(line, start column)` of the literal whose swatch is at
+    /// `point`, if any.
+    ///
+    /// # Arguments
+    ///
+    /// * `point` - The click position in canvas coordinates
+    pub(crate) fn color_swatch_at_point(
+        &self,
+        point: Point,
+    ) -> Option<(usize, usize)> {
+        if !self.show_color_previews || point.y < 0.0 {
+            return None;
+        }
+
+        let visual_index = (point.y / self.line_height) as usize;
+        let visual_lines = self.visual_lines_cached(self.viewport_width);
+        let visual_line = visual_lines.get(visual_index)?;
+        let y = visual_index as f32 * self.line_height;
+
+        self.placed_colors(
+            &mut LineLiterals::default(),
+            visual_line.logical_line,
+        )
+        .into_iter()
+        .map(|placed| placed.literal)
+        .find(|literal| {
+            literal.end_col > visual_line.start_col
+                && literal.end_col <= visual_line.end_col
+                && self
+                    .color_swatch_bounds(visual_line, y, literal.end_col)
+                    .contains(point)
+        })
+        .map(|literal| (visual_line.logical_line, literal.start_col))
+    }
+
+    /// Returns the literal starting at `(line, start_col)`, if any.
+    pub(crate) fn placed_color_at(
+        &self,
+        line: usize,
+        start_col: usize,
+    ) -> Option<PlacedColor> {
+        self.placed_colors(&mut LineLiterals::default(), line)
+            .into_iter()
+            .find(|placed| placed.literal.start_col == start_col)
+    }
+
+    /// Returns where the open picker's top-left corner goes, in viewport
+    /// coordinates: just below the swatch, or above it when the picker would
+    /// overflow the bottom of the viewport.
+    pub(crate) fn color_picker_position(&self) -> Option<Point> {
+        let target = self.color_picker_state.target.as_ref()?;
+        let visual_lines = self.visual_lines_cached(self.viewport_width);
+        let visual_index = WrappingCalculator::logical_to_visual(
+            &visual_lines,
+            target.line,
+            target.end_col.saturating_sub(1),
+        )?;
+        let swatch = self.color_swatch_bounds(
+            visual_lines.get(visual_index)?,
+            visual_index as f32 * self.line_height,
+            target.end_col,
+        );
+
+        let top = swatch.y - self.viewport_scroll;
+        let below = top + swatch.height + PICKER_OFFSET;
+        let y = if below + dialog::PICKER_HEIGHT > self.viewport_height {
+            (top - PICKER_OFFSET - dialog::PICKER_HEIGHT).max(0.0)
+        } else {
+            below
+        };
+        let x =
+            swatch.x.min(self.viewport_width - dialog::PICKER_WIDTH).max(0.0);
+
+        Some(Point::new(x, y))
+    }
+
+    /// Closes the color picker programmatically.
+    ///
+    /// The color picked so far stays in the buffer, as one undoable edit.
+    /// Safe to call when the picker is already closed.
+    ///
+    /// # Returns
+    ///
+    /// A `Task<Message>` for any follow-up work
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use iced_code_editor::CodeEditor;
+    ///
+    /// let mut editor = CodeEditor::new("body { color: #f0c; }", "css");
+    /// let _task = editor.close_color_picker();
+    /// ```
+    pub fn close_color_picker(&mut self) -> iced::Task<Message> {
+        self.update(&Message::CloseColorPicker)
+    }
+
+    /// Returns whether the color picker is open.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use iced_code_editor::CodeEditor;
+    ///
+    /// let editor = CodeEditor::new("body { color: #f0c; }", "css");
+    /// // Opens when the user clicks a color-preview swatch.
+    /// assert!(!editor.is_color_picker_open());
+    /// ```
+    pub fn is_color_picker_open(&self) -> bool {
+        self.color_picker_state.is_open()
+    }
+
+    /// Offers a language server's notations for the picked color.
+    ///
+    /// Feed this the payload of a `textDocument/colorPresentation` response
+    /// (with the bundled client, [`LspEvent::ColorPresentations`]). The editor
+    /// asks for them while the picker edits a color the server reported; they
+    /// are listed in the picker, and when the editor cannot rewrite the
+    /// literal itself — `Color::RED`, a named CSS color — the first one is
+    /// written in its place. Ignored while the picker is closed.
+    ///
+    /// # Arguments
+    ///
+    /// * `presentations` - The notations offered by the server
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use iced_code_editor::{CodeEditor, LspColorPresentation};
+    ///
+    /// let mut editor = CodeEditor::new("let c = Color::RED;", "rs");
+    /// editor.set_lsp_color_presentations(&[LspColorPresentation {
+    ///     label: "Color::from_rgb(1.0, 0.0, 0.0)".to_string(),
+    ///     text_edit: None,
+    /// }]);
+    /// ```
+    ///
+    /// [`LspEvent::ColorPresentations`]: crate::LspEvent::ColorPresentations
+    pub fn set_lsp_color_presentations(
+        &mut self,
+        presentations: &[LspColorPresentation],
+    ) {
+        if !self
+            .color_picker_state
+            .target
+            .as_ref()
+            .is_some_and(|target| target.from_server)
+        {
+            return;
+        }
+
+        self.color_picker_state.presentations = presentations.to_vec();
+        if self.color_picker_state.awaiting_presentation
+            && let Some(first) = presentations.first()
+        {
+            self.color_picker_state.awaiting_presentation = false;
+            self.write_picked_text(first.text());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cmp::Ordering;
+
+    use iced::Color;
+
+    use super::{color_to_hsv, hex_string, hsv_to_color, parse_hex_input};
+    use crate::canvas_editor::compare_floats;
+
+    /// Returns whether two colors match to within 8-bit precision.
+    fn same_color(a: Color, b: Color) -> bool {
+        a.into_rgba8() == b.into_rgba8()
+    }
+
+    #[test]
+    fn test_hsv_round_trips_through_rgb() {
+        for color in [
+            Color::from_rgb8(255, 0, 0),
+            Color::from_rgb8(58, 123, 213),
+            Color::from_rgb8(18, 128, 128),
+            Color::from_rgb8(200, 200, 40),
+            Color::from_rgb8(130, 20, 200),
+        ] {
+            let (hue, saturation, value) = color_to_hsv(color);
+            assert!(same_color(
+                hsv_to_color(hue, saturation, value, 1.0),
+                color
+            ));
+        }
+
+        let (hue, saturation, value) =
+            color_to_hsv(Color::from_rgb8(0, 0, 255));
+        assert_eq!(compare_floats(hue, 240.0), Ordering::Equal);
+        assert_eq!(compare_floats(saturation, 1.0), Ordering::Equal);
+        assert_eq!(compare_floats(value, 1.0), Ordering::Equal);
+    }
+
+    #[test]
+    fn test_hex_input_accepts_complete_colors_only() {
+        let color = Color::from_rgb8(0x12, 0x80, 0x80);
+
+        assert!(
+            parse_hex_input("#128080").is_some_and(|c| same_color(c, color))
+        );
+        assert!(
+            parse_hex_input(" 128080 ").is_some_and(|c| same_color(c, color))
+        );
+        assert!(parse_hex_input("#1280").is_some());
+        assert!(parse_hex_input("#12808").is_none());
+        assert!(parse_hex_input("#12808g").is_none());
+
+        assert_eq!(hex_string(color), "#128080");
+        assert_eq!(hex_string(Color { a: 0.5, ..color }), "#12808080");
+    }
+}
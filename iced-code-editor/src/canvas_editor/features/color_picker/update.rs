@@ -0,0 +1,360 @@
+//! Message handling for the color picker.
+
+use iced::Task;
+
+use super::{PickerTarget, hsv_to_color, parse_hex_input};
+use crate::canvas_editor::editing::command::{Command, ReplaceTextCommand};
+use crate::canvas_editor::features::color_preview::format_color_like;
+use crate::canvas_editor::{CodeEditor, Message};
+
+impl CodeEditor {
+    /// Opens the picker on the literal starting at `(line, start_col)`.
+    pub(crate) fn handle_open_color_picker_msg(
+        &mut self,
+        line: usize,
+        start_col: usize,
+    ) -> Task<Message> {
+        self.commit_color_picker();
+        let Some(placed) = self.placed_color_at(line, start_col) else {
+            return Task::none();
+        };
+
+        let literal = placed.literal;
+        self.color_picker_state.target = Some(PickerTarget {
+            line,
+            start_col,
+            end_col: literal.end_col,
+            original: self.color_picker_text(line, start_col, literal.end_col),
+            from_server: placed.from_server,
+        });
+        self.color_picker_state.set_color(literal.color);
+        self.color_picker_state.presentations.clear();
+        self.color_picker_state.awaiting_presentation = false;
+        if placed.from_server {
+            self.lsp_request_color_presentations(
+                literal.color,
+                (line, start_col, literal.end_col),
+            );
+        }
+        Task::none()
+    }
+
+    /// Closes the picker, keeping the picked color as one undoable edit.
+    pub(crate) fn handle_close_color_picker_msg(&mut self) -> Task<Message> {
+        self.commit_color_picker();
+        Task::none()
+    }
+
+    /// Moves the saturation/value marker and writes the new color.
+    pub(crate) fn handle_color_picker_saturation_value_msg(
+        &mut self,
+        saturation: f32,
+        value: f32,
+    ) -> Task<Message> {
+        self.color_picker_state.saturation = saturation.clamp(0.0, 1.0);
+        self.color_picker_state.value = value.clamp(0.0, 1.0);
+        self.apply_picked_color(true)
+    }
+
+    /// Moves the hue marker and writes the new color.
+    pub(crate) fn handle_color_picker_hue_msg(
+        &mut self,
+        hue: f32,
+    ) -> Task<Message> {
+        self.color_picker_state.hue = hue.clamp(0.0, 360.0);
+        self.apply_picked_color(true)
+    }
+
+    /// Moves the alpha marker and writes the new color.
+    pub(crate) fn handle_color_picker_alpha_msg(
+        &mut self,
+        alpha: f32,
+    ) -> Task<Message> {
+        self.color_picker_state.alpha = alpha.clamp(0.0, 1.0);
+        self.apply_picked_color(true)
+    }
+
+    /// Updates the hex input, writing the color once the entry is complete.
+    pub(crate) fn handle_color_picker_hex_changed_msg(
+        &mut self,
+        input: &str,
+    ) -> Task<Message> {
+        self.color_picker_state.hex = input.to_string();
+        if let Some(color) = parse_hex_input(input) {
+            self.color_picker_state.set_color_keeping_hex(color);
+            return self.apply_picked_color(false);
+        }
+        Task::none()
+    }
+
+    /// Rewrites the literal with the server presentation at `index`.
+    pub(crate) fn handle_color_picker_presentation_msg(
+        &mut self,
+        index: usize,
+    ) -> Task<Message> {
+        if let Some(presentation) =
+            self.color_picker_state.presentations.get(index)
+        {
+            let text = presentation.text().to_string();
+            self.color_picker_state.awaiting_presentation = false;
+            self.write_picked_text(&text);
+        }
+        Task::none()
+    }
+
+    /// Writes the picked color over the literal, in the literal's notation.
+    ///
+    /// A literal only the server understands is rewritten once the server
+    /// answers with a presentation for the new color.
+    ///
+    /// # Arguments
+    ///
+    /// * `refresh_hex` - Whether to rewrite the hex input, which is left as
+    ///   typed when the change came from it
+    fn apply_picked_color(&mut self, refresh_hex: bool) -> Task<Message> {
+        let state = &self.color_picker_state;
+        let Some(target) = state.target.clone() else {
+            return Task::none();
+        };
+        let color =
+            hsv_to_color(state.hue, state.saturation, state.value, state.alpha);
+        if refresh_hex {
+            self.color_picker_state.hex = super::hex_string(color);
+        }
+
+        let current = self.color_picker_text(
+            target.line,
+            target.start_col,
+            target.end_col,
+        );
+        match format_color_like(&current, color) {
+            Some(text) => self.write_picked_text(&text),
+            None => self.color_picker_state.awaiting_presentation = true,
+        }
+        if target.from_server
+            && let Some(target) = &self.color_picker_state.target
+        {
+            let range = (target.line, target.start_col, target.end_col);
+            self.lsp_request_color_presentations(color, range);
+        }
+        Task::none()
+    }
+
+    /// Returns the text of `line` between two columns.
+    fn color_picker_text(
+        &self,
+        line: usize,
+        start_col: usize,
+        end_col: usize,
+    ) -> String {
+        self.buffer
+            .line(line)
+            .chars()
+            .skip(start_col)
+            .take(end_col.saturating_sub(start_col))
+            .collect()
+    }
+
+    /// Replaces the edited literal with `text`, outside the undo history.
+    ///
+    /// Cursors after the literal on the same line shift with its end, so the
+    /// caret does not drift into the color while it is being picked.
+    pub(crate) fn write_picked_text(&mut self, text: &str) {
+        let Some(target) = self.color_picker_state.target.as_mut() else {
+            return;
+        };
+
+        let (line, start_col, old_end) =
+            (target.line, target.start_col, target.end_col);
+        let new_end = start_col + text.chars().count();
+        target.end_col = new_end;
+        if self.color_picker_text(line, start_col, old_end) == text {
+            return;
+        }
+        self.buffer.replace_range(line, start_col, old_end - start_col, text);
+
+        for cursor in self.cursors.as_mut_slice() {
+            for position in
+                std::iter::once(&mut cursor.position).chain(&mut cursor.anchor)
+            {
+                if position.0 == line && position.1 >= old_end {
+                    position.1 = position.1 - old_end + new_end;
+                }
+            }
+        }
+
+        self.pre_edit_line = line;
+        self.pre_edit_last_line = line;
+        self.finish_edit_operation();
+    }
+
+    /// Closes the picker, recording everything it wrote as a single
+    /// undoable edit.
+    ///
+    /// The live writes bypass the history, so the original literal is put
+    /// back for a moment and the final text re-applied through a
+    /// [`ReplaceTextCommand`]: undo then restores the original in one step.
+    pub(crate) fn commit_color_picker(&mut self) {
+        let Some(target) = self.color_picker_state.target.take() else {
+            return;
+        };
+        self.color_picker_state.presentations.clear();
+        self.color_picker_state.awaiting_presentation = false;
+
+        let picked = self.color_picker_text(
+            target.line,
+            target.start_col,
+            target.end_col,
+        );
+        if picked == target.original {
+            return;
+        }
+
+        self.buffer.replace_range(
+            target.line,
+            target.start_col,
+            target.end_col - target.start_col,
+            &target.original,
+        );
+        self.end_grouping_if_active();
+        let cursor = self.cursors.primary_position();
+        let mut command = ReplaceTextCommand::new(
+            &self.buffer,
+            (target.line, target.start_col),
+            target.original.chars().count(),
+            picked,
+            cursor,
+        );
+        let mut cursor_after = cursor;
+        command.execute(&mut self.buffer, &mut cursor_after);
+        self.history.push(Box::new(command));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    use iced::{Color, Point};
+
+    use crate::canvas_editor::lsp::{
+        LspClient, LspColorPresentation, LspDocument, LspDocumentColor,
+        LspPosition, LspRange,
+    };
+    use crate::canvas_editor::{CodeEditor, Message};
+
+    /// Records the colors the picker asks presentations for.
+    struct PresentationClient(Rc<RefCell<Vec<Color>>>);
+
+    impl LspClient for PresentationClient {
+        fn request_color_presentations(
+            &mut self,
+            _document: &LspDocument,
+            color: Color,
+            _range: LspRange,
+        ) {
+            self.0.borrow_mut().push(color);
+        }
+    }
+
+    #[test]
+    fn test_picking_rewrites_the_literal_in_its_own_notation() {
+        let mut editor = CodeEditor::new("a { color: #f00; } x", "css");
+        editor.cursors.primary_mut().position = (0, 20);
+
+        let _ = editor.update(&Message::OpenColorPicker(0, 11));
+        assert!(editor.is_color_picker_open());
+
+        // Pure blue still fits the short form.
+        let _ = editor.update(&Message::ColorPickerHue(240.0));
+        assert_eq!(editor.content(), "a { color: #00f; } x");
+        let _ = editor.update(&Message::ColorPickerSaturationValue(0.5, 0.5));
+        assert_eq!(editor.content(), "a { color: #404080; } x");
+        // The cursor after the literal moved with its end.
+        assert_eq!(editor.cursors.primary_position(), (0, 23));
+        let _ = editor.update(&Message::ColorPickerAlpha(0.5));
+        assert_eq!(editor.content(), "a { color: #40408080; } x");
+        assert_eq!(editor.color_picker_state.hex, "#40408080");
+    }
+
+    #[test]
+    fn test_closing_the_picker_leaves_a_single_undo_step() {
+        let mut editor = CodeEditor::new("let c = rgb(255, 0, 0);", "rs");
+
+        let _ = editor.update(&Message::OpenColorPicker(0, 8));
+        let _ = editor.update(&Message::ColorPickerHue(120.0));
+        let _ = editor.update(&Message::ColorPickerHexChanged("#00f".into()));
+        assert_eq!(editor.content(), "let c = rgb(0, 0, 255);");
+        // A partial hex entry keeps the last complete color.
+        let _ = editor.update(&Message::ColorPickerHexChanged("#12".into()));
+        assert_eq!(editor.content(), "let c = rgb(0, 0, 255);");
+
+        // Any other message commits the session before it is handled.
+        let _ = editor.update(&Message::Undo);
+        assert!(!editor.is_color_picker_open());
+        assert_eq!(editor.content(), "let c = rgb(255, 0, 0);");
+        let _ = editor.update(&Message::Redo);
+        assert_eq!(editor.content(), "let c = rgb(0, 0, 255);");
+    }
+
+    #[test]
+    fn test_server_colors_are_written_through_presentations() {
+        let requests = Rc::new(RefCell::new(Vec::new()));
+        let mut editor = CodeEditor::new("let c = Color::RED;", "rs");
+        editor.attach_lsp(
+            Box::new(PresentationClient(Rc::clone(&requests))),
+            LspDocument::new("file:///tmp/main.rs", "rust"),
+        );
+        editor.lsp_request_document_colors();
+        editor.set_lsp_document_colors(&[LspDocumentColor {
+            range: LspRange {
+                start: LspPosition { line: 0, character: 8 },
+                end: LspPosition { line: 0, character: 18 },
+            },
+            color: Color::from_rgb(1.0, 0.0, 0.0),
+        }]);
+
+        let _ = editor.update(&Message::OpenColorPicker(0, 8));
+        let _ = editor.update(&Message::ColorPickerHue(240.0));
+        assert_eq!(requests.borrow().len(), 2);
+        // `Color::RED` has no lexical notation: nothing is written until the
+        // server answers.
+        assert_eq!(editor.content(), "let c = Color::RED;");
+
+        let presentations = [
+            LspColorPresentation {
+                label: "Color::BLUE".to_string(),
+                text_edit: None,
+            },
+            LspColorPresentation {
+                label: "Color::from_rgb(0.0, 0.0, 1.0)".to_string(),
+                text_edit: None,
+            },
+        ];
+        editor.set_lsp_color_presentations(&presentations);
+        assert_eq!(editor.content(), "let c = Color::BLUE;");
+
+        let _ = editor.update(&Message::ColorPickerPresentation(1));
+        assert_eq!(editor.content(), "let c = Color::from_rgb(0.0, 0.0, 1.0);");
+        let _ = editor.update(&Message::CloseColorPicker);
+        let _ = editor.update(&Message::Undo);
+        assert_eq!(editor.content(), "let c = Color::RED;");
+    }
+
+    #[test]
+    fn test_clicking_a_swatch_finds_its_literal() {
+        let editor = CodeEditor::new("#ff0000", "css");
+        let swatch = editor.color_swatch_bounds(
+            &editor.visual_lines_cached(editor.viewport_width)[0],
+            0.0,
+            7,
+        );
+
+        assert_eq!(editor.color_swatch_at_point(swatch.center()), Some((0, 0)));
+        assert_eq!(
+            editor.color_swatch_at_point(Point::new(swatch.x - 10.0, 1.0)),
+            None
+        );
+    }
+}
@@ -129,6 +129,152 @@ impl LineLiterals {
     }
 }
 
+/// Writes `color` in the notation of the literal `original`.
+///
+/// This is the inverse of [`color_literals`], used by the color picker to
+/// write a picked color back the way the source already spells it:
+///
+/// * hexadecimal keeps its prefix (`#`, `0x`, `0X`) and its letter case, and
+///   keeps a short form (`#f0c`) as long as the color can still be written
+///   with doubled digits
+/// * `rgb(…)`/`rgba(…)` keep the function name's case, the spacing after
+///   commas, and percentages when the original used them
+///
+/// An alpha channel is kept when the original had one, and added when the
+/// new color is translucent — `rgb` then becomes `rgba`.
+///
+/// # Arguments
+///
+/// * `original` - The literal's current text, as found by [`color_literals`]
+/// * `color` - The color to write
+///
+/// # Returns
+///
+/// The new literal, or `None` when `original` is not a recognized literal.
+pub(crate) fn format_color_like(
+    original: &str,
+    color: Color,
+) -> Option<String> {
+    let literal = color_literals(original).into_iter().next()?;
+    if literal.start_col != 0 || literal.end_col != original.chars().count() {
+        return None;
+    }
+
+    if let Some(digits) = original.strip_prefix('#') {
+        return Some(format!("#{}", format_hexadecimal(digits, color, true)));
+    }
+    if original.starts_with("0x") || original.starts_with("0X") {
+        let (prefix, digits) = original.split_at(2);
+        return Some(format!(
+            "{prefix}{}",
+            format_hexadecimal(digits, color, false)
+        ));
+    }
+    format_functional(original, color)
+}
+
+/// Writes `color` as hexadecimal digits shaped like `digits`.
+///
+/// # Arguments
+///
+/// * `digits` - The original digit run, without its prefix
+/// * `color` - The color to write
+/// * `allow_short` - Whether the notation has a three/four-digit form
+fn format_hexadecimal(digits: &str, color: Color, allow_short: bool) -> String {
+    let [red, green, blue, alpha] = color.into_rgba8();
+    let had_alpha = matches!(digits.len(), 4 | 8);
+    let channels: &[u8] = if had_alpha || alpha < u8::MAX {
+        &[red, green, blue, alpha]
+    } else {
+        &[red, green, blue]
+    };
+
+    let short = allow_short
+        && matches!(digits.len(), 3 | 4)
+        && channels.iter().all(|channel| channel >> 4 == channel & 0x0f);
+    let formatted: String = channels
+        .iter()
+        .map(|channel| {
+            if short {
+                format!("{:x}", channel & 0x0f)
+            } else {
+                format!("{channel:02x}")
+            }
+        })
+        .collect();
+
+    if digits.chars().any(|digit| digit.is_ascii_uppercase()) {
+        formatted.to_ascii_uppercase()
+    } else {
+        formatted
+    }
+}
+
+/// Writes `color` as an `rgb(…)`/`rgba(…)` call shaped like `original`.
+///
+/// # Arguments
+///
+/// * `original` - The original functional literal
+/// * `color` - The color to write
+///
+/// # Returns
+///
+/// The new literal, or `None` when `original` has no parentheses.
+fn format_functional(original: &str, color: Color) -> Option<String> {
+    let (name, rest) = original.split_once('(')?;
+    let arguments = rest.strip_suffix(')')?;
+    let components: Vec<&str> = arguments.split(',').collect();
+    let separator = if arguments.contains(", ") { ", " } else { "," };
+    let percentages = components
+        .first()
+        .is_some_and(|component| component.trim().ends_with('%'));
+
+    let [red, green, blue, _] = color.into_rgba8();
+    let mut written: Vec<String> = [red, green, blue]
+        .into_iter()
+        .map(|channel| {
+            if percentages {
+                format!(
+                    "{}%",
+                    trim_number(f32::from(channel) / 255.0 * 100.0, 1)
+                )
+            } else {
+                channel.to_string()
+            }
+        })
+        .collect();
+
+    let had_alpha = components.len() == RGBA_COMPONENTS;
+    let mut name = name.to_string();
+    if had_alpha || color.a < 1.0 {
+        let alpha_in_percent = components
+            .get(RGBA_COMPONENTS - 1)
+            .is_some_and(|component| component.trim().ends_with('%'));
+        written.push(if alpha_in_percent {
+            format!("{}%", trim_number(color.a * 100.0, 1))
+        } else {
+            trim_number(color.a, 2)
+        });
+        if !had_alpha && name.len() == "rgb".len() {
+            let uppercase = name.chars().all(|c| c.is_ascii_uppercase());
+            name.push(if uppercase { 'A' } else { 'a' });
+        }
+    }
+
+    Some(format!("{name}({})", written.join(separator)))
+}
+
+/// Formats `value` with at most `decimals` decimals, dropping trailing
+/// zeros (`0.50` → `0.5`, `1.00` → `1`).
+fn trim_number(value: f32, decimals: usize) -> String {
+    let formatted = format!("{value:.decimals$}");
+    if formatted.contains('.') {
+        formatted.trim_end_matches('0').trim_end_matches('.').to_string()
+    } else {
+        formatted
+    }
+}
+
 /// Returns whether the character at `col` can begin a color literal.
 ///
 /// A literal never continues a word, so the preceding character must not be
@@ -382,7 +528,10 @@ fn parse_ratio(component: &str, full_scale: f32) -> Option<f32> {
 mod tests {
     use std::cmp::Ordering;
 
-    use super::{ColorLiteral, LineLiterals, TextBuffer, color_literals};
+    use super::{
+        ColorLiteral, LineLiterals, TextBuffer, color_literals,
+        format_color_like,
+    };
     use crate::canvas_editor::compare_floats;
     use iced::Color;
 
@@ -593,6 +742,50 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_format_color_like_keeps_hexadecimal_shape() {
+        let red = Color::from_rgb8(255, 0, 0);
+        let teal = Color::from_rgb8(0x12, 0x80, 0x80);
+
+        assert_eq!(format_color_like("#0f0", red).as_deref(), Some("#f00"));
+        assert_eq!(format_color_like("#0f0", teal).as_deref(), Some("#128080"));
+        assert_eq!(
+            format_color_like("#ABCDEF", red).as_deref(),
+            Some("#FF0000")
+        );
+        assert_eq!(
+            format_color_like("0xff6b6b", teal).as_deref(),
+            Some("0x128080")
+        );
+        // A translucent color gains an alpha pair; an opaque one keeps the
+        // alpha the literal already had.
+        assert_eq!(
+            format_color_like("#00ff00", Color { a: 0.5, ..red }).as_deref(),
+            Some("#ff000080")
+        );
+        assert_eq!(format_color_like("#0f0f", red).as_deref(), Some("#f00f"));
+    }
+
+    #[test]
+    fn test_format_color_like_keeps_functional_shape() {
+        let color = Color::from_rgb8(58, 123, 213);
+
+        assert_eq!(
+            format_color_like("rgb(0, 0, 0)", color).as_deref(),
+            Some("rgb(58, 123, 213)")
+        );
+        assert_eq!(
+            format_color_like("RGB(0,0,0)", Color { a: 0.25, ..color })
+                .as_deref(),
+            Some("RGBA(58,123,213,0.25)")
+        );
+        assert_eq!(
+            format_color_like("rgba(0%, 0%, 0%, 50%)", Color::WHITE).as_deref(),
+            Some("rgba(100%, 100%, 100%, 100%)")
+        );
+        assert_eq!(format_color_like("not a color", color), None);
+    }
+
     #[test]
     fn test_line_without_color_yields_nothing() {
         assert!(color_literals("fn main() { let x = 42; }").is_empty());
@@ -0,0 +1,519 @@
+//! Column (box) selection: a rectangle of text spanning several lines,
+//! selected with `Alt+Shift+drag` or `Ctrl+Alt+Shift+Arrow`.
+//!
+//! The rectangle is kept in visual columns, where a tab counts as
+//! `TAB_WIDTH` columns and a wide character as many columns as it is drawn
+//! wide, so it lines up on screen whatever the lines contain. It becomes
+//! one cursor per line, selecting the part of the line the rectangle
+//! covers. A line that ends left of the rectangle gets its cursor in
+//! *virtual space*: the caret is drawn where the column would be, and the
+//! first text typed or pasted there pads the line with spaces up to the
+//! rectangle, in the same undo step as the edit.
+//!
+//! The rectangle lives until a message other than the ones listed in
+//! [`keeps_column_selection`] arrives; from then on its cursors are
+//! ordinary multi-cursors.
+
+use std::ops::RangeInclusive;
+
+use iced::Task;
+
+use crate::canvas_editor::editing::command::{Command, InsertTextCommand};
+use crate::canvas_editor::editing::cursor_set::Cursor;
+use crate::canvas_editor::{
+    ArrowDirection, CodeEditor, Message, measure_char_width, measure_text_width,
+};
+
+/// A rectangular selection, as two `(line, visual column)` corners.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct ColumnSelection {
+    /// Corner where the selection started.
+    pub(crate) anchor: (usize, usize),
+    /// Corner that follows the mouse or the arrow keys; its line holds the
+    /// primary cursor.
+    pub(crate) active: (usize, usize),
+}
+
+impl ColumnSelection {
+    /// Creates an empty selection at one corner.
+    pub(crate) fn new(corner: (usize, usize)) -> Self {
+        Self { anchor: corner, active: corner }
+    }
+
+    /// Returns the lines the rectangle spans.
+    pub(crate) fn lines(&self) -> RangeInclusive<usize> {
+        self.anchor.0.min(self.active.0)..=self.anchor.0.max(self.active.0)
+    }
+
+    /// Returns the rectangle's leftmost visual column.
+    pub(crate) fn left(&self) -> usize {
+        self.anchor.1.min(self.active.1)
+    }
+
+    /// Returns the rectangle's rightmost visual column.
+    pub(crate) fn right(&self) -> usize {
+        self.anchor.1.max(self.active.1)
+    }
+}
+
+/// Returns whether `message` leaves an active column selection in place.
+///
+/// Backspace and Delete are handled by the selection itself (see
+/// [`CodeEditor::handle_column_delete`]), and an empty [`Message::Paste`]
+/// only reads the clipboard before the real paste arrives.
+pub(crate) fn keeps_column_selection(message: &Message) -> bool {
+    match message {
+        Message::Paste(text) => text.is_empty(),
+        message => matches!(
+            message,
+            Message::ColumnSelectClick(_)
+                | Message::ColumnSelect(_)
+                | Message::MouseDrag(_)
+                | Message::MouseHover(_)
+                | Message::MouseRelease
+                | Message::Backspace
+                | Message::Delete
+                | Message::Copy
+                | Message::Tick
+                | Message::Scrolled(_)
+                | Message::HorizontalScrolled(_)
+                | Message::CanvasFocusGained
+                | Message::CanvasFocusLost
+                | Message::ImeOpened
+                | Message::ImePreedit(..)
+                | Message::ImeClosed
+        ),
+    }
+}
+
+/// Returns whether `message` inserts text at the cursors, so lines ending
+/// left of the selection must first be padded up to it.
+fn fills_virtual_space(message: &Message) -> bool {
+    matches!(
+        message,
+        Message::CharacterInput(_)
+            | Message::Paste(_)
+            | Message::Tab
+            | Message::ImeCommit(_)
+    )
+}
+
+impl CodeEditor {
+    /// Returns how many visual columns `ch` spans.
+    fn char_columns(&self, ch: char) -> usize {
+        let width =
+            measure_char_width(ch, self.full_char_width, self.char_width);
+        (width / self.char_width).round() as usize
+    }
+
+    /// Returns the visual column of the character boundary at `col`.
+    pub(crate) fn visual_column(&self, line: usize, col: usize) -> usize {
+        self.buffer
+            .line(line)
+            .chars()
+            .take(col)
+            .map(|ch| self.char_columns(ch))
+            .sum()
+    }
+
+    /// Maps a visual column to the character boundary nearest to it.
+    ///
+    /// # Returns
+    ///
+    /// The column, and how many visual columns past the end of the line
+    /// `visual` lies (`0` unless the line is shorter)
+    pub(crate) fn column_at_visual(
+        &self,
+        line: usize,
+        visual: usize,
+    ) -> (usize, usize) {
+        let mut start = 0;
+        for (col, ch) in self.buffer.line(line).chars().enumerate() {
+            let width = self.char_columns(ch);
+            if visual < start + width {
+                // Inside a tab or a wide character: snap to its nearer edge.
+                let after = (visual - start) * 2 >= width;
+                return (col + usize::from(after), 0);
+            }
+            start += width;
+        }
+        (self.buffer.line_len(line), visual - start)
+    }
+
+    /// Returns the visual column of the next character boundary after
+    /// `visual` on `line` (or before it when `forward` is `false`). Past
+    /// the end of the line, every column is a boundary.
+    fn next_visual_boundary(
+        &self,
+        line: usize,
+        visual: usize,
+        forward: bool,
+    ) -> usize {
+        let mut boundaries = vec![0];
+        for ch in self.buffer.line(line).chars() {
+            let last = boundaries.last().copied().unwrap_or(0);
+            boundaries.push(last + self.char_columns(ch));
+        }
+        let end = boundaries.last().copied().unwrap_or(0);
+        if forward {
+            boundaries
+                .into_iter()
+                .find(|&boundary| boundary > visual)
+                .unwrap_or(visual.max(end) + 1)
+        } else if visual > end {
+            visual - 1
+        } else {
+            boundaries
+                .into_iter()
+                .rev()
+                .find(|&boundary| boundary < visual)
+                .unwrap_or(0)
+        }
+    }
+
+    /// Replaces the cursors with one per line of `selection`, skipping
+    /// lines hidden by folds, and makes the one on the active corner's line
+    /// primary.
+    pub(crate) fn select_columns(&mut self, selection: ColumnSelection) {
+        let hidden = self.hidden_lines_set();
+        let (anchor_line, active_line) =
+            (selection.anchor.0, selection.active.0);
+        // Add the active line last so it ends up primary.
+        let lines: Vec<usize> = if anchor_line <= active_line {
+            selection.lines().collect()
+        } else {
+            selection.lines().rev().collect()
+        };
+        let mut first = true;
+        for line in lines {
+            if hidden.contains(&line) && line != active_line {
+                continue;
+            }
+            let (anchor, _) = self.column_at_visual(line, selection.anchor.1);
+            let (position, _) = self.column_at_visual(line, selection.active.1);
+            let cursor = Cursor {
+                position: (line, position),
+                anchor: (anchor != position).then_some((line, anchor)),
+            };
+            if first {
+                self.cursors.set_single(cursor.position);
+                *self.cursors.primary_mut() = cursor;
+                first = false;
+            } else {
+                self.cursors.add_cursor_with_selection(cursor);
+            }
+        }
+        self.overlay_cache.clear();
+    }
+
+    /// Returns how far past the end of its line the caret at `position` is
+    /// drawn, in visual columns: non-zero only for a cursor of the active
+    /// column selection on a line that ends left of the active corner.
+    pub(crate) fn column_virtual_space(
+        &self,
+        position: (usize, usize),
+    ) -> usize {
+        match self.column_selection {
+            Some(selection)
+                if selection.lines().contains(&position.0)
+                    && position.1 == self.buffer.line_len(position.0) =>
+            {
+                self.column_at_visual(position.0, selection.active.1).1
+            }
+            _ => 0,
+        }
+    }
+
+    /// Returns the `(line, visual column)` under a canvas point, counting
+    /// columns past the end of the line when wrapping is off.
+    fn column_at_point(&self, point: iced::Point) -> Option<(usize, usize)> {
+        let (line, col) = self.calculate_cursor_from_point(point)?;
+        let visual = self.visual_column(line, col);
+        if self.wrap_enabled || col < self.buffer.line_len(line) {
+            return Some((line, visual));
+        }
+        let x =
+            point.x - self.gutter_width() - 5.0 + self.horizontal_scroll_offset;
+        let line_width = measure_text_width(
+            self.buffer.line(line),
+            self.full_char_width,
+            self.char_width,
+        );
+        let past_end = ((x - line_width) / self.char_width).round().max(0.0);
+        Some((line, visual + past_end as usize))
+    }
+
+    /// Ends the active column selection before `message` is handled, unless
+    /// the message keeps it. Before text is inserted at its cursors, lines
+    /// ending left of the rectangle are padded with spaces up to it.
+    ///
+    /// The padding opens a history group without marking the editor as
+    /// grouping, so the handler's own edits join it whether or not they
+    /// group themselves; [`Self::update`] closes it afterwards unless the
+    /// handler left it open for a run of typing.
+    ///
+    /// # Returns
+    ///
+    /// `true` if lines were padded and the history group is open
+    pub(crate) fn prepare_column_selection(
+        &mut self,
+        message: &Message,
+    ) -> bool {
+        let Some(selection) = self.column_selection else {
+            return false;
+        };
+        if keeps_column_selection(message) {
+            return false;
+        }
+        self.column_selection = None;
+        if !fills_virtual_space(message) || !self.vim_accepts_insert_input() {
+            return false;
+        }
+
+        let left = selection.left();
+        let hidden = self.hidden_lines_set();
+        let mut padded = false;
+        for line in selection.lines() {
+            let width = self.visual_column(line, self.buffer.line_len(line));
+            if width >= left || hidden.contains(&line) {
+                continue;
+            }
+            if !padded {
+                self.end_grouping_if_active();
+                self.history.begin_group();
+                padded = true;
+            }
+            let col = self.buffer.line_len(line);
+            let mut cmd = InsertTextCommand::new(
+                line,
+                col,
+                " ".repeat(left - width),
+                (line, col),
+            );
+            let mut cursor = (line, col);
+            cmd.execute(&mut self.buffer, &mut cursor);
+            self.history.push(Box::new(cmd));
+        }
+        if padded {
+            self.select_columns(selection);
+            self.finish_edit_operation();
+        }
+        padded
+    }
+
+    /// Handles Alt+Shift+Click: starts a column selection at the clicked
+    /// position, extended by dragging.
+    ///
+    /// # Arguments
+    ///
+    /// * `point` - Canvas-local position of the click
+    ///
+    /// # Returns
+    ///
+    /// `Task::none()` — no async work needed
+    pub(crate) fn handle_column_select_click_msg(
+        &mut self,
+        point: iced::Point,
+    ) -> Task<Message> {
+        if self.vim_enabled {
+            return Task::none();
+        }
+        self.request_focus();
+        self.has_canvas_focus = true;
+        self.end_grouping_if_active();
+        if let Some(corner) = self.column_at_point(point) {
+            let selection = ColumnSelection::new(corner);
+            self.column_selection = Some(selection);
+            self.select_columns(selection);
+            self.is_dragging = true;
+        }
+        self.show_cursor = true;
+        self.reset_cursor_blink();
+        Task::none()
+    }
+
+    /// Moves the active corner of the column selection to the dragged
+    /// position.
+    ///
+    /// # Arguments
+    ///
+    /// * `point` - Canvas-local position of the mouse
+    pub(crate) fn drag_column_selection(&mut self, point: iced::Point) {
+        let (Some(mut selection), Some(corner)) =
+            (self.column_selection, self.column_at_point(point))
+        else {
+            return;
+        };
+        if selection.active != corner {
+            selection.active = corner;
+            self.column_selection = Some(selection);
+            self.select_columns(selection);
+        }
+    }
+
+    /// Handles Ctrl+Alt+Shift+Arrow: extends the column selection by one
+    /// line or one character, starting one from the primary cursor's
+    /// selection when none is active.
+    ///
+    /// # Arguments
+    ///
+    /// * `direction` - The direction to move the active corner in
+    ///
+    /// # Returns
+    ///
+    /// A `Task<Message>` that scrolls to keep the cursor visible
+    pub(crate) fn handle_column_select_msg(
+        &mut self,
+        direction: ArrowDirection,
+    ) -> Task<Message> {
+        if self.vim_enabled {
+            return Task::none();
+        }
+        self.end_grouping_if_active();
+        let mut selection = self.column_selection.unwrap_or_else(|| {
+            let cursor = self.cursors.primary();
+            let corner = |(line, col)| (line, self.visual_column(line, col));
+            ColumnSelection {
+                anchor: corner(cursor.anchor.unwrap_or(cursor.position)),
+                active: corner(cursor.position),
+            }
+        });
+        let (line, visual) = selection.active;
+        selection.active = match direction {
+            ArrowDirection::Up => (line.saturating_sub(1), visual),
+            ArrowDirection::Down => {
+                let last_line = self.buffer.line_count().saturating_sub(1);
+                ((line + 1).min(last_line), visual)
+            }
+            ArrowDirection::Left => {
+                (line, self.next_visual_boundary(line, visual, false))
+            }
+            ArrowDirection::Right => {
+                (line, self.next_visual_boundary(line, visual, true))
+            }
+        };
+        self.column_selection = Some(selection);
+        self.select_columns(selection);
+        self.finish_navigation_operation();
+        self.scroll_to_cursor()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_visual_columns_expand_tabs() {
+        let editor = CodeEditor::new("\tab\ncd", "txt");
+        assert_eq!(editor.visual_column(0, 1), 4);
+        assert_eq!(editor.column_at_visual(0, 5), (2, 0));
+        // Inside the tab: the nearer edge wins.
+        assert_eq!(editor.column_at_visual(0, 1), (0, 0));
+        assert_eq!(editor.column_at_visual(0, 3), (1, 0));
+        assert_eq!(editor.column_at_visual(1, 5), (2, 3));
+    }
+
+    #[test]
+    fn test_select_columns_puts_one_cursor_per_line() {
+        let mut editor = CodeEditor::new("abcdef\n\tx\nab", "txt");
+        editor
+            .select_columns(ColumnSelection { anchor: (0, 1), active: (2, 5) });
+
+        let cursor = |anchor, position| Cursor { position, anchor };
+        assert_eq!(
+            editor.cursors.as_slice(),
+            [
+                cursor(Some((0, 1)), (0, 5)),
+                cursor(Some((1, 0)), (1, 2)),
+                cursor(Some((2, 1)), (2, 2)),
+            ]
+        );
+        assert_eq!(editor.cursors.primary_position(), (2, 2));
+    }
+
+    #[test]
+    fn test_keyboard_column_selection_grows_from_the_primary_cursor() {
+        let mut editor = CodeEditor::new("abc\nabcdef\nabcdef", "txt");
+        editor.request_focus();
+        editor.has_canvas_focus = true;
+        editor.cursors.primary_mut().position = (0, 1);
+
+        let _ = editor.update(&Message::ColumnSelect(ArrowDirection::Down));
+        let _ = editor.update(&Message::ColumnSelect(ArrowDirection::Down));
+        let _ = editor.update(&Message::ColumnSelect(ArrowDirection::Right));
+        let _ = editor.update(&Message::ColumnSelect(ArrowDirection::Right));
+
+        assert_eq!(editor.cursors.len(), 3);
+        let _ = editor.update(&Message::CharacterInput('X'));
+        assert_eq!(editor.content(), "aX\naXdef\naXdef");
+    }
+
+    #[test]
+    fn test_typing_in_virtual_space_pads_short_lines() {
+        let mut editor = CodeEditor::new("long line\nab\nlonger line", "txt");
+        editor.request_focus();
+        editor.has_canvas_focus = true;
+        let selection = ColumnSelection { anchor: (0, 5), active: (2, 5) };
+        editor.column_selection = Some(selection);
+        editor.select_columns(selection);
+        assert_eq!(editor.column_virtual_space((1, 2)), 3);
+
+        let _ = editor.update(&Message::CharacterInput('|'));
+        assert_eq!(editor.content(), "long |line\nab   |\nlonge|r line");
+
+        let _ = editor.update(&Message::Undo);
+        assert_eq!(editor.content(), "long line\nab\nlonger line");
+    }
+
+    #[test]
+    fn test_backspace_in_a_column_skips_virtual_space() {
+        let mut editor = CodeEditor::new("abcd\na\nabcd", "txt");
+        let selection = ColumnSelection { anchor: (0, 3), active: (2, 3) };
+        editor.column_selection = Some(selection);
+        editor.select_columns(selection);
+
+        let _ = editor.update(&Message::Backspace);
+        assert_eq!(editor.content(), "abd\na\nabd");
+        assert_eq!(editor.column_virtual_space((1, 1)), 1);
+
+        let _ = editor.update(&Message::Delete);
+        assert_eq!(editor.content(), "ab\na\nab");
+        assert!(editor.column_selection.is_some());
+    }
+
+    #[test]
+    fn test_paste_distributes_lines_over_the_column() {
+        let mut editor = CodeEditor::new("ab\nab", "txt");
+        let selection = ColumnSelection { anchor: (0, 1), active: (1, 1) };
+        editor.column_selection = Some(selection);
+        editor.select_columns(selection);
+
+        let _ = editor.update(&Message::Paste("1\n2".to_string()));
+        assert_eq!(editor.content(), "a1b\na2b");
+        assert!(editor.column_selection.is_none());
+    }
+
+    #[test]
+    fn test_alt_shift_drag_selects_a_box_into_virtual_space() {
+        let mut editor = CodeEditor::new("abcdef\nab\nabcdef", "txt");
+        let point = |line: usize, col: usize| {
+            iced::Point::new(
+                editor.gutter_width() + 5.0 + col as f32 * editor.char_width,
+                (line as f32 + 0.5) * editor.line_height,
+            )
+        };
+        let (start, end) = (point(0, 1), point(2, 4));
+
+        let _ = editor.update(&Message::ColumnSelectClick(start));
+        let _ = editor.update(&Message::MouseDrag(end));
+        let _ = editor.update(&Message::MouseRelease);
+
+        assert_eq!(
+            editor.column_selection,
+            Some(ColumnSelection { anchor: (0, 1), active: (2, 4) })
+        );
+        assert_eq!(editor.get_selected_text(), Some("bcd".to_string()));
+        assert_eq!(editor.column_virtual_space((1, 2)), 2);
+    }
+}
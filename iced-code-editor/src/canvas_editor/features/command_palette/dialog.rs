@@ -10,7 +10,7 @@ use iced::{
     Shadow, Theme, Vector, keyboard,
 };
 
-use super::{CommandPaletteState, PaletteEntry};
+use super::{CommandPaletteState, PaletteEntry, PaletteMode};
 use crate::canvas_editor::Message;
 use crate::canvas_editor::render::view::scrollable_rail;
 use crate::i18n::Translations;
@@ -123,17 +123,27 @@ pub(crate) fn view<'a>(
         return Space::new().into();
     }
 
-    let query_input =
-        text_input(&translations.command_palette_placeholder(), &state.query)
-            .id(state.input_id.clone())
-            .on_input(Message::CommandPaletteChanged)
-            .on_submit(Message::SubmitCommandPalette)
-            .padding(8)
-            .size(14)
-            .width(Length::Fill);
+    let (placeholder, no_results) = match state.mode {
+        PaletteMode::Commands => (
+            translations.command_palette_placeholder(),
+            translations.command_palette_no_results(),
+        ),
+        PaletteMode::ClipboardHistory => (
+            translations.command_palette_clipboard_placeholder(),
+            translations.command_palette_clipboard_no_results(),
+        ),
+    };
+
+    let query_input = text_input(&placeholder, &state.query)
+        .id(state.input_id.clone())
+        .on_input(Message::CommandPaletteChanged)
+        .on_submit(Message::SubmitCommandPalette)
+        .padding(8)
+        .size(14)
+        .width(Length::Fill);
 
     let results: Element<'a, Message> = if entries.is_empty() {
-        container(text(translations.command_palette_no_results()).size(13))
+        container(text(no_results).size(13))
             .padding([6, 10])
             .width(Length::Fill)
             .into()
@@ -9,6 +9,10 @@
 //! The registry is extensible: a host application registers its own commands
 //! with [`CodeEditor::set_custom_command_palette_entries`] and receives
 //! [`Message::CommandPaletteAction`] carrying the entry's `id` when one is run.
+//!
+//! The same list also serves as the clipboard history picker
+//! (`Ctrl+Shift+V`): in [`PaletteMode::ClipboardHistory`] its rows are the
+//! recent copies and cuts, and running one pastes it.
 
 pub(crate) mod dialog;
 mod update;
@@ -18,15 +22,19 @@ use iced::widget::Id;
 use super::actions::{
     ADD_CURSOR_ABOVE_SHORTCUT, ADD_CURSOR_BELOW_SHORTCUT, ActionContext,
     COPY_SHORTCUT, CUT_SHORTCUT, DUPLICATE_LINE_DOWN_SHORTCUT,
-    DUPLICATE_LINE_UP_SHORTCUT, FIND_SHORTCUT, FOLD_ALL_SHORTCUT,
-    FOLD_AT_CURSOR_SHORTCUT, GOTO_LINE_SHORTCUT, MOVE_LINE_DOWN_SHORTCUT,
-    MOVE_LINE_UP_SHORTCUT, PASTE_SHORTCUT, REDO_SHORTCUT, REPLACE_SHORTCUT,
-    SAVE_SHORTCUT, SELECT_ALL_SHORTCUT, SELECT_NEXT_OCCURRENCE_SHORTCUT,
-    TOGGLE_COMMENT_SHORTCUT, TOGGLE_VIM_MODE_SHORTCUT, UNDO_SHORTCUT,
-    UNFOLD_ALL_SHORTCUT,
+    DUPLICATE_LINE_UP_SHORTCUT, EXPAND_SELECTION_SHORTCUT, FIND_SHORTCUT,
+    FOLD_ALL_SHORTCUT, FOLD_AT_CURSOR_SHORTCUT, GOTO_LINE_SHORTCUT,
+    MOVE_LINE_DOWN_SHORTCUT, MOVE_LINE_UP_SHORTCUT, NAVIGATE_BACK_SHORTCUT,
+    NAVIGATE_FORWARD_SHORTCUT, NEXT_HIGHLIGHT_SHORTCUT,
+    PASTE_FROM_HISTORY_SHORTCUT, PASTE_SHORTCUT, PREVIOUS_HIGHLIGHT_SHORTCUT,
+    REDO_SHORTCUT, REPLACE_SHORTCUT, SAVE_SHORTCUT, SELECT_ALL_SHORTCUT,
+    SELECT_NEXT_OCCURRENCE_SHORTCUT, SHRINK_SELECTION_SHORTCUT,
+    TOGGLE_BLOCK_COMMENT_SHORTCUT, TOGGLE_COMMENT_SHORTCUT,
+    TOGGLE_VIM_MODE_SHORTCUT, UNDO_SHORTCUT, UNFOLD_ALL_SHORTCUT,
 };
+use super::clipboard_history;
 use super::context_menu::ContextMenuItem;
-use crate::canvas_editor::{CodeEditor, Message};
+use crate::canvas_editor::{CodeEditor, Message, SortOrder, TextCase};
 use crate::i18n::Translations;
 
 /// What running a palette row does.
@@ -65,9 +73,21 @@ impl PaletteEntry {
     }
 }
 
+/// What the palette lists.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub(crate) enum PaletteMode {
+    /// Editor and host commands.
+    #[default]
+    Commands,
+    /// The editor's recent copies and cuts; running a row pastes it.
+    ClipboardHistory,
+}
+
 /// State owned by the command palette.
 #[derive(Debug, Clone)]
 pub(crate) struct CommandPaletteState {
+    /// What the rows are: commands or clipboard history entries.
+    pub(crate) mode: PaletteMode,
     /// Current filter text typed by the user.
     pub(crate) query: String,
     /// Whether the palette is visible.
@@ -90,6 +110,7 @@ pub(crate) struct CommandPaletteState {
 impl Default for CommandPaletteState {
     fn default() -> Self {
         Self {
+            mode: PaletteMode::Commands,
             query: String::new(),
             is_open: false,
             selected: 0,
@@ -106,12 +127,14 @@ impl CommandPaletteState {
         Self::default()
     }
 
-    /// Opens the palette with an empty query and the first row highlighted.
+    /// Opens the palette listing `mode`'s rows, with an empty query and the
+    /// first row highlighted.
     ///
     /// The query is always reset: a palette that reopened on the previous
     /// search would hide most commands behind a filter the user did not
     /// type this time.
-    pub(crate) fn open(&mut self) {
+    pub(crate) fn open(&mut self, mode: PaletteMode) {
+        self.mode = mode;
         self.query.clear();
         self.select_first_row();
         self.is_open = true;
@@ -240,6 +263,116 @@ fn default_entries(
             TOGGLE_COMMENT_SHORTCUT,
             Message::ToggleComment,
         ),
+        PaletteEntry::builtin(
+            translations.command_palette_toggle_block_comment(),
+            TOGGLE_BLOCK_COMMENT_SHORTCUT,
+            Message::ToggleBlockComment,
+        ),
+        PaletteEntry::builtin(
+            translations.command_palette_reindent_lines(),
+            "",
+            Message::ReindentLines,
+        ),
+        PaletteEntry::builtin(
+            translations.command_palette_sort_lines_ascending(),
+            "",
+            Message::SortLines(SortOrder::Ascending),
+        ),
+        PaletteEntry::builtin(
+            translations.command_palette_sort_lines_descending(),
+            "",
+            Message::SortLines(SortOrder::Descending),
+        ),
+        PaletteEntry::builtin(
+            translations.command_palette_sort_lines_natural(),
+            "",
+            Message::SortLines(SortOrder::Natural),
+        ),
+        PaletteEntry::builtin(
+            translations.command_palette_reverse_lines(),
+            "",
+            Message::ReverseLines,
+        ),
+        PaletteEntry::builtin(
+            translations.command_palette_remove_duplicate_lines(),
+            "",
+            Message::RemoveDuplicateLines,
+        ),
+        PaletteEntry::builtin(
+            translations.command_palette_join_lines(),
+            "",
+            Message::JoinLines,
+        ),
+        PaletteEntry::builtin(
+            translations.command_palette_transpose_characters(),
+            "",
+            Message::TransposeCharacters,
+        ),
+        PaletteEntry::builtin(
+            translations.command_palette_upper_case(),
+            "",
+            Message::ConvertCase(TextCase::Upper),
+        ),
+        PaletteEntry::builtin(
+            translations.command_palette_lower_case(),
+            "",
+            Message::ConvertCase(TextCase::Lower),
+        ),
+        PaletteEntry::builtin(
+            translations.command_palette_title_case(),
+            "",
+            Message::ConvertCase(TextCase::Title),
+        ),
+        PaletteEntry::builtin(
+            translations.command_palette_snake_case(),
+            "",
+            Message::ConvertCase(TextCase::Snake),
+        ),
+        PaletteEntry::builtin(
+            translations.command_palette_camel_case(),
+            "",
+            Message::ConvertCase(TextCase::Camel),
+        ),
+        PaletteEntry::builtin(
+            translations.command_palette_kebab_case(),
+            "",
+            Message::ConvertCase(TextCase::Kebab),
+        ),
+        PaletteEntry::builtin(
+            translations.command_palette_trim_trailing_whitespace(),
+            "",
+            Message::TrimTrailingWhitespace,
+        ),
+        PaletteEntry::builtin(
+            translations.command_palette_increment_number(),
+            "",
+            Message::IncrementNumber(1),
+        ),
+        PaletteEntry::builtin(
+            translations.command_palette_decrement_number(),
+            "",
+            Message::IncrementNumber(-1),
+        ),
+        PaletteEntry::builtin(
+            translations.command_palette_indent_lines(),
+            "",
+            Message::IndentLines,
+        ),
+        PaletteEntry::builtin(
+            translations.command_palette_outdent_lines(),
+            "",
+            Message::OutdentLines,
+        ),
+        PaletteEntry::builtin(
+            translations.command_palette_convert_indentation_to_spaces(),
+            "",
+            Message::ConvertIndentationToSpaces,
+        ),
+        PaletteEntry::builtin(
+            translations.command_palette_convert_indentation_to_tabs(),
+            "",
+            Message::ConvertIndentationToTabs,
+        ),
         PaletteEntry::builtin(
             translations.command_palette_move_line_up(),
             MOVE_LINE_UP_SHORTCUT,
@@ -265,6 +398,16 @@ fn default_entries(
             GOTO_LINE_SHORTCUT,
             Message::OpenGotoLine,
         ),
+        PaletteEntry::builtin(
+            translations.command_palette_navigate_back(),
+            NAVIGATE_BACK_SHORTCUT,
+            Message::NavigateBack,
+        ),
+        PaletteEntry::builtin(
+            translations.command_palette_navigate_forward(),
+            NAVIGATE_FORWARD_SHORTCUT,
+            Message::NavigateForward,
+        ),
         PaletteEntry::builtin(
             translations.command_palette_add_cursor_above(),
             ADD_CURSOR_ABOVE_SHORTCUT,
@@ -280,6 +423,26 @@ fn default_entries(
             SELECT_NEXT_OCCURRENCE_SHORTCUT,
             Message::SelectNextOccurrence,
         ),
+        PaletteEntry::builtin(
+            translations.command_palette_next_highlight(),
+            NEXT_HIGHLIGHT_SHORTCUT,
+            Message::NextDocumentHighlight,
+        ),
+        PaletteEntry::builtin(
+            translations.command_palette_previous_highlight(),
+            PREVIOUS_HIGHLIGHT_SHORTCUT,
+            Message::PreviousDocumentHighlight,
+        ),
+        PaletteEntry::builtin(
+            translations.command_palette_expand_selection(),
+            EXPAND_SELECTION_SHORTCUT,
+            Message::ExpandSelection,
+        ),
+        PaletteEntry::builtin(
+            translations.command_palette_shrink_selection(),
+            SHRINK_SELECTION_SHORTCUT,
+            Message::ShrinkSelection,
+        ),
         PaletteEntry::builtin(
             translations.command_palette_toggle_vim_mode(),
             TOGGLE_VIM_MODE_SHORTCUT,
@@ -294,6 +457,11 @@ fn default_entries(
             PASTE_SHORTCUT,
             Message::Paste(String::new()),
         ),
+        PaletteEntry::builtin(
+            translations.command_palette_paste_from_history(),
+            PASTE_FROM_HISTORY_SHORTCUT,
+            Message::OpenClipboardHistory,
+        ),
     ];
 
     if context.can_undo {
@@ -369,6 +537,21 @@ fn default_entries(
     entries
 }
 
+/// Builds one row per clipboard history entry, newest first, each pasting
+/// the full entry when run.
+fn clipboard_entries(history: &[String]) -> Vec<PaletteEntry> {
+    history
+        .iter()
+        .map(|text| PaletteEntry {
+            label: clipboard_history::preview(text),
+            shortcut: String::new(),
+            action: PaletteAction::Builtin(Box::new(Message::Paste(
+                text.clone(),
+            ))),
+        })
+        .collect()
+}
+
 /// Assembles the full command list: host-registered entries first, then the
 /// built-in ones when they are enabled.
 fn build_entries(
@@ -387,17 +570,26 @@ fn build_entries(
 impl CodeEditor {
     /// Returns the palette rows matching the current query, in display order.
     pub(crate) fn command_palette_entries(&self) -> Vec<PaletteEntry> {
-        build_entries(
-            self.custom_command_palette_entries(),
-            self.default_command_palette_enabled(),
-            self.action_context(),
-            &self.translations,
-        )
-        .into_iter()
-        .filter(|entry| {
-            matches_query(&entry.label, self.command_palette_state.query.trim())
-        })
-        .collect()
+        let entries = match self.command_palette_state.mode {
+            PaletteMode::Commands => build_entries(
+                self.custom_command_palette_entries(),
+                self.default_command_palette_enabled(),
+                self.action_context(),
+                &self.translations,
+            ),
+            PaletteMode::ClipboardHistory => {
+                clipboard_entries(self.clipboard_history())
+            }
+        };
+        entries
+            .into_iter()
+            .filter(|entry| {
+                matches_query(
+                    &entry.label,
+                    self.command_palette_state.query.trim(),
+                )
+            })
+            .collect()
     }
 
     /// Opens the command palette programmatically.
@@ -586,7 +778,7 @@ mod tests {
         state.selected = 3;
         state.first_visible_row = 3;
 
-        state.open();
+        state.open(PaletteMode::Commands);
 
         assert!(state.is_open);
         assert!(state.query.is_empty());
@@ -594,6 +786,19 @@ mod tests {
         assert_eq!(state.first_visible_row, 0);
     }
 
+    #[test]
+    fn test_clipboard_entries_paste_the_full_text() {
+        let entries =
+            clipboard_entries(&["a\n  b".to_string(), "c".to_string()]);
+
+        assert_eq!(labels(&entries), vec!["a ⏎ b", "c"]);
+        assert!(matches!(
+            &entries[0].action,
+            PaletteAction::Builtin(message)
+                if matches!(&**message, Message::Paste(text) if text == "a\n  b")
+        ));
+    }
+
     // ---- Scrolling the result list ----
     //
     // `window_origin` is the whole of the scroll arithmetic: the offset handed
@@ -4,12 +4,12 @@ use iced::Task;
 use iced::widget::operation::{focus, scroll_to};
 use iced::widget::scrollable::AbsoluteOffset;
 
-use super::PaletteAction;
 use super::dialog::rows_to_pixels;
+use super::{PaletteAction, PaletteMode};
 use crate::canvas_editor::{CodeEditor, Message};
 
 impl CodeEditor {
-    /// Opens the palette and focuses its input.
+    /// Opens the palette listing `mode`'s rows and focuses its input.
     ///
     /// The search and go-to-line dialogs are closed first: all three are
     /// modal input surfaces stacked over the same canvas, and only one can
@@ -19,14 +19,17 @@ impl CodeEditor {
     /// `overlay_cache`: search match highlights are canvas geometry, so they
     /// have to be erased. The palette itself is a widget in the view tree,
     /// which the cache knows nothing about.
-    pub(crate) fn handle_open_command_palette_msg(&mut self) -> Task<Message> {
+    pub(crate) fn handle_open_command_palette_msg(
+        &mut self,
+        mode: PaletteMode,
+    ) -> Task<Message> {
         if !self.command_palette_enabled {
             return Task::none();
         }
 
         self.search_state.close();
         self.goto_line_state.close();
-        self.command_palette_state.open();
+        self.command_palette_state.open(mode);
         self.overlay_cache.clear();
 
         Task::batch([
@@ -255,6 +258,44 @@ mod tests {
         assert!(!editor.command_palette_state.is_open);
     }
 
+    #[test]
+    fn test_clipboard_history_lists_copies_and_pastes_the_picked_one() {
+        let mut editor = CodeEditor::new("alpha beta", "txt");
+        for (start, end) in [(0, 5), (6, 10)] {
+            editor.cursors.primary_mut().anchor = Some((0, start));
+            editor.cursors.primary_mut().position = (0, end);
+            let _ = editor.update(&Message::Copy);
+        }
+
+        let _ = editor.update(&Message::OpenClipboardHistory);
+        assert!(editor.command_palette_state.is_open);
+        let labels: Vec<String> = editor
+            .command_palette_entries()
+            .into_iter()
+            .map(|entry| entry.label)
+            .collect();
+        assert_eq!(labels, ["beta", "alpha"]);
+
+        let _ = editor.update(&Message::CommandPaletteNavigate(true));
+        let _ = editor.update(&Message::SubmitCommandPalette);
+        assert!(!editor.command_palette_state.is_open);
+
+        // The submit only emits the paste; run it the way the host would.
+        let _ = editor.update(&Message::Paste("alpha".to_string()));
+        assert_eq!(editor.content(), "alpha alpha");
+    }
+
+    #[test]
+    fn test_opening_the_command_list_after_the_history_lists_commands() {
+        let mut editor = CodeEditor::new("fn main() {}", "rs");
+        let _ = editor.update(&Message::OpenClipboardHistory);
+        assert!(editor.command_palette_entries().is_empty());
+
+        let _ = editor.update(&Message::OpenCommandPalette);
+
+        assert!(!editor.command_palette_entries().is_empty());
+    }
+
     #[test]
     fn test_command_palette_action_is_left_to_the_host() {
         let mut editor = CodeEditor::new("fn main() {}", "rs");
@@ -0,0 +1,354 @@
+//! Comment tokens of each language.
+//!
+//! Toggling comments looks the tokens of the editor's syntax up in three
+//! places, in order:
+//!
+//! 1. tokens registered with [`CodeEditor::set_comment_tokens`];
+//! 2. the built-in table ([`CommentTokens::builtin`]);
+//! 3. the syntect grammar of the syntax, so languages only known to the
+//!    grammar set (such as the extra `two-face` syntaxes) work without
+//!    configuration.
+//!
+//! syntect's packed syntax sets do not keep the `tmPreferences` files that
+//! name comment markers, so the grammar is asked instead: a few common
+//! markers are highlighted on a probe line, and the first one the grammar
+//! scopes as `comment` wins. Results are cached per syntax for the process.
+
+use std::collections::HashMap;
+use std::sync::{Mutex, OnceLock, PoisonError};
+
+use syntect::parsing::{ParseState, Scope, ScopeStack, SyntaxReference};
+
+use crate::canvas_editor::CodeEditor;
+use crate::canvas_editor::render::syntax::{find_syntax, syntax_set};
+
+/// Line-comment markers probed against a grammar, in order of preference.
+const LINE_CANDIDATES: &[&str] = &["//", "#", "--", ";", "%", "'"];
+
+/// Block-comment marker pairs probed against a grammar, in order of
+/// preference.
+const BLOCK_CANDIDATES: &[(&str, &str)] = &[
+    ("/*", "*/"),
+    ("<!--", "-->"),
+    ("--[[", "]]"),
+    ("{-", "-}"),
+    ("(*", "*)"),
+    ("#|", "|#"),
+];
+
+/// Tokens found in the grammars, keyed by syntax name.
+static GRAMMAR_TOKENS: OnceLock<Mutex<HashMap<String, CommentTokens>>> =
+    OnceLock::new();
+
+/// The comment tokens of a language.
+///
+/// # Example
+///
+/// ```
+/// use iced_code_editor::{CodeEditor, CommentTokens};
+///
+/// let mut editor = CodeEditor::new("", "nim");
+/// editor.set_comment_tokens(
+///     "nim",
+///     CommentTokens::new().with_line("#").with_block("#[", "]#"),
+/// );
+/// assert_eq!(editor.comment_tokens().line.as_deref(), Some("#"));
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CommentTokens {
+    /// The token starting a comment that runs to the end of the line.
+    pub line: Option<String>,
+    /// The tokens opening and closing a block comment.
+    pub block: Option<(String, String)>,
+}
+
+impl CommentTokens {
+    /// Creates an empty token set: the language has no comments.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the line-comment token.
+    ///
+    /// # Arguments
+    ///
+    /// * `token` - The token, e.g. `"//"`
+    #[must_use]
+    pub fn with_line(mut self, token: &str) -> Self {
+        self.line = Some(token.to_string());
+        self
+    }
+
+    /// Sets the block-comment tokens.
+    ///
+    /// # Arguments
+    ///
+    /// * `open` - The opening token, e.g. `"/*"`
+    /// * `close` - The closing token, e.g. `"*/"`
+    #[must_use]
+    pub fn with_block(mut self, open: &str, close: &str) -> Self {
+        self.block = Some((open.to_string(), close.to_string()));
+        self
+    }
+
+    /// Returns `true` when neither a line nor a block token is set.
+    pub fn is_empty(&self) -> bool {
+        self.line.is_none() && self.block.is_none()
+    }
+
+    /// Returns the built-in tokens of a syntax.
+    ///
+    /// Accepts both file extensions (`"rs"`) and language names (`"rust"`),
+    /// compared case-insensitively.
+    ///
+    /// # Returns
+    ///
+    /// The tokens, or `None` when the table does not know the syntax
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use iced_code_editor::CommentTokens;
+    ///
+    /// let html = CommentTokens::builtin("html").unwrap_or_default();
+    /// assert_eq!(html.line, None);
+    /// assert_eq!(
+    ///     html.block,
+    ///     Some(("<!--".to_string(), "-->".to_string()))
+    /// );
+    /// ```
+    pub fn builtin(syntax: &str) -> Option<Self> {
+        let tokens = match syntax.to_lowercase().as_str() {
+            "rs" | "rust" | "js" | "javascript" | "ts" | "typescript"
+            | "jsx" | "tsx" | "go" | "c" | "h" | "cpp" | "cc" | "cxx"
+            | "hpp" | "c++" | "java" | "cs" | "csharp" | "kt" | "kotlin"
+            | "swift" | "php" | "scss" => {
+                Self::new().with_line("//").with_block("/*", "*/")
+            }
+            "css" => Self::new().with_block("/*", "*/"),
+            "py" | "python" | "sh" | "bash" | "shell" | "zsh" | "rb"
+            | "ruby" | "toml" | "yaml" | "yml" => Self::new().with_line("#"),
+            "lua" => Self::new().with_line("--").with_block("--[[", "]]"),
+            "sql" => Self::new().with_line("--").with_block("/*", "*/"),
+            "hs" | "haskell" => {
+                Self::new().with_line("--").with_block("{-", "-}")
+            }
+            "html" | "htm" | "xml" | "svg" | "vue" | "md" | "markdown" => {
+                Self::new().with_block("<!--", "-->")
+            }
+            _ => return None,
+        };
+        Some(tokens)
+    }
+
+    /// Returns the tokens the syntect grammar of a syntax scopes as
+    /// comments, computed once per syntax.
+    ///
+    /// # Returns
+    ///
+    /// The tokens found; empty when the syntax is unknown or none of the
+    /// probed markers is a comment in its grammar
+    pub(crate) fn from_grammar(syntax: &str) -> Self {
+        let cache = GRAMMAR_TOKENS.get_or_init(|| Mutex::new(HashMap::new()));
+        if let Some(tokens) =
+            cache.lock().unwrap_or_else(PoisonError::into_inner).get(syntax)
+        {
+            return tokens.clone();
+        }
+        let tokens = probe_grammar(syntax);
+        cache
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .insert(syntax.to_string(), tokens.clone());
+        tokens
+    }
+}
+
+/// Highlights `lines` with `syntax` and reports, for each probe, whether
+/// the character at that byte offset is inside a `comment` scope.
+///
+/// # Arguments
+///
+/// * `syntax` - The grammar to parse with
+/// * `lines` - The probe text, one entry per line
+/// * `probes` - `(line, byte)` offsets to test, in document order
+fn comment_at(
+    syntax: &SyntaxReference,
+    lines: &[&str],
+    probes: &[(usize, usize)],
+) -> Option<Vec<bool>> {
+    let comment = Scope::new("comment").ok()?;
+    let mut state = ParseState::new(syntax);
+    let mut stack = ScopeStack::new();
+    let mut result = Vec::with_capacity(probes.len());
+    for (index, line) in lines.iter().enumerate() {
+        let ops = state.parse_line(&format!("{line}\n"), syntax_set()).ok()?;
+        let mut ops = ops.iter().peekable();
+        for &(_, byte) in probes.iter().filter(|(line, _)| *line == index) {
+            while let Some((_, op)) = ops.next_if(|(at, _)| *at <= byte) {
+                stack.apply(op).ok()?;
+            }
+            result.push(
+                stack.as_slice().iter().any(|s| comment.is_prefix_of(*s)),
+            );
+        }
+        for (_, op) in ops {
+            stack.apply(op).ok()?;
+        }
+    }
+    Some(result)
+}
+
+/// Finds the comment tokens of a syntax by probing its grammar.
+fn probe_grammar(syntax: &str) -> CommentTokens {
+    let syntax = find_syntax(syntax_set(), syntax);
+    let line = LINE_CANDIDATES.iter().find(|token| {
+        let text = format!("{token} x");
+        comment_at(syntax, &[&text, "x"], &[(0, token.len() + 1), (1, 0)])
+            .is_some_and(|found| found == [true, false])
+    });
+    let block = BLOCK_CANDIDATES.iter().find(|(open, close)| {
+        let text = format!("{open} x {close} y");
+        let inside = open.len() + 1;
+        let after = inside + 3 + close.len();
+        comment_at(syntax, &[&text], &[(0, inside), (0, after)])
+            .is_some_and(|found| found == [true, false])
+    });
+    CommentTokens {
+        line: line.map(|token| (*token).to_string()),
+        block: block
+            .map(|(open, close)| ((*open).to_string(), (*close).to_string())),
+    }
+}
+
+impl CodeEditor {
+    /// Sets the comment tokens of a language.
+    ///
+    /// They replace the built-in tokens of that language and whatever its
+    /// grammar provides, for every editor syntax with that name, compared
+    /// case-insensitively.
+    ///
+    /// # Arguments
+    ///
+    /// * `language` - The syntax the tokens apply to, e.g. `"rs"`
+    /// * `tokens` - The tokens
+    pub fn set_comment_tokens(
+        &mut self,
+        language: &str,
+        tokens: CommentTokens,
+    ) {
+        self.comment_tokens.insert(language.to_lowercase(), tokens);
+    }
+
+    /// Removes the tokens set for a language, restoring the built-in or
+    /// grammar-provided ones.
+    ///
+    /// # Arguments
+    ///
+    /// * `language` - The syntax whose tokens are removed
+    pub fn clear_comment_tokens(&mut self, language: &str) {
+        self.comment_tokens.remove(&language.to_lowercase());
+    }
+
+    /// Returns the comment tokens in effect for the editor's syntax.
+    ///
+    /// # Returns
+    ///
+    /// The tokens set with [`CodeEditor::set_comment_tokens`], else the
+    /// built-in ones, else those found in the syntax's grammar; empty when
+    /// the language has no known comments
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use iced_code_editor::CodeEditor;
+    ///
+    /// let tokens = CodeEditor::new("", "rs").comment_tokens();
+    /// assert_eq!(tokens.line.as_deref(), Some("//"));
+    /// assert!(CodeEditor::new("", "txt").comment_tokens().is_empty());
+    /// ```
+    pub fn comment_tokens(&self) -> CommentTokens {
+        let syntax = self.syntax.to_lowercase();
+        if let Some(tokens) = self.comment_tokens.get(&syntax) {
+            return tokens.clone();
+        }
+        CommentTokens::builtin(&syntax)
+            .unwrap_or_else(|| CommentTokens::from_grammar(&syntax))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn line(syntax: &str) -> Option<String> {
+        CodeEditor::new("", syntax).comment_tokens().line
+    }
+
+    fn block(syntax: &str) -> Option<(String, String)> {
+        CodeEditor::new("", syntax).comment_tokens().block
+    }
+
+    fn pair(open: &str, close: &str) -> (String, String) {
+        (open.to_string(), close.to_string())
+    }
+
+    #[test]
+    fn test_builtin_tokens() {
+        for syntax in ["rs", "rust", "ts", "go", "c", "cpp", "java", "cs"] {
+            assert_eq!(line(syntax).as_deref(), Some("//"), "{syntax}");
+            assert_eq!(block(syntax), Some(pair("/*", "*/")), "{syntax}");
+        }
+        for syntax in ["py", "python", "sh", "bash", "rb", "toml", "yml"] {
+            assert_eq!(line(syntax).as_deref(), Some("#"), "{syntax}");
+        }
+        assert_eq!(line("lua").as_deref(), Some("--"));
+        assert_eq!(block("lua"), Some(pair("--[[", "]]")));
+        assert_eq!(line("html"), None);
+        assert_eq!(block("html"), Some(pair("<!--", "-->")));
+        assert_eq!(line("css"), None);
+        assert!(CodeEditor::new("", "txt").comment_tokens().is_empty());
+    }
+
+    #[test]
+    fn test_grammar_tokens_for_languages_without_builtins() {
+        // OCaml and LaTeX have no built-in entry; their grammars know them.
+        assert_eq!(CommentTokens::builtin("ml"), None);
+        assert_eq!(block("ml"), Some(pair("(*", "*)")));
+        assert_eq!(line("tex").as_deref(), Some("%"));
+        assert_eq!(block("tex"), None);
+        // Asking again is served from the cache.
+        assert_eq!(block("ml"), Some(pair("(*", "*)")));
+    }
+
+    #[test]
+    fn test_grammar_agrees_with_builtins() {
+        assert_eq!(
+            CommentTokens::from_grammar("rs").line.as_deref(),
+            Some("//")
+        );
+        assert_eq!(
+            CommentTokens::from_grammar("rs").block,
+            Some(pair("/*", "*/"))
+        );
+        assert_eq!(
+            CommentTokens::from_grammar("py").line.as_deref(),
+            Some("#")
+        );
+        assert_eq!(
+            CommentTokens::from_grammar("html").block,
+            Some(pair("<!--", "-->"))
+        );
+    }
+
+    #[test]
+    fn test_registered_tokens_win_and_can_be_cleared() {
+        let mut editor = CodeEditor::new("", "RS");
+        editor.set_comment_tokens("rs", CommentTokens::new().with_line("#"));
+        assert_eq!(editor.comment_tokens().line.as_deref(), Some("#"));
+        assert_eq!(editor.comment_tokens().block, None);
+
+        editor.clear_comment_tokens("Rs");
+        assert_eq!(editor.comment_tokens().line.as_deref(), Some("//"));
+    }
+}
@@ -0,0 +1,252 @@
+//! Document colors: the color literals a language server reports.
+//!
+//! Inline color previews are found lexically by
+//! [`color_preview`](super::color_preview), which only knows notations such as
+//! `#rrggbb` or `rgb(…)`. A language server knows more — `Color::RED`, a named
+//! CSS color, a `Color32::from_rgb(…)` call — and reports them in reply to
+//! `textDocument/documentColor`. Those replies are requested once the buffer
+//! has stopped changing for [`DOCUMENT_COLOR_DELAY`], handed back through
+//! [`CodeEditor::set_lsp_document_colors`], and merged with the lexical
+//! literals: a swatch is drawn, and the color picker opens, for both.
+
+#[cfg(not(target_arch = "wasm32"))]
+use std::time::{Duration, Instant};
+#[cfg(target_arch = "wasm32")]
+use {std::time::Duration, web_time::Instant};
+
+use super::color_preview::{ColorLiteral, LineLiterals};
+use crate::canvas_editor::CodeEditor;
+use crate::canvas_editor::lsp::LspDocumentColor;
+
+/// How long the buffer must stay unchanged before the language server is
+/// asked for its colors again.
+pub(crate) const DOCUMENT_COLOR_DELAY: Duration = Duration::from_millis(500);
+
+/// A color literal on the page, lexical or reported by the server.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) struct PlacedColor {
+    /// The literal's columns and color.
+    pub literal: ColorLiteral,
+    /// Whether the language server reported it, in which case only the
+    /// server can tell how to rewrite it.
+    pub from_server: bool,
+}
+
+/// Document-color state owned by the editor.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct DocumentColorState {
+    /// Colors reported by the language server, as `(line, literal)` pairs.
+    colors: Vec<(usize, ColorLiteral)>,
+    /// Buffer revision the last server request was sent for.
+    pub(crate) requested: Option<u64>,
+    /// When the buffer was last edited, for the request debounce.
+    edited_at: Option<Instant>,
+}
+
+impl DocumentColorState {
+    /// Drops the server's colors and forgets what was requested, so the next
+    /// tick asks again.
+    pub(crate) fn clear(&mut self) {
+        *self = Self::default();
+    }
+
+    /// Drops the server's colors, whose positions an edit has invalidated,
+    /// and restarts the request debounce.
+    pub(crate) fn mark_edited(&mut self) {
+        self.colors.clear();
+        self.edited_at = Some(Instant::now());
+    }
+}
+
+impl CodeEditor {
+    /// Asks the language server for the document's colors once the buffer
+    /// has rested for [`DOCUMENT_COLOR_DELAY`]; called on every
+    /// [`Message::Tick`](crate::canvas_editor::Message::Tick).
+    pub(crate) fn refresh_document_colors(&mut self) {
+        if !self.show_color_previews || self.lsp_client.is_none() {
+            return;
+        }
+
+        let state = &self.document_color_state;
+        if state.requested == Some(self.buffer_revision)
+            || state
+                .edited_at
+                .is_some_and(|at| at.elapsed() < DOCUMENT_COLOR_DELAY)
+        {
+            return;
+        }
+        self.lsp_request_document_colors();
+    }
+
+    /// Replaces the server-provided colors with a language server's answer.
+    ///
+    /// Feed this the payload of a `textDocument/documentColor` response (with
+    /// the bundled client, [`LspEvent::DocumentColors`]). Each color gets a
+    /// swatch, and clicking the swatch opens the color picker on it. Ranges
+    /// are read in UTF-16 code units, as servers send them; colors spanning
+    /// several lines are ignored. A reply that arrives after the buffer was
+    /// edited answers text that no longer exists, and is dropped.
+    ///
+    /// # Arguments
+    ///
+    /// * `colors` - The colors reported by the server
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use iced::Color;
+    /// use iced_code_editor::{
+    ///     CodeEditor, LspDocumentColor, LspPosition, LspRange,
+    /// };
+    ///
+    /// let mut editor = CodeEditor::new("let c = Color::RED;", "rs");
+    /// editor.lsp_request_document_colors();
+    ///
+    /// // `Color::RED` now gets a swatch.
+    /// editor.set_lsp_document_colors(&[LspDocumentColor {
+    ///     range: LspRange {
+    ///         start: LspPosition { line: 0, character: 8 },
+    ///         end: LspPosition { line: 0, character: 18 },
+    ///     },
+    ///     color: Color::from_rgb(1.0, 0.0, 0.0),
+    /// }]);
+    /// ```
+    ///
+    /// [`LspEvent::DocumentColors`]: crate::LspEvent::DocumentColors
+    pub fn set_lsp_document_colors(&mut self, colors: &[LspDocumentColor]) {
+        if !self.show_color_previews
+            || self.document_color_state.requested != Some(self.buffer_revision)
+        {
+            return;
+        }
+
+        let mut resolved: Vec<(usize, ColorLiteral)> = colors
+            .iter()
+            .filter_map(|color| {
+                let start = self.position_from_lsp(color.range.start);
+                let end = self.position_from_lsp(color.range.end);
+                (start.0 == end.0 && start.1 < end.1).then_some((
+                    start.0,
+                    ColorLiteral {
+                        start_col: start.1,
+                        end_col: end.1,
+                        color: color.color,
+                    },
+                ))
+            })
+            .collect();
+        resolved.sort_by_key(|(line, literal)| (*line, literal.start_col));
+        self.document_color_state.colors = resolved;
+        self.content_cache.clear();
+    }
+
+    /// Returns every color on `line`: the server's, then the lexical
+    /// literals that do not overlap one of them, ordered by column.
+    ///
+    /// # Arguments
+    ///
+    /// * `literals` - Memo of the lexical literals of the line last looked at
+    /// * `line` - Logical line to look at
+    pub(crate) fn placed_colors(
+        &self,
+        literals: &mut LineLiterals,
+        line: usize,
+    ) -> Vec<PlacedColor> {
+        let mut placed: Vec<PlacedColor> = self
+            .document_color_state
+            .colors
+            .iter()
+            .filter(|(color_line, _)| *color_line == line)
+            .map(|(_, literal)| PlacedColor {
+                literal: *literal,
+                from_server: true,
+            })
+            .collect();
+        let server_count = placed.len();
+
+        for literal in literals.get(&self.buffer, line) {
+            let overlaps = placed[..server_count].iter().any(|server| {
+                literal.start_col < server.literal.end_col
+                    && server.literal.start_col < literal.end_col
+            });
+            if !overlaps {
+                placed.push(PlacedColor {
+                    literal: *literal,
+                    from_server: false,
+                });
+            }
+        }
+
+        placed.sort_by_key(|color| color.literal.start_col);
+        placed
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use iced::Color;
+
+    use super::LineLiterals;
+    use crate::canvas_editor::lsp::{LspDocumentColor, LspPosition, LspRange};
+    use crate::canvas_editor::{CodeEditor, Message};
+
+    /// Returns a server color spanning `start..end` of line 0.
+    fn server_color(start: u32, end: u32) -> LspDocumentColor {
+        LspDocumentColor {
+            range: LspRange {
+                start: LspPosition { line: 0, character: start },
+                end: LspPosition { line: 0, character: end },
+            },
+            color: Color::from_rgb(1.0, 0.0, 0.0),
+        }
+    }
+
+    #[test]
+    fn test_server_colors_merge_with_lexical_literals() {
+        let mut editor = CodeEditor::new("Color::RED, #00ff00, #0000ff", "rs");
+        editor.lsp_request_document_colors();
+        // The server reports `Color::RED` and also the last hex literal.
+        editor.set_lsp_document_colors(&[
+            server_color(0, 10),
+            server_color(21, 28),
+        ]);
+
+        let placed = editor.placed_colors(&mut LineLiterals::default(), 0);
+        let spans: Vec<(usize, usize, bool)> = placed
+            .iter()
+            .map(|color| {
+                (
+                    color.literal.start_col,
+                    color.literal.end_col,
+                    color.from_server,
+                )
+            })
+            .collect();
+
+        assert_eq!(spans, [(0, 10, true), (12, 19, false), (21, 28, true)]);
+    }
+
+    #[test]
+    fn test_stale_or_multiline_server_colors_are_dropped() {
+        let mut editor = CodeEditor::new("Color::RED\n", "rs");
+        editor.lsp_request_document_colors();
+        // The reply answers the text as it was before this edit.
+        let _ = editor.update(&Message::Paste(" ".to_string()));
+        editor.set_lsp_document_colors(&[server_color(1, 11)]);
+        assert!(
+            editor.placed_colors(&mut LineLiterals::default(), 0).is_empty()
+        );
+
+        editor.lsp_request_document_colors();
+        editor.set_lsp_document_colors(&[LspDocumentColor {
+            range: LspRange {
+                start: LspPosition { line: 0, character: 1 },
+                end: LspPosition { line: 1, character: 0 },
+            },
+            color: Color::BLACK,
+        }]);
+        assert!(
+            editor.placed_colors(&mut LineLiterals::default(), 0).is_empty()
+        );
+    }
+}
@@ -0,0 +1,481 @@
+//! Occurrence highlighting for the symbol under the cursor.
+//!
+//! Once the primary cursor has rested on an identifier for
+//! [`DOCUMENT_HIGHLIGHT_DELAY`], every occurrence of that symbol is painted
+//! behind the text, and `F7`/`Shift+F7` step between them. Occurrences come
+//! from two sources:
+//!
+//! * **Lexical**: every whole-word match of the identifier in the buffer.
+//!   Computed immediately, and the only source when no language server is
+//!   attached.
+//! * **Language server**: the reply to `textDocument/documentHighlight`,
+//!   handed back through [`CodeEditor::set_lsp_document_highlights`]. It
+//!   replaces the lexical guess once it arrives, and only the server can tell
+//!   read occurrences from write ones.
+//!
+//! Highlights survive cursor movement within any of the highlighted
+//! occurrences (so stepping between them does not re-request anything) and
+//! are dropped as soon as the cursor leaves them or the buffer is edited.
+
+use iced::Task;
+#[cfg(not(target_arch = "wasm32"))]
+use std::time::{Duration, Instant};
+#[cfg(target_arch = "wasm32")]
+use {std::time::Duration, web_time::Instant};
+
+use crate::buffer::TextBuffer;
+use crate::canvas_editor::lsp::{
+    LspDocumentHighlight, LspDocumentHighlightKind,
+};
+use crate::canvas_editor::{CodeEditor, Message};
+
+/// How long the primary cursor must stay put before its symbol's occurrences
+/// are looked up.
+///
+/// Long enough that holding an arrow key or typing does not fire a server
+/// request per keystroke, short enough to feel immediate once the cursor
+/// stops.
+pub(crate) const DOCUMENT_HIGHLIGHT_DELAY: Duration =
+    Duration::from_millis(250);
+
+/// Maximum number of lexical occurrences collected for one identifier.
+///
+/// Bounds the scan result for a name repeated throughout a huge generated
+/// file; past this many, further highlights add nothing a reader can use.
+const MAX_LEXICAL_HIGHLIGHTS: usize = 1_000;
+
+/// One highlighted occurrence, in buffer coordinates.
+///
+/// Positions are `(line, column)` pairs with columns counted in characters,
+/// like the editor's cursors; `end` is exclusive.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct Occurrence {
+    /// Position of the occurrence's first character.
+    pub(crate) start: (usize, usize),
+    /// Position one past the occurrence's last character.
+    pub(crate) end: (usize, usize),
+    /// How the occurrence uses the symbol; always
+    /// [`LspDocumentHighlightKind::Text`] for lexical matches.
+    pub(crate) kind: LspDocumentHighlightKind,
+}
+
+impl Occurrence {
+    /// Returns `true` when a caret at `position` touches this occurrence,
+    /// including a caret sitting just after its last character.
+    fn touches(&self, position: (usize, usize)) -> bool {
+        self.start <= position && position <= self.end
+    }
+}
+
+/// Document-highlight state owned by the editor.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct DocumentHighlightState {
+    /// Occurrences currently painted, sorted by start position.
+    pub(crate) occurrences: Vec<Occurrence>,
+    /// Primary cursor position seen on the last tick, and when the cursor
+    /// arrived there.
+    observed: Option<((usize, usize), Instant)>,
+    /// Whether occurrences were already looked up for the current resting
+    /// position, so a cursor that stays put is not looked up every tick.
+    resolved: bool,
+}
+
+impl DocumentHighlightState {
+    /// Drops every occurrence and forgets where the cursor rested.
+    pub(crate) fn clear(&mut self) {
+        self.occurrences.clear();
+        self.observed = None;
+        self.resolved = false;
+    }
+
+    /// Returns `true` when `position` touches one of the occurrences.
+    fn touches(&self, position: (usize, usize)) -> bool {
+        self.occurrences.iter().any(|occurrence| occurrence.touches(position))
+    }
+}
+
+/// Finds every whole-word occurrence of `word` in `buffer`.
+///
+/// A match only counts when the characters on either side of it are not
+/// word characters, so `count` does not highlight `counter` or `recount`.
+/// Stops after [`MAX_LEXICAL_HIGHLIGHTS`] matches.
+///
+/// # Arguments
+///
+/// * `buffer` - The text to scan
+/// * `word` - The identifier to look for; an empty word matches nothing
+///
+/// # Returns
+///
+/// The matches in document order, all of kind
+/// [`LspDocumentHighlightKind::Text`]
+pub(crate) fn lexical_occurrences(
+    buffer: &TextBuffer,
+    word: &str,
+) -> Vec<Occurrence> {
+    let word: Vec<char> = word.chars().collect();
+    let mut occurrences = Vec::new();
+    if word.is_empty() {
+        return occurrences;
+    }
+
+    for line in 0..buffer.line_count() {
+        let chars: Vec<char> = buffer.line(line).chars().collect();
+        let mut col = 0;
+        while col + word.len() <= chars.len() {
+            let end = col + word.len();
+            let bounded_before =
+                col == 0 || !CodeEditor::is_word_char(chars[col - 1]);
+            let bounded_after =
+                end == chars.len() || !CodeEditor::is_word_char(chars[end]);
+            if bounded_before && bounded_after && chars[col..end] == word[..] {
+                occurrences.push(Occurrence {
+                    start: (line, col),
+                    end: (line, end),
+                    kind: LspDocumentHighlightKind::Text,
+                });
+                if occurrences.len() >= MAX_LEXICAL_HIGHLIGHTS {
+                    return occurrences;
+                }
+                col = end;
+            } else {
+                col += 1;
+            }
+        }
+    }
+    occurrences
+}
+
+impl CodeEditor {
+    /// Advances the document-highlight debounce; called on every
+    /// [`Message::Tick`].
+    ///
+    /// Moving the cursor off the highlighted occurrences clears them at once;
+    /// occurrences for the new position are only looked up after the cursor
+    /// has rested there for [`DOCUMENT_HIGHLIGHT_DELAY`]. The same resting
+    /// point is when the language server is asked for linked editing ranges.
+    pub(crate) fn refresh_document_highlights(&mut self) {
+        if !self.document_highlight_enabled && !self.linked_editing_enabled {
+            return;
+        }
+
+        let position = self.cursors.primary_position();
+        let state = &mut self.document_highlight_state;
+        match state.observed {
+            Some((observed, since)) if observed == position => {
+                if state.resolved || since.elapsed() < DOCUMENT_HIGHLIGHT_DELAY
+                {
+                    return;
+                }
+            }
+            _ => {
+                state.observed = Some((position, Instant::now()));
+                if !state.touches(position) {
+                    state.resolved = false;
+                    if !state.occurrences.is_empty() {
+                        state.occurrences.clear();
+                        self.overlay_cache.clear();
+                    }
+                }
+                return;
+            }
+        }
+
+        if self.document_highlight_enabled {
+            self.resolve_document_highlights();
+        } else {
+            self.document_highlight_state.resolved = true;
+        }
+        self.request_linked_editing_ranges();
+    }
+
+    /// Looks up the occurrences of the identifier under the primary cursor.
+    ///
+    /// The lexical occurrences are shown straight away; when a language
+    /// server is attached it is asked as well, and its answer replaces them.
+    fn resolve_document_highlights(&mut self) {
+        self.document_highlight_state.resolved = true;
+
+        let (line, col) = self.cursors.primary_position();
+        let text = self.buffer.line(line);
+        let start = Self::word_start_in_line(text, col);
+        let end = Self::word_end_in_line(text, col);
+        let word: String = text.chars().skip(start).take(end - start).collect();
+
+        self.document_highlight_state.occurrences =
+            lexical_occurrences(&self.buffer, &word);
+        self.overlay_cache.clear();
+
+        if !word.is_empty() {
+            self.lsp_request_document_highlight();
+        }
+    }
+
+    /// Replaces the highlighted occurrences with a language server's answer.
+    ///
+    /// Feed this the payload of a `textDocument/documentHighlight` response
+    /// (with the bundled client, [`LspEvent::DocumentHighlight`]). Ranges are
+    /// read in UTF-16 code units, as servers send them. A reply that does not
+    /// touch the primary cursor answers a position the cursor has since left,
+    /// and is ignored; an empty reply clears the highlights.
+    ///
+    /// # Arguments
+    ///
+    /// * `highlights` - The occurrences reported by the server
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use iced_code_editor::{
+    ///     CodeEditor, LspDocumentHighlight, LspDocumentHighlightKind,
+    ///     LspPosition, LspRange,
+    /// };
+    ///
+    /// let mut editor = CodeEditor::new("let x = 1;\nx + 1", "rs");
+    /// let at = |line, character| LspPosition { line, character };
+    ///
+    /// // The cursor starts at (0, 0), outside both occurrences of `x`, so
+    /// // this reply is treated as stale and dropped.
+    /// editor.set_lsp_document_highlights(&[LspDocumentHighlight {
+    ///     range: LspRange { start: at(0, 4), end: at(0, 5) },
+    ///     kind: LspDocumentHighlightKind::Write,
+    /// }]);
+    /// ```
+    ///
+    /// [`LspEvent::DocumentHighlight`]: crate::LspEvent::DocumentHighlight
+    pub fn set_lsp_document_highlights(
+        &mut self,
+        highlights: &[LspDocumentHighlight],
+    ) {
+        if !self.document_highlight_enabled {
+            return;
+        }
+
+        let mut occurrences: Vec<Occurrence> = highlights
+            .iter()
+            .map(|highlight| Occurrence {
+                start: self.position_from_lsp(highlight.range.start),
+                end: self.position_from_lsp(highlight.range.end),
+                kind: highlight.kind,
+            })
+            .collect();
+        occurrences.sort_by_key(|occurrence| occurrence.start);
+
+        let position = self.cursors.primary_position();
+        if !occurrences.is_empty()
+            && !occurrences
+                .iter()
+                .any(|occurrence| occurrence.touches(position))
+        {
+            return;
+        }
+
+        self.document_highlight_state.occurrences = occurrences;
+        self.overlay_cache.clear();
+    }
+
+    /// Moves the cursor to the start of the next (`forward`) or previous
+    /// highlighted occurrence, wrapping around at either end.
+    ///
+    /// When nothing is highlighted yet — the cursor has not rested long
+    /// enough — the occurrences are looked up immediately instead of waiting
+    /// for the debounce.
+    ///
+    /// # Returns
+    ///
+    /// A scroll task that keeps the new cursor position visible
+    pub(crate) fn handle_document_highlight_navigation(
+        &mut self,
+        forward: bool,
+    ) -> Task<Message> {
+        if !self.document_highlight_enabled {
+            return Task::none();
+        }
+
+        let position = self.cursors.primary_position();
+        if !self.document_highlight_state.touches(position) {
+            self.document_highlight_state.observed =
+                Some((position, Instant::now()));
+            self.resolve_document_highlights();
+        }
+
+        let occurrences = &self.document_highlight_state.occurrences;
+        let count = occurrences.len();
+        if count == 0 {
+            return Task::none();
+        }
+
+        let current = occurrences
+            .iter()
+            .position(|occurrence| occurrence.touches(position));
+        let target = match (current, forward) {
+            (Some(index), true) => (index + 1) % count,
+            (Some(index), false) => (index + count - 1) % count,
+            (None, true) => occurrences
+                .iter()
+                .position(|occurrence| occurrence.start > position)
+                .unwrap_or(0),
+            (None, false) => occurrences
+                .iter()
+                .rposition(|occurrence| occurrence.end < position)
+                .unwrap_or(count - 1),
+        };
+
+        let start = occurrences[target].start;
+        self.cursors.set_single(start);
+        self.overlay_cache.clear();
+        self.reset_cursor_blink();
+        self.scroll_to_cursor()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::canvas_editor::lsp::{LspPosition, LspRange};
+
+    fn starts(editor: &CodeEditor) -> Vec<(usize, usize)> {
+        editor
+            .document_highlight_state
+            .occurrences
+            .iter()
+            .map(|occurrence| occurrence.start)
+            .collect()
+    }
+
+    fn server_highlight(
+        line: u32,
+        start: u32,
+        end: u32,
+        kind: LspDocumentHighlightKind,
+    ) -> LspDocumentHighlight {
+        LspDocumentHighlight {
+            range: LspRange {
+                start: LspPosition { line, character: start },
+                end: LspPosition { line, character: end },
+            },
+            kind,
+        }
+    }
+
+    #[test]
+    fn test_lexical_occurrences_match_whole_words_only() {
+        let buffer =
+            TextBuffer::new("count = count + 1\nrecount(counter, count)");
+        let found: Vec<(usize, usize)> = lexical_occurrences(&buffer, "count")
+            .iter()
+            .map(|occurrence| occurrence.start)
+            .collect();
+        assert_eq!(found, [(0, 0), (0, 8), (1, 17)]);
+    }
+
+    #[test]
+    fn test_lexical_occurrences_of_empty_word_is_empty() {
+        let buffer = TextBuffer::new("abc");
+        assert!(lexical_occurrences(&buffer, "").is_empty());
+    }
+
+    #[test]
+    fn test_resting_cursor_highlights_lexical_occurrences() {
+        let mut editor = CodeEditor::new("let foo = 1;\nfoo + foo", "rs");
+        editor.cursors.set_single((0, 5));
+
+        // The first tick only records where the cursor is.
+        editor.refresh_document_highlights();
+        assert!(starts(&editor).is_empty());
+
+        // Pretend the cursor has rested long enough.
+        editor.document_highlight_state.observed = Some((
+            (0, 5),
+            Instant::now()
+                .checked_sub(DOCUMENT_HIGHLIGHT_DELAY)
+                .unwrap_or_else(Instant::now),
+        ));
+        editor.refresh_document_highlights();
+        assert_eq!(starts(&editor), [(0, 4), (1, 0), (1, 6)]);
+    }
+
+    #[test]
+    fn test_moving_off_the_symbol_clears_highlights() {
+        let mut editor = CodeEditor::new("foo bar foo", "rs");
+        let _ = editor.handle_document_highlight_navigation(true);
+        assert_eq!(starts(&editor), [(0, 0), (0, 8)]);
+
+        // Moving within an occurrence keeps the highlights...
+        editor.cursors.set_single((0, 10));
+        editor.refresh_document_highlights();
+        assert_eq!(starts(&editor).len(), 2);
+
+        // ...moving onto another word drops them.
+        editor.cursors.set_single((0, 5));
+        editor.refresh_document_highlights();
+        assert!(starts(&editor).is_empty());
+    }
+
+    #[test]
+    fn test_navigation_cycles_through_occurrences() {
+        let mut editor = CodeEditor::new("a b a c a", "rs");
+
+        let _ = editor.update(&Message::NextDocumentHighlight);
+        assert_eq!(editor.cursors.primary_position(), (0, 4));
+        let _ = editor.update(&Message::NextDocumentHighlight);
+        assert_eq!(editor.cursors.primary_position(), (0, 8));
+        let _ = editor.update(&Message::NextDocumentHighlight);
+        assert_eq!(editor.cursors.primary_position(), (0, 0));
+        let _ = editor.update(&Message::PreviousDocumentHighlight);
+        assert_eq!(editor.cursors.primary_position(), (0, 8));
+    }
+
+    #[test]
+    fn test_edit_clears_highlights() {
+        let mut editor = CodeEditor::new("foo foo", "rs");
+        let _ = editor.handle_document_highlight_navigation(true);
+        assert!(!starts(&editor).is_empty());
+
+        let _ = editor.update(&Message::Paste("x".to_string()));
+        assert!(starts(&editor).is_empty());
+    }
+
+    #[test]
+    fn test_server_highlights_replace_lexical_ones() {
+        let mut editor = CodeEditor::new("let x = 1;\nx + 1 // x", "rs");
+        editor.cursors.set_single((1, 0));
+        let _ = editor.handle_document_highlight_navigation(true);
+        editor.cursors.set_single((1, 0));
+        assert_eq!(starts(&editor).len(), 3, "lexical includes the comment");
+
+        editor.set_lsp_document_highlights(&[
+            server_highlight(1, 0, 1, LspDocumentHighlightKind::Read),
+            server_highlight(0, 4, 5, LspDocumentHighlightKind::Write),
+        ]);
+        let occurrences = &editor.document_highlight_state.occurrences;
+        assert_eq!(occurrences.len(), 2);
+        assert_eq!(occurrences[0].start, (0, 4), "sorted by position");
+        assert_eq!(occurrences[0].kind, LspDocumentHighlightKind::Write);
+        assert_eq!(occurrences[1].kind, LspDocumentHighlightKind::Read);
+    }
+
+    #[test]
+    fn test_stale_server_highlights_are_ignored() {
+        let mut editor = CodeEditor::new("let x = 1;\nlet y = x;", "rs");
+        editor.cursors.set_single((1, 4));
+
+        // A reply about `x` arriving after the cursor moved to `y`.
+        editor.set_lsp_document_highlights(&[server_highlight(
+            0,
+            4,
+            5,
+            LspDocumentHighlightKind::Write,
+        )]);
+        assert!(starts(&editor).is_empty());
+    }
+
+    #[test]
+    fn test_disabled_highlights_do_nothing() {
+        let mut editor = CodeEditor::new("foo foo", "rs");
+        editor.set_document_highlight_enabled(false);
+        let _ = editor.update(&Message::NextDocumentHighlight);
+        assert_eq!(editor.cursors.primary_position(), (0, 0));
+        assert!(starts(&editor).is_empty());
+    }
+}
@@ -0,0 +1,550 @@
+//! Document links: URLs and file paths that Ctrl+Click (Cmd+Click on macOS)
+//! activates.
+//!
+//! Links come from two sources:
+//!
+//! * **Lexical**: URLs (`https://…`, `file://…`, any `scheme://…`) and file
+//!   paths (`./src/main.rs`, `../README.md`, `~/notes.txt`, `/etc/hosts`,
+//!   optionally followed by `:line` or `:line:column`) found by scanning the
+//!   hovered line. Always available, language server or not.
+//! * **Language server**: the reply to `textDocument/documentLink`, requested
+//!   once the buffer has stopped changing for [`DOCUMENT_LINK_DELAY`] and
+//!   handed back through [`CodeEditor::set_lsp_document_links`]. Server links
+//!   take precedence where both apply, since only the server can resolve
+//!   things like a crate name to its documentation.
+//!
+//! Holding the modifier underlines the link under the mouse instead of the
+//! word, and clicking it publishes [`Message::LinkActivated`] rather than
+//! [`Message::JumpClick`]: the editor never opens anything itself, the host
+//! decides what a target means.
+
+#[cfg(not(target_arch = "wasm32"))]
+use std::time::{Duration, Instant};
+#[cfg(target_arch = "wasm32")]
+use {std::time::Duration, web_time::Instant};
+
+use crate::buffer::TextBuffer;
+use crate::canvas_editor::lsp::LspDocumentLink;
+use crate::canvas_editor::{CodeEditor, Message};
+
+/// How long the buffer must stay unchanged before the language server is
+/// asked for its links again.
+///
+/// Links only matter once the user reaches for the modifier key, so there is
+/// no point in asking the server on every keystroke.
+pub(crate) const DOCUMENT_LINK_DELAY: Duration = Duration::from_millis(500);
+
+/// Characters that end a URL: whitespace aside, these are the delimiters a
+/// URL is typically wrapped in.
+const URL_DELIMITERS: &[char] = &['"', '\'', '`', '<', '>'];
+
+/// Trailing characters dropped from a match, as they usually belong to the
+/// surrounding prose (`see https://example.com.`) rather than to the link.
+const TRAILING_PUNCTUATION: &[char] = &['.', ',', ';', ':', '!', '?'];
+
+/// A link found in a line of text.
+///
+/// Columns are character indices into the logical line, matching the
+/// convention used by the renderer and by [`crate::buffer::TextBuffer`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct DocumentLink {
+    /// Column of the link's first character.
+    pub start_col: usize,
+    /// Column one past the link's last character.
+    pub end_col: usize,
+    /// What the link points to: the URL, or the path as written.
+    pub target: String,
+}
+
+impl DocumentLink {
+    /// Returns `true` when `col` falls on the link, including the column just
+    /// past its last character, where a click on its right half lands.
+    fn touches(&self, col: usize) -> bool {
+        self.start_col <= col && col <= self.end_col
+    }
+}
+
+/// Finds every URL and file path in `line`.
+///
+/// A URL is a `scheme://` prefix followed by anything up to whitespace or
+/// one of [`URL_DELIMITERS`]. A path starts with `./`, `../`, `~/` or `/`
+/// and may end in `:line` or `:line:column`; an absolute path needs at least
+/// two components (`/usr/bin`), which keeps `// comments` and divisions out.
+/// Neither may continue a word, and trailing punctuation or an unbalanced
+/// closing bracket is left out of the match.
+///
+/// # Arguments
+///
+/// * `line` - The logical line to scan
+///
+/// # Returns
+///
+/// The links found, ordered by increasing column. Links never overlap:
+/// scanning resumes after the end of each match.
+pub(crate) fn document_links(line: &str) -> Vec<DocumentLink> {
+    let chars: Vec<char> = line.chars().collect();
+    let mut links = Vec::new();
+    let mut col = 0;
+
+    while col < chars.len() {
+        let after_word = col > 0 && is_word_char(chars[col - 1]);
+        let end = if after_word {
+            None
+        } else {
+            url_end(&chars, col).or_else(|| path_end(&chars, col))
+        };
+        match end {
+            Some(end) => {
+                links.push(DocumentLink {
+                    start_col: col,
+                    end_col: end,
+                    target: chars[col..end].iter().collect(),
+                });
+                col = end;
+            }
+            None => col += 1,
+        }
+    }
+
+    links
+}
+
+/// Returns whether `c` continues a word, so a link cannot start after it.
+fn is_word_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '_'
+}
+
+/// Returns whether `c` can appear in a file path.
+fn is_path_char(c: char) -> bool {
+    c.is_alphanumeric() || matches!(c, '_' | '-' | '.' | '~' | '+' | '@' | '%')
+}
+
+/// Returns the end column of a URL starting at `start`, if there is one.
+fn url_end(chars: &[char], start: usize) -> Option<usize> {
+    if !chars.get(start)?.is_ascii_alphabetic() {
+        return None;
+    }
+    let mut col = start + 1;
+    while chars.get(col).is_some_and(|c| {
+        c.is_ascii_alphanumeric() || matches!(c, '+' | '-' | '.')
+    }) {
+        col += 1;
+    }
+    if !chars[col..].starts_with(&[':', '/', '/']) {
+        return None;
+    }
+    let body_start = col + 3;
+    let mut end = body_start;
+    while chars
+        .get(end)
+        .is_some_and(|c| !c.is_whitespace() && !URL_DELIMITERS.contains(c))
+    {
+        end += 1;
+    }
+
+    let end = trim_trailing(chars, start, end);
+    (end > body_start).then_some(end)
+}
+
+/// Returns the end column of a file path starting at `start`, if there is
+/// one.
+fn path_end(chars: &[char], start: usize) -> Option<usize> {
+    let rest = chars.get(start..)?;
+    let (prefix_len, absolute) = if rest.starts_with(&['.', '.', '/']) {
+        (3, false)
+    } else if rest.starts_with(&['.', '/']) || rest.starts_with(&['~', '/']) {
+        (2, false)
+    } else if rest.starts_with(&['/']) {
+        (1, true)
+    } else {
+        return None;
+    };
+
+    let mut end = start + prefix_len;
+    let mut separators = 0;
+    while let Some(&c) = chars.get(end) {
+        if c == '/' {
+            // `//` is a comment marker or an empty component, never a path.
+            if chars.get(end - 1) == Some(&'/') {
+                break;
+            }
+            separators += 1;
+        } else if !is_path_char(c) {
+            break;
+        }
+        end += 1;
+    }
+    let end = trim_trailing(chars, start, end);
+    if end <= start + prefix_len
+        || !is_path_char(chars[end - 1])
+        || (absolute && separators == 0)
+    {
+        return None;
+    }
+
+    Some(line_suffix_end(chars, end))
+}
+
+/// Extends a path ending at `end` over a `:line` or `:line:column` suffix.
+fn line_suffix_end(chars: &[char], end: usize) -> usize {
+    let digits_end = |from: usize| {
+        let mut col = from;
+        while chars.get(col).is_some_and(char::is_ascii_digit) {
+            col += 1;
+        }
+        col
+    };
+
+    let mut end = end;
+    for _ in 0..2 {
+        if chars.get(end) != Some(&':') {
+            break;
+        }
+        let next = digits_end(end + 1);
+        if next == end + 1 {
+            break;
+        }
+        end = next;
+    }
+    end
+}
+
+/// Drops trailing punctuation and unbalanced closing brackets from the
+/// match `chars[start..end]`, returning the new end.
+///
+/// A closing bracket is kept when the match opened it, so
+/// `https://en.wikipedia.org/wiki/Rust_(programming_language)` survives
+/// intact while `(see https://example.com)` loses the parenthesis.
+fn trim_trailing(chars: &[char], start: usize, end: usize) -> usize {
+    let mut end = end;
+    while end > start {
+        let last = chars[end - 1];
+        let open = match last {
+            ')' => Some('('),
+            ']' => Some('['),
+            '}' => Some('{'),
+            _ => None,
+        };
+        let drop = match open {
+            Some(open) => {
+                let matched = &chars[start..end];
+                let opened = matched.iter().filter(|&&c| c == open).count();
+                let closed = matched.iter().filter(|&&c| c == last).count();
+                closed > opened
+            }
+            None => TRAILING_PUNCTUATION.contains(&last),
+        };
+        if !drop {
+            break;
+        }
+        end -= 1;
+    }
+    end
+}
+
+/// Reuses one logical line's links across lookups in the same pass.
+///
+/// Like [`LineLiterals`](super::color_preview::LineLiterals), the memo is
+/// keyed on the line index alone, so it is only valid for as long as the
+/// buffer cannot change — build one per draw pass or per event and drop it.
+#[derive(Debug, Default)]
+pub(crate) struct LineLinks {
+    /// Line the memo currently holds, if any.
+    line: Option<usize>,
+    /// Links found on that line.
+    links: Vec<DocumentLink>,
+}
+
+impl LineLinks {
+    /// Returns the links on `line`, scanning it only when it differs from
+    /// the line asked for last.
+    ///
+    /// # Arguments
+    ///
+    /// * `buffer` - The buffer the line belongs to
+    /// * `line` - Index of the logical line to scan
+    ///
+    /// # Returns
+    ///
+    /// The links found, ordered by increasing column
+    pub(crate) fn get(
+        &mut self,
+        buffer: &TextBuffer,
+        line: usize,
+    ) -> &[DocumentLink] {
+        if self.line != Some(line) {
+            self.links = document_links(buffer.line(line));
+            self.line = Some(line);
+        }
+
+        &self.links
+    }
+}
+
+/// Document-link state owned by the editor.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct DocumentLinkState {
+    /// Links reported by the language server, as `(line, link)` pairs.
+    links: Vec<(usize, DocumentLink)>,
+    /// Buffer revision the last server request was sent for.
+    pub(crate) requested: Option<u64>,
+    /// When the buffer was last edited, for the request debounce.
+    edited_at: Option<Instant>,
+}
+
+impl DocumentLinkState {
+    /// Drops the server's links and forgets what was requested, so the next
+    /// tick asks again.
+    pub(crate) fn clear(&mut self) {
+        *self = Self::default();
+    }
+
+    /// Drops the server's links, whose positions an edit has invalidated,
+    /// and restarts the request debounce.
+    pub(crate) fn mark_edited(&mut self) {
+        self.links.clear();
+        self.edited_at = Some(Instant::now());
+    }
+}
+
+impl CodeEditor {
+    /// Asks the language server for the document's links once the buffer
+    /// has rested for [`DOCUMENT_LINK_DELAY`]; called on every
+    /// [`Message::Tick`].
+    pub(crate) fn refresh_document_links(&mut self) {
+        if !self.document_links_enabled || self.lsp_client.is_none() {
+            return;
+        }
+
+        let state = &self.document_link_state;
+        if state.requested == Some(self.buffer_revision)
+            || state
+                .edited_at
+                .is_some_and(|at| at.elapsed() < DOCUMENT_LINK_DELAY)
+        {
+            return;
+        }
+        self.lsp_request_document_links();
+    }
+
+    /// Replaces the server-provided links with a language server's answer.
+    ///
+    /// Feed this the payload of a `textDocument/documentLink` response (with
+    /// the bundled client, [`LspEvent::DocumentLinks`]). Ranges are read in
+    /// UTF-16 code units, as servers send them; links spanning several lines
+    /// are ignored. A reply that arrives after the buffer was edited answers
+    /// text that no longer exists, and is dropped.
+    ///
+    /// Lexically detected URLs and paths keep working whether or not this
+    /// is ever called.
+    ///
+    /// # Arguments
+    ///
+    /// * `links` - The links reported by the server
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use iced_code_editor::{
+    ///     CodeEditor, LspDocumentLink, LspPosition, LspRange,
+    /// };
+    ///
+    /// let mut editor = CodeEditor::new("use serde;", "rs");
+    /// editor.lsp_request_document_links();
+    ///
+    /// // Ctrl+Click on `serde` now activates the documentation link.
+    /// editor.set_lsp_document_links(&[LspDocumentLink {
+    ///     range: LspRange {
+    ///         start: LspPosition { line: 0, character: 4 },
+    ///         end: LspPosition { line: 0, character: 9 },
+    ///     },
+    ///     target: "https://docs.rs/serde".to_string(),
+    /// }]);
+    /// ```
+    ///
+    /// [`LspEvent::DocumentLinks`]: crate::LspEvent::DocumentLinks
+    pub fn set_lsp_document_links(&mut self, links: &[LspDocumentLink]) {
+        if !self.document_links_enabled
+            || self.document_link_state.requested != Some(self.buffer_revision)
+        {
+            return;
+        }
+
+        let mut resolved: Vec<(usize, DocumentLink)> = links
+            .iter()
+            .filter_map(|link| {
+                let start = self.position_from_lsp(link.range.start);
+                let end = self.position_from_lsp(link.range.end);
+                (start.0 == end.0 && start.1 < end.1).then(|| {
+                    (
+                        start.0,
+                        DocumentLink {
+                            start_col: start.1,
+                            end_col: end.1,
+                            target: link.target.clone(),
+                        },
+                    )
+                })
+            })
+            .collect();
+        resolved.sort_by_key(|(line, link)| (*line, link.start_col));
+        self.document_link_state.links = resolved;
+    }
+
+    /// Returns the link at `(line, col)`, if any.
+    ///
+    /// Server links win over lexical ones; `None` when links are disabled.
+    ///
+    /// # Arguments
+    ///
+    /// * `links` - Memo of the lexical links of the line last looked at
+    /// * `line` - Logical line of the position
+    /// * `col` - Column of the position, in characters
+    pub(crate) fn document_link_at(
+        &self,
+        links: &mut LineLinks,
+        line: usize,
+        col: usize,
+    ) -> Option<DocumentLink> {
+        if !self.document_links_enabled {
+            return None;
+        }
+
+        self.document_link_state
+            .links
+            .iter()
+            .find(|(link_line, link)| *link_line == line && link.touches(col))
+            .map(|(_, link)| link)
+            .or_else(move || {
+                links
+                    .get(&self.buffer, line)
+                    .iter()
+                    .find(|link| link.touches(col))
+            })
+            .cloned()
+    }
+
+    /// Returns the message a Ctrl+Click (Cmd+Click on macOS) at `point`
+    /// publishes: [`Message::LinkActivated`] over a link, otherwise
+    /// [`Message::JumpClick`].
+    pub(crate) fn modifier_click_message(&self, point: iced::Point) -> Message {
+        self.calculate_cursor_from_point(point)
+            .and_then(|(line, col)| {
+                self.document_link_at(&mut LineLinks::default(), line, col)
+            })
+            .map_or(Message::JumpClick(point), |link| Message::LinkActivated {
+                target: link.target,
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{DocumentLink, LineLinks, TextBuffer, document_links};
+    use crate::canvas_editor::CodeEditor;
+    use crate::canvas_editor::lsp::{LspDocumentLink, LspPosition, LspRange};
+
+    /// Returns the targets of every link in `line`.
+    fn targets(line: &str) -> Vec<String> {
+        document_links(line).into_iter().map(|link| link.target).collect()
+    }
+
+    #[test]
+    fn test_urls_are_detected_with_their_columns() {
+        assert_eq!(
+            document_links("see https://example.com/a?b=1 now"),
+            vec![DocumentLink {
+                start_col: 4,
+                end_col: 29,
+                target: "https://example.com/a?b=1".to_string(),
+            }]
+        );
+        assert_eq!(
+            targets("\"file:///tmp/x.txt\" and <ftp://host/f>"),
+            ["file:///tmp/x.txt", "ftp://host/f"]
+        );
+    }
+
+    #[test]
+    fn test_trailing_punctuation_and_unbalanced_brackets_are_trimmed() {
+        assert_eq!(
+            targets("Go to https://example.com."),
+            ["https://example.com"]
+        );
+        assert_eq!(
+            targets("(see https://example.com/docs)"),
+            ["https://example.com/docs"]
+        );
+        assert_eq!(
+            targets("https://en.wikipedia.org/wiki/Rust_(language)"),
+            ["https://en.wikipedia.org/wiki/Rust_(language)"]
+        );
+    }
+
+    #[test]
+    fn test_paths_are_detected_with_line_suffixes() {
+        assert_eq!(
+            targets("at ./src/main.rs:12:5, then ../README.md"),
+            ["./src/main.rs:12:5", "../README.md"]
+        );
+        assert_eq!(
+            targets("open ~/notes.txt or /etc/hosts"),
+            ["~/notes.txt", "/etc/hosts"]
+        );
+    }
+
+    #[test]
+    fn test_non_links_are_ignored() {
+        assert!(targets("// a comment with a / b").is_empty());
+        assert!(targets("let ratio = width/2;").is_empty());
+        assert!(targets("</div> and /root alone").is_empty());
+        assert!(targets("notascheme:// and https://").is_empty());
+        assert!(targets("_https://glued.example").is_empty());
+    }
+
+    #[test]
+    fn test_line_links_returns_each_line_it_is_asked_for() {
+        let buffer = TextBuffer::new("https://a.example\nplain\n./b.rs");
+        let mut memo = LineLinks::default();
+
+        assert_eq!(memo.get(&buffer, 0).len(), 1);
+        assert!(memo.get(&buffer, 1).is_empty());
+        assert_eq!(memo.get(&buffer, 2).len(), 1);
+        assert_eq!(memo.get(&buffer, 0).len(), 1);
+    }
+
+    #[test]
+    fn test_server_links_win_and_stale_replies_are_dropped() {
+        let mut editor = CodeEditor::new("use serde;\nhttps://x.example", "rs");
+        let link = |character, target: &str| LspDocumentLink {
+            range: LspRange {
+                start: LspPosition { line: 0, character },
+                end: LspPosition { line: 0, character: character + 5 },
+            },
+            target: target.to_string(),
+        };
+        let target_at = |editor: &CodeEditor, line, col| {
+            editor
+                .document_link_at(&mut LineLinks::default(), line, col)
+                .map(|link| link.target)
+        };
+
+        // Nothing was requested for this revision: the reply is stale.
+        editor.set_lsp_document_links(&[link(4, "https://docs.rs/serde")]);
+        assert_eq!(target_at(&editor, 0, 5), None);
+
+        editor.lsp_request_document_links();
+        editor.set_lsp_document_links(&[link(4, "https://docs.rs/serde")]);
+        assert_eq!(
+            target_at(&editor, 0, 5).as_deref(),
+            Some("https://docs.rs/serde")
+        );
+        assert_eq!(
+            target_at(&editor, 1, 3).as_deref(),
+            Some("https://x.example")
+        );
+
+        editor.set_document_links_enabled(false);
+        assert_eq!(target_at(&editor, 1, 3), None);
+    }
+}
@@ -0,0 +1,826 @@
+//! Language-aware indentation rules.
+//!
+//! Without rules, Enter copies the indentation of the current line. A
+//! language with [`IndentRules`] gets block-aware indentation instead:
+//!
+//! * a line ending with an opening token (`{`, `:`, `then`…) indents the next
+//!   line one level;
+//! * typing a closing token (`}`, `end`…) as the first thing on a line
+//!   outdents it to the level of the block it closes;
+//! * a line continuing a statement (after a trailing `,` or operator) keeps
+//!   its indentation, and the line after the statement returns to the
+//!   statement's first line.
+//!
+//! The same rules drive **Reindent Lines** ([`Message::ReindentLines`]) and,
+//! when enabled with [`CodeEditor::set_reindent_on_paste`], the re-indentation
+//! of pasted text. Rules are looked up by the editor's syntax, the name
+//! [`CodeEditor::syntax`] returns; languages without built-in rules can be
+//! given some with [`CodeEditor::set_indent_rules`].
+
+use std::borrow::Cow;
+
+use iced::Task;
+
+use crate::canvas_editor::editing::command::{Command, ReindentLinesCommand};
+use crate::canvas_editor::{CodeEditor, IndentStyle, Message, TAB_WIDTH};
+
+/// Maximum number of lines scanned upward to find the line a block or a
+/// statement starts on.
+///
+/// Keeps Enter and typing cheap in very long files; past this distance the
+/// indentation of the nearest lines is trusted as is.
+const MAX_SCAN: usize = 200;
+
+/// How a language indents its blocks.
+///
+/// Tokens are matched against a line with its indentation and trailing line
+/// comment removed. A token starting or ending with a letter or digit only
+/// matches as a whole word, so `end` does not match `append`.
+///
+/// # Example
+///
+/// ```
+/// use iced_code_editor::{CodeEditor, IndentRules};
+///
+/// let mut editor = CodeEditor::new("", "nim");
+/// editor.set_indent_rules(
+///     "nim",
+///     IndentRules::new()
+///         .with_indent_after(&[":", "="])
+///         .with_outdent_on(&["else", "elif"]),
+/// );
+/// assert!(editor.indent_rules().is_some());
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct IndentRules {
+    /// A line ending with one of these indents the next line.
+    pub indent_after: Vec<String>,
+    /// Keywords opening a block wherever they appear on a line (Lua's
+    /// `function`), unless an [`outdent_on`](Self::outdent_on) token closes
+    /// it later on the same line.
+    pub open_words: Vec<String>,
+    /// A line starting with one of these is outdented one level.
+    pub outdent_on: Vec<String>,
+    /// A line ending with one of these continues on the next line, which
+    /// keeps its indentation.
+    pub continue_after: Vec<String>,
+}
+
+/// Converts a token list into owned strings.
+fn owned(tokens: &[&str]) -> Vec<String> {
+    tokens.iter().map(ToString::to_string).collect()
+}
+
+impl IndentRules {
+    /// Creates an empty rule set, which keeps every line's indentation.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the tokens after which the next line is indented.
+    ///
+    /// # Arguments
+    ///
+    /// * `tokens` - Line endings that open a block, e.g. `["{", ":"]`
+    #[must_use]
+    pub fn with_indent_after(mut self, tokens: &[&str]) -> Self {
+        self.indent_after = owned(tokens);
+        self
+    }
+
+    /// Sets the keywords that open a block anywhere on a line.
+    ///
+    /// # Arguments
+    ///
+    /// * `words` - Block keywords, e.g. `["function"]`
+    #[must_use]
+    pub fn with_open_words(mut self, words: &[&str]) -> Self {
+        self.open_words = owned(words);
+        self
+    }
+
+    /// Sets the tokens that outdent the line they start.
+    ///
+    /// # Arguments
+    ///
+    /// * `tokens` - Line starts that close a block, e.g. `["}", "end"]`
+    #[must_use]
+    pub fn with_outdent_on(mut self, tokens: &[&str]) -> Self {
+        self.outdent_on = owned(tokens);
+        self
+    }
+
+    /// Sets the tokens after which a statement continues on the next line.
+    ///
+    /// # Arguments
+    ///
+    /// * `tokens` - Line endings that continue a statement, e.g. `[","]`
+    #[must_use]
+    pub fn with_continue_after(mut self, tokens: &[&str]) -> Self {
+        self.continue_after = owned(tokens);
+        self
+    }
+
+    /// Returns the built-in rules for a syntax, if there are any.
+    ///
+    /// Accepts both file extensions (`"rs"`) and language names (`"rust"`),
+    /// like the rest of the editor. Built-in rules cover the C family
+    /// (braces), Python, Lua, shell scripts and YAML.
+    ///
+    /// # Arguments
+    ///
+    /// * `syntax` - The syntax name, e.g. `"py"`
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use iced_code_editor::IndentRules;
+    ///
+    /// let rules = IndentRules::builtin("lua").unwrap_or_default();
+    /// assert!(rules.indent_after.contains(&"then".to_string()));
+    /// assert_eq!(IndentRules::builtin("txt"), None);
+    /// ```
+    pub fn builtin(syntax: &str) -> Option<Self> {
+        let rules = match syntax.to_lowercase().as_str() {
+            "rs" | "rust" | "js" | "javascript" | "ts" | "typescript"
+            | "jsx" | "tsx" | "go" | "c" | "h" | "cpp" | "cc" | "cxx"
+            | "hpp" | "c++" | "java" | "cs" | "csharp" | "kt" | "kotlin"
+            | "swift" | "php" | "json" | "css" | "scss" => Self::new()
+                .with_indent_after(&["{", "(", "["])
+                .with_outdent_on(&["}", ")", "]"])
+                .with_continue_after(&[",", "=", "+", "-", "&&", "||"]),
+            "py" | "python" => Self::new()
+                .with_indent_after(&[":", "(", "[", "{"])
+                .with_outdent_on(&[
+                    ")", "]", "}", "else", "elif", "except", "finally",
+                ])
+                .with_continue_after(&[",", "\\", "=", "+", "-", "and", "or"]),
+            "lua" => Self::new()
+                .with_indent_after(&["then", "do", "else", "repeat", "{", "("])
+                .with_open_words(&["function"])
+                .with_outdent_on(&["end", "else", "elseif", "until", "}", ")"])
+                .with_continue_after(&[",", "=", "..", "and", "or"]),
+            "sh" | "bash" | "shell" | "zsh" => Self::new()
+                .with_indent_after(&["then", "do", "else", "in", "{", "("])
+                .with_outdent_on(&[
+                    "fi", "done", "esac", "else", "elif", "}", ")",
+                ])
+                .with_continue_after(&["\\", "&&", "||", "|"]),
+            "yaml" | "yml" => Self::new()
+                .with_indent_after(&[":"])
+                .with_continue_after(&[","]),
+            _ => return None,
+        };
+        Some(rules)
+    }
+
+    /// Returns `true` when the line after `code` opens a block.
+    fn opens(&self, code: &str) -> bool {
+        self.indent_after.iter().any(|token| ends_with_token(code, token))
+            || self.open_words.iter().any(|word| {
+                last_word_end(code, word).is_some_and(|end| {
+                    let rest = &code[end..];
+                    !self
+                        .outdent_on
+                        .iter()
+                        .any(|token| last_word_end(rest, token).is_some())
+                })
+            })
+    }
+
+    /// Returns `true` when `code` starts by closing a block.
+    fn closes(&self, code: &str) -> bool {
+        self.outdent_on.iter().any(|token| starts_with_token(code, token))
+    }
+
+    /// Returns `true` when the statement on `code` goes on to the next line.
+    fn continues(&self, code: &str) -> bool {
+        !self.opens(code)
+            && self
+                .continue_after
+                .iter()
+                .any(|token| ends_with_token(code, token))
+    }
+}
+
+/// Returns `true` for characters that make up a word-like token.
+fn is_word_char(ch: char) -> bool {
+    ch.is_alphanumeric() || ch == '_'
+}
+
+/// Returns `true` when `code` ends with `token`, as a whole word when the
+/// token starts with a word character.
+fn ends_with_token(code: &str, token: &str) -> bool {
+    code.strip_suffix(token).is_some_and(|rest| {
+        !(token.starts_with(is_word_char) && rest.ends_with(is_word_char))
+    })
+}
+
+/// Returns `true` when `code` starts with `token`, as a whole word when the
+/// token ends with a word character.
+fn starts_with_token(code: &str, token: &str) -> bool {
+    code.strip_prefix(token).is_some_and(|rest| {
+        !(token.ends_with(is_word_char) && rest.starts_with(is_word_char))
+    })
+}
+
+/// Returns the byte offset just past the last whole-word occurrence of
+/// `word` in `code`.
+fn last_word_end(code: &str, word: &str) -> Option<usize> {
+    code.match_indices(word)
+        .filter(|(start, _)| {
+            let before = &code[..*start];
+            let after = &code[start + word.len()..];
+            let joins_before = word.starts_with(is_word_char)
+                && before.ends_with(is_word_char);
+            let joins_after =
+                word.ends_with(is_word_char) && after.starts_with(is_word_char);
+            !joins_before && !joins_after
+        })
+        .map(|(start, _)| start + word.len())
+        .last()
+}
+
+/// Returns the width of the leading whitespace of `line`, in display columns.
+pub(crate) fn leading_width(line: &str) -> usize {
+    line.chars()
+        .take_while(|ch| *ch == ' ' || *ch == '\t')
+        .map(|ch| if ch == '\t' { TAB_WIDTH } else { 1 })
+        .sum()
+}
+
+/// Applies [`IndentRules`] to a window of lines.
+///
+/// Works on owned copies of the lines so a re-indentation can see the lines
+/// it has already changed.
+struct Indenter<'a> {
+    rules: &'a IndentRules,
+    /// The syntax's line-comment token, stripped before matching.
+    comment: Option<&'a str>,
+    /// Width of one indentation level, in display columns.
+    unit: usize,
+}
+
+impl Indenter<'_> {
+    /// Returns the code of `line`: without indentation, trailing line
+    /// comment and trailing whitespace.
+    fn code<'l>(&self, line: &'l str) -> &'l str {
+        let line = line.trim_start();
+        let Some(comment) = self.comment else {
+            return line.trim_end();
+        };
+        let mut quote = None;
+        let mut escaped = false;
+        for (index, ch) in line.char_indices() {
+            match quote {
+                _ if escaped => escaped = false,
+                Some(_) if ch == '\\' => escaped = true,
+                Some(open) if ch == open => quote = None,
+                Some(_) => {}
+                None if ch == '"' || ch == '\'' => quote = Some(ch),
+                None if line[index..].starts_with(comment) => {
+                    return line[..index].trim_end();
+                }
+                None => {}
+            }
+        }
+        line.trim_end()
+    }
+
+    /// Returns the nearest line above `index` that holds code.
+    fn previous(&self, lines: &[String], index: usize) -> Option<usize> {
+        (0..index).rev().find(|&line| !self.code(&lines[line]).is_empty())
+    }
+
+    /// Returns the line the statement on line `index` starts on, following
+    /// continuation lines upward.
+    fn statement_start(&self, lines: &[String], index: usize) -> usize {
+        let mut current = index;
+        loop {
+            let code = self.code(&lines[current]);
+            if code.is_empty() || self.rules.closes(code) {
+                return current;
+            }
+            match self.previous(lines, current) {
+                Some(previous)
+                    if self.rules.continues(self.code(&lines[previous])) =>
+                {
+                    current = previous;
+                }
+                _ => return current,
+            }
+        }
+    }
+
+    /// Returns the indentation width of a new line following line `index`.
+    fn width_after(&self, lines: &[String], index: usize) -> usize {
+        let code = self.code(&lines[index]);
+        if self.rules.opens(code) {
+            leading_width(&lines[self.statement_start(lines, index)])
+                + self.unit
+        } else if self.rules.continues(code) {
+            leading_width(&lines[index])
+        } else {
+            leading_width(&lines[self.statement_start(lines, index)])
+        }
+    }
+
+    /// Returns the indentation width line `index` should have, given the
+    /// lines above it, and whether it continues a statement.
+    ///
+    /// `None` when there is no code above it within the window.
+    fn width_of(
+        &self,
+        lines: &[String],
+        index: usize,
+    ) -> Option<(usize, bool)> {
+        let previous = self.previous(lines, index)?;
+        let closes = self.rules.closes(self.code(&lines[index]));
+        if !closes && self.rules.continues(self.code(&lines[previous])) {
+            return Some((leading_width(&lines[index]), true));
+        }
+        let width = self.width_after(lines, previous);
+        Some((
+            if closes { width.saturating_sub(self.unit) } else { width },
+            false,
+        ))
+    }
+}
+
+impl CodeEditor {
+    /// Sets the indentation rules of a language.
+    ///
+    /// They replace the built-in rules of that language, if any (see
+    /// [`IndentRules::builtin`]), for every editor syntax with that name,
+    /// compared case-insensitively.
+    ///
+    /// # Arguments
+    ///
+    /// * `language` - The syntax the rules apply to, e.g. `"rs"`
+    /// * `rules` - The rules
+    pub fn set_indent_rules(&mut self, language: &str, rules: IndentRules) {
+        self.indent_rules.insert(language.to_lowercase(), rules);
+    }
+
+    /// Removes the rules set for a language, restoring its built-in rules.
+    ///
+    /// # Arguments
+    ///
+    /// * `language` - The syntax whose rules are removed
+    pub fn clear_indent_rules(&mut self, language: &str) {
+        self.indent_rules.remove(&language.to_lowercase());
+    }
+
+    /// Returns the indentation rules in effect for the editor's syntax.
+    ///
+    /// # Returns
+    ///
+    /// The rules set with [`CodeEditor::set_indent_rules`], else the
+    /// built-in ones, or `None` when Enter just copies the indentation
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use iced_code_editor::CodeEditor;
+    ///
+    /// assert!(CodeEditor::new("", "py").indent_rules().is_some());
+    /// assert!(CodeEditor::new("", "txt").indent_rules().is_none());
+    /// ```
+    pub fn indent_rules(&self) -> Option<IndentRules> {
+        self.active_indent_rules().map(Cow::into_owned)
+    }
+
+    /// Returns the rules for the editor's syntax without cloning set ones.
+    fn active_indent_rules(&self) -> Option<Cow<'_, IndentRules>> {
+        let syntax = self.syntax.to_lowercase();
+        match self.indent_rules.get(&syntax) {
+            Some(rules) => Some(Cow::Borrowed(rules)),
+            None => IndentRules::builtin(&syntax).map(Cow::Owned),
+        }
+    }
+
+    /// Returns the width of one indentation level, in display columns.
+    fn indent_unit_width(&self) -> usize {
+        match self.indent_style {
+            IndentStyle::Spaces(n) => usize::from(n),
+            IndentStyle::Tab => TAB_WIDTH,
+        }
+    }
+
+    /// Renders an indentation `width` columns wide in the editor's style.
+    fn indent_string(&self, width: usize) -> String {
+        match self.indent_style {
+            IndentStyle::Spaces(_) => " ".repeat(width),
+            IndentStyle::Tab => {
+                let mut indent = "\t".repeat(width / TAB_WIDTH);
+                indent.push_str(&" ".repeat(width % TAB_WIDTH));
+                indent
+            }
+        }
+    }
+
+    /// Renders an indentation `width` columns wide, reusing `existing`
+    /// (leading whitespace already in the buffer) when it is that wide, so
+    /// a file indented in another style is not rewritten.
+    fn indent_like(&self, width: usize, existing: &str) -> String {
+        if leading_width(existing) == width {
+            existing.to_string()
+        } else {
+            self.indent_string(width)
+        }
+    }
+
+    /// Copies the lines from up to [`MAX_SCAN`] lines above `start` through
+    /// `end`, for an [`Indenter`] to work on.
+    ///
+    /// # Returns
+    ///
+    /// The copied lines and the buffer line the first one comes from
+    fn indent_window(&self, start: usize, end: usize) -> (Vec<String>, usize) {
+        let first = start.saturating_sub(MAX_SCAN);
+        let lines = (first..=end)
+            .map(|line| self.buffer.line(line).to_string())
+            .collect();
+        (lines, first)
+    }
+
+    /// Returns the indentation of the line Enter creates when splitting
+    /// `line` at `col`, per the syntax's rules.
+    ///
+    /// # Returns
+    ///
+    /// `None` when the syntax has no rules. Otherwise the indentation of the
+    /// new line and, when the text moved to it starts by closing the block
+    /// the split line opens (`{|}`), the indentation of a further line
+    /// holding that text, so the cursor lands on an indented empty line in
+    /// between.
+    pub(crate) fn rule_based_newline_indent(
+        &self,
+        line: usize,
+        col: usize,
+    ) -> Option<(String, Option<String>)> {
+        let rules = self.active_indent_rules()?;
+        let comment = self.comment_tokens().line;
+        let indenter = Indenter {
+            rules: &rules,
+            comment: comment.as_deref(),
+            unit: self.indent_unit_width(),
+        };
+        let (mut lines, first) = self.indent_window(line, line);
+        let text = self.buffer.line(line);
+        let rest: String = text.chars().skip(col).collect();
+        let existing: String = text
+            .chars()
+            .take(col)
+            .take_while(|ch| *ch == ' ' || *ch == '\t')
+            .collect();
+        let last = line - first;
+        lines[last] = text.chars().take(col).collect();
+
+        let width = indenter.width_after(&lines, last);
+        let rest_closes = rules.closes(indenter.code(&rest));
+        if rest_closes && rules.opens(indenter.code(&lines[last])) {
+            let outer = width.saturating_sub(indenter.unit);
+            return Some((
+                self.indent_like(width, &existing),
+                Some(self.indent_like(outer, &existing)),
+            ));
+        }
+        let width = if rest_closes {
+            width.saturating_sub(indenter.unit)
+        } else {
+            width
+        };
+        Some((self.indent_like(width, &existing), None))
+    }
+
+    /// Computes the new indentation of lines `start..=end`.
+    ///
+    /// Each line is indented from the lines above it, including the ones
+    /// already re-indented. Continuation lines move by as much as the line
+    /// they continue, which keeps hanging alignments intact. Blank lines are
+    /// left alone.
+    ///
+    /// # Returns
+    ///
+    /// One indentation per line, or `None` when the syntax has no rules
+    fn reindented(&self, start: usize, end: usize) -> Option<Vec<String>> {
+        let rules = self.active_indent_rules()?;
+        let comment = self.comment_tokens().line;
+        let indenter = Indenter {
+            rules: &rules,
+            comment: comment.as_deref(),
+            unit: self.indent_unit_width(),
+        };
+        let (mut lines, first) = self.indent_window(start, end);
+        let mut shifts = vec![0isize; lines.len()];
+        let mut indents = Vec::with_capacity(end + 1 - start);
+
+        for index in start - first..lines.len() {
+            let line = lines[index].clone();
+            let old_width = leading_width(&line);
+            let old_indent: String = line
+                .chars()
+                .take_while(|ch| *ch == ' ' || *ch == '\t')
+                .collect();
+            if line.trim().is_empty() {
+                indents.push(old_indent);
+                continue;
+            }
+            let width = match indenter.width_of(&lines, index) {
+                Some((width, true)) => {
+                    indenter.previous(&lines, index).map_or(width, |previous| {
+                        width.saturating_add_signed(shifts[previous])
+                    })
+                }
+                Some((width, false)) => width,
+                None if first == 0 => 0,
+                None => old_width,
+            };
+            shifts[index] = width as isize - old_width as isize;
+            let indent = self.indent_like(width, &old_indent);
+            lines[index] = format!("{indent}{}", line.trim_start());
+            indents.push(indent);
+        }
+        Some(indents)
+    }
+
+    /// Re-indents lines `start..=end` as one undoable command, moving every
+    /// cursor and selection with the text.
+    pub(crate) fn reindent_range(&mut self, start: usize, end: usize) {
+        let Some(indents) = self.reindented(start, end) else {
+            return;
+        };
+        let pos = self.cursors.primary_position();
+        let mut cmd =
+            ReindentLinesCommand::new(&self.buffer, start, &indents, pos);
+        if cmd.is_noop() {
+            return;
+        }
+        let mut cursor_pos = pos;
+        cmd.execute(&mut self.buffer, &mut cursor_pos);
+        for cursor in self.cursors.as_mut_slice() {
+            cursor.position = cmd.adjust_position(cursor.position);
+            if let Some(anchor) = cursor.anchor.as_mut() {
+                *anchor = cmd.adjust_position(*anchor);
+            }
+        }
+        self.history.push(Box::new(cmd));
+    }
+
+    /// Outdents the lines where a cursor has just typed a closing token as
+    /// the first thing on the line (`}`, `end`…); called after each typed
+    /// character.
+    ///
+    /// A word character typed right after a closing keyword makes it part
+    /// of a longer identifier (`end` becoming `endpoint`), so that line gets
+    /// its indentation back. Otherwise a line is only ever moved left, so
+    /// indenting a closing token by hand is not undone by the next
+    /// keystroke.
+    pub(crate) fn outdent_typed_closers(&mut self) {
+        if !self.auto_indent_enabled {
+            return;
+        }
+        let Some(rules) = self.active_indent_rules().map(Cow::into_owned)
+        else {
+            return;
+        };
+        let mut lines: Vec<(usize, bool)> = self
+            .cursors
+            .iter()
+            .filter(|cursor| !cursor.has_selection())
+            .map(|cursor| cursor.position)
+            .filter_map(|(line, col)| {
+                let text = self.buffer.line(line);
+                let before: String = text.chars().take(col).collect();
+                let next = text.chars().nth(col);
+                let typed = before.trim_start();
+                let closes = rules.outdent_on.iter().any(|token| {
+                    typed == token
+                        && !(token.ends_with(is_word_char)
+                            && next.is_some_and(is_word_char))
+                });
+                let extends = rules.outdent_on.iter().any(|token| {
+                    token.ends_with(is_word_char)
+                        && typed.strip_prefix(token.as_str()).is_some_and(
+                            |rest| {
+                                rest.chars().count() == 1
+                                    && rest.starts_with(is_word_char)
+                            },
+                        )
+                });
+                (closes || extends).then_some((line, closes))
+            })
+            .collect();
+        lines.dedup();
+
+        for (line, closes) in lines {
+            let old_width = leading_width(self.buffer.line(line));
+            let moves = self
+                .reindented(line, line)
+                .and_then(|indents| indents.into_iter().next())
+                .is_some_and(|indent| {
+                    let width = leading_width(&indent);
+                    if closes { width < old_width } else { width > old_width }
+                });
+            if moves {
+                self.reindent_range(line, line);
+            }
+        }
+    }
+
+    /// Re-indents the current line, or the lines spanned by the primary
+    /// selection, per the syntax's rules.
+    ///
+    /// Secondary cursors are collapsed onto the primary one. A no-op when
+    /// the syntax has no rules.
+    ///
+    /// # Returns
+    ///
+    /// A `Task<Message>` that scrolls to keep the cursor visible
+    pub(crate) fn reindent_lines(&mut self) -> Task<Message> {
+        self.end_grouping_if_active();
+        self.cursors.remove_all_but_primary();
+        let (start, end) = self.primary_line_range();
+        self.reindent_range(start, end);
+        self.finish_edit_operation();
+        self.scroll_to_cursor()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn editor_with(content: &str, syntax: &str) -> CodeEditor {
+        let mut editor = CodeEditor::new(content, syntax);
+        editor.request_focus();
+        editor.has_canvas_focus = true;
+        editor.focus_locked = false;
+        editor
+    }
+
+    fn enter_at_end(editor: &mut CodeEditor, line: usize) {
+        let col = editor.buffer.line_len(line);
+        editor.cursors.set_single((line, col));
+        let _ = editor.update(&Message::Enter);
+    }
+
+    #[test]
+    fn test_token_matching_respects_word_boundaries() {
+        assert!(ends_with_token("if x then", "then"));
+        assert!(!ends_with_token("strengthen", "then"));
+        assert!(ends_with_token("foo{", "{"));
+        assert!(starts_with_token("end)", "end"));
+        assert!(!starts_with_token("ending", "end"));
+        let lua = IndentRules::builtin("lua").unwrap_or_default();
+        assert!(!lua.closes("endpoint = 1"));
+        assert!(!lua.closes("elsewhere()"));
+        assert!(!lua.opens("x = thenable"));
+        assert!(lua.closes("end"));
+        assert_eq!(last_word_end("f = function(x)", "function"), Some(12));
+        assert_eq!(last_word_end("functional", "function"), None);
+    }
+
+    #[test]
+    fn test_code_strips_comments_outside_strings() {
+        let rules = IndentRules::new();
+        let indenter = Indenter { rules: &rules, comment: Some("//"), unit: 4 };
+        assert_eq!(indenter.code("  if x { // open"), "if x {");
+        assert_eq!(indenter.code("s = \"//\" +"), "s = \"//\" +");
+    }
+
+    #[test]
+    fn test_enter_indents_after_opener_and_splits_pairs() {
+        let mut editor = editor_with("fn main() {", "rs");
+        enter_at_end(&mut editor, 0);
+        assert_eq!(editor.content(), "fn main() {\n    ");
+        assert_eq!(editor.cursors.primary_position(), (1, 4));
+
+        let mut editor = editor_with("    if x {}", "rs");
+        editor.cursors.set_single((0, 10));
+        let _ = editor.update(&Message::Enter);
+        assert_eq!(editor.content(), "    if x {\n        \n    }");
+        assert_eq!(editor.cursors.primary_position(), (1, 8));
+
+        // The split is a single undo step.
+        let _ = editor.update(&Message::Undo);
+        assert_eq!(editor.content(), "    if x {}");
+    }
+
+    #[test]
+    fn test_enter_returns_to_statement_after_continuation_lines() {
+        let mut editor = editor_with("    call(a,\n         b);", "rs");
+        enter_at_end(&mut editor, 0);
+        assert_eq!(editor.buffer.line(1), "    ");
+
+        let mut editor = editor_with("    call(a,\n         b);", "rs");
+        enter_at_end(&mut editor, 1);
+        assert_eq!(editor.buffer.line(2), "    ");
+
+        // A continuation line keeps its own indentation.
+        let mut editor = editor_with("    call(a,\n         b,", "rs");
+        enter_at_end(&mut editor, 1);
+        assert_eq!(editor.buffer.line(2), "         ");
+    }
+
+    #[test]
+    fn test_typing_a_closer_outdents_the_line() {
+        let mut editor = editor_with("if x then\n    y()\n    ", "lua");
+        editor.cursors.set_single((2, 4));
+        for ch in "end".chars() {
+            let _ = editor.update(&Message::CharacterInput(ch));
+        }
+        assert_eq!(editor.buffer.line(2), "end");
+        assert_eq!(editor.cursors.primary_position(), (2, 3));
+
+        // Typing on past `end` makes it an identifier again.
+        let mut editor = editor_with("if x then\n    ", "lua");
+        editor.cursors.set_single((1, 4));
+        for ch in "endpoint = 1".chars() {
+            let _ = editor.update(&Message::CharacterInput(ch));
+        }
+        assert_eq!(editor.buffer.line(1), "    endpoint = 1");
+        assert_eq!(editor.cursors.primary_position(), (1, 16));
+        enter_at_end(&mut editor, 1);
+        assert_eq!(editor.buffer.line(2), "    ");
+
+        // Typing inside a longer word is not typing `end`.
+        let mut editor = editor_with("if x then\n    y()\n    enx", "lua");
+        editor.cursors.set_single((2, 6));
+        let _ = editor.update(&Message::CharacterInput('d'));
+        assert_eq!(editor.buffer.line(2), "    endx");
+    }
+
+    #[test]
+    fn test_python_rules_indent_after_colon_and_outdent_else() {
+        let mut editor = editor_with("if x:  # check", "py");
+        enter_at_end(&mut editor, 0);
+        for ch in "pass".chars() {
+            let _ = editor.update(&Message::CharacterInput(ch));
+        }
+        let _ = editor.update(&Message::Enter);
+        for ch in "else:".chars() {
+            let _ = editor.update(&Message::CharacterInput(ch));
+        }
+        assert_eq!(editor.content(), "if x:  # check\n    pass\nelse:");
+    }
+
+    #[test]
+    fn test_reindent_lines_fixes_blocks_and_keeps_hanging_alignment() {
+        let mut editor = editor_with(
+            "fn f() {\nlet x = call(a,\n             b);\nif x {\ny();\n}\n}",
+            "rs",
+        );
+        editor.cursors.set_single((0, 0));
+        editor.cursors.primary_mut().anchor = Some((0, 0));
+        editor.cursors.primary_mut().position = (6, 1);
+        let _ = editor.update(&Message::ReindentLines);
+        assert_eq!(
+            editor.content(),
+            "fn f() {\n    let x = call(a,\n                 b);\n    if x {\n        y();\n    }\n}"
+        );
+
+        let _ = editor.update(&Message::Undo);
+        assert!(editor.content().starts_with("fn f() {\nlet x"));
+    }
+
+    #[test]
+    fn test_reindent_on_paste_indents_pasted_block() {
+        let mut editor = editor_with("fn f() {\n    \n}", "rs");
+        editor.set_reindent_on_paste(true);
+        editor.cursors.set_single((1, 4));
+        let _ = editor.update(&Message::Paste("if x {\ny();\n}".to_string()));
+        assert_eq!(
+            editor.content(),
+            "fn f() {\n    if x {\n        y();\n    }\n}"
+        );
+        assert_eq!(editor.cursors.primary_position(), (3, 5));
+
+        let _ = editor.update(&Message::Undo);
+        assert_eq!(editor.content(), "fn f() {\n    \n}");
+    }
+
+    #[test]
+    fn test_custom_rules_override_builtin_and_tabs_are_honoured() {
+        let mut editor = editor_with("begin", "pas");
+        assert!(editor.indent_rules().is_none());
+        editor.set_indent_rules(
+            "PAS",
+            IndentRules::new().with_indent_after(&["begin"]),
+        );
+        editor.set_indent_style(IndentStyle::Tab);
+        enter_at_end(&mut editor, 0);
+        assert_eq!(editor.content(), "begin\n\t");
+
+        editor.clear_indent_rules("pas");
+        assert!(editor.indent_rules().is_none());
+    }
+
+    #[test]
+    fn test_without_rules_enter_copies_the_indentation() {
+        let mut editor = editor_with("  note {", "txt");
+        enter_at_end(&mut editor, 0);
+        assert_eq!(editor.content(), "  note {\n  ");
+    }
+}
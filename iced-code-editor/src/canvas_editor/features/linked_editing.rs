@@ -0,0 +1,515 @@
+//! Linked editing: typing in one range mirrors the edit into its partners.
+//!
+//! The typical case is an HTML or XML tag name, where renaming the opening
+//! tag renames the closing one with it. Linked ranges come from two sources:
+//!
+//! * **Language server**: the reply to `textDocument/linkedEditingRange`,
+//!   requested once the cursor rests and handed back through
+//!   [`CodeEditor::set_lsp_linked_editing_ranges`].
+//! * **Lexical fallback**: for markup syntaxes, the opening and closing tag
+//!   names paired by a lightweight tag scan, computed on the first keystroke
+//!   inside a tag name.
+//!
+//! A mirrored keystroke is applied by placing a temporary cursor at the same
+//! offset in every partner range and running the ordinary edit through the
+//! multi-cursor machinery, so the whole change is one undo step. The ranges
+//! are then shifted to follow the edit and kept for the next keystroke; any
+//! edit that is not mirrored drops them.
+
+use iced::Task;
+
+use crate::buffer::TextBuffer;
+use crate::canvas_editor::lsp::LspRange;
+use crate::canvas_editor::{CodeEditor, Message};
+
+/// Maximum number of lines the lexical tag scan reads.
+///
+/// Bounds the cost of the first mirrored keystroke in a huge document; a
+/// closing tag further down than this is not paired.
+const MAX_TAG_SCAN_LINES: usize = 10_000;
+
+/// Elements that never have a closing tag in HTML.
+const VOID_ELEMENTS: &[&str] = &[
+    "area", "base", "br", "col", "embed", "hr", "img", "input", "link", "meta",
+    "source", "track", "wbr",
+];
+
+/// One linked range, confined to a single line.
+///
+/// Columns are counted in characters; `end` is exclusive.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct LinkedRange {
+    /// Line holding the range.
+    pub(crate) line: usize,
+    /// Column of the range's first character.
+    pub(crate) start: usize,
+    /// Column one past the range's last character.
+    pub(crate) end: usize,
+}
+
+impl LinkedRange {
+    /// Returns `true` when a caret at `position` lies inside this range or
+    /// on either of its edges.
+    fn touches(&self, (line, col): (usize, usize)) -> bool {
+        self.line == line && self.start <= col && col <= self.end
+    }
+}
+
+/// Linked-editing state owned by the editor.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct LinkedEditingState {
+    /// The ranges edited together, in document order. Empty when no linked
+    /// session is active.
+    pub(crate) ranges: Vec<LinkedRange>,
+}
+
+/// A keystroke that can be mirrored into linked ranges.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum LinkedEdit {
+    /// A typed character.
+    Insert(char),
+    /// Deletion of the character before the caret.
+    Backspace,
+    /// Deletion of the character after the caret.
+    Delete,
+}
+
+/// Returns `true` for characters that may appear in a tag name.
+fn is_tag_name_char(ch: char) -> bool {
+    ch.is_alphanumeric() || matches!(ch, '-' | '_' | ':' | '.')
+}
+
+/// Returns `true` when `syntax` is a markup language whose tags can be
+/// paired lexically.
+fn is_markup_syntax(syntax: &str) -> bool {
+    matches!(syntax, "html" | "htm" | "xhtml" | "xml" | "svg" | "vue")
+}
+
+/// A tag name found by [`scan_tags`].
+struct Tag {
+    /// The tag name as written.
+    name: String,
+    /// Where the name sits.
+    range: LinkedRange,
+    /// Whether this is a closing tag (`</name>`).
+    closing: bool,
+}
+
+/// Collects the opening and closing tags of a markup document, in order.
+///
+/// Self-closing tags, void elements, comments, doctypes and processing
+/// instructions are skipped, and quoted attribute values may contain `>`.
+/// Reads at most [`MAX_TAG_SCAN_LINES`] lines.
+fn scan_tags(buffer: &TextBuffer) -> Vec<Tag> {
+    let mut tags = Vec::new();
+    let mut in_comment = false;
+    // Inside a tag's `<...>`: whether it pushed an opening tag (undone if it
+    // turns out to be self-closing), and the open attribute quote, if any.
+    let mut in_tag: Option<(bool, Option<char>)> = None;
+
+    for line in 0..buffer.line_count().min(MAX_TAG_SCAN_LINES) {
+        let chars: Vec<char> = buffer.line(line).chars().collect();
+        let mut col = 0;
+        while col < chars.len() {
+            let ch = chars[col];
+            if in_comment {
+                if chars[col..].starts_with(&['-', '-', '>']) {
+                    in_comment = false;
+                    col += 3;
+                } else {
+                    col += 1;
+                }
+                continue;
+            }
+            if let Some((pushed_opening, quote)) = in_tag.as_mut() {
+                match *quote {
+                    Some(open) if ch == open => *quote = None,
+                    Some(_) => {}
+                    None if ch == '"' || ch == '\'' => *quote = Some(ch),
+                    None if ch == '>' => {
+                        if *pushed_opening && col > 0 && chars[col - 1] == '/' {
+                            tags.pop();
+                        }
+                        in_tag = None;
+                    }
+                    None => {}
+                }
+                col += 1;
+                continue;
+            }
+            if ch != '<' {
+                col += 1;
+                continue;
+            }
+            if chars[col..].starts_with(&['<', '!', '-', '-']) {
+                in_comment = true;
+                col += 4;
+                continue;
+            }
+
+            let closing = chars.get(col + 1) == Some(&'/');
+            let name_start = col + 1 + usize::from(closing);
+            let name_end = chars[name_start.min(chars.len())..]
+                .iter()
+                .take_while(|ch| is_tag_name_char(**ch))
+                .count()
+                + name_start;
+            let named =
+                chars.get(name_start).is_some_and(|ch| ch.is_alphabetic())
+                    && name_end > name_start;
+            if named {
+                let name: String = chars[name_start..name_end].iter().collect();
+                let pushed = closing
+                    || !VOID_ELEMENTS.contains(&name.to_lowercase().as_str());
+                if pushed {
+                    tags.push(Tag {
+                        name,
+                        range: LinkedRange {
+                            line,
+                            start: name_start,
+                            end: name_end,
+                        },
+                        closing,
+                    });
+                }
+                in_tag = Some((pushed && !closing, None));
+                col = name_end;
+            } else {
+                if matches!(chars.get(col + 1), Some('!' | '?')) {
+                    in_tag = Some((false, None));
+                }
+                col += 1;
+            }
+        }
+    }
+    tags
+}
+
+/// Finds the tag name under `position` and the name of its partner tag.
+///
+/// Returns the two ranges in document order, or `None` when the caret is
+/// not on a tag name or the tag has no partner.
+fn lexical_tag_pair(
+    buffer: &TextBuffer,
+    position: (usize, usize),
+) -> Option<Vec<LinkedRange>> {
+    // Cheap check before scanning the document: the caret must sit in a
+    // name directly preceded by `<` or `</`.
+    let (line, col) = position;
+    let chars: Vec<char> = buffer.line(line).chars().collect();
+    let col = col.min(chars.len());
+    let name_start = chars[..col]
+        .iter()
+        .rposition(|ch| !is_tag_name_char(*ch))
+        .map_or(0, |index| index + 1);
+    let before = name_start.checked_sub(1).and_then(|index| chars.get(index));
+    if !matches!(before, Some('<' | '/')) {
+        return None;
+    }
+
+    let tags = scan_tags(buffer);
+    let mut open: Vec<usize> = Vec::new();
+    for (index, tag) in tags.iter().enumerate() {
+        if !tag.closing {
+            open.push(index);
+            continue;
+        }
+        // Unclosed tags in between are implicitly closed, as browsers do.
+        let Some(depth) =
+            open.iter().rposition(|&opening| tags[opening].name == tag.name)
+        else {
+            continue;
+        };
+        let opening = open[depth];
+        open.truncate(depth);
+        if tags[opening].range.touches(position) || tag.range.touches(position)
+        {
+            return Some(vec![tags[opening].range, tag.range]);
+        }
+    }
+    None
+}
+
+impl CodeEditor {
+    /// Asks the language server for the ranges linked to the caret; called
+    /// once the cursor has rested.
+    pub(crate) fn request_linked_editing_ranges(&mut self) {
+        if self.linked_editing_enabled {
+            self.lsp_request_linked_editing_range();
+        }
+    }
+
+    /// Replaces the linked ranges with a language server's answer.
+    ///
+    /// Feed this the payload of a `textDocument/linkedEditingRange` response
+    /// (with the bundled client, [`LspEvent::LinkedEditingRange`]). Ranges
+    /// are read in UTF-16 code units, as servers send them. The reply is
+    /// ignored unless it holds at least two single-line ranges and one of
+    /// them touches the primary cursor; an empty reply ends linked editing.
+    ///
+    /// # Arguments
+    ///
+    /// * `ranges` - The linked ranges reported by the server
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use iced_code_editor::{CodeEditor, LspPosition, LspRange};
+    ///
+    /// let mut editor = CodeEditor::new("<div></div>", "html");
+    /// let range = |start, end| LspRange {
+    ///     start: LspPosition { line: 0, character: start },
+    ///     end: LspPosition { line: 0, character: end },
+    /// };
+    ///
+    /// // Typing inside either `div` now renames both.
+    /// editor.set_lsp_linked_editing_ranges(&[range(1, 4), range(7, 10)]);
+    /// ```
+    ///
+    /// [`LspEvent::LinkedEditingRange`]: crate::LspEvent::LinkedEditingRange
+    pub fn set_lsp_linked_editing_ranges(&mut self, ranges: &[LspRange]) {
+        if !self.linked_editing_enabled {
+            return;
+        }
+        if ranges.is_empty() {
+            self.linked_editing_state.ranges.clear();
+            return;
+        }
+
+        let mut linked = Vec::with_capacity(ranges.len());
+        for range in ranges {
+            let start = self.position_from_lsp(range.start);
+            let end = self.position_from_lsp(range.end);
+            if start.0 != end.0 || end.1 < start.1 {
+                return;
+            }
+            linked.push(LinkedRange {
+                line: start.0,
+                start: start.1,
+                end: end.1,
+            });
+        }
+        linked.sort_by_key(|range| (range.line, range.start));
+
+        let position = self.cursors.primary_position();
+        if linked.len() < 2
+            || !linked.iter().any(|range| range.touches(position))
+        {
+            return;
+        }
+        self.linked_editing_state.ranges = linked;
+    }
+
+    /// Runs `apply` — the ordinary handler for `edit` — and mirrors the
+    /// keystroke into every range linked to the caret.
+    ///
+    /// When linked editing does not apply (several cursors, a selection, a
+    /// caret outside any linked range, or a character that cannot be part of
+    /// a tag name), `apply` runs alone and the linked ranges are dropped by
+    /// the edit.
+    ///
+    /// # Returns
+    ///
+    /// The task returned by `apply`
+    pub(crate) fn with_linked_editing(
+        &mut self,
+        edit: LinkedEdit,
+        apply: impl FnOnce(&mut Self) -> Task<Message>,
+    ) -> Task<Message> {
+        let Some((ranges, index, offset)) = self.plan_linked_edit(edit) else {
+            return apply(self);
+        };
+
+        let primary = self.cursors.primary_position();
+        for (other, range) in ranges.iter().enumerate() {
+            if other != index {
+                self.cursors.add_cursor((range.line, range.start + offset));
+            }
+        }
+        // `add_cursor` makes each new cursor primary; hand it back.
+        self.cursors.add_cursor(primary);
+
+        // Typing is already grouped for undo; a multi-cursor deletion is
+        // not, so group it here to undo the mirrored edit in one step.
+        let own_group = !matches!(edit, LinkedEdit::Insert(_))
+            && !self.keep_vim_insert_group();
+        if own_group {
+            self.end_grouping_if_active();
+            self.history.begin_group();
+        }
+        let revision = self.buffer_revision;
+        let task = apply(self);
+        if own_group {
+            self.history.end_group();
+        }
+        self.cursors.remove_all_but_primary();
+        if self.buffer_revision == revision {
+            self.linked_editing_state.ranges = ranges;
+            return task;
+        }
+
+        let grows = matches!(edit, LinkedEdit::Insert(_));
+        let mut shifted = Vec::with_capacity(ranges.len());
+        let mut earlier_on_line = 0;
+        for (position, range) in ranges.iter().enumerate() {
+            if position > 0 && ranges[position - 1].line != range.line {
+                earlier_on_line = 0;
+            }
+            let (start, end) = if grows {
+                (range.start + earlier_on_line, range.end + earlier_on_line + 1)
+            } else {
+                (range.start - earlier_on_line, range.end - earlier_on_line - 1)
+            };
+            shifted.push(LinkedRange { line: range.line, start, end });
+            earlier_on_line += 1;
+        }
+        self.linked_editing_state.ranges = shifted;
+        task
+    }
+
+    /// Decides whether `edit` should be mirrored, and where.
+    ///
+    /// Returns the linked ranges, the index of the one holding the caret,
+    /// and the caret's offset within it.
+    fn plan_linked_edit(
+        &mut self,
+        edit: LinkedEdit,
+    ) -> Option<(Vec<LinkedRange>, usize, usize)> {
+        if !self.linked_editing_enabled
+            || self.cursors.is_multi()
+            || self.cursors.primary().has_selection()
+        {
+            return None;
+        }
+        if let LinkedEdit::Insert(ch) = edit
+            && !is_tag_name_char(ch)
+        {
+            return None;
+        }
+
+        let position = self.cursors.primary_position();
+        let stored = &self.linked_editing_state.ranges;
+        let ranges = if stored.iter().any(|range| range.touches(position)) {
+            stored.clone()
+        } else if is_markup_syntax(&self.syntax) {
+            lexical_tag_pair(&self.buffer, position)?
+        } else {
+            return None;
+        };
+
+        let index = ranges.iter().position(|range| range.touches(position))?;
+        let range = ranges[index];
+        let offset = position.1 - range.start;
+        let mirrorable = match edit {
+            LinkedEdit::Insert(_) => true,
+            LinkedEdit::Backspace => offset > 0,
+            LinkedEdit::Delete => offset < range.end - range.start,
+        };
+        mirrorable.then_some((ranges, index, offset))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::canvas_editor::lsp::LspPosition;
+
+    fn editor_at(
+        content: &str,
+        syntax: &str,
+        position: (usize, usize),
+    ) -> CodeEditor {
+        let mut editor = CodeEditor::new(content, syntax);
+        editor.request_focus();
+        editor.has_canvas_focus = true;
+        editor.focus_locked = false;
+        editor.cursors.set_single(position);
+        editor
+    }
+
+    #[test]
+    fn test_scan_tags_skips_void_self_closing_and_comments() {
+        let buffer = TextBuffer::new(
+            "<!-- <p> --><div a=\"x>y\"><br><img/><span/></div>",
+        );
+        let names: Vec<(String, bool)> = scan_tags(&buffer)
+            .into_iter()
+            .map(|tag| (tag.name, tag.closing))
+            .collect();
+        assert_eq!(
+            names,
+            [("div".to_string(), false), ("div".to_string(), true)]
+        );
+    }
+
+    #[test]
+    fn test_lexical_tag_pair_matches_nested_tags() {
+        let buffer = TextBuffer::new("<ul>\n  <li><ul></ul></li>\n</ul>");
+        let outer = Some(vec![
+            LinkedRange { line: 0, start: 1, end: 3 },
+            LinkedRange { line: 2, start: 2, end: 4 },
+        ]);
+        assert_eq!(lexical_tag_pair(&buffer, (0, 2)), outer);
+        assert_eq!(lexical_tag_pair(&buffer, (2, 4)), outer);
+        // Outside a tag name there is nothing to link.
+        assert_eq!(lexical_tag_pair(&buffer, (1, 0)), None);
+    }
+
+    #[test]
+    fn test_typing_in_opening_tag_renames_closing_tag() {
+        let mut editor = editor_at("<div>x</div>", "html", (0, 4));
+
+        let _ = editor.update(&Message::CharacterInput('s'));
+        let _ = editor.update(&Message::CharacterInput('x'));
+        assert_eq!(editor.content(), "<divsx>x</divsx>");
+        assert_eq!(editor.cursors.primary_position(), (0, 6));
+        assert!(!editor.cursors.is_multi());
+
+        let _ = editor.update(&Message::Backspace);
+        assert_eq!(editor.content(), "<divs>x</divs>");
+
+        // The mirrored edit is a single undo step.
+        let _ = editor.update(&Message::Undo);
+        assert_eq!(editor.content(), "<divsx>x</divsx>");
+    }
+
+    #[test]
+    fn test_editing_closing_tag_renames_opening_tag() {
+        let mut editor = editor_at("<p>\n</p>", "html", (1, 3));
+
+        let _ = editor.update(&Message::Backspace);
+        let _ = editor.update(&Message::CharacterInput('b'));
+        assert_eq!(editor.content(), "<b>\n</b>");
+    }
+
+    #[test]
+    fn test_non_markup_and_non_name_characters_are_not_mirrored() {
+        let mut editor = editor_at("<div></div>", "rs", (0, 4));
+        let _ = editor.update(&Message::CharacterInput('s'));
+        assert_eq!(editor.content(), "<divs></div>");
+
+        let mut editor = editor_at("<div></div>", "html", (0, 4));
+        let _ = editor.update(&Message::CharacterInput(' '));
+        assert_eq!(editor.content(), "<div ></div>");
+    }
+
+    #[test]
+    fn test_server_ranges_drive_linked_editing() {
+        let mut editor = editor_at("<Foo></Foo>", "tsx", (0, 4));
+        let range = |start, end| LspRange {
+            start: LspPosition { line: 0, character: start },
+            end: LspPosition { line: 0, character: end },
+        };
+        editor.set_lsp_linked_editing_ranges(&[range(1, 4), range(7, 10)]);
+
+        let _ = editor.update(&Message::CharacterInput('d'));
+        assert_eq!(editor.content(), "<Food></Food>");
+        assert_eq!(
+            editor.linked_editing_state.ranges,
+            [
+                LinkedRange { line: 0, start: 1, end: 5 },
+                LinkedRange { line: 0, start: 8, end: 12 },
+            ]
+        );
+    }
+}
@@ -0,0 +1,255 @@
+//! Marks and the jumplist.
+//!
+//! In Vim mode, `m{a-z}` marks the cursor position; `` `{mark} `` jumps back
+//! to it and `'{mark}` to the first non-blank character of its line. Marks
+//! follow the text as lines are inserted or removed above them, the same way
+//! breakpoints do.
+//!
+//! The jumplist records where the cursor was before each long jump: `G`,
+//! `gg`, `/` searches and `n`/`N`, mark jumps, go-to-line,
+//! [`Message::GotoPosition`] and go-to-definition requests.
+//! [`Message::NavigateBack`] and [`Message::NavigateForward`] walk it, bound
+//! to `Ctrl+O`/`Ctrl+I` in Vim's Normal mode and to `Alt+Left`/`Alt+Right`
+//! for everyone. The position before the latest jump is also the Vim mark
+//! `` ` `` (or `'`), so ``` `` ``` returns there.
+
+use std::collections::HashMap;
+
+use iced::Task;
+
+use super::breakpoints::shift_line_after_edit;
+use crate::canvas_editor::{CodeEditor, Message, VimMode};
+
+/// Number of positions the jumplist keeps.
+pub(crate) const JUMPLIST_LIMIT: usize = 100;
+
+/// The mark holding the position before the latest jump.
+pub(crate) const PREVIOUS_CONTEXT_MARK: char = '`';
+
+/// Marks and jumplist owned by the editor.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct MarkState {
+    /// Marked positions by name.
+    marks: HashMap<char, (usize, usize)>,
+    /// Positions jumped from, oldest first.
+    jumps: Vec<(usize, usize)>,
+    /// The entry navigation stands on; `jumps.len()` until the first
+    /// [`MarkState::back`].
+    jump_index: usize,
+    /// Buffer line count the positions above were last reconciled with.
+    line_count: usize,
+}
+
+impl MarkState {
+    pub(crate) fn mark(&self, name: char) -> Option<(usize, usize)> {
+        self.marks.get(&name).copied()
+    }
+
+    /// Records `position` as the place a jump left, dropping the positions
+    /// navigated back over and any older entry on the same line.
+    fn push_jump(&mut self, position: (usize, usize)) {
+        self.jumps.truncate(self.jump_index);
+        self.jumps.retain(|jump| jump.0 != position.0);
+        self.jumps.push(position);
+        if self.jumps.len() > JUMPLIST_LIMIT {
+            self.jumps.remove(0);
+        }
+        self.jump_index = self.jumps.len();
+    }
+
+    /// Steps back from `current`, returning the position to move to.
+    ///
+    /// The first step back also records `current`, so navigating forward
+    /// again returns to it.
+    fn back(&mut self, current: (usize, usize)) -> Option<(usize, usize)> {
+        if self.jump_index == self.jumps.len() {
+            self.push_jump(current);
+            self.jump_index = self.jumps.len() - 1;
+        }
+        self.jump_index = self.jump_index.checked_sub(1)?;
+        self.jumps.get(self.jump_index).copied()
+    }
+
+    /// Steps forward again after [`MarkState::back`].
+    fn forward(&mut self) -> Option<(usize, usize)> {
+        let position = self.jumps.get(self.jump_index + 1).copied()?;
+        self.jump_index += 1;
+        Some(position)
+    }
+}
+
+impl CodeEditor {
+    /// Marks `position` as `name`.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - The mark, `a`–`z` or [`PREVIOUS_CONTEXT_MARK`]
+    /// * `position` - The marked position
+    pub(crate) fn set_mark(&mut self, name: char, position: (usize, usize)) {
+        self.mark_state.line_count = self.buffer.line_count();
+        self.mark_state.marks.insert(name, position);
+    }
+
+    /// Records the cursor position in the jumplist, before the cursor jumps
+    /// away.
+    pub(crate) fn push_jump(&mut self) {
+        let position = self.cursors.primary_position();
+        self.set_mark(PREVIOUS_CONTEXT_MARK, position);
+        self.mark_state.push_jump(position);
+    }
+
+    /// Moves marks and jumplist entries along with the lines an edit
+    /// inserted or removed, as [`Self::shift_breakpoints_after_edit`] does
+    /// for breakpoints.
+    pub(crate) fn shift_marks_after_edit(&mut self) {
+        let line_count = self.buffer.line_count();
+        let state = &mut self.mark_state;
+        let old_count = std::mem::replace(&mut state.line_count, line_count);
+        if old_count == line_count
+            || (state.marks.is_empty() && state.jumps.is_empty())
+        {
+            return;
+        }
+
+        let edit_line = self.pre_edit_line;
+        let shift = |(line, col): (usize, usize)| {
+            (shift_line_after_edit(line, edit_line, old_count, line_count), col)
+        };
+        for position in state.marks.values_mut() {
+            *position = shift(*position);
+        }
+        for position in &mut state.jumps {
+            *position = shift(*position);
+        }
+    }
+
+    /// Handles [`Message::NavigateBack`] and [`Message::NavigateForward`]:
+    /// moves the cursor to the previous or next jumplist position.
+    ///
+    /// # Arguments
+    ///
+    /// * `forward` - Whether to step forward rather than back
+    ///
+    /// # Returns
+    ///
+    /// A `Task<Message>` that scrolls to keep the cursor visible
+    pub(crate) fn handle_navigate_msg(
+        &mut self,
+        forward: bool,
+    ) -> Task<Message> {
+        let target = if forward {
+            self.mark_state.forward()
+        } else {
+            self.mark_state.back(self.cursors.primary_position())
+        };
+        let Some((line, col)) = target else {
+            return Task::none();
+        };
+        self.end_grouping_if_active();
+        let line = line.min(self.buffer.line_count().saturating_sub(1));
+        let col = col.min(self.buffer.line_len(line));
+        if self.vim_enabled() && self.vim_state.mode() != VimMode::Insert {
+            let target = self.vim_normal_position((line, col));
+            self.move_vim_cursor(target);
+        } else {
+            self.cursors.set_single((line, col));
+        }
+        self.finish_navigation_operation();
+        self.scroll_to_cursor()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{LspClient, LspDocument};
+
+    #[test]
+    fn test_navigate_back_and_forward_through_jumps() {
+        let mut editor = CodeEditor::new("a\nb\nc\nd\ne", "txt");
+        let _ = editor.update(&Message::GotoPosition(2, 0));
+        let _ = editor.update(&Message::GotoPosition(4, 0));
+
+        let _ = editor.update(&Message::NavigateBack);
+        assert_eq!(editor.cursors.primary_position(), (2, 0));
+        let _ = editor.update(&Message::NavigateBack);
+        assert_eq!(editor.cursors.primary_position(), (0, 0));
+        let _ = editor.update(&Message::NavigateBack);
+        assert_eq!(editor.cursors.primary_position(), (0, 0));
+
+        let _ = editor.update(&Message::NavigateForward);
+        let _ = editor.update(&Message::NavigateForward);
+        assert_eq!(editor.cursors.primary_position(), (4, 0));
+        let _ = editor.update(&Message::NavigateForward);
+        assert_eq!(editor.cursors.primary_position(), (4, 0));
+    }
+
+    #[test]
+    fn test_a_new_jump_drops_the_positions_navigated_back_over() {
+        let mut editor = CodeEditor::new("a\nb\nc\nd\ne", "txt");
+        let _ = editor.update(&Message::GotoPosition(1, 0));
+        let _ = editor.update(&Message::GotoPosition(2, 0));
+        let _ = editor.update(&Message::NavigateBack);
+        let _ = editor.update(&Message::NavigateBack);
+        assert_eq!(editor.cursors.primary_position(), (0, 0));
+
+        let _ = editor.update(&Message::GotoPosition(4, 0));
+        let _ = editor.update(&Message::NavigateForward);
+        assert_eq!(editor.cursors.primary_position(), (4, 0));
+        let _ = editor.update(&Message::NavigateBack);
+        assert_eq!(editor.cursors.primary_position(), (0, 0));
+    }
+
+    #[test]
+    fn test_go_to_definition_records_the_position_it_leaves() {
+        struct DefinitionClient;
+        impl LspClient for DefinitionClient {}
+
+        let mut editor = CodeEditor::new("a\nb\nc\nd", "txt");
+        editor.cursors.set_single((1, 0));
+        // Without a client nothing is requested, so nothing is recorded.
+        editor.lsp_request_definition();
+        editor.attach_lsp(
+            Box::new(DefinitionClient),
+            LspDocument::new("file:///tmp/a.txt", "plaintext"),
+        );
+        editor.lsp_request_definition();
+        let _ = editor.set_cursor(3, 0);
+
+        let _ = editor.update(&Message::NavigateBack);
+        assert_eq!(editor.cursors.primary_position(), (1, 0));
+        let _ = editor.update(&Message::NavigateBack);
+        assert_eq!(editor.cursors.primary_position(), (1, 0));
+    }
+
+    #[test]
+    fn test_jumplist_keeps_one_entry_per_line_up_to_the_limit() {
+        let mut state = MarkState::default();
+        for line in 0..JUMPLIST_LIMIT + 10 {
+            state.push_jump((line, 0));
+        }
+        state.push_jump((50, 3));
+
+        assert_eq!(state.jumps.len(), JUMPLIST_LIMIT);
+        assert_eq!(state.jumps.first(), Some(&(10, 0)));
+        assert_eq!(state.jumps.last(), Some(&(50, 3)));
+        assert_eq!(state.jumps.iter().filter(|jump| jump.0 == 50).count(), 1);
+    }
+
+    #[test]
+    fn test_jumps_follow_lines_inserted_and_removed_above_them() {
+        let mut editor = CodeEditor::new("a\nb\nc\nd", "txt");
+        let _ = editor.update(&Message::GotoPosition(3, 1));
+        let _ = editor.update(&Message::GotoPosition(0, 1));
+
+        let _ = editor.update(&Message::Enter);
+        let _ = editor.update(&Message::NavigateBack);
+        assert_eq!(editor.cursors.primary_position(), (4, 1));
+
+        editor.cursors.set_single((1, 0));
+        let _ = editor.update(&Message::Backspace);
+        let _ = editor.update(&Message::NavigateForward);
+        let _ = editor.update(&Message::NavigateBack);
+        assert_eq!(editor.cursors.primary_position(), (3, 1));
+    }
+}
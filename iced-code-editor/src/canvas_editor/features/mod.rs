@@ -1,14 +1,30 @@
-//! Optional editor features: bracket matching, inline color previews, the
-//! command palette, the right-click context menu, code folding, go-to-line,
-//! indentation guides, search/replace, and Vim emulation.
+//! Optional editor features: breakpoints, bracket matching, the clipboard
+//! history, inline color previews and the color picker, column selection, the
+//! command palette, comment tokens, the right-click context menu, document
+//! colors, document highlights, document links, code folding, go-to-line, indentation guides, indentation rules,
+//! linked editing, marks and the jumplist, search/replace, expand/shrink
+//! selection, snippets, and Vim emulation.
 
 pub(crate) mod actions;
 pub(crate) mod bracket_match;
+pub mod breakpoints;
+pub(crate) mod clipboard_history;
+pub(crate) mod color_picker;
 pub(crate) mod color_preview;
+pub(crate) mod column_selection;
 pub(crate) mod command_palette;
+pub mod comment_tokens;
 pub(crate) mod context_menu;
+pub(crate) mod document_color;
+pub(crate) mod document_highlight;
+pub(crate) mod document_link;
 pub mod folding;
 pub(crate) mod goto_line;
 pub(crate) mod indent_guides;
+pub mod indent_rules;
+pub(crate) mod linked_editing;
+pub(crate) mod marks;
 pub(crate) mod search;
+pub(crate) mod selection_range;
+pub mod snippet;
 pub(crate) mod vim;
@@ -45,6 +45,10 @@ pub struct SearchState {
     pub replace_with: String,
     /// Case-sensitive search flag
     pub case_sensitive: bool,
+    /// Whether a match must be a whole word, with no letter, digit or `_`
+    /// right before or after it, as Vim's `*` and `#` search. The dialog
+    /// turns this off when it opens.
+    pub whole_word: bool,
     /// Whether the search dialog is visible
     pub is_open: bool,
     /// Whether replace mode is active (true) or just search (false)
@@ -69,6 +73,7 @@ impl Default for SearchState {
             query: String::new(),
             replace_with: String::new(),
             case_sensitive: false,
+            whole_word: false,
             is_open: false,
             is_replace_mode: false,
             matches: Vec::new(),
@@ -91,6 +96,7 @@ impl SearchState {
     /// Opens the search dialog in search-only mode.
     pub fn open_search(&mut self) {
         self.is_open = true;
+        self.whole_word = false;
         self.is_replace_mode = false;
         self.focused_field = SearchFocusedField::Search;
     }
@@ -98,6 +104,7 @@ impl SearchState {
     /// Opens the search dialog in search-and-replace mode.
     pub fn open_replace(&mut self) {
         self.is_open = true;
+        self.whole_word = false;
         self.is_replace_mode = true;
         self.focused_field = SearchFocusedField::Search;
     }
@@ -154,6 +161,9 @@ impl SearchState {
             self.case_sensitive,
             Some(MAX_MATCHES),
         );
+        if self.whole_word {
+            retain_whole_words(buffer, &self.query, &mut self.matches);
+        }
         self.buffer_line_count = buffer.line_count();
 
         // Update current match index
@@ -206,7 +216,7 @@ impl SearchState {
             self.matches.partition_point(|item| item.line < start_line);
         let replace_end =
             self.matches.partition_point(|item| item.line < old_end_exclusive);
-        let replacement = find_matches_in_range(
+        let mut replacement = find_matches_in_range(
             buffer,
             &self.query,
             self.case_sensitive,
@@ -214,6 +224,9 @@ impl SearchState {
             new_end_exclusive,
             Some(MAX_MATCHES),
         );
+        if self.whole_word {
+            retain_whole_words(buffer, &self.query, &mut replacement);
+        }
         let replacement_len = replacement.len();
 
         self.matches.splice(replace_start..replace_end, replacement);
@@ -512,6 +525,24 @@ pub fn find_matches(
     find_matches_in_range(buffer, query, case_sensitive, 0, line_count, limit)
 }
 
+/// Drops the matches of `query` that continue a longer word, keeping only
+/// those with no letter, digit or `_` right before or after them.
+pub(crate) fn retain_whole_words(
+    buffer: &TextBuffer,
+    query: &str,
+    matches: &mut Vec<SearchMatch>,
+) {
+    let query_len = query.chars().count();
+    let is_word = |ch: char| ch.is_alphanumeric() || ch == '_';
+    matches.retain(|item| {
+        let line = buffer.line(item.line);
+        let before =
+            item.col.checked_sub(1).and_then(|col| line.chars().nth(col));
+        let after = line.chars().nth(item.col + query_len);
+        !before.is_some_and(is_word) && !after.is_some_and(is_word)
+    });
+}
+
 /// Threshold for line count to trigger parallel search.
 const PARALLEL_SEARCH_THRESHOLD: usize = 1000;
 
@@ -547,7 +578,9 @@ pub fn get_visible_match_range(
     start_idx..end_idx
 }
 
-fn find_matches_in_range(
+/// Finds the matches on lines `start_line..end_line`, as [`find_matches`]
+/// does for the whole buffer.
+pub(crate) fn find_matches_in_range(
     buffer: &TextBuffer,
     query: &str,
     case_sensitive: bool,
@@ -700,7 +733,8 @@ impl CodeEditor {
 
     pub(crate) fn search_matches_visible(&self) -> bool {
         self.search_state.is_open
-            || (self.vim_enabled && self.vim_state.last_search().is_some())
+            || (self.vim_enabled
+                && self.vim_state.highlighted_search().is_some())
     }
 
     /// Opens the search dialog programmatically.
@@ -902,6 +936,31 @@ mod tests {
         assert_eq!(state.match_count(), 2);
     }
 
+    #[test]
+    fn test_search_state_whole_word() {
+        let mut buffer = TextBuffer::new("foo foobar _foo\nfoo.x");
+        let mut state = SearchState::new();
+        state.whole_word = true;
+        state.set_query("foo".to_string(), &buffer);
+        assert_eq!(
+            state.matches,
+            vec![
+                SearchMatch { line: 0, col: 0 },
+                SearchMatch { line: 1, col: 0 },
+            ]
+        );
+
+        // Edits keep to whole words too.
+        buffer.insert_char(1, 3, 'd');
+        state.update_matches_after_edit(&buffer, 1, 2);
+        assert_eq!(state.matches, vec![SearchMatch { line: 0, col: 0 }]);
+
+        // The dialog searches plain text.
+        state.open_search();
+        state.update_matches(&buffer);
+        assert_eq!(state.match_count(), 4);
+    }
+
     #[test]
     fn test_incremental_match_update_replaces_only_affected_lines() {
         let mut buffer = TextBuffer::new("foo\nfoo\nfoo");
@@ -210,12 +210,19 @@ impl CodeEditor {
     /// A `Task<Message>` that scrolls to cursor after replacement
     pub(crate) fn handle_replace_all_msg(&mut self) -> Task<Message> {
         // Perform a fresh search to find ALL matches (ignoring the display limit)
-        let all_matches = super::find_matches(
+        let mut all_matches = super::find_matches(
             &self.buffer,
             &self.search_state.query,
             self.search_state.case_sensitive,
             None, // No limit for Replace All
         );
+        if self.search_state.whole_word {
+            super::retain_whole_words(
+                &self.buffer,
+                &self.search_state.query,
+                &mut all_matches,
+            );
+        }
 
         if !all_matches.is_empty() {
             let query_len = self.search_state.query.chars().count();
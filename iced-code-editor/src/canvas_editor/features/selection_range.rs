@@ -0,0 +1,401 @@
+//! Expand Selection and Shrink Selection.
+//!
+//! `Shift+Alt+Right` grows the primary selection to the next enclosing
+//! syntactic range and `Shift+Alt+Left` walks back through the selections it
+//! replaced. The enclosing ranges come from two sources:
+//!
+//! * **Language server**: the chain returned by `textDocument/selectionRange`
+//!   for the position where expansion started, handed back through
+//!   [`CodeEditor::set_lsp_selection_ranges`]. Expansions requested while the
+//!   reply is on its way are queued and applied once it arrives.
+//! * **Lexical fallback**: the word under the cursor, the contents of the
+//!   enclosing brackets, the brackets themselves, the line, and finally the
+//!   whole document. Used when no server is attached, when it answers with
+//!   nothing, or when its chain has been exhausted.
+//!
+//! An expansion session lasts as long as the primary selection is the one the
+//! last expansion or shrink produced; moving the cursor or editing starts a
+//! fresh one.
+
+use iced::Task;
+#[cfg(not(target_arch = "wasm32"))]
+use std::time::{Duration, Instant};
+#[cfg(target_arch = "wasm32")]
+use {std::time::Duration, web_time::Instant};
+
+use crate::buffer::TextBuffer;
+use crate::canvas_editor::editing::cursor_set::Cursor;
+use crate::canvas_editor::features::bracket_match;
+use crate::canvas_editor::lsp::LspRange;
+use crate::canvas_editor::{CodeEditor, Message};
+
+/// How long expansions wait for a `textDocument/selectionRange` reply before
+/// falling back to the lexical ranges.
+///
+/// Servers that do not implement the request may never answer; this keeps
+/// the shortcut from silently doing nothing against them.
+const SELECTION_RANGE_TIMEOUT: Duration = Duration::from_millis(500);
+
+/// A range of the buffer as `(start, end)` positions, `end` exclusive.
+type Span = ((usize, usize), (usize, usize));
+
+/// Expand/shrink-selection state owned by the editor.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct SelectionRangeState {
+    /// The primary selection the session last produced. The session only
+    /// continues while the primary selection still equals it.
+    current: Option<Span>,
+    /// Selections replaced by each expansion, most recent last, so shrinking
+    /// can restore them in reverse.
+    history: Vec<Cursor>,
+    /// The server's enclosing ranges for this session, innermost first;
+    /// `None` until a reply arrives.
+    server_ranges: Option<Vec<Span>>,
+    /// When the server was asked, and how many expansions are queued behind
+    /// its reply.
+    awaiting: Option<(Instant, usize)>,
+}
+
+impl SelectionRangeState {
+    /// Ends the current session.
+    pub(crate) fn clear(&mut self) {
+        *self = Self::default();
+    }
+}
+
+/// Returns `true` when `outer` contains `inner` and is larger than it.
+fn strictly_contains(outer: Span, inner: Span) -> bool {
+    outer != inner && outer.0 <= inner.0 && inner.1 <= outer.1
+}
+
+/// Returns the number of characters in `span`, counting line breaks as one.
+fn span_len(buffer: &TextBuffer, span: Span) -> usize {
+    let ((start_line, start_col), (end_line, end_col)) = span;
+    if start_line == end_line {
+        return end_col.saturating_sub(start_col);
+    }
+    let middle: usize =
+        (start_line + 1..end_line).map(|line| buffer.line_len(line) + 1).sum();
+    buffer.line_len(start_line).saturating_sub(start_col) + 1 + middle + end_col
+}
+
+/// Returns the lexical ranges that may enclose `span`, smallest kinds first:
+/// the word, the bracket contents, the brackets, the trimmed lines, the full
+/// lines and the document.
+fn lexical_candidates(buffer: &TextBuffer, span: Span) -> Vec<Span> {
+    let ((start_line, start_col), end) = span;
+    let mut candidates = Vec::with_capacity(6);
+
+    if start_line == end.0 {
+        let text = buffer.line(start_line);
+        let word_start = CodeEditor::word_start_in_line(text, start_col);
+        let word_end = CodeEditor::word_end_in_line(text, start_col);
+        candidates.push(((start_line, word_start), (start_line, word_end)));
+    }
+
+    if let Some((open, close)) =
+        bracket_match::enclosing_pair(buffer, span.0, end)
+    {
+        candidates.push(((open.0, open.1 + 1), close));
+        candidates.push((open, (close.0, close.1 + 1)));
+    }
+
+    let indent = buffer
+        .line(start_line)
+        .chars()
+        .take_while(|ch| ch.is_whitespace())
+        .count();
+    let line_end = (end.0, buffer.line_len(end.0));
+    candidates.push(((start_line, indent), line_end));
+    candidates.push(((start_line, 0), line_end));
+
+    let last_line = buffer.line_count().saturating_sub(1);
+    candidates.push(((0, 0), (last_line, buffer.line_len(last_line))));
+    candidates
+}
+
+/// Returns the smallest of `candidates` that strictly encloses `span`.
+fn smallest_enclosing(
+    buffer: &TextBuffer,
+    candidates: impl IntoIterator<Item = Span>,
+    span: Span,
+) -> Option<Span> {
+    candidates
+        .into_iter()
+        .filter(|candidate| strictly_contains(*candidate, span))
+        .min_by_key(|candidate| span_len(buffer, *candidate))
+}
+
+impl CodeEditor {
+    /// Returns the primary cursor's selection, or an empty span at the
+    /// caret when nothing is selected.
+    fn primary_span(&self) -> Span {
+        let cursor = self.cursors.primary();
+        cursor.selection_range().unwrap_or((cursor.position, cursor.position))
+    }
+
+    /// Grows the primary selection to the next enclosing range.
+    ///
+    /// With a language server attached, the first expansion at a position
+    /// asks it for the enclosing ranges and waits for
+    /// [`Self::set_lsp_selection_ranges`]; otherwise the lexical fallback
+    /// applies straight away.
+    ///
+    /// # Returns
+    ///
+    /// A scroll task that keeps the cursor visible
+    pub(crate) fn handle_expand_selection_msg(&mut self) -> Task<Message> {
+        let span = self.primary_span();
+        if self.selection_range_state.current != Some(span) {
+            self.selection_range_state.clear();
+            self.selection_range_state.current = Some(span);
+        }
+
+        if self.selection_range_state.server_ranges.is_none() {
+            match self.selection_range_state.awaiting {
+                Some((since, queued))
+                    if since.elapsed() < SELECTION_RANGE_TIMEOUT =>
+                {
+                    self.selection_range_state.awaiting =
+                        Some((since, queued + 1));
+                    return Task::none();
+                }
+                Some(_) => {
+                    // The server never answered; stop waiting for it.
+                    self.selection_range_state.awaiting = None;
+                    self.selection_range_state.server_ranges = Some(Vec::new());
+                }
+                None => {
+                    if self.lsp_request_selection_range() {
+                        self.selection_range_state.awaiting =
+                            Some((Instant::now(), 1));
+                        return Task::none();
+                    }
+                }
+            }
+        }
+
+        self.expand_selection_once();
+        self.scroll_to_cursor()
+    }
+
+    /// Restores the selection the most recent expansion replaced.
+    ///
+    /// # Returns
+    ///
+    /// A scroll task that keeps the cursor visible
+    pub(crate) fn handle_shrink_selection_msg(&mut self) -> Task<Message> {
+        if self.selection_range_state.current != Some(self.primary_span()) {
+            self.selection_range_state.clear();
+            return Task::none();
+        }
+        let Some(previous) = self.selection_range_state.history.pop() else {
+            return Task::none();
+        };
+
+        self.cursors.set_single(previous.position);
+        *self.cursors.primary_mut() = previous;
+        self.selection_range_state.current = Some(self.primary_span());
+        self.overlay_cache.clear();
+        self.reset_cursor_blink();
+        self.scroll_to_cursor()
+    }
+
+    /// Applies one expansion step, preferring the server's ranges over the
+    /// lexical ones. Extra cursors are dropped: only the primary selection
+    /// grows.
+    fn expand_selection_once(&mut self) {
+        let span = self.primary_span();
+        let state = &self.selection_range_state;
+        let server = state.server_ranges.iter().flatten().copied();
+        let next =
+            smallest_enclosing(&self.buffer, server, span).or_else(|| {
+                smallest_enclosing(
+                    &self.buffer,
+                    lexical_candidates(&self.buffer, span),
+                    span,
+                )
+            });
+        let Some((start, end)) = next else { return };
+
+        let replaced = self.cursors.primary().clone();
+        self.selection_range_state.history.push(replaced);
+        self.cursors.set_single(end);
+        self.cursors.primary_mut().anchor = Some(start);
+        self.selection_range_state.current = Some((start, end));
+        self.overlay_cache.clear();
+        self.reset_cursor_blink();
+    }
+
+    /// Supplies a language server's selection ranges to a pending Expand
+    /// Selection.
+    ///
+    /// Feed this the payload of a `textDocument/selectionRange` response for
+    /// the position sent by [`Self::lsp_request_selection_range`] (with the
+    /// bundled client, [`LspEvent::SelectionRange`]), flattened innermost
+    /// first. Ranges are read in UTF-16 code units, as servers send them. An
+    /// empty reply makes the editor fall back to its lexical ranges. Replies
+    /// that arrive when no expansion is waiting, or after the cursor has
+    /// moved, are ignored.
+    ///
+    /// # Arguments
+    ///
+    /// * `ranges` - The enclosing ranges reported by the server
+    ///
+    /// # Returns
+    ///
+    /// A scroll task that keeps the cursor visible
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use iced_code_editor::{CodeEditor, LspPosition, LspRange};
+    ///
+    /// let mut editor = CodeEditor::new("let x = f(a);", "rs");
+    /// let at = |character| LspPosition { line: 0, character };
+    ///
+    /// // No expansion is waiting for an answer, so this reply is dropped.
+    /// let _task = editor.set_lsp_selection_ranges(&[LspRange {
+    ///     start: at(0),
+    ///     end: at(13),
+    /// }]);
+    /// assert_eq!(editor.cursor_position(), (0, 0));
+    /// ```
+    ///
+    /// [`LspEvent::SelectionRange`]: crate::LspEvent::SelectionRange
+    pub fn set_lsp_selection_ranges(
+        &mut self,
+        ranges: &[LspRange],
+    ) -> Task<Message> {
+        let Some((_, queued)) = self.selection_range_state.awaiting.take()
+        else {
+            return Task::none();
+        };
+        if self.selection_range_state.current != Some(self.primary_span()) {
+            return Task::none();
+        }
+
+        let spans = ranges
+            .iter()
+            .map(|range| {
+                (
+                    self.position_from_lsp(range.start),
+                    self.position_from_lsp(range.end),
+                )
+            })
+            .collect();
+        self.selection_range_state.server_ranges = Some(spans);
+        for _ in 0..queued {
+            self.expand_selection_once();
+        }
+        self.scroll_to_cursor()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::canvas_editor::lsp::LspPosition;
+
+    fn selection(editor: &CodeEditor) -> Option<Span> {
+        editor.cursors.primary().selection_range()
+    }
+
+    fn expand(editor: &mut CodeEditor) -> Option<Span> {
+        let _ = editor.handle_expand_selection_msg();
+        selection(editor)
+    }
+
+    #[test]
+    fn test_expand_walks_word_brackets_line_document() {
+        let mut editor = CodeEditor::new("fn f() {\n    g(alpha, b);\n}", "rs");
+        editor.cursors.set_single((1, 7));
+
+        assert_eq!(expand(&mut editor), Some(((1, 6), (1, 11))));
+        assert_eq!(expand(&mut editor), Some(((1, 6), (1, 14))));
+        assert_eq!(expand(&mut editor), Some(((1, 5), (1, 15))));
+        assert_eq!(expand(&mut editor), Some(((1, 4), (1, 16))));
+        assert_eq!(expand(&mut editor), Some(((1, 0), (1, 16))));
+        // The braces' contents start right after `{` on the first line.
+        assert_eq!(expand(&mut editor), Some(((0, 8), (2, 0))));
+        assert_eq!(expand(&mut editor), Some(((0, 7), (2, 1))));
+        assert_eq!(expand(&mut editor), Some(((0, 0), (2, 1))));
+        // Nothing encloses the whole document.
+        assert_eq!(expand(&mut editor), Some(((0, 0), (2, 1))));
+    }
+
+    #[test]
+    fn test_shrink_retraces_expansions() {
+        let mut editor = CodeEditor::new("x = (abc)", "rs");
+        editor.cursors.set_single((0, 6));
+
+        expand(&mut editor);
+        expand(&mut editor);
+        assert_eq!(selection(&editor), Some(((0, 4), (0, 9))));
+
+        let _ = editor.handle_shrink_selection_msg();
+        assert_eq!(selection(&editor), Some(((0, 5), (0, 8))));
+        let _ = editor.handle_shrink_selection_msg();
+        assert_eq!(selection(&editor), None);
+        assert_eq!(editor.cursors.primary_position(), (0, 6));
+
+        // Shrinking past the start of the session does nothing.
+        let _ = editor.handle_shrink_selection_msg();
+        assert_eq!(editor.cursors.primary_position(), (0, 6));
+    }
+
+    #[test]
+    fn test_moving_the_cursor_starts_a_new_session() {
+        let mut editor = CodeEditor::new("aa bb", "rs");
+        editor.cursors.set_single((0, 1));
+        expand(&mut editor);
+
+        editor.cursors.set_single((0, 4));
+        let _ = editor.handle_shrink_selection_msg();
+        assert_eq!(selection(&editor), None);
+        assert_eq!(expand(&mut editor), Some(((0, 3), (0, 5))));
+    }
+
+    #[test]
+    fn test_server_ranges_answer_queued_expansions() {
+        let mut editor = CodeEditor::new("let value = call(1);", "rs");
+        editor.cursors.set_single((0, 6));
+        editor.selection_range_state.current = Some(((0, 6), (0, 6)));
+        editor.selection_range_state.awaiting = Some((Instant::now(), 2));
+
+        let at = |character| LspPosition { line: 0, character };
+        let _ = editor.set_lsp_selection_ranges(&[
+            LspRange { start: at(4), end: at(9) },
+            LspRange { start: at(0), end: at(20) },
+        ]);
+        assert_eq!(selection(&editor), Some(((0, 0), (0, 20))));
+
+        // Shrinking retraces the server's steps.
+        let _ = editor.handle_shrink_selection_msg();
+        assert_eq!(selection(&editor), Some(((0, 4), (0, 9))));
+    }
+
+    #[test]
+    fn test_empty_server_reply_falls_back_to_lexical_ranges() {
+        let mut editor = CodeEditor::new("foo bar", "rs");
+        editor.cursors.set_single((0, 5));
+        editor.selection_range_state.current = Some(((0, 5), (0, 5)));
+        editor.selection_range_state.awaiting = Some((Instant::now(), 1));
+
+        let _ = editor.set_lsp_selection_ranges(&[]);
+        assert_eq!(selection(&editor), Some(((0, 4), (0, 7))));
+    }
+
+    #[test]
+    fn test_late_server_reply_after_cursor_moved_is_ignored() {
+        let mut editor = CodeEditor::new("foo bar", "rs");
+        editor.selection_range_state.current = Some(((0, 5), (0, 5)));
+        editor.selection_range_state.awaiting = Some((Instant::now(), 1));
+        editor.cursors.set_single((0, 1));
+
+        let at = |character| LspPosition { line: 0, character };
+        let _ = editor
+            .set_lsp_selection_ranges(&[LspRange { start: at(0), end: at(7) }]);
+        assert_eq!(selection(&editor), None);
+    }
+}
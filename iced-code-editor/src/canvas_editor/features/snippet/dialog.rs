@@ -0,0 +1,103 @@
+//! The list of options shown under the caret on a choice tabstop.
+
+use iced::widget::{button, column, container, text};
+use iced::{
+    Background, Border, Color, Element, Font, Length, Shadow, Theme, Vector,
+};
+
+use crate::canvas_editor::Message;
+
+/// Horizontal padding inside an option row, in pixels.
+const ROW_PADDING: f32 = 8.0;
+
+/// Padding between the list frame and its rows, in pixels.
+const FRAME_PADDING: f32 = 3.0;
+
+/// Border radius of the list frame.
+const BORDER_RADIUS: f32 = 4.0;
+
+/// Builds the option list.
+///
+/// # Arguments
+///
+/// * `choices` - The options of the current tabstop
+/// * `selected` - Index of the option shown as selected
+/// * `font` - The editor's font, so options read like the code around them
+/// * `font_size` - The editor's font size
+/// * `char_width` - Width of one character in that font, which sizes every
+///   row to the longest option
+pub(crate) fn view<'a>(
+    choices: &[String],
+    selected: usize,
+    font: Font,
+    font_size: f32,
+    char_width: f32,
+) -> Element<'a, Message> {
+    let longest =
+        choices.iter().map(|choice| choice.chars().count()).max().unwrap_or(0);
+    let width = longest as f32 * char_width + 2.0 * ROW_PADDING;
+    let rows = choices.iter().enumerate().map(|(index, choice)| {
+        button(text(choice.clone()).font(font).size(font_size))
+            .width(Length::Fixed(width))
+            .padding([2.0, ROW_PADDING])
+            .on_press(Message::SelectSnippetChoice(index))
+            .style(move |theme: &Theme, status| {
+                row_style(theme, status, index == selected)
+            })
+            .into()
+    });
+
+    container(column(rows).spacing(1))
+        .padding(FRAME_PADDING)
+        .style(|theme: &Theme| {
+            let palette = theme.extended_palette();
+            container::Style {
+                background: Some(Background::Color(
+                    palette.background.weak.color,
+                )),
+                text_color: Some(palette.background.weak.text),
+                border: Border {
+                    color: palette.background.strong.color,
+                    width: 1.0,
+                    radius: BORDER_RADIUS.into(),
+                },
+                shadow: Shadow {
+                    color: Color::BLACK.scale_alpha(0.3),
+                    offset: Vector::new(0.0, 2.0),
+                    blur_radius: 8.0,
+                },
+                ..container::Style::default()
+            }
+        })
+        .into()
+}
+
+/// Styles one option row.
+fn row_style(
+    theme: &Theme,
+    status: button::Status,
+    is_selected: bool,
+) -> button::Style {
+    let palette = theme.extended_palette();
+    let hovered =
+        matches!(status, button::Status::Hovered | button::Status::Pressed);
+    let background = if is_selected {
+        Some(Background::Color(palette.primary.weak.color))
+    } else if hovered {
+        Some(Background::Color(palette.background.strong.color))
+    } else {
+        None
+    };
+    let text_color = if is_selected {
+        palette.primary.weak.text
+    } else {
+        palette.background.weak.text
+    };
+
+    button::Style {
+        background,
+        text_color,
+        border: Border { radius: BORDER_RADIUS.into(), ..Border::default() },
+        ..button::Style::default()
+    }
+}
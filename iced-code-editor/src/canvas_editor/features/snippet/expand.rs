@@ -0,0 +1,358 @@
+//! Expansion of a parsed snippet into the text to insert.
+//!
+//! Rendering resolves variables, fills mirrors (a tabstop repeated without a
+//! placeholder takes the text of the occurrence that has one) and re-indents
+//! the body for the line it is inserted on. It also records where every
+//! tabstop ended up, as character offsets into the rendered text, so the
+//! session can select them.
+
+use std::collections::{BTreeMap, HashMap};
+
+use super::parser::SnippetNode;
+
+/// A snippet rendered to plain text.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct Expansion {
+    /// The text to insert.
+    pub(crate) text: String,
+    /// The tabstops in visiting order, with `$0` last.
+    pub(crate) tabstops: Vec<ExpandedTabstop>,
+}
+
+/// Where one tabstop ended up in an [`Expansion`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct ExpandedTabstop {
+    /// The tabstop number.
+    pub(crate) index: usize,
+    /// Every occurrence, as `[start, end)` character offsets into the text.
+    pub(crate) spans: Vec<(usize, usize)>,
+    /// The options of a choice, empty otherwise.
+    pub(crate) choices: Vec<String>,
+}
+
+/// How an expansion is adapted to the insertion point.
+pub(crate) struct ExpandOptions<'a> {
+    /// Leading whitespace of the insertion line, repeated after every line
+    /// break of the body.
+    pub(crate) line_indent: &'a str,
+    /// What a `\t` of the body becomes: one level of the editor's
+    /// indentation.
+    pub(crate) indent_unit: &'a str,
+    /// Looks up a variable's value: `None` when it is unknown or has no
+    /// value here, an empty string when it is known to be empty (no
+    /// selection for `TM_SELECTED_TEXT`, say).
+    pub(crate) resolve: &'a dyn Fn(&str) -> Option<String>,
+}
+
+/// Renders `nodes` for insertion.
+///
+/// An empty variable falls back to its default; one without a value and
+/// without a default becomes a placeholder holding its name, as in VS Code.
+/// A snippet without `$0` gets one at its end.
+pub(crate) fn expand(
+    nodes: &[SnippetNode],
+    options: &ExpandOptions<'_>,
+) -> Expansion {
+    let mut definitions = HashMap::new();
+    collect_definitions(nodes, &mut definitions);
+    let next_index =
+        definitions.keys().copied().chain(max_index(nodes)).max().unwrap_or(0)
+            + 1;
+
+    let mut renderer = Renderer {
+        options,
+        definitions,
+        text: String::new(),
+        len: 0,
+        spans: BTreeMap::new(),
+        choices: HashMap::new(),
+        variable_stops: HashMap::new(),
+        next_index,
+        mirroring: Vec::new(),
+    };
+    renderer.render(nodes, true);
+
+    let final_stop = renderer.spans.remove(&0);
+    let mut tabstops: Vec<ExpandedTabstop> =
+        std::mem::take(&mut renderer.spans)
+            .into_iter()
+            .map(|(index, spans)| ExpandedTabstop {
+                index,
+                spans,
+                choices: renderer.choices.remove(&index).unwrap_or_default(),
+            })
+            .collect();
+    tabstops.push(ExpandedTabstop {
+        index: 0,
+        spans: final_stop.unwrap_or_else(|| vec![(renderer.len, renderer.len)]),
+        choices: renderer.choices.remove(&0).unwrap_or_default(),
+    });
+
+    Expansion { text: renderer.text, tabstops }
+}
+
+/// Records, for every tabstop number, the first occurrence carrying a
+/// placeholder or choices: the one its mirrors copy.
+fn collect_definitions<'a>(
+    nodes: &'a [SnippetNode],
+    definitions: &mut HashMap<usize, &'a SnippetNode>,
+) {
+    for node in nodes {
+        match node {
+            SnippetNode::Text(_) => {}
+            SnippetNode::Tabstop { index, placeholder, choices } => {
+                if !placeholder.is_empty() || !choices.is_empty() {
+                    definitions.entry(*index).or_insert(node);
+                }
+                collect_definitions(placeholder, definitions);
+            }
+            SnippetNode::Variable { default, .. } => {
+                collect_definitions(default, definitions);
+            }
+        }
+    }
+}
+
+/// Returns the highest tabstop number in `nodes`.
+fn max_index(nodes: &[SnippetNode]) -> Option<usize> {
+    nodes
+        .iter()
+        .filter_map(|node| match node {
+            SnippetNode::Text(_) => None,
+            SnippetNode::Tabstop { index, placeholder, .. } => {
+                Some(max_index(placeholder).map_or(*index, |n| n.max(*index)))
+            }
+            SnippetNode::Variable { default, .. } => max_index(default),
+        })
+        .max()
+}
+
+/// Accumulates the rendered text and tabstop spans.
+struct Renderer<'a, 'n> {
+    options: &'a ExpandOptions<'a>,
+    /// See [`collect_definitions`].
+    definitions: HashMap<usize, &'n SnippetNode>,
+    text: String,
+    /// Length of `text` in characters.
+    len: usize,
+    spans: BTreeMap<usize, Vec<(usize, usize)>>,
+    choices: HashMap<usize, Vec<String>>,
+    /// Tabstop numbers given to unresolved variables, by name.
+    variable_stops: HashMap<String, usize>,
+    /// Number for the next unresolved variable.
+    next_index: usize,
+    /// Tabstops whose placeholder is being rendered, to cut
+    /// self-referencing placeholders such as `${1:a$1}` short.
+    mirroring: Vec<usize>,
+}
+
+impl<'n> Renderer<'_, 'n> {
+    /// Renders `nodes`, recording tabstop spans when `record` is set; the
+    /// content of a mirror is plain text.
+    fn render(&mut self, nodes: &'n [SnippetNode], record: bool) {
+        for node in nodes {
+            match node {
+                SnippetNode::Text(text) => self.push(text, true),
+                SnippetNode::Tabstop { index, placeholder, choices } => {
+                    let start = self.len;
+                    if let Some(first) = choices.first() {
+                        self.push(first, false);
+                        if record {
+                            self.choices
+                                .entry(*index)
+                                .or_insert_with(|| choices.clone());
+                        }
+                    } else if !placeholder.is_empty() {
+                        self.mirroring.push(*index);
+                        self.render(placeholder, record);
+                        self.mirroring.pop();
+                    } else {
+                        self.render_mirror(*index);
+                    }
+                    if record {
+                        self.record(*index, start);
+                    }
+                }
+                SnippetNode::Variable { name, default } => {
+                    let value = (self.options.resolve)(name);
+                    if let Some(value) =
+                        value.as_ref().filter(|v| !v.is_empty())
+                    {
+                        self.push(value, false);
+                    } else if !default.is_empty() {
+                        self.render(default, record);
+                    } else if value.is_none() {
+                        let start = self.len;
+                        self.push(name, false);
+                        if record {
+                            let index = self.variable_stop(name);
+                            self.record(index, start);
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    /// Renders the definition of tabstop `index` as plain text.
+    fn render_mirror(&mut self, index: usize) {
+        if self.mirroring.contains(&index) {
+            return;
+        }
+        let Some(SnippetNode::Tabstop { placeholder, choices, .. }) =
+            self.definitions.get(&index).copied()
+        else {
+            return;
+        };
+        self.mirroring.push(index);
+        if let Some(first) = choices.first() {
+            self.push(first, false);
+        } else {
+            self.render(placeholder, false);
+        }
+        self.mirroring.pop();
+    }
+
+    /// Returns the tabstop number of the unresolved variable `name`.
+    fn variable_stop(&mut self, name: &str) -> usize {
+        if let Some(index) = self.variable_stops.get(name) {
+            return *index;
+        }
+        let index = self.next_index;
+        self.next_index += 1;
+        self.variable_stops.insert(name.to_string(), index);
+        index
+    }
+
+    /// Records an occurrence of tabstop `index` from `start` to the current
+    /// end of the text.
+    fn record(&mut self, index: usize, start: usize) {
+        self.spans.entry(index).or_default().push((start, self.len));
+    }
+
+    /// Appends `text`, indenting every new line like the insertion line
+    /// and, for snippet text (`expand_tabs`), turning tabs into the editor's
+    /// indentation.
+    fn push(&mut self, text: &str, expand_tabs: bool) {
+        for ch in text.chars() {
+            match ch {
+                '\n' => {
+                    self.push_str("\n");
+                    self.push_str(self.options.line_indent);
+                }
+                '\t' if expand_tabs => {
+                    self.push_str(self.options.indent_unit);
+                }
+                '\r' => {}
+                _ => {
+                    self.text.push(ch);
+                    self.len += 1;
+                }
+            }
+        }
+    }
+
+    fn push_str(&mut self, text: &str) {
+        self.text.push_str(text);
+        self.len += text.chars().count();
+    }
+}
+
+/// Converts a character offset into `text` to a `(line, col)` position
+/// relative to where the text starts.
+///
+/// Offsets past the end map to the end of the text.
+pub(crate) fn offset_to_position(text: &str, offset: usize) -> (usize, usize) {
+    let mut line = 0;
+    let mut col = 0;
+    for ch in text.chars().take(offset) {
+        if ch == '\n' {
+            line += 1;
+            col = 0;
+        } else {
+            col += 1;
+        }
+    }
+    (line, col)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::parser::parse;
+    use super::*;
+
+    fn expand_plain(body: &str) -> Expansion {
+        let resolve = |name: &str| (name == "NAME").then(|| "x".to_string());
+        expand(
+            &parse(body),
+            &ExpandOptions {
+                line_indent: "  ",
+                indent_unit: "    ",
+                resolve: &resolve,
+            },
+        )
+    }
+
+    fn stop(
+        index: usize,
+        spans: &[(usize, usize)],
+        choices: &[&str],
+    ) -> ExpandedTabstop {
+        ExpandedTabstop {
+            index,
+            spans: spans.to_vec(),
+            choices: choices.iter().map(ToString::to_string).collect(),
+        }
+    }
+
+    #[test]
+    fn test_expand_orders_tabstops_with_final_last() {
+        let expansion = expand_plain("$0${2:b}${1:a}");
+        assert_eq!(expansion.text, "ba");
+        assert_eq!(
+            expansion.tabstops,
+            [
+                stop(1, &[(1, 2)], &[]),
+                stop(2, &[(0, 1)], &[]),
+                stop(0, &[(0, 0)], &[])
+            ]
+        );
+    }
+
+    #[test]
+    fn test_expand_mirrors_and_appends_implicit_final_stop() {
+        let expansion = expand_plain("${1:id} = $1;");
+        assert_eq!(expansion.text, "id = id;");
+        assert_eq!(
+            expansion.tabstops,
+            [stop(1, &[(0, 2), (5, 7)], &[]), stop(0, &[(8, 8)], &[])]
+        );
+    }
+
+    #[test]
+    fn test_expand_choices_variables_and_indentation() {
+        let expansion = expand_plain("${1|on,off|} $NAME\n\t$OTHER");
+        assert_eq!(expansion.text, "on x\n      OTHER");
+        assert_eq!(
+            expansion.tabstops,
+            [
+                stop(1, &[(0, 2)], &["on", "off"]),
+                stop(2, &[(11, 16)], &[]),
+                stop(0, &[(16, 16)], &[]),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_expand_cuts_self_referencing_placeholders() {
+        let expansion = expand_plain("${1:a$1}");
+        assert_eq!(expansion.text, "a");
+    }
+
+    #[test]
+    fn test_offset_to_position() {
+        assert_eq!(offset_to_position("ab\ncd", 1), (0, 1));
+        assert_eq!(offset_to_position("ab\ncd", 4), (1, 1));
+        assert_eq!(offset_to_position("ab", 9), (0, 2));
+    }
+}
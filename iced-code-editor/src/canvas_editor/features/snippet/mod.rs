@@ -0,0 +1,945 @@
+//! Snippets: templates whose tabstops the caret walks through.
+//!
+//! Bodies use the LSP/TextMate snippet syntax (see [`parser`]). A snippet
+//! comes from one of three places:
+//!
+//! * **Libraries** the host registers per language with
+//!   [`CodeEditor::register_snippets`]; typing a prefix and pressing `Tab`
+//!   expands it.
+//! * **Language servers**, as completion items with `insertTextFormat = 2`,
+//!   inserted through [`Message::InsertSnippet`].
+//! * **The host**, calling [`CodeEditor::insert_snippet`] directly.
+//!
+//! Inserting a snippet starts a session: every occurrence of the first
+//! tabstop is selected, one cursor each, so typing fills repeated tabstops
+//! together. `Tab` and `Shift+Tab` move between tabstops while the caret is on
+//! the current one; reaching `$0`, pressing Escape, or editing outside the
+//! current tabstop ends the session. A choice tabstop shows its options in a
+//! small list under the caret, cycled with the arrow keys.
+
+pub(crate) mod dialog;
+mod expand;
+mod parser;
+
+use std::collections::HashMap;
+use std::path::Path;
+
+use iced::{Point, Task};
+
+use crate::buffer::TextBuffer;
+use crate::canvas_editor::editing::cursor_set::Cursor;
+use crate::canvas_editor::{CodeEditor, IndentStyle, Message};
+use expand::{ExpandOptions, Expansion, offset_to_position};
+
+/// Seconds in a day, for the `CURRENT_*` date variables.
+const SECONDS_PER_DAY: u64 = 86_400;
+
+/// English month names, for `CURRENT_MONTH_NAME`.
+const MONTH_NAMES: [&str; 12] = [
+    "January",
+    "February",
+    "March",
+    "April",
+    "May",
+    "June",
+    "July",
+    "August",
+    "September",
+    "October",
+    "November",
+    "December",
+];
+
+/// English day names from Monday, for `CURRENT_DAY_NAME`.
+const DAY_NAMES: [&str; 7] = [
+    "Monday",
+    "Tuesday",
+    "Wednesday",
+    "Thursday",
+    "Friday",
+    "Saturday",
+    "Sunday",
+];
+
+/// A snippet in a library registered with
+/// [`CodeEditor::register_snippets`].
+///
+/// # Examples
+///
+/// ```
+/// use iced_code_editor::Snippet;
+///
+/// let snippet = Snippet::new("fn", "fn ${1:name}(${2}) {\n\t$0\n}")
+///     .with_description("Function definition");
+/// assert_eq!(snippet.prefix, "fn");
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Snippet {
+    /// Text that expands the snippet when followed by `Tab`.
+    pub prefix: String,
+    /// The body, in LSP/TextMate snippet syntax.
+    pub body: String,
+    /// Human-readable description, for hosts that list snippets.
+    pub description: String,
+}
+
+impl Snippet {
+    /// Creates a snippet without a description.
+    ///
+    /// # Arguments
+    ///
+    /// * `prefix` - Text that expands the snippet when followed by `Tab`
+    /// * `body` - The body, in LSP/TextMate snippet syntax
+    pub fn new(prefix: impl Into<String>, body: impl Into<String>) -> Self {
+        Self {
+            prefix: prefix.into(),
+            body: body.into(),
+            description: String::new(),
+        }
+    }
+
+    /// Sets the snippet's description.
+    #[must_use]
+    pub fn with_description(mut self, description: impl Into<String>) -> Self {
+        self.description = description.into();
+        self
+    }
+}
+
+/// A region of the buffer a tabstop occupies.
+///
+/// Columns are counted in characters; `end` is exclusive.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct SnippetRange {
+    /// First position of the range.
+    pub(crate) start: (usize, usize),
+    /// Position one past the range's last character.
+    pub(crate) end: (usize, usize),
+}
+
+impl SnippetRange {
+    fn is_empty(&self) -> bool {
+        self.start == self.end
+    }
+
+    /// Returns `true` when a caret at `position` lies inside this range or
+    /// on either of its edges.
+    fn touches(&self, position: (usize, usize)) -> bool {
+        self.start <= position && position <= self.end
+    }
+}
+
+/// A tabstop of the snippet being filled in.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct SessionTabstop {
+    /// The tabstop number; `0` is the final caret position.
+    index: usize,
+    /// Its occurrences, in document order. Empty once the text holding
+    /// them has been typed over.
+    pub(crate) ranges: Vec<SnippetRange>,
+    /// The options of a choice, empty otherwise.
+    pub(crate) choices: Vec<String>,
+}
+
+/// A snippet being filled in.
+#[derive(Debug, Clone)]
+pub(crate) struct SnippetSession {
+    /// The tabstops in visiting order, with `$0` last.
+    pub(crate) tabstops: Vec<SessionTabstop>,
+    /// Position of the current tabstop in `tabstops`.
+    pub(crate) active: usize,
+    /// Option of the current choice tabstop shown as selected.
+    pub(crate) choice: usize,
+    /// Buffer line count when the ranges were last brought up to date.
+    line_count: usize,
+    /// Length, in characters, of every line holding the current tabstop
+    /// when the ranges were last brought up to date.
+    line_lengths: Vec<(usize, usize)>,
+}
+
+impl SnippetSession {
+    /// Returns the current tabstop.
+    pub(crate) fn active_tabstop(&self) -> Option<&SessionTabstop> {
+        self.tabstops.get(self.active)
+    }
+
+    /// Records the current line count and the lengths of the current
+    /// tabstop's lines, against which the next edit is measured.
+    fn sync(&mut self, buffer: &TextBuffer) {
+        self.line_count = buffer.line_count();
+        self.line_lengths.clear();
+        if let Some(tabstop) = self.tabstops.get(self.active) {
+            for range in &tabstop.ranges {
+                let line = range.start.0;
+                if !self.line_lengths.iter().any(|(known, _)| *known == line) {
+                    self.line_lengths.push((line, buffer.line_len(line)));
+                }
+            }
+        }
+    }
+
+    /// Moves the ranges to follow an edit made through the current
+    /// tabstop's cursors.
+    ///
+    /// The edit is only followed when it stayed on the current tabstop's
+    /// lines, changed each occurrence on a line by the same amount, and left
+    /// every cursor on an occurrence. Ranges after an occurrence on its line
+    /// shift, ranges enclosing it grow, and ranges nested in it are dropped:
+    /// their text was typed over.
+    ///
+    /// # Returns
+    ///
+    /// `false` when the edit cannot be followed and the session should end
+    fn follow_edit(&mut self, buffer: &TextBuffer, cursors: &[Cursor]) -> bool {
+        let Some(tabstop) = self.tabstops.get(self.active) else {
+            return false;
+        };
+        let mut active = tabstop.ranges.clone();
+        active.sort_by_key(|range| range.start);
+        if buffer.line_count() != self.line_count
+            || cursors.len() != active.len()
+        {
+            return false;
+        }
+
+        // Every occurrence changed by the same amount, so each line's
+        // growth is split evenly between the occurrences it holds.
+        let mut deltas: HashMap<usize, isize> = HashMap::new();
+        for &(line, old_len) in &self.line_lengths {
+            let count = active.iter().filter(|r| r.start.0 == line).count();
+            let Ok(count) = isize::try_from(count) else {
+                return false;
+            };
+            let total = signed(buffer.line_len(line)) - signed(old_len);
+            if count == 0 || total % count != 0 {
+                return false;
+            }
+            deltas.insert(line, total / count);
+        }
+        let changed = |line: usize| deltas.get(&line).copied().unwrap_or(0);
+        if active
+            .iter()
+            .any(|r| r.start.0 != r.end.0 && changed(r.start.0) != 0)
+        {
+            return false;
+        }
+
+        // Shift of a position by the occurrences before it on its line.
+        // At an occurrence's end, what follows shifts; so does the end of a
+        // range enclosing it, but not the end of a range just before an
+        // empty occurrence.
+        let shift = |position: (usize, usize), is_start: bool, owner_start| {
+            let delta = changed(position.0);
+            let before = active
+                .iter()
+                .filter(|a| a.start.0 == position.0)
+                .filter(|a| {
+                    position.1 > a.end.1
+                        || (position.1 == a.end.1
+                            && (!a.is_empty()
+                                || is_start
+                                || owner_start >= a.start))
+                })
+                .count();
+            let Ok(before) = isize::try_from(before) else {
+                return position;
+            };
+            (position.0, offset(position.1, before * delta))
+        };
+
+        for (position, tabstop) in self.tabstops.iter_mut().enumerate() {
+            if position == self.active {
+                continue;
+            }
+            tabstop.ranges.retain(|range| {
+                !active.iter().any(|a| {
+                    !a.is_empty()
+                        && a.start <= range.start
+                        && range.end <= a.end
+                        && range != a
+                })
+            });
+            for range in &mut tabstop.ranges {
+                let start = range.start;
+                range.start = shift(range.start, true, start);
+                range.end = shift(range.end, false, start);
+            }
+        }
+
+        let mut moved = Vec::with_capacity(active.len());
+        let mut earlier_on_line = 0;
+        for (position, range) in active.iter().enumerate() {
+            if position > 0 && active[position - 1].start.0 != range.start.0 {
+                earlier_on_line = 0;
+            }
+            let delta = changed(range.start.0);
+            let start = offset(range.start.1, earlier_on_line * delta);
+            let len = signed(range.end.1) - signed(range.start.1) + delta;
+            if len < 0 {
+                return false;
+            }
+            moved.push(SnippetRange {
+                start: (range.start.0, start),
+                end: (range.end.0, offset(start, len)),
+            });
+            earlier_on_line += 1;
+        }
+        if !cursors
+            .iter()
+            .all(|cursor| moved.iter().any(|r| r.touches(cursor.position)))
+        {
+            return false;
+        }
+        self.tabstops[self.active].ranges = moved;
+        self.sync(buffer);
+        true
+    }
+}
+
+/// Converts a column or length to `isize`, saturating.
+fn signed(value: usize) -> isize {
+    isize::try_from(value).unwrap_or(isize::MAX)
+}
+
+/// Moves `column` by `delta`, stopping at zero.
+fn offset(column: usize, delta: isize) -> usize {
+    column.saturating_add_signed(delta)
+}
+
+/// Snippet state owned by the editor.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct SnippetState {
+    /// Registered snippets, by lowercased language.
+    libraries: HashMap<String, Vec<Snippet>>,
+    /// Variable values set by the host, overriding the built-in ones.
+    variables: HashMap<String, String>,
+    /// The snippet being filled in, if any.
+    pub(crate) session: Option<SnippetSession>,
+}
+
+impl CodeEditor {
+    /// Adds snippets to the library of a language.
+    ///
+    /// The library used is the one registered under the editor's syntax
+    /// (the `syntax` given to [`CodeEditor::new`], compared
+    /// case-insensitively). Typing a snippet's prefix and pressing `Tab`
+    /// expands it; when several prefixes match, the longest wins.
+    ///
+    /// # Arguments
+    ///
+    /// * `language` - The syntax the snippets apply to, e.g. `"rs"`
+    /// * `snippets` - The snippets to add
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use iced_code_editor::{CodeEditor, Message, Snippet};
+    ///
+    /// let mut editor = CodeEditor::new("", "rs");
+    /// editor.register_snippets(
+    ///     "rs",
+    ///     [Snippet::new("pl", "println!(\"${1:value}\");$0")],
+    /// );
+    /// assert_eq!(editor.snippets().len(), 1);
+    /// ```
+    pub fn register_snippets(
+        &mut self,
+        language: &str,
+        snippets: impl IntoIterator<Item = Snippet>,
+    ) {
+        self.snippet_state
+            .libraries
+            .entry(language.to_lowercase())
+            .or_default()
+            .extend(snippets);
+    }
+
+    /// Removes every snippet registered for a language.
+    ///
+    /// # Arguments
+    ///
+    /// * `language` - The syntax whose library is cleared
+    pub fn clear_snippets(&mut self, language: &str) {
+        self.snippet_state.libraries.remove(&language.to_lowercase());
+    }
+
+    /// Returns the snippets registered for the editor's syntax.
+    pub fn snippets(&self) -> &[Snippet] {
+        self.snippet_state
+            .libraries
+            .get(&self.syntax.to_lowercase())
+            .map_or(&[], Vec::as_slice)
+    }
+
+    /// Sets the value of a snippet variable.
+    ///
+    /// Host values override the built-in ones, and supply those the editor
+    /// cannot know: `TM_FILEPATH` and its relatives are otherwise derived
+    /// from the LSP document URI, when there is one.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - The variable name, without the `$`
+    /// * `value` - The text the variable expands to
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use iced_code_editor::CodeEditor;
+    ///
+    /// let mut editor = CodeEditor::new("", "rs");
+    /// editor.set_snippet_variable("TM_FILENAME", "main.rs");
+    /// let _ = editor.insert_snippet("// $TM_FILENAME");
+    /// assert_eq!(editor.content(), "// main.rs");
+    /// ```
+    pub fn set_snippet_variable(
+        &mut self,
+        name: impl Into<String>,
+        value: impl Into<String>,
+    ) {
+        self.snippet_state.variables.insert(name.into(), value.into());
+    }
+
+    /// Returns `true` while a snippet's tabstops are being filled in.
+    pub fn is_snippet_active(&self) -> bool {
+        self.snippet_state.session.is_some()
+    }
+
+    /// Inserts a snippet at the primary cursor, replacing its selection.
+    ///
+    /// Other cursors are dropped. The body is indented like the cursor's
+    /// line, and every `\t` in it becomes one level of the editor's
+    /// [`IndentStyle`]. When the snippet has tabstops other than `$0`, the
+    /// first one is selected and a session starts (see the module docs);
+    /// otherwise the caret lands on `$0`, or after the text.
+    ///
+    /// # Arguments
+    ///
+    /// * `body` - The snippet, in LSP/TextMate snippet syntax
+    ///
+    /// # Returns
+    ///
+    /// A `Task<Message>` that scrolls to keep the cursor visible
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use iced_code_editor::{CodeEditor, Message};
+    ///
+    /// let mut editor = CodeEditor::new("", "rs");
+    /// let _ = editor.insert_snippet("let ${1:x} = $1 + 1;");
+    /// assert_eq!(editor.content(), "let x = x + 1;");
+    ///
+    /// // Both occurrences of `$1` are selected: typing renames them together.
+    /// let _ = editor.update(&Message::Paste("y".to_string()));
+    /// assert_eq!(editor.content(), "let y = y + 1;");
+    /// ```
+    pub fn insert_snippet(&mut self, body: &str) -> Task<Message> {
+        self.cursors.remove_all_but_primary();
+        let cursor = self.cursors.primary();
+        let start = cursor
+            .selection_range()
+            .map_or(cursor.position, |(start, _)| start);
+        let expansion = self.expand_snippet(body, start);
+        self.apply_snippet(start, &expansion)
+    }
+
+    /// Expands the library snippet whose prefix ends at the caret; called
+    /// when `Tab` is pressed.
+    ///
+    /// # Returns
+    ///
+    /// `None`, leaving `Tab` to indent, when there are several cursors, a
+    /// selection, or no matching prefix
+    pub(crate) fn expand_snippet_prefix(&mut self) -> Option<Task<Message>> {
+        if self.cursors.is_multi() || self.cursors.primary().has_selection() {
+            return None;
+        }
+        let (line, col) = self.cursors.primary_position();
+        let before: String = self.buffer.line(line).chars().take(col).collect();
+        let snippet = self
+            .snippets()
+            .iter()
+            .filter(|snippet| !snippet.prefix.is_empty())
+            .filter(|snippet| {
+                // A word-like prefix must start a word, so `xfn` does not
+                // expand `fn`.
+                before.strip_suffix(snippet.prefix.as_str()).is_some_and(
+                    |rest| {
+                        !(rest.chars().last().is_some_and(Self::is_word_char)
+                            && snippet
+                                .prefix
+                                .chars()
+                                .next()
+                                .is_some_and(Self::is_word_char))
+                    },
+                )
+            })
+            .max_by_key(|snippet| snippet.prefix.chars().count())?
+            .clone();
+
+        let start = (line, col - snippet.prefix.chars().count());
+        let expansion = self.expand_snippet(&snippet.body, start);
+        self.cursors.primary_mut().anchor = Some(start);
+        Some(self.apply_snippet(start, &expansion))
+    }
+
+    /// Renders `body` for insertion at `start`.
+    ///
+    /// Variables see the selection and the line as they are before the
+    /// snippet replaces them.
+    fn expand_snippet(&self, body: &str, start: (usize, usize)) -> Expansion {
+        let line_indent: String = self
+            .buffer
+            .line(start.0)
+            .chars()
+            .take_while(|ch| *ch == ' ' || *ch == '\t')
+            .collect();
+        let indent_unit = match self.indent_style {
+            IndentStyle::Spaces(n) => " ".repeat(usize::from(n)),
+            IndentStyle::Tab => "\t".to_string(),
+        };
+        let resolve = |name: &str| self.snippet_variable(name);
+        expand::expand(
+            &parser::parse(body),
+            &ExpandOptions {
+                line_indent: &line_indent,
+                indent_unit: &indent_unit,
+                resolve: &resolve,
+            },
+        )
+    }
+
+    /// Replaces the primary cursor's selection with `expansion`, which must
+    /// start at `start`, as one undo step, then starts its session.
+    fn apply_snippet(
+        &mut self,
+        start: (usize, usize),
+        expansion: &Expansion,
+    ) -> Task<Message> {
+        self.snippet_state.session = None;
+        self.end_grouping_if_active();
+        self.history.begin_group();
+        self.paste_text(&expansion.text);
+        self.history.end_group();
+        self.finish_edit_operation();
+        self.start_snippet_session(start, expansion);
+        self.scroll_to_cursor()
+    }
+
+    /// Starts a session for a snippet just inserted at `start`, selecting
+    /// its first tabstop.
+    fn start_snippet_session(
+        &mut self,
+        start: (usize, usize),
+        expansion: &Expansion,
+    ) {
+        let to_position = |offset: usize| {
+            let (line, col) = offset_to_position(&expansion.text, offset);
+            if line == 0 {
+                (start.0, start.1 + col)
+            } else {
+                (start.0 + line, col)
+            }
+        };
+        let tabstops: Vec<SessionTabstop> = expansion
+            .tabstops
+            .iter()
+            .map(|tabstop| SessionTabstop {
+                index: tabstop.index,
+                ranges: tabstop
+                    .spans
+                    .iter()
+                    .map(|&(from, to)| SnippetRange {
+                        start: to_position(from),
+                        end: to_position(to),
+                    })
+                    .collect(),
+                choices: tabstop.choices.clone(),
+            })
+            .collect();
+
+        self.snippet_state.session = Some(SnippetSession {
+            tabstops,
+            active: 0,
+            choice: 0,
+            line_count: 0,
+            line_lengths: Vec::new(),
+        });
+        self.select_snippet_tabstop(0);
+    }
+
+    /// Selects every occurrence of the tabstop at `position`, one cursor
+    /// each with the first occurrence primary. Reaching `$0` ends the
+    /// session.
+    fn select_snippet_tabstop(&mut self, position: usize) {
+        let Some(session) = self.snippet_state.session.as_mut() else {
+            return;
+        };
+        let Some(tabstop) = session.tabstops.get(position) else {
+            self.snippet_state.session = None;
+            return;
+        };
+        let ranges = tabstop.ranges.clone();
+        let is_final = tabstop.index == 0;
+        session.active = position;
+        session.choice = 0;
+
+        // `add_cursor_with_selection` makes each new cursor primary, so the
+        // first occurrence goes last.
+        let ordered = ranges.iter().skip(1).chain(ranges.first());
+        for (n, range) in ordered.enumerate() {
+            let cursor = Cursor {
+                position: range.end,
+                anchor: (!range.is_empty()).then_some(range.start),
+            };
+            if n == 0 {
+                self.cursors.set_single(range.end);
+                *self.cursors.primary_mut() = cursor;
+            } else {
+                self.cursors.add_cursor_with_selection(cursor);
+            }
+        }
+
+        if is_final {
+            self.snippet_state.session = None;
+        } else if let Some(session) = self.snippet_state.session.as_mut() {
+            session.sync(&self.buffer);
+        }
+        self.reset_cursor_blink();
+        self.overlay_cache.clear();
+    }
+
+    /// Returns `true` when `Tab`/`Shift+Tab` should move between tabstops:
+    /// a session is active and the primary cursor is on its current tabstop.
+    pub(crate) fn on_snippet_tabstop(&self) -> bool {
+        let position = self.cursors.primary_position();
+        self.snippet_state
+            .session
+            .as_ref()
+            .and_then(SnippetSession::active_tabstop)
+            .is_some_and(|tabstop| {
+                tabstop.ranges.iter().any(|range| range.touches(position))
+            })
+    }
+
+    /// Returns the options of the current tabstop, when it is a choice the
+    /// primary cursor is on, and the index of the one shown as selected.
+    pub(crate) fn snippet_choices(&self) -> Option<(&[String], usize)> {
+        if !self.on_snippet_tabstop() {
+            return None;
+        }
+        let session = self.snippet_state.session.as_ref()?;
+        let tabstop = session.active_tabstop()?;
+        (!tabstop.choices.is_empty())
+            .then_some((tabstop.choices.as_slice(), session.choice))
+    }
+
+    /// Handles `Tab` (`forward`) and `Shift+Tab` in a session: selects the
+    /// next or previous tabstop that still has occurrences.
+    ///
+    /// # Returns
+    ///
+    /// A `Task<Message>` that scrolls to keep the cursor visible
+    pub(crate) fn handle_snippet_tabstop_msg(
+        &mut self,
+        forward: bool,
+    ) -> Task<Message> {
+        let Some(session) = self.snippet_state.session.as_ref() else {
+            return Task::none();
+        };
+        let mut target = session.active;
+        loop {
+            target = if forward {
+                target + 1
+            } else if let Some(previous) = target.checked_sub(1) {
+                previous
+            } else {
+                return Task::none();
+            };
+            match session.tabstops.get(target) {
+                Some(tabstop) if tabstop.ranges.is_empty() => {}
+                _ => break,
+            }
+        }
+        // What is typed into the next tabstop is its own undo step.
+        self.end_grouping_if_active();
+        self.select_snippet_tabstop(target);
+        self.scroll_to_cursor()
+    }
+
+    /// Replaces every occurrence of the current choice tabstop with its
+    /// option at `index`, keeping them selected.
+    ///
+    /// # Returns
+    ///
+    /// A `Task<Message>` that scrolls to keep the cursor visible
+    pub(crate) fn handle_select_snippet_choice_msg(
+        &mut self,
+        index: usize,
+    ) -> Task<Message> {
+        let Some((choices, _)) = self.snippet_choices() else {
+            return Task::none();
+        };
+        let Some(choice) = choices.get(index).cloned() else {
+            return Task::none();
+        };
+        let Some(active) =
+            self.snippet_state.session.as_ref().map(|session| session.active)
+        else {
+            return Task::none();
+        };
+
+        self.select_snippet_tabstop(active);
+        self.end_grouping_if_active();
+        self.history.begin_group();
+        self.paste_text(&choice);
+        self.history.end_group();
+        self.finish_edit_operation();
+        if self.snippet_state.session.is_some() {
+            self.select_snippet_tabstop(active);
+            if let Some(session) = self.snippet_state.session.as_mut() {
+                session.choice = index;
+            }
+        }
+        self.scroll_to_cursor()
+    }
+
+    /// Ends the snippet session, leaving the cursors where they are.
+    pub(crate) fn leave_snippet(&mut self) {
+        if self.snippet_state.session.take().is_some() {
+            self.overlay_cache.clear();
+        }
+    }
+
+    /// Moves the session's ranges to follow an edit, or ends the session
+    /// when the edit went elsewhere; called from `finish_edit_operation`.
+    pub(crate) fn track_snippet_edit(&mut self) {
+        if let Some(session) = self.snippet_state.session.as_mut()
+            && !session.follow_edit(&self.buffer, self.cursors.as_slice())
+        {
+            self.snippet_state.session = None;
+        }
+    }
+
+    /// Returns where the choice list goes: under the primary cursor, in
+    /// viewport coordinates.
+    pub(crate) fn snippet_choice_position(&self) -> Option<Point> {
+        let point = self.cursor_screen_position()?;
+        Some(Point::new(
+            (point.x - self.horizontal_scroll_offset).max(0.0),
+            point.y - self.viewport_scroll + self.line_height,
+        ))
+    }
+
+    /// Returns the value of a snippet variable, as
+    /// [`ExpandOptions::resolve`] expects it.
+    fn snippet_variable(&self, name: &str) -> Option<String> {
+        if let Some(value) = self.snippet_state.variables.get(name) {
+            return Some(value.clone());
+        }
+        let (line, col) = self.cursors.primary_position();
+        let file_path = || {
+            let uri = &self.lsp_document.as_ref()?.uri;
+            uri.strip_prefix("file://").map(Path::new)
+        };
+        match name {
+            "TM_SELECTED_TEXT" => {
+                Some(self.get_selected_text().unwrap_or_default())
+            }
+            "TM_CURRENT_LINE" => Some(self.buffer.line(line).to_string()),
+            "TM_CURRENT_WORD" => {
+                let text = self.buffer.line(line);
+                let start = Self::word_start_in_line(text, col);
+                let end = Self::word_end_in_line(text, col);
+                Some(text.chars().skip(start).take(end - start).collect())
+            }
+            "TM_LINE_INDEX" => Some(line.to_string()),
+            "TM_LINE_NUMBER" => Some((line + 1).to_string()),
+            "TM_FILEPATH" => Some(file_path()?.to_string_lossy().into_owned()),
+            "TM_FILENAME" => file_path()?
+                .file_name()
+                .map(|name| name.to_string_lossy().into_owned()),
+            "TM_FILENAME_BASE" => file_path()?
+                .file_stem()
+                .map(|stem| stem.to_string_lossy().into_owned()),
+            "TM_DIRECTORY" => file_path()?
+                .parent()
+                .map(|parent| parent.to_string_lossy().into_owned()),
+            "LINE_COMMENT" => self.comment_tokens().line,
+            _ => date_variable(name),
+        }
+    }
+}
+
+/// Returns the value of a `CURRENT_*` date variable, in UTC.
+fn date_variable(name: &str) -> Option<String> {
+    let seconds = web_time::SystemTime::now()
+        .duration_since(web_time::UNIX_EPOCH)
+        .ok()?
+        .as_secs();
+    let days = seconds / SECONDS_PER_DAY;
+    let time = seconds % SECONDS_PER_DAY;
+    let (year, month, day) = civil_from_days(days);
+    let month_name =
+        *MONTH_NAMES.get(usize::try_from(month).ok()?.checked_sub(1)?)?;
+    // 1970-01-01 was a Thursday.
+    let day_name = *DAY_NAMES.get(usize::try_from((days + 3) % 7).ok()?)?;
+
+    let value = match name {
+        "CURRENT_YEAR" => year.to_string(),
+        "CURRENT_YEAR_SHORT" => format!("{:02}", year % 100),
+        "CURRENT_MONTH" => format!("{month:02}"),
+        "CURRENT_MONTH_NAME" => month_name.to_string(),
+        "CURRENT_MONTH_NAME_SHORT" => month_name.chars().take(3).collect(),
+        "CURRENT_DATE" => format!("{day:02}"),
+        "CURRENT_DAY_NAME" => day_name.to_string(),
+        "CURRENT_DAY_NAME_SHORT" => day_name.chars().take(3).collect(),
+        "CURRENT_HOUR" => format!("{:02}", time / 3600),
+        "CURRENT_MINUTE" => format!("{:02}", time % 3600 / 60),
+        "CURRENT_SECOND" => format!("{:02}", time % 60),
+        "CURRENT_SECONDS_UNIX" => seconds.to_string(),
+        _ => return None,
+    };
+    Some(value)
+}
+
+/// Converts days since 1970-01-01 to a `(year, month, day)` civil date.
+///
+/// Howard Hinnant's `civil_from_days`, restricted to dates after the epoch.
+fn civil_from_days(days: u64) -> (u64, u64, u64) {
+    let z = days + 719_468;
+    let era = z / 146_097;
+    let day_of_era = z - era * 146_097;
+    let year_of_era = (day_of_era - day_of_era / 1460 + day_of_era / 36_524
+        - day_of_era / 146_096)
+        / 365;
+    let day_of_year =
+        day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let shifted_month = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * shifted_month + 2) / 5 + 1;
+    let month =
+        if shifted_month < 10 { shifted_month + 3 } else { shifted_month - 9 };
+    let year = year_of_era + era * 400 + u64::from(month <= 2);
+    (year, month, day)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn editor_with(content: &str) -> CodeEditor {
+        let mut editor = CodeEditor::new(content, "rs");
+        editor.request_focus();
+        editor.has_canvas_focus = true;
+        editor.focus_locked = false;
+        editor
+    }
+
+    fn type_text(editor: &mut CodeEditor, text: &str) {
+        for ch in text.chars() {
+            let _ = editor.update(&Message::CharacterInput(ch));
+        }
+    }
+
+    #[test]
+    fn test_tab_walks_tabstops_and_ends_on_final_stop() {
+        let mut editor = editor_with("");
+        let _ = editor.insert_snippet("fn ${1:name}(${2:args}) {$0}");
+        assert_eq!(editor.content(), "fn name(args) {}");
+        assert_eq!(editor.get_selected_text().as_deref(), Some("name"));
+
+        type_text(&mut editor, "run");
+        let _ = editor.update(&Message::NextSnippetTabstop);
+        assert_eq!(editor.get_selected_text().as_deref(), Some("args"));
+
+        let _ = editor.update(&Message::PreviousSnippetTabstop);
+        assert_eq!(editor.get_selected_text().as_deref(), Some("run"));
+
+        let _ = editor.update(&Message::NextSnippetTabstop);
+        let _ = editor.update(&Message::NextSnippetTabstop);
+        assert_eq!(editor.cursors.primary_position(), (0, 14));
+        assert!(!editor.is_snippet_active());
+    }
+
+    #[test]
+    fn test_typing_fills_mirrors_on_every_line() {
+        let mut editor = editor_with("    ");
+        editor.cursors.set_single((0, 4));
+        let _ = editor.insert_snippet("let ${1:x} = 1;\n$1 += ${2:2};");
+        assert_eq!(editor.content(), "    let x = 1;\n    x += 2;");
+        assert_eq!(editor.cursors.len(), 2);
+
+        type_text(&mut editor, "total");
+        assert_eq!(editor.content(), "    let total = 1;\n    total += 2;");
+        assert!(editor.is_snippet_active());
+
+        let _ = editor.update(&Message::NextSnippetTabstop);
+        assert_eq!(editor.cursors.len(), 1);
+        assert_eq!(editor.get_selected_text().as_deref(), Some("2"));
+    }
+
+    #[test]
+    fn test_choice_selection_replaces_every_occurrence() {
+        let mut editor = editor_with("");
+        let _ = editor.insert_snippet("${1|pub,pub(crate)|} fn $1");
+        assert_eq!(editor.snippet_choices().map(|(c, _)| c.len()), Some(2));
+
+        let _ = editor.update(&Message::SelectSnippetChoice(1));
+        assert_eq!(editor.content(), "pub(crate) fn pub(crate)");
+        assert_eq!(editor.snippet_choices().map(|(_, n)| n), Some(1));
+        assert_eq!(editor.get_selected_text().as_deref(), Some("pub(crate)"));
+    }
+
+    #[test]
+    fn test_tab_expands_library_prefix_at_word_start() {
+        let mut editor = editor_with("xfn");
+        editor.register_snippets(
+            "RS",
+            [Snippet::new("fn", "fn ${1:name}() {}"), Snippet::new("f", "F")],
+        );
+        editor.cursors.set_single((0, 3));
+
+        // `xfn` does not start with the prefix: Tab indents as usual.
+        assert!(editor.expand_snippet_prefix().is_none());
+
+        let _ = editor.reset("fn");
+        editor.cursors.set_single((0, 2));
+        let _ = editor.update(&Message::Tab);
+        assert_eq!(editor.content(), "fn name() {}");
+        assert_eq!(editor.get_selected_text().as_deref(), Some("name"));
+
+        // The expansion is a single undo step.
+        let _ = editor.update(&Message::Undo);
+        assert_eq!(editor.content(), "fn");
+    }
+
+    #[test]
+    fn test_escape_and_outside_edits_end_the_session() {
+        let mut editor = editor_with("");
+        let _ = editor.insert_snippet("${1:a} ${2:b}");
+        let _ = editor.update(&Message::LeaveSnippet);
+        assert!(!editor.is_snippet_active());
+
+        let _ = editor.reset("");
+        let _ = editor.insert_snippet("${1:a} ${2:b}");
+        editor.cursors.set_single((0, 3));
+        type_text(&mut editor, "!");
+        assert!(!editor.is_snippet_active());
+    }
+
+    #[test]
+    fn test_unresolved_variables_become_placeholders() {
+        let mut editor = editor_with("");
+        let _ = editor.insert_snippet("$TM_FILENAME ${UNKNOWN:default}");
+        assert_eq!(editor.content(), "TM_FILENAME default");
+        assert_eq!(editor.get_selected_text().as_deref(), Some("TM_FILENAME"));
+    }
+
+    #[test]
+    fn test_civil_from_days() {
+        assert_eq!(civil_from_days(0), (1970, 1, 1));
+        assert_eq!(civil_from_days(19_723), (2024, 1, 1));
+        assert_eq!(civil_from_days(19_782), (2024, 2, 29));
+    }
+}
@@ -0,0 +1,311 @@
+//! Parser for the LSP/TextMate snippet syntax.
+//!
+//! Supported constructs:
+//!
+//! * tabstops `$1` and `${1}`, with `$0` as the final caret position;
+//! * placeholders `${1:default}`, which may nest other placeholders;
+//! * choices `${1|one,two,three|}`;
+//! * variables `$NAME`, `${NAME}` and `${NAME:default}`.
+//!
+//! `\$`, `\}` and `\\` escape those characters; inside a choice `\,` and
+//! `\|` do too. Transforms (`${NAME/regex/format/}`) are accepted but not
+//! applied. Anything that does not parse is kept as literal text, as VS Code
+//! does, so a stray `$` or an unclosed `${` never makes a snippet unusable.
+
+/// One piece of a parsed snippet body.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum SnippetNode {
+    /// Literal text, with escapes resolved.
+    Text(String),
+    /// A tabstop, placeholder or choice.
+    Tabstop {
+        /// The tabstop number; `0` is the final caret position.
+        index: usize,
+        /// Default content, empty for a bare tabstop.
+        placeholder: Vec<SnippetNode>,
+        /// The options of a choice, empty otherwise.
+        choices: Vec<String>,
+    },
+    /// A variable, resolved when the snippet is inserted.
+    Variable {
+        /// The variable name, e.g. `TM_FILENAME`.
+        name: String,
+        /// Content used when the variable has no value.
+        default: Vec<SnippetNode>,
+    },
+}
+
+/// Parses a snippet body.
+///
+/// Never fails: malformed constructs are kept as literal text.
+pub(crate) fn parse(body: &str) -> Vec<SnippetNode> {
+    let mut parser = Parser { chars: body.chars().collect(), pos: 0 };
+    parser.nodes(false).unwrap_or_default()
+}
+
+/// Recursive-descent parser over the body's characters.
+struct Parser {
+    chars: Vec<char>,
+    pos: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.pos).copied()
+    }
+
+    /// Parses nodes up to the end of the input or, when `nested`, up to and
+    /// including the `}` closing the enclosing construct.
+    ///
+    /// Returns `None` when `nested` and the input ends first.
+    fn nodes(&mut self, nested: bool) -> Option<Vec<SnippetNode>> {
+        let mut nodes = Vec::new();
+        let mut text = String::new();
+        while let Some(ch) = self.peek() {
+            match ch {
+                '\\' => match self.chars.get(self.pos + 1) {
+                    Some(&escaped @ ('$' | '}' | '\\')) => {
+                        text.push(escaped);
+                        self.pos += 2;
+                    }
+                    _ => {
+                        text.push('\\');
+                        self.pos += 1;
+                    }
+                },
+                '}' if nested => {
+                    self.pos += 1;
+                    flush_text(&mut nodes, &mut text);
+                    return Some(nodes);
+                }
+                '$' => {
+                    let start = self.pos;
+                    self.pos += 1;
+                    if let Some(node) = self.dollar() {
+                        flush_text(&mut nodes, &mut text);
+                        nodes.push(node);
+                    } else {
+                        self.pos = start + 1;
+                        text.push('$');
+                    }
+                }
+                _ => {
+                    text.push(ch);
+                    self.pos += 1;
+                }
+            }
+        }
+        if nested {
+            return None;
+        }
+        flush_text(&mut nodes, &mut text);
+        Some(nodes)
+    }
+
+    /// Parses what follows a `$`; `None` when it is not a valid construct.
+    fn dollar(&mut self) -> Option<SnippetNode> {
+        if self.peek() != Some('{') {
+            if let Some(index) = self.number() {
+                return Some(tabstop(index, Vec::new(), Vec::new()));
+            }
+            let name = self.name()?;
+            return Some(SnippetNode::Variable { name, default: Vec::new() });
+        }
+
+        self.pos += 1;
+        if let Some(index) = self.number() {
+            let next = self.peek()?;
+            self.pos += 1;
+            return match next {
+                '}' => Some(tabstop(index, Vec::new(), Vec::new())),
+                ':' => Some(tabstop(index, self.nodes(true)?, Vec::new())),
+                '|' => Some(tabstop(index, Vec::new(), self.choices()?)),
+                '/' => {
+                    self.skip_transform()?;
+                    Some(tabstop(index, Vec::new(), Vec::new()))
+                }
+                _ => None,
+            };
+        }
+
+        let name = self.name()?;
+        let next = self.peek()?;
+        self.pos += 1;
+        let default = match next {
+            '}' => Vec::new(),
+            ':' => self.nodes(true)?,
+            '/' => {
+                self.skip_transform()?;
+                Vec::new()
+            }
+            _ => return None,
+        };
+        Some(SnippetNode::Variable { name, default })
+    }
+
+    /// Parses a run of ASCII digits.
+    fn number(&mut self) -> Option<usize> {
+        let start = self.pos;
+        while self.peek().is_some_and(|ch| ch.is_ascii_digit()) {
+            self.pos += 1;
+        }
+        if self.pos == start {
+            return None;
+        }
+        let digits: String = self.chars[start..self.pos].iter().collect();
+        digits.parse().ok()
+    }
+
+    /// Parses a variable name: `[_a-zA-Z][_a-zA-Z0-9]*`.
+    fn name(&mut self) -> Option<String> {
+        let start = self.pos;
+        if !self.peek().is_some_and(|ch| ch == '_' || ch.is_ascii_alphabetic())
+        {
+            return None;
+        }
+        while self
+            .peek()
+            .is_some_and(|ch| ch == '_' || ch.is_ascii_alphanumeric())
+        {
+            self.pos += 1;
+        }
+        Some(self.chars[start..self.pos].iter().collect())
+    }
+
+    /// Parses the options of a choice, after its opening `|`, through the
+    /// closing `|}`.
+    fn choices(&mut self) -> Option<Vec<String>> {
+        let mut choices = Vec::new();
+        let mut current = String::new();
+        loop {
+            let ch = self.peek()?;
+            self.pos += 1;
+            match ch {
+                '\\' => match self.peek() {
+                    Some(escaped @ ('$' | '}' | '\\' | ',' | '|')) => {
+                        current.push(escaped);
+                        self.pos += 1;
+                    }
+                    _ => current.push('\\'),
+                },
+                ',' => choices.push(std::mem::take(&mut current)),
+                '|' if self.peek() == Some('}') => {
+                    self.pos += 1;
+                    choices.push(current);
+                    return Some(choices);
+                }
+                _ => current.push(ch),
+            }
+        }
+    }
+
+    /// Skips a transform, after its first `/`, through the closing `}`.
+    fn skip_transform(&mut self) -> Option<()> {
+        let mut depth = 0usize;
+        loop {
+            let ch = self.peek()?;
+            self.pos += 1;
+            match ch {
+                '\\' => self.pos += 1,
+                '{' => depth += 1,
+                '}' if depth == 0 => return Some(()),
+                '}' => depth -= 1,
+                _ => {}
+            }
+        }
+    }
+}
+
+/// Builds a [`SnippetNode::Tabstop`].
+fn tabstop(
+    index: usize,
+    placeholder: Vec<SnippetNode>,
+    choices: Vec<String>,
+) -> SnippetNode {
+    SnippetNode::Tabstop { index, placeholder, choices }
+}
+
+/// Moves pending literal text into `nodes`.
+fn flush_text(nodes: &mut Vec<SnippetNode>, text: &mut String) {
+    if !text.is_empty() {
+        nodes.push(SnippetNode::Text(std::mem::take(text)));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn text(value: &str) -> SnippetNode {
+        SnippetNode::Text(value.to_string())
+    }
+
+    #[test]
+    fn test_parse_tabstops_placeholders_and_choices() {
+        assert_eq!(
+            parse("fn ${1:name}($2) ${3|a,b|}$0"),
+            [
+                text("fn "),
+                tabstop(1, vec![text("name")], Vec::new()),
+                text("("),
+                tabstop(2, Vec::new(), Vec::new()),
+                text(") "),
+                tabstop(3, Vec::new(), vec!["a".to_string(), "b".to_string()]),
+                tabstop(0, Vec::new(), Vec::new()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_nested_placeholders_and_variables() {
+        assert_eq!(
+            parse("${1:a ${2:b}} $TM_FILENAME ${X:${3}}"),
+            [
+                tabstop(
+                    1,
+                    vec![text("a "), tabstop(2, vec![text("b")], Vec::new())],
+                    Vec::new(),
+                ),
+                text(" "),
+                SnippetNode::Variable {
+                    name: "TM_FILENAME".to_string(),
+                    default: Vec::new(),
+                },
+                text(" "),
+                SnippetNode::Variable {
+                    name: "X".to_string(),
+                    default: vec![tabstop(3, Vec::new(), Vec::new())],
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_escapes_and_malformed_input_stay_literal() {
+        assert_eq!(parse(r"\$1 \} \\ a\b"), [text(r"$1 } \ a\b")]);
+        assert_eq!(parse("cost: $ 5"), [text("cost: $ 5")]);
+        assert_eq!(parse("${1:open"), [text("${1:open")]);
+        assert_eq!(
+            parse(r"${1|x\,y,z\|w|}"),
+            [tabstop(
+                1,
+                Vec::new(),
+                vec!["x,y".to_string(), "z|w".to_string()]
+            )]
+        );
+    }
+
+    #[test]
+    fn test_parse_ignores_transforms() {
+        assert_eq!(
+            parse("${TM_FILENAME/(.*)\\..*/${1:/upcase}/}!"),
+            [
+                SnippetNode::Variable {
+                    name: "TM_FILENAME".to_string(),
+                    default: Vec::new(),
+                },
+                text("!"),
+            ]
+        );
+    }
+}
@@ -0,0 +1,689 @@
+//! Vim Visual Block mode (`Ctrl+V`): a rectangle of text selected with one
+//! cursor per line, and the operators, replace and inserts that act on it.
+//!
+//! As with a column selection (see [`ColumnSelection`]), the rectangle is
+//! kept in visual columns, so tabs and wide characters line up on screen.
+//! Lines ending left of the rectangle have no part in it. After `$`, the
+//! rectangle reaches the end of every line instead.
+//!
+//! Every edit rewrites the block's lines through one
+//! [`ReplaceLinesCommand`], so it undoes as one step. A block insert (`I`,
+//! `A`, `c`) types on the first line only; when Insert mode ends, the typed
+//! text is copied to the other lines in the same history group.
+//!
+//! [`ColumnSelection`]: crate::canvas_editor::features::column_selection::ColumnSelection
+
+use iced::Task;
+
+use super::{
+    VimBlockInsert, VimMode, VimOperator, VimPastePosition, VimRegister,
+    VimRegisterKind,
+};
+use crate::canvas_editor::editing::command::{Command, ReplaceLinesCommand};
+use crate::canvas_editor::editing::cursor_set::Cursor;
+use crate::canvas_editor::{CodeEditor, Message};
+
+/// The rectangle a Visual Block covers.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct VimBlock {
+    top: usize,
+    bottom: usize,
+    /// Leftmost visual column.
+    left: usize,
+    /// Visual column just past the rightmost character.
+    right: usize,
+    /// Whether the block reaches the end of every line (`$`).
+    to_line_end: bool,
+}
+
+/// Returns `line` with its characters `[start, end)` replaced by `text`.
+pub(super) fn splice(
+    line: &str,
+    start: usize,
+    end: usize,
+    text: &str,
+) -> String {
+    let mut chars = line.chars();
+    let mut spliced: String = chars.by_ref().take(start).collect();
+    spliced.push_str(text);
+    spliced.extend(chars.skip(end.saturating_sub(start)));
+    spliced
+}
+
+impl CodeEditor {
+    /// Returns the visual columns `[start, end)` of the character at
+    /// `position`; an empty line counts as one column.
+    fn vim_char_columns(&self, (line, col): (usize, usize)) -> (usize, usize) {
+        let start = self.visual_column(line, col);
+        let end = if col < self.buffer.line_len(line) {
+            self.visual_column(line, col + 1)
+        } else {
+            start + 1
+        };
+        (start, end)
+    }
+
+    /// Returns the character of `line` covering the visual `column`, or
+    /// the line's length when the line ends before it.
+    pub(super) fn vim_char_at_visual(
+        &self,
+        line: usize,
+        column: usize,
+    ) -> usize {
+        let (col, _) = self.column_at_visual(line, column);
+        if col > 0 && self.visual_column(line, col) > column {
+            col - 1
+        } else {
+            col
+        }
+    }
+
+    /// Returns the visual columns `[start, end)` of the block's moving
+    /// corner on `active`'s line: the column `j` and `k` aim for, even
+    /// where the line is too short to reach it.
+    fn vim_active_columns(&self, active: (usize, usize)) -> (usize, usize) {
+        let Some(column) = self.vim_state.block_column() else {
+            return self.vim_char_columns(active);
+        };
+        let col = self.vim_char_at_visual(active.0, column);
+        if col < self.buffer.line_len(active.0) {
+            self.vim_char_columns((active.0, col))
+        } else {
+            (column, column + 1)
+        }
+    }
+
+    /// Returns the block with corners on the character at `anchor` and on
+    /// the moving corner at `active`.
+    fn vim_block(
+        &self,
+        anchor: (usize, usize),
+        active: (usize, usize),
+    ) -> VimBlock {
+        let (anchor_left, anchor_right) = self.vim_char_columns(anchor);
+        let (active_left, active_right) = self.vim_active_columns(active);
+        VimBlock {
+            top: anchor.0.min(active.0),
+            bottom: anchor.0.max(active.0),
+            left: anchor_left.min(active_left),
+            right: anchor_right.max(active_right),
+            to_line_end: self.vim_state.block_to_line_end(),
+        }
+    }
+
+    /// Returns the block of the current Visual Block selection.
+    fn current_vim_block(&self) -> Option<VimBlock> {
+        let (anchor, active) = self.vim_state.visual_positions()?;
+        Some(self.vim_block(
+            self.vim_normal_position(anchor),
+            self.vim_normal_position(active),
+        ))
+    }
+
+    /// Returns the characters `[start, end)` the block covers on `line`, or
+    /// `None` when the line ends left of it.
+    fn vim_block_span(
+        &self,
+        block: &VimBlock,
+        line: usize,
+    ) -> Option<(usize, usize)> {
+        let line_len = self.buffer.line_len(line);
+        let (start, _) = self.column_at_visual(line, block.left);
+        if start >= line_len {
+            return None;
+        }
+        let end = if block.to_line_end {
+            line_len
+        } else {
+            self.column_at_visual(line, block.right).0.max(start)
+        };
+        Some((start, end))
+    }
+
+    /// Selects the block with corners on `anchor` and `active` as one
+    /// cursor per line, skipping lines hidden by folds and lines ending
+    /// left of the block. The cursor on the active corner's line is
+    /// primary.
+    pub(crate) fn apply_vim_block_selection(
+        &mut self,
+        anchor: (usize, usize),
+        active: (usize, usize),
+    ) {
+        let anchor = self.vim_normal_position(anchor);
+        let active = self.vim_normal_position(active);
+        let block = self.vim_block(anchor, active);
+        let hidden = self.hidden_lines_set();
+        let span_cursor = |editor: &Self, line: usize| {
+            editor.vim_block_span(&block, line).map(|(start, end)| Cursor {
+                position: (line, end),
+                anchor: Some((line, start)),
+            })
+        };
+
+        let others: Vec<Cursor> = (block.top..=block.bottom)
+            .filter(|line| *line != active.0 && !hidden.contains(line))
+            .filter_map(|line| span_cursor(self, line))
+            .collect();
+        let primary =
+            span_cursor(self, active.0).unwrap_or_else(|| Cursor::new(active));
+        let mut cursors = others.into_iter();
+        if let Some(first) = cursors.next() {
+            self.cursors.set_single(first.position);
+            *self.cursors.primary_mut() = first;
+            for cursor in cursors {
+                self.cursors.add_cursor_with_selection(cursor);
+            }
+            // Added last, so it ends up primary.
+            self.cursors.add_cursor_with_selection(primary);
+        } else {
+            self.cursors.set_single(primary.position);
+            *self.cursors.primary_mut() = primary;
+        }
+
+        let width = if block.to_line_end {
+            (block.top..=block.bottom)
+                .map(|line| {
+                    self.visual_column(line, self.buffer.line_len(line))
+                })
+                .max()
+                .unwrap_or(0)
+                .saturating_sub(block.left)
+        } else {
+            block.right - block.left
+        };
+        self.vim_state.set_block_size((block.bottom - block.top + 1, width));
+        self.overlay_cache.clear();
+    }
+
+    /// Replaces the lines from `start` with `new_lines`, which may hold
+    /// more lines than they replace, as one undo step. The caller manages
+    /// the history group.
+    pub(super) fn rewrite_vim_lines(
+        &mut self,
+        start: usize,
+        old_len: usize,
+        new_lines: Vec<String>,
+        cursor_after: (usize, usize),
+    ) {
+        let cursor_before = self.cursors.primary_position();
+        let mut command = ReplaceLinesCommand::new(
+            &self.buffer,
+            start,
+            old_len,
+            new_lines,
+            cursor_before,
+            cursor_after,
+        );
+        if !command.is_noop() {
+            self.pre_edit_line = start;
+            self.pre_edit_last_line = start + old_len - 1;
+            self.capture_lsp_edit_snapshot(&Message::DeleteSelection);
+            let mut cursor = cursor_before;
+            command.execute(&mut self.buffer, &mut cursor);
+            self.history.push(Box::new(command));
+        }
+        self.cursors.set_single(cursor_after);
+        self.finish_edit_operation();
+    }
+
+    /// Applies `operator` to the Visual Block: the block's rows go to the
+    /// register as block-wise text, one `\n`-separated row per line.
+    /// Changing the block deletes it and starts a block insert on its left
+    /// edge.
+    pub(super) fn handle_vim_block_operator(
+        &mut self,
+        operator: VimOperator,
+    ) -> Task<Message> {
+        let Some(block) = self.current_vim_block() else {
+            return Task::none();
+        };
+        let spans: Vec<Option<(usize, usize)>> = (block.top..=block.bottom)
+            .map(|line| self.vim_block_span(&block, line))
+            .collect();
+        let lines: Vec<&str> = (block.top..=block.bottom)
+            .map(|line| self.buffer.line(line))
+            .collect();
+        let rows: Vec<String> = lines
+            .iter()
+            .zip(&spans)
+            .map(|(line, span)| {
+                span.map_or_else(String::new, |(start, end)| {
+                    line.chars().skip(start).take(end - start).collect()
+                })
+            })
+            .collect();
+        let new_lines: Vec<String> = lines
+            .iter()
+            .zip(&spans)
+            .map(|(line, span)| match span {
+                Some((start, end)) => splice(line, *start, *end, ""),
+                None => (*line).to_owned(),
+            })
+            .collect();
+        let register = VimRegister {
+            text: rows.join("\n"),
+            kind: VimRegisterKind::Blockwise,
+        };
+        let clipboard = self.store_vim_register(operator, register);
+        let corner =
+            (block.top, self.column_at_visual(block.top, block.left).0);
+
+        if operator == VimOperator::Yank {
+            self.cursors.set_single(self.vim_normal_position(corner));
+            self.vim_state.enter_clean_normal_mode();
+            self.finish_navigation_operation();
+            return Task::batch([clipboard, self.scroll_to_cursor()]);
+        }
+
+        self.end_grouping_if_active();
+        self.ensure_grouping_started();
+        let old_len = new_lines.len();
+        self.rewrite_vim_lines(block.top, old_len, new_lines, corner);
+        if operator == VimOperator::Change {
+            let lines = (block.top + 1..=block.bottom)
+                .zip(spans.iter().skip(1))
+                .filter_map(|(line, span)| span.map(|_| line))
+                .collect();
+            self.vim_state.enter_insert_mode();
+            self.vim_state.set_block_insert(VimBlockInsert {
+                start: corner,
+                line_len: self.buffer.line_len(block.top),
+                lines,
+                column: Some(block.left),
+                pad: false,
+            });
+        } else {
+            self.end_grouping_if_active();
+            self.cursors.set_single(self.vim_normal_position(corner));
+            self.vim_state.enter_clean_normal_mode();
+        }
+        Task::batch([clipboard, self.scroll_to_cursor()])
+    }
+
+    /// Returns the characters `[start, end)` the current Visual selection
+    /// covers on each of its lines, as `(line, start, end)`, or `None`
+    /// outside Visual modes.
+    pub(super) fn vim_visual_spans(
+        &self,
+    ) -> Option<Vec<(usize, usize, usize)>> {
+        let spans = match self.vim_state.mode() {
+            VimMode::VisualBlock => {
+                let block = self.current_vim_block()?;
+                (block.top..=block.bottom)
+                    .filter_map(|line| {
+                        let (start, end) = self.vim_block_span(&block, line)?;
+                        Some((line, start, end))
+                    })
+                    .collect()
+            }
+            VimMode::VisualLine => {
+                let (anchor, active) = self.vim_state.visual_positions()?;
+                (anchor.0.min(active.0)..=anchor.0.max(active.0))
+                    .map(|line| (line, 0, self.buffer.line_len(line)))
+                    .collect()
+            }
+            VimMode::Visual => {
+                let (start, end) = self.cursors.primary().selection_range()?;
+                (start.0..=end.0)
+                    .map(|line| {
+                        let from = if line == start.0 { start.1 } else { 0 };
+                        let to = if line == end.0 {
+                            end.1
+                        } else {
+                            self.buffer.line_len(line)
+                        };
+                        (line, from, to)
+                    })
+                    .collect()
+            }
+            VimMode::Normal | VimMode::Insert => return None,
+        };
+        Some(spans)
+    }
+
+    /// Replaces every selected character with `ch` (`r{char}` in Visual
+    /// modes), leaving line breaks in place.
+    pub(super) fn handle_vim_visual_replace(
+        &mut self,
+        ch: char,
+    ) -> Task<Message> {
+        let Some(spans) = self.vim_visual_spans() else {
+            return Task::none();
+        };
+        let (Some(first), Some(last)) = (spans.first(), spans.last()) else {
+            self.vim_state.enter_clean_normal_mode();
+            return Task::none();
+        };
+        let (top, corner) = (first.0, (first.0, first.1));
+        let bottom = last.0;
+
+        let mut new_lines: Vec<String> = (top..=bottom)
+            .map(|line| self.buffer.line(line).to_owned())
+            .collect();
+        for (line, start, end) in &spans {
+            if let Some(text) = new_lines.get_mut(line - top) {
+                *text = splice(
+                    text,
+                    *start,
+                    *end,
+                    &ch.to_string().repeat(end - start),
+                );
+            }
+        }
+        self.end_grouping_if_active();
+        let old_len = new_lines.len();
+        let corner = self.vim_normal_position(corner);
+        self.rewrite_vim_lines(top, old_len, new_lines, corner);
+        self.vim_state.enter_clean_normal_mode();
+        self.scroll_to_cursor()
+    }
+
+    /// Starts a block insert: Insert mode on the block's first line, before
+    /// the block (`I`) or after it (`A`). `I` later copies the text to the
+    /// lines that reach into the block; `A` to every line, padding short
+    /// ones with spaces, or at the end of every line after `$`.
+    pub(super) fn handle_vim_block_insert(
+        &mut self,
+        append: bool,
+    ) -> Task<Message> {
+        let Some(block) = self.current_vim_block() else {
+            return Task::none();
+        };
+        let (column, pad) = match (append, block.to_line_end) {
+            (true, true) => (None, false),
+            (true, false) => (Some(block.right), true),
+            (false, _) => (Some(block.left), false),
+        };
+        let lines = (block.top + 1..=block.bottom)
+            .filter(|&line| match column {
+                Some(column) if !pad => {
+                    self.visual_column(line, self.buffer.line_len(line))
+                        > column
+                }
+                _ => true,
+            })
+            .collect();
+
+        self.end_grouping_if_active();
+        self.ensure_grouping_started();
+        let top_len = self.buffer.line_len(block.top);
+        let (col, padding) = column.map_or((top_len, 0), |column| {
+            self.column_at_visual(block.top, column)
+        });
+        if pad && padding > 0 {
+            let padded = format!(
+                "{}{}",
+                self.buffer.line(block.top),
+                " ".repeat(padding)
+            );
+            self.rewrite_vim_lines(block.top, 1, vec![padded], (block.top, 0));
+        }
+        let start = (block.top, col + if pad { padding } else { 0 });
+        self.cursors.set_single(start);
+        self.vim_state.enter_insert_mode();
+        self.vim_state.set_block_insert(VimBlockInsert {
+            start,
+            line_len: self.buffer.line_len(block.top),
+            lines,
+            column,
+            pad,
+        });
+        self.overlay_cache.clear();
+        self.reset_cursor_blink();
+        self.scroll_to_cursor()
+    }
+
+    /// Ends a block insert as Insert mode ends: copies the text typed on
+    /// the block's first line to its other lines. Nothing is copied when
+    /// the typing left the first line, as a line break does.
+    ///
+    /// # Returns
+    ///
+    /// Where the block insert started, on which Normal mode resumes, or
+    /// `None` when no block insert was active
+    pub(super) fn finish_vim_block_insert(&mut self) -> Option<(usize, usize)> {
+        let block_insert = self.vim_state.take_block_insert()?;
+        let (line, col) = block_insert.start;
+        let grown =
+            self.buffer.line_len(line).saturating_sub(block_insert.line_len);
+        let (Some(first), Some(last)) =
+            (block_insert.lines.first(), block_insert.lines.last())
+        else {
+            return Some(block_insert.start);
+        };
+        if grown == 0 || self.cursors.primary_position() != (line, col + grown)
+        {
+            return Some(block_insert.start);
+        }
+        let text: String =
+            self.buffer.line(line).chars().skip(col).take(grown).collect();
+
+        let (first, last) = (*first, *last);
+        let new_lines: Vec<String> = (first..=last)
+            .map(|target| {
+                let content = self.buffer.line(target);
+                if !block_insert.lines.contains(&target) {
+                    return content.to_owned();
+                }
+                let (at, past_end) = block_insert
+                    .column
+                    .map_or((self.buffer.line_len(target), 0), |column| {
+                        self.column_at_visual(target, column)
+                    });
+                if past_end > 0 && !block_insert.pad {
+                    return content.to_owned();
+                }
+                let inserted = format!("{}{text}", " ".repeat(past_end));
+                splice(content, at, at, &inserted)
+            })
+            .collect();
+        let cursor = self.cursors.primary_position();
+        self.rewrite_vim_lines(first, last - first + 1, new_lines, cursor);
+        Some(block_insert.start)
+    }
+
+    /// Pastes block-wise register text: each row goes into the next line
+    /// at the cursor's visual column (after the cursor for `p`), padding
+    /// short lines with spaces and adding lines past the end of the buffer.
+    /// A count repeats each row.
+    pub(super) fn paste_vim_block(
+        &mut self,
+        text: &str,
+        position: VimPastePosition,
+        count: usize,
+    ) -> Task<Message> {
+        let current = self.vim_normal_position(self.cursors.primary_position());
+        let column = match position {
+            VimPastePosition::AfterCursor
+                if self.buffer.line_len(current.0) > 0 =>
+            {
+                self.vim_char_columns(current).1
+            }
+            _ => self.visual_column(current.0, current.1),
+        };
+        let rows: Vec<String> =
+            text.split('\n').map(|row| row.repeat(count.max(1))).collect();
+        let last_line = self.buffer.line_count().saturating_sub(1);
+        let old_len = (rows.len().min(last_line + 1 - current.0)).max(1);
+        let new_lines: Vec<String> = rows
+            .iter()
+            .enumerate()
+            .map(|(offset, row)| {
+                let line = current.0 + offset;
+                if line > last_line {
+                    return format!("{}{row}", " ".repeat(column));
+                }
+                let (at, past_end) = self.column_at_visual(line, column);
+                let inserted = format!("{}{row}", " ".repeat(past_end));
+                splice(self.buffer.line(line), at, at, &inserted)
+            })
+            .collect();
+
+        let cursor_after =
+            (current.0, self.column_at_visual(current.0, column).0);
+        self.rewrite_vim_lines(current.0, old_len, new_lines, cursor_after);
+        self.cursors.set_single(self.vim_normal_position(cursor_after));
+        self.vim_state.enter_clean_normal_mode();
+        self.scroll_to_cursor()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::canvas_editor::VimMode;
+    use crate::canvas_editor::features::vim::test_support::{
+        type_text, vim_keys,
+    };
+
+    fn selections(
+        editor: &CodeEditor,
+    ) -> Vec<((usize, usize), (usize, usize))> {
+        editor.cursors.iter().filter_map(Cursor::selection_range).collect()
+    }
+
+    #[test]
+    fn test_block_selection_is_one_cursor_per_line() {
+        let mut editor =
+            CodeEditor::new("abcdef\nab\nabcdef", "txt").with_vim_enabled(true);
+        editor.cursors.set_single((0, 1));
+        vim_keys(&mut editor, "\u{16}jjll");
+        assert_eq!(editor.vim_mode(), Some(VimMode::VisualBlock));
+        // The short middle line only covers the part reaching into the block.
+        assert_eq!(
+            selections(&editor),
+            vec![((0, 1), (0, 4)), ((1, 1), (1, 2)), ((2, 1), (2, 4))]
+        );
+        assert_eq!(editor.cursors.primary_position(), (2, 4));
+        assert_eq!(
+            editor.vim_state.status_line_text(),
+            ("-- VISUAL BLOCK --".to_owned(), "3x3".to_owned())
+        );
+
+        // `$` reaches the end of every line.
+        vim_keys(&mut editor, "$");
+        assert_eq!(
+            selections(&editor),
+            vec![((0, 1), (0, 6)), ((1, 1), (1, 2)), ((2, 1), (2, 6))]
+        );
+
+        vim_keys(&mut editor, "\u{1b}");
+        assert_eq!(editor.cursors.len(), 1);
+    }
+
+    #[test]
+    fn test_block_delete_yank_and_paste() {
+        let mut editor =
+            CodeEditor::new("abcd\nefgh\nijkl", "txt").with_vim_enabled(true);
+        editor.cursors.set_single((0, 1));
+        vim_keys(&mut editor, "\u{16}jld");
+        assert_eq!(editor.content(), "ad\neh\nijkl");
+        assert_eq!(editor.vim_state.register.text, "bc\nfg");
+        assert_eq!(editor.vim_state.register.kind, VimRegisterKind::Blockwise);
+        assert_eq!(editor.cursors.primary_position(), (0, 1));
+
+        // Pasting puts each row on the next line, adding lines at the end.
+        vim_keys(&mut editor, "jjp");
+        assert_eq!(editor.content(), "ad\neh\nijbckl\n  fg");
+
+        let _ = editor.update(&Message::Undo);
+        assert_eq!(editor.content(), "ad\neh\nijkl");
+        let _ = editor.update(&Message::Undo);
+        assert_eq!(editor.content(), "abcd\nefgh\nijkl");
+
+        vim_keys(&mut editor, "gg\u{16}jy");
+        assert_eq!(editor.vim_state.register.text, "a\ne");
+        assert_eq!(editor.content(), "abcd\nefgh\nijkl");
+    }
+
+    #[test]
+    fn test_block_insert_copies_the_text_to_every_line() {
+        let mut editor =
+            CodeEditor::new("one\n\ntwo\nthree", "txt").with_vim_enabled(true);
+        vim_keys(&mut editor, "\u{16}3jI");
+        assert_eq!(editor.vim_mode(), Some(VimMode::Insert));
+        type_text(&mut editor, "# ");
+        assert_eq!(editor.content(), "# one\n\ntwo\nthree");
+        vim_keys(&mut editor, "\u{1b}");
+        // The empty line does not reach into the block.
+        assert_eq!(editor.content(), "# one\n\n# two\n# three");
+        assert_eq!(editor.cursors.primary_position(), (0, 0));
+
+        let _ = editor.update(&Message::Undo);
+        assert_eq!(editor.content(), "one\n\ntwo\nthree");
+    }
+
+    #[test]
+    fn test_block_append_pads_short_lines_or_follows_line_ends() {
+        let mut editor =
+            CodeEditor::new("abc\na\nabc", "txt").with_vim_enabled(true);
+        editor.cursors.set_single((0, 1));
+        vim_keys(&mut editor, "\u{16}jjA");
+        type_text(&mut editor, "|");
+        vim_keys(&mut editor, "\u{1b}");
+        assert_eq!(editor.content(), "ab|c\na |\nab|c");
+
+        let mut editor =
+            CodeEditor::new("abc\na\nabcd", "txt").with_vim_enabled(true);
+        vim_keys(&mut editor, "\u{16}jj$A");
+        type_text(&mut editor, ";");
+        vim_keys(&mut editor, "\u{1b}");
+        assert_eq!(editor.content(), "abc;\na;\nabcd;");
+    }
+
+    #[test]
+    fn test_block_keeps_its_column_through_short_lines() {
+        for text in ["abc\nd\nghi", "abc\n\nghi"] {
+            let mut editor =
+                CodeEditor::new(text, "txt").with_vim_enabled(true);
+            editor.cursors.set_single((0, 2));
+            vim_keys(&mut editor, "\u{16}jj");
+            assert_eq!(editor.cursors.primary_position(), (2, 3));
+            assert_eq!(editor.vim_state.status_line_text().1, "3x1".to_owned());
+
+            // `I` skips the line that does not reach the block.
+            vim_keys(&mut editor, "I");
+            type_text(&mut editor, "X");
+            vim_keys(&mut editor, "\u{1b}");
+            let short = text.lines().nth(1).unwrap_or_default();
+            assert_eq!(editor.content(), format!("abXc\n{short}\nghXi"));
+
+            // `A` pads it, and a counted move keeps the column too.
+            let mut editor =
+                CodeEditor::new(text, "txt").with_vim_enabled(true);
+            editor.cursors.set_single((0, 2));
+            vim_keys(&mut editor, "\u{16}2jA");
+            type_text(&mut editor, "X");
+            vim_keys(&mut editor, "\u{1b}");
+            let padded = format!("{short:<3}X");
+            assert_eq!(editor.content(), format!("abcX\n{padded}\nghiX"));
+        }
+
+        // A horizontal move aims for the column it lands on.
+        let mut editor =
+            CodeEditor::new("abc\nd\nghi", "txt").with_vim_enabled(true);
+        editor.cursors.set_single((0, 2));
+        vim_keys(&mut editor, "\u{16}jhj");
+        assert_eq!(editor.vim_state.visual_positions(), Some(((0, 2), (2, 0))));
+    }
+
+    #[test]
+    fn test_block_change_and_replace() {
+        let mut editor = CodeEditor::new("foo = 1\nbar = 2\nbaz", "txt")
+            .with_vim_enabled(true);
+        vim_keys(&mut editor, "\u{16}jjllc");
+        type_text(&mut editor, "x");
+        vim_keys(&mut editor, "\u{1b}");
+        assert_eq!(editor.content(), "x = 1\nx = 2\nx");
+        let _ = editor.update(&Message::Undo);
+        assert_eq!(editor.content(), "foo = 1\nbar = 2\nbaz");
+
+        vim_keys(&mut editor, "gg\u{16}jlr-");
+        assert_eq!(editor.content(), "--o = 1\n--r = 2\nbaz");
+        assert_eq!(editor.vim_mode(), Some(VimMode::Normal));
+
+        // `r` also replaces a character-wise selection, keeping line breaks.
+        vim_keys(&mut editor, "wvjr.");
+        assert_eq!(editor.content(), "--.....\n... = 2\nbaz");
+    }
+}
@@ -0,0 +1,1021 @@
+//! Ex commands typed on the Vim `:` command line.
+//!
+//! [`parse_ex_command`] turns a line such as `'<,'>s/foo/bar/g` into an
+//! [`ExCommand`]: the line addresses of its range and the command to run
+//! over them. Addresses stay symbolic (`.`, `$`, `'a`, `+2`) until
+//! execution resolves them against the buffer, which is done by the
+//! handlers in `update`.
+//!
+//! `:s` and `:g` take Vim regular expressions, which `pattern` compiles
+//! for the `regex` crate; a `:s` replacement may refer to the pattern's
+//! groups as `\1` to `\9`.
+
+mod pattern;
+mod update;
+
+use super::VimMapMode;
+use super::mapping::parse_vim_keys;
+
+/// The line an address starts from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum ExLine {
+    /// A 1-based line number (`12`); `0` is the position before the first
+    /// line, which only `:m` and `:t` accept.
+    Number(usize),
+    /// The cursor line (`.`).
+    Current,
+    /// The last line (`$`).
+    Last,
+    /// The line of a mark (`'a`, `'<`).
+    Mark(char),
+}
+
+/// A line address: a line and the `+N`/`-N` offsets that follow it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct ExAddress {
+    pub(crate) line: ExLine,
+    pub(crate) offset: isize,
+}
+
+impl ExAddress {
+    const fn new(line: ExLine) -> Self {
+        Self { line, offset: 0 }
+    }
+}
+
+/// A part of a `:s` replacement.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum ExReplacement {
+    /// Literal text, which may hold line breaks (`\r`).
+    Text(String),
+    /// The matched text (`&`, `\0`).
+    Match,
+    /// The text a `\(` group of the pattern matched (`\1` to `\9`).
+    Group(usize),
+}
+
+/// The pattern, replacement and flags of a `:s/pattern/replacement/flags`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct ExSubstitute {
+    /// The pattern to replace, in Vim's regex syntax; empty for the last
+    /// search.
+    pub(crate) pattern: String,
+    pub(crate) replacement: Vec<ExReplacement>,
+    /// Whether every match on a line is replaced (`g`), not just the first.
+    pub(crate) global: bool,
+    /// Case sensitivity forced with `I` or `i`; otherwise the search
+    /// dialog's setting applies.
+    pub(crate) case_sensitive: Option<bool>,
+}
+
+/// An editor option `:set` reads or changes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum ExOption {
+    /// `wrap`: line wrapping.
+    Wrap,
+    /// `number` (`nu`): the line-number gutter.
+    Number,
+    /// `expandtab` (`et`): indenting with spaces rather than tabs.
+    ExpandTab,
+    /// `shiftwidth` (`sw`): the number of spaces one indent level inserts.
+    ShiftWidth,
+    /// `autoindent` (`ai`): indenting new lines like the previous one.
+    AutoIndent,
+    /// `list`: drawing whitespace characters.
+    List,
+    /// `timeoutlen` (`tm`): how many milliseconds typed keys wait for the
+    /// rest of a mapping.
+    TimeoutLen,
+}
+
+impl ExOption {
+    /// Every option, in the order `:set` with no argument lists them.
+    pub(crate) const ALL: [Self; 7] = [
+        Self::AutoIndent,
+        Self::ExpandTab,
+        Self::List,
+        Self::Number,
+        Self::ShiftWidth,
+        Self::TimeoutLen,
+        Self::Wrap,
+    ];
+
+    fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "wrap" => Some(Self::Wrap),
+            "number" | "nu" => Some(Self::Number),
+            "expandtab" | "et" => Some(Self::ExpandTab),
+            "shiftwidth" | "sw" => Some(Self::ShiftWidth),
+            "autoindent" | "ai" => Some(Self::AutoIndent),
+            "list" => Some(Self::List),
+            "timeoutlen" | "tm" => Some(Self::TimeoutLen),
+            _ => None,
+        }
+    }
+
+    pub(crate) fn name(self) -> &'static str {
+        match self {
+            Self::Wrap => "wrap",
+            Self::Number => "number",
+            Self::ExpandTab => "expandtab",
+            Self::ShiftWidth => "shiftwidth",
+            Self::AutoIndent => "autoindent",
+            Self::List => "list",
+            Self::TimeoutLen => "timeoutlen",
+        }
+    }
+
+    /// Returns whether the option is on or off rather than a number.
+    pub(crate) fn is_boolean(self) -> bool {
+        !matches!(self, Self::ShiftWidth | Self::TimeoutLen)
+    }
+}
+
+/// What one `:set` argument does to its option.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum ExOptionChange {
+    /// Turn a boolean option on (`wrap`) or off (`nowrap`).
+    Set(bool),
+    /// Flip a boolean option (`wrap!`, `invwrap`).
+    Toggle,
+    /// Give a number option a value (`sw=2`, `tm=500`).
+    Value(usize),
+    /// Show the option's value (`wrap?`, or a bare number option).
+    Query,
+}
+
+/// One argument of `:set`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct ExSetting {
+    pub(crate) option: ExOption,
+    pub(crate) change: ExOptionChange,
+}
+
+/// The command of an Ex command line.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum ExCommandKind {
+    /// A bare range (`:12`, `:'a`): jump to its last line.
+    Goto,
+    /// `:p`: show the last line of the range.
+    Print,
+    /// `:s/pattern/replacement/flags`, or a bare `:s` repeating the last
+    /// substitution.
+    Substitute(Option<ExSubstitute>),
+    /// `:g/pattern/command`, running `command` on every line of the range
+    /// that contains `pattern`, or that does not for `:g!` and `:v`.
+    Global { pattern: String, invert: bool, command: Box<ExCommand> },
+    /// `:d [x] [count]`: delete lines into a register.
+    Delete { register: Option<char>, count: Option<usize> },
+    /// `:y [x] [count]`: yank lines into a register.
+    Yank { register: Option<char>, count: Option<usize> },
+    /// `:m {address}`: move lines below the addressed line.
+    Move(ExAddress),
+    /// `:t {address}` or `:co {address}`: copy lines below the addressed
+    /// line.
+    Copy(ExAddress),
+    /// `:noh`: hide the search highlight until the next search.
+    NoHighlight,
+    /// `:set`: read or change editor options; with no argument, show them
+    /// all.
+    Set(Vec<ExSetting>),
+    /// `:w`, `:wq` or `:x`: ask the host to save, then turn Vim off for
+    /// `:wq`.
+    Write { quit: bool },
+    /// `:q`: turn Vim off.
+    Quit,
+    /// `:reg [names]` or `:di [names]`: list registers.
+    Registers(String),
+    /// `:map`, `:nnoremap` and the like: map `lhs` to `rhs` in `modes`, or
+    /// without `rhs` list the mappings starting with `lhs`.
+    Map {
+        modes: Vec<VimMapMode>,
+        recursive: bool,
+        lhs: String,
+        rhs: Option<String>,
+    },
+    /// `:unmap`, `:nunmap` and the like: remove the mapping of `lhs`.
+    Unmap { modes: Vec<VimMapMode>, lhs: String },
+    /// `:let mapleader = "x"`: set the key `<Leader>` stands for.
+    SetLeader(char),
+}
+
+/// A parsed Ex command line.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct ExCommand {
+    /// Up to two addresses; none means the command's default range.
+    pub(crate) range: Vec<ExAddress>,
+    pub(crate) kind: ExCommandKind,
+}
+
+/// Lines an Ex command removed and inserted, which `:g` uses to follow the
+/// lines it has still to visit.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct ExLineEdit {
+    /// The first 0-based line touched.
+    pub(crate) at: usize,
+    pub(crate) removed: usize,
+    pub(crate) inserted: usize,
+}
+
+impl ExLineEdit {
+    /// Returns where `line` is after the edit, or `None` when it was
+    /// removed.
+    pub(crate) fn map_line(self, line: usize) -> Option<usize> {
+        if line < self.at {
+            Some(line)
+        } else if line < self.at + self.removed {
+            None
+        } else {
+            Some(line - self.removed + self.inserted)
+        }
+    }
+}
+
+/// Command names and the length each may be abbreviated to, so `:s`,
+/// `:sub` and `:substitute` are the same command.
+const EX_COMMANDS: [(&str, usize); 30] = [
+    ("substitute", 1),
+    ("global", 1),
+    ("vglobal", 1),
+    ("delete", 1),
+    ("yank", 1),
+    ("move", 1),
+    ("copy", 2),
+    ("t", 1),
+    ("print", 1),
+    ("nohlsearch", 3),
+    ("set", 2),
+    ("write", 1),
+    ("wq", 2),
+    ("xit", 1),
+    ("quit", 1),
+    ("registers", 3),
+    ("display", 2),
+    ("map", 3),
+    ("nmap", 2),
+    ("vmap", 2),
+    ("imap", 2),
+    ("noremap", 2),
+    ("nnoremap", 2),
+    ("vnoremap", 2),
+    ("inoremap", 3),
+    ("unmap", 3),
+    ("nunmap", 3),
+    ("vunmap", 2),
+    ("iunmap", 2),
+    ("let", 3),
+];
+
+fn command_name(name: &str) -> Option<&'static str> {
+    EX_COMMANDS
+        .iter()
+        .find(|(full, min_len)| {
+            name.len() >= *min_len && full.starts_with(name)
+        })
+        .map(|(full, _)| *full)
+}
+
+/// Parses an Ex command line, typed without its leading `:`.
+///
+/// # Arguments
+///
+/// * `input` - The command line
+///
+/// # Returns
+///
+/// The parsed command, or the Vim-style error message to show, such as
+/// `E492: Not an editor command: foo`
+pub(crate) fn parse_ex_command(input: &str) -> Result<ExCommand, String> {
+    let mut rest = input.trim_start_matches([' ', ':']);
+    let range = parse_range(&mut rest)?;
+    let rest = rest.trim_start();
+    let name_len =
+        rest.find(|ch: char| !ch.is_ascii_alphabetic()).unwrap_or(rest.len());
+    let (name, args) = rest.split_at(name_len);
+    let (bang, args) = match args.strip_prefix('!') {
+        Some(args) => (true, args),
+        None => (false, args),
+    };
+
+    let kind = if name.is_empty() {
+        if bang {
+            return Err(not_an_editor_command(input));
+        }
+        expect_end(args)?;
+        ExCommandKind::Goto
+    } else {
+        let full_name =
+            command_name(name).ok_or_else(|| not_an_editor_command(input))?;
+        match full_name {
+            "substitute" => ExCommandKind::Substitute(parse_substitute(args)?),
+            "global" | "vglobal" => {
+                let (pattern, command) = parse_global(args)?;
+                ExCommandKind::Global {
+                    pattern,
+                    invert: bang || full_name == "vglobal",
+                    command: Box::new(command),
+                }
+            }
+            "delete" => {
+                let (register, count) = parse_register_and_count(args)?;
+                ExCommandKind::Delete { register, count }
+            }
+            "yank" => {
+                let (register, count) = parse_register_and_count(args)?;
+                ExCommandKind::Yank { register, count }
+            }
+            "move" => ExCommandKind::Move(parse_target(args)?),
+            "copy" | "t" => ExCommandKind::Copy(parse_target(args)?),
+            "print" => {
+                expect_end(args)?;
+                ExCommandKind::Print
+            }
+            "nohlsearch" => {
+                expect_end(args)?;
+                ExCommandKind::NoHighlight
+            }
+            "set" => ExCommandKind::Set(
+                args.split_whitespace()
+                    .map(parse_setting)
+                    .collect::<Result<_, _>>()?,
+            ),
+            "write" | "wq" | "xit" => {
+                expect_end(args)?;
+                ExCommandKind::Write { quit: full_name != "write" }
+            }
+            "quit" => {
+                expect_end(args)?;
+                ExCommandKind::Quit
+            }
+            "registers" | "display" => {
+                ExCommandKind::Registers(args.split_whitespace().collect())
+            }
+            "let" => ExCommandKind::SetLeader(parse_let_leader(args)?),
+            _ => parse_mapping(full_name, bang, args)?,
+        }
+    };
+    Ok(ExCommand { range, kind })
+}
+
+/// Parses the arguments of a mapping command named `name`: `lhs rhs`,
+/// after any `<silent>`-style modifiers, which are ignored.
+fn parse_mapping(
+    name: &str,
+    bang: bool,
+    args: &str,
+) -> Result<ExCommandKind, String> {
+    let (unmap, base) = match name.strip_suffix("unmap") {
+        Some(prefix) => (true, prefix),
+        None => (false, name.trim_end_matches("map").trim_end_matches("nore")),
+    };
+    let modes = match (base, bang) {
+        ("", false) => vec![VimMapMode::Normal, VimMapMode::Visual],
+        ("", true) => vec![VimMapMode::Insert],
+        (_, true) => return Err("E477: No ! allowed".to_owned()),
+        ("n", _) => vec![VimMapMode::Normal],
+        ("v", _) => vec![VimMapMode::Visual],
+        _ => vec![VimMapMode::Insert],
+    };
+
+    let mut args = args.trim_start();
+    while let Some(rest) = ["<silent>", "<nowait>", "<buffer>", "<unique>"]
+        .iter()
+        .find_map(|modifier| {
+            let prefix = args.get(..modifier.len())?;
+            prefix.eq_ignore_ascii_case(modifier).then(|| &args[prefix.len()..])
+        })
+    {
+        args = rest.trim_start();
+    }
+    let (lhs, rhs) = match args.split_once(char::is_whitespace) {
+        Some((lhs, rhs)) => (lhs.to_owned(), Some(rhs.trim().to_owned())),
+        None => (args.to_owned(), None),
+    };
+
+    if unmap {
+        if lhs.is_empty() {
+            return Err("E474: Invalid argument".to_owned());
+        }
+        return Ok(ExCommandKind::Unmap { modes, lhs });
+    }
+    let rhs = rhs.filter(|rhs| !rhs.is_empty());
+    Ok(ExCommandKind::Map {
+        modes,
+        recursive: !name.contains("nore"),
+        lhs,
+        rhs,
+    })
+}
+
+/// Parses `mapleader = "x"` after `:let`: a double-quoted string, where
+/// `\<Key>`, `\\` and `\"` are escapes, or a single-quoted one, where
+/// `''` is a quote. The string must be a single key.
+fn parse_let_leader(args: &str) -> Result<char, String> {
+    let (name, value) = args
+        .split_once('=')
+        .ok_or_else(|| format!("E15: Invalid expression: {}", args.trim()))?;
+    let name = name.trim();
+    if name != "mapleader" && name != "g:mapleader" {
+        return Err(format!("E121: Undefined variable: {name}"));
+    }
+    let value = value.trim();
+    let invalid = || format!("E15: Invalid expression: {value}");
+    let keys = if let Some(quoted) =
+        value.strip_prefix('"').and_then(|value| value.strip_suffix('"'))
+    {
+        let mut keys = Vec::new();
+        let mut rest = quoted;
+        while let Some(ch) = rest.chars().next() {
+            rest = &rest[ch.len_utf8()..];
+            if ch != '\\' {
+                keys.push(ch);
+            } else if rest.starts_with('<')
+                && let Some(end) = rest.find('>')
+            {
+                keys.extend(parse_vim_keys(&rest[..=end], '\\'));
+                rest = &rest[end + 1..];
+            } else if let Some(escaped) = rest.chars().next() {
+                keys.push(escaped);
+                rest = &rest[escaped.len_utf8()..];
+            }
+        }
+        keys
+    } else if let Some(quoted) =
+        value.strip_prefix('\'').and_then(|value| value.strip_suffix('\''))
+    {
+        quoted.replace("''", "'").chars().collect()
+    } else {
+        return Err(invalid());
+    };
+    match keys.as_slice() {
+        [leader] => Ok(*leader),
+        _ => Err(format!("E474: Invalid argument: {value}")),
+    }
+}
+
+fn not_an_editor_command(input: &str) -> String {
+    format!("E492: Not an editor command: {}", input.trim())
+}
+
+fn invalid_range() -> String {
+    "E16: Invalid range".to_owned()
+}
+
+fn expect_end(args: &str) -> Result<(), String> {
+    let args = args.trim();
+    if args.is_empty() {
+        Ok(())
+    } else {
+        Err(format!("E488: Trailing characters: {args}"))
+    }
+}
+
+/// Parses the range before a command: `%`, or up to two addresses
+/// separated by `,` or `;`, where a missing address is the cursor line.
+fn parse_range(rest: &mut &str) -> Result<Vec<ExAddress>, String> {
+    if let Some(after) = rest.strip_prefix('%') {
+        *rest = after;
+        return Ok(vec![
+            ExAddress::new(ExLine::Number(1)),
+            ExAddress::new(ExLine::Last),
+        ]);
+    }
+
+    let mut addresses = Vec::new();
+    let mut after_separator = false;
+    loop {
+        *rest = rest.trim_start();
+        let address = parse_address(rest)?;
+        *rest = rest.trim_start();
+        if let Some(after) = rest.strip_prefix([',', ';']) {
+            *rest = after;
+            addresses.push(address.unwrap_or(ExAddress::new(ExLine::Current)));
+            after_separator = true;
+            continue;
+        }
+        if let Some(address) = address {
+            addresses.push(address);
+        } else if after_separator {
+            addresses.push(ExAddress::new(ExLine::Current));
+        }
+        break;
+    }
+    // Like Vim, only the last two addresses count.
+    let extra = addresses.len().saturating_sub(2);
+    addresses.drain(..extra);
+    Ok(addresses)
+}
+
+/// Parses one address, returning `None` when `rest` does not start with
+/// one.
+fn parse_address(rest: &mut &str) -> Result<Option<ExAddress>, String> {
+    let mut chars = rest.chars();
+    let line = match chars.next() {
+        Some('.') => {
+            *rest = chars.as_str();
+            Some(ExLine::Current)
+        }
+        Some('$') => {
+            *rest = chars.as_str();
+            Some(ExLine::Last)
+        }
+        Some('\'') => {
+            let mark = match chars.next() {
+                Some(mark @ ('a'..='z' | '<' | '>')) => mark,
+                Some('\'' | '`') => super::PREVIOUS_CONTEXT_MARK,
+                _ => return Err("E78: Unknown mark".to_owned()),
+            };
+            *rest = chars.as_str();
+            Some(ExLine::Mark(mark))
+        }
+        Some(ch) if ch.is_ascii_digit() => {
+            Some(ExLine::Number(parse_number(rest)?.unwrap_or(0)))
+        }
+        _ => None,
+    };
+
+    let mut offset = 0isize;
+    let mut has_offset = false;
+    while let Some(sign) =
+        rest.chars().next().filter(|ch| matches!(ch, '+' | '-'))
+    {
+        *rest = &rest[1..];
+        let amount = isize::try_from(parse_number(rest)?.unwrap_or(1))
+            .map_err(|_| invalid_range())?;
+        offset = if sign == '+' {
+            offset.checked_add(amount)
+        } else {
+            offset.checked_sub(amount)
+        }
+        .ok_or_else(invalid_range)?;
+        has_offset = true;
+    }
+
+    Ok(match line {
+        Some(line) => Some(ExAddress { line, offset }),
+        None if has_offset => Some(ExAddress { line: ExLine::Current, offset }),
+        None => None,
+    })
+}
+
+/// Parses the digits `rest` starts with, if any.
+fn parse_number(rest: &mut &str) -> Result<Option<usize>, String> {
+    let len = rest.find(|ch: char| !ch.is_ascii_digit()).unwrap_or(rest.len());
+    if len == 0 {
+        return Ok(None);
+    }
+    let (digits, after) = rest.split_at(len);
+    *rest = after;
+    digits.parse().map(Some).map_err(|_| invalid_range())
+}
+
+/// Parses the `:m` and `:t` destination.
+fn parse_target(args: &str) -> Result<ExAddress, String> {
+    let mut rest = args.trim_start();
+    let target = parse_address(&mut rest)?
+        .ok_or_else(|| "E14: Invalid address".to_owned())?;
+    expect_end(rest)?;
+    Ok(target)
+}
+
+/// Parses the `[x] [count]` arguments of `:d` and `:y`.
+fn parse_register_and_count(
+    args: &str,
+) -> Result<(Option<char>, Option<usize>), String> {
+    let mut rest = args.trim_start();
+    let mut register = None;
+    if let Some(name) = rest.chars().next().filter(|ch| {
+        ch.is_ascii_alphabetic() || matches!(ch, '"' | '-' | '_' | '+' | '*')
+    }) {
+        rest = rest[name.len_utf8()..].trim_start();
+        register = (name != '"').then_some(name);
+    }
+    let count = parse_number(&mut rest)?;
+    if count == Some(0) {
+        return Err("E939: Positive count required".to_owned());
+    }
+    expect_end(rest)?;
+    Ok((register, count))
+}
+
+/// Returns the delimiter a `:s` or `:g` pattern starts with, if it may
+/// delimit one.
+fn pattern_delimiter(args: &str) -> Result<Option<char>, String> {
+    match args.chars().next() {
+        None => Ok(None),
+        Some(ch)
+            if ch.is_alphanumeric()
+                || ch.is_whitespace()
+                || matches!(ch, '\\' | '"' | '|') =>
+        {
+            Err("E146: Regular expressions can't be delimited by letters"
+                .to_owned())
+        }
+        Some(ch) => Ok(Some(ch)),
+    }
+}
+
+/// Splits `input` at the first `delimiter` not escaped with a backslash,
+/// returning the text before it, with `\{delimiter}` unescaped, and the
+/// text after it. Without a closing delimiter the whole input is taken.
+fn split_delimited(input: &str, delimiter: char) -> (String, &str) {
+    let mut text = String::new();
+    let mut chars = input.char_indices();
+    while let Some((index, ch)) = chars.next() {
+        if ch == delimiter {
+            return (text, &input[index + ch.len_utf8()..]);
+        }
+        if ch == '\\' {
+            match chars.next() {
+                Some((_, escaped)) if escaped == delimiter => {
+                    text.push(escaped);
+                }
+                Some((_, escaped)) => {
+                    text.push('\\');
+                    text.push(escaped);
+                }
+                None => text.push('\\'),
+            }
+        } else {
+            text.push(ch);
+        }
+    }
+    (text, "")
+}
+
+/// Parses `/pattern/replacement/flags`; no arguments repeat the last
+/// substitution.
+fn parse_substitute(args: &str) -> Result<Option<ExSubstitute>, String> {
+    let Some(delimiter) = pattern_delimiter(args)? else {
+        return Ok(None);
+    };
+    let (pattern, rest) =
+        split_delimited(&args[delimiter.len_utf8()..], delimiter);
+    let (replacement, flags) = split_delimited(rest, delimiter);
+
+    let mut substitute = ExSubstitute {
+        pattern,
+        replacement: parse_replacement(&replacement),
+        global: false,
+        case_sensitive: None,
+    };
+    let flags = flags.trim_end();
+    for flag in flags.chars() {
+        match flag {
+            'g' => substitute.global = true,
+            'i' => substitute.case_sensitive = Some(false),
+            'I' => substitute.case_sensitive = Some(true),
+            _ => return Err(format!("E488: Trailing characters: {flags}")),
+        }
+    }
+    Ok(Some(substitute))
+}
+
+/// Parses a replacement: `&` and `\0` are the match, `\1` to `\9` its
+/// groups, `\r` a line break, `\t` a tab, and a backslash makes any other
+/// character literal.
+fn parse_replacement(replacement: &str) -> Vec<ExReplacement> {
+    let mut parts = Vec::new();
+    let mut text = String::new();
+    let mut chars = replacement.chars();
+    while let Some(ch) = chars.next() {
+        match ch {
+            '&' => {
+                if !text.is_empty() {
+                    parts.push(ExReplacement::Text(std::mem::take(&mut text)));
+                }
+                parts.push(ExReplacement::Match);
+            }
+            '\\' => match chars.next() {
+                Some(digit @ '0'..='9') => {
+                    if !text.is_empty() {
+                        parts.push(ExReplacement::Text(std::mem::take(
+                            &mut text,
+                        )));
+                    }
+                    parts.push(match digit.to_digit(10) {
+                        Some(group @ 1..) => {
+                            ExReplacement::Group(group as usize)
+                        }
+                        _ => ExReplacement::Match,
+                    });
+                }
+                Some('r' | 'n') => text.push('\n'),
+                Some('t') => text.push('\t'),
+                Some(escaped) => text.push(escaped),
+                None => text.push('\\'),
+            },
+            ch => text.push(ch),
+        }
+    }
+    if !text.is_empty() {
+        parts.push(ExReplacement::Text(text));
+    }
+    parts
+}
+
+/// Parses `/pattern/command` for `:g`, where the command defaults to `:p`.
+fn parse_global(args: &str) -> Result<(String, ExCommand), String> {
+    let Some(delimiter) = pattern_delimiter(args)? else {
+        return Err("E476: Invalid command".to_owned());
+    };
+    let (pattern, rest) =
+        split_delimited(&args[delimiter.len_utf8()..], delimiter);
+    let command = if rest.trim().is_empty() {
+        ExCommand { range: Vec::new(), kind: ExCommandKind::Print }
+    } else {
+        parse_ex_command(rest)?
+    };
+    if matches!(command.kind, ExCommandKind::Global { .. }) {
+        return Err("E147: Cannot do :global recursive".to_owned());
+    }
+    Ok((pattern, command))
+}
+
+/// Parses one `:set` argument: `wrap`, `nowrap`, `invwrap`, `wrap!`,
+/// `wrap?`, `sw=4` or `sw`.
+fn parse_setting(arg: &str) -> Result<ExSetting, String> {
+    let unknown = || format!("E518: Unknown option: {arg}");
+    let invalid = || format!("E474: Invalid argument: {arg}");
+    let setting = |option: ExOption, change| Ok(ExSetting { option, change });
+
+    if let Some((name, value)) = arg.split_once('=') {
+        let option = ExOption::from_name(name).ok_or_else(unknown)?;
+        // Only `timeoutlen` may be 0: no waiting for longer mappings.
+        let value = value
+            .parse()
+            .ok()
+            .filter(|value| *value > 0 || option == ExOption::TimeoutLen);
+        return match value {
+            Some(value) if !option.is_boolean() => {
+                setting(option, ExOptionChange::Value(value))
+            }
+            _ => Err(invalid()),
+        };
+    }
+    if let Some(name) = arg.strip_suffix('?') {
+        let option = ExOption::from_name(name).ok_or_else(unknown)?;
+        return setting(option, ExOptionChange::Query);
+    }
+    if let Some(name) = arg.strip_suffix('!') {
+        let option = ExOption::from_name(name).ok_or_else(unknown)?;
+        return if option.is_boolean() {
+            setting(option, ExOptionChange::Toggle)
+        } else {
+            Err(invalid())
+        };
+    }
+    if let Some(option) = ExOption::from_name(arg) {
+        let change = if option.is_boolean() {
+            ExOptionChange::Set(true)
+        } else {
+            ExOptionChange::Query
+        };
+        return setting(option, change);
+    }
+    let negated =
+        [("no", ExOptionChange::Set(false)), ("inv", ExOptionChange::Toggle)]
+            .into_iter()
+            .find_map(|(prefix, change)| {
+                let option = ExOption::from_name(arg.strip_prefix(prefix)?)?;
+                option.is_boolean().then_some(ExSetting { option, change })
+            });
+    negated.ok_or_else(unknown)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn address(line: ExLine, offset: isize) -> ExAddress {
+        ExAddress { line, offset }
+    }
+
+    #[allow(clippy::panic)]
+    fn command(input: &str) -> ExCommand {
+        match parse_ex_command(input) {
+            Ok(command) => command,
+            Err(error) => panic!("`{input}` did not parse: {error}"),
+        }
+    }
+
+    #[test]
+    fn test_ex_parses_ranges() {
+        assert_eq!(command("12").range, [address(ExLine::Number(12), 0)]);
+        assert_eq!(
+            command("%d").range,
+            [address(ExLine::Number(1), 0), address(ExLine::Last, 0)]
+        );
+        assert_eq!(
+            command(".,$-1y").range,
+            [address(ExLine::Current, 0), address(ExLine::Last, -1)]
+        );
+        assert_eq!(
+            command("'<,'>s/a/b/").range,
+            [address(ExLine::Mark('<'), 0), address(ExLine::Mark('>'), 0)]
+        );
+        assert_eq!(
+            command(",+2d").range,
+            [address(ExLine::Current, 0), address(ExLine::Current, 2)]
+        );
+        assert_eq!(
+            command("'a+1,5d").range,
+            [address(ExLine::Mark('a'), 1), address(ExLine::Number(5), 0)]
+        );
+        assert_eq!(
+            parse_ex_command("'Zd"),
+            Err("E78: Unknown mark".to_owned())
+        );
+    }
+
+    #[test]
+    fn test_ex_parses_commands_and_abbreviations() {
+        assert_eq!(command("d").kind, command("delete").kind);
+        assert_eq!(
+            command("y a 3").kind,
+            ExCommandKind::Yank { register: Some('a'), count: Some(3) }
+        );
+        assert_eq!(
+            command("m0").kind,
+            ExCommandKind::Move(address(ExLine::Number(0), 0))
+        );
+        assert_eq!(command("t.").kind, command("co .").kind);
+        assert_eq!(command("noh").kind, ExCommandKind::NoHighlight);
+        assert_eq!(command("wq").kind, ExCommandKind::Write { quit: true });
+        assert_eq!(command("q!").kind, ExCommandKind::Quit);
+        assert_eq!(
+            command("reg a b").kind,
+            ExCommandKind::Registers("ab".into())
+        );
+        assert_eq!(
+            parse_ex_command("frobnicate"),
+            Err("E492: Not an editor command: frobnicate".to_owned())
+        );
+        assert_eq!(
+            parse_ex_command("d x y"),
+            Err("E488: Trailing characters: y".to_owned())
+        );
+        assert_eq!(
+            parse_ex_command("m"),
+            Err("E14: Invalid address".to_owned())
+        );
+    }
+
+    #[test]
+    #[allow(clippy::panic)]
+    fn test_ex_parses_substitutions() {
+        let ExCommandKind::Substitute(Some(substitute)) =
+            command(r"s#a\#b#[&]\t\&#gI").kind
+        else {
+            panic!("not a substitution");
+        };
+        assert_eq!(substitute.pattern, "a#b");
+        assert_eq!(
+            substitute.replacement,
+            [
+                ExReplacement::Text("[".into()),
+                ExReplacement::Match,
+                ExReplacement::Text("]\t&".into()),
+            ]
+        );
+        assert!(substitute.global);
+        assert_eq!(substitute.case_sensitive, Some(true));
+        let ExCommandKind::Substitute(Some(substitute)) =
+            command(r"s/\(a\)\\/\1\0\\").kind
+        else {
+            panic!("not a substitution");
+        };
+        assert_eq!(substitute.pattern, r"\(a\)\\");
+        assert_eq!(
+            substitute.replacement,
+            [
+                ExReplacement::Group(1),
+                ExReplacement::Match,
+                ExReplacement::Text("\\".into()),
+            ]
+        );
+
+        assert_eq!(command("s").kind, ExCommandKind::Substitute(None));
+        assert_eq!(
+            parse_ex_command("s/a/b/x"),
+            Err("E488: Trailing characters: x".to_owned())
+        );
+        assert_eq!(
+            parse_ex_command("s a b "),
+            Err("E146: Regular expressions can't be delimited by letters"
+                .to_owned())
+        );
+    }
+
+    #[test]
+    fn test_ex_parses_global_commands() {
+        assert_eq!(
+            command("g!/todo/d").kind,
+            ExCommandKind::Global {
+                pattern: "todo".into(),
+                invert: true,
+                command: Box::new(command("d")),
+            }
+        );
+        assert_eq!(command("v/x/").kind, command("g!/x/p").kind);
+        assert_eq!(
+            parse_ex_command("g/a/g/b/d"),
+            Err("E147: Cannot do :global recursive".to_owned())
+        );
+    }
+
+    #[test]
+    fn test_ex_parses_settings() {
+        let setting = |option, change| ExSetting { option, change };
+        assert_eq!(
+            command("set nowrap nu! invlist sw=2 et? sw").kind,
+            ExCommandKind::Set(vec![
+                setting(ExOption::Wrap, ExOptionChange::Set(false)),
+                setting(ExOption::Number, ExOptionChange::Toggle),
+                setting(ExOption::List, ExOptionChange::Toggle),
+                setting(ExOption::ShiftWidth, ExOptionChange::Value(2)),
+                setting(ExOption::ExpandTab, ExOptionChange::Query),
+                setting(ExOption::ShiftWidth, ExOptionChange::Query),
+            ])
+        );
+        assert_eq!(
+            parse_ex_command("set nosw"),
+            Err("E518: Unknown option: nosw".to_owned())
+        );
+        assert_eq!(
+            parse_ex_command("set wrap=2"),
+            Err("E474: Invalid argument: wrap=2".to_owned())
+        );
+    }
+
+    #[test]
+    fn test_ex_parses_mapping_commands() {
+        let map = |modes: &[VimMapMode], recursive, lhs: &str, rhs: &str| {
+            ExCommandKind::Map {
+                modes: modes.to_vec(),
+                recursive,
+                lhs: lhs.to_owned(),
+                rhs: (!rhs.is_empty()).then(|| rhs.to_owned()),
+            }
+        };
+        assert_eq!(
+            command("ino <silent> jk   <Esc>").kind,
+            map(&[VimMapMode::Insert], false, "jk", "<Esc>")
+        );
+        assert_eq!(
+            command("map Y y$").kind,
+            map(&[VimMapMode::Normal, VimMapMode::Visual], true, "Y", "y$")
+        );
+        assert_eq!(
+            command("map! ;; <Esc>").kind,
+            map(&[VimMapMode::Insert], true, ";;", "<Esc>")
+        );
+        assert_eq!(
+            command("vm").kind,
+            map(&[VimMapMode::Visual], true, "", "")
+        );
+        assert_eq!(
+            command("nun Q").kind,
+            ExCommandKind::Unmap {
+                modes: vec![VimMapMode::Normal],
+                lhs: "Q".to_owned()
+            }
+        );
+        assert_eq!(
+            parse_ex_command("nmap! x y"),
+            Err("E477: No ! allowed".to_owned())
+        );
+
+        assert_eq!(
+            command(r#"let mapleader = "\<Space>""#).kind,
+            ExCommandKind::SetLeader(' ')
+        );
+        assert_eq!(
+            command("let g:mapleader = ''''").kind,
+            ExCommandKind::SetLeader('\'')
+        );
+        assert_eq!(
+            parse_ex_command("let mapleader = ',,'"),
+            Err("E474: Invalid argument: ',,'".to_owned())
+        );
+        assert_eq!(
+            parse_ex_command("let foo = 1"),
+            Err("E121: Undefined variable: foo".to_owned())
+        );
+        assert_eq!(
+            command("set tm=0").kind,
+            ExCommandKind::Set(vec![ExSetting {
+                option: ExOption::TimeoutLen,
+                change: ExOptionChange::Value(0),
+            }])
+        );
+    }
+
+    #[test]
+    fn test_ex_line_edits_follow_lines() {
+        let edit = ExLineEdit { at: 2, removed: 2, inserted: 1 };
+        assert_eq!(edit.map_line(1), Some(1));
+        assert_eq!(edit.map_line(3), None);
+        assert_eq!(edit.map_line(6), Some(5));
+    }
+}
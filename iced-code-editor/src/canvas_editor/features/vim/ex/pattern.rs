@@ -0,0 +1,225 @@
+//! Vim regular expressions, as `:s` and `:g` take them.
+//!
+//! Patterns use Vim's default `magic` syntax, which
+//! [`compile_vim_pattern`] rewrites for the `regex` crate: `\(`, `\)`, `\|`,
+//! `\+`, `\?`, `\=` and `\{n,m}` group, alternate and repeat, while the same
+//! characters without a backslash are literal. `.`, `*`, `[...]`, `^` and
+//! `$` keep their meaning, `\<` and `\>` match at the start and end of a
+//! word, and `\c` or `\C` anywhere makes the pattern ignore or match case.
+
+use regex::{Regex, RegexBuilder};
+
+/// Compiles a Vim pattern.
+///
+/// # Arguments
+///
+/// * `pattern` - The pattern, in Vim's `magic` syntax
+/// * `case_sensitive` - Whether it matches case, unless `\c` or `\C` says
+///   otherwise
+///
+/// # Errors
+///
+/// Returns `E383: Invalid search string: {pattern}` when the pattern is
+/// malformed, such as an unclosed `\(`.
+pub(crate) fn compile_vim_pattern(
+    pattern: &str,
+    case_sensitive: bool,
+) -> Result<Regex, String> {
+    let invalid = || format!("E383: Invalid search string: {pattern}");
+    let chars: Vec<char> = pattern.chars().collect();
+    let mut case_sensitive = case_sensitive;
+    let mut translated = String::new();
+    // Whether the next character starts a branch, where `^` anchors and
+    // `*` is literal.
+    let mut branch_start = true;
+    let mut index = 0;
+    while let Some(&ch) = chars.get(index) {
+        index += 1;
+        let starts_branch = branch_start;
+        branch_start = false;
+        match ch {
+            '\\' => {
+                let Some(&escaped) = chars.get(index) else {
+                    translated.push_str(r"\\");
+                    break;
+                };
+                index += 1;
+                match escaped {
+                    '(' | '|' => {
+                        translated.push(escaped);
+                        branch_start = true;
+                    }
+                    ')' | '+' | '?' => translated.push(escaped),
+                    '=' => translated.push('?'),
+                    '{' => {
+                        let close = chars[index..]
+                            .iter()
+                            .position(|ch| *ch == '}')
+                            .ok_or_else(invalid)?;
+                        let bounds: String = chars[index..index + close]
+                            .iter()
+                            .filter(|ch| **ch != '\\')
+                            .collect();
+                        index += close + 1;
+                        let (lazy, bounds) = match bounds.strip_prefix('-') {
+                            Some(bounds) => (true, bounds),
+                            None => (false, bounds.as_str()),
+                        };
+                        if bounds.is_empty() {
+                            translated.push('*');
+                        } else if bounds.starts_with(',') {
+                            translated.push_str(&format!("{{0{bounds}}}"));
+                        } else {
+                            translated.push_str(&format!("{{{bounds}}}"));
+                        }
+                        if lazy {
+                            translated.push('?');
+                        }
+                    }
+                    '<' => translated.push_str(r"\b{start}"),
+                    '>' => translated.push_str(r"\b{end}"),
+                    'w' | 'W' | 's' | 'S' | 'd' | 'D' | 't' => {
+                        translated.push('\\');
+                        translated.push(escaped);
+                    }
+                    'a' => translated.push_str("[[:alpha:]]"),
+                    'A' => translated.push_str("[^[:alpha:]]"),
+                    'l' => translated.push_str("[[:lower:]]"),
+                    'u' => translated.push_str("[[:upper:]]"),
+                    'x' => translated.push_str("[[:xdigit:]]"),
+                    'h' => translated.push_str("[A-Za-z_]"),
+                    'c' => case_sensitive = false,
+                    'C' => case_sensitive = true,
+                    escaped => push_literal(&mut translated, escaped),
+                }
+            }
+            '^' if starts_branch => translated.push('^'),
+            '$' if index == chars.len()
+                || matches!(
+                    chars.get(index..index + 2),
+                    Some(['\\', ')' | '|'])
+                ) =>
+            {
+                translated.push('$');
+            }
+            '*' if !starts_branch => translated.push('*'),
+            '.' => translated.push('.'),
+            '[' => match collection_end(&chars, index) {
+                Some(end) => {
+                    translated.push('[');
+                    push_collection(&mut translated, &chars[index..end]);
+                    translated.push(']');
+                    index = end + 1;
+                }
+                None => push_literal(&mut translated, '['),
+            },
+            ch => push_literal(&mut translated, ch),
+        }
+    }
+    RegexBuilder::new(&translated)
+        .case_insensitive(!case_sensitive)
+        .build()
+        .map_err(|_| invalid())
+}
+
+fn push_literal(translated: &mut String, ch: char) {
+    let mut buffer = [0; 4];
+    translated.push_str(&regex::escape(ch.encode_utf8(&mut buffer)));
+}
+
+/// Returns the index of the `]` closing a `[` collection whose contents
+/// start at `start`, where a leading `]` (after any `^`) is literal.
+fn collection_end(chars: &[char], start: usize) -> Option<usize> {
+    let mut index = start;
+    if chars.get(index) == Some(&'^') {
+        index += 1;
+    }
+    if chars.get(index) == Some(&']') {
+        index += 1;
+    }
+    while let Some(&ch) = chars.get(index) {
+        match ch {
+            ']' => return Some(index),
+            '\\' => index += 2,
+            '[' if chars.get(index + 1) == Some(&':') => {
+                let close = chars[index..]
+                    .windows(2)
+                    .position(|pair| pair == [':', ']'])?;
+                index += close + 2;
+            }
+            _ => index += 1,
+        }
+    }
+    None
+}
+
+/// Copies the contents of a `[` collection, escaping what the `regex`
+/// crate would read as a nested class or set operation.
+fn push_collection(translated: &mut String, contents: &[char]) {
+    let mut index = 0;
+    while let Some(&ch) = contents.get(index) {
+        index += 1;
+        match ch {
+            '^' if index == 1 => translated.push('^'),
+            '[' if contents.get(index) == Some(&':') => {
+                translated.push('[');
+                while let Some(&class) = contents.get(index) {
+                    index += 1;
+                    translated.push(class);
+                    if class == ']' {
+                        break;
+                    }
+                }
+            }
+            '\\' => match contents.get(index) {
+                Some(&escaped @ ('t' | 'n' | 'r' | ']' | '\\' | '-' | '^')) => {
+                    index += 1;
+                    translated.push('\\');
+                    translated.push(escaped);
+                }
+                _ => translated.push_str(r"\\"),
+            },
+            '[' | '&' | '~' | ']' => {
+                translated.push('\\');
+                translated.push(ch);
+            }
+            ch => translated.push(ch),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn finds(pattern: &str, text: &str) -> Option<String> {
+        compile_vim_pattern(pattern, true)
+            .ok()?
+            .find(text)
+            .map(|found| found.as_str().to_owned())
+    }
+
+    #[test]
+    fn test_vim_patterns_use_magic_syntax() {
+        assert_eq!(finds(r"\(ab\)\+", "xababy"), Some("abab".into()));
+        assert_eq!(finds("(a+)", "a (a+) b"), Some("(a+)".into()));
+        assert_eq!(finds(r"a\|b", "cb"), Some("b".into()));
+        assert_eq!(finds(r"x\{2,}", "xxxy"), Some("xxx".into()));
+        assert_eq!(finds(r"x\{-1,}", "xxxy"), Some("x".into()));
+        assert_eq!(finds(r"\<foo\>", "foobar foo"), Some("foo".into()));
+        assert_eq!(finds(r"\w\+", "  bar-"), Some("bar".into()));
+        assert_eq!(finds("[]a]*", "]a]b"), Some("]a]".into()));
+        assert_eq!(finds("[[:digit:]x]", "ab7"), Some("7".into()));
+        assert_eq!(finds(r"\cFOO", "foo"), Some("foo".into()));
+        // `*` starting a pattern, and `^` and `$` inside it, are literal.
+        assert_eq!(finds("*a", "b*a"), Some("*a".into()));
+        assert_eq!(finds("a^b$c", "a^b$c"), Some("a^b$c".into()));
+        assert_eq!(finds("^a.c$", "abc"), Some("abc".into()));
+        assert_eq!(finds("^a", "ba"), None);
+
+        assert_eq!(
+            compile_vim_pattern(r"\(a", true).err(),
+            Some(r"E383: Invalid search string: \(a".to_owned())
+        );
+    }
+}
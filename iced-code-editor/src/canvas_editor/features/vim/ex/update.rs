@@ -0,0 +1,925 @@
+//! Execution of parsed Ex commands: resolving their ranges and applying
+//! `:s`, `:g`, `:d`, `:y`, `:m`, `:t`, `:set` and the mapping commands to
+//! the editor.
+
+use std::collections::VecDeque;
+use std::time::Duration;
+
+use iced::Task;
+
+use super::pattern::compile_vim_pattern;
+use super::{
+    ExAddress, ExCommand, ExCommandKind, ExLine, ExLineEdit, ExOption,
+    ExOptionChange, ExReplacement, ExSetting, ExSubstitute, invalid_range,
+    parse_ex_command,
+};
+use crate::canvas_editor::editing::command::{Command, ReplaceLinesCommand};
+use crate::canvas_editor::features::vim::VimOperator;
+use crate::canvas_editor::{CodeEditor, IndentStyle, Message, TAB_WIDTH};
+
+/// Returns the column of the first non-blank character of `line`.
+fn first_non_blank(line: &str) -> usize {
+    line.chars().position(|ch| !ch.is_whitespace()).unwrap_or(0)
+}
+
+impl CodeEditor {
+    /// Runs a command submitted on the `:` command line. An error, like a
+    /// failing motion, aborts a macro replay and is shown on the status
+    /// line.
+    ///
+    /// # Arguments
+    ///
+    /// * `input` - The command line, without its leading `:`
+    ///
+    /// # Returns
+    ///
+    /// A `Task<Message>` produced by the command, such as a save request for
+    /// `:w`
+    pub(crate) fn handle_vim_ex_command(
+        &mut self,
+        input: &str,
+    ) -> Task<Message> {
+        let result = parse_ex_command(input)
+            .and_then(|command| self.run_ex_command(&command, &mut Vec::new()));
+        result.unwrap_or_else(|error| {
+            self.vim_state.set_message(error);
+            self.vim_state.fail_motion();
+            self.overlay_cache.clear();
+            Task::none()
+        })
+    }
+
+    /// Runs one line of a vimrc, which may only hold mapping commands,
+    /// `:let mapleader` and `:set`.
+    ///
+    /// # Arguments
+    ///
+    /// * `line` - The line, with or without a leading `:`
+    ///
+    /// # Errors
+    ///
+    /// Returns the Vim-style error message for the line.
+    pub(crate) fn run_vimrc_line(&mut self, line: &str) -> Result<(), String> {
+        let command = parse_ex_command(line)?;
+        let allowed = matches!(
+            command.kind,
+            ExCommandKind::Map { .. }
+                | ExCommandKind::Unmap { .. }
+                | ExCommandKind::SetLeader(_)
+                | ExCommandKind::Set(_)
+        );
+        if !allowed {
+            return Err(format!("Not allowed in a vimrc: {}", line.trim()));
+        }
+        self.run_ex_command(&command, &mut Vec::new()).map(|_| ())
+    }
+
+    /// Opens the command line from Visual mode: marks the selection as
+    /// `'<` and `'>`, which the prefilled `'<,'>` range addresses, and
+    /// returns to Normal mode.
+    pub(crate) fn handle_vim_visual_command_line(&mut self) -> Task<Message> {
+        let Some((anchor, active)) = self.vim_state.visual_positions() else {
+            return Task::none();
+        };
+        self.set_mark('<', anchor.min(active));
+        self.set_mark('>', anchor.max(active));
+        self.vim_state.leave_visual();
+        self.cursors.set_single(self.vim_normal_position(active));
+        self.finish_navigation_operation();
+        Task::none()
+    }
+
+    /// Runs one parsed command, appending the lines it inserted and removed
+    /// to `edits`.
+    fn run_ex_command(
+        &mut self,
+        command: &ExCommand,
+        edits: &mut Vec<ExLineEdit>,
+    ) -> Result<Task<Message>, String> {
+        let range = command.range.as_slice();
+        if !range.is_empty()
+            && matches!(
+                command.kind,
+                ExCommandKind::NoHighlight
+                    | ExCommandKind::Set(_)
+                    | ExCommandKind::Quit
+                    | ExCommandKind::Registers(_)
+                    | ExCommandKind::Map { .. }
+                    | ExCommandKind::Unmap { .. }
+                    | ExCommandKind::SetLeader(_)
+            )
+        {
+            return Err("E481: No range allowed".to_owned());
+        }
+
+        match &command.kind {
+            ExCommandKind::Goto => {
+                // Unlike other commands, a jump past the end stops on the
+                // last line.
+                let line = match range.last() {
+                    Some(address) => {
+                        self.resolve_ex_line(*address)?
+                            .clamp(1, self.buffer.line_count())
+                            - 1
+                    }
+                    None => self.cursors.primary_position().0,
+                };
+                let col = first_non_blank(self.buffer.line(line));
+                Ok(self.handle_goto_position(line, col))
+            }
+            ExCommandKind::Print => {
+                let (_, line) = self.resolve_ex_range(range, false)?;
+                let text = self.buffer.line(line).to_owned();
+                self.cursors.set_single((line, first_non_blank(&text)));
+                self.vim_state.set_message(text);
+                self.finish_navigation_operation();
+                Ok(self.scroll_to_cursor())
+            }
+            ExCommandKind::Substitute(substitute) => {
+                let lines = self.resolve_ex_range(range, false)?;
+                let substitute = match substitute {
+                    Some(substitute) => {
+                        self.vim_state.set_last_substitute(substitute.clone());
+                        substitute.clone()
+                    }
+                    None => {
+                        self.vim_state.last_substitute().cloned().ok_or_else(
+                            || "E35: No previous regular expression".to_owned(),
+                        )?
+                    }
+                };
+                self.run_ex_substitute(lines, &substitute, edits)
+            }
+            ExCommandKind::Global { pattern, invert, command } => {
+                self.run_ex_global(range, pattern, *invert, command)
+            }
+            ExCommandKind::Delete { register, count } => {
+                let (start, end) = self.resolve_ex_count(range, *count)?;
+                edits.push(ExLineEdit {
+                    at: start,
+                    removed: end - start + 1,
+                    inserted: 0,
+                });
+                Ok(self.run_ex_line_operator(
+                    VimOperator::Delete,
+                    (start, end),
+                    *register,
+                ))
+            }
+            ExCommandKind::Yank { register, count } => {
+                let lines = self.resolve_ex_count(range, *count)?;
+                Ok(self.run_ex_line_operator(
+                    VimOperator::Yank,
+                    lines,
+                    *register,
+                ))
+            }
+            ExCommandKind::Move(target) => {
+                let lines = self.resolve_ex_range(range, false)?;
+                let target = self.resolve_ex_address(*target)?;
+                self.run_ex_move(lines, target, edits)
+            }
+            ExCommandKind::Copy(target) => {
+                let lines = self.resolve_ex_range(range, false)?;
+                let target = self.resolve_ex_address(*target)?;
+                Ok(self.run_ex_copy(lines, target, edits))
+            }
+            ExCommandKind::NoHighlight => {
+                self.vim_state.hide_search_highlight();
+                self.overlay_cache.clear();
+                Ok(Task::none())
+            }
+            ExCommandKind::Set(settings) => {
+                self.run_ex_set(settings)?;
+                self.overlay_cache.clear();
+                Ok(Task::none())
+            }
+            ExCommandKind::Write { quit } => {
+                if *quit {
+                    self.set_vim_enabled(false);
+                }
+                Ok(Task::done(Message::WriteRequested))
+            }
+            ExCommandKind::Quit => {
+                self.set_vim_enabled(false);
+                Ok(Task::none())
+            }
+            ExCommandKind::Registers(names) => {
+                self.vim_state.show_registers(names);
+                self.overlay_cache.clear();
+                Ok(Task::none())
+            }
+            ExCommandKind::Map { modes, recursive, lhs, rhs } => {
+                match rhs {
+                    Some(rhs) => {
+                        for mode in modes {
+                            self.vim_mappings.map(*mode, lhs, rhs, *recursive);
+                        }
+                    }
+                    None => {
+                        let listed = self.vim_mappings.list(modes, lhs);
+                        self.vim_state.set_message(listed);
+                        self.overlay_cache.clear();
+                    }
+                }
+                Ok(Task::none())
+            }
+            ExCommandKind::Unmap { modes, lhs } => {
+                let removed = modes
+                    .iter()
+                    .map(|mode| self.vim_mappings.unmap(*mode, lhs))
+                    .fold(false, |any, removed| any | removed);
+                if removed {
+                    Ok(Task::none())
+                } else {
+                    Err("E31: No such mapping".to_owned())
+                }
+            }
+            ExCommandKind::SetLeader(leader) => {
+                self.vim_mappings.set_leader(*leader);
+                Ok(Task::none())
+            }
+        }
+    }
+
+    /// Resolves an address to a 1-based line number, which is `0` for the
+    /// position before the first line.
+    fn resolve_ex_address(&self, address: ExAddress) -> Result<usize, String> {
+        let line = self.resolve_ex_line(address)?;
+        if line <= self.buffer.line_count() {
+            Ok(line)
+        } else {
+            Err(invalid_range())
+        }
+    }
+
+    /// Resolves an address to a 1-based line number that may lie past the
+    /// last line.
+    fn resolve_ex_line(&self, address: ExAddress) -> Result<usize, String> {
+        let base = match address.line {
+            ExLine::Number(line) => line,
+            ExLine::Current => self.cursors.primary_position().0 + 1,
+            ExLine::Last => self.buffer.line_count(),
+            ExLine::Mark(mark) => {
+                self.mark_state
+                    .mark(mark)
+                    .ok_or_else(|| "E20: Mark not set".to_owned())?
+                    .0
+                    + 1
+            }
+        };
+        base.checked_add_signed(address.offset).ok_or_else(invalid_range)
+    }
+
+    /// Resolves a range to 0-based lines `start..=end`, swapping a
+    /// backwards range.
+    ///
+    /// # Arguments
+    ///
+    /// * `range` - The parsed addresses
+    /// * `whole_buffer` - Whether an empty range covers the whole buffer
+    ///   (`:g`) rather than the cursor line
+    fn resolve_ex_range(
+        &self,
+        range: &[ExAddress],
+        whole_buffer: bool,
+    ) -> Result<(usize, usize), String> {
+        let (start, end) = match range {
+            [] if whole_buffer => (1, self.buffer.line_count()),
+            [] => {
+                let line = self.cursors.primary_position().0 + 1;
+                (line, line)
+            }
+            [address] => {
+                let line = self.resolve_ex_address(*address)?;
+                (line, line)
+            }
+            [first, second, ..] => (
+                self.resolve_ex_address(*first)?,
+                self.resolve_ex_address(*second)?,
+            ),
+        };
+        let (start, end) = (start.min(end), start.max(end));
+        Ok((start.saturating_sub(1), end.saturating_sub(1)))
+    }
+
+    /// Resolves the range of `:d` and `:y`, where a count takes that many
+    /// lines from the range's last line.
+    fn resolve_ex_count(
+        &self,
+        range: &[ExAddress],
+        count: Option<usize>,
+    ) -> Result<(usize, usize), String> {
+        let (start, end) = self.resolve_ex_range(range, false)?;
+        Ok(match count {
+            Some(count) => {
+                let last_line = self.buffer.line_count().saturating_sub(1);
+                (end, end.saturating_add(count - 1).min(last_line))
+            }
+            None => (start, end),
+        })
+    }
+
+    /// Returns `pattern`, or the last search pattern when it is empty.
+    fn ex_pattern(&self, pattern: &str) -> Result<String, String> {
+        if pattern.is_empty() {
+            self.vim_state
+                .last_search()
+                .map(str::to_owned)
+                .ok_or_else(|| "E35: No previous regular expression".to_owned())
+        } else {
+            Ok(pattern.to_owned())
+        }
+    }
+
+    /// Replaces `old_len` lines from `start` with `new_lines` as one undo
+    /// step, leaving the cursor on the first non-blank character of
+    /// `cursor_line`.
+    fn replace_ex_lines(
+        &mut self,
+        start: usize,
+        old_len: usize,
+        new_lines: Vec<String>,
+        cursor_line: usize,
+    ) -> Task<Message> {
+        self.end_grouping_if_active();
+        let cursor_col = new_lines
+            .get(cursor_line.saturating_sub(start))
+            .map_or(0, |line| first_non_blank(line));
+        let cursor_before = self.cursors.primary_position();
+        let cursor_after = (cursor_line, cursor_col);
+        let mut command = ReplaceLinesCommand::new(
+            &self.buffer,
+            start,
+            old_len,
+            new_lines,
+            cursor_before,
+            cursor_after,
+        );
+        if command.is_noop() {
+            self.cursors.set_single(cursor_after);
+            self.finish_navigation_operation();
+            return self.scroll_to_cursor();
+        }
+
+        self.pre_edit_line = start;
+        self.pre_edit_last_line = start + old_len - 1;
+        self.capture_lsp_edit_snapshot(&Message::DeleteSelection);
+        let mut cursor = cursor_before;
+        command.execute(&mut self.buffer, &mut cursor);
+        self.history.push(Box::new(command));
+        self.cursors.set_single(cursor);
+        self.finish_edit_operation();
+        self.scroll_to_cursor()
+    }
+
+    /// Runs `:s` over lines `start..=end`, replacing each line's first
+    /// match, or all of them with the `g` flag, in one undo step.
+    fn run_ex_substitute(
+        &mut self,
+        (start, end): (usize, usize),
+        substitute: &ExSubstitute,
+        edits: &mut Vec<ExLineEdit>,
+    ) -> Result<Task<Message>, String> {
+        let pattern = self.ex_pattern(&substitute.pattern)?;
+        let case_sensitive = substitute
+            .case_sensitive
+            .unwrap_or(self.search_state.case_sensitive);
+        let regex = compile_vim_pattern(&pattern, case_sensitive)?;
+        self.vim_state.set_last_search(pattern.clone());
+
+        let mut substitutions = 0;
+        let replaced: Vec<Option<String>> = (start..=end)
+            .map(|line| {
+                let line = self.buffer.line(line);
+                let mut text = String::new();
+                let mut copied = 0;
+                let mut matched = false;
+                for captures in regex.captures_iter(line) {
+                    let Some(found) = captures.get(0) else {
+                        continue;
+                    };
+                    text.push_str(&line[copied..found.start()]);
+                    for part in &substitute.replacement {
+                        text.push_str(match part {
+                            ExReplacement::Text(replacement) => replacement,
+                            ExReplacement::Match => found.as_str(),
+                            ExReplacement::Group(group) => captures
+                                .get(*group)
+                                .map_or("", |group| group.as_str()),
+                        });
+                    }
+                    copied = found.end();
+                    matched = true;
+                    substitutions += 1;
+                    if !substitute.global {
+                        break;
+                    }
+                }
+                matched.then(|| {
+                    text.push_str(&line[copied..]);
+                    text
+                })
+            })
+            .collect();
+        let (Some(first), Some(last)) = (
+            replaced.iter().position(Option::is_some),
+            replaced.iter().rposition(Option::is_some),
+        ) else {
+            return Err(format!("E486: Pattern not found: {pattern}"));
+        };
+        let (first_line, last_line) = (start + first, start + last);
+        let changed_lines = replaced.iter().flatten().count();
+        let new_lines: Vec<String> = replaced[first..=last]
+            .iter()
+            .zip(first_line..)
+            .flat_map(|(text, line)| {
+                text.as_deref()
+                    .unwrap_or(self.buffer.line(line))
+                    .split('\n')
+                    .map(str::to_owned)
+                    .collect::<Vec<_>>()
+            })
+            .collect();
+
+        let old_len = last_line - first_line + 1;
+        let new_len = new_lines.len();
+        if new_len != old_len {
+            edits.push(ExLineEdit {
+                at: first_line,
+                removed: old_len,
+                inserted: new_len,
+            });
+        }
+        let task = self.replace_ex_lines(
+            first_line,
+            old_len,
+            new_lines,
+            first_line + new_len - 1,
+        );
+        if substitutions > 2 {
+            let lines = if changed_lines == 1 { "line" } else { "lines" };
+            self.vim_state.set_message(format!(
+                "{substitutions} substitutions on {changed_lines} {lines}"
+            ));
+        }
+        Ok(task)
+    }
+
+    /// Runs `command` on every line of the range that contains `pattern`,
+    /// or that does not when `invert` is set, as one undo step.
+    ///
+    /// Lines are marked before any runs, and the marks follow the lines
+    /// the command inserts or removes, so `:g/x/d` visits each marked line
+    /// once. A command failing on some lines, like `:s` without a match,
+    /// does not stop the others.
+    fn run_ex_global(
+        &mut self,
+        range: &[ExAddress],
+        pattern: &str,
+        invert: bool,
+        command: &ExCommand,
+    ) -> Result<Task<Message>, String> {
+        let (start, end) = self.resolve_ex_range(range, true)?;
+        let pattern = self.ex_pattern(pattern)?;
+        let regex =
+            compile_vim_pattern(&pattern, self.search_state.case_sensitive)?;
+        let mut pending: VecDeque<usize> = (start..=end)
+            .filter(|line| regex.is_match(self.buffer.line(*line)) != invert)
+            .collect();
+        self.vim_state.set_last_search(pattern.clone());
+        if pending.is_empty() {
+            return Err(if invert {
+                format!("Pattern found in every line: {pattern}")
+            } else {
+                format!("E486: Pattern not found: {pattern}")
+            });
+        }
+
+        let nested = self.vim_state.replaying();
+        if !nested {
+            self.end_grouping_if_active();
+            self.ensure_grouping_started();
+        }
+        self.vim_state.set_replaying(true);
+        let mut tasks = Vec::new();
+        let mut error = None;
+        while let Some(line) = pending.pop_front() {
+            self.cursors.set_single((line, 0));
+            let mut edits = Vec::new();
+            match self.run_ex_command(command, &mut edits) {
+                Ok(task) => tasks.push(task),
+                Err(message) => {
+                    error.get_or_insert(message);
+                }
+            }
+            if !edits.is_empty() {
+                pending = pending
+                    .into_iter()
+                    .filter_map(|line| {
+                        edits
+                            .iter()
+                            .try_fold(line, |line, edit| edit.map_line(line))
+                    })
+                    .collect();
+            }
+        }
+        self.vim_state.set_replaying(nested);
+        self.end_grouping_if_active();
+
+        match error {
+            Some(error) if tasks.is_empty() => Err(error),
+            _ => Ok(Task::batch(tasks)),
+        }
+    }
+
+    /// Deletes or yanks lines `start..=end` into `register`, as `dd` and
+    /// `yy` do.
+    fn run_ex_line_operator(
+        &mut self,
+        operator: VimOperator,
+        (start, end): (usize, usize),
+        register: Option<char>,
+    ) -> Task<Message> {
+        self.vim_state.select_register(register);
+        let task = self.handle_vim_line_operator(operator, start, end, false);
+        self.vim_state.select_register(None);
+        task
+    }
+
+    /// Moves lines `start..=end` below the 1-based line `target`.
+    fn run_ex_move(
+        &mut self,
+        (start, end): (usize, usize),
+        target: usize,
+        edits: &mut Vec<ExLineEdit>,
+    ) -> Result<Task<Message>, String> {
+        if target > start && target <= end {
+            return Err(
+                "E134: Cannot move a range of lines into itself".to_owned()
+            );
+        }
+        let count = end - start + 1;
+        let moved = self.ex_lines(start..end + 1);
+        let (span_start, old_len, new_lines, cursor_line, inserted_at) =
+            if target > end {
+                let mut new_lines = self.ex_lines(end + 1..target);
+                new_lines.extend(moved);
+                (start, target - start, new_lines, target - 1, target - count)
+            } else {
+                let mut new_lines = moved;
+                new_lines.extend(self.ex_lines(target..start));
+                (
+                    target,
+                    end + 1 - target,
+                    new_lines,
+                    target + count - 1,
+                    target,
+                )
+            };
+        edits.push(ExLineEdit { at: start, removed: count, inserted: 0 });
+        edits.push(ExLineEdit { at: inserted_at, removed: 0, inserted: count });
+        Ok(self.replace_ex_lines(span_start, old_len, new_lines, cursor_line))
+    }
+
+    fn ex_lines(&self, lines: std::ops::Range<usize>) -> Vec<String> {
+        lines.map(|line| self.buffer.line(line).to_owned()).collect()
+    }
+
+    /// Copies lines `start..=end` below the 1-based line `target`.
+    fn run_ex_copy(
+        &mut self,
+        (start, end): (usize, usize),
+        target: usize,
+        edits: &mut Vec<ExLineEdit>,
+    ) -> Task<Message> {
+        let copies = self.ex_lines(start..end + 1);
+        let count = copies.len();
+        edits.push(ExLineEdit { at: target, removed: 0, inserted: count });
+        // The replaced line is the one the copies go next to, as the
+        // command replaces at least one line.
+        if target == 0 {
+            let mut new_lines = copies;
+            new_lines.push(self.buffer.line(0).to_owned());
+            self.replace_ex_lines(0, 1, new_lines, count - 1)
+        } else {
+            let mut new_lines = vec![self.buffer.line(target - 1).to_owned()];
+            new_lines.extend(copies);
+            self.replace_ex_lines(target - 1, 1, new_lines, target + count - 1)
+        }
+    }
+
+    /// Applies `:set` arguments in order, showing the queried values; with
+    /// no arguments every option is shown.
+    fn run_ex_set(&mut self, settings: &[ExSetting]) -> Result<(), String> {
+        let mut shown: Vec<String> = Vec::new();
+        if settings.is_empty() {
+            shown.extend(
+                ExOption::ALL.iter().map(|option| self.ex_option_text(*option)),
+            );
+        }
+        for setting in settings {
+            match setting.change {
+                ExOptionChange::Set(enabled) => {
+                    self.set_ex_option(setting.option, enabled);
+                }
+                ExOptionChange::Toggle => {
+                    let enabled = !self.ex_option_enabled(setting.option);
+                    self.set_ex_option(setting.option, enabled);
+                }
+                ExOptionChange::Value(value) => {
+                    self.set_ex_value(setting.option, value)?
+                }
+                ExOptionChange::Query => {
+                    shown.push(self.ex_option_text(setting.option));
+                }
+            }
+        }
+        if !shown.is_empty() {
+            self.vim_state.set_message(shown.join("  "));
+        }
+        Ok(())
+    }
+
+    fn ex_option_enabled(&self, option: ExOption) -> bool {
+        match option {
+            ExOption::Wrap => self.wrap_enabled(),
+            ExOption::Number => self.line_numbers_enabled(),
+            ExOption::ExpandTab => {
+                matches!(self.indent_style(), IndentStyle::Spaces(_))
+            }
+            ExOption::AutoIndent => self.auto_indent_enabled(),
+            ExOption::List => self.show_whitespace(),
+            ExOption::ShiftWidth | ExOption::TimeoutLen => true,
+        }
+    }
+
+    /// Formats an option as `:set` shows it: `wrap`, `nowrap` or
+    /// `shiftwidth=4`.
+    fn ex_option_text(&self, option: ExOption) -> String {
+        if option.is_boolean() {
+            let prefix = if self.ex_option_enabled(option) { "" } else { "no" };
+            return format!("{prefix}{}", option.name());
+        }
+        let value = match (option, self.indent_style()) {
+            (ExOption::TimeoutLen, _) => {
+                self.vim_mappings.timeout().as_millis().to_string()
+            }
+            (_, IndentStyle::Spaces(width)) => width.to_string(),
+            (_, IndentStyle::Tab) => TAB_WIDTH.to_string(),
+        };
+        format!("{}={value}", option.name())
+    }
+
+    fn set_ex_option(&mut self, option: ExOption, enabled: bool) {
+        match option {
+            ExOption::Wrap => self.set_wrap_enabled(enabled),
+            ExOption::Number => self.set_line_numbers_enabled(enabled),
+            ExOption::ExpandTab => {
+                if enabled != self.ex_option_enabled(option) {
+                    self.set_indent_style(if enabled {
+                        IndentStyle::Spaces(TAB_WIDTH as u8)
+                    } else {
+                        IndentStyle::Tab
+                    });
+                }
+            }
+            ExOption::AutoIndent => self.set_auto_indent_enabled(enabled),
+            ExOption::List => self.set_show_whitespace(enabled),
+            ExOption::ShiftWidth | ExOption::TimeoutLen => {}
+        }
+    }
+
+    /// Sets a number option: `:set tm=N`, or the indent width for
+    /// `:set sw=N`. Tab indentation is always [`TAB_WIDTH`] wide, so
+    /// another width needs `expandtab`.
+    fn set_ex_value(
+        &mut self,
+        option: ExOption,
+        width: usize,
+    ) -> Result<(), String> {
+        if option == ExOption::TimeoutLen {
+            let millis = u64::try_from(width).unwrap_or(u64::MAX);
+            self.vim_mappings.set_timeout(Duration::from_millis(millis));
+            return Ok(());
+        }
+        let invalid = || format!("E474: Invalid argument: shiftwidth={width}");
+        match self.indent_style() {
+            IndentStyle::Spaces(_) => {
+                let width = u8::try_from(width).map_err(|_| invalid())?;
+                self.set_indent_style(IndentStyle::Spaces(width));
+                Ok(())
+            }
+            IndentStyle::Tab if width == TAB_WIDTH => Ok(()),
+            IndentStyle::Tab => Err(invalid()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::canvas_editor::VimMode;
+    use crate::canvas_editor::features::vim::test_support::vim_keys;
+
+    fn ex(editor: &mut CodeEditor, command: &str) {
+        vim_keys(editor, &format!(":{command}\n"));
+    }
+
+    fn status(editor: &CodeEditor) -> String {
+        editor.vim_state.status_line_text().0
+    }
+
+    #[test]
+    fn test_ex_substitute_over_ranges_with_flags() {
+        let mut editor =
+            CodeEditor::new("a a\nb a\nA a", "txt").with_vim_enabled(true);
+        ex(&mut editor, "s/a/x/");
+        assert_eq!(editor.content(), "x a\nb a\nA a");
+
+        ex(&mut editor, "2,$s/a/[&]/gI");
+        assert_eq!(editor.content(), "x a\nb [a]\nA [a]");
+        assert_eq!(editor.cursors.primary_position(), (2, 0));
+
+        ex(&mut editor, "%s/A/y\\r/");
+        // The search dialog's case sensitivity applies without `I` or `i`.
+        assert_eq!(editor.content(), "x y\n\nb [y\n]\ny\n [a]");
+        assert_eq!(status(&editor), "3 substitutions on 3 lines");
+
+        vim_keys(&mut editor, "u");
+        assert_eq!(editor.content(), "x a\nb [a]\nA [a]");
+    }
+
+    #[test]
+    fn test_ex_substitute_matches_vim_regular_expressions() {
+        let mut editor = CodeEditor::new("foo bar\nbaz.qux\n  x", "txt")
+            .with_vim_enabled(true);
+        ex(&mut editor, r"1s/\(\w\+\) \(\w\+\)/\2 \1/");
+        assert_eq!(editor.content(), "bar foo\nbaz.qux\n  x");
+
+        ex(&mut editor, r"2s/\./-/");
+        assert_eq!(editor.content(), "bar foo\nbaz-qux\n  x");
+        ex(&mut editor, "%s/^/# /");
+        assert_eq!(editor.content(), "# bar foo\n# baz-qux\n#   x");
+        ex(&mut editor, r"%s/\<\(ba\)\a*/<\0|\1>/g");
+        assert_eq!(editor.content(), "# <bar|ba> foo\n# <baz|ba>-qux\n#   x");
+        ex(&mut editor, r"%s/x$/y/");
+        assert_eq!(editor.content(), "# <bar|ba> foo\n# <baz|ba>-quy\n#   y");
+
+        ex(&mut editor, r"s/\(/x/");
+        assert_eq!(status(&editor), r"E383: Invalid search string: \(");
+    }
+
+    #[test]
+    fn test_ex_substitute_reuses_the_last_search_and_substitution() {
+        let mut editor =
+            CodeEditor::new("one two\ntwo one", "txt").with_vim_enabled(true);
+        vim_keys(&mut editor, "/two\n");
+        ex(&mut editor, "%s//2/");
+        assert_eq!(editor.content(), "one 2\n2 one");
+
+        ex(&mut editor, "s/one/1/");
+        ex(&mut editor, "1s");
+        assert_eq!(editor.content(), "1 2\n2 1");
+
+        ex(&mut editor, "%s/three/3/");
+        assert_eq!(status(&editor), "E486: Pattern not found: three");
+        assert_eq!(editor.content(), "1 2\n2 1");
+    }
+
+    #[test]
+    fn test_ex_visual_range_addresses_the_selection() {
+        let mut editor =
+            CodeEditor::new("a\nb\nc\nd", "txt").with_vim_enabled(true);
+        vim_keys(&mut editor, "jVj:");
+        assert_eq!(status(&editor), ":'<,'>");
+        assert_eq!(editor.vim_state.mode(), VimMode::Normal);
+
+        vim_keys(&mut editor, "d\n");
+        assert_eq!(editor.content(), "a\nd");
+        assert_eq!(editor.vim_state.register.text, "b\nc\n");
+    }
+
+    #[test]
+    fn test_ex_global_runs_a_command_on_matching_lines() {
+        let mut editor =
+            CodeEditor::new("x1\ny\nx2\nx3\nz", "txt").with_vim_enabled(true);
+        ex(&mut editor, "g/x/d");
+        assert_eq!(editor.content(), "y\nz");
+        vim_keys(&mut editor, "u");
+        assert_eq!(editor.content(), "x1\ny\nx2\nx3\nz");
+
+        ex(&mut editor, "v/x/s/$/!/");
+        assert_eq!(editor.content(), "x1\ny!\nx2\nx3\nz!");
+        ex(&mut editor, "v/x/s/y/why/");
+        assert_eq!(editor.content(), "x1\nwhy!\nx2\nx3\nz!");
+
+        ex(&mut editor, "g/^x[23]/m0");
+        assert_eq!(editor.content(), "x3\nx2\nx1\nwhy!\nz!");
+        ex(&mut editor, "g/^/m0");
+        assert_eq!(editor.content(), "z!\nwhy!\nx1\nx2\nx3");
+        ex(&mut editor, "g/^q/d");
+        assert_eq!(status(&editor), "E486: Pattern not found: ^q");
+    }
+
+    #[test]
+    fn test_ex_move_copy_delete_and_yank_lines() {
+        let mut editor =
+            CodeEditor::new("1\n2\n3\n4\n5", "txt").with_vim_enabled(true);
+        ex(&mut editor, "1,2m$");
+        assert_eq!(editor.content(), "3\n4\n5\n1\n2");
+        assert_eq!(editor.cursors.primary_position(), (4, 0));
+
+        ex(&mut editor, "4m0");
+        assert_eq!(editor.content(), "1\n3\n4\n5\n2");
+        ex(&mut editor, "2,4m3");
+        assert_eq!(
+            status(&editor),
+            "E134: Cannot move a range of lines into itself"
+        );
+
+        ex(&mut editor, "$t0");
+        assert_eq!(editor.content(), "2\n1\n3\n4\n5\n2");
+        ex(&mut editor, "2co.");
+        assert_eq!(editor.content(), "2\n1\n1\n3\n4\n5\n2");
+        assert_eq!(editor.cursors.primary_position(), (1, 0));
+
+        ex(&mut editor, "y a 2");
+        assert_eq!(
+            editor
+                .vim_state
+                .register_for_paste(Some('a'))
+                .map(|r| r.text.as_str()),
+            Some("1\n1\n")
+        );
+        ex(&mut editor, "4,$-1d");
+        assert_eq!(editor.content(), "2\n1\n1\n2");
+        ex(&mut editor, "9d");
+        assert_eq!(status(&editor), "E16: Invalid range");
+    }
+
+    #[test]
+    fn test_ex_ranges_resolve_marks_and_offsets() {
+        let mut editor =
+            CodeEditor::new("1\n2\n3\n4\n5", "txt").with_vim_enabled(true);
+        vim_keys(&mut editor, "jmajj");
+        ex(&mut editor, "'a,.-1d");
+        assert_eq!(editor.content(), "1\n4\n5");
+
+        ex(&mut editor, "'b");
+        assert_eq!(status(&editor), "E20: Mark not set");
+        ex(&mut editor, "$");
+        assert_eq!(editor.cursors.primary_position(), (2, 0));
+    }
+
+    #[test]
+    fn test_ex_set_changes_editor_options() {
+        let mut editor = CodeEditor::new("a", "txt").with_vim_enabled(true);
+        editor.set_indent_style(IndentStyle::Spaces(4));
+        ex(&mut editor, "set nowrap nonu list sw=2");
+        assert!(!editor.wrap_enabled());
+        assert!(!editor.line_numbers_enabled());
+        assert!(editor.show_whitespace());
+        assert_eq!(editor.indent_style(), IndentStyle::Spaces(2));
+
+        ex(&mut editor, "se invwrap noet sw?");
+        assert!(editor.wrap_enabled());
+        assert_eq!(editor.indent_style(), IndentStyle::Tab);
+        assert_eq!(status(&editor), format!("shiftwidth={TAB_WIDTH}"));
+
+        ex(&mut editor, "set sw=3");
+        assert_eq!(status(&editor), "E474: Invalid argument: shiftwidth=3");
+        ex(&mut editor, "set foo");
+        assert_eq!(status(&editor), "E518: Unknown option: foo");
+    }
+
+    #[test]
+    fn test_ex_nohlsearch_hides_matches_until_the_next_search() {
+        let mut editor =
+            CodeEditor::new("ab\nab", "txt").with_vim_enabled(true);
+        vim_keys(&mut editor, "/b\n");
+        assert!(editor.search_matches_visible());
+        ex(&mut editor, "noh");
+        assert!(!editor.search_matches_visible());
+        vim_keys(&mut editor, "n");
+        assert!(editor.search_matches_visible());
+    }
+
+    #[test]
+    fn test_ex_errors_and_history() {
+        let mut editor =
+            CodeEditor::new("a\nb\nc", "txt").with_vim_enabled(true);
+        ex(&mut editor, "frob");
+        assert_eq!(status(&editor), "E492: Not an editor command: frob");
+        ex(&mut editor, "3");
+        ex(&mut editor, "1");
+        assert_eq!(editor.cursors.primary_position(), (0, 0));
+
+        vim_keys(&mut editor, ":");
+        let _ = editor.update(&Message::VimCommandHistory(true));
+        let _ = editor.update(&Message::VimCommandHistory(true));
+        assert_eq!(status(&editor), ":3");
+        vim_keys(&mut editor, "\n");
+        assert_eq!(editor.cursors.primary_position(), (2, 0));
+    }
+}
@@ -0,0 +1,735 @@
+//! User-defined Vim key mappings (`:nnoremap`, `:inoremap`, `:vnoremap` and
+//! their recursive forms).
+//!
+//! Typed keys wait in a typeahead queue while they could still start a
+//! longer mapping; a key that rules that out, or the mapping timeout,
+//! resolves them to the longest matching mapping, or to the keys
+//! themselves. A mapping's right-hand side is pushed back onto the queue:
+//! a recursive mapping's keys are mapped again, a non-recursive one's are
+//! run as typed. Resolved keys re-enter [`CodeEditor::update`] as the
+//! messages the keyboard would have sent, so macros and `.` record the
+//! mapped keys and replay them without mapping them again.
+//!
+//! Keys use Vim's notation: `<Esc>`, `<CR>`, `<BS>`, `<Tab>`, `<Space>`,
+//! `<lt>`, `<Bar>`, `<Bslash>`, `<C-x>` and `<Leader>`, which stands for the
+//! leader key at the time the mapping is defined.
+
+use std::collections::{BTreeMap, VecDeque};
+use std::time::{Duration, Instant};
+
+use iced::Task;
+
+use super::VimMode;
+use crate::canvas_editor::{CodeEditor, Message};
+
+/// How long typed keys wait for the rest of a mapping, like Vim's
+/// `timeoutlen`.
+const DEFAULT_MAPPING_TIMEOUT: Duration = Duration::from_millis(1000);
+
+/// How many mappings one typed key may expand through before the expansion
+/// is abandoned as recursive, like Vim's `maxmapdepth`.
+const MAX_MAPPING_DEPTH: usize = 1000;
+
+/// The leader key `<Leader>` stands for until another is set.
+const DEFAULT_LEADER: char = '\\';
+
+/// The modes a Vim key mapping applies in.
+///
+/// # Example
+///
+/// ```
+/// use iced_code_editor::{CodeEditor, Message, VimMapMode, VimMode};
+///
+/// let mut editor = CodeEditor::new("abc", "txt").with_vim_enabled(true);
+/// editor.vim_noremap(VimMapMode::Visual, "<Space>", "d");
+///
+/// for key in "vl ".chars() {
+///     let _ = editor.update(&Message::VimKey(key));
+/// }
+/// assert_eq!(editor.content(), "c");
+/// assert_eq!(editor.vim_mode(), Some(VimMode::Normal));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum VimMapMode {
+    /// Normal mode, without an operator pending (`:nmap`, `:nnoremap`).
+    Normal,
+    /// Visual, Visual Line and Visual Block modes (`:vmap`, `:vnoremap`).
+    Visual,
+    /// Insert and Replace modes (`:imap`, `:inoremap`).
+    Insert,
+}
+
+impl VimMapMode {
+    /// The letter `:map` listings show for the mode.
+    fn letter(self) -> char {
+        match self {
+            Self::Normal => 'n',
+            Self::Visual => 'v',
+            Self::Insert => 'i',
+        }
+    }
+}
+
+/// The right-hand side of a mapping.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct VimMapping {
+    rhs: Vec<char>,
+    /// Whether the keys of `rhs` are mapped again (`:nmap`), rather than
+    /// run as typed (`:nnoremap`).
+    recursive: bool,
+}
+
+/// What the keys at the front of the typeahead resolve to.
+enum Resolution {
+    /// They may still start a longer mapping.
+    Wait,
+    /// The first `len` keys are a mapping's left-hand side.
+    Map { len: usize, mapping: VimMapping },
+    /// The first key is not mapped.
+    Key,
+}
+
+/// Per-editor key mappings and the typed keys waiting to be resolved.
+#[derive(Debug)]
+pub(crate) struct VimMappings {
+    mappings: BTreeMap<(VimMapMode, Vec<char>), VimMapping>,
+    leader: char,
+    timeout: Duration,
+    /// Keys waiting to be resolved, each with whether it may be mapped.
+    typeahead: VecDeque<(char, bool)>,
+    /// When the typeahead started waiting for more keys.
+    waiting_since: Option<Instant>,
+    /// Set while resolved keys re-enter the editor, so they skip mapping.
+    emitting: bool,
+}
+
+impl Default for VimMappings {
+    fn default() -> Self {
+        Self {
+            mappings: BTreeMap::new(),
+            leader: DEFAULT_LEADER,
+            timeout: DEFAULT_MAPPING_TIMEOUT,
+            typeahead: VecDeque::new(),
+            waiting_since: None,
+            emitting: false,
+        }
+    }
+}
+
+impl VimMappings {
+    /// Defines a mapping, replacing any with the same left-hand side. An
+    /// empty left-hand side is ignored.
+    pub(crate) fn map(
+        &mut self,
+        mode: VimMapMode,
+        lhs: &str,
+        rhs: &str,
+        recursive: bool,
+    ) {
+        let lhs = parse_vim_keys(lhs, self.leader);
+        if lhs.is_empty() {
+            return;
+        }
+        let rhs = parse_vim_keys(rhs, self.leader);
+        self.mappings.insert((mode, lhs), VimMapping { rhs, recursive });
+    }
+
+    /// Removes a mapping, returning whether there was one.
+    pub(crate) fn unmap(&mut self, mode: VimMapMode, lhs: &str) -> bool {
+        let lhs = parse_vim_keys(lhs, self.leader);
+        self.mappings.remove(&(mode, lhs)).is_some()
+    }
+
+    pub(crate) fn set_leader(&mut self, leader: char) {
+        self.leader = leader;
+    }
+
+    pub(crate) fn leader(&self) -> char {
+        self.leader
+    }
+
+    pub(crate) fn set_timeout(&mut self, timeout: Duration) {
+        self.timeout = timeout;
+    }
+
+    pub(crate) fn timeout(&self) -> Duration {
+        self.timeout
+    }
+
+    /// Forgets the keys waiting to be resolved.
+    pub(crate) fn clear_typeahead(&mut self) {
+        self.typeahead.clear();
+        self.waiting_since = None;
+    }
+
+    /// Lists the mappings of `modes` whose left-hand side starts with
+    /// `lhs`, as `:map` shows them: mode, keys, and `*` before the
+    /// right-hand side of a non-recursive mapping.
+    pub(crate) fn list(&self, modes: &[VimMapMode], lhs: &str) -> String {
+        let lhs = parse_vim_keys(lhs, self.leader);
+        let listed: Vec<String> = self
+            .mappings
+            .iter()
+            .filter(|((mode, keys), _)| {
+                modes.contains(mode) && keys.starts_with(&lhs)
+            })
+            .map(|((mode, keys), mapping)| {
+                format!(
+                    "{} {} {}{}",
+                    mode.letter(),
+                    format_vim_keys(keys),
+                    if mapping.recursive { "" } else { "*" },
+                    format_vim_keys(&mapping.rhs),
+                )
+            })
+            .collect();
+        if listed.is_empty() {
+            "No mapping found".to_owned()
+        } else {
+            listed.join("  ")
+        }
+    }
+
+    /// Returns the typed keys waiting for the rest of a mapping, for the
+    /// status line.
+    pub(crate) fn pending_keys(&self) -> String {
+        let keys: Vec<char> =
+            self.typeahead.iter().map(|(key, _)| *key).collect();
+        format_vim_keys(&keys)
+    }
+
+    /// Resolves the keys at the front of the typeahead against the mappings
+    /// of `mode`. Unless `forced`, keys that could start a longer mapping
+    /// wait for more.
+    fn resolve(&self, mode: VimMapMode, forced: bool) -> Resolution {
+        let keys: Vec<char> = self
+            .typeahead
+            .iter()
+            .take_while(|(_, remap)| *remap)
+            .map(|(key, _)| *key)
+            .collect();
+        let mut best: Option<(usize, &VimMapping)> = None;
+        let mut longer = false;
+        for ((_, lhs), mapping) in
+            self.mappings.iter().filter(|((map_mode, _), _)| *map_mode == mode)
+        {
+            if keys.starts_with(lhs) {
+                if best.is_none_or(|(len, _)| lhs.len() > len) {
+                    best = Some((lhs.len(), mapping));
+                }
+            } else if lhs.starts_with(&keys) {
+                longer = true;
+            }
+        }
+        // Only keys still to be typed can complete a longer mapping.
+        if longer && !forced && keys.len() == self.typeahead.len() {
+            return Resolution::Wait;
+        }
+        match best {
+            Some((len, mapping)) => {
+                Resolution::Map { len, mapping: mapping.clone() }
+            }
+            None => Resolution::Key,
+        }
+    }
+}
+
+/// Parses keys written in Vim's notation, such as `<Esc>` or `<C-a>`;
+/// `<` that starts no known key name is taken literally.
+pub(crate) fn parse_vim_keys(notation: &str, leader: char) -> Vec<char> {
+    let mut keys = Vec::new();
+    let mut rest = notation;
+    while let Some(ch) = rest.chars().next() {
+        if ch == '<'
+            && let Some(end) = rest.find('>')
+            && let Some(key) = named_key(&rest[1..end], leader)
+        {
+            keys.push(key);
+            rest = &rest[end + 1..];
+            continue;
+        }
+        keys.push(ch);
+        rest = &rest[ch.len_utf8()..];
+    }
+    keys
+}
+
+/// Returns the key a `<name>` stands for.
+fn named_key(name: &str, leader: char) -> Option<char> {
+    let name = name.to_ascii_lowercase();
+    let key = match name.as_str() {
+        "esc" | "c-[" => '\u{1b}',
+        "cr" | "enter" | "return" => '\r',
+        "bs" => '\u{8}',
+        "tab" => '\t',
+        "space" => ' ',
+        "lt" => '<',
+        "bar" => '|',
+        "bslash" => '\\',
+        "leader" => leader,
+        _ => {
+            let letter = name.strip_prefix("c-")?;
+            let mut chars = letter.chars();
+            let byte = chars
+                .next()
+                .filter(char::is_ascii_lowercase)
+                .and_then(|ch| u8::try_from(ch).ok())?;
+            if chars.next().is_some() {
+                return None;
+            }
+            char::from(byte & 0x1f)
+        }
+    };
+    Some(key)
+}
+
+/// Writes keys in Vim's notation, the inverse of [`parse_vim_keys`].
+pub(crate) fn format_vim_keys(keys: &[char]) -> String {
+    let mut text = String::new();
+    for key in keys {
+        match key {
+            '\u{1b}' => text.push_str("<Esc>"),
+            '\r' | '\n' => text.push_str("<CR>"),
+            '\u{8}' => text.push_str("<BS>"),
+            '\t' => text.push_str("<Tab>"),
+            ' ' => text.push_str("<Space>"),
+            '<' => text.push_str("<lt>"),
+            '\u{1}'..='\u{1a}' => {
+                let letter =
+                    char::from_u32(u32::from(*key) + 0x60).unwrap_or('?');
+                text.push_str(&format!("<C-{letter}>"));
+            }
+            key => text.push(*key),
+        }
+    }
+    text
+}
+
+impl CodeEditor {
+    /// Defines a recursive Vim key mapping (`:nmap`, `:vmap`, `:imap`): the
+    /// keys of `rhs` are themselves mapped.
+    ///
+    /// Both sides use Vim's key notation, such as `<Esc>`, `<CR>`,
+    /// `<Space>`, `<C-a>` or `<Leader>`; `<Leader>` is the leader key set
+    /// when the mapping is defined (see [`CodeEditor::set_vim_leader`]).
+    /// A mapping replaces any earlier one for the same mode and keys.
+    ///
+    /// # Arguments
+    ///
+    /// * `mode` - The modes the mapping applies in
+    /// * `lhs` - The keys to type
+    /// * `rhs` - The keys they stand for
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use iced_code_editor::{CodeEditor, Message, VimMapMode};
+    ///
+    /// let mut editor =
+    ///     CodeEditor::new("one\ntwo", "txt").with_vim_enabled(true);
+    /// editor.vim_noremap(VimMapMode::Normal, "<Space>", "dd");
+    /// // `Q` runs `<Space>`, which is mapped again.
+    /// editor.vim_map(VimMapMode::Normal, "Q", "<Space>");
+    ///
+    /// let _ = editor.update(&Message::VimKey('Q'));
+    /// assert_eq!(editor.content(), "two");
+    /// ```
+    pub fn vim_map(&mut self, mode: VimMapMode, lhs: &str, rhs: &str) {
+        self.vim_mappings.map(mode, lhs, rhs, true);
+    }
+
+    /// Defines a non-recursive Vim key mapping (`:nnoremap`, `:vnoremap`,
+    /// `:inoremap`): the keys of `rhs` run as typed, whatever they are
+    /// mapped to.
+    ///
+    /// # Arguments
+    ///
+    /// * `mode` - The modes the mapping applies in
+    /// * `lhs` - The keys to type, in Vim's key notation
+    /// * `rhs` - The keys they stand for, in Vim's key notation
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use iced_code_editor::{CodeEditor, Message, VimMapMode};
+    ///
+    /// let mut editor = CodeEditor::new("a\nb", "txt").with_vim_enabled(true);
+    /// // Swap `j` and `k`.
+    /// editor.vim_noremap(VimMapMode::Normal, "j", "k");
+    /// editor.vim_noremap(VimMapMode::Normal, "k", "j");
+    ///
+    /// let _ = editor.update(&Message::VimKey('k'));
+    /// assert_eq!(editor.cursor_position(), (1, 0));
+    /// ```
+    pub fn vim_noremap(&mut self, mode: VimMapMode, lhs: &str, rhs: &str) {
+        self.vim_mappings.map(mode, lhs, rhs, false);
+    }
+
+    /// Removes a Vim key mapping (`:nunmap`, `:vunmap`, `:iunmap`).
+    ///
+    /// # Arguments
+    ///
+    /// * `mode` - The mode the mapping applies in
+    /// * `lhs` - Its keys, in Vim's key notation
+    ///
+    /// # Returns
+    ///
+    /// `true` if there was such a mapping
+    pub fn vim_unmap(&mut self, mode: VimMapMode, lhs: &str) -> bool {
+        self.vim_mappings.unmap(mode, lhs)
+    }
+
+    /// Sets the key `<Leader>` stands for in mappings defined afterwards,
+    /// like `:let mapleader`. Defaults to `\`.
+    ///
+    /// # Arguments
+    ///
+    /// * `leader` - The leader key
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use iced_code_editor::{CodeEditor, Message, VimMapMode};
+    ///
+    /// let mut editor = CodeEditor::new("a b", "txt").with_vim_enabled(true);
+    /// editor.set_vim_leader(',');
+    /// editor.vim_noremap(VimMapMode::Normal, "<Leader>d", "dw");
+    ///
+    /// let _ = editor.update(&Message::VimKey(','));
+    /// let _ = editor.update(&Message::VimKey('d'));
+    /// assert_eq!(editor.content(), "b");
+    /// ```
+    pub fn set_vim_leader(&mut self, leader: char) {
+        self.vim_mappings.set_leader(leader);
+    }
+
+    /// Returns the key `<Leader>` stands for, `\\` unless set with
+    /// [`CodeEditor::set_vim_leader`] or `:let mapleader`.
+    pub fn vim_leader(&self) -> char {
+        self.vim_mappings.leader()
+    }
+
+    /// Sets how long typed keys that could start a longer mapping wait for
+    /// the rest of it, like Vim's `timeoutlen`. When it runs out, the keys
+    /// typed so far run as the mapping they match, or as typed. Defaults to
+    /// one second.
+    ///
+    /// The timeout is checked on [`Message::Tick`], so hosts mapping keys
+    /// should keep sending it.
+    ///
+    /// # Arguments
+    ///
+    /// * `timeout` - How long to wait
+    pub fn set_vim_mapping_timeout(&mut self, timeout: Duration) {
+        self.vim_mappings.set_timeout(timeout);
+    }
+
+    /// Runs a vimrc: mapping commands (`map`, `noremap`, `unmap` and their
+    /// `n`, `v` and `i` forms), `let mapleader = "…"` and `set`, one per
+    /// line. Blank lines and comment lines starting with `"` are skipped.
+    ///
+    /// The editor never reads files itself; the host passes the file's
+    /// contents.
+    ///
+    /// # Arguments
+    ///
+    /// * `vimrc` - The vimrc's contents
+    ///
+    /// # Errors
+    ///
+    /// Returns one message per line that failed, such as
+    /// `line 3: E492: Not an editor command: foo`; the other lines still
+    /// take effect.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use iced_code_editor::CodeEditor;
+    ///
+    /// let mut editor = CodeEditor::new("", "txt").with_vim_enabled(true);
+    /// let vimrc = "\" Leave Insert mode\nlet mapleader = \",\"\ninoremap jk <Esc>\nset sw=2";
+    /// assert_eq!(editor.source_vimrc(vimrc), Ok(()));
+    /// ```
+    pub fn source_vimrc(&mut self, vimrc: &str) -> Result<(), Vec<String>> {
+        let errors: Vec<String> = vimrc
+            .lines()
+            .enumerate()
+            .filter_map(|(index, line)| {
+                let line = line.trim();
+                if line.is_empty() || line.starts_with('"') {
+                    return None;
+                }
+                self.run_vimrc_line(line)
+                    .err()
+                    .map(|error| format!("line {}: {error}", index + 1))
+            })
+            .collect();
+        if errors.is_empty() { Ok(()) } else { Err(errors) }
+    }
+
+    /// Returns the key `message` types when it goes through the key
+    /// mappings: Vim keys outside the command line, and text input,
+    /// `Enter`, `Backspace` and `Tab` in Insert mode. Keys resolved from a
+    /// mapping and replayed by macros or `.` are not mapped again.
+    pub(crate) fn vim_mapped_key(&self, message: &Message) -> Option<char> {
+        if !self.vim_enabled
+            || self.vim_mappings.emitting
+            || self.vim_state.replaying()
+            || self.vim_state.command_line_active()
+            || (self.vim_mappings.mappings.is_empty()
+                && self.vim_mappings.typeahead.is_empty())
+        {
+            return None;
+        }
+        if let Message::VimKey(key) = message {
+            return Some(*key);
+        }
+        if self.vim_state.mode() != VimMode::Insert {
+            return None;
+        }
+        match message {
+            Message::CharacterInput(ch) => Some(*ch),
+            Message::Enter => Some('\r'),
+            Message::Backspace => Some('\u{8}'),
+            Message::Tab => Some('\t'),
+            _ => None,
+        }
+    }
+
+    /// Queues a typed key and runs every key that no longer waits for a
+    /// mapping.
+    pub(crate) fn handle_vim_typed_key(&mut self, key: char) -> Task<Message> {
+        self.vim_mappings.typeahead.push_back((key, true));
+        self.overlay_cache.clear();
+        self.run_vim_typeahead(false)
+    }
+
+    /// Runs the keys still waiting for a mapping once the mapping timeout
+    /// has passed; called on every [`Message::Tick`].
+    pub(crate) fn flush_vim_typeahead(&mut self) -> Task<Message> {
+        let timed_out = self
+            .vim_mappings
+            .waiting_since
+            .is_some_and(|since| since.elapsed() >= self.vim_mappings.timeout);
+        if !timed_out {
+            return Task::none();
+        }
+        self.overlay_cache.clear();
+        self.run_vim_typeahead(true)
+    }
+
+    /// Returns the mappings that apply now, or `None` while keys are not
+    /// mapped: on the command line, with an operator pending, and for the
+    /// character after `f`, `r`, `"`, `m` and the like.
+    fn vim_map_mode(&self) -> Option<VimMapMode> {
+        if self.vim_state.command_line_active()
+            || self.vim_state.awaits_key_argument()
+        {
+            return None;
+        }
+        match self.vim_state.mode() {
+            VimMode::Insert => Some(VimMapMode::Insert),
+            VimMode::Normal if self.vim_state.operator_pending() => None,
+            VimMode::Normal => Some(VimMapMode::Normal),
+            VimMode::Visual | VimMode::VisualLine | VimMode::VisualBlock => {
+                Some(VimMapMode::Visual)
+            }
+        }
+    }
+
+    /// Resolves and runs the typeahead's keys until it is empty or waits
+    /// for more. Unless `forced`, keys that could start a longer mapping
+    /// wait for the next key or the timeout.
+    fn run_vim_typeahead(&mut self, forced: bool) -> Task<Message> {
+        let mut tasks = Vec::new();
+        let mut depth = 0;
+        while let Some(&(key, remap)) = self.vim_mappings.typeahead.front() {
+            let resolution = match self.vim_map_mode() {
+                Some(mode) if remap => self.vim_mappings.resolve(mode, forced),
+                _ => Resolution::Key,
+            };
+            match resolution {
+                Resolution::Wait => {
+                    self.vim_mappings.waiting_since = Some(Instant::now());
+                    return Task::batch(tasks);
+                }
+                Resolution::Map { len, mapping } => {
+                    depth += 1;
+                    if depth > MAX_MAPPING_DEPTH {
+                        self.vim_mappings.clear_typeahead();
+                        self.vim_state
+                            .set_message("E223: recursive mapping".to_owned());
+                        break;
+                    }
+                    let lhs: Vec<char> = self
+                        .vim_mappings
+                        .typeahead
+                        .drain(..len)
+                        .map(|(key, _)| key)
+                        .collect();
+                    // Like Vim, a recursive mapping whose keys start with
+                    // its own does not map those again.
+                    let unmapped =
+                        if mapping.rhs.starts_with(&lhs) { len } else { 0 };
+                    for (index, key) in mapping.rhs.iter().enumerate().rev() {
+                        let remap = mapping.recursive && index >= unmapped;
+                        self.vim_mappings.typeahead.push_front((*key, remap));
+                    }
+                }
+                Resolution::Key => {
+                    self.vim_mappings.typeahead.pop_front();
+                    tasks.push(self.emit_vim_key(key));
+                }
+            }
+        }
+        self.vim_mappings.waiting_since = None;
+        Task::batch(tasks)
+    }
+
+    /// Runs a resolved key as the message the keyboard sends for it in the
+    /// current mode.
+    fn emit_vim_key(&mut self, key: char) -> Task<Message> {
+        let insert = self.vim_state.mode() == VimMode::Insert
+            && !self.vim_state.command_line_active();
+        let message = match key {
+            '\r' | '\n' if insert => Message::Enter,
+            '\u{8}' if insert => Message::Backspace,
+            '\t' if insert => Message::Tab,
+            key if insert && !key.is_control() => Message::CharacterInput(key),
+            '\r' => Message::VimKey('\n'),
+            key => Message::VimKey(key),
+        };
+        let emitting = std::mem::replace(&mut self.vim_mappings.emitting, true);
+        let task = self.update(&message);
+        self.vim_mappings.emitting = emitting;
+        task
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::canvas_editor::features::vim::test_support::{
+        type_text, vim_keys,
+    };
+
+    #[test]
+    fn test_key_notation_round_trips() {
+        let keys = parse_vim_keys("<Esc>:w<CR><leader>x<C-a><lt><Foo>", ',');
+        assert_eq!(
+            keys,
+            vec![
+                '\u{1b}', ':', 'w', '\r', ',', 'x', '\u{1}', '<', '<', 'F',
+                'o', 'o', '>'
+            ]
+        );
+        assert_eq!(format_vim_keys(&keys), "<Esc>:w<CR>,x<C-a><lt><lt>Foo>");
+    }
+
+    #[test]
+    fn test_insert_mapping_waits_for_the_rest_of_its_keys() {
+        let mut editor = CodeEditor::new("", "txt").with_vim_enabled(true);
+        editor.vim_noremap(VimMapMode::Insert, "jk", "<Esc>");
+        vim_keys(&mut editor, "i");
+        type_text(&mut editor, "ajb");
+        // `j` followed by another key is typed as it is.
+        assert_eq!(editor.content(), "ajb");
+
+        type_text(&mut editor, "j");
+        assert_eq!(editor.content(), "ajb");
+        assert_eq!(editor.vim_mappings.pending_keys(), "j");
+        type_text(&mut editor, "k");
+        assert_eq!(editor.content(), "ajb");
+        assert_eq!(editor.vim_mode(), Some(VimMode::Normal));
+        assert_eq!(editor.cursor_position(), (0, 2));
+
+        // `.` repeats the insert without the mapped keys.
+        vim_keys(&mut editor, "0.");
+        assert_eq!(editor.content(), "ajbajb");
+    }
+
+    #[test]
+    fn test_timeout_runs_the_waiting_keys() {
+        let mut editor = CodeEditor::new("", "txt").with_vim_enabled(true);
+        editor.vim_noremap(VimMapMode::Insert, "jk", "<Esc>");
+        editor.vim_noremap(VimMapMode::Insert, "j", "J");
+        editor.set_vim_mapping_timeout(Duration::ZERO);
+        vim_keys(&mut editor, "i");
+        type_text(&mut editor, "j");
+        assert_eq!(editor.content(), "");
+
+        // The waiting `j` is itself mapped.
+        let _ = editor.update(&Message::Tick);
+        assert_eq!(editor.content(), "J");
+        assert_eq!(editor.vim_mappings.pending_keys(), "");
+
+        // Enter ends the wait too, after typing the waiting key.
+        type_text(&mut editor, "j");
+        let _ = editor.update(&Message::Enter);
+        assert_eq!(editor.content(), "JJ\n");
+    }
+
+    #[test]
+    fn test_recursive_and_non_recursive_mappings() {
+        let mut editor =
+            CodeEditor::new("a\nb\nc\nd", "txt").with_vim_enabled(true);
+        editor.vim_noremap(VimMapMode::Normal, "j", "jj");
+        editor.vim_map(VimMapMode::Normal, "J", "j");
+        editor.vim_noremap(VimMapMode::Normal, "K", "j");
+        vim_keys(&mut editor, "J");
+        assert_eq!(editor.cursor_position(), (2, 0));
+        vim_keys(&mut editor, "ggK");
+        assert_eq!(editor.cursor_position(), (1, 0));
+
+        // A mapping that expands into itself is abandoned.
+        editor.vim_map(VimMapMode::Normal, "x", "y");
+        editor.vim_map(VimMapMode::Normal, "y", "x");
+        vim_keys(&mut editor, "x");
+        assert_eq!(
+            editor.vim_state.status_line_text().0,
+            "E223: recursive mapping"
+        );
+        assert_eq!(editor.content(), "a\nb\nc\nd");
+
+        // Normal mappings leave operator arguments and Visual mode alone.
+        editor.vim_noremap(VimMapMode::Normal, "w", "x");
+        let mut editor2 =
+            CodeEditor::new("one two", "txt").with_vim_enabled(true);
+        editor2.vim_noremap(VimMapMode::Normal, "w", "x");
+        vim_keys(&mut editor2, "dw");
+        assert_eq!(editor2.content(), "two");
+        vim_keys(&mut editor2, "vwd");
+        assert_eq!(editor2.content(), "");
+    }
+
+    #[test]
+    fn test_vimrc_and_ex_mapping_commands() {
+        let mut editor = CodeEditor::new("a b c", "txt").with_vim_enabled(true);
+        let vimrc = "\" comment\n\nlet mapleader = \"\\<Space>\"\nnnoremap <leader>d dw\nnmap Q <Space>d\nset tm=500\nfoo\n3d";
+        assert_eq!(
+            editor.source_vimrc(vimrc),
+            Err(vec![
+                "line 7: E492: Not an editor command: foo".to_owned(),
+                "line 8: Not allowed in a vimrc: 3d".to_owned(),
+            ])
+        );
+        assert_eq!(editor.vim_leader(), ' ');
+        assert_eq!(editor.vim_mappings.timeout(), Duration::from_millis(500));
+        vim_keys(&mut editor, "Q");
+        assert_eq!(editor.content(), "b c");
+
+        vim_keys(&mut editor, ":nmap\n");
+        assert_eq!(
+            editor.vim_state.status_line_text().0,
+            "n <Space>d *dw  n Q <Space>d"
+        );
+        vim_keys(&mut editor, ":nunmap Q\n");
+        vim_keys(&mut editor, "Q");
+        assert_eq!(editor.content(), "b c");
+        vim_keys(&mut editor, ":iunmap Q\n");
+        assert_eq!(
+            editor.vim_state.status_line_text().0,
+            "E31: No such mapping"
+        );
+    }
+}
@@ -1,11 +1,28 @@
 //! Vim emulation for the canvas editor.
 //!
 //! This module parses key sequences into motions, operators, and actions,
-//! and tracks the modal state (normal/insert/visual/visual-line) needed to
-//! reproduce Vim-style editing behavior.
-
+//! and tracks the modal state (normal/insert/visual/visual-line/visual-block)
+//! needed to reproduce Vim-style editing behavior.
+
+mod block;
+mod ex;
+mod mapping;
+mod motion;
+mod text_object;
+mod transform;
 mod update;
 
+#[cfg(test)]
+mod test_support;
+
+use std::collections::HashMap;
+
+use self::ex::ExSubstitute;
+pub use self::mapping::VimMapMode;
+pub(crate) use self::mapping::VimMappings;
+use crate::canvas_editor::Message;
+use crate::canvas_editor::features::marks::PREVIOUS_CONTEXT_MARK;
+
 /// The active editing mode when Vim behavior is enabled.
 ///
 /// Read it through [`CodeEditor::vim_mode`], which returns `None` while Vim is
@@ -39,6 +56,9 @@ pub enum VimMode {
     Visual,
     /// Line-wise visual selection mode.
     VisualLine,
+    /// Block-wise visual selection mode (`Ctrl+V`): a rectangle of columns,
+    /// selected with one cursor per line.
+    VisualBlock,
 }
 
 /// A cursor motion recognized by the Vim parser.
@@ -58,6 +78,17 @@ pub(crate) enum VimMotion {
     WordBackward,
     /// Move to the end of the current/next word (`e`).
     WordEnd,
+    /// Move to the end of the previous word (`ge`).
+    WordEndBackward,
+    /// Move to the start of the next run of non-blank characters (`W`).
+    BigWordForward,
+    /// Move to the start of the previous run of non-blank characters (`B`).
+    BigWordBackward,
+    /// Move to the end of the current/next run of non-blank characters
+    /// (`E`).
+    BigWordEnd,
+    /// Move to the end of the previous run of non-blank characters (`gE`).
+    BigWordEndBackward,
     /// Move to column 0 of the current line (`0`).
     LineStart,
     /// Move to the first non-blank character of the line (`^`).
@@ -69,6 +100,94 @@ pub(crate) enum VimMotion {
     /// Move to line `count` (1-based) if a count is explicitly given,
     /// otherwise the last line (`G`).
     DocumentEnd,
+    /// Move to the `count`-th occurrence of a character on the line (`f`,
+    /// `t`, `F`, `T`). `repeated` marks a repeat with `;` or `,`, which
+    /// skips a character `t` or `T` already stands next to.
+    FindChar { find: VimFind, repeated: bool },
+    /// Move to the bracket matching the one at or after the cursor on its
+    /// line, or with a count to that percentage of the document (`%`).
+    MatchPair,
+    /// Move to the next blank line after a paragraph (`}`).
+    ParagraphForward,
+    /// Move to the previous blank line before a paragraph (`{`).
+    ParagraphBackward,
+    /// Move to the `count`-th visible line from the top (`H`).
+    ScreenTop,
+    /// Move to the middle visible line (`M`).
+    ScreenMiddle,
+    /// Move to the `count`-th visible line from the bottom (`L`).
+    ScreenBottom,
+    /// Search for the word under the cursor, forward (`*`) or backward
+    /// (`#`).
+    SearchWord { forward: bool },
+}
+
+/// A character search on the cursor's line, repeated by `;` and `,`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct VimFind {
+    /// The character searched for.
+    pub(crate) target: char,
+    /// Whether the search goes right (`f`, `t`) rather than left (`F`,
+    /// `T`).
+    pub(crate) forward: bool,
+    /// Whether the cursor stops next to the character (`t`, `T`) rather
+    /// than on it (`f`, `F`).
+    pub(crate) till: bool,
+}
+
+/// Where `zz`, `zt` and `zb` scroll the cursor's line to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum VimScroll {
+    /// The middle of the viewport (`zz`).
+    Center,
+    /// The top of the viewport (`zt`).
+    Top,
+    /// The bottom of the viewport (`zb`).
+    Bottom,
+}
+
+impl VimMotion {
+    /// Returns whether the motion fails when it leaves the cursor where it
+    /// was, as `l` at the end of a line or `j` on the last one does. A
+    /// failing motion aborts a macro replay.
+    pub(crate) fn fails_in_place(self) -> bool {
+        matches!(
+            self,
+            Self::Left
+                | Self::Down
+                | Self::Up
+                | Self::Right
+                | Self::WordForward
+                | Self::WordBackward
+                | Self::WordEnd
+                | Self::WordEndBackward
+                | Self::BigWordForward
+                | Self::BigWordBackward
+                | Self::BigWordEnd
+                | Self::BigWordEndBackward
+                | Self::FindChar { .. }
+                | Self::MatchPair
+                | Self::ParagraphForward
+                | Self::ParagraphBackward
+        )
+    }
+
+    /// Returns whether the motion records the position it leaves in the
+    /// jumplist, as `G`, `%` and `}` do.
+    pub(crate) fn is_jump(self) -> bool {
+        matches!(
+            self,
+            Self::DocumentStart
+                | Self::DocumentEnd
+                | Self::MatchPair
+                | Self::ParagraphForward
+                | Self::ParagraphBackward
+                | Self::ScreenTop
+                | Self::ScreenMiddle
+                | Self::ScreenBottom
+                | Self::SearchWord { .. }
+        )
+    }
 }
 
 /// An operator waiting for, or combined with, a motion.
@@ -80,6 +199,76 @@ pub(crate) enum VimOperator {
     Change,
     /// Yank (copy) the targeted range into the unnamed register (`y`).
     Yank,
+    /// Indent the targeted lines by one level (`>`).
+    ShiftRight,
+    /// Outdent the targeted lines by one level (`<`).
+    ShiftLeft,
+    /// Re-indent the targeted lines per the syntax's indent rules (`=`).
+    Reindent,
+    /// Lowercase the targeted range (`gu`).
+    Lowercase,
+    /// Uppercase the targeted range (`gU`).
+    Uppercase,
+    /// Switch the case of every letter in the targeted range (`g~`, `~`).
+    ToggleCase,
+    /// Join the targeted lines, with one space between them (`J`) or
+    /// exactly as they are (`gJ`).
+    Join { spaces: bool },
+}
+
+impl VimOperator {
+    /// Returns whether the operator rewrites the text in place, rather than
+    /// deleting or yanking it through a register.
+    pub(crate) fn rewrites_in_place(self) -> bool {
+        !matches!(self, Self::Delete | Self::Change | Self::Yank)
+    }
+
+    /// Returns the keys that type the operator, as the status line shows
+    /// them while it waits for a motion.
+    fn keys(self) -> &'static str {
+        match self {
+            Self::Delete => "d",
+            Self::Change => "c",
+            Self::Yank => "y",
+            Self::ShiftRight => ">",
+            Self::ShiftLeft => "<",
+            Self::Reindent => "=",
+            Self::Lowercase => "gu",
+            Self::Uppercase => "gU",
+            Self::ToggleCase => "g~",
+            Self::Join { spaces: true } => "J",
+            Self::Join { spaces: false } => "gJ",
+        }
+    }
+}
+
+/// What a text object covers.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum VimTextObjectKind {
+    /// A run of word characters, punctuation or whitespace (`w`).
+    Word,
+    /// A run of non-blank characters (`W`).
+    BigWord,
+    /// A sentence ending in `.`, `!` or `?` (`s`).
+    Sentence,
+    /// A run of non-blank lines, or of blank ones (`p`).
+    Paragraph,
+    /// A bracket pair (`(`/`)`/`b`, `[`/`]`, `{`/`}`/`B`, `<`/`>`).
+    Bracket { open: char, close: char },
+    /// A quoted string on the cursor's line (`"`, `'`, `` ` ``).
+    Quote(char),
+    /// An XML/HTML element (`t`).
+    Tag,
+}
+
+/// A text object typed after an operator or in Visual mode: `i` selects the
+/// inner object, `a` the object with its delimiters or surrounding
+/// whitespace.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct VimTextObject {
+    pub(crate) kind: VimTextObjectKind,
+    /// Whether the object was typed with `a` rather than `i`.
+    pub(crate) around: bool,
 }
 
 /// The insertion position requested by a Normal-mode command.
@@ -129,14 +318,55 @@ pub(crate) enum VimAction {
         count: usize,
         explicit_count: bool,
     },
+    /// Apply an operator to a text object (e.g. `diw`, `ca(`), taken
+    /// `count` times over.
+    TextObjectOperator {
+        operator: VimOperator,
+        object: VimTextObject,
+        count: usize,
+    },
+    /// Select a text object in Visual or Visual Line mode (e.g. `viw`).
+    SelectTextObject { object: VimTextObject, count: usize },
     /// Apply an operator to `count` whole lines (e.g. `dd`, `yy`, `cc`).
     LineOperator { operator: VimOperator, count: usize },
-    /// Apply an operator to the active Visual/Visual Line selection.
+    /// Apply an operator to the active Visual, Visual Line or Visual Block
+    /// selection.
     VisualOperator(VimOperator),
+    /// Replace every selected character with the given one (`r{char}` in
+    /// Visual modes).
+    VisualReplace(char),
+    /// Start Insert mode before (`I`) or after (`A`) the Visual Block
+    /// selection; the text typed on its first line is copied to the other
+    /// lines when Insert mode ends.
+    BlockInsert { append: bool },
     /// Delete `count` characters under and after the cursor (`x`).
     DeleteCharacters { count: usize },
+    /// Replace `count` characters from the cursor with the given one
+    /// (`r{char}`).
+    ReplaceCharacters { ch: char, count: usize },
+    /// Switch the case of `count` characters from the cursor, moving past
+    /// them (`~`).
+    ToggleCharacters { count: usize },
+    /// Add `count` to the number at or after the cursor, or subtract it
+    /// (`Ctrl+A`, `Ctrl+X`); in Visual modes, to the first number of each
+    /// selected line.
+    AddToNumber { count: usize, subtract: bool },
+    /// Enter Replace mode, where typed characters overwrite the text (`R`).
+    ReplaceMode,
     /// Paste the unnamed register `count` times at the given position.
     Paste { position: VimPastePosition, count: usize },
+    /// Start or stop recording a macro (`q{register}`, then `q`).
+    MacroRecording,
+    /// Replay the macro in `register` `count` times (`@{register}`, `@@`).
+    ReplayMacro { register: char, count: usize },
+    /// Mark the cursor position (`m{a-z}`).
+    SetMark(char),
+    /// Jump to a mark's position (`` `{mark} ``), or to the first non-blank
+    /// character of its line (`'{mark}`).
+    JumpToMark { mark: char, linewise: bool },
+    /// Repeat the last change (`.`), with `count` replacing its recorded
+    /// count when given.
+    RepeatChange { count: Option<usize> },
     /// Undo the last `count` grouped commands (`u`).
     Undo { count: usize },
     /// Redo the last `count` undone commands (`Ctrl+R`).
@@ -146,18 +376,110 @@ pub(crate) enum VimAction {
     RepeatSearch { reverse: bool },
     /// The pending `/` or `:` command-line input changed.
     CommandLineChanged,
+    /// Scroll the cursor's line, or line `line` (1-based) with the cursor
+    /// moved there, to the middle, top or bottom of the viewport (`zz`,
+    /// `zt`, `zb`).
+    Scroll { scroll: VimScroll, line: Option<usize> },
+    /// `:` was typed in a Visual mode, opening the command line on the
+    /// selected lines (`:'<,'>`).
+    VisualCommandLine,
     /// A `/` search was submitted with the given pattern.
     SubmitSearch(String),
-    /// A `:N` command was submitted, requesting a jump to the given
-    /// 1-based line.
-    SubmitGotoLine(usize),
-    /// A `:w` or `:wq` command was submitted, requesting a host save and
-    /// optionally exiting Vim mode.
-    WriteFile { exit_vim: bool },
-    /// A `:q` command was submitted, requesting Vim mode be turned off.
-    ExitVimMode,
+    /// A `:` command line was submitted with the given Ex command.
+    SubmitCommand(String),
+}
+
+impl VimAction {
+    /// Returns whether `.` repeats this action: an operator other than
+    /// yank, `x`, a paste, or an Insert-mode entry.
+    pub(crate) fn is_repeatable_change(&self) -> bool {
+        match self {
+            Self::Operator { operator, .. }
+            | Self::TextObjectOperator { operator, .. }
+            | Self::LineOperator { operator, .. }
+            | Self::VisualOperator(operator) => *operator != VimOperator::Yank,
+            Self::Insert { .. }
+            | Self::DeleteCharacters { .. }
+            | Self::ReplaceCharacters { .. }
+            | Self::ToggleCharacters { .. }
+            | Self::AddToNumber { .. }
+            | Self::ReplaceMode
+            | Self::Paste { .. } => true,
+            _ => false,
+        }
+    }
+
+    /// Returns the action with its count replaced by `count`, as a count
+    /// typed before `.` does.
+    pub(crate) fn with_count(mut self, count: usize) -> Self {
+        match &mut self {
+            Self::Operator { count: slot, explicit_count, .. } => {
+                *slot = count;
+                *explicit_count = true;
+            }
+            Self::Insert { count: slot, .. }
+            | Self::TextObjectOperator { count: slot, .. }
+            | Self::LineOperator { count: slot, .. }
+            | Self::DeleteCharacters { count: slot }
+            | Self::ReplaceCharacters { count: slot, .. }
+            | Self::ToggleCharacters { count: slot }
+            | Self::AddToNumber { count: slot, .. }
+            | Self::Paste { count: slot, .. } => *slot = count,
+            _ => {}
+        }
+        self
+    }
+}
+
+/// The last change, replayed by `.`.
+#[derive(Debug, Clone)]
+pub(crate) struct VimChange {
+    /// The action that made the change, with its count.
+    pub(crate) action: VimAction,
+    /// The input typed in the Insert-mode session the change opened, such
+    /// as the replacement text of `ciw`.
+    pub(crate) insert_input: Vec<Message>,
+    /// The register selected for the change with `"{register}`.
+    pub(crate) register: Option<char>,
+}
+
+/// A key awaiting the register it applies to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum VimRegisterPrefix {
+    /// `"`, selecting the register of the next yank, delete or paste.
+    Select,
+    /// `q`, starting a macro recording.
+    Record,
+    /// `@`, replaying a macro.
+    Replay,
+}
+
+/// A key awaiting the mark it applies to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum VimMarkPrefix {
+    /// `m`, setting a mark.
+    Set,
+    /// `` ` `` or `'`, jumping to a mark's position or to its line.
+    Jump { linewise: bool },
+}
+
+/// A macro being recorded with `q{register}`.
+#[derive(Debug, Clone)]
+struct VimMacroRecording {
+    register: char,
+    /// Whether the register was typed in uppercase, appending to the macro
+    /// already stored there.
+    append: bool,
+    input: Vec<Message>,
 }
 
+/// How deeply macros may replay each other before the replay aborts, which
+/// stops a macro that replays itself.
+pub(crate) const MAX_MACRO_DEPTH: usize = 100;
+
+/// Number of entries each command-line history keeps.
+pub(crate) const COMMAND_HISTORY_LIMIT: usize = 50;
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 enum VimCommandLineKind {
     Search,
@@ -168,36 +490,126 @@ enum VimCommandLineKind {
 struct VimCommandLine {
     kind: VimCommandLineKind,
     input: String,
+    /// The history entry shown while browsing with Up and Down.
+    history_index: Option<usize>,
+    /// The input typed before browsing, which recalled entries must start
+    /// with.
+    typed: String,
 }
 
-/// Whether register text represents a character range or complete lines.
+/// Whether register text represents a character range, complete lines, or
+/// a block of columns with one `\n`-separated row per line.
 #[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
 pub(crate) enum VimRegisterKind {
     #[default]
     Characterwise,
     Linewise,
+    Blockwise,
 }
 
-/// The per-editor unnamed Vim register.
+/// The text held by one Vim register.
 #[derive(Debug, Default, Clone, PartialEq, Eq)]
 pub(crate) struct VimRegister {
     pub(crate) text: String,
     pub(crate) kind: VimRegisterKind,
 }
 
+/// An Insert session opened by `I`, `A` or `c` in Visual Block mode, whose
+/// text is copied to the block's other lines when it ends.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct VimBlockInsert {
+    /// Where Insert mode started on the block's first line.
+    pub(crate) start: (usize, usize),
+    /// The length of the first line when Insert mode started.
+    pub(crate) line_len: usize,
+    /// The other lines that receive the typed text.
+    pub(crate) lines: Vec<usize>,
+    /// The visual column to insert at, or `None` for the end of each line.
+    pub(crate) column: Option<usize>,
+    /// Whether lines ending left of `column` are padded with spaces up to
+    /// it (`A`), rather than skipped.
+    pub(crate) pad: bool,
+}
+
+/// A character typed in Replace mode, remembered so Backspace can undo it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct VimReplaced {
+    /// The cursor position right after the typed character.
+    cursor: (usize, usize),
+    /// The character it overwrote, or `None` where it extended the line.
+    original: Option<char>,
+}
+
 /// Pure Vim parsing state owned by one editor instance.
 #[derive(Debug, Default)]
 pub(crate) struct VimState {
     mode: VimMode,
     count: Option<usize>,
     g_prefix: bool,
+    z_prefix: bool,
+    /// `f`, `t`, `F` or `T` typed, awaiting the character to find.
+    find_prefix: Option<VimFind>,
+    /// The last `f`, `t`, `F` or `T` search, repeated by `;` and `,`.
+    last_find: Option<VimFind>,
+    /// `i` or `a` typed where a text object may follow; `true` for `a`.
+    text_object_prefix: Option<bool>,
+    register_prefix: Option<VimRegisterPrefix>,
+    mark_prefix: Option<VimMarkPrefix>,
+    /// `r` typed, awaiting the replacement character.
+    replace_prefix: bool,
     pending_operator: Option<VimOperator>,
     pending_operator_count: usize,
     visual_anchor: Option<(usize, usize)>,
     visual_active: Option<(usize, usize)>,
+    /// Set by `$` in Visual Block mode: the block extends to the end of
+    /// every line, however long.
+    block_to_line_end: bool,
+    /// The visual column the Visual Block's moving corner aims for, kept
+    /// while `j` and `k` pass through lines too short to reach it.
+    block_column: Option<usize>,
+    /// The Visual Block's height in lines and width in columns, shown in
+    /// the status line.
+    block_size: Option<(usize, usize)>,
+    block_insert: Option<VimBlockInsert>,
+    /// Set in Replace mode (`R`), which is Insert mode with typed
+    /// characters overwriting the text: what each typed character
+    /// replaced, so Backspace can restore it.
+    replaced: Option<Vec<VimReplaced>>,
     command_line: Option<VimCommandLine>,
     last_search: Option<String>,
+    /// Set by `:noh` until the next search.
+    search_highlight_hidden: bool,
+    /// Submitted `/` patterns and `:` commands, oldest first.
+    search_history: Vec<String>,
+    command_history: Vec<String>,
+    last_substitute: Option<ExSubstitute>,
+    last_change: Option<VimChange>,
+    /// Whether Insert-mode input still belongs to `last_change`.
+    recording_change: bool,
+    /// Whether `.` or a macro is replaying input, which keeps its edits in
+    /// one undo group.
+    replaying: bool,
+    macro_recording: Option<VimMacroRecording>,
+    macros: HashMap<char, Vec<Message>>,
+    last_macro: Option<char>,
+    /// How many macro replays are running, one inside the other.
+    macro_depth: usize,
+    /// Set when a motion or search could not move, which aborts a macro
+    /// replay.
+    motion_failed: bool,
+    /// The unnamed register, which every yank, delete and paste uses.
     pub(crate) register: VimRegister,
+    /// Named (`a`–`z`), numbered (`0`–`9`) and small-delete (`-`)
+    /// registers.
+    registers: HashMap<char, VimRegister>,
+    /// The register typed with `"{register}` for the next yank, delete or
+    /// paste.
+    selected_register: Option<char>,
+    /// A `"+p` or `"*p` waiting for the clipboard: its position and count.
+    pending_clipboard_paste: Option<(VimPastePosition, usize)>,
+    /// A message shown in place of the mode until the next key, such as the
+    /// `:registers` listing.
+    message: Option<String>,
 }
 
 impl VimState {
@@ -213,9 +625,260 @@ impl VimState {
         self.mode = VimMode::Normal;
         self.clear_visual();
         self.command_line = None;
+        self.block_insert = None;
+        self.replaced = None;
         self.clear_pending();
     }
 
+    pub(crate) fn last_change(&self) -> Option<&VimChange> {
+        self.last_change.as_ref()
+    }
+
+    /// Makes `action` the change `.` repeats and, when it left the editor
+    /// in Insert mode, starts recording the input that follows it.
+    pub(crate) fn record_change(
+        &mut self,
+        action: VimAction,
+        register: Option<char>,
+    ) {
+        self.last_change =
+            Some(VimChange { action, insert_input: Vec::new(), register });
+        self.recording_change = self.mode == VimMode::Insert;
+    }
+
+    /// Appends Insert-mode input to the change being recorded.
+    pub(crate) fn record_insert_input(&mut self, message: &Message) {
+        if self.recording_change
+            && let Some(change) = self.last_change.as_mut()
+        {
+            change.insert_input.push(message.clone());
+        }
+    }
+
+    pub(crate) fn stop_recording_change(&mut self) {
+        self.recording_change = false;
+    }
+
+    pub(crate) fn replaying(&self) -> bool {
+        self.replaying
+    }
+
+    pub(crate) fn set_replaying(&mut self, replaying: bool) {
+        self.replaying = replaying;
+        self.recording_change = false;
+    }
+
+    /// Appends input to the macro being recorded, unless it comes from a
+    /// replay: a macro records the `@` or `.` keys, not what they replay.
+    pub(crate) fn record_macro_input(&mut self, message: &Message) {
+        if !self.replaying
+            && let Some(recording) = self.macro_recording.as_mut()
+        {
+            recording.input.push(message.clone());
+        }
+    }
+
+    pub(crate) fn recording_macro(&self) -> Option<char> {
+        self.macro_recording.as_ref().map(|recording| recording.register)
+    }
+
+    pub(crate) fn macro_input(&self, register: char) -> Option<&[Message]> {
+        self.macros.get(&register).map(Vec::as_slice)
+    }
+
+    /// Enters a macro replay, returning `false` when replays are already
+    /// nested [`MAX_MACRO_DEPTH`] deep.
+    pub(crate) fn enter_macro(&mut self) -> bool {
+        if self.macro_depth >= MAX_MACRO_DEPTH {
+            return false;
+        }
+        self.macro_depth += 1;
+        true
+    }
+
+    pub(crate) fn leave_macro(&mut self) {
+        self.macro_depth = self.macro_depth.saturating_sub(1);
+    }
+
+    pub(crate) fn fail_motion(&mut self) {
+        self.motion_failed = true;
+    }
+
+    /// Returns whether a motion failed since the last call, and clears it.
+    pub(crate) fn take_motion_failed(&mut self) -> bool {
+        std::mem::take(&mut self.motion_failed)
+    }
+
+    fn start_macro_recording(&mut self, key: char) {
+        let register = key.to_ascii_lowercase();
+        self.macro_recording = Some(VimMacroRecording {
+            register,
+            append: key.is_ascii_uppercase(),
+            input: Vec::new(),
+        });
+    }
+
+    fn stop_macro_recording(&mut self) {
+        if let Some(recording) = self.macro_recording.take() {
+            let stored = self.macros.entry(recording.register).or_default();
+            if !recording.append {
+                stored.clear();
+            }
+            stored.extend(recording.input);
+        }
+    }
+
+    pub(crate) fn selected_register(&self) -> Option<char> {
+        self.selected_register
+    }
+
+    pub(crate) fn select_register(&mut self, register: Option<char>) {
+        self.selected_register = register;
+    }
+
+    /// Stores yanked or deleted text in `register`, or, without one, in the
+    /// unnamed register and the yank or delete history.
+    ///
+    /// Yanks go to `"0`. Deletes of whole or several lines shift `"1`–`"8`
+    /// down to `"2`–`"9` and go to `"1`; smaller deletes go to `"-`. An
+    /// uppercase register appends to its lowercase one, and the black-hole
+    /// register `"_` keeps nothing.
+    pub(crate) fn store_register(
+        &mut self,
+        register: Option<char>,
+        operator: VimOperator,
+        value: VimRegister,
+    ) {
+        match register {
+            Some('_') => return,
+            Some(name) if name.is_ascii_uppercase() => {
+                let stored = self
+                    .registers
+                    .entry(name.to_ascii_lowercase())
+                    .or_default();
+                if value.kind == VimRegisterKind::Linewise
+                    && stored.kind == VimRegisterKind::Characterwise
+                    && !stored.text.is_empty()
+                {
+                    stored.text.push('\n');
+                }
+                stored.text.push_str(&value.text);
+                if value.kind == VimRegisterKind::Linewise {
+                    stored.kind = VimRegisterKind::Linewise;
+                }
+                self.register = stored.clone();
+                return;
+            }
+            Some(name) if name.is_ascii_alphanumeric() || name == '-' => {
+                self.registers.insert(name, value.clone());
+            }
+            Some(_) => {}
+            None if operator == VimOperator::Yank => {
+                self.registers.insert('0', value.clone());
+            }
+            None if value.kind == VimRegisterKind::Linewise
+                || value.text.contains('\n') =>
+            {
+                for digit in (1..9u8).rev() {
+                    if let Some(older) =
+                        self.registers.remove(&char::from(b'0' + digit))
+                    {
+                        self.registers.insert(char::from(b'1' + digit), older);
+                    }
+                }
+                self.registers.insert('1', value.clone());
+            }
+            None => {
+                self.registers.insert('-', value.clone());
+            }
+        }
+        self.register = value;
+    }
+
+    /// Returns the register a paste from `register` uses: the unnamed one
+    /// by default, or `None` for the black-hole register and empty ones.
+    pub(crate) fn register_for_paste(
+        &self,
+        register: Option<char>,
+    ) -> Option<&VimRegister> {
+        let value = match register {
+            None | Some('"') => &self.register,
+            Some(name) => self.registers.get(&name.to_ascii_lowercase())?,
+        };
+        (!value.text.is_empty()).then_some(value)
+    }
+
+    pub(crate) fn set_pending_clipboard_paste(
+        &mut self,
+        paste: (VimPastePosition, usize),
+    ) {
+        self.pending_clipboard_paste = Some(paste);
+    }
+
+    pub(crate) fn take_pending_clipboard_paste(
+        &mut self,
+    ) -> Option<(VimPastePosition, usize)> {
+        self.pending_clipboard_paste.take()
+    }
+
+    /// Shows the `:registers` listing: each non-empty register in `names`
+    /// (every register when empty) with its text on one line, newlines
+    /// shown as `^J`.
+    pub(crate) fn show_registers(&mut self, names: &str) {
+        self.message = Some(self.register_listing(names));
+    }
+
+    fn register_listing(&self, names: &str) -> String {
+        const SHOWN_CHARS: usize = 30;
+        let mut listed = vec![('"', &self.register)];
+        let mut stored: Vec<_> = self.registers.iter().collect();
+        stored.sort_by_key(|(name, _)| {
+            (!name.is_ascii_digit(), **name != '-', **name)
+        });
+        listed.extend(stored.into_iter().map(|(name, value)| (*name, value)));
+
+        let entries: Vec<String> = listed
+            .into_iter()
+            .filter(|(name, value)| {
+                !value.text.is_empty()
+                    && (names.is_empty() || names.contains(*name))
+            })
+            .map(|(name, value)| {
+                let text: String = value
+                    .text
+                    .chars()
+                    .take(SHOWN_CHARS)
+                    .map(|ch| match ch {
+                        '\n' => "^J".to_owned(),
+                        '\t' => "^I".to_owned(),
+                        ch => ch.to_string(),
+                    })
+                    .collect();
+                format!("\"{name} {text}")
+            })
+            .collect();
+        if entries.is_empty() {
+            "No registers".to_owned()
+        } else {
+            entries.join("  ")
+        }
+    }
+
+    /// Returns whether the parser waits for a character argument, such as
+    /// the target of `f` or the name after `"`, which key mappings leave
+    /// alone.
+    pub(crate) fn awaits_key_argument(&self) -> bool {
+        self.replace_prefix
+            || self.find_prefix.is_some()
+            || self.register_prefix.is_some()
+            || self.mark_prefix.is_some()
+            || self.text_object_prefix.is_some()
+    }
+
+    pub(crate) fn operator_pending(&self) -> bool {
+        self.pending_operator.is_some()
+    }
+
     pub(crate) fn command_line_active(&self) -> bool {
         self.command_line.is_some()
     }
@@ -224,6 +887,89 @@ impl VimState {
         self.last_search.as_deref()
     }
 
+    /// Makes `pattern` the one `n` and `N` repeat, and highlights it again
+    /// after `:noh`.
+    pub(crate) fn set_last_search(&mut self, pattern: String) {
+        self.last_search = Some(pattern);
+        self.search_highlight_hidden = false;
+    }
+
+    /// Returns the search pattern to highlight: the last one, unless `:noh`
+    /// hid it.
+    pub(crate) fn highlighted_search(&self) -> Option<&str> {
+        self.last_search().filter(|_| !self.search_highlight_hidden)
+    }
+
+    pub(crate) fn hide_search_highlight(&mut self) {
+        self.search_highlight_hidden = true;
+    }
+
+    pub(crate) fn last_substitute(&self) -> Option<&ExSubstitute> {
+        self.last_substitute.as_ref()
+    }
+
+    pub(crate) fn set_last_substitute(&mut self, substitute: ExSubstitute) {
+        self.last_substitute = Some(substitute);
+    }
+
+    /// Shows `message` in place of the mode until the next key.
+    pub(crate) fn set_message(&mut self, message: String) {
+        self.message = Some(message);
+    }
+
+    /// Replaces the command-line input with an older or newer entry of its
+    /// history that starts with the text typed before browsing, returning
+    /// whether the input changed. Browsing past the newest entry restores
+    /// the typed text.
+    pub(crate) fn browse_command_history(&mut self, older: bool) -> bool {
+        let Some(command_line) = self.command_line.as_mut() else {
+            return false;
+        };
+        let history = match command_line.kind {
+            VimCommandLineKind::Search => &self.search_history,
+            VimCommandLineKind::Command => &self.command_history,
+        };
+        let typed = match command_line.history_index {
+            Some(_) => command_line.typed.as_str(),
+            None => command_line.input.as_str(),
+        };
+        let recalled = |entry: &String| entry.starts_with(typed);
+        let index = match (older, command_line.history_index) {
+            (true, index) => history
+                .get(..index.unwrap_or(history.len()))
+                .and_then(|older| older.iter().rposition(recalled)),
+            (false, Some(index)) => history
+                .iter()
+                .skip(index + 1)
+                .position(recalled)
+                .map(|offset| index + 1 + offset),
+            (false, None) => return false,
+        };
+
+        match index.and_then(|index| Some((index, history.get(index)?))) {
+            Some((index, entry)) => {
+                if command_line.history_index.is_none() {
+                    command_line.typed =
+                        std::mem::take(&mut command_line.input);
+                }
+                command_line.input.clone_from(entry);
+                command_line.history_index = Some(index);
+            }
+            None if !older && command_line.history_index.is_some() => {
+                command_line.input = std::mem::take(&mut command_line.typed);
+                command_line.history_index = None;
+            }
+            None => return false,
+        }
+        true
+    }
+
+    /// Leaves Visual mode for Normal mode, keeping the command line open.
+    pub(crate) fn leave_visual(&mut self) {
+        self.mode = VimMode::Normal;
+        self.clear_visual();
+    }
+
     pub(crate) fn command_line_text(&self) -> Option<String> {
         self.command_line.as_ref().map(|command_line| {
             let prefix = match command_line.kind {
@@ -240,11 +986,7 @@ impl VimState {
             if self.pending_operator_count > 1 {
                 pending.push_str(&self.pending_operator_count.to_string());
             }
-            pending.push(match operator {
-                VimOperator::Delete => 'd',
-                VimOperator::Change => 'c',
-                VimOperator::Yank => 'y',
-            });
+            pending.push_str(operator.keys());
             if let Some(count) = self.count {
                 pending.push_str(&count.to_string());
             }
@@ -254,6 +996,39 @@ impl VimState {
         if self.g_prefix {
             pending.push('g');
         }
+        if self.z_prefix {
+            pending.push('z');
+        }
+        if let Some(find) = self.find_prefix {
+            pending.push(match (find.forward, find.till) {
+                (true, false) => 'f',
+                (true, true) => 't',
+                (false, false) => 'F',
+                (false, true) => 'T',
+            });
+        }
+        if let Some(around) = self.text_object_prefix {
+            pending.push(if around { 'a' } else { 'i' });
+        }
+        if let Some(register) = self.selected_register {
+            pending.insert(0, register);
+            pending.insert(0, '"');
+        }
+        match self.register_prefix {
+            Some(VimRegisterPrefix::Select) => pending.push('"'),
+            Some(VimRegisterPrefix::Record) => pending.push('q'),
+            Some(VimRegisterPrefix::Replay) => pending.push('@'),
+            None => {}
+        }
+        match self.mark_prefix {
+            Some(VimMarkPrefix::Set) => pending.push('m'),
+            Some(VimMarkPrefix::Jump { linewise: true }) => pending.push('\''),
+            Some(VimMarkPrefix::Jump { linewise: false }) => pending.push('`'),
+            None => {}
+        }
+        if self.replace_prefix {
+            pending.push('r');
+        }
         pending
     }
 
@@ -261,15 +1036,32 @@ impl VimState {
         if let Some(command_line) = self.command_line_text() {
             return (command_line, String::new());
         }
+        if let Some(message) = &self.message {
+            return (message.clone(), String::new());
+        }
 
         let mode = match self.mode {
             VimMode::Normal => "NORMAL",
+            VimMode::Insert if self.replaced.is_some() => "REPLACE",
             VimMode::Insert => "INSERT",
             VimMode::Visual => "VISUAL",
             VimMode::VisualLine => "VISUAL LINE",
+            VimMode::VisualBlock => "VISUAL BLOCK",
         };
 
-        (format!("-- {mode} --"), self.pending_keys())
+        let status = match self.recording_macro() {
+            Some(register) => format!("-- {mode} -- recording @{register}"),
+            None => format!("-- {mode} --"),
+        };
+        let pending = self.pending_keys();
+        match self.block_size {
+            Some((lines, columns))
+                if pending.is_empty() && self.mode == VimMode::VisualBlock =>
+            {
+                (status, format!("{lines}x{columns}"))
+            }
+            _ => (status, pending),
+        }
     }
 
     pub(crate) fn begin_visual(&mut self, position: (usize, usize)) {
@@ -290,10 +1082,56 @@ impl VimState {
     pub(crate) fn clear_visual(&mut self) {
         self.visual_anchor = None;
         self.visual_active = None;
+        self.block_to_line_end = false;
+        self.block_column = None;
+        self.block_size = None;
+    }
+
+    /// Switches between Visual modes, keeping the selection, as selecting a
+    /// text object of another kind does.
+    pub(crate) fn switch_visual_mode(&mut self, mode: VimMode) {
+        if is_visual(self.mode) {
+            self.mode = mode;
+        }
+    }
+
+    pub(crate) fn block_to_line_end(&self) -> bool {
+        self.block_to_line_end
+    }
+
+    /// Updates whether the Visual Block extends to the end of every line
+    /// after `motion`: `$` sets it, and only vertical motions keep it.
+    pub(crate) fn track_block_line_end(&mut self, motion: VimMotion) {
+        self.block_to_line_end = motion == VimMotion::LineEnd
+            || (self.block_to_line_end
+                && matches!(motion, VimMotion::Up | VimMotion::Down));
+    }
+
+    pub(crate) fn block_column(&self) -> Option<usize> {
+        self.block_column
+    }
+
+    pub(crate) fn set_block_column(&mut self, column: Option<usize>) {
+        self.block_column = column;
+    }
+
+    pub(crate) fn set_block_size(&mut self, size: (usize, usize)) {
+        self.block_size = Some(size);
+    }
+
+    pub(crate) fn set_block_insert(&mut self, block_insert: VimBlockInsert) {
+        self.block_insert = Some(block_insert);
+    }
+
+    pub(crate) fn take_block_insert(&mut self) -> Option<VimBlockInsert> {
+        self.block_insert.take()
     }
 
     pub(crate) fn set_mode_from_mouse(&mut self, mode: VimMode) {
         self.mode = mode;
+        self.recording_change = false;
+        self.block_insert = None;
+        self.replaced = None;
         self.clear_pending();
     }
 
@@ -301,10 +1139,57 @@ impl VimState {
         self.mode = VimMode::Insert;
         self.clear_visual();
         self.command_line = None;
+        self.replaced = None;
         self.clear_pending();
     }
 
+    /// Enters Replace mode: Insert mode in which typed characters overwrite
+    /// the text.
+    pub(crate) fn enter_replace_mode(&mut self) {
+        self.enter_insert_mode();
+        self.replaced = Some(Vec::new());
+    }
+
+    pub(crate) fn replacing(&self) -> bool {
+        self.mode == VimMode::Insert && self.replaced.is_some()
+    }
+
+    /// Records what a character typed in Replace mode overwrote, and the
+    /// cursor position after it.
+    pub(crate) fn push_replaced(
+        &mut self,
+        cursor: (usize, usize),
+        original: Option<char>,
+    ) {
+        if let Some(replaced) = self.replaced.as_mut() {
+            replaced.push(VimReplaced { cursor, original });
+        }
+    }
+
+    /// Takes what the character typed just before `cursor` in Replace mode
+    /// overwrote. Returns `None` when that character was not typed in this
+    /// session, as after moving the cursor, and forgets the older ones.
+    pub(crate) fn pop_replaced(
+        &mut self,
+        cursor: (usize, usize),
+    ) -> Option<Option<char>> {
+        let replaced = self.replaced.as_mut()?;
+        match replaced.last() {
+            Some(&VimReplaced { cursor: typed, original })
+                if typed == cursor =>
+            {
+                replaced.pop();
+                Some(original)
+            }
+            _ => {
+                replaced.clear();
+                None
+            }
+        }
+    }
+
     pub(crate) fn parse_key(&mut self, key: char) -> Option<VimAction> {
+        self.message = None;
         if self.command_line.is_some() {
             return self.parse_command_line_key(key);
         }
@@ -321,18 +1206,98 @@ impl VimState {
             return Some(self.set_mode(VimMode::Normal));
         }
 
+        if let Some(prefix) = self.register_prefix.take() {
+            return self.finish_register_prefix(prefix, key);
+        }
+
+        if let Some(prefix) = self.mark_prefix.take() {
+            return self.finish_mark_prefix(prefix, key);
+        }
+
+        if std::mem::take(&mut self.replace_prefix) {
+            let count = self.take_count();
+            self.clear_pending();
+            if key.is_control() {
+                return None;
+            }
+            return Some(if self.mode == VimMode::Normal {
+                VimAction::ReplaceCharacters { ch: key, count }
+            } else {
+                VimAction::VisualReplace(key)
+            });
+        }
+
+        if let Some(find) = self.find_prefix.take() {
+            if key.is_control() {
+                self.clear_pending();
+                return None;
+            }
+            let find = VimFind { target: key, ..find };
+            self.last_find = Some(find);
+            return Some(
+                self.finish_motion(VimMotion::FindChar {
+                    find,
+                    repeated: false,
+                }),
+            );
+        }
+
         if key.is_ascii_digit() && (key != '0' || self.count.is_some()) {
             self.push_count_digit(key);
             return None;
         }
 
+        if let Some(around) = self.text_object_prefix.take() {
+            let Some(kind) = text_object_for_key(key) else {
+                self.clear_pending();
+                return None;
+            };
+            return Some(
+                self.finish_text_object(VimTextObject { kind, around }),
+            );
+        }
+
         if self.g_prefix {
-            if key == 'g' {
+            let operator = match key {
+                'u' => Some(VimOperator::Lowercase),
+                'U' => Some(VimOperator::Uppercase),
+                '~' => Some(VimOperator::ToggleCase),
+                _ => None,
+            };
+            if let Some(operator) = operator {
+                self.g_prefix = false;
+                return self.parse_operator(operator);
+            }
+            if key == 'J' {
                 self.g_prefix = false;
-                return Some(self.finish_motion(VimMotion::DocumentStart));
+                return Some(self.join(false));
             }
+            let motion = match key {
+                'g' => VimMotion::DocumentStart,
+                'e' => VimMotion::WordEndBackward,
+                'E' => VimMotion::BigWordEndBackward,
+                _ => {
+                    self.clear_pending();
+                    return None;
+                }
+            };
+            self.g_prefix = false;
+            return Some(self.finish_motion(motion));
+        }
+
+        if std::mem::take(&mut self.z_prefix) {
+            let scroll = match key {
+                'z' => VimScroll::Center,
+                't' => VimScroll::Top,
+                'b' => VimScroll::Bottom,
+                _ => {
+                    self.clear_pending();
+                    return None;
+                }
+            };
+            let line = self.count.take();
             self.clear_pending();
-            return None;
+            return Some(VimAction::Scroll { scroll, line });
         }
 
         if key == 'g' {
@@ -340,6 +1305,36 @@ impl VimState {
             return None;
         }
 
+        if key == 'z' && self.pending_operator.is_none() {
+            self.z_prefix = true;
+            return None;
+        }
+
+        if let Some(find) = find_for_key(key) {
+            self.find_prefix = Some(find);
+            return None;
+        }
+
+        if matches!(key, ';' | ',') {
+            let Some(find) = self.last_find else {
+                self.clear_pending();
+                return None;
+            };
+            let forward = find.forward == (key == ';');
+            return Some(self.finish_motion(VimMotion::FindChar {
+                find: VimFind { forward, ..find },
+                repeated: true,
+            }));
+        }
+
+        // `guu`, `gUU` and `g~~` act on lines like `gugu`.
+        if let Some(operator) = self.pending_operator
+            && operator.keys().strip_prefix('g')
+                == Some(key.encode_utf8(&mut [0; 4]))
+        {
+            return self.parse_operator(operator);
+        }
+
         if let Some(operator) = operator_for_key(key) {
             return self.parse_operator(operator);
         }
@@ -348,6 +1343,15 @@ impl VimState {
             return Some(self.finish_motion(motion));
         }
 
+        // After an operator, and in Visual modes, `i` and `a` start a text
+        // object instead of entering Insert mode.
+        if matches!(key, 'i' | 'a')
+            && (self.pending_operator.is_some() || self.mode != VimMode::Normal)
+        {
+            self.text_object_prefix = Some(key == 'a');
+            return None;
+        }
+
         let action = match key {
             'i' => Some(self.insert(VimInsertPosition::BeforeCursor)),
             'a' => Some(self.insert(VimInsertPosition::AfterCursor)),
@@ -357,6 +1361,43 @@ impl VimState {
             'O' => Some(self.insert(VimInsertPosition::NewLineAbove)),
             'v' => Some(self.set_mode(VimMode::Visual)),
             'V' => Some(self.set_mode(VimMode::VisualLine)),
+            '\u{16}' => Some(self.set_mode(VimMode::VisualBlock)),
+            'r' if self.pending_operator.is_none() => {
+                if self.mode != VimMode::Normal {
+                    self.count = None;
+                }
+                self.replace_prefix = true;
+                None
+            }
+            'R' if self.mode == VimMode::Normal
+                && self.pending_operator.is_none() =>
+            {
+                self.enter_replace_mode();
+                Some(VimAction::ReplaceMode)
+            }
+            'J' if self.pending_operator.is_none() => Some(self.join(true)),
+            '~' if self.mode == VimMode::Normal
+                && self.pending_operator.is_none() =>
+            {
+                Some(VimAction::ToggleCharacters { count: self.take_count() })
+            }
+            '~' if is_visual(self.mode) => {
+                self.parse_operator(VimOperator::ToggleCase)
+            }
+            'u' if is_visual(self.mode) => {
+                self.parse_operator(VimOperator::Lowercase)
+            }
+            'U' if is_visual(self.mode) => {
+                self.parse_operator(VimOperator::Uppercase)
+            }
+            '\u{1}' | '\u{18}' if self.pending_operator.is_none() => {
+                let count = self.take_count();
+                self.clear_pending();
+                Some(VimAction::AddToNumber {
+                    count,
+                    subtract: key == '\u{18}',
+                })
+            }
             'x' => {
                 Some(VimAction::DeleteCharacters { count: self.take_count() })
             }
@@ -368,24 +1409,160 @@ impl VimState {
                 position: VimPastePosition::BeforeCursor,
                 count: self.take_count(),
             }),
+            '.' if self.mode == VimMode::Normal => {
+                let count = self.count.take();
+                self.clear_pending();
+                Some(VimAction::RepeatChange { count })
+            }
+            'q' if self.mode == VimMode::Normal
+                && self.pending_operator.is_none() =>
+            {
+                if self.macro_recording.is_some() {
+                    self.stop_macro_recording();
+                    self.clear_pending();
+                    Some(VimAction::MacroRecording)
+                } else {
+                    self.count = None;
+                    self.register_prefix = Some(VimRegisterPrefix::Record);
+                    None
+                }
+            }
+            '"' if self.pending_operator.is_none() => {
+                self.register_prefix = Some(VimRegisterPrefix::Select);
+                None
+            }
+            '@' if self.mode == VimMode::Normal
+                && self.pending_operator.is_none() =>
+            {
+                self.register_prefix = Some(VimRegisterPrefix::Replay);
+                None
+            }
+            'm' if self.pending_operator.is_none() => {
+                self.count = None;
+                self.mark_prefix = Some(VimMarkPrefix::Set);
+                None
+            }
+            '`' | '\'' if self.pending_operator.is_none() => {
+                self.count = None;
+                self.mark_prefix =
+                    Some(VimMarkPrefix::Jump { linewise: key == '\'' });
+                None
+            }
             'u' => Some(VimAction::Undo { count: self.take_count() }),
             '\u{12}' => Some(VimAction::Redo { count: self.take_count() }),
-            'n' => Some(VimAction::RepeatSearch { reverse: false }),
-            'N' => Some(VimAction::RepeatSearch { reverse: true }),
-            '/' => Some(self.open_command_line(VimCommandLineKind::Search)),
-            ':' => Some(self.open_command_line(VimCommandLineKind::Command)),
+            'n' | 'N' => {
+                self.search_highlight_hidden = false;
+                Some(VimAction::RepeatSearch { reverse: key == 'N' })
+            }
+            '/' => {
+                Some(self.open_command_line(
+                    VimCommandLineKind::Search,
+                    String::new(),
+                ))
+            }
+            ':' if self.mode == VimMode::Normal => {
+                Some(self.open_command_line(
+                    VimCommandLineKind::Command,
+                    String::new(),
+                ))
+            }
+            ':' => {
+                self.open_command_line(
+                    VimCommandLineKind::Command,
+                    "'<,'>".to_owned(),
+                );
+                Some(VimAction::VisualCommandLine)
+            }
             _ => None,
         };
 
-        if action.is_none() {
+        if action.is_none()
+            && self.register_prefix.is_none()
+            && self.mark_prefix.is_none()
+            && !self.replace_prefix
+        {
             self.clear_pending();
+            self.selected_register = None;
         }
         action
     }
 
-    fn open_command_line(&mut self, kind: VimCommandLineKind) -> VimAction {
+    /// Completes `q{register}` or `@{register}`; `@@` replays the last
+    /// replayed macro.
+    fn finish_register_prefix(
+        &mut self,
+        prefix: VimRegisterPrefix,
+        key: char,
+    ) -> Option<VimAction> {
+        let register = match (prefix, key) {
+            (VimRegisterPrefix::Replay, '@') => self.last_macro,
+            (VimRegisterPrefix::Select, '"') => {
+                self.selected_register = None;
+                return None;
+            }
+            (VimRegisterPrefix::Select, '-' | '_' | '+' | '*') => Some(key),
+            (_, key) if key.is_ascii_alphanumeric() => Some(key),
+            _ => None,
+        };
+        let Some(register) = register else {
+            self.clear_pending();
+            return None;
+        };
+        match prefix {
+            VimRegisterPrefix::Select => {
+                self.selected_register = Some(register);
+                None
+            }
+            VimRegisterPrefix::Record => {
+                self.start_macro_recording(register);
+                self.clear_pending();
+                Some(VimAction::MacroRecording)
+            }
+            VimRegisterPrefix::Replay => {
+                let register = register.to_ascii_lowercase();
+                self.last_macro = Some(register);
+                let count = self.take_count();
+                self.clear_pending();
+                Some(VimAction::ReplayMacro { register, count })
+            }
+        }
+    }
+
+    /// Completes `m{a-z}`, `` `{mark} `` or `'{mark}`, where `` ` `` and
+    /// `'` name the position before the latest jump.
+    fn finish_mark_prefix(
+        &mut self,
+        prefix: VimMarkPrefix,
+        key: char,
+    ) -> Option<VimAction> {
+        self.clear_pending();
+        match (prefix, key) {
+            (VimMarkPrefix::Set, 'a'..='z') => Some(VimAction::SetMark(key)),
+            (VimMarkPrefix::Jump { linewise }, '`' | '\'') => {
+                Some(VimAction::JumpToMark {
+                    mark: PREVIOUS_CONTEXT_MARK,
+                    linewise,
+                })
+            }
+            (VimMarkPrefix::Jump { linewise }, 'a'..='z' | '<' | '>') => {
+                Some(VimAction::JumpToMark { mark: key, linewise })
+            }
+            _ => None,
+        }
+    }
+
+    fn open_command_line(
+        &mut self,
+        kind: VimCommandLineKind,
+        input: String,
+    ) -> VimAction {
         self.clear_pending();
-        self.command_line = Some(VimCommandLine { kind, input: String::new() });
+        self.command_line = Some(VimCommandLine {
+            kind,
+            input,
+            history_index: None,
+            typed: String::new(),
+        });
         VimAction::CommandLineChanged
     }
 
@@ -396,10 +1573,11 @@ impl VimState {
                 Some(VimAction::CommandLineChanged)
             }
             '\u{8}' => {
-                if let Some(command_line) = self.command_line.as_mut()
-                    && command_line.input.pop().is_none()
-                {
-                    self.command_line = None;
+                if let Some(command_line) = self.command_line.as_mut() {
+                    command_line.history_index = None;
+                    if command_line.input.pop().is_none() {
+                        self.command_line = None;
+                    }
                 }
                 Some(VimAction::CommandLineChanged)
             }
@@ -410,31 +1588,22 @@ impl VimState {
                 }
                 match command_line.kind {
                     VimCommandLineKind::Search => {
-                        self.last_search = Some(command_line.input.clone());
+                        remember(&mut self.search_history, &command_line.input);
+                        self.set_last_search(command_line.input.clone());
                         Some(VimAction::SubmitSearch(command_line.input))
                     }
                     VimCommandLineKind::Command => {
-                        match command_line.input.as_str() {
-                            "q" => Some(VimAction::ExitVimMode),
-                            "w" => {
-                                Some(VimAction::WriteFile { exit_vim: false })
-                            }
-                            "wq" => {
-                                Some(VimAction::WriteFile { exit_vim: true })
-                            }
-                            _ => command_line
-                                .input
-                                .parse::<usize>()
-                                .ok()
-                                .filter(|line| *line > 0)
-                                .map(VimAction::SubmitGotoLine)
-                                .or(Some(VimAction::CommandLineChanged)),
-                        }
+                        remember(
+                            &mut self.command_history,
+                            &command_line.input,
+                        );
+                        Some(VimAction::SubmitCommand(command_line.input))
                     }
                 }
             }
             key if !key.is_control() => {
                 if let Some(command_line) = self.command_line.as_mut() {
+                    command_line.history_index = None;
                     command_line.input.push(key);
                 }
                 Some(VimAction::CommandLineChanged)
@@ -445,17 +1614,39 @@ impl VimState {
 
     fn set_mode(&mut self, mode: VimMode) -> VimAction {
         self.mode = mode;
+        self.replaced = None;
         self.clear_pending();
+        self.selected_register = None;
         VimAction::Mode(mode)
     }
 
     fn insert(&mut self, position: VimInsertPosition) -> VimAction {
+        if self.mode == VimMode::VisualBlock {
+            self.clear_pending();
+            return VimAction::BlockInsert {
+                append: position == VimInsertPosition::EndOfLine,
+            };
+        }
         let count = self.take_count();
         self.mode = VimMode::Insert;
+        self.block_insert = None;
         self.clear_pending();
         VimAction::Insert { position, count }
     }
 
+    /// Joins lines: `count` of them, at least two, in Normal mode, or the
+    /// selected ones in Visual modes (`J`, `gJ`).
+    fn join(&mut self, spaces: bool) -> VimAction {
+        let operator = VimOperator::Join { spaces };
+        if self.mode != VimMode::Normal {
+            self.clear_pending();
+            return VimAction::VisualOperator(operator);
+        }
+        let count = self.take_count().max(2);
+        self.clear_pending();
+        VimAction::LineOperator { operator, count }
+    }
+
     fn parse_operator(&mut self, operator: VimOperator) -> Option<VimAction> {
         if self.mode != VimMode::Normal {
             self.clear_pending();
@@ -502,6 +1693,18 @@ impl VimState {
         }
     }
 
+    fn finish_text_object(&mut self, object: VimTextObject) -> VimAction {
+        let count = self.take_count();
+        if let Some(operator) = self.pending_operator {
+            let count = self.pending_operator_count.saturating_mul(count);
+            self.clear_pending();
+            VimAction::TextObjectOperator { operator, object, count }
+        } else {
+            self.clear_pending();
+            VimAction::SelectTextObject { object, count }
+        }
+    }
+
     fn push_count_digit(&mut self, key: char) {
         let digit = key.to_digit(10).unwrap_or_default() as usize;
         self.count = Some(
@@ -519,11 +1722,22 @@ impl VimState {
     fn clear_pending(&mut self) {
         self.count = None;
         self.g_prefix = false;
+        self.z_prefix = false;
+        self.find_prefix = None;
+        self.text_object_prefix = None;
+        self.register_prefix = None;
+        self.mark_prefix = None;
+        self.replace_prefix = false;
         self.pending_operator = None;
         self.pending_operator_count = 1;
     }
 }
 
+/// Returns whether `mode` is one of the Visual modes.
+pub(crate) fn is_visual(mode: VimMode) -> bool {
+    matches!(mode, VimMode::Visual | VimMode::VisualLine | VimMode::VisualBlock)
+}
+
 fn motion_for_key(key: char) -> Option<VimMotion> {
     match key {
         'h' => Some(VimMotion::Left),
@@ -533,10 +1747,51 @@ fn motion_for_key(key: char) -> Option<VimMotion> {
         'w' => Some(VimMotion::WordForward),
         'b' => Some(VimMotion::WordBackward),
         'e' => Some(VimMotion::WordEnd),
+        'W' => Some(VimMotion::BigWordForward),
+        'B' => Some(VimMotion::BigWordBackward),
+        'E' => Some(VimMotion::BigWordEnd),
         '0' => Some(VimMotion::LineStart),
         '^' => Some(VimMotion::FirstNonBlank),
         '$' => Some(VimMotion::LineEnd),
         'G' => Some(VimMotion::DocumentEnd),
+        '%' => Some(VimMotion::MatchPair),
+        '}' => Some(VimMotion::ParagraphForward),
+        '{' => Some(VimMotion::ParagraphBackward),
+        'H' => Some(VimMotion::ScreenTop),
+        'M' => Some(VimMotion::ScreenMiddle),
+        'L' => Some(VimMotion::ScreenBottom),
+        '*' => Some(VimMotion::SearchWord { forward: true }),
+        '#' => Some(VimMotion::SearchWord { forward: false }),
+        _ => None,
+    }
+}
+
+/// Returns the character search `f`, `t`, `F` or `T` starts, its target
+/// still unknown.
+fn find_for_key(key: char) -> Option<VimFind> {
+    let (forward, till) = match key {
+        'f' => (true, false),
+        't' => (true, true),
+        'F' => (false, false),
+        'T' => (false, true),
+        _ => return None,
+    };
+    Some(VimFind { target: ' ', forward, till })
+}
+
+fn text_object_for_key(key: char) -> Option<VimTextObjectKind> {
+    let bracket = |open, close| VimTextObjectKind::Bracket { open, close };
+    match key {
+        'w' => Some(VimTextObjectKind::Word),
+        'W' => Some(VimTextObjectKind::BigWord),
+        's' => Some(VimTextObjectKind::Sentence),
+        'p' => Some(VimTextObjectKind::Paragraph),
+        '(' | ')' | 'b' => Some(bracket('(', ')')),
+        '[' | ']' => Some(bracket('[', ']')),
+        '{' | '}' | 'B' => Some(bracket('{', '}')),
+        '<' | '>' => Some(bracket('<', '>')),
+        '"' | '\'' | '`' => Some(VimTextObjectKind::Quote(key)),
+        't' => Some(VimTextObjectKind::Tag),
         _ => None,
     }
 }
@@ -546,15 +1801,29 @@ fn operator_for_key(key: char) -> Option<VimOperator> {
         'd' => Some(VimOperator::Delete),
         'c' => Some(VimOperator::Change),
         'y' => Some(VimOperator::Yank),
+        '>' => Some(VimOperator::ShiftRight),
+        '<' => Some(VimOperator::ShiftLeft),
+        '=' => Some(VimOperator::Reindent),
         _ => None,
     }
 }
 
+/// Adds `entry` as the newest history entry, dropping an older copy and the
+/// oldest entry past [`COMMAND_HISTORY_LIMIT`].
+fn remember(history: &mut Vec<String>, entry: &str) {
+    history.retain(|older| older != entry);
+    history.push(entry.to_owned());
+    if history.len() > COMMAND_HISTORY_LIMIT {
+        history.remove(0);
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::{
-        VimAction, VimMotion, VimOperator, VimRegister, VimRegisterKind,
-        VimState,
+        Message, VimAction, VimFind, VimMode, VimMotion, VimOperator,
+        VimRegister, VimRegisterKind, VimScroll, VimState, VimTextObject,
+        VimTextObjectKind,
     };
 
     #[test]
@@ -600,6 +1869,156 @@ mod tests {
         );
     }
 
+    #[test]
+    fn vim_parser_reads_case_shift_join_and_replace_keys() {
+        let mut state = VimState::default();
+
+        assert_eq!(state.parse_key('g'), None);
+        assert_eq!(state.parse_key('U'), None);
+        assert_eq!(state.pending_keys(), "gU");
+        assert_eq!(
+            state.parse_key('w'),
+            Some(VimAction::Operator {
+                operator: VimOperator::Uppercase,
+                motion: VimMotion::WordForward,
+                count: 1,
+                explicit_count: false,
+            })
+        );
+        for keys in ["g~~", "g~g~"] {
+            let action = keys.chars().map(|key| state.parse_key(key)).last();
+            assert_eq!(
+                action,
+                Some(Some(VimAction::LineOperator {
+                    operator: VimOperator::ToggleCase,
+                    count: 1,
+                }))
+            );
+        }
+        assert_eq!(state.parse_key('3'), None);
+        assert_eq!(state.parse_key('>'), None);
+        assert_eq!(
+            state.parse_key('>'),
+            Some(VimAction::LineOperator {
+                operator: VimOperator::ShiftRight,
+                count: 3,
+            })
+        );
+
+        // `J` joins at least two lines.
+        assert_eq!(
+            state.parse_key('J'),
+            Some(VimAction::LineOperator {
+                operator: VimOperator::Join { spaces: true },
+                count: 2,
+            })
+        );
+        assert_eq!(state.parse_key('g'), None);
+        assert_eq!(
+            state.parse_key('J'),
+            Some(VimAction::LineOperator {
+                operator: VimOperator::Join { spaces: false },
+                count: 2,
+            })
+        );
+
+        assert_eq!(state.parse_key('2'), None);
+        assert_eq!(state.parse_key('r'), None);
+        assert_eq!(
+            state.parse_key('x'),
+            Some(VimAction::ReplaceCharacters { ch: 'x', count: 2 })
+        );
+        assert_eq!(
+            state.parse_key('~'),
+            Some(VimAction::ToggleCharacters { count: 1 })
+        );
+        assert_eq!(state.parse_key('5'), None);
+        assert_eq!(
+            state.parse_key('\u{18}'),
+            Some(VimAction::AddToNumber { count: 5, subtract: true })
+        );
+        assert_eq!(state.parse_key('R'), Some(VimAction::ReplaceMode));
+        assert_eq!(state.mode(), VimMode::Insert);
+        assert_eq!(state.status_line_text().0, "-- REPLACE --");
+        assert_eq!(
+            state.parse_key('\u{1b}'),
+            Some(VimAction::Mode(VimMode::Normal))
+        );
+        assert!(!state.replacing());
+
+        // In Visual modes, `u`, `U` and `~` change case instead.
+        let _ = state.parse_key('v');
+        assert_eq!(
+            state.parse_key('u'),
+            Some(VimAction::VisualOperator(VimOperator::Lowercase))
+        );
+    }
+
+    #[test]
+    fn vim_parser_finds_characters_and_repeats_the_search() {
+        let mut state = VimState::default();
+        let find = |target, forward, till| VimFind { target, forward, till };
+
+        assert_eq!(state.parse_key('2'), None);
+        assert_eq!(state.parse_key('t'), None);
+        assert_eq!(state.pending_keys(), "2t");
+        assert_eq!(
+            state.parse_key('x'),
+            Some(VimAction::Motion {
+                motion: VimMotion::FindChar {
+                    find: find('x', true, true),
+                    repeated: false,
+                },
+                count: 2,
+                explicit_count: true,
+            })
+        );
+        assert_eq!(
+            state.parse_key(','),
+            Some(VimAction::Motion {
+                motion: VimMotion::FindChar {
+                    find: find('x', false, true),
+                    repeated: true,
+                },
+                count: 1,
+                explicit_count: false,
+            })
+        );
+
+        assert_eq!(state.parse_key('d'), None);
+        assert_eq!(state.parse_key('F'), None);
+        assert_eq!(
+            state.parse_key('('),
+            Some(VimAction::Operator {
+                operator: VimOperator::Delete,
+                motion: VimMotion::FindChar {
+                    find: find('(', false, false),
+                    repeated: false,
+                },
+                count: 1,
+                explicit_count: false,
+            })
+        );
+
+        assert_eq!(state.parse_key('g'), None);
+        assert_eq!(
+            state.parse_key('e'),
+            Some(VimAction::Motion {
+                motion: VimMotion::WordEndBackward,
+                count: 1,
+                explicit_count: false,
+            })
+        );
+
+        assert_eq!(state.parse_key('5'), None);
+        assert_eq!(state.parse_key('z'), None);
+        assert_eq!(state.pending_keys(), "5z");
+        assert_eq!(
+            state.parse_key('t'),
+            Some(VimAction::Scroll { scroll: VimScroll::Top, line: Some(5) })
+        );
+    }
+
     #[test]
     fn vim_parser_distinguishes_explicit_count_for_document_end() {
         let mut state = VimState::default();
@@ -673,17 +2092,9 @@ mod tests {
         for key in "12".chars() {
             let _ = state.parse_key(key);
         }
-        assert_eq!(state.parse_key('\n'), Some(VimAction::SubmitGotoLine(12)));
-
-        let _ = state.parse_key(':');
-        let _ = state.parse_key('q');
-        assert_eq!(state.parse_key('\n'), Some(VimAction::ExitVimMode));
-
-        let _ = state.parse_key(':');
-        let _ = state.parse_key('w');
         assert_eq!(
             state.parse_key('\n'),
-            Some(VimAction::WriteFile { exit_vim: false })
+            Some(VimAction::SubmitCommand("12".into()))
         );
 
         let _ = state.parse_key(':');
@@ -691,7 +2102,7 @@ mod tests {
         let _ = state.parse_key('q');
         assert_eq!(
             state.parse_key('\n'),
-            Some(VimAction::WriteFile { exit_vim: true })
+            Some(VimAction::SubmitCommand("wq".into()))
         );
 
         let _ = state.parse_key('/');
@@ -703,6 +2114,194 @@ mod tests {
         assert_eq!(state.command_line_text(), None);
     }
 
+    #[test]
+    fn vim_parser_browses_command_line_history() {
+        let mut state = VimState::default();
+        for command in [":s/a/b/\n", ":12\n", ":set wrap\n", ":12\n"] {
+            for key in command.chars() {
+                let _ = state.parse_key(key);
+            }
+        }
+
+        let _ = state.parse_key(':');
+        assert!(state.browse_command_history(true));
+        assert_eq!(state.command_line_text().as_deref(), Some(":12"));
+        assert!(state.browse_command_history(true));
+        assert_eq!(state.command_line_text().as_deref(), Some(":set wrap"));
+        assert!(state.browse_command_history(false));
+        assert!(state.browse_command_history(false));
+        assert_eq!(state.command_line_text().as_deref(), Some(":"));
+        assert!(!state.browse_command_history(false));
+
+        // Only entries starting with the typed text are recalled.
+        let _ = state.parse_key('s');
+        assert!(state.browse_command_history(true));
+        assert_eq!(state.command_line_text().as_deref(), Some(":set wrap"));
+        assert!(state.browse_command_history(true));
+        assert_eq!(state.command_line_text().as_deref(), Some(":s/a/b/"));
+        assert!(!state.browse_command_history(true));
+    }
+
+    #[test]
+    fn vim_parser_reads_text_objects_after_operators_and_in_visual() {
+        let mut state = VimState::default();
+
+        assert_eq!(state.parse_key('2'), None);
+        assert_eq!(state.parse_key('d'), None);
+        assert_eq!(state.parse_key('3'), None);
+        assert_eq!(state.parse_key('a'), None);
+        assert_eq!(state.pending_keys(), "2d3a");
+        assert_eq!(
+            state.parse_key('('),
+            Some(VimAction::TextObjectOperator {
+                operator: VimOperator::Delete,
+                object: VimTextObject {
+                    kind: VimTextObjectKind::Bracket { open: '(', close: ')' },
+                    around: true,
+                },
+                count: 6,
+            })
+        );
+
+        // An unknown object key cancels the pending operator.
+        let _ = state.parse_key('c');
+        let _ = state.parse_key('i');
+        assert_eq!(state.parse_key('z'), None);
+        assert_eq!(state.pending_keys(), "");
+
+        let _ = state.parse_key('v');
+        assert_eq!(state.parse_key('i'), None);
+        assert_eq!(
+            state.parse_key('"'),
+            Some(VimAction::SelectTextObject {
+                object: VimTextObject {
+                    kind: VimTextObjectKind::Quote('"'),
+                    around: false,
+                },
+                count: 1,
+            })
+        );
+
+        // In Normal mode `i` still enters Insert mode.
+        let _ = state.parse_key('\u{1b}');
+        let _ = state.parse_key('i');
+        assert_eq!(state.mode(), VimMode::Insert);
+    }
+
+    #[test]
+    fn vim_parser_reads_dot_with_an_optional_count() {
+        let mut state = VimState::default();
+
+        assert_eq!(
+            state.parse_key('.'),
+            Some(VimAction::RepeatChange { count: None })
+        );
+        assert_eq!(state.parse_key('3'), None);
+        assert_eq!(
+            state.parse_key('.'),
+            Some(VimAction::RepeatChange { count: Some(3) })
+        );
+
+        let change = VimAction::Operator {
+            operator: VimOperator::Delete,
+            motion: VimMotion::WordForward,
+            count: 2,
+            explicit_count: false,
+        };
+        assert!(change.is_repeatable_change());
+        assert_eq!(
+            change.with_count(5),
+            VimAction::Operator {
+                operator: VimOperator::Delete,
+                motion: VimMotion::WordForward,
+                count: 5,
+                explicit_count: true,
+            }
+        );
+        assert!(
+            !VimAction::LineOperator { operator: VimOperator::Yank, count: 1 }
+                .is_repeatable_change()
+        );
+    }
+
+    #[test]
+    fn vim_parser_records_and_replays_macros() {
+        let mut state = VimState::default();
+
+        assert_eq!(state.parse_key('q'), None);
+        assert_eq!(state.pending_keys(), "q");
+        assert_eq!(state.parse_key('a'), Some(VimAction::MacroRecording));
+        assert_eq!(state.recording_macro(), Some('a'));
+        state.record_macro_input(&Message::VimKey('x'));
+        assert_eq!(state.parse_key('q'), Some(VimAction::MacroRecording));
+        assert_eq!(state.recording_macro(), None);
+        assert_eq!(state.macro_input('a').map(<[_]>::len), Some(1));
+
+        // `@@` without a previous replay does nothing.
+        assert_eq!(state.parse_key('@'), None);
+        assert_eq!(state.parse_key('@'), None);
+        assert_eq!(state.pending_keys(), "");
+
+        assert_eq!(state.parse_key('3'), None);
+        assert_eq!(state.parse_key('@'), None);
+        assert_eq!(state.pending_keys(), "3@");
+        assert_eq!(
+            state.parse_key('A'),
+            Some(VimAction::ReplayMacro { register: 'a', count: 3 })
+        );
+        assert_eq!(state.parse_key('@'), None);
+        assert_eq!(
+            state.parse_key('@'),
+            Some(VimAction::ReplayMacro { register: 'a', count: 1 })
+        );
+    }
+
+    #[test]
+    fn vim_parser_selects_registers() {
+        let mut state = VimState::default();
+
+        assert_eq!(state.parse_key('"'), None);
+        assert_eq!(state.pending_keys(), "\"");
+        assert_eq!(state.parse_key('a'), None);
+        assert_eq!(state.parse_key('d'), None);
+        assert_eq!(state.pending_keys(), "\"ad");
+        assert_eq!(state.selected_register(), Some('a'));
+
+        // Escape drops the selection, and invalid names cancel it.
+        let _ = state.parse_key('\u{1b}');
+        assert_eq!(state.selected_register(), None);
+        let _ = state.parse_key('"');
+        let _ = state.parse_key('!');
+        assert_eq!(state.selected_register(), None);
+        assert_eq!(state.pending_keys(), "");
+    }
+
+    #[test]
+    fn vim_parser_reads_marks() {
+        let mut state = VimState::default();
+
+        assert_eq!(state.parse_key('m'), None);
+        assert_eq!(state.pending_keys(), "m");
+        assert_eq!(state.parse_key('a'), Some(VimAction::SetMark('a')));
+
+        assert_eq!(state.parse_key('\''), None);
+        assert_eq!(state.pending_keys(), "'");
+        assert_eq!(
+            state.parse_key('a'),
+            Some(VimAction::JumpToMark { mark: 'a', linewise: true })
+        );
+        assert_eq!(state.parse_key('`'), None);
+        assert_eq!(
+            state.parse_key('\''),
+            Some(VimAction::JumpToMark { mark: '`', linewise: false })
+        );
+
+        // Only lowercase marks can be set.
+        assert_eq!(state.parse_key('m'), None);
+        assert_eq!(state.parse_key('A'), None);
+        assert_eq!(state.pending_keys(), "");
+    }
+
     #[test]
     fn vim_pending_keys_formats_counts_prefixes_and_operators() {
         let mut state = VimState::default();
@@ -764,5 +2363,22 @@ mod tests {
             state.status_line_text(),
             ("-- VISUAL LINE --".to_owned(), String::new())
         );
+
+        assert_eq!(
+            state.parse_key('\u{16}'),
+            Some(VimAction::Mode(VimMode::VisualBlock))
+        );
+        assert_eq!(state.mode(), VimMode::VisualBlock);
+        state.set_block_size((2, 3));
+        assert_eq!(
+            state.status_line_text(),
+            ("-- VISUAL BLOCK --".to_owned(), "2x3".to_owned())
+        );
+        assert_eq!(state.parse_key('r'), None);
+        assert_eq!(state.parse_key('x'), Some(VimAction::VisualReplace('x')));
+        assert_eq!(
+            state.parse_key('A'),
+            Some(VimAction::BlockInsert { append: true })
+        );
     }
 }
@@ -0,0 +1,546 @@
+//! Vim motions that need more than the cursor's line and the word index:
+//! character searches (`f`, `t`, `;`), bracket matching (`%`), paragraphs
+//! (`{`, `}`), screen lines (`H`, `M`, `L`), word searches (`*`, `#`), and
+//! the `zz`, `zt` and `zb` scrolls.
+//!
+//! [`CodeEditor::vim_motion_target`] resolves each of these motions here, so
+//! they compose with operators, counts and Visual modes like the others.
+
+use iced::Task;
+use iced::widget::operation::scroll_to;
+use iced::widget::scrollable;
+
+use super::{VimFind, VimMotion, VimScroll};
+use crate::canvas_editor::editing::cursor::{WordClass, word_class};
+use crate::canvas_editor::features::bracket_match::find_matching_pair;
+use crate::canvas_editor::render::wrapping::WrappingCalculator;
+use crate::canvas_editor::{CodeEditor, Message};
+
+/// Lines kept between the cursor and the viewport's top or bottom edge, the
+/// same margin [`CodeEditor::scroll_to_cursor`] keeps.
+const SCROLL_MARGIN_LINES: usize = 2;
+
+impl CodeEditor {
+    /// Returns where `f`, `t`, `F` or `T` moves from `start`: onto, or next
+    /// to, the `count`-th `find.target` on the line. A repeated `t` or `T`
+    /// skips a target the cursor already stands next to, so `;` moves on.
+    ///
+    /// # Returns
+    ///
+    /// The target position, which is `start` itself for a `t` or `T` whose
+    /// target is adjacent, or `None` when the line has too few targets
+    pub(crate) fn vim_find_target(
+        &self,
+        start: (usize, usize),
+        find: VimFind,
+        repeated: bool,
+        count: usize,
+    ) -> Option<(usize, usize)> {
+        let chars: Vec<char> = self.buffer.line(start.0).chars().collect();
+        let skip = usize::from(find.till && repeated);
+        let hits = |col: &usize| chars[*col] == find.target;
+        let found = if find.forward {
+            (start.1 + 1 + skip..chars.len())
+                .filter(hits)
+                .nth(count - 1)
+                .map(|col| if find.till { col - 1 } else { col })
+        } else {
+            (0..start.1.saturating_sub(skip).min(chars.len()))
+                .rev()
+                .filter(hits)
+                .nth(count - 1)
+                .map(|col| if find.till { col + 1 } else { col })
+        };
+        found.map(|col| (start.0, col))
+    }
+
+    /// Returns where `%` moves from `start`: to the bracket matching the
+    /// first `(`, `)`, `[`, `]`, `{` or `}` at or after the cursor on its
+    /// line. With a count, it moves to the first non-blank character of the
+    /// line that far through the document, in percent.
+    ///
+    /// # Returns
+    ///
+    /// The target position, or `start` when no bracket or match is found
+    pub(crate) fn vim_match_pair_target(
+        &self,
+        start: (usize, usize),
+        count: usize,
+        explicit_count: bool,
+    ) -> (usize, usize) {
+        if explicit_count {
+            let line = (count.min(100) * self.buffer.line_count())
+                .div_ceil(100)
+                .saturating_sub(1);
+            return self.vim_motion_target(
+                (line, 0),
+                VimMotion::FirstNonBlank,
+                1,
+                false,
+            );
+        }
+        self.buffer
+            .line(start.0)
+            .chars()
+            .enumerate()
+            .skip(start.1)
+            .find(|(_, ch)| matches!(ch, '(' | ')' | '[' | ']' | '{' | '}'))
+            .and_then(|(col, _)| {
+                find_matching_pair(&self.buffer, (start.0, col))
+            })
+            .map_or(start, |(_, matched)| matched)
+    }
+
+    /// Returns where `}` (`forward`) or `{` moves from `start`: `count`
+    /// paragraphs on, to the blank line after or before each one. Past the
+    /// last paragraph, the motion stops at the end or the start of the
+    /// document. Lines holding only whitespace count as blank, as for the
+    /// `ip` text object.
+    pub(crate) fn vim_paragraph_target(
+        &self,
+        start: (usize, usize),
+        forward: bool,
+        count: usize,
+    ) -> (usize, usize) {
+        let blank = |line: usize| self.buffer.line(line).trim().is_empty();
+        let last = self.buffer.line_count().saturating_sub(1);
+        let mut line = start.0;
+        for _ in 0..count {
+            if forward {
+                while line < last && blank(line) {
+                    line += 1;
+                }
+                while line < last && !blank(line) {
+                    line += 1;
+                }
+            } else {
+                while line > 0 && blank(line) {
+                    line -= 1;
+                }
+                while line > 0 && !blank(line) {
+                    line -= 1;
+                }
+            }
+        }
+        if forward && line == last && !blank(line) {
+            (line, self.buffer.line_len(line))
+        } else {
+            (line, 0)
+        }
+    }
+
+    /// Returns where `H`, `M` or `L` moves: the first non-blank character
+    /// of the `count`-th visible line from the top or bottom, or of the
+    /// middle one. `H` and `L` stay the scroll margin away from the
+    /// viewport's edges unless the document ends there.
+    pub(crate) fn vim_screen_line_target(
+        &self,
+        motion: VimMotion,
+        count: usize,
+    ) -> (usize, usize) {
+        let visual_lines = self.visual_lines_cached(self.viewport_width);
+        let Some(last) = visual_lines.len().checked_sub(1) else {
+            return (0, 0);
+        };
+        let top = ((self.viewport_scroll / self.line_height).ceil() as usize)
+            .min(last);
+        let bottom = ((self.viewport_scroll + self.viewport_height)
+            / self.line_height)
+            .floor() as usize;
+        let bottom = bottom.saturating_sub(1).clamp(top, last);
+
+        let first = if top > 0 { top + SCROLL_MARGIN_LINES } else { top };
+        let final_line = if bottom < last {
+            bottom.saturating_sub(SCROLL_MARGIN_LINES)
+        } else {
+            bottom
+        };
+        let (first, final_line) = if first <= final_line {
+            (first, final_line)
+        } else {
+            (top, bottom)
+        };
+        let index = match motion {
+            VimMotion::ScreenTop => (first + count - 1).min(final_line),
+            VimMotion::ScreenBottom => {
+                final_line.saturating_sub(count - 1).max(first)
+            }
+            _ => top + (bottom - top) / 2,
+        };
+
+        let Some(visual_line) = visual_lines.get(index) else {
+            return (0, 0);
+        };
+        if visual_line.segment_index > 0 {
+            return (visual_line.logical_line, visual_line.start_col);
+        }
+        self.vim_motion_target(
+            (visual_line.logical_line, 0),
+            VimMotion::FirstNonBlank,
+            1,
+            false,
+        )
+    }
+
+    /// Returns the word `*` and `#` search for: the keyword under the
+    /// cursor at `position`, or the next one on its line.
+    ///
+    /// # Returns
+    ///
+    /// The word's first column and its text, or `None` when no keyword
+    /// follows the cursor on its line
+    pub(crate) fn vim_search_word_at(
+        &self,
+        position: (usize, usize),
+    ) -> Option<(usize, String)> {
+        let chars: Vec<char> = self.buffer.line(position.0).chars().collect();
+        let keyword = |col: &usize| {
+            word_class(chars[*col], &self.word_separators) == WordClass::Word
+        };
+        let found = (position.1..chars.len()).find(keyword)?;
+        let start = if found == position.1 {
+            (0..found).rev().take_while(keyword).last().unwrap_or(found)
+        } else {
+            found
+        };
+        let end = (found..chars.len())
+            .find(|col| !keyword(col))
+            .unwrap_or(chars.len());
+        Some((start, chars[start..end].iter().collect()))
+    }
+
+    /// Returns where `*` (`forward`) or `#` moves from `start`: to the
+    /// `count`-th match of the search [`Self::start_vim_word_search`] set
+    /// up, after or before the word under the cursor, wrapping around the
+    /// document.
+    pub(crate) fn vim_search_word_target(
+        &self,
+        start: (usize, usize),
+        forward: bool,
+        count: usize,
+    ) -> (usize, usize) {
+        let matches = &self.search_state.matches;
+        let Some((word_start, _)) = self.vim_search_word_at(start) else {
+            return start;
+        };
+        if matches.is_empty() {
+            return start;
+        }
+        let mut position = (start.0, word_start);
+        for _ in 0..count {
+            let index = if forward {
+                let next = matches
+                    .partition_point(|item| (item.line, item.col) <= position);
+                if next == matches.len() { 0 } else { next }
+            } else {
+                matches
+                    .partition_point(|item| (item.line, item.col) < position)
+                    .checked_sub(1)
+                    .unwrap_or(matches.len() - 1)
+            };
+            position = (matches[index].line, matches[index].col);
+        }
+        position
+    }
+
+    /// Starts the search `*` or `#` runs from `position`: the word under
+    /// the cursor, as a whole word (`\<word\>`), becomes the last search,
+    /// which `n` and `N` repeat.
+    ///
+    /// # Returns
+    ///
+    /// Whether a word was found to search for
+    pub(super) fn start_vim_word_search(
+        &mut self,
+        position: (usize, usize),
+    ) -> bool {
+        let word = self
+            .vim_search_word_at(self.vim_normal_position(position))
+            .filter(|_| self.search_replace_enabled);
+        let Some((_, word)) = word else {
+            self.vim_state.fail_motion();
+            return false;
+        };
+        let pattern = format!("\\<{word}\\>");
+        self.search_state.close();
+        self.set_vim_search_pattern(&pattern);
+        self.vim_state.set_last_search(pattern);
+        true
+    }
+
+    /// Scrolls the cursor's line to the middle, top or bottom of the
+    /// viewport (`zz`, `zt`, `zb`), first moving the cursor to `line`
+    /// (1-based) when a count gave one.
+    pub(super) fn handle_vim_scroll(
+        &mut self,
+        scroll: VimScroll,
+        line: Option<usize>,
+    ) -> Task<Message> {
+        self.end_grouping_if_active();
+        if let Some(line) = line {
+            let column = self.vim_state.visual_positions().map_or_else(
+                || self.cursors.primary_position().1,
+                |(_, active)| active.1,
+            );
+            let line = line
+                .saturating_sub(1)
+                .min(self.buffer.line_count().saturating_sub(1));
+            self.move_vim_cursor(self.vim_normal_position((line, column)));
+            self.finish_navigation_operation();
+        }
+        scroll_to(
+            self.scrollable_id.clone(),
+            scrollable::AbsoluteOffset {
+                x: 0.0,
+                y: self.vim_scroll_offset(scroll),
+            },
+        )
+    }
+
+    /// Returns the vertical scroll offset that puts the cursor's line where
+    /// `scroll` asks, keeping the scroll margin for `zt` and `zb`.
+    pub(crate) fn vim_scroll_offset(&self, scroll: VimScroll) -> f32 {
+        let visual_lines = self.visual_lines_cached(self.viewport_width);
+        let (line, col) = self.cursors.primary_position();
+        let cursor_y =
+            WrappingCalculator::logical_to_visual(&visual_lines, line, col)
+                .unwrap_or(line) as f32
+                * self.line_height;
+        let margin = SCROLL_MARGIN_LINES as f32 * self.line_height;
+        let offset = match scroll {
+            VimScroll::Center => {
+                cursor_y - (self.viewport_height - self.line_height) / 2.0
+            }
+            VimScroll::Top => cursor_y - margin,
+            VimScroll::Bottom => {
+                cursor_y + self.line_height + margin - self.viewport_height
+            }
+        };
+        offset.max(0.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::canvas_editor::features::vim::test_support::vim_keys;
+    use crate::canvas_editor::{CodeEditor, VimMode};
+
+    use super::VimScroll;
+
+    fn cursor_after(
+        content: &str,
+        start: (usize, usize),
+        keys: &str,
+    ) -> (usize, usize) {
+        let mut editor = CodeEditor::new(content, "txt").with_vim_enabled(true);
+        editor.cursors.set_single(start);
+        vim_keys(&mut editor, keys);
+        editor.cursors.primary_position()
+    }
+
+    fn content_after(
+        content: &str,
+        start: (usize, usize),
+        keys: &str,
+    ) -> String {
+        let mut editor = CodeEditor::new(content, "txt").with_vim_enabled(true);
+        editor.cursors.set_single(start);
+        vim_keys(&mut editor, keys);
+        editor.content()
+    }
+
+    #[test]
+    fn test_find_char_motions_and_repeats() {
+        let line = "a,b,c,d";
+        assert_eq!(cursor_after(line, (0, 0), "f,"), (0, 1));
+        assert_eq!(cursor_after(line, (0, 0), "2f,"), (0, 3));
+        assert_eq!(cursor_after(line, (0, 0), "t,"), (0, 0));
+        assert_eq!(cursor_after(line, (0, 0), "f,;;"), (0, 5));
+        assert_eq!(cursor_after(line, (0, 0), "f,;,"), (0, 1));
+        // A repeated `t` moves past the target it stands next to.
+        assert_eq!(cursor_after(line, (0, 0), "tc;"), (0, 3));
+        assert_eq!(cursor_after(line, (0, 6), "F,"), (0, 5));
+        assert_eq!(cursor_after(line, (0, 6), "T,;"), (0, 4));
+        // A missing target leaves the cursor in place.
+        assert_eq!(cursor_after(line, (0, 0), "fz"), (0, 0));
+
+        assert_eq!(content_after(line, (0, 0), "df,"), "b,c,d");
+        assert_eq!(content_after(line, (0, 0), "d2t,"), ",c,d");
+        assert_eq!(content_after(line, (0, 6), "dF,"), "a,b,cd");
+        assert_eq!(content_after(line, (0, 0), "dfz"), line);
+        assert_eq!(content_after(line, (0, 0), "f,d;"), "ac,d");
+    }
+
+    #[test]
+    fn test_till_operators_with_an_adjacent_target() {
+        assert_eq!(content_after("a,b", (0, 0), "dt,"), ",b");
+        assert_eq!(content_after("a,b", (0, 2), "dT,"), "a,b");
+        assert_eq!(content_after("a,b", (0, 0), "dtz"), "a,b");
+
+        let mut editor = CodeEditor::new("a,b", "txt").with_vim_enabled(true);
+        editor.cursors.set_single((0, 2));
+        vim_keys(&mut editor, "cT,");
+        assert_eq!(editor.vim_state.mode(), VimMode::Insert);
+        assert_eq!(editor.cursors.primary_position(), (0, 2));
+    }
+
+    #[test]
+    fn test_match_pair_motion() {
+        let code = "fn f(a: [u8]) {\n    g();\n}";
+        assert_eq!(cursor_after(code, (0, 0), "%"), (0, 12));
+        assert_eq!(cursor_after(code, (0, 12), "%"), (0, 4));
+        assert_eq!(cursor_after(code, (0, 13), "%"), (2, 0));
+        assert_eq!(cursor_after(code, (1, 4), "%"), (1, 6));
+        assert_eq!(cursor_after(code, (0, 0), "100%"), (2, 0));
+        assert_eq!(cursor_after(code, (2, 0), "50%"), (1, 4));
+
+        assert_eq!(content_after(code, (0, 4), "d%"), "fn f {\n    g();\n}");
+        assert_eq!(content_after("no brackets", (0, 0), "d%"), "no brackets");
+    }
+
+    #[test]
+    fn test_paragraph_motions() {
+        let text = "a\nb\n\nc\n\n\nd\ne";
+        assert_eq!(cursor_after(text, (0, 0), "}"), (2, 0));
+        assert_eq!(cursor_after(text, (0, 0), "2}"), (4, 0));
+        assert_eq!(cursor_after(text, (0, 0), "3}"), (7, 0));
+        assert_eq!(cursor_after(text, (7, 0), "{"), (5, 0));
+        assert_eq!(cursor_after(text, (7, 0), "3{"), (0, 0));
+
+        assert_eq!(content_after(text, (0, 0), "d}"), "\nc\n\n\nd\ne");
+        assert_eq!(content_after(text, (6, 0), "d}"), "a\nb\n\nc\n\n\n");
+    }
+
+    #[test]
+    fn test_screen_line_motions() {
+        let text: Vec<String> =
+            (0..100).map(|line| format!("  {line}")).collect();
+        let mut editor =
+            CodeEditor::new(&text.join("\n"), "txt").with_vim_enabled(true);
+        editor.viewport_height = editor.line_height * 20.0;
+        editor.viewport_scroll = editor.line_height * 10.0;
+        editor.cursors.set_single((15, 0));
+
+        vim_keys(&mut editor, "H");
+        assert_eq!(editor.cursors.primary_position(), (12, 2));
+        vim_keys(&mut editor, "L");
+        assert_eq!(editor.cursors.primary_position(), (27, 2));
+        vim_keys(&mut editor, "3H");
+        assert_eq!(editor.cursors.primary_position(), (14, 2));
+        vim_keys(&mut editor, "M");
+        assert_eq!(editor.cursors.primary_position(), (19, 2));
+
+        // At the top of the document, `H` reaches the first line.
+        editor.viewport_scroll = 0.0;
+        vim_keys(&mut editor, "ggdL");
+        assert_eq!(editor.buffer.line_count(), 82);
+        assert_eq!(editor.buffer.line(0), "  18");
+    }
+
+    #[test]
+    fn test_word_search_motions_feed_the_last_search() {
+        let text = "foo bar\nbaz foo\nbar foo";
+        assert_eq!(cursor_after(text, (0, 1), "*"), (1, 4));
+        assert_eq!(cursor_after(text, (0, 1), "2*"), (2, 4));
+        assert_eq!(cursor_after(text, (0, 1), "#"), (2, 4));
+        // On a blank, the next word on the line is searched for.
+        assert_eq!(cursor_after(text, (0, 3), "*"), (2, 0));
+
+        let mut editor = CodeEditor::new(text, "txt").with_vim_enabled(true);
+        vim_keys(&mut editor, "*");
+        assert_eq!(editor.vim_state.last_search(), Some(r"\<foo\>"));
+        vim_keys(&mut editor, "n");
+        assert_eq!(editor.cursors.primary_position(), (2, 4));
+
+        assert_eq!(content_after(text, (0, 4), "d*"), "foo bar foo");
+    }
+
+    #[test]
+    fn test_word_search_motions_skip_longer_words() {
+        let text = "foo bar foo foobar
+barfoo foo_x foo";
+        assert_eq!(cursor_after(text, (0, 0), "*"), (0, 8));
+        assert_eq!(cursor_after(text, (0, 0), "2*"), (1, 13));
+        assert_eq!(cursor_after(text, (0, 0), "#"), (1, 13));
+        assert_eq!(cursor_after("foobar foo x foo", (0, 0), "w#"), (0, 13));
+
+        // `n` and `N` keep to whole words, and so does `:s` with the
+        // pattern `*` left behind.
+        let mut editor = CodeEditor::new(text, "txt").with_vim_enabled(true);
+        vim_keys(&mut editor, "*n");
+        assert_eq!(editor.cursors.primary_position(), (1, 13));
+        vim_keys(&mut editor, "NN");
+        assert_eq!(editor.cursors.primary_position(), (0, 0));
+        vim_keys(&mut editor, ":%s//X/g\n");
+        assert_eq!(editor.content(), "X bar X foobar\nbarfoo foo_x X");
+
+        // A search typed with `/` stays plain text.
+        let mut editor = CodeEditor::new(text, "txt").with_vim_enabled(true);
+        vim_keys(&mut editor, "/foo\nn");
+        assert_eq!(editor.cursors.primary_position(), (0, 12));
+    }
+
+    #[test]
+    fn test_word_and_big_word_motions() {
+        let text = "a.b c-d e";
+        assert_eq!(cursor_after(text, (0, 0), "W"), (0, 4));
+        assert_eq!(cursor_after(text, (0, 8), "B"), (0, 4));
+        assert_eq!(cursor_after(text, (0, 8), "2B"), (0, 0));
+        assert_eq!(cursor_after(text, (0, 0), "E"), (0, 2));
+        assert_eq!(cursor_after(text, (0, 8), "ge"), (0, 6));
+        assert_eq!(cursor_after(text, (0, 6), "ge"), (0, 5));
+        assert_eq!(cursor_after(text, (0, 8), "gE"), (0, 6));
+        assert_eq!(cursor_after(text, (0, 6), "gE"), (0, 2));
+        assert_eq!(cursor_after("one\n\ntwo", (2, 1), "ge"), (0, 2));
+
+        assert_eq!(content_after(text, (0, 0), "dW"), "c-d e");
+        assert_eq!(content_after(text, (0, 0), "dE"), " c-d e");
+        assert_eq!(content_after(text, (0, 8), "dge"), "a.b c-");
+        assert_eq!(content_after(text, (0, 8), "d2B"), "e");
+    }
+
+    #[test]
+    fn test_motions_extend_visual_selections() {
+        let mut editor =
+            CodeEditor::new("call(a, b) end", "txt").with_vim_enabled(true);
+        editor.cursors.set_single((0, 4));
+        vim_keys(&mut editor, "v%");
+        assert_eq!(editor.vim_mode(), Some(VimMode::Visual));
+        assert_eq!(
+            editor.cursors.primary().selection_range(),
+            Some(((0, 4), (0, 10)))
+        );
+        vim_keys(&mut editor, "\u{1b}0vt(d");
+        assert_eq!(editor.content(), "(a, b) end");
+    }
+
+    #[test]
+    fn test_scroll_commands_place_the_cursor_line() {
+        let text = vec!["x"; 100].join("\n");
+        let mut editor = CodeEditor::new(&text, "txt").with_vim_enabled(true);
+        let line_height = editor.line_height;
+        editor.viewport_height = line_height * 20.0;
+        editor.cursors.set_single((50, 0));
+
+        assert!(
+            (editor.vim_scroll_offset(VimScroll::Top) - line_height * 48.0)
+                .abs()
+                < 0.5
+        );
+        assert!(
+            (editor.vim_scroll_offset(VimScroll::Bottom) - line_height * 33.0)
+                .abs()
+                < 0.5
+        );
+        assert!(
+            (editor.vim_scroll_offset(VimScroll::Center) - line_height * 40.5)
+                .abs()
+                < 0.5
+        );
+
+        // A count moves the cursor to that line first.
+        vim_keys(&mut editor, "10zt");
+        assert_eq!(editor.cursors.primary_position(), (9, 0));
+        assert!(editor.vim_state.pending_keys().is_empty());
+    }
+}
@@ -0,0 +1,32 @@
+//! Helpers shared by the Vim tests, which drive the editor through the same
+//! messages key presses produce.
+
+use crate::canvas_editor::{CodeEditor, Message};
+
+/// Sends each character of `keys` as a [`Message::VimKey`].
+pub(super) fn vim_keys(editor: &mut CodeEditor, keys: &str) {
+    for key in keys.chars() {
+        let _ = editor.update(&Message::VimKey(key));
+    }
+}
+
+/// Gives the editor keyboard focus, which Insert-mode typing needs.
+pub(super) fn focus_editor(editor: &mut CodeEditor) {
+    editor.request_focus();
+    editor.has_canvas_focus = true;
+    editor.focus_locked = false;
+}
+
+/// Focuses the editor and types `text` as Insert-mode input, pressing
+/// Enter for each `\n`.
+pub(super) fn type_text(editor: &mut CodeEditor, text: &str) {
+    focus_editor(editor);
+    for ch in text.chars() {
+        let message = if ch == '\n' {
+            Message::Enter
+        } else {
+            Message::CharacterInput(ch)
+        };
+        let _ = editor.update(&message);
+    }
+}
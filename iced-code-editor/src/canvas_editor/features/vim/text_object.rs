@@ -0,0 +1,799 @@
+//! Vim text objects (`iw`, `a(`, `i"`, `at`…): the range an operator or
+//! Visual mode selects around the cursor.
+//!
+//! Objects are resolved on the buffer flattened into one character slice
+//! with `'\n'` between lines, the same index the word motions walk (see
+//! [`CodeEditor::char_index`]). Paragraphs are whole lines; every other
+//! object is a character range.
+
+use super::{VimTextObject, VimTextObjectKind};
+use crate::canvas_editor::CodeEditor;
+use crate::canvas_editor::editing::cursor::{WordClass, word_class};
+
+/// The range a text object covers.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum TextObjectRange {
+    /// Characters `[start, end)` of the flattened buffer.
+    Chars(usize, usize),
+    /// Whole lines `start..=end`.
+    Lines(usize, usize),
+}
+
+/// A resolved text object in buffer positions.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct TextObjectSpan {
+    pub(crate) start: (usize, usize),
+    /// Exclusive end position.
+    pub(crate) end: (usize, usize),
+    /// Whether the object covers whole lines (`start.0..=end.0`).
+    pub(crate) linewise: bool,
+}
+
+/// Characters that may follow a sentence's final `.`, `!` or `?`.
+const SENTENCE_CLOSERS: &[char] = &[')', ']', '"', '\''];
+
+/// Resolves `object` around the character at `cursor`, `count` times over:
+/// `count` words, sentences or paragraphs, or the `count`-th enclosing
+/// bracket pair or tag.
+///
+/// # Returns
+///
+/// The covered range, or `None` when the cursor is not inside such an object
+pub(crate) fn resolve(
+    chars: &[char],
+    cursor: usize,
+    object: VimTextObject,
+    count: usize,
+    separators: &str,
+) -> Option<TextObjectRange> {
+    let count = count.max(1);
+    if object.kind == VimTextObjectKind::Paragraph {
+        return Some(paragraph(chars, cursor, count, object.around));
+    }
+    if chars.is_empty() {
+        return None;
+    }
+    let cursor = cursor.min(chars.len() - 1);
+    let range = match object.kind {
+        VimTextObjectKind::Word => {
+            word(chars, cursor, count, object.around, |ch| {
+                word_class(ch, separators)
+            })
+        }
+        VimTextObjectKind::BigWord => {
+            word(chars, cursor, count, object.around, |ch| {
+                if ch.is_whitespace() {
+                    WordClass::Space
+                } else {
+                    WordClass::Word
+                }
+            })
+        }
+        VimTextObjectKind::Sentence => {
+            sentence(chars, cursor, count, object.around)
+        }
+        VimTextObjectKind::Bracket { open, close } => {
+            bracket(chars, cursor, count, open, close, object.around)
+        }
+        VimTextObjectKind::Quote(quote) => {
+            quoted(chars, cursor, quote, object.around)
+        }
+        VimTextObjectKind::Tag => tag(chars, cursor, count, object.around),
+        VimTextObjectKind::Paragraph => None,
+    }?;
+    Some(TextObjectRange::Chars(range.0, range.1))
+}
+
+/// `iw`/`aw` and `iW`/`aW`: runs of characters of one class on the cursor's
+/// line.
+///
+/// The inner object counts the whitespace between words as a run of its
+/// own. The outer one takes `count` words and the whitespace after them, or
+/// before them when nothing follows; started on whitespace, it takes that
+/// whitespace and the `count` words after it.
+fn word<F>(
+    chars: &[char],
+    cursor: usize,
+    count: usize,
+    around: bool,
+    class: F,
+) -> Option<(usize, usize)>
+where
+    F: Fn(char) -> WordClass,
+{
+    if chars[cursor] == '\n' {
+        return None;
+    }
+    let len = chars.len();
+    let at_line_end = |index: usize| index >= len || chars[index] == '\n';
+    let is_space = |index: usize| {
+        !at_line_end(index) && class(chars[index]) == WordClass::Space
+    };
+    let run_end = |index: usize| {
+        let run_class = class(chars[index]);
+        let mut end = index;
+        while !at_line_end(end) && class(chars[end]) == run_class {
+            end += 1;
+        }
+        end
+    };
+
+    let run_class = class(chars[cursor]);
+    let mut start = cursor;
+    while start > 0
+        && chars[start - 1] != '\n'
+        && class(chars[start - 1]) == run_class
+    {
+        start -= 1;
+    }
+    let mut end = run_end(cursor);
+
+    if !around {
+        for _ in 1..count {
+            if at_line_end(end) {
+                break;
+            }
+            end = run_end(end);
+        }
+        return Some((start, end));
+    }
+
+    let started_on_space = run_class == WordClass::Space;
+    let words = if started_on_space { count } else { count - 1 };
+    for _ in 0..words {
+        if is_space(end) {
+            end = run_end(end);
+        }
+        if at_line_end(end) {
+            break;
+        }
+        end = run_end(end);
+    }
+    if started_on_space {
+        return Some((start, end));
+    }
+    if is_space(end) {
+        end = run_end(end);
+    } else {
+        while start > 0 && is_space(start - 1) {
+            start -= 1;
+        }
+    }
+    Some((start, end))
+}
+
+/// Returns where a sentence ending at `index` stops (after its closing
+/// brackets and quotes), when `index` holds a `.`, `!` or `?` followed by
+/// whitespace or the end of the text.
+fn sentence_end_at(chars: &[char], index: usize) -> Option<usize> {
+    if !matches!(chars[index], '.' | '!' | '?') {
+        return None;
+    }
+    let mut end = index + 1;
+    while end < chars.len() && SENTENCE_CLOSERS.contains(&chars[end]) {
+        end += 1;
+    }
+    (end == chars.len() || chars[end].is_whitespace()).then_some(end)
+}
+
+/// Returns whether a blank line starts right after `index`, which ends any
+/// sentence or whitespace run.
+fn paragraph_break_at(chars: &[char], index: usize) -> bool {
+    chars[index] == '\n' && chars.get(index + 1) == Some(&'\n')
+}
+
+/// Splits `chars` into sentences, as `[start, end)` ranges without the
+/// whitespace between them.
+fn sentences(chars: &[char]) -> Vec<(usize, usize)> {
+    let mut spans = Vec::new();
+    let mut index = 0;
+    while index < chars.len() {
+        if chars[index].is_whitespace() {
+            index += 1;
+            continue;
+        }
+        let start = index;
+        let mut end = chars.len();
+        while index < chars.len() {
+            if let Some(stop) = sentence_end_at(chars, index) {
+                end = stop;
+                break;
+            }
+            if paragraph_break_at(chars, index) {
+                end = index;
+                break;
+            }
+            index += 1;
+        }
+        spans.push((start, end));
+        index = end.max(start + 1);
+    }
+    spans
+}
+
+/// Extends `end` over the whitespace after it, stopping at a blank line.
+fn skip_whitespace_forward(chars: &[char], mut end: usize) -> usize {
+    while end < chars.len()
+        && chars[end].is_whitespace()
+        && !paragraph_break_at(chars, end)
+    {
+        end += 1;
+    }
+    end
+}
+
+/// `is`/`as`: sentences ending in `.`, `!` or `?`, or at a blank line.
+///
+/// On the whitespace between two sentences, the inner object is that
+/// whitespace and the outer one adds the sentences after it.
+fn sentence(
+    chars: &[char],
+    cursor: usize,
+    count: usize,
+    around: bool,
+) -> Option<(usize, usize)> {
+    let spans = sentences(chars);
+    let next = spans.partition_point(|(start, _)| *start <= cursor);
+    let inside = next.checked_sub(1).filter(|index| cursor < spans[*index].1);
+
+    let Some(first) = inside else {
+        // Between sentences: the gap, plus sentences for the outer object.
+        let gap_start = next.checked_sub(1).map_or(0, |index| spans[index].1);
+        let gap_end = spans.get(next).map_or(chars.len(), |span| span.0);
+        if !around {
+            return Some((gap_start, gap_end));
+        }
+        let last = (next + count - 1).min(spans.len().checked_sub(1)?);
+        return Some((gap_start, spans.get(last)?.1.max(gap_end)));
+    };
+
+    let last = (first + count - 1).min(spans.len() - 1);
+    let start = spans[first].0;
+    let end = spans[last].1;
+    if !around {
+        return Some((start, end));
+    }
+    let trailing = skip_whitespace_forward(chars, end);
+    if trailing > end {
+        return Some((start, trailing));
+    }
+    let mut leading = start;
+    while leading > 0
+        && chars[leading - 1].is_whitespace()
+        && !paragraph_break_at(chars, leading - 1)
+    {
+        leading -= 1;
+    }
+    Some((leading, end))
+}
+
+/// `ip`/`ap`: runs of non-blank lines, or of blank ones.
+///
+/// The outer object adds the blank lines after the paragraph, or before it
+/// when none follow; started on blank lines, it adds the paragraph after
+/// them.
+fn paragraph(
+    chars: &[char],
+    cursor: usize,
+    count: usize,
+    around: bool,
+) -> TextObjectRange {
+    let blank: Vec<bool> = chars
+        .split(|ch| *ch == '\n')
+        .map(|line| line.iter().all(|ch| ch.is_whitespace()))
+        .collect();
+    let last = blank.len() - 1;
+    let line =
+        chars.iter().take(cursor).filter(|ch| **ch == '\n').count().min(last);
+    let run_end = |from: usize| {
+        let mut end = from;
+        while end < last && blank[end + 1] == blank[from] {
+            end += 1;
+        }
+        end
+    };
+    let mut start = line;
+    while start > 0 && blank[start - 1] == blank[line] {
+        start -= 1;
+    }
+    let mut end = run_end(line);
+
+    if !around {
+        for _ in 1..count {
+            if end == last {
+                break;
+            }
+            end = run_end(end + 1);
+        }
+        return TextObjectRange::Lines(start, end);
+    }
+
+    // Each step takes the next run; the outer object spans `2 * count` runs.
+    for _ in 1..count * 2 {
+        if end == last {
+            break;
+        }
+        end = run_end(end + 1);
+    }
+    if !blank[line] && !blank[end] && start > 0 {
+        // No blank lines after the paragraph: take the ones before it.
+        while start > 0 && blank[start - 1] {
+            start -= 1;
+        }
+    }
+    TextObjectRange::Lines(start, end)
+}
+
+/// Returns the innermost unmatched `open` at or before `from`.
+fn unmatched_open(
+    chars: &[char],
+    from: usize,
+    open: char,
+    close: char,
+) -> Option<usize> {
+    let mut depth = 0usize;
+    for index in (0..=from).rev() {
+        if chars[index] == close {
+            depth += 1;
+        } else if chars[index] == open {
+            if depth == 0 {
+                return Some(index);
+            }
+            depth -= 1;
+        }
+    }
+    None
+}
+
+/// Returns the `close` matching the `open` at `open_at`.
+fn matching_close(
+    chars: &[char],
+    open_at: usize,
+    open: char,
+    close: char,
+) -> Option<usize> {
+    let mut depth = 0usize;
+    for (index, ch) in chars.iter().enumerate().skip(open_at + 1) {
+        if *ch == open {
+            depth += 1;
+        } else if *ch == close {
+            if depth == 0 {
+                return Some(index);
+            }
+            depth -= 1;
+        }
+    }
+    None
+}
+
+/// `i(`/`a(` and the other bracket kinds: the `count`-th pair enclosing the
+/// cursor, a bracket under the cursor counting as enclosing it.
+///
+/// When the opening bracket ends its line and the closing one starts its
+/// own, the inner object is the whole lines between them, so `di{` on a
+/// block leaves `{` and `}` on adjacent lines.
+fn bracket(
+    chars: &[char],
+    cursor: usize,
+    count: usize,
+    open: char,
+    close: char,
+    around: bool,
+) -> Option<(usize, usize)> {
+    let mut open_at = if chars[cursor] == open {
+        cursor
+    } else {
+        unmatched_open(chars, cursor.checked_sub(1)?, open, close)?
+    };
+    for _ in 1..count {
+        open_at = unmatched_open(chars, open_at.checked_sub(1)?, open, close)?;
+    }
+    let close_at = matching_close(chars, open_at, open, close)?;
+    if around {
+        return Some((open_at, close_at + 1));
+    }
+
+    let mut start = open_at + 1;
+    if chars.get(start) == Some(&'\n') {
+        start += 1;
+    }
+    let mut end = close_at;
+    let mut line_start = close_at;
+    while line_start > start && matches!(chars[line_start - 1], ' ' | '\t') {
+        line_start -= 1;
+    }
+    if line_start > start
+        && chars[line_start - 1] == '\n'
+        && start > open_at + 1
+    {
+        end = line_start;
+    }
+    Some((start.min(end), end))
+}
+
+/// `i"`/`a"` and the other quotes: a quoted string on the cursor's line.
+///
+/// Quotes pair up from the start of the line, skipping escaped ones. The
+/// string is the one around the cursor, or else the first one after it.
+/// The outer object takes the whitespace after the closing quote, or
+/// before the opening one when none follows.
+fn quoted(
+    chars: &[char],
+    cursor: usize,
+    quote: char,
+    around: bool,
+) -> Option<(usize, usize)> {
+    let line_start = chars[..cursor]
+        .iter()
+        .rposition(|ch| *ch == '\n')
+        .map_or(0, |index| index + 1);
+    let line_end = chars[cursor..]
+        .iter()
+        .position(|ch| *ch == '\n')
+        .map_or(chars.len(), |index| cursor + index);
+
+    let mut quotes = Vec::new();
+    let mut escaped = false;
+    for (index, ch) in chars.iter().enumerate().take(line_end).skip(line_start)
+    {
+        if escaped {
+            escaped = false;
+        } else if *ch == '\\' {
+            escaped = true;
+        } else if *ch == quote {
+            quotes.push(index);
+        }
+    }
+    let (open_at, close_at) = quotes
+        .chunks_exact(2)
+        .map(|pair| (pair[0], pair[1]))
+        .find(|(_, close_at)| cursor <= *close_at)?;
+
+    if !around {
+        return Some((open_at + 1, close_at));
+    }
+    let mut start = open_at;
+    let mut end = close_at + 1;
+    while end < line_end && matches!(chars[end], ' ' | '\t') {
+        end += 1;
+    }
+    if end == close_at + 1 {
+        while start > line_start && matches!(chars[start - 1], ' ' | '\t') {
+            start -= 1;
+        }
+    }
+    Some((start, end))
+}
+
+/// An XML/HTML tag: `[start, end)` and its name.
+struct TagToken {
+    start: usize,
+    end: usize,
+    name: String,
+    closing: bool,
+}
+
+/// Scans `chars` for opening and closing tags, skipping comments and
+/// self-closing tags.
+fn tags(chars: &[char]) -> Vec<TagToken> {
+    let mut tokens = Vec::new();
+    let mut index = 0;
+    while index < chars.len() {
+        if chars[index] != '<' {
+            index += 1;
+            continue;
+        }
+        if chars[index..].starts_with(&['<', '!', '-', '-']) {
+            index = chars[index..]
+                .windows(3)
+                .position(|window| window == ['-', '-', '>'])
+                .map_or(chars.len(), |offset| index + offset + 3);
+            continue;
+        }
+        let start = index;
+        let mut cursor = index + 1;
+        let closing = chars.get(cursor) == Some(&'/');
+        if closing {
+            cursor += 1;
+        }
+        let name: String = chars[cursor..]
+            .iter()
+            .take_while(|ch| {
+                ch.is_alphanumeric() || matches!(**ch, '-' | '_' | ':' | '.')
+            })
+            .collect();
+        if name.is_empty() {
+            index += 1;
+            continue;
+        }
+        let Some(offset) = chars[cursor..].iter().position(|ch| *ch == '>')
+        else {
+            break;
+        };
+        let end = cursor + offset + 1;
+        if chars[end - 2] != '/' {
+            tokens.push(TagToken { start, end, name, closing });
+        }
+        index = end;
+    }
+    tokens
+}
+
+/// `it`/`at`: the `count`-th element enclosing the cursor.
+fn tag(
+    chars: &[char],
+    cursor: usize,
+    count: usize,
+    around: bool,
+) -> Option<(usize, usize)> {
+    let mut open: Vec<TagToken> = Vec::new();
+    let mut enclosing = Vec::new();
+    for token in tags(chars) {
+        if !token.closing {
+            open.push(token);
+            continue;
+        }
+        // Drop unclosed tags (`<br>`, `<li>`…) until the matching opener.
+        let Some(position) =
+            open.iter().rposition(|candidate| candidate.name == token.name)
+        else {
+            continue;
+        };
+        open.truncate(position + 1);
+        let Some(opener) = open.pop() else {
+            continue;
+        };
+        if opener.start <= cursor && cursor < token.end {
+            enclosing.push((opener.start, opener.end, token.start, token.end));
+        }
+    }
+    // Inner elements close first, so `enclosing` runs from the innermost.
+    let (outer_start, inner_start, inner_end, outer_end) =
+        *enclosing.get(count - 1)?;
+    Some(if around {
+        (outer_start, outer_end)
+    } else {
+        (inner_start, inner_end)
+    })
+}
+
+impl CodeEditor {
+    /// Resolves a Vim text object around `position`.
+    ///
+    /// # Returns
+    ///
+    /// The object's span, or `None` when there is no such object there
+    pub(crate) fn vim_text_object_range(
+        &self,
+        position: (usize, usize),
+        object: VimTextObject,
+        count: usize,
+    ) -> Option<TextObjectSpan> {
+        let index = self.char_index();
+        let chars: Vec<char> = index.iter().map(|(_, ch)| *ch).collect();
+        let cursor = index.partition_point(|(at, _)| *at < position);
+        let to_position = |offset: usize| {
+            index.get(offset).map_or_else(
+                || {
+                    let line = self.buffer.line_count().saturating_sub(1);
+                    (line, self.buffer.line_len(line))
+                },
+                |(at, _)| *at,
+            )
+        };
+        Some(
+            match resolve(&chars, cursor, object, count, &self.word_separators)?
+            {
+                TextObjectRange::Chars(start, end) => TextObjectSpan {
+                    start: to_position(start),
+                    end: to_position(end),
+                    linewise: false,
+                },
+                TextObjectRange::Lines(start, end) => TextObjectSpan {
+                    start: (start, 0),
+                    end: (end, self.buffer.line_len(end)),
+                    linewise: true,
+                },
+            },
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::canvas_editor::editing::cursor::DEFAULT_WORD_SEPARATORS;
+
+    fn object(kind: VimTextObjectKind, around: bool) -> VimTextObject {
+        VimTextObject { kind, around }
+    }
+
+    /// Resolves `object` at the `|` marking the cursor in `text`, and
+    /// returns the covered text.
+    fn covered(
+        text: &str,
+        object: VimTextObject,
+        count: usize,
+    ) -> Option<String> {
+        let cursor = text.find('|')?;
+        let text = text.replace('|', "");
+        let chars: Vec<char> = text.chars().collect();
+        let cursor = text[..cursor].chars().count();
+        match resolve(&chars, cursor, object, count, DEFAULT_WORD_SEPARATORS)? {
+            TextObjectRange::Chars(start, end) => {
+                Some(chars[start..end].iter().collect())
+            }
+            TextObjectRange::Lines(start, end) => Some(
+                text.split('\n')
+                    .skip(start)
+                    .take(end - start + 1)
+                    .collect::<Vec<_>>()
+                    .join("\n"),
+            ),
+        }
+    }
+
+    #[test]
+    fn test_words_and_big_words() {
+        let word = VimTextObjectKind::Word;
+        let big = VimTextObjectKind::BigWord;
+        let text = "let fo|o.bar = 1;";
+        assert_eq!(
+            covered(text, object(word, false), 1).as_deref(),
+            Some("foo")
+        );
+        assert_eq!(
+            covered(text, object(word, false), 3).as_deref(),
+            Some("foo.bar")
+        );
+        assert_eq!(
+            covered(text, object(big, false), 1).as_deref(),
+            Some("foo.bar")
+        );
+        assert_eq!(
+            covered(text, object(big, true), 1).as_deref(),
+            Some("foo.bar ")
+        );
+        assert_eq!(
+            covered("one tw|o", object(word, true), 1).as_deref(),
+            Some(" two")
+        );
+        assert_eq!(
+            covered("a| b c d", object(word, true), 2).as_deref(),
+            Some(" b c")
+        );
+        assert_eq!(
+            covered("|one two three", object(word, true), 2).as_deref(),
+            Some("one two ")
+        );
+    }
+
+    #[test]
+    fn test_sentences() {
+        let text = "First one. Sec|ond (really)! Third?";
+        let kind = VimTextObjectKind::Sentence;
+        assert_eq!(
+            covered(text, object(kind, false), 1).as_deref(),
+            Some("Second (really)!")
+        );
+        assert_eq!(
+            covered(text, object(kind, true), 1).as_deref(),
+            Some("Second (really)! ")
+        );
+        assert_eq!(
+            covered("One. Tw|o.", object(kind, true), 1).as_deref(),
+            Some(" Two.")
+        );
+        assert_eq!(
+            covered("Head|ing\n\nBody.", object(kind, false), 1).as_deref(),
+            Some("Heading")
+        );
+    }
+
+    #[test]
+    fn test_paragraphs_are_whole_lines() {
+        let text = "a\nb|\n\n\nc\nd";
+        let kind = VimTextObjectKind::Paragraph;
+        assert_eq!(
+            covered(text, object(kind, false), 1).as_deref(),
+            Some("a\nb")
+        );
+        assert_eq!(
+            covered(text, object(kind, true), 1).as_deref(),
+            Some("a\nb\n\n")
+        );
+        assert_eq!(
+            covered("a\n\nb\nc|", object(kind, true), 1).as_deref(),
+            Some("\nb\nc")
+        );
+        assert_eq!(
+            covered(text, object(kind, false), 3).as_deref(),
+            Some("a\nb\n\n\nc\nd")
+        );
+    }
+
+    #[test]
+    fn test_brackets_nest_and_count() {
+        let paren = VimTextObjectKind::Bracket { open: '(', close: ')' };
+        let text = "f(a, g(b|, c), d)";
+        assert_eq!(
+            covered(text, object(paren, false), 1).as_deref(),
+            Some("b, c")
+        );
+        assert_eq!(
+            covered(text, object(paren, true), 1).as_deref(),
+            Some("(b, c)")
+        );
+        assert_eq!(
+            covered(text, object(paren, false), 2).as_deref(),
+            Some("a, g(b, c), d")
+        );
+        assert_eq!(
+            covered("|(x)", object(paren, false), 1).as_deref(),
+            Some("x")
+        );
+        assert_eq!(
+            covered("(x|)", object(paren, true), 1).as_deref(),
+            Some("(x)")
+        );
+        assert_eq!(covered("x| (y)", object(paren, false), 1), None);
+
+        let brace = VimTextObjectKind::Bracket { open: '{', close: '}' };
+        assert_eq!(
+            covered("fn a() {\n    b|();\n}", object(brace, false), 1)
+                .as_deref(),
+            Some("    b();\n")
+        );
+        assert_eq!(
+            covered("{|}", object(brace, false), 1).as_deref(),
+            Some("")
+        );
+    }
+
+    #[test]
+    fn test_quotes_pair_up_on_the_line() {
+        let kind = VimTextObjectKind::Quote('"');
+        let text = r#"say("a \"b\" c|", "d")"#;
+        assert_eq!(
+            covered(text, object(kind, false), 1).as_deref(),
+            Some(r#"a \"b\" c"#)
+        );
+        assert_eq!(
+            covered("x = |\"one\" + \"two\"", object(kind, true), 1).as_deref(),
+            Some("\"one\" ")
+        );
+        assert_eq!(
+            covered(
+                "|x = 'a'",
+                object(VimTextObjectKind::Quote('\''), false),
+                1
+            )
+            .as_deref(),
+            Some("a")
+        );
+        assert_eq!(covered("no| quotes", object(kind, false), 1), None);
+    }
+
+    #[test]
+    fn test_tags_skip_void_and_self_closing_elements() {
+        let kind = VimTextObjectKind::Tag;
+        let text = "<div class=\"x\"><p>Hi <b>th|ere</b><br><img/></p></div>";
+        assert_eq!(
+            covered(text, object(kind, false), 1).as_deref(),
+            Some("there")
+        );
+        assert_eq!(
+            covered(text, object(kind, true), 1).as_deref(),
+            Some("<b>there</b>")
+        );
+        assert_eq!(
+            covered(text, object(kind, false), 2).as_deref(),
+            Some("Hi <b>there</b><br><img/>")
+        );
+        assert_eq!(
+            covered(text, object(kind, true), 3).as_deref(),
+            Some(text.replace('|', "").as_str())
+        );
+        assert_eq!(covered(text, object(kind, true), 4), None);
+    }
+}
@@ -0,0 +1,515 @@
+//! Vim operators that rewrite text in place rather than through a register:
+//! case changes (`gu`, `gU`, `g~`, `~`), shifts (`>`, `<`), re-indenting
+//! (`=`) and joins (`J`, `gJ`), plus `r{char}`, Replace mode (`R`) and
+//! `Ctrl+A` / `Ctrl+X`.
+//!
+//! Case changes act on the characters a motion or selection covers; the
+//! other operators act on every line it touches. Each rewrite goes through
+//! one [`ReplaceLinesCommand`](crate::canvas_editor::editing::command::ReplaceLinesCommand),
+//! so it undoes as one step.
+
+use iced::Task;
+
+use super::VimOperator;
+use super::block::splice;
+use crate::canvas_editor::editing::indentation::{
+    indent_line, leading_len, outdent_line,
+};
+use crate::canvas_editor::editing::transform::{
+    convert_case, increment_number, join_lines,
+};
+use crate::canvas_editor::{CodeEditor, Message, TextCase};
+
+/// Returns `text` with the case change of `operator` applied; any other
+/// operator switches the case of every letter.
+fn change_case(operator: VimOperator, text: &str) -> String {
+    match operator {
+        VimOperator::Lowercase => convert_case(text, TextCase::Lower),
+        VimOperator::Uppercase => convert_case(text, TextCase::Upper),
+        _ => {
+            let mut toggled = String::with_capacity(text.len());
+            for ch in text.chars() {
+                if ch.is_uppercase() {
+                    toggled.extend(ch.to_lowercase());
+                } else {
+                    toggled.extend(ch.to_uppercase());
+                }
+            }
+            toggled
+        }
+    }
+}
+
+impl CodeEditor {
+    /// Returns whether typed characters overwrite the text (Replace mode).
+    pub(crate) fn vim_replacing(&self) -> bool {
+        self.vim_enabled && self.vim_state.replacing()
+    }
+
+    /// Applies an in-place operator to the characters `[start, end)`: case
+    /// changes rewrite just those characters, the line operators every line
+    /// the range touches.
+    pub(super) fn handle_vim_transform_range(
+        &mut self,
+        operator: VimOperator,
+        start: (usize, usize),
+        end: (usize, usize),
+    ) -> Task<Message> {
+        if !matches!(
+            operator,
+            VimOperator::Lowercase
+                | VimOperator::Uppercase
+                | VimOperator::ToggleCase
+        ) {
+            // A range ending at the start of a line does not touch it.
+            let end_line =
+                if end.1 == 0 && end.0 > start.0 { end.0 - 1 } else { end.0 };
+            return self
+                .handle_vim_transform_lines(operator, start.0, end_line);
+        }
+
+        if start != end {
+            let first = self.buffer.line(start.0);
+            let mut text: String = first.chars().take(start.1).collect();
+            text.push_str(&change_case(
+                operator,
+                &self.extract_text_range(start, end),
+            ));
+            text.extend(self.buffer.line(end.0).chars().skip(end.1));
+            let new_lines = text.split('\n').map(str::to_owned).collect();
+            self.end_grouping_if_active();
+            self.rewrite_vim_lines(
+                start.0,
+                end.0 - start.0 + 1,
+                new_lines,
+                start,
+            );
+        }
+        self.cursors.set_single(self.vim_normal_position(start));
+        self.vim_state.enter_clean_normal_mode();
+        self.scroll_to_cursor()
+    }
+
+    /// Applies an in-place operator to lines `start_line..=end_line`. A join
+    /// of one line joins it with the next; it fails on the last line.
+    pub(super) fn handle_vim_transform_lines(
+        &mut self,
+        operator: VimOperator,
+        start_line: usize,
+        end_line: usize,
+    ) -> Task<Message> {
+        let last_line = self.buffer.line_count().saturating_sub(1);
+        let start_line = start_line.min(last_line);
+        let mut end_line = end_line.min(last_line).max(start_line);
+        self.end_grouping_if_active();
+
+        if operator == VimOperator::Reindent {
+            self.cursors.remove_all_but_primary();
+            self.reindent_range(start_line, end_line);
+            let indent = leading_len(self.buffer.line(start_line));
+            self.cursors
+                .set_single(self.vim_normal_position((start_line, indent)));
+            self.vim_state.enter_clean_normal_mode();
+            self.finish_edit_operation();
+            return self.scroll_to_cursor();
+        }
+
+        if matches!(operator, VimOperator::Join { .. })
+            && end_line == start_line
+        {
+            if start_line == last_line {
+                self.vim_state.fail_motion();
+                self.vim_state.enter_clean_normal_mode();
+                return Task::none();
+            }
+            end_line += 1;
+        }
+
+        let lines: Vec<String> = (start_line..=end_line)
+            .map(|line| self.buffer.line(line).to_owned())
+            .collect();
+        let (new_lines, cursor) = match operator {
+            VimOperator::Join { spaces } => {
+                let (joined, join_col) = if spaces {
+                    let (joined, layout) = join_lines(&lines);
+                    let col = layout.last().map_or(0, |(col, _)| *col);
+                    (joined, col)
+                } else {
+                    let col = lines
+                        .iter()
+                        .rev()
+                        .skip(1)
+                        .map(|line| line.chars().count())
+                        .sum();
+                    (lines.concat(), col)
+                };
+                // The cursor lands just before the last joined line's text.
+                (vec![joined], (start_line, join_col.saturating_sub(1)))
+            }
+            VimOperator::ShiftRight | VimOperator::ShiftLeft => {
+                let shifted: Vec<String> = lines
+                    .iter()
+                    .map(|line| {
+                        if operator == VimOperator::ShiftRight {
+                            indent_line(line, self.indent_style).0
+                        } else {
+                            outdent_line(line, self.indent_style).0
+                        }
+                    })
+                    .collect();
+                let indent =
+                    shifted.first().map_or(0, |line| leading_len(line));
+                (shifted, (start_line, indent))
+            }
+            _ => {
+                let position = self.cursors.primary_position();
+                let cursor = if (start_line..=end_line).contains(&position.0) {
+                    position
+                } else {
+                    (start_line, 0)
+                };
+                let changed = lines
+                    .iter()
+                    .map(|line| change_case(operator, line))
+                    .collect();
+                (changed, cursor)
+            }
+        };
+
+        self.rewrite_vim_lines(start_line, lines.len(), new_lines, cursor);
+        self.cursors.set_single(self.vim_normal_position(cursor));
+        self.vim_state.enter_clean_normal_mode();
+        self.scroll_to_cursor()
+    }
+
+    /// Applies an in-place operator to the Visual selection: case changes to
+    /// the selected characters of each line, the line operators to every
+    /// selected line. Case changes return the cursor to the selection's
+    /// start.
+    pub(super) fn handle_vim_visual_transform(
+        &mut self,
+        operator: VimOperator,
+    ) -> Task<Message> {
+        let Some(mut spans) = self.vim_visual_spans() else {
+            return Task::none();
+        };
+        let (Some(&(top, left, _)), Some(&(bottom, ..))) =
+            (spans.first(), spans.last())
+        else {
+            self.vim_state.enter_clean_normal_mode();
+            return Task::none();
+        };
+        if !matches!(
+            operator,
+            VimOperator::Lowercase
+                | VimOperator::Uppercase
+                | VimOperator::ToggleCase
+        ) {
+            let bottom = if bottom > top
+                && spans.last().is_some_and(|&(_, start, end)| start == end)
+            {
+                bottom - 1
+            } else {
+                bottom
+            };
+            return self.handle_vim_transform_lines(operator, top, bottom);
+        }
+
+        let mut new_lines: Vec<String> = (top..=bottom)
+            .map(|line| self.buffer.line(line).to_owned())
+            .collect();
+        for (line, start, end) in spans.drain(..) {
+            if let Some(text) = new_lines.get_mut(line - top) {
+                let selected: String =
+                    text.chars().skip(start).take(end - start).collect();
+                *text =
+                    splice(text, start, end, &change_case(operator, &selected));
+            }
+        }
+        self.end_grouping_if_active();
+        let old_len = new_lines.len();
+        let corner = self.vim_normal_position((top, left));
+        self.rewrite_vim_lines(top, old_len, new_lines, corner);
+        self.vim_state.enter_clean_normal_mode();
+        self.scroll_to_cursor()
+    }
+
+    /// Replaces `count` characters from the cursor with `ch` (`r{char}`),
+    /// leaving the cursor on the last one. Fails when the line is too short.
+    pub(super) fn handle_vim_replace_characters(
+        &mut self,
+        ch: char,
+        count: usize,
+    ) -> Task<Message> {
+        let (line, col) =
+            self.vim_normal_position(self.cursors.primary_position());
+        let end = col.saturating_add(count);
+        if end > self.buffer.line_len(line) {
+            self.vim_state.fail_motion();
+            return Task::none();
+        }
+        let text = splice(
+            self.buffer.line(line),
+            col,
+            end,
+            &ch.to_string().repeat(count),
+        );
+        self.end_grouping_if_active();
+        self.rewrite_vim_lines(line, 1, vec![text], (line, end - 1));
+        self.scroll_to_cursor()
+    }
+
+    /// Switches the case of `count` characters from the cursor and moves
+    /// past them, stopping on the line's last character (`~`).
+    pub(super) fn handle_vim_toggle_characters(
+        &mut self,
+        count: usize,
+    ) -> Task<Message> {
+        let (line, col) =
+            self.vim_normal_position(self.cursors.primary_position());
+        let text = self.buffer.line(line);
+        let end = col.saturating_add(count).min(text.chars().count());
+        if col >= end {
+            return Task::none();
+        }
+        let selected: String = text.chars().skip(col).take(end - col).collect();
+        let toggled = change_case(VimOperator::ToggleCase, &selected);
+        let cursor = (line, col + toggled.chars().count());
+        let text = splice(text, col, end, &toggled);
+        let cursor =
+            (line, cursor.1.min(text.chars().count().saturating_sub(1)));
+        self.end_grouping_if_active();
+        self.rewrite_vim_lines(line, 1, vec![text], cursor);
+        self.scroll_to_cursor()
+    }
+
+    /// Adds `count` to the number at or after the cursor, or subtracts it,
+    /// leaving the cursor on the number's last digit (`Ctrl+A`, `Ctrl+X`).
+    /// In Visual modes, changes the first number in each line's part of the
+    /// selection and returns the cursor to its start. Fails when there is no
+    /// number to change.
+    pub(super) fn handle_vim_add_to_number(
+        &mut self,
+        count: usize,
+        subtract: bool,
+    ) -> Task<Message> {
+        let amount = i64::try_from(count).unwrap_or(i64::MAX);
+        let delta = if subtract { -amount } else { amount };
+        let visual = self.vim_visual_spans();
+        let spans = visual.clone().unwrap_or_else(|| {
+            let (line, col) =
+                self.vim_normal_position(self.cursors.primary_position());
+            vec![(line, col, usize::MAX)]
+        });
+        let (Some(&(top, left, _)), Some(&(bottom, ..))) =
+            (spans.first(), spans.last())
+        else {
+            return Task::none();
+        };
+
+        let mut new_lines: Vec<String> = (top..=bottom)
+            .map(|line| self.buffer.line(line).to_owned())
+            .collect();
+        let mut cursor = None;
+        for (line, start, end) in spans {
+            let Some(text) = new_lines.get_mut(line - top) else {
+                continue;
+            };
+            let Some((from, to, replacement)) =
+                increment_number(text, start, delta)
+                    .filter(|(from, ..)| *from < end)
+            else {
+                continue;
+            };
+            let last = from + replacement.chars().count().saturating_sub(1);
+            *text = splice(text, from, to, &replacement);
+            cursor = Some((line, last));
+        }
+        let Some(cursor) = cursor else {
+            self.vim_state.fail_motion();
+            if visual.is_some() {
+                self.vim_state.enter_clean_normal_mode();
+            }
+            return Task::none();
+        };
+        let cursor = if visual.is_some() { (top, left) } else { cursor };
+        self.end_grouping_if_active();
+        self.rewrite_vim_lines(top, new_lines.len(), new_lines, cursor);
+        self.vim_state.enter_clean_normal_mode();
+        self.scroll_to_cursor()
+    }
+
+    /// Enters Replace mode (`R`), which undoes as one step like an Insert
+    /// session.
+    pub(super) fn handle_vim_replace_mode(&mut self) -> Task<Message> {
+        self.end_grouping_if_active();
+        self.ensure_grouping_started();
+        let position =
+            self.vim_normal_position(self.cursors.primary_position());
+        self.cursors.set_single(position);
+        self.vim_state.enter_replace_mode();
+        self.finish_navigation_operation();
+        self.scroll_to_cursor()
+    }
+
+    /// Handles a character typed in Replace mode: it overwrites the
+    /// character under the cursor, or extends the line at its end.
+    pub(crate) fn handle_vim_replace_input(
+        &mut self,
+        ch: char,
+    ) -> Task<Message> {
+        if ch.is_control() {
+            return Task::none();
+        }
+        let (line, col) = self.cursors.primary_position();
+        let text = self.buffer.line(line);
+        let original = text.chars().nth(col);
+        let end = if original.is_some() { col + 1 } else { col };
+        let text = splice(text, col, end, &ch.to_string());
+        self.rewrite_vim_lines(line, 1, vec![text], (line, col + 1));
+        self.vim_state.push_replaced((line, col + 1), original);
+        self.scroll_to_cursor()
+    }
+
+    /// Handles Backspace in Replace mode: it restores the character the
+    /// last typed one overwrote, or just moves left past text that was not
+    /// typed in this session.
+    pub(crate) fn handle_vim_replace_backspace(&mut self) -> Task<Message> {
+        let (line, col) = self.cursors.primary_position();
+        let Some(original) = self.vim_state.pop_replaced((line, col)) else {
+            self.cursors.set_single((line, col.saturating_sub(1)));
+            self.finish_navigation_operation();
+            return self.scroll_to_cursor();
+        };
+        let restored = original.map(String::from).unwrap_or_default();
+        let col = col.saturating_sub(1);
+        let text = splice(self.buffer.line(line), col, col + 1, &restored);
+        self.rewrite_vim_lines(line, 1, vec![text], (line, col));
+        self.scroll_to_cursor()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::canvas_editor::features::vim::test_support::{
+        type_text, vim_keys,
+    };
+    use crate::canvas_editor::{IndentStyle, VimMode};
+
+    #[test]
+    fn test_case_operators_take_motions_text_objects_and_selections() {
+        let mut editor = CodeEditor::new("hello World foo\nbar", "txt")
+            .with_vim_enabled(true);
+        vim_keys(&mut editor, "gUw");
+        assert_eq!(editor.content(), "HELLO World foo\nbar");
+        assert_eq!(editor.cursors.primary_position(), (0, 0));
+
+        vim_keys(&mut editor, "wg~iw");
+        assert_eq!(editor.content(), "HELLO wORLD foo\nbar");
+        vim_keys(&mut editor, "guu");
+        assert_eq!(editor.content(), "hello world foo\nbar");
+
+        // `~` switches the case under the cursor and moves on.
+        vim_keys(&mut editor, "0");
+        vim_keys(&mut editor, "3~");
+        assert_eq!(editor.content(), "HELlo world foo\nbar");
+        assert_eq!(editor.cursors.primary_position(), (0, 3));
+
+        vim_keys(&mut editor, "wvjU");
+        assert_eq!(editor.content(), "HELlo WORLD FOO\nBAR");
+        assert_eq!(editor.vim_mode(), Some(VimMode::Normal));
+        assert_eq!(editor.cursors.primary_position(), (0, 6));
+
+        let _ = editor.update(&Message::Undo);
+        assert_eq!(editor.content(), "HELlo world foo\nbar");
+    }
+
+    #[test]
+    fn test_shift_reindent_and_join_act_on_lines() {
+        let mut editor =
+            CodeEditor::new("a\n  b\nc", "txt").with_vim_enabled(true);
+        editor.set_indent_style(IndentStyle::Spaces(2));
+        vim_keys(&mut editor, ">j");
+        assert_eq!(editor.content(), "  a\n    b\nc");
+        assert_eq!(editor.cursors.primary_position(), (0, 2));
+        vim_keys(&mut editor, "j<<");
+        assert_eq!(editor.content(), "  a\n  b\nc");
+        vim_keys(&mut editor, "Vj>");
+        assert_eq!(editor.content(), "  a\n    b\n  c");
+
+        vim_keys(&mut editor, "ggJ");
+        assert_eq!(editor.content(), "  a b\n  c");
+        assert_eq!(editor.cursors.primary_position(), (0, 3));
+        vim_keys(&mut editor, "gJ");
+        assert_eq!(editor.content(), "  a b  c");
+        // Joining the last line fails.
+        vim_keys(&mut editor, "J");
+        assert_eq!(editor.content(), "  a b  c");
+
+        let mut editor =
+            CodeEditor::new("fn a() {\nb();\n}", "rs").with_vim_enabled(true);
+        vim_keys(&mut editor, "=G");
+        assert_eq!(editor.content(), "fn a() {\n    b();\n}");
+        assert_eq!(editor.cursors.primary_position(), (0, 0));
+    }
+
+    #[test]
+    fn test_replace_characters_and_replace_mode() {
+        let mut editor = CodeEditor::new("abcd", "txt").with_vim_enabled(true);
+        vim_keys(&mut editor, "3rx");
+        assert_eq!(editor.content(), "xxxd");
+        assert_eq!(editor.cursors.primary_position(), (0, 2));
+        // Too few characters left: nothing is replaced.
+        vim_keys(&mut editor, "5ry");
+        assert_eq!(editor.content(), "xxxd");
+
+        vim_keys(&mut editor, "0R");
+        type_text(&mut editor, "12345");
+        assert_eq!(editor.content(), "12345");
+        // Backspace restores what was overwritten, removing what extended
+        // the line.
+        let _ = editor.update(&Message::Backspace);
+        let _ = editor.update(&Message::Backspace);
+        assert_eq!(editor.content(), "123d");
+        vim_keys(&mut editor, "\u{1b}");
+        assert_eq!(editor.cursors.primary_position(), (0, 2));
+
+        let _ = editor.update(&Message::Undo);
+        assert_eq!(editor.content(), "xxxd");
+        vim_keys(&mut editor, "0.");
+        assert_eq!(editor.content(), "123d");
+    }
+
+    #[test]
+    fn test_control_a_and_x_change_numbers() {
+        let mut editor =
+            CodeEditor::new("x = 9;\ny = -2 + 7", "txt").with_vim_enabled(true);
+        vim_keys(&mut editor, "\u{1}");
+        assert_eq!(editor.content(), "x = 10;\ny = -2 + 7");
+        assert_eq!(editor.cursors.primary_position(), (0, 5));
+        vim_keys(&mut editor, "j05\u{18}");
+        assert_eq!(editor.content(), "x = 10;\ny = -7 + 7");
+        vim_keys(&mut editor, ".");
+        assert_eq!(editor.content(), "x = 10;\ny = -12 + 7");
+
+        // In Visual modes, the first number of each line changes.
+        vim_keys(&mut editor, "ggVj\u{1}");
+        assert_eq!(editor.content(), "x = 11;\ny = -11 + 7");
+        assert_eq!(editor.vim_mode(), Some(VimMode::Normal));
+    }
+
+    #[test]
+    fn test_control_a_and_x_change_hex_and_binary_numbers() {
+        let mut editor = CodeEditor::new("c = 0xff\nm = 0b0111", "txt")
+            .with_vim_enabled(true);
+        vim_keys(&mut editor, "\u{1}");
+        assert_eq!(editor.content(), "c = 0x100\nm = 0b0111");
+        assert_eq!(editor.cursors.primary_position(), (0, 8));
+        vim_keys(&mut editor, "j\u{1}");
+        assert_eq!(editor.content(), "c = 0x100\nm = 0b1000");
+        // With the cursor on the prefix, the number still changes whole.
+        vim_keys(&mut editor, "k05l\u{18}");
+        assert_eq!(editor.content(), "c = 0x0ff\nm = 0b1000");
+    }
+}
@@ -3,9 +3,10 @@
 
 use iced::Task;
 
+use super::text_object::TextObjectSpan;
 use super::{
     VimAction, VimInsertPosition, VimMode, VimMotion, VimOperator,
-    VimPastePosition, VimRegister, VimRegisterKind,
+    VimPastePosition, VimRegister, VimRegisterKind, VimTextObject, is_visual,
 };
 use crate::canvas_editor::editing::command::{
     Command, DeleteRangeCommand, InsertTextCommand,
@@ -13,7 +14,8 @@ use crate::canvas_editor::editing::command::{
 use crate::canvas_editor::{CodeEditor, Message};
 
 impl CodeEditor {
-    /// Dispatches a parsed Vim key action to the corresponding handler.
+    /// Dispatches a parsed Vim key action to the corresponding handler, and
+    /// records it as the change `.` repeats when it edits the buffer.
     ///
     /// # Arguments
     ///
@@ -30,28 +32,77 @@ impl CodeEditor {
 
         let previous_mode = self.vim_state.mode();
         let action = self.vim_state.parse_key(key);
+        // The keys that start and stop a recording are not part of it.
+        if action != Some(VimAction::MacroRecording) {
+            self.vim_state.record_macro_input(&Message::VimKey(key));
+        }
+        let Some(action) = action else {
+            return Task::none();
+        };
+        let change = self.vim_repeatable_change(&action);
+        let register = self.vim_state.selected_register();
+        let task = self.dispatch_vim_action(action, previous_mode);
+        if let Some(change) = change {
+            self.vim_state.record_change(change, register);
+        }
+        self.vim_state.select_register(None);
+        task
+    }
+
+    /// Handles [`Message::VimCommandHistory`]: recalls an older or newer
+    /// entry of the open command line's history.
+    ///
+    /// # Arguments
+    ///
+    /// * `older` - Whether to recall an older entry rather than a newer one
+    ///
+    /// # Returns
+    ///
+    /// `Task::none()`; the command line is redrawn with the entry
+    pub(crate) fn handle_vim_command_history_msg(
+        &mut self,
+        older: bool,
+    ) -> Task<Message> {
+        if self.vim_enabled && self.vim_state.browse_command_history(older) {
+            self.vim_state
+                .record_macro_input(&Message::VimCommandHistory(older));
+            self.overlay_cache.clear();
+        }
+        Task::none()
+    }
+
+    /// Runs a parsed Vim action.
+    ///
+    /// # Arguments
+    ///
+    /// * `action` - The action to run
+    /// * `previous_mode` - The mode before the key that produced `action`
+    ///
+    /// # Returns
+    ///
+    /// A `Task<Message>` produced by the action's handler
+    fn dispatch_vim_action(
+        &mut self,
+        action: VimAction,
+        previous_mode: VimMode,
+    ) -> Task<Message> {
         match action {
-            Some(VimAction::Mode(mode)) => {
-                self.handle_vim_mode(mode, previous_mode)
-            }
-            Some(VimAction::Motion { motion, count, explicit_count }) => {
+            VimAction::Mode(mode) => self.handle_vim_mode(mode, previous_mode),
+            VimAction::Motion { motion, count, explicit_count } => {
                 self.handle_vim_motion(motion, count, explicit_count)
             }
-            Some(VimAction::Insert { position, count }) => {
+            VimAction::Insert { position, count } => {
                 self.handle_vim_insert(position, count)
             }
-            Some(VimAction::Operator {
-                operator,
-                motion,
-                count,
-                explicit_count,
-            }) => self.handle_vim_motion_operator(
-                operator,
-                motion,
-                count,
-                explicit_count,
-            ),
-            Some(VimAction::LineOperator { operator, count }) => {
+            VimAction::Operator { operator, motion, count, explicit_count } => {
+                self.handle_vim_motion_operator(
+                    operator,
+                    motion,
+                    count,
+                    explicit_count,
+                )
+            }
+            VimAction::LineOperator { operator, count } => {
                 let start_line = self.cursors.primary_position().0;
                 let end_line = start_line
                     .saturating_add(count.saturating_sub(1))
@@ -60,45 +111,243 @@ impl CodeEditor {
                     operator, start_line, end_line, false,
                 )
             }
-            Some(VimAction::VisualOperator(operator)) => {
+            VimAction::TextObjectOperator { operator, object, count } => {
+                self.handle_vim_text_object_operator(operator, object, count)
+            }
+            VimAction::SelectTextObject { object, count } => {
+                self.handle_vim_select_text_object(object, count)
+            }
+            VimAction::VisualOperator(operator) => {
                 self.handle_vim_visual_operator(operator)
             }
-            Some(VimAction::DeleteCharacters { count }) => {
+            VimAction::VisualReplace(ch) => self.handle_vim_visual_replace(ch),
+            VimAction::BlockInsert { append } => {
+                self.handle_vim_block_insert(append)
+            }
+            VimAction::DeleteCharacters { count } => {
                 self.handle_vim_delete_characters(count)
             }
-            Some(VimAction::Paste { position, count }) => {
+            VimAction::ReplaceCharacters { ch, count } => {
+                self.handle_vim_replace_characters(ch, count)
+            }
+            VimAction::ToggleCharacters { count } => {
+                self.handle_vim_toggle_characters(count)
+            }
+            VimAction::AddToNumber { count, subtract } => {
+                self.handle_vim_add_to_number(count, subtract)
+            }
+            VimAction::ReplaceMode => self.handle_vim_replace_mode(),
+            VimAction::Paste { position, count } => {
                 self.handle_vim_paste(position, count)
             }
-            Some(VimAction::Undo { count }) => {
-                self.handle_vim_history(false, count)
+            VimAction::MacroRecording => {
+                self.overlay_cache.clear();
+                Task::none()
+            }
+            VimAction::ReplayMacro { register, count } => {
+                self.handle_vim_replay_macro(register, count)
             }
-            Some(VimAction::Redo { count }) => {
-                self.handle_vim_history(true, count)
+            VimAction::SetMark(mark) => {
+                let position = self.vim_state.visual_positions().map_or_else(
+                    || self.cursors.primary_position(),
+                    |(_, active)| active,
+                );
+                self.set_mark(mark, position);
+                Task::none()
             }
-            Some(VimAction::RepeatSearch { reverse }) => {
-                self.handle_vim_repeat_search(reverse)
+            VimAction::JumpToMark { mark, linewise } => {
+                self.handle_vim_jump_to_mark(mark, linewise)
             }
-            Some(VimAction::SubmitSearch(query)) => {
-                self.handle_vim_search(&query)
+            VimAction::RepeatChange { count } => {
+                self.handle_vim_repeat_change(count)
             }
-            Some(VimAction::SubmitGotoLine(line)) => {
-                self.handle_goto_position(line.saturating_sub(1), 0)
+            VimAction::Undo { count } => self.handle_vim_history(false, count),
+            VimAction::Redo { count } => self.handle_vim_history(true, count),
+            VimAction::RepeatSearch { reverse } => {
+                self.handle_vim_repeat_search(reverse)
             }
-            Some(VimAction::WriteFile { exit_vim }) => {
-                if exit_vim {
-                    self.set_vim_enabled(false);
-                }
-                Task::done(Message::WriteRequested)
+            VimAction::SubmitSearch(query) => self.handle_vim_search(&query),
+            VimAction::SubmitCommand(input) => {
+                self.handle_vim_ex_command(&input)
             }
-            Some(VimAction::ExitVimMode) => {
-                self.set_vim_enabled(false);
-                Task::none()
+            VimAction::Scroll { scroll, line } => {
+                self.handle_vim_scroll(scroll, line)
+            }
+            VimAction::VisualCommandLine => {
+                self.handle_vim_visual_command_line()
             }
-            Some(VimAction::CommandLineChanged) => {
+            VimAction::CommandLineChanged => {
                 self.overlay_cache.clear();
                 Task::none()
             }
-            None => Task::none(),
+        }
+    }
+
+    /// Returns the form in which `.` should repeat `action`, or `None` when
+    /// it is not a change.
+    ///
+    /// A Visual operator is recorded over the same extent from the cursor:
+    /// as many lines for Visual Line mode, or as many characters for a
+    /// selection within one line. Multi-line character-wise selections and
+    /// Visual Block changes are not recorded.
+    fn vim_repeatable_change(&self, action: &VimAction) -> Option<VimAction> {
+        if !action.is_repeatable_change() {
+            return None;
+        }
+        let VimAction::VisualOperator(operator) = *action else {
+            return Some(action.clone());
+        };
+        if self.vim_state.mode() == VimMode::VisualBlock {
+            return None;
+        }
+        if self.vim_state.mode() == VimMode::VisualLine {
+            let (anchor, active) = self.vim_state.visual_positions()?;
+            return Some(VimAction::LineOperator {
+                operator,
+                count: anchor.0.abs_diff(active.0) + 1,
+            });
+        }
+        let (start, end) = self.cursors.primary().selection_range()?;
+        (start.0 == end.0).then(|| VimAction::Operator {
+            operator,
+            motion: VimMotion::Right,
+            count: end.1 - start.1,
+            explicit_count: true,
+        })
+    }
+
+    /// Replays the last change at the cursor (`.`), then returns to Normal
+    /// mode.
+    ///
+    /// The replay runs in one history group, so it undoes as one step.
+    ///
+    /// # Arguments
+    ///
+    /// * `count` - A count typed before `.`, replacing the recorded one
+    fn handle_vim_repeat_change(
+        &mut self,
+        count: Option<usize>,
+    ) -> Task<Message> {
+        let Some(change) = self.vim_state.last_change().cloned() else {
+            return Task::none();
+        };
+        let action = match count {
+            Some(count) => change.action.with_count(count),
+            None => change.action,
+        };
+
+        // Inside a macro replay, the macro's group already holds the edits.
+        let nested = self.vim_state.replaying();
+        if !nested {
+            self.end_grouping_if_active();
+            self.ensure_grouping_started();
+        }
+        self.vim_state.set_replaying(true);
+        if matches!(action, VimAction::Insert { .. }) {
+            self.vim_state.enter_insert_mode();
+        }
+        // An insert's count repeats the typed text, so `3.` after `iab<Esc>`
+        // inserts "ababab"; `o` and `O` use it to open that many lines.
+        let repeats = match action {
+            VimAction::Insert {
+                position:
+                    VimInsertPosition::NewLineBelow
+                    | VimInsertPosition::NewLineAbove,
+                ..
+            } => 1,
+            VimAction::Insert { count, .. } => count.max(1),
+            _ => 1,
+        };
+        self.vim_state.select_register(change.register);
+        let mut task = self.dispatch_vim_action(action, VimMode::Normal);
+        self.vim_state.select_register(None);
+        for _ in 0..repeats {
+            for message in &change.insert_input {
+                let _ = self.update(message);
+            }
+        }
+        if self.vim_state.mode() == VimMode::Insert {
+            self.vim_state.enter_clean_normal_mode();
+            task = self.handle_vim_mode(VimMode::Normal, VimMode::Insert);
+        }
+        self.vim_state.set_replaying(nested);
+        self.end_grouping_if_active();
+        task
+    }
+
+    /// Replays the macro recorded in `register` `count` times (`@a`).
+    ///
+    /// The replay is one undo group. It stops early when a motion or search
+    /// fails to move, as at the end of the buffer, so `100@a` applies the
+    /// macro until it runs out of text.
+    ///
+    /// # Arguments
+    ///
+    /// * `register` - The register holding the macro
+    /// * `count` - How many times to replay it
+    fn handle_vim_replay_macro(
+        &mut self,
+        register: char,
+        count: usize,
+    ) -> Task<Message> {
+        let Some(input) =
+            self.vim_state.macro_input(register).map(<[_]>::to_vec)
+        else {
+            return Task::none();
+        };
+        if !self.vim_state.enter_macro() {
+            self.vim_state.fail_motion();
+            return Task::none();
+        }
+
+        let nested = self.vim_state.replaying();
+        if !nested {
+            self.end_grouping_if_active();
+            self.ensure_grouping_started();
+            self.vim_state.take_motion_failed();
+        }
+        self.vim_state.set_replaying(true);
+        let mut failed = false;
+        'replay: for _ in 0..count {
+            for message in &input {
+                let _ = self.update(message);
+                if self.vim_state.take_motion_failed() {
+                    failed = true;
+                    break 'replay;
+                }
+            }
+        }
+        self.vim_state.set_replaying(nested);
+        self.vim_state.leave_macro();
+        if nested && failed {
+            // Abort the enclosing replays too.
+            self.vim_state.fail_motion();
+        }
+        self.end_grouping_if_active();
+        self.overlay_cache.clear();
+        self.scroll_to_cursor()
+    }
+
+    /// Records Insert-mode input typed after a change, so `.` can replay it,
+    /// and into the macro being recorded.
+    pub(crate) fn record_vim_insert_input(&mut self, message: &Message) {
+        if self.vim_enabled
+            && self.vim_state.mode() == VimMode::Insert
+            && matches!(
+                message,
+                Message::CharacterInput(_)
+                    | Message::Tab
+                    | Message::Enter
+                    | Message::Backspace
+                    | Message::Delete
+                    | Message::DeleteWordLeft(_)
+                    | Message::DeleteWordRight(_)
+                    | Message::Paste(_)
+                    | Message::ImeCommit(_)
+            )
+        {
+            self.vim_state.record_insert_input(message);
+            self.vim_state.record_macro_input(message);
         }
     }
 
@@ -108,8 +357,9 @@ impl CodeEditor {
         }
 
         self.search_state.close();
-        self.search_state.set_query(query.to_owned(), &self.buffer);
+        self.set_vim_search_pattern(query);
         if self.search_state.matches.is_empty() {
+            self.vim_state.fail_motion();
             self.overlay_cache.clear();
             return Task::none();
         }
@@ -127,23 +377,48 @@ impl CodeEditor {
             });
 
         if let Some(search_match) = self.search_state.current_match() {
-            self.cursors.set_single((search_match.line, search_match.col));
+            let position = (search_match.line, search_match.col);
+            self.push_jump();
+            self.cursors.set_single(position);
         }
         self.finish_navigation_operation();
         self.scroll_to_cursor()
     }
 
+    /// Points the search state at a Vim search pattern, which is literal
+    /// text except that `\<word\>`, as `*` and `#` search, only matches
+    /// `word` as a whole word.
+    ///
+    /// # Returns
+    ///
+    /// Whether the search state's matches were recomputed
+    pub(super) fn set_vim_search_pattern(&mut self, pattern: &str) -> bool {
+        let (text, whole_word) = pattern
+            .strip_prefix("\\<")
+            .and_then(|text| text.strip_suffix("\\>"))
+            .map_or((pattern, false), |text| (text, true));
+        if self.search_state.query == text
+            && self.search_state.whole_word == whole_word
+        {
+            return false;
+        }
+        self.search_state.whole_word = whole_word;
+        self.search_state.set_query(text.to_owned(), &self.buffer);
+        true
+    }
+
     fn handle_vim_repeat_search(&mut self, reverse: bool) -> Task<Message> {
         let Some(last_search) = self.vim_state.last_search().map(str::to_owned)
         else {
+            self.vim_state.fail_motion();
             return Task::none();
         };
-        if self.search_state.query != last_search {
-            self.search_state.set_query(last_search, &self.buffer);
+        if self.set_vim_search_pattern(&last_search) {
             self.search_state
                 .select_match_near_cursor(self.cursors.primary_position());
         }
         if self.search_state.matches.is_empty() {
+            self.vim_state.fail_motion();
             return Task::none();
         }
 
@@ -153,7 +428,9 @@ impl CodeEditor {
             self.search_state.next_match();
         }
         if let Some(search_match) = self.search_state.current_match() {
-            self.cursors.set_single((search_match.line, search_match.col));
+            let position = (search_match.line, search_match.col);
+            self.push_jump();
+            self.cursors.set_single(position);
         }
         self.finish_navigation_operation();
         self.scroll_to_cursor()
@@ -179,13 +456,22 @@ impl CodeEditor {
         explicit_count: bool,
     ) -> Task<Message> {
         let start = self.vim_normal_position(self.cursors.primary_position());
+        if matches!(motion, VimMotion::SearchWord { .. })
+            && !self.start_vim_word_search(start)
+        {
+            return Task::none();
+        }
         if matches!(
             motion,
             VimMotion::Up
                 | VimMotion::Down
                 | VimMotion::DocumentStart
                 | VimMotion::DocumentEnd
-        ) {
+                | VimMotion::ScreenTop
+                | VimMotion::ScreenMiddle
+                | VimMotion::ScreenBottom
+        ) || (motion == VimMotion::MatchPair && explicit_count)
+        {
             let target =
                 self.vim_motion_target(start, motion, count, explicit_count);
             return self.handle_vim_line_operator(
@@ -198,6 +484,31 @@ impl CodeEditor {
 
         let target =
             self.vim_motion_target(start, motion, count, explicit_count);
+        // A search that finds nothing cancels the operator. A `t` or `T`
+        // whose target is adjacent finds it without moving, and still
+        // applies the operator to the character before it or to nothing.
+        let missed = match motion {
+            VimMotion::FindChar { find, repeated } => {
+                self.vim_find_target(start, find, repeated, count).is_none()
+            }
+            VimMotion::MatchPair | VimMotion::SearchWord { .. } => {
+                target == start
+            }
+            _ => false,
+        };
+        if missed {
+            self.vim_state.fail_motion();
+            return Task::none();
+        }
+        let inclusive_end = |position: (usize, usize)| {
+            (
+                position.0,
+                position
+                    .1
+                    .saturating_add(1)
+                    .min(self.buffer.line_len(position.0)),
+            )
+        };
         let (range_start, range_end) = match motion {
             VimMotion::Right => (
                 start,
@@ -212,21 +523,33 @@ impl CodeEditor {
             VimMotion::Left => {
                 ((start.0, start.1.saturating_sub(count)), start)
             }
-            VimMotion::WordEnd | VimMotion::LineEnd => {
+            VimMotion::WordEnd | VimMotion::BigWordEnd | VimMotion::LineEnd => {
                 let end = if motion == VimMotion::LineEnd {
                     (start.0, self.buffer.line_len(start.0))
                 } else {
-                    (
-                        target.0,
-                        target
-                            .1
-                            .saturating_add(1)
-                            .min(self.buffer.line_len(target.0)),
-                    )
+                    inclusive_end(target)
+                };
+                (start.min(end), start.max(end))
+            }
+            VimMotion::WordEndBackward | VimMotion::BigWordEndBackward => {
+                (target.min(start), inclusive_end(target.max(start)))
+            }
+            VimMotion::FindChar { find, .. } if find.forward => {
+                (start, inclusive_end(target))
+            }
+            VimMotion::MatchPair => {
+                (start.min(target), inclusive_end(start.max(target)))
+            }
+            VimMotion::ParagraphForward => {
+                // Past the last paragraph, the end of the document.
+                let end = if self.buffer.line(target.0).trim().is_empty() {
+                    target
+                } else {
+                    (target.0, self.buffer.line_len(target.0))
                 };
                 (start.min(end), start.max(end))
             }
-            VimMotion::WordForward => {
+            VimMotion::WordForward | VimMotion::BigWordForward => {
                 let end = if target > start {
                     target
                 } else {
@@ -235,14 +558,21 @@ impl CodeEditor {
                 (start.min(end), start.max(end))
             }
             VimMotion::WordBackward
+            | VimMotion::BigWordBackward
             | VimMotion::LineStart
-            | VimMotion::FirstNonBlank => {
+            | VimMotion::FirstNonBlank
+            | VimMotion::FindChar { .. }
+            | VimMotion::ParagraphBackward
+            | VimMotion::SearchWord { .. } => {
                 (start.min(target), start.max(target))
             }
             VimMotion::Up
             | VimMotion::Down
             | VimMotion::DocumentStart
-            | VimMotion::DocumentEnd => return Task::none(),
+            | VimMotion::DocumentEnd
+            | VimMotion::ScreenTop
+            | VimMotion::ScreenMiddle
+            | VimMotion::ScreenBottom => return Task::none(),
         };
 
         self.handle_vim_character_operator(
@@ -253,10 +583,137 @@ impl CodeEditor {
         )
     }
 
+    /// Applies `operator` to the text object around the cursor.
+    ///
+    /// Linewise objects (paragraphs, the lines inside a bracket block) act
+    /// on whole lines, except that changing them keeps one empty line to
+    /// type in. An empty object, such as the inside of `()`, can still be
+    /// changed: Insert mode starts between the delimiters.
+    fn handle_vim_text_object_operator(
+        &mut self,
+        operator: VimOperator,
+        object: VimTextObject,
+        count: usize,
+    ) -> Task<Message> {
+        let position = self.cursors.primary_position();
+        let Some(TextObjectSpan { start, end, linewise }) =
+            self.vim_text_object_range(position, object, count)
+        else {
+            self.vim_state.enter_clean_normal_mode();
+            return Task::none();
+        };
+
+        if start == end {
+            if operator == VimOperator::Change {
+                self.end_grouping_if_active();
+                self.ensure_grouping_started();
+                self.cursors.set_single(start);
+                self.vim_state.enter_insert_mode();
+                self.finish_navigation_operation();
+                return self.scroll_to_cursor();
+            }
+            self.vim_state.enter_clean_normal_mode();
+            return Task::none();
+        }
+        if linewise && operator != VimOperator::Change {
+            return self
+                .handle_vim_line_operator(operator, start.0, end.0, false);
+        }
+        self.handle_vim_character_operator(operator, start, end, true)
+    }
+
+    /// Selects the text object around the Visual cursor, switching between
+    /// Visual and Visual Line mode to match the object.
+    ///
+    /// Repeating the same object when it is already selected grows the
+    /// selection to the next enclosing one, as `vi(i(` does.
+    fn handle_vim_select_text_object(
+        &mut self,
+        object: VimTextObject,
+        count: usize,
+    ) -> Task<Message> {
+        let (anchor, active) =
+            self.vim_state.visual_positions().unwrap_or_else(|| {
+                let position =
+                    self.vim_normal_position(self.cursors.primary_position());
+                (position, position)
+            });
+        let (from, to) = (anchor.min(active), anchor.max(active));
+        let line_mode = self.vim_state.mode() == VimMode::VisualLine;
+        let selected = |span: TextObjectSpan| {
+            if span.linewise {
+                line_mode && (span.start.0, span.end.0) == (from.0, to.0)
+            } else {
+                !line_mode && from != to && (span.start, span.end) == (from, to)
+            }
+        };
+        let mut range = self.vim_visual_text_object(active, object, count);
+        if range.is_some_and(selected) {
+            range = self.vim_visual_text_object(
+                active,
+                object,
+                count.saturating_add(1),
+            );
+        }
+        let Some(TextObjectSpan { start, end, linewise }) = range else {
+            return Task::none();
+        };
+
+        self.vim_state.switch_visual_mode(if linewise {
+            VimMode::VisualLine
+        } else {
+            VimMode::Visual
+        });
+        self.vim_state.begin_visual(start);
+        self.vim_state.set_visual_active(end);
+        self.apply_vim_visual_selection(start, end, linewise);
+        self.finish_navigation_operation();
+        self.scroll_to_cursor()
+    }
+
+    /// Resolves a text object for Visual mode, whose selection ends on the
+    /// object's last character rather than after it.
+    fn vim_visual_text_object(
+        &self,
+        position: (usize, usize),
+        object: VimTextObject,
+        count: usize,
+    ) -> Option<TextObjectSpan> {
+        let TextObjectSpan { start, end, linewise } =
+            self.vim_text_object_range(position, object, count)?;
+        if linewise {
+            return Some(TextObjectSpan {
+                start: (start.0, 0),
+                end: (end.0, 0),
+                linewise,
+            });
+        }
+        if start == end {
+            return None;
+        }
+        let last = if end.1 > 0 {
+            (end.0, end.1 - 1)
+        } else {
+            let line = end.0.saturating_sub(1);
+            (line, self.buffer.line_len(line))
+        };
+        Some(TextObjectSpan {
+            start,
+            end: self.vim_normal_position(last),
+            linewise,
+        })
+    }
+
     fn handle_vim_visual_operator(
         &mut self,
         operator: VimOperator,
     ) -> Task<Message> {
+        if operator.rewrites_in_place() {
+            return self.handle_vim_visual_transform(operator);
+        }
+        if self.vim_state.mode() == VimMode::VisualBlock {
+            return self.handle_vim_block_operator(operator);
+        }
         if self.vim_state.mode() == VimMode::VisualLine {
             let (anchor, active) =
                 self.vim_state.visual_positions().unwrap_or_else(|| {
@@ -285,7 +742,18 @@ impl CodeEditor {
         end: (usize, usize),
         from_visual: bool,
     ) -> Task<Message> {
+        if operator.rewrites_in_place() {
+            return self.handle_vim_transform_range(operator, start, end);
+        }
         if start == end {
+            // Changing nothing, as `cT,` right after the comma does, still
+            // starts Insert mode.
+            if operator == VimOperator::Change {
+                self.cursors.set_single(start);
+                self.vim_state.enter_insert_mode();
+                return self
+                    .handle_vim_insert(VimInsertPosition::BeforeCursor, 1);
+            }
             return Task::none();
         }
         let register = VimRegister {
@@ -295,13 +763,17 @@ impl CodeEditor {
         self.apply_vim_operator(operator, start, end, register, from_visual)
     }
 
-    fn handle_vim_line_operator(
+    pub(super) fn handle_vim_line_operator(
         &mut self,
         operator: VimOperator,
         start_line: usize,
         end_line: usize,
         from_visual: bool,
     ) -> Task<Message> {
+        if operator.rewrites_in_place() {
+            return self
+                .handle_vim_transform_lines(operator, start_line, end_line);
+        }
         let last_line = self.buffer.line_count().saturating_sub(1);
         let start_line = start_line.min(last_line);
         let end_line = end_line.min(last_line).max(start_line);
@@ -338,7 +810,7 @@ impl CodeEditor {
         register: VimRegister,
         from_visual: bool,
     ) -> Task<Message> {
-        self.vim_state.register = register;
+        let clipboard = self.store_vim_register(operator, register);
 
         if operator == VimOperator::Yank {
             if from_visual {
@@ -350,7 +822,7 @@ impl CodeEditor {
             }
             self.vim_state.enter_clean_normal_mode();
             self.finish_navigation_operation();
-            return self.scroll_to_cursor();
+            return Task::batch([clipboard, self.scroll_to_cursor()]);
         }
 
         self.end_grouping_if_active();
@@ -368,29 +840,107 @@ impl CodeEditor {
         let mut cursor_after = cursor_before;
         command.execute(&mut self.buffer, &mut cursor_after);
         self.history.push(Box::new(command));
-        self.cursors.set_single(self.vim_normal_position(cursor_after));
 
+        // Insert mode may sit past the last character, so a change keeps
+        // the cursor where the deleted text started instead of clamping it.
         if operator == VimOperator::Change {
+            self.cursors.set_single(cursor_after);
             self.vim_state.enter_insert_mode();
         } else {
+            self.cursors.set_single(self.vim_normal_position(cursor_after));
             self.vim_state.enter_clean_normal_mode();
         }
         self.finish_edit_operation();
-        self.scroll_to_cursor()
+        Task::batch([clipboard, self.scroll_to_cursor()])
     }
 
+    /// Stores yanked or deleted text in the register selected with
+    /// `"{register}`, or in the unnamed register and its history.
+    ///
+    /// # Returns
+    ///
+    /// A `Task<Message>` writing the text to the system clipboard for the
+    /// `"+` and `"*` registers, otherwise `Task::none()`
+    pub(super) fn store_vim_register(
+        &mut self,
+        operator: VimOperator,
+        register: VimRegister,
+    ) -> Task<Message> {
+        let selected = self.vim_state.selected_register();
+        let clipboard = if matches!(selected, Some('+' | '*')) {
+            self.clipboard_history.push(&register.text);
+            iced::clipboard::write(register.text.clone())
+        } else {
+            Task::none()
+        };
+        self.vim_state.store_register(selected, operator, register);
+        clipboard
+    }
+
+    /// Pastes the selected register, or the unnamed one, `count` times.
+    /// The `"+` and `"*` registers read the system clipboard first.
     fn handle_vim_paste(
         &mut self,
         position: VimPastePosition,
         count: usize,
     ) -> Task<Message> {
-        let register = self.vim_state.register.clone();
-        if register.text.is_empty() {
+        let selected = self.vim_state.selected_register();
+        if matches!(selected, Some('+' | '*')) {
+            self.vim_state.set_pending_clipboard_paste((position, count));
+            return iced::clipboard::read()
+                .and_then(|text| Task::done(Message::VimClipboardPaste(text)));
+        }
+        let Some(register) =
+            self.vim_state.register_for_paste(selected).cloned()
+        else {
+            return Task::none();
+        };
+        self.paste_vim_register(&register, position, count)
+    }
+
+    /// Finishes a `"+p` or `"*p` paste with the clipboard's text, which is
+    /// pasted line-wise when it ends in a newline.
+    ///
+    /// # Arguments
+    ///
+    /// * `text` - The text read from the clipboard
+    ///
+    /// # Returns
+    ///
+    /// A `Task<Message>` that scrolls to keep the cursor visible
+    pub(crate) fn handle_vim_clipboard_paste_msg(
+        &mut self,
+        text: &str,
+    ) -> Task<Message> {
+        let Some((position, count)) =
+            self.vim_state.take_pending_clipboard_paste()
+        else {
+            return Task::none();
+        };
+        if !self.vim_enabled || text.is_empty() {
             return Task::none();
         }
+        let text = text.replace("\r\n", "\n");
+        let kind = if text.ends_with('\n') {
+            VimRegisterKind::Linewise
+        } else {
+            VimRegisterKind::Characterwise
+        };
+        self.paste_vim_register(&VimRegister { text, kind }, position, count)
+    }
+
+    fn paste_vim_register(
+        &mut self,
+        register: &VimRegister,
+        position: VimPastePosition,
+        count: usize,
+    ) -> Task<Message> {
         self.end_grouping_if_active();
 
         let current = self.vim_normal_position(self.cursors.primary_position());
+        if register.kind == VimRegisterKind::Blockwise {
+            return self.paste_vim_block(&register.text, position, count);
+        }
         let (insert_at, text, cursor_after) = match register.kind {
             VimRegisterKind::Characterwise => {
                 let insert_at = match position {
@@ -431,6 +981,7 @@ impl CodeEditor {
                     }
                 }
             }
+            VimRegisterKind::Blockwise => return Task::none(),
         };
 
         self.pre_edit_line = insert_at.0;
@@ -490,7 +1041,17 @@ impl CodeEditor {
         mode: VimMode,
         previous_mode: VimMode,
     ) -> Task<Message> {
+        // A block insert copies its text in the Insert session's group, so
+        // the whole change undoes as one step.
+        let block_start = if previous_mode == VimMode::Insert {
+            self.finish_vim_block_insert()
+        } else {
+            None
+        };
         self.end_grouping_if_active();
+        if previous_mode == VimMode::Insert && mode != VimMode::Insert {
+            self.vim_state.stop_recording_change();
+        }
         match mode {
             VimMode::Normal => {
                 let mut active = self
@@ -498,21 +1059,35 @@ impl CodeEditor {
                     .visual_positions()
                     .map(|(_, active)| active)
                     .unwrap_or_else(|| self.cursors.primary_position());
-                if previous_mode == VimMode::Insert {
+                if let Some(start) = block_start {
+                    active = start;
+                } else if previous_mode == VimMode::Insert {
                     active.1 = active.1.saturating_sub(1);
                 }
                 self.vim_state.clear_visual();
                 self.cursors.set_single(self.vim_normal_position(active));
             }
-            VimMode::Visual | VimMode::VisualLine => {
-                let position =
-                    self.vim_normal_position(self.cursors.primary_position());
-                self.vim_state.begin_visual(position);
-                self.apply_vim_visual_selection(
-                    position,
-                    position,
-                    mode == VimMode::VisualLine,
-                );
+            VimMode::Visual | VimMode::VisualLine | VimMode::VisualBlock => {
+                // Switching between Visual modes keeps the selection.
+                let (anchor, active) = self
+                    .vim_state
+                    .visual_positions()
+                    .filter(|_| is_visual(previous_mode))
+                    .unwrap_or_else(|| {
+                        let position = self.vim_normal_position(
+                            self.cursors.primary_position(),
+                        );
+                        (position, position)
+                    });
+                self.vim_state.clear_visual();
+                self.vim_state.begin_visual(anchor);
+                if mode == VimMode::VisualBlock {
+                    let active = self.vim_normal_position(active);
+                    self.vim_state.set_block_column(Some(
+                        self.visual_column(active.0, active.1),
+                    ));
+                }
+                self.move_vim_cursor(active);
             }
             VimMode::Insert => {}
         }
@@ -527,40 +1102,150 @@ impl CodeEditor {
         explicit_count: bool,
     ) -> Task<Message> {
         self.end_grouping_if_active();
+        let origin = match self.vim_state.mode() {
+            VimMode::Visual | VimMode::VisualLine | VimMode::VisualBlock => {
+                self.vim_state.visual_positions().map_or_else(
+                    || {
+                        self.vim_normal_position(
+                            self.cursors.primary_position(),
+                        )
+                    },
+                    |(_, active)| active,
+                )
+            }
+            VimMode::Normal => self.cursors.primary_position(),
+            VimMode::Insert => return Task::none(),
+        };
+        if matches!(motion, VimMotion::SearchWord { .. })
+            && !self.start_vim_word_search(origin)
+        {
+            return Task::none();
+        }
+        let mut target =
+            self.vim_motion_target(origin, motion, count, explicit_count);
+        if self.vim_state.mode() == VimMode::VisualBlock {
+            target = self.track_vim_block_column(motion, target);
+        }
+        if target == origin && motion.fails_in_place() {
+            self.vim_state.fail_motion();
+        }
+        if motion.is_jump() {
+            self.push_jump();
+        }
+        if matches!(motion, VimMotion::SearchWord { .. }) {
+            self.search_state.select_match_near_cursor(target);
+        }
+        if self.vim_state.mode() == VimMode::VisualBlock {
+            self.vim_state.track_block_line_end(motion);
+        }
+        self.move_vim_cursor(target);
+        self.finish_navigation_operation();
+        self.scroll_to_cursor()
+    }
+
+    /// Keeps the Visual Block's moving corner on the column it aims for:
+    /// `j` and `k` return to it on lines long enough to reach it, and
+    /// every other motion aims for the column it lands on. After `$` the
+    /// block follows every line end instead.
+    ///
+    /// # Returns
+    ///
+    /// `target`, moved to the aimed-for column after `j` or `k`
+    fn track_vim_block_column(
+        &mut self,
+        motion: VimMotion,
+        target: (usize, usize),
+    ) -> (usize, usize) {
+        match (motion, self.vim_state.block_column()) {
+            (VimMotion::Up | VimMotion::Down, Some(column)) => self
+                .vim_normal_position((
+                    target.0,
+                    self.vim_char_at_visual(target.0, column),
+                )),
+            (VimMotion::Up | VimMotion::Down, None) => target,
+            (VimMotion::LineEnd, _) => {
+                self.vim_state.set_block_column(None);
+                target
+            }
+            _ => {
+                self.vim_state.set_block_column(Some(
+                    self.visual_column(target.0, target.1),
+                ));
+                target
+            }
+        }
+    }
+
+    /// Moves the cursor to `target` as a motion does: in Visual modes the
+    /// selection grows from its anchor to `target`.
+    pub(crate) fn move_vim_cursor(&mut self, target: (usize, usize)) {
         match self.vim_state.mode() {
-            VimMode::Visual | VimMode::VisualLine => {
-                let (anchor, active) =
-                    self.vim_state.visual_positions().unwrap_or_else(|| {
-                        let position = self.vim_normal_position(
+            mode @ (VimMode::Visual | VimMode::VisualLine) => {
+                let anchor = self.vim_state.visual_positions().map_or_else(
+                    || {
+                        self.vim_normal_position(
                             self.cursors.primary_position(),
-                        );
-                        (position, position)
-                    });
-                let target = self.vim_motion_target(
-                    active,
-                    motion,
-                    count,
-                    explicit_count,
+                        )
+                    },
+                    |(anchor, _)| anchor,
                 );
                 self.vim_state.set_visual_active(target);
                 self.apply_vim_visual_selection(
                     anchor,
                     target,
-                    self.vim_state.mode() == VimMode::VisualLine,
+                    mode == VimMode::VisualLine,
                 );
             }
-            VimMode::Normal => {
-                let target = self.vim_motion_target(
-                    self.cursors.primary_position(),
-                    motion,
-                    count,
-                    explicit_count,
+            VimMode::VisualBlock => {
+                let anchor = self.vim_state.visual_positions().map_or_else(
+                    || {
+                        self.vim_normal_position(
+                            self.cursors.primary_position(),
+                        )
+                    },
+                    |(anchor, _)| anchor,
                 );
+                self.vim_state.set_visual_active(target);
+                self.apply_vim_block_selection(anchor, target);
+            }
+            VimMode::Normal | VimMode::Insert => {
                 self.cursors.set_single(target);
                 self.overlay_cache.clear();
             }
-            VimMode::Insert => return Task::none(),
         }
+    }
+
+    /// Jumps to `mark` (`` `a ``), or to the first non-blank character of
+    /// its line (`'a`), recording the position it leaves in the jumplist.
+    ///
+    /// # Arguments
+    ///
+    /// * `mark` - The mark to jump to
+    /// * `linewise` - Whether to jump to the mark's line rather than its
+    ///   exact position
+    fn handle_vim_jump_to_mark(
+        &mut self,
+        mark: char,
+        linewise: bool,
+    ) -> Task<Message> {
+        let Some(position) = self.mark_state.mark(mark) else {
+            self.vim_state.fail_motion();
+            return Task::none();
+        };
+        self.end_grouping_if_active();
+        let position = self.vim_normal_position(position);
+        let target = if linewise {
+            self.vim_motion_target(
+                (position.0, 0),
+                VimMotion::FirstNonBlank,
+                1,
+                false,
+            )
+        } else {
+            position
+        };
+        self.push_jump();
+        self.move_vim_cursor(target);
         self.finish_navigation_operation();
         self.scroll_to_cursor()
     }
@@ -627,22 +1312,14 @@ impl CodeEditor {
 mod tests {
     use super::*;
     use crate::canvas_editor::VimMode;
+    use crate::canvas_editor::features::vim::MAX_MACRO_DEPTH;
+    use crate::canvas_editor::features::vim::test_support::{
+        focus_editor, type_text, vim_keys,
+    };
     use crate::canvas_editor::lsp;
     use std::cell::RefCell;
     use std::rc::Rc;
 
-    fn vim_keys(editor: &mut CodeEditor, keys: &str) {
-        for key in keys.chars() {
-            let _ = editor.update(&Message::VimKey(key));
-        }
-    }
-
-    fn focus_editor(editor: &mut CodeEditor) {
-        editor.request_focus();
-        editor.has_canvas_focus = true;
-        editor.focus_locked = false;
-    }
-
     fn assert_vim_delete(
         content: &str,
         cursor: (usize, usize),
@@ -1093,4 +1770,382 @@ mod tests {
         assert_eq!(change.range.end.character, 0);
         assert_eq!(change.text, "line4\nlie5\nline6\n");
     }
+
+    #[test]
+    fn test_vim_text_objects_with_operators() {
+        assert_vim_delete("foo bar baz", (0, 5), "diw", "foo  baz", "bar");
+        assert_vim_delete("foo bar baz", (0, 5), "daw", "foo baz", "bar ");
+        assert_vim_delete("a b c d", (0, 0), "d2aw", "c d", "a b ");
+        assert_vim_delete("f(a, (b))", (0, 6), "da(", "f(a, )", "(b)");
+        assert_vim_delete("f(a, (b))", (0, 6), "d2i(", "f()", "a, (b)");
+        assert_vim_delete(
+            "x = \"a \\\" b\";",
+            (0, 6),
+            "di\"",
+            "x = \"\";",
+            "a \\\" b",
+        );
+        assert_vim_delete(
+            "<p><b>hi</b></p>",
+            (0, 7),
+            "dat",
+            "<p></p>",
+            "<b>hi</b>",
+        );
+        assert_vim_delete(
+            "fn a() {\n    b();\n    c();\n}",
+            (1, 5),
+            "di{",
+            "fn a() {\n}",
+            "    b();\n    c();\n",
+        );
+        assert_vim_delete("a\nb\n\nc", (0, 0), "dap", "c", "a\nb\n\n");
+    }
+
+    #[test]
+    fn test_vim_text_objects_change_and_yank() {
+        let mut editor =
+            CodeEditor::new("let x = foo(1, 2);", "rs").with_vim_enabled(true);
+        focus_editor(&mut editor);
+        editor.cursors.set_single((0, 9));
+        vim_keys(&mut editor, "ciw");
+        assert_eq!(editor.content(), "let x = (1, 2);");
+        assert_eq!(editor.vim_state.mode(), VimMode::Insert);
+        for ch in "bar".chars() {
+            let _ = editor.update(&Message::CharacterInput(ch));
+        }
+        assert_eq!(editor.content(), "let x = bar(1, 2);");
+
+        vim_keys(&mut editor, "\u{1b}");
+        editor.cursors.set_single((0, 13));
+        vim_keys(&mut editor, "yib");
+        assert_eq!(editor.vim_state.register.text, "1, 2");
+        assert_eq!(editor.cursors.primary_position(), (0, 12));
+        assert_eq!(editor.content(), "let x = bar(1, 2);");
+
+        // Changing an empty object inserts between its delimiters.
+        let mut editor = CodeEditor::new("f()", "rs").with_vim_enabled(true);
+        focus_editor(&mut editor);
+        editor.cursors.set_single((0, 1));
+        vim_keys(&mut editor, "ci(");
+        let _ = editor.update(&Message::CharacterInput('x'));
+        assert_eq!(editor.content(), "f(x)");
+
+        let _ = editor.update(&Message::Undo);
+        assert_eq!(editor.content(), "f()");
+    }
+
+    #[test]
+    fn test_vim_text_objects_in_visual_modes() {
+        let mut editor =
+            CodeEditor::new("a (b (cd) e) f", "txt").with_vim_enabled(true);
+        editor.cursors.set_single((0, 6));
+        vim_keys(&mut editor, "vi(");
+        assert_eq!(
+            editor.cursors.primary().selection_range(),
+            Some(((0, 6), (0, 8)))
+        );
+
+        // Repeating the object grows it to the enclosing pair.
+        vim_keys(&mut editor, "i(");
+        assert_eq!(
+            editor.cursors.primary().selection_range(),
+            Some(((0, 3), (0, 11)))
+        );
+
+        vim_keys(&mut editor, "d");
+        assert_eq!(editor.content(), "a () f");
+
+        // A paragraph switches Visual to Visual Line mode.
+        let mut editor =
+            CodeEditor::new("a\nb\n\nc", "txt").with_vim_enabled(true);
+        vim_keys(&mut editor, "vip");
+        assert_eq!(editor.vim_state.mode(), VimMode::VisualLine);
+        vim_keys(&mut editor, "y");
+        assert_eq!(editor.vim_state.register.text, "a\nb\n");
+    }
+
+    #[test]
+    fn test_vim_dot_repeats_operators_with_count_override() {
+        let mut editor =
+            CodeEditor::new("a b c d e f g", "txt").with_vim_enabled(true);
+        vim_keys(&mut editor, "2dw");
+        assert_eq!(editor.content(), "c d e f g");
+
+        vim_keys(&mut editor, ".");
+        assert_eq!(editor.content(), "e f g");
+
+        // Yanks and motions are not changes, so `.` still deletes.
+        vim_keys(&mut editor, "ywl");
+        vim_keys(&mut editor, "0");
+        vim_keys(&mut editor, "1.");
+        assert_eq!(editor.content(), "f g");
+
+        let _ = editor.update(&Message::Undo);
+        assert_eq!(editor.content(), "e f g");
+    }
+
+    #[test]
+    fn test_vim_dot_replays_the_inserted_text_as_one_undo_step() {
+        let mut editor =
+            CodeEditor::new("foo bar baz", "txt").with_vim_enabled(true);
+        focus_editor(&mut editor);
+        vim_keys(&mut editor, "ciw");
+        type_text(&mut editor, "qux");
+        vim_keys(&mut editor, "\u{1b}");
+        assert_eq!(editor.content(), "qux bar baz");
+
+        vim_keys(&mut editor, "w.");
+        assert_eq!(editor.content(), "qux qux baz");
+        assert_eq!(editor.vim_state.mode(), VimMode::Normal);
+        assert_eq!(editor.cursors.primary_position(), (0, 6));
+
+        let _ = editor.update(&Message::Undo);
+        assert_eq!(editor.content(), "qux bar baz");
+    }
+
+    #[test]
+    fn test_vim_change_text_objects_at_the_end_of_a_line() {
+        let cases = [
+            ("foo bar\nbaz qux", (0, 5), "ciw", "foo X\nbaz X"),
+            ("say \"hi\"\nask \"yo\"", (0, 6), "ci\"", "say \"X\"\nask \"X\""),
+            ("foo bar\nbaz qux", (0, 5), "caw", "fooX\nbazX"),
+        ];
+        for (text, position, keys, expected) in cases {
+            let mut editor =
+                CodeEditor::new(text, "txt").with_vim_enabled(true);
+            focus_editor(&mut editor);
+            editor.cursors.set_single(position);
+            vim_keys(&mut editor, keys);
+            assert_eq!(editor.vim_state.mode(), VimMode::Insert, "{keys}");
+            type_text(&mut editor, "X");
+            vim_keys(&mut editor, "\u{1b}");
+
+            // `.` replays the change on the other line's last word.
+            vim_keys(&mut editor, "j$.");
+            assert_eq!(editor.content(), expected, "{keys}");
+        }
+    }
+
+    #[test]
+    fn test_vim_dot_count_repeats_the_inserted_text() {
+        let mut editor = CodeEditor::new("x", "txt").with_vim_enabled(true);
+        focus_editor(&mut editor);
+        vim_keys(&mut editor, "i");
+        type_text(&mut editor, "ab");
+        vim_keys(&mut editor, "\u{1b}");
+        assert_eq!(editor.content(), "abx");
+
+        vim_keys(&mut editor, "3.");
+        assert_eq!(editor.content(), "aabababbx");
+        assert_eq!(editor.cursors.primary_position(), (0, 6));
+
+        let _ = editor.update(&Message::Undo);
+        assert_eq!(editor.content(), "abx");
+    }
+
+    #[test]
+    fn test_vim_dot_repeats_inserts_and_visual_line_operators() {
+        let mut editor =
+            CodeEditor::new("a\nb\nc\nd\ne", "txt").with_vim_enabled(true);
+        focus_editor(&mut editor);
+        vim_keys(&mut editor, "A");
+        type_text(&mut editor, ";\nx");
+        vim_keys(&mut editor, "\u{1b}");
+        assert_eq!(editor.content(), "a;\nx\nb\nc\nd\ne");
+
+        vim_keys(&mut editor, "j.");
+        assert_eq!(editor.content(), "a;\nx\nb;\nx\nc\nd\ne");
+
+        let _ = editor.update(&Message::Undo);
+        assert_eq!(editor.content(), "a;\nx\nb\nc\nd\ne");
+
+        editor.cursors.set_single((0, 0));
+        vim_keys(&mut editor, "Vjd");
+        assert_eq!(editor.content(), "b\nc\nd\ne");
+        vim_keys(&mut editor, ".");
+        assert_eq!(editor.content(), "d\ne");
+    }
+
+    #[test]
+    fn test_vim_macro_records_and_replays_as_one_undo_step() {
+        let mut editor =
+            CodeEditor::new("a\nb\nc\nd", "txt").with_vim_enabled(true);
+        focus_editor(&mut editor);
+        vim_keys(&mut editor, "qa");
+        assert_eq!(
+            editor.vim_state.status_line_text().0,
+            "-- NORMAL -- recording @a"
+        );
+        vim_keys(&mut editor, "A");
+        type_text(&mut editor, ";");
+        vim_keys(&mut editor, "\u{1b}jq");
+        assert_eq!(editor.vim_state.status_line_text().0, "-- NORMAL --");
+        assert_eq!(editor.content(), "a;\nb\nc\nd");
+
+        vim_keys(&mut editor, "@a");
+        assert_eq!(editor.content(), "a;\nb;\nc\nd");
+        vim_keys(&mut editor, "@@");
+        assert_eq!(editor.content(), "a;\nb;\nc;\nd");
+        assert_eq!(editor.cursors.primary_position(), (3, 0));
+
+        let _ = editor.update(&Message::Undo);
+        assert_eq!(editor.content(), "a;\nb;\nc\nd");
+    }
+
+    #[test]
+    fn test_vim_macro_count_stops_at_a_failing_motion() {
+        let mut editor =
+            CodeEditor::new("1\n2\n3", "txt").with_vim_enabled(true);
+        focus_editor(&mut editor);
+        vim_keys(&mut editor, "qbA");
+        type_text(&mut editor, "!");
+        vim_keys(&mut editor, "\u{1b}jq");
+
+        vim_keys(&mut editor, "5@b");
+        assert_eq!(editor.content(), "1!\n2!\n3!");
+
+        let _ = editor.update(&Message::Undo);
+        assert_eq!(editor.content(), "1!\n2\n3");
+
+        // Uppercase registers append to the macro.
+        vim_keys(&mut editor, "gg0qBxq");
+        vim_keys(&mut editor, "@b");
+        assert_eq!(editor.content(), "!!\n\n3");
+    }
+
+    #[test]
+    fn test_vim_macro_replaying_itself_stops() {
+        let mut editor =
+            CodeEditor::new(&"x".repeat(500), "txt").with_vim_enabled(true);
+        vim_keys(&mut editor, "qcx@cq");
+        vim_keys(&mut editor, "@c");
+        assert_eq!(editor.content().len(), 500 - 1 - MAX_MACRO_DEPTH);
+    }
+
+    #[test]
+    fn test_vim_named_registers_and_append() {
+        let mut editor =
+            CodeEditor::new("one\ntwo\nthree", "txt").with_vim_enabled(true);
+        vim_keys(&mut editor, "\"ayyj\"Ayyyy");
+        assert_eq!(editor.vim_state.register.text, "two\n");
+
+        vim_keys(&mut editor, "G\"ap");
+        assert_eq!(editor.content(), "one\ntwo\nthree\none\ntwo");
+
+        vim_keys(&mut editor, "gg\"_dd");
+        assert_eq!(editor.content(), "two\nthree\none\ntwo");
+        assert_eq!(editor.vim_state.register.text, "two\n");
+    }
+
+    #[test]
+    fn test_vim_numbered_registers_keep_yank_and_delete_history() {
+        let mut editor =
+            CodeEditor::new("a\nb\nc\nd", "txt").with_vim_enabled(true);
+        vim_keys(&mut editor, "yyjdddd");
+        assert_eq!(editor.content(), "a\nd");
+
+        vim_keys(&mut editor, "\"0P");
+        assert_eq!(editor.content(), "a\na\nd");
+        vim_keys(&mut editor, "\"2p");
+        assert_eq!(editor.content(), "a\na\nb\nd");
+        vim_keys(&mut editor, "\"1p");
+        assert_eq!(editor.content(), "a\na\nb\nc\nd");
+
+        // Deletes within a line go to the small-delete register.
+        vim_keys(&mut editor, "x\"-P");
+        assert_eq!(editor.content(), "a\na\nb\nc\nd");
+        assert_eq!(
+            editor.vim_state.register_for_paste(Some('1')).map(|r| &r.text),
+            Some(&"c\n".to_owned())
+        );
+    }
+
+    #[test]
+    fn test_vim_clipboard_registers_use_the_system_clipboard() {
+        let mut editor =
+            CodeEditor::new("alpha beta", "txt").with_vim_enabled(true);
+        vim_keys(&mut editor, "\"+yw");
+        assert_eq!(editor.clipboard_history(), ["alpha "]);
+        assert_eq!(editor.vim_state.register.text, "alpha ");
+
+        vim_keys(&mut editor, "$\"*p");
+        assert_eq!(editor.content(), "alpha beta");
+        let _ = editor.update(&Message::VimClipboardPaste("!".to_owned()));
+        assert_eq!(editor.content(), "alpha beta!");
+
+        // A clipboard read with no paste waiting does nothing.
+        let _ = editor.update(&Message::VimClipboardPaste("?".to_owned()));
+        assert_eq!(editor.content(), "alpha beta!");
+    }
+
+    #[test]
+    fn test_vim_registers_command_lists_registers() {
+        let mut editor =
+            CodeEditor::new("a\tb\nc", "txt").with_vim_enabled(true);
+        vim_keys(&mut editor, "\"qyy:registers\n");
+        assert_eq!(
+            editor.vim_state.status_line_text().0,
+            "\"\" a^Ib^J  \"q a^Ib^J"
+        );
+
+        vim_keys(&mut editor, ":reg 0\n");
+        assert_eq!(editor.vim_state.status_line_text().0, "No registers");
+
+        vim_keys(&mut editor, "j");
+        assert_eq!(editor.vim_state.status_line_text().0, "-- NORMAL --");
+    }
+
+    #[test]
+    fn test_vim_marks_jump_to_the_position_or_its_line() {
+        let mut editor = CodeEditor::new("  one two\nthree\nfour", "txt")
+            .with_vim_enabled(true);
+        editor.cursors.set_single((0, 6));
+        vim_keys(&mut editor, "maG`a");
+        assert_eq!(editor.cursors.primary_position(), (0, 6));
+
+        vim_keys(&mut editor, "G'a");
+        assert_eq!(editor.cursors.primary_position(), (0, 2));
+
+        // An unset mark leaves the cursor where it is.
+        vim_keys(&mut editor, "`z");
+        assert_eq!(editor.cursors.primary_position(), (0, 2));
+    }
+
+    #[test]
+    fn test_vim_marks_follow_inserted_and_deleted_lines() {
+        let mut editor =
+            CodeEditor::new("a\nb\nc\nd", "txt").with_vim_enabled(true);
+        editor.cursors.set_single((2, 0));
+        vim_keys(&mut editor, "mcggOnew\u{1b}`c");
+        assert_eq!(editor.cursors.primary_position(), (3, 0));
+        assert_eq!(editor.buffer.line(3), "c");
+
+        vim_keys(&mut editor, "ggdd'c");
+        assert_eq!(editor.cursors.primary_position(), (2, 0));
+        assert_eq!(editor.buffer.line(2), "c");
+    }
+
+    #[test]
+    fn test_vim_jumps_return_with_control_o_and_backticks() {
+        let mut editor = CodeEditor::new("one\ntwo\nthree\nfour", "txt")
+            .with_vim_enabled(true);
+        editor.cursors.set_single((1, 1));
+        vim_keys(&mut editor, "G");
+        assert_eq!(editor.cursors.primary_position(), (3, 0));
+
+        vim_keys(&mut editor, "``");
+        assert_eq!(editor.cursors.primary_position(), (1, 1));
+        vim_keys(&mut editor, "``");
+        assert_eq!(editor.cursors.primary_position(), (3, 0));
+
+        vim_keys(&mut editor, "gg/thr\n");
+        assert_eq!(editor.cursors.primary_position(), (2, 0));
+        let _ = editor.update(&Message::NavigateBack);
+        assert_eq!(editor.cursors.primary_position(), (0, 0));
+        let _ = editor.update(&Message::NavigateBack);
+        assert_eq!(editor.cursors.primary_position(), (3, 0));
+        let _ = editor.update(&Message::NavigateForward);
+        assert_eq!(editor.cursors.primary_position(), (0, 0));
+    }
 }
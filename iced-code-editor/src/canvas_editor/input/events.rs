@@ -7,7 +7,7 @@ use iced::{Point, Rectangle, keyboard, mouse};
 use crate::canvas_editor::features::folding;
 use crate::canvas_editor::features::vim::VimMode;
 use crate::canvas_editor::{
-    ArrowDirection, CodeEditor, FOCUSED_EDITOR_ID, Message,
+    ArrowDirection, CodeEditor, FOCUSED_EDITOR_ID, Message, WordUnit,
 };
 
 /// Returns `true` when either `key` or `modified_key` is the character `ch`.
@@ -94,15 +94,28 @@ fn write_shortcut(
 }
 
 /// Handles copy (`Ctrl/Cmd+C`, `Ctrl+Insert`), cut (`Ctrl/Cmd+X`), select
-/// all (`Ctrl/Cmd+A`), and paste (`Ctrl/Cmd+V`, `Shift+Insert`, read from
-/// clipboard and forwarded as an empty [`Message::Paste`]).
+/// all (`Ctrl/Cmd+A`), the clipboard history picker (`Ctrl/Cmd+Shift+V`,
+/// when `history_picker` is set because the command palette that shows it is
+/// enabled), and paste (`Ctrl/Cmd+V`, `Shift+Insert`, read from clipboard
+/// and forwarded as an empty [`Message::Paste`]).
 fn clipboard_shortcut(
     key: &keyboard::Key,
     modified_key: &keyboard::Key,
     modifiers: &keyboard::Modifiers,
+    history_picker: bool,
 ) -> Option<Action<Message>> {
     let command_pressed = modifiers.command() || modifiers.control();
 
+    if history_picker
+        && command_pressed
+        && modifiers.shift()
+        && is_key_char(key, modified_key, "v")
+    {
+        return Some(
+            Action::publish(Message::OpenClipboardHistory).and_capture(),
+        );
+    }
+
     if (command_pressed && is_key_char(key, modified_key, "c"))
         || (modifiers.control()
             && matches!(
@@ -134,6 +147,35 @@ fn clipboard_shortcut(
     None
 }
 
+/// Handles `Ctrl+Alt+Shift+Arrow`, which extends a column (box) selection.
+/// Tried before [`multi_cursor_shortcut`] and the word-wise group, whose
+/// `Ctrl+Alt+Arrow` bindings ignore Shift.
+fn column_selection_shortcut(
+    key: &keyboard::Key,
+    modifiers: &keyboard::Modifiers,
+) -> Option<Action<Message>> {
+    if !(modifiers.control() && modifiers.alt() && modifiers.shift()) {
+        return None;
+    }
+
+    let direction = match key {
+        keyboard::Key::Named(keyboard::key::Named::ArrowUp) => {
+            ArrowDirection::Up
+        }
+        keyboard::Key::Named(keyboard::key::Named::ArrowDown) => {
+            ArrowDirection::Down
+        }
+        keyboard::Key::Named(keyboard::key::Named::ArrowLeft) => {
+            ArrowDirection::Left
+        }
+        keyboard::Key::Named(keyboard::key::Named::ArrowRight) => {
+            ArrowDirection::Right
+        }
+        _ => return None,
+    };
+    Some(Action::publish(Message::ColumnSelect(direction)).and_capture())
+}
+
 /// Handles `Ctrl/Cmd+D` (select next occurrence) and `Ctrl+Alt+Up`/`Down`
 /// (add a cursor above/below the current one).
 fn multi_cursor_shortcut(
@@ -167,8 +209,8 @@ fn multi_cursor_shortcut(
 }
 
 /// Handles direct-edit shortcuts that don't touch the clipboard: toggling
-/// the line comment (`Ctrl/Cmd+/`) and deleting the current selection
-/// (`Shift+Delete`).
+/// the line comment (`Ctrl/Cmd+/`), toggling a block comment
+/// (`Shift+Alt+A`) and deleting the current selection (`Shift+Delete`).
 fn editing_shortcut(
     key: &keyboard::Key,
     modified_key: &keyboard::Key,
@@ -182,6 +224,17 @@ fn editing_shortcut(
         return Some(Action::publish(Message::ToggleComment).and_capture());
     }
 
+    if !command_pressed
+        && modifiers.alt()
+        && modifiers.shift()
+        && (is_key_char(key, modified_key, "a")
+            || is_key_char(key, modified_key, "A"))
+    {
+        return Some(
+            Action::publish(Message::ToggleBlockComment).and_capture(),
+        );
+    }
+
     if modifiers.shift()
         && matches!(key, keyboard::Key::Named(keyboard::key::Named::Delete))
     {
@@ -191,9 +244,10 @@ fn editing_shortcut(
     None
 }
 
-/// Handles `Alt+Up`/`Alt+Down` (move the current line) and
-/// `Shift+Alt+Up`/`Down` (duplicate it). Excludes Control to avoid
-/// clashing with the `Ctrl+Alt+Up`/`Down` multi-cursor shortcuts (see
+/// Handles `Alt+Up`/`Alt+Down` (move the current line),
+/// `Shift+Alt+Up`/`Down` (duplicate it) and `Shift+Alt+Right`/`Left`
+/// (expand/shrink the selection). Excludes Control to avoid clashing with
+/// the `Ctrl+Alt+Up`/`Down` multi-cursor shortcuts (see
 /// [`multi_cursor_shortcut`]).
 fn line_move_shortcut(
     key: &keyboard::Key,
@@ -221,11 +275,25 @@ fn line_move_shortcut(
         return Some(Action::publish(message).and_capture());
     }
 
+    if modifiers.shift() {
+        let message = match key {
+            keyboard::Key::Named(keyboard::key::Named::ArrowRight) => {
+                Message::ExpandSelection
+            }
+            keyboard::Key::Named(keyboard::key::Named::ArrowLeft) => {
+                Message::ShrinkSelection
+            }
+            _ => return None,
+        };
+        return Some(Action::publish(message).and_capture());
+    }
+
     None
 }
 
-/// Handles `Ctrl/Cmd+Home` (jump to the start of the document) and
-/// `Ctrl/Cmd+End` (jump to the end).
+/// Handles `Ctrl/Cmd+Home` (jump to the start of the document),
+/// `Ctrl/Cmd+End` (jump to the end), and `F7`/`Shift+F7` (step to the
+/// next/previous occurrence of the symbol under the cursor).
 fn navigation_shortcut(
     key: &keyboard::Key,
     modifiers: &keyboard::Modifiers,
@@ -244,6 +312,18 @@ fn navigation_shortcut(
         return Some(Action::publish(Message::CtrlEnd).and_capture());
     }
 
+    if !command_pressed
+        && !modifiers.alt()
+        && matches!(key, keyboard::Key::Named(keyboard::key::Named::F7))
+    {
+        let message = if modifiers.shift() {
+            Message::PreviousDocumentHighlight
+        } else {
+            Message::NextDocumentHighlight
+        };
+        return Some(Action::publish(message).and_capture());
+    }
+
     None
 }
 
@@ -293,20 +373,79 @@ impl CodeEditor {
     ) -> Option<Action<Message>> {
         vim_toggle_shortcut(key, modified_key, modifiers)
             .or_else(|| write_shortcut(key, modified_key, modifiers))
+            .or_else(|| self.snippet_shortcut(key, modifiers))
             .or_else(|| self.focus_navigation_shortcut(key, modifiers))
-            .or_else(|| clipboard_shortcut(key, modified_key, modifiers))
+            .or_else(|| self.vim_control_shortcut(key, modified_key, modifiers))
+            .or_else(|| {
+                clipboard_shortcut(
+                    key,
+                    modified_key,
+                    modifiers,
+                    self.command_palette_enabled,
+                )
+            })
             .or_else(|| self.history_shortcut(key, modified_key, modifiers))
+            .or_else(|| self.jumplist_shortcut(key, modified_key, modifiers))
             .or_else(|| self.dialog_shortcut(key, modified_key, modifiers))
             .or_else(|| self.escape_shortcut(key))
+            .or_else(|| column_selection_shortcut(key, modifiers))
             .or_else(|| multi_cursor_shortcut(key, modified_key, modifiers))
             .or_else(|| editing_shortcut(key, modified_key, modifiers))
+            .or_else(|| self.word_shortcut(key, modifiers))
             .or_else(|| line_move_shortcut(key, modifiers))
             .or_else(|| navigation_shortcut(key, modifiers))
             .or_else(|| self.folding_shortcut(key, modified_key, modifiers))
+            .or_else(|| self.breakpoint_shortcut(key, modifiers))
+    }
+
+    /// Handles the keys of a snippet session while the caret is on its
+    /// current tabstop: `Tab`/`Shift+Tab` move to the next/previous tabstop,
+    /// and the up/down arrows pick among the options of a choice. Skipped
+    /// while the search dialog is open and outside Vim's Insert mode.
+    fn snippet_shortcut(
+        &self,
+        key: &keyboard::Key,
+        modifiers: &keyboard::Modifiers,
+    ) -> Option<Action<Message>> {
+        if !self.on_snippet_tabstop()
+            || self.search_state.is_open
+            || modifiers.control()
+            || modifiers.alt()
+            || modifiers.logo()
+            || (self.vim_enabled && self.vim_state.mode() != VimMode::Insert)
+        {
+            return None;
+        }
+
+        let message = match key {
+            keyboard::Key::Named(keyboard::key::Named::Tab) => {
+                if modifiers.shift() {
+                    Message::PreviousSnippetTabstop
+                } else {
+                    Message::NextSnippetTabstop
+                }
+            }
+            keyboard::Key::Named(
+                direction @ (keyboard::key::Named::ArrowUp
+                | keyboard::key::Named::ArrowDown),
+            ) if !modifiers.shift() => {
+                let (choices, selected) = self.snippet_choices()?;
+                let count = choices.len();
+                let next = if *direction == keyboard::key::Named::ArrowDown {
+                    (selected + 1) % count
+                } else {
+                    (selected + count - 1) % count
+                };
+                Message::SelectSnippetChoice(next)
+            }
+            _ => return None,
+        };
+        Some(Action::publish(message).and_capture())
     }
 
-    /// Handles `Shift+Tab` for backward focus-chain navigation between
-    /// editors. Plain `Tab` inserts indentation instead, handled by
+    /// Handles `Shift+Tab`: outdents the selected lines while a selection
+    /// spans several lines, and otherwise navigates the focus chain backward
+    /// between editors. Plain `Tab` inserts or indents instead, handled by
     /// [`Self::handle_character_input`]. Skipped while the search dialog is
     /// open, where Tab/Shift+Tab cycle its fields instead (see
     /// [`Self::dialog_shortcut`]).
@@ -319,13 +458,49 @@ impl CodeEditor {
             && modifiers.shift()
             && !self.search_state.is_open
         {
-            return Some(
-                Action::publish(Message::FocusNavigationShiftTab).and_capture(),
-            );
+            let message = if self.has_multi_line_selection()
+                && (!self.vim_enabled
+                    || self.vim_state.mode() == VimMode::Insert)
+            {
+                Message::OutdentLines
+            } else {
+                Message::FocusNavigationShiftTab
+            };
+            return Some(Action::publish(message).and_capture());
         }
         None
     }
 
+    /// Handles Vim's control keys outside Insert mode and the command line:
+    /// `Ctrl+V` enters Visual Block mode, and `Ctrl+A` / `Ctrl+X` add to and
+    /// subtract from the number under the cursor. Tried before
+    /// [`clipboard_shortcut`], whose `Ctrl/Cmd+V`, `Ctrl/Cmd+A` and
+    /// `Ctrl/Cmd+X` paste, select all and cut everywhere else; the `Cmd`
+    /// forms on macOS are left to it.
+    fn vim_control_shortcut(
+        &self,
+        key: &keyboard::Key,
+        modified_key: &keyboard::Key,
+        modifiers: &keyboard::Modifiers,
+    ) -> Option<Action<Message>> {
+        if !self.vim_enabled
+            || self.vim_state.mode() == VimMode::Insert
+            || self.vim_state.command_line_active()
+            || !modifiers.control()
+            || modifiers.shift()
+            || modifiers.alt()
+        {
+            return None;
+        }
+
+        [("v", '\u{16}'), ("a", '\u{1}'), ("x", '\u{18}')]
+            .into_iter()
+            .find(|(name, _)| is_key_char(key, modified_key, name))
+            .map(|(_, control)| {
+                Action::publish(Message::VimKey(control)).and_capture()
+            })
+    }
+
     /// Handles undo (`Ctrl/Cmd+Z`), redo (`Ctrl/Cmd+Y`, or `Shift+Cmd+Z` on
     /// macOS), and Vim's `Ctrl+R` redo binding in Normal mode — kept
     /// alongside the platform shortcuts above, which stay available in
@@ -364,6 +539,52 @@ impl CodeEditor {
         None
     }
 
+    /// Handles jumplist navigation: `Alt+Left`/`Alt+Right` in every mode,
+    /// and Vim's `Ctrl+O`/`Ctrl+I` in Normal mode.
+    fn jumplist_shortcut(
+        &self,
+        key: &keyboard::Key,
+        modified_key: &keyboard::Key,
+        modifiers: &keyboard::Modifiers,
+    ) -> Option<Action<Message>> {
+        if modifiers.alt()
+            && !modifiers.control()
+            && !modifiers.shift()
+            && !modifiers.logo()
+        {
+            let message = match key {
+                keyboard::Key::Named(keyboard::key::Named::ArrowLeft) => {
+                    Message::NavigateBack
+                }
+                keyboard::Key::Named(keyboard::key::Named::ArrowRight) => {
+                    Message::NavigateForward
+                }
+                _ => return None,
+            };
+            return Some(Action::publish(message).and_capture());
+        }
+
+        if self.vim_enabled
+            && self.vim_state.mode() == VimMode::Normal
+            && modifiers.control()
+            && !modifiers.shift()
+            && !modifiers.alt()
+        {
+            if is_key_char(key, modified_key, "o") {
+                return Some(
+                    Action::publish(Message::NavigateBack).and_capture(),
+                );
+            }
+            if is_key_char(key, modified_key, "i") {
+                return Some(
+                    Action::publish(Message::NavigateForward).and_capture(),
+                );
+            }
+        }
+
+        None
+    }
+
     /// Handles the search/replace, goto-line and command-palette dialogs:
     /// opening them (`Ctrl/Cmd+F`, `Ctrl/Cmd+H`, `Ctrl/Cmd+G`,
     /// `Ctrl/Cmd+Shift+P`), cycling the search dialog's fields while it is
@@ -431,8 +652,9 @@ impl CodeEditor {
         None
     }
 
-    /// Handles Escape: closes the innermost open dialog (command palette,
-    /// then go-to-line, then search), collapses a multi-cursor
+    /// Handles Escape: closes the innermost open dialog (color picker, then
+    /// command palette, then go-to-line, then search), leaves a snippet
+    /// session (Vim ends it when leaving Insert mode), collapses a multi-cursor
     /// selection down to the primary cursor (`handle_close_search_msg`
     /// does this when more than one cursor is active, even with no dialog
     /// open), or forwards it to Vim's modal state machine. If none of
@@ -445,12 +667,16 @@ impl CodeEditor {
             return None;
         }
 
-        let message = if self.command_palette_state.is_open {
+        let message = if self.color_picker_state.is_open() {
+            Some(Message::CloseColorPicker)
+        } else if self.command_palette_state.is_open {
             Some(Message::CloseCommandPalette)
         } else if self.goto_line_state.is_open {
             Some(Message::CloseGotoLine)
         } else if self.search_state.is_open {
             Some(Message::CloseSearch)
+        } else if self.is_snippet_active() && !self.vim_enabled {
+            Some(Message::LeaveSnippet)
         } else if self.vim_enabled {
             Some(Message::VimKey('\u{1b}'))
         } else if self.cursors.is_multi() {
@@ -461,6 +687,44 @@ impl CodeEditor {
         message.map(|message| Action::publish(message).and_capture())
     }
 
+    /// Handles word-wise moves and deletions: `Ctrl/Cmd+Left`/`Right`
+    /// (with `Shift` to select) and `Ctrl/Cmd+Backspace`/`Delete`. Adding
+    /// `Alt` stops at camelCase and snake_case boundaries instead. Skipped
+    /// outside Vim's Insert mode; `Ctrl+Alt+Up`/`Down` stay with the
+    /// multi-cursor group (see [`multi_cursor_shortcut`]) and
+    /// `Ctrl+Alt+Shift+Arrow` with [`column_selection_shortcut`].
+    fn word_shortcut(
+        &self,
+        key: &keyboard::Key,
+        modifiers: &keyboard::Modifiers,
+    ) -> Option<Action<Message>> {
+        let command_pressed = modifiers.command() || modifiers.control();
+        if !command_pressed
+            || (self.vim_enabled && self.vim_state.mode() != VimMode::Insert)
+        {
+            return None;
+        }
+
+        let unit =
+            if modifiers.alt() { WordUnit::SubWord } else { WordUnit::Word };
+        let message = match key {
+            keyboard::Key::Named(keyboard::key::Named::ArrowLeft) => {
+                Message::WordLeft(unit, modifiers.shift())
+            }
+            keyboard::Key::Named(keyboard::key::Named::ArrowRight) => {
+                Message::WordRight(unit, modifiers.shift())
+            }
+            keyboard::Key::Named(keyboard::key::Named::Backspace) => {
+                Message::DeleteWordLeft(unit)
+            }
+            keyboard::Key::Named(keyboard::key::Named::Delete) => {
+                Message::DeleteWordRight(unit)
+            }
+            _ => return None,
+        };
+        Some(Action::publish(message).and_capture())
+    }
+
     /// Handles code-folding shortcuts, active only while folding is
     /// enabled: toggling the fold at the cursor (`Ctrl/Cmd+.`), folding
     /// everything (`Ctrl+K`), and unfolding everything (`Ctrl+J`).
@@ -504,6 +768,23 @@ impl CodeEditor {
         None
     }
 
+    /// Handles `F9`, which toggles the breakpoint on the cursor line while
+    /// breakpoints are enabled.
+    fn breakpoint_shortcut(
+        &self,
+        key: &keyboard::Key,
+        modifiers: &keyboard::Modifiers,
+    ) -> Option<Action<Message>> {
+        (self.breakpoints_enabled
+            && !modifiers.control()
+            && !modifiers.shift()
+            && !modifiers.alt()
+            && matches!(key, keyboard::Key::Named(keyboard::key::Named::F9)))
+        .then(|| {
+            Action::publish(Message::ToggleBreakpointAtCursor).and_capture()
+        })
+    }
+
     fn printable_input_message(&self, ch: char) -> Message {
         if self.vim_enabled && self.vim_state.mode() != VimMode::Insert {
             Message::VimKey(ch)
@@ -604,12 +885,23 @@ impl CodeEditor {
                     // Regular Tab: check if search dialog is open
                     if self.search_state.is_open {
                         Some(Message::SearchDialogTab)
+                    } else if self.has_multi_line_selection() {
+                        // Indent every selected line
+                        Some(Message::IndentLines)
                     } else {
-                        // Insert 4 spaces for Tab when not in search dialog
+                        // Insert one indentation level at each cursor
                         Some(Message::Tab)
                     }
                 }
             }
+            keyboard::Key::Named(
+                named @ (keyboard::key::Named::ArrowUp
+                | keyboard::key::Named::ArrowDown),
+            ) if self.vim_state.command_line_active() => {
+                Some(Message::VimCommandHistory(
+                    *named == keyboard::key::Named::ArrowUp,
+                ))
+            }
             keyboard::Key::Named(keyboard::key::Named::ArrowUp) => {
                 Some(Message::ArrowKey(ArrowDirection::Up, modifiers.shift()))
             }
@@ -725,7 +1017,7 @@ impl CodeEditor {
         }
 
         // The fold margin is the strip between the line-number area and the text.
-        let margin_start = self.line_number_gutter_width();
+        let margin_start = self.fold_margin_start();
         if point.x < margin_start || point.x >= self.gutter_width() {
             return None;
         }
@@ -761,6 +1053,15 @@ impl CodeEditor {
         match event {
             mouse::Event::ButtonPressed(mouse::Button::Left) => {
                 cursor.position_in(bounds).map(|position| {
+                    // Clicking the breakpoint margin toggles a breakpoint.
+                    if let Some(line) = self.breakpoint_line_at_point(position)
+                    {
+                        return Action::publish(Message::ToggleBreakpoint(
+                            line,
+                        ))
+                        .and_capture();
+                    }
+
                     // Clicking a fold chevron toggles the block instead of
                     // moving the caret.
                     if let Some(header) = self.fold_header_at_point(position) {
@@ -768,6 +1069,16 @@ impl CodeEditor {
                             .and_capture();
                     }
 
+                    // Clicking a color swatch opens the picker on its literal.
+                    if let Some((line, start_col)) =
+                        self.color_swatch_at_point(position)
+                    {
+                        return Action::publish(Message::OpenColorPicker(
+                            line, start_col,
+                        ))
+                        .and_capture();
+                    }
+
                     // Check for Ctrl (or Command on macOS) + Click
                     #[cfg(target_os = "macos")]
                     let is_jump_click = self.modifiers.get().command();
@@ -775,13 +1086,18 @@ impl CodeEditor {
                     let is_jump_click = self.modifiers.get().control();
 
                     if is_jump_click {
-                        return Action::publish(Message::JumpClick(position));
+                        return Action::publish(
+                            self.modifier_click_message(position),
+                        );
                     }
 
-                    // Alt+Click: add a new cursor at the clicked position
+                    // Alt+Click: add a new cursor at the clicked position;
+                    // Alt+Shift+Click starts a column selection instead.
                     if self.modifiers.get().alt() {
                         let message = if self.vim_enabled {
                             Message::MouseClick(position)
+                        } else if self.modifiers.get().shift() {
+                            Message::ColumnSelectClick(position)
                         } else {
                             Message::AltClick(position)
                         };
@@ -964,10 +1280,35 @@ mod tests {
             .handle_keyboard_shortcuts(&key, &key, &keyboard::Modifiers::CTRL)
             .map(|action| action.into_inner().0);
         assert!(matches!(message, Some(Some(Message::Redo))));
+
+        // Ctrl+V enters Visual Block mode, but still pastes in Insert mode.
+        let key = keyboard::Key::Character("v".into());
+        assert!(matches!(
+            shortcut(&editor, &key, keyboard::Modifiers::CTRL),
+            Some(Message::VimKey('\u{16}'))
+        ));
+        let _ = editor.vim_state.parse_key('i');
+        assert!(matches!(
+            shortcut(&editor, &key, keyboard::Modifiers::CTRL),
+            Some(Message::Paste(_))
+        ));
+
+        // Ctrl+A and Ctrl+X change numbers outside Insert mode.
+        let _ = editor.vim_state.parse_key('\u{1b}');
+        let key = keyboard::Key::Character("a".into());
+        assert!(matches!(
+            shortcut(&editor, &key, keyboard::Modifiers::CTRL),
+            Some(Message::VimKey('\u{1}'))
+        ));
+        let key = keyboard::Key::Character("x".into());
+        assert!(matches!(
+            shortcut(&editor, &key, keyboard::Modifiers::CTRL),
+            Some(Message::VimKey('\u{18}'))
+        ));
     }
 
     #[test]
-    fn test_vim_command_line_routes_enter_and_backspace() {
+    fn test_vim_command_line_routes_enter_backspace_and_history() {
         let mut editor = CodeEditor::new("abc", "txt").with_vim_enabled(true);
         editor.request_focus();
         editor.has_canvas_focus = true;
@@ -991,6 +1332,15 @@ mod tests {
             )
             .map(|action| action.into_inner().0);
         assert!(matches!(enter, Some(Some(Message::VimKey('\n')))));
+
+        let up = editor
+            .handle_character_input(
+                &keyboard::Key::Named(keyboard::key::Named::ArrowUp),
+                &keyboard::Modifiers::NONE,
+                None,
+            )
+            .map(|action| action.into_inner().0);
+        assert!(matches!(up, Some(Some(Message::VimCommandHistory(true)))));
     }
 
     #[test]
@@ -1069,6 +1419,7 @@ mod tests {
         let editor = CodeEditor::new("fn a() {\n}\n", "rs");
         let ctrl = keyboard::Modifiers::CTRL;
         let ctrl_alt = keyboard::Modifiers::CTRL | keyboard::Modifiers::ALT;
+        let shift_alt = keyboard::Modifiers::SHIFT | keyboard::Modifiers::ALT;
         // A base key that matches no shortcut, so only `modified_key` can
         // possibly satisfy the check.
         let unmatched = character("&");
@@ -1083,7 +1434,7 @@ mod tests {
         type ShortcutCase =
             (&'static str, keyboard::Modifiers, fn(&Message) -> bool);
 
-        let cases: [ShortcutCase; 15] = [
+        let cases: [ShortcutCase; 16] = [
             ("v", ctrl_alt, |m| matches!(m, Message::ToggleVimMode)),
             ("s", ctrl, |m| matches!(m, Message::WriteRequested)),
             ("c", ctrl, |m| matches!(m, Message::Copy)),
@@ -1099,6 +1450,7 @@ mod tests {
             ("k", ctrl, |m| matches!(m, Message::FoldAll)),
             ("j", ctrl, |m| matches!(m, Message::UnfoldAll)),
             (".", ctrl, |m| matches!(m, Message::ToggleFoldAtCursor)),
+            ("A", shift_alt, |m| matches!(m, Message::ToggleBlockComment)),
         ];
 
         for (ch, modifiers, expected) in cases {
@@ -1201,6 +1553,24 @@ mod tests {
         ));
     }
 
+    #[test]
+    fn test_control_shift_v_opens_the_clipboard_history() {
+        let editor = CodeEditor::new("one", "txt");
+        let ctrl_shift = keyboard::Modifiers::CTRL | keyboard::Modifiers::SHIFT;
+
+        assert!(matches!(
+            shortcut(&editor, &character("v"), ctrl_shift),
+            Some(Message::OpenClipboardHistory)
+        ));
+
+        // Without the palette there is no picker; Shift+V stays a paste.
+        let editor = editor.with_command_palette_enabled(false);
+        assert!(matches!(
+            shortcut(&editor, &character("v"), ctrl_shift),
+            Some(Message::Paste(_))
+        ));
+    }
+
     #[test]
     fn test_clipboard_shortcuts_accept_the_insert_key_bindings() {
         let editor = CodeEditor::new("one\ntwo", "txt");
@@ -1277,6 +1647,98 @@ mod tests {
         ));
     }
 
+    #[test]
+    fn test_word_shortcuts_move_select_and_delete_by_word() {
+        let mut editor = CodeEditor::new("fooBar baz", "txt");
+        let ctrl = keyboard::Modifiers::CTRL;
+        let left = named(keyboard::key::Named::ArrowLeft);
+        let right = named(keyboard::key::Named::ArrowRight);
+
+        assert!(matches!(
+            shortcut(&editor, &left, ctrl),
+            Some(Message::WordLeft(WordUnit::Word, false))
+        ));
+        assert!(matches!(
+            shortcut(&editor, &right, ctrl | keyboard::Modifiers::SHIFT),
+            Some(Message::WordRight(WordUnit::Word, true))
+        ));
+        assert!(matches!(
+            shortcut(&editor, &right, ctrl | keyboard::Modifiers::ALT),
+            Some(Message::WordRight(WordUnit::SubWord, false))
+        ));
+        assert!(matches!(
+            shortcut(&editor, &named(keyboard::key::Named::Backspace), ctrl),
+            Some(Message::DeleteWordLeft(WordUnit::Word))
+        ));
+        assert!(matches!(
+            shortcut(&editor, &named(keyboard::key::Named::Delete), ctrl),
+            Some(Message::DeleteWordRight(WordUnit::Word))
+        ));
+
+        // Vim's Normal mode keeps Ctrl+Arrow to itself.
+        editor.set_vim_enabled(true);
+        assert!(shortcut(&editor, &left, ctrl).is_none());
+    }
+
+    #[test]
+    fn test_jumplist_shortcuts_navigate_back_and_forward() {
+        let mut editor = CodeEditor::new("one\ntwo", "txt");
+        let alt = keyboard::Modifiers::ALT;
+        let ctrl = keyboard::Modifiers::CTRL;
+        let left = named(keyboard::key::Named::ArrowLeft);
+        let right = named(keyboard::key::Named::ArrowRight);
+
+        assert!(matches!(
+            shortcut(&editor, &left, alt),
+            Some(Message::NavigateBack)
+        ));
+        assert!(matches!(
+            shortcut(&editor, &right, alt),
+            Some(Message::NavigateForward)
+        ));
+        // Ctrl+O and Ctrl+I belong to Vim's Normal mode only.
+        assert!(shortcut(&editor, &character("o"), ctrl).is_none());
+
+        editor.set_vim_enabled(true);
+        assert!(matches!(
+            shortcut(&editor, &character("o"), ctrl),
+            Some(Message::NavigateBack)
+        ));
+        assert!(matches!(
+            shortcut(&editor, &character("i"), ctrl),
+            Some(Message::NavigateForward)
+        ));
+        assert!(matches!(
+            shortcut(&editor, &left, alt),
+            Some(Message::NavigateBack)
+        ));
+    }
+
+    #[test]
+    fn test_control_alt_shift_arrows_extend_a_column_selection() {
+        let editor = CodeEditor::new("one\ntwo", "txt");
+        let modifiers = keyboard::Modifiers::CTRL
+            | keyboard::Modifiers::ALT
+            | keyboard::Modifiers::SHIFT;
+
+        assert!(matches!(
+            shortcut(
+                &editor,
+                &named(keyboard::key::Named::ArrowDown),
+                modifiers
+            ),
+            Some(Message::ColumnSelect(ArrowDirection::Down))
+        ));
+        assert!(matches!(
+            shortcut(
+                &editor,
+                &named(keyboard::key::Named::ArrowRight),
+                modifiers
+            ),
+            Some(Message::ColumnSelect(ArrowDirection::Right))
+        ));
+    }
+
     #[test]
     fn test_line_move_shortcuts_move_and_duplicate_lines() {
         let editor = CodeEditor::new("one\ntwo\nthree", "txt");
@@ -1340,6 +1802,43 @@ mod tests {
         ));
     }
 
+    #[test]
+    fn test_f7_steps_through_document_highlights() {
+        let editor = CodeEditor::new("one\ntwo", "txt");
+        let f7 = named(keyboard::key::Named::F7);
+
+        assert!(matches!(
+            shortcut(&editor, &f7, keyboard::Modifiers::NONE),
+            Some(Message::NextDocumentHighlight)
+        ));
+        assert!(matches!(
+            shortcut(&editor, &f7, keyboard::Modifiers::SHIFT),
+            Some(Message::PreviousDocumentHighlight)
+        ));
+    }
+
+    #[test]
+    fn test_shift_alt_arrows_expand_and_shrink_the_selection() {
+        let editor = CodeEditor::new("one\ntwo", "txt");
+        let shift_alt = keyboard::Modifiers::SHIFT | keyboard::Modifiers::ALT;
+        let right = named(keyboard::key::Named::ArrowRight);
+        let left = named(keyboard::key::Named::ArrowLeft);
+
+        assert!(matches!(
+            shortcut(&editor, &right, shift_alt),
+            Some(Message::ExpandSelection)
+        ));
+        assert!(matches!(
+            shortcut(&editor, &left, shift_alt),
+            Some(Message::ShrinkSelection)
+        ));
+        // Without Shift the arrows walk the jumplist instead.
+        assert!(matches!(
+            shortcut(&editor, &right, keyboard::Modifiers::ALT),
+            Some(Message::NavigateForward)
+        ));
+    }
+
     #[test]
     fn test_shift_tab_navigates_focus_unless_the_search_dialog_is_open() {
         let mut editor = CodeEditor::new("one", "txt");
@@ -1365,6 +1864,66 @@ mod tests {
         ));
     }
 
+    #[test]
+    fn test_tab_and_shift_tab_indent_a_multi_line_selection() {
+        let mut editor = CodeEditor::new("one\ntwo", "txt");
+        editor.request_focus();
+        editor.has_canvas_focus = true;
+        editor.focus_locked = false;
+        let tab = named(keyboard::key::Named::Tab);
+        let tab_message = |editor: &CodeEditor| {
+            editor
+                .handle_character_input(&tab, &keyboard::Modifiers::NONE, None)
+                .and_then(|action| action.into_inner().0)
+        };
+
+        // A selection within one line is replaced by typing, as usual.
+        editor.cursors.set_single((0, 2));
+        editor.cursors.primary_mut().anchor = Some((0, 0));
+        assert!(matches!(tab_message(&editor), Some(Message::Tab)));
+
+        editor.cursors.set_single((1, 1));
+        editor.cursors.primary_mut().anchor = Some((0, 0));
+        assert!(matches!(tab_message(&editor), Some(Message::IndentLines)));
+        assert!(matches!(
+            shortcut(&editor, &tab, keyboard::Modifiers::SHIFT),
+            Some(Message::OutdentLines)
+        ));
+    }
+
+    #[test]
+    fn test_tab_moves_between_snippet_tabstops_while_on_one() {
+        let mut editor = CodeEditor::new("", "txt");
+        let tab = named(keyboard::key::Named::Tab);
+        let down = named(keyboard::key::Named::ArrowDown);
+        let escape = named(keyboard::key::Named::Escape);
+        let _ = editor.insert_snippet("${1|a,b|} x $2");
+
+        assert!(matches!(
+            shortcut(&editor, &tab, keyboard::Modifiers::NONE),
+            Some(Message::NextSnippetTabstop)
+        ));
+        assert!(matches!(
+            shortcut(&editor, &tab, keyboard::Modifiers::SHIFT),
+            Some(Message::PreviousSnippetTabstop)
+        ));
+        assert!(matches!(
+            shortcut(&editor, &down, keyboard::Modifiers::NONE),
+            Some(Message::SelectSnippetChoice(1))
+        ));
+        assert!(matches!(
+            shortcut(&editor, &escape, keyboard::Modifiers::NONE),
+            Some(Message::LeaveSnippet)
+        ));
+
+        // Away from the tabstop, Tab is back to focus navigation.
+        editor.cursors.set_single((0, 2));
+        assert!(matches!(
+            shortcut(&editor, &tab, keyboard::Modifiers::SHIFT),
+            Some(Message::FocusNavigationShiftTab)
+        ));
+    }
+
     #[test]
     fn test_history_shortcuts_route_undo_and_both_redo_bindings() {
         let editor = CodeEditor::new("one", "txt");
@@ -1527,6 +2086,22 @@ mod tests {
         assert!(shortcut(&editor, &character("j"), ctrl).is_none());
     }
 
+    #[test]
+    fn test_f9_toggles_a_breakpoint_only_when_breakpoints_are_enabled() {
+        let f9 = named(keyboard::key::Named::F9);
+        let none = keyboard::Modifiers::default();
+
+        let editor = CodeEditor::new("fn a() {\n}\n", "rs");
+        assert!(shortcut(&editor, &f9, none).is_none());
+
+        let editor = editor.with_breakpoints_enabled(true);
+        assert!(matches!(
+            shortcut(&editor, &f9, none),
+            Some(Message::ToggleBreakpointAtCursor)
+        ));
+        assert!(shortcut(&editor, &f9, keyboard::Modifiers::CTRL).is_none());
+    }
+
     // =========================================================================
     // Mouse events
     // =========================================================================
@@ -1756,6 +2331,21 @@ mod tests {
         ));
     }
 
+    #[test]
+    fn test_alt_shift_click_starts_a_column_selection() {
+        let editor = CodeEditor::new("one\ntwo", "txt");
+        editor
+            .modifiers
+            .set(keyboard::Modifiers::ALT | keyboard::Modifiers::SHIFT);
+
+        let message =
+            mouse_message(&editor, &LEFT_PRESS, &cursor_at(70.0, 10.0));
+        assert!(
+            matches!(message, Some(Message::ColumnSelectClick(_))),
+            "expected a ColumnSelectClick, got {message:?}"
+        );
+    }
+
     #[test]
     fn test_control_click_jumps_to_definition_without_capturing() {
         let editor = CodeEditor::new("one\ntwo", "txt");
@@ -1780,6 +2370,53 @@ mod tests {
         ));
     }
 
+    #[test]
+    fn test_control_click_on_a_link_activates_it() {
+        let mut editor =
+            CodeEditor::new("https://example.com/a/long/enough/path", "txt");
+        editor
+            .modifiers
+            .set(keyboard::Modifiers::CTRL | keyboard::Modifiers::COMMAND);
+        let cursor = cursor_at(80.0, 12.0);
+
+        let message = mouse_message(&editor, &LEFT_PRESS, &cursor);
+        assert!(
+            matches!(
+                &message,
+                Some(Message::LinkActivated { target })
+                    if target == "https://example.com/a/long/enough/path"
+            ),
+            "expected a LinkActivated, got {message:?}"
+        );
+
+        // With links disabled the same click is an ordinary jump.
+        editor.set_document_links_enabled(false);
+        assert!(matches!(
+            mouse_message(&editor, &LEFT_PRESS, &cursor),
+            Some(Message::JumpClick(_))
+        ));
+    }
+
+    #[test]
+    fn test_clicking_a_color_swatch_opens_the_picker() {
+        let editor = CodeEditor::new("a { color: #ff0000; }", "css");
+        let swatch = editor.color_swatch_bounds(
+            &editor.visual_lines_cached(editor.viewport_width)[0],
+            0.0,
+            18,
+        );
+        let cursor = cursor_at(swatch.center_x(), swatch.center_y());
+
+        assert!(matches!(
+            mouse_message(&editor, &LEFT_PRESS, &cursor),
+            Some(Message::OpenColorPicker(0, 11))
+        ));
+        assert_eq!(
+            mouse_status(&editor, &LEFT_PRESS, &cursor),
+            Some(event::Status::Captured)
+        );
+    }
+
     #[test]
     fn test_repeated_clicks_at_one_spot_escalate_to_double_then_triple() {
         let editor = CodeEditor::new("one two three", "txt");
@@ -1827,10 +2464,8 @@ mod tests {
 
         // The chevron column sits between the line-number area and the text;
         // aim at its middle, on the first visual line.
-        let x = f32::midpoint(
-            editor.line_number_gutter_width(),
-            editor.gutter_width(),
-        );
+        let x =
+            f32::midpoint(editor.fold_margin_start(), editor.gutter_width());
         let y = editor.line_height * 0.5;
         let cursor = cursor_at(x, y);
 
@@ -1848,6 +2483,28 @@ mod tests {
         ));
     }
 
+    #[test]
+    fn test_clicking_the_breakpoint_margin_toggles_a_breakpoint() {
+        let editor = CodeEditor::new("fn a() {\n    body\n}\n", "rs")
+            .with_breakpoints_enabled(true);
+
+        // The breakpoint margin is the leftmost strip of the gutter; the fold
+        // chevron still sits right before the text.
+        let cursor = cursor_at(4.0, editor.line_height * 1.5);
+        assert!(matches!(
+            mouse_message(&editor, &LEFT_PRESS, &cursor),
+            Some(Message::ToggleBreakpoint(1))
+        ));
+
+        let x =
+            f32::midpoint(editor.fold_margin_start(), editor.gutter_width());
+        let cursor = cursor_at(x, editor.line_height * 0.5);
+        assert!(matches!(
+            mouse_message(&editor, &LEFT_PRESS, &cursor),
+            Some(Message::ToggleFold(0))
+        ));
+    }
+
     #[test]
     fn test_clicking_the_text_area_is_not_a_fold_toggle() {
         let editor = CodeEditor::new("fn a() {\n    body\n}\n", "rs");
@@ -38,7 +38,8 @@ impl CodeEditor {
     /// Handles paste operations.
     ///
     /// If the provided text is empty, reads from clipboard. Otherwise pastes
-    /// the provided text at the cursor position.
+    /// the provided text at the cursor position, re-indenting the pasted
+    /// lines when [`CodeEditor::reindent_on_paste`] is enabled.
     ///
     /// # Arguments
     ///
@@ -64,11 +65,31 @@ impl CodeEditor {
             // deletions it performs first) into one composite, so a single
             // undo restores every cursor's paste instead of just the last.
             let multi = self.cursors.len() > 1;
-            if multi {
+            let reindent =
+                self.reindent_on_paste && !multi && text.contains('\n');
+            if multi || reindent {
                 self.ensure_grouping_started();
             }
+            let cursor = self.cursors.primary();
+            let start = cursor
+                .selection_range()
+                .map_or(cursor.position, |(start, _)| start);
             self.paste_text(text);
-            if multi {
+            if reindent {
+                // The first pasted line only moves when the paste starts it.
+                let before: String =
+                    self.buffer.line(start.0).chars().take(start.1).collect();
+                let first = if before.trim().is_empty() {
+                    start.0
+                } else {
+                    start.0 + 1
+                };
+                let last = self.cursors.primary_position().0;
+                if first <= last {
+                    self.reindent_range(first, last);
+                }
+            }
+            if multi || reindent {
                 self.end_grouping_if_active();
             }
             self.finish_edit_operation();
@@ -1,4 +1,4 @@
-//! Backspace, delete, and explicit-selection-delete message handlers.
+//! Backspace, delete, word-delete, and explicit-selection-delete message handlers.
 
 use iced::Task;
 
@@ -6,7 +6,8 @@ use super::{EditType, adjust_other_cursors};
 use crate::canvas_editor::editing::command::{
     Command, DeleteCharCommand, DeleteForwardCommand,
 };
-use crate::canvas_editor::{CodeEditor, Message};
+use crate::canvas_editor::features::column_selection::ColumnSelection;
+use crate::canvas_editor::{CodeEditor, Message, WordUnit};
 
 impl CodeEditor {
     /// Handles Backspace key press.
@@ -127,6 +128,140 @@ impl CodeEditor {
         Task::none()
     }
 
+    /// Handles Backspace/Delete while a column selection is active.
+    ///
+    /// A rectangle with width has its text deleted on every line and
+    /// collapses onto its left edge. An empty one deletes one character
+    /// before (or after) the caret on each line, without ever joining
+    /// lines; carets in virtual space just move left with the others. The
+    /// column selection stays active either way.
+    ///
+    /// # Arguments
+    ///
+    /// * `selection` - The active column selection
+    /// * `forward` - `true` for Delete, `false` for Backspace
+    ///
+    /// # Returns
+    ///
+    /// A `Task<Message>` that scrolls to keep the cursor visible
+    pub(crate) fn handle_column_delete(
+        &mut self,
+        mut selection: ColumnSelection,
+        forward: bool,
+    ) -> Task<Message> {
+        self.end_grouping_if_active();
+
+        let visual = if self.delete_selection_if_present() {
+            selection.left()
+        } else {
+            let visual = selection.active.1;
+            self.ensure_grouping_started();
+            for idx in 0..self.cursors.len() {
+                let pos = self.cursors.as_slice()[idx].position;
+                if self.column_at_visual(pos.0, visual).1 > 0 {
+                    continue;
+                }
+                let mut cmd: Box<dyn Command> = if forward {
+                    if pos.1 >= self.buffer.line_len(pos.0) {
+                        continue;
+                    }
+                    Box::new(DeleteForwardCommand::new(
+                        &self.buffer,
+                        pos.0,
+                        pos.1,
+                        pos,
+                    ))
+                } else {
+                    if pos.1 == 0 {
+                        continue;
+                    }
+                    Box::new(DeleteCharCommand::new(
+                        &self.buffer,
+                        pos.0,
+                        pos.1,
+                        pos,
+                    ))
+                };
+                let mut cursor_pos = pos;
+                cmd.execute(&mut self.buffer, &mut cursor_pos);
+                self.cursors.as_mut_slice()[idx].position = cursor_pos;
+                self.history.push(cmd);
+            }
+            self.end_grouping_if_active();
+            self.finish_edit_operation();
+
+            // The caret column follows the primary cursor's line.
+            let (line, col) = self.cursors.primary_position();
+            if forward {
+                visual
+            } else if self.column_at_visual(line, visual).1 > 0 {
+                visual.saturating_sub(1)
+            } else {
+                self.visual_column(line, col)
+            }
+        };
+        selection.anchor.1 = visual;
+        selection.active.1 = visual;
+        self.column_selection = Some(selection);
+        self.select_columns(selection);
+        self.scroll_to_cursor()
+    }
+
+    /// Handles Ctrl+Backspace/Delete.
+    ///
+    /// If there's a selection, deletes the selection. Otherwise, deletes from
+    /// each cursor back to the previous word start or up to the next word
+    /// end, as Ctrl+Left/Right would move (see [`Self::handle_word_move`]).
+    /// A deletion never reaches past the neighbouring line break: at the
+    /// edge of a line it joins the lines, and before that it stops at the
+    /// edge, like VS Code.
+    ///
+    /// # Arguments
+    ///
+    /// * `forward` - `true` for Ctrl+Delete, `false` for Ctrl+Backspace
+    /// * `unit` - Whether to stop at sub-word boundaries
+    ///
+    /// # Returns
+    ///
+    /// A `Task<Message>` that scrolls to keep the cursor visible
+    pub(crate) fn handle_delete_word(
+        &mut self,
+        forward: bool,
+        unit: WordUnit,
+    ) -> Task<Message> {
+        self.end_grouping_if_active();
+
+        if !self.delete_selection_if_present() {
+            // Select up to each target and delete the selections in one go,
+            // so several cursors undo as a single step.
+            self.clear_selection();
+            let targets = self.word_targets(forward, unit);
+            for (cursor, target) in
+                self.cursors.as_mut_slice().iter_mut().zip(targets)
+            {
+                let (line, col) = cursor.position;
+                cursor.set_anchor();
+                cursor.position = match (target.0 == line, forward) {
+                    (true, _) => target,
+                    (false, true) if col < self.buffer.line_len(line) => {
+                        (line, self.buffer.line_len(line))
+                    }
+                    (false, true) => (line + 1, 0),
+                    (false, false) if col > 0 => (line, 0),
+                    (false, false) => {
+                        (line - 1, self.buffer.line_len(line - 1))
+                    }
+                };
+            }
+            self.cursors.sort_and_merge();
+            if !self.delete_selection_if_present() {
+                self.clear_selection();
+                return Task::none();
+            }
+        }
+        self.scroll_to_cursor()
+    }
+
     /// Handles explicit selection deletion (Shift+Delete).
     ///
     /// Deletes the selected text if a selection exists.
@@ -255,4 +390,60 @@ mod tests {
         assert_eq!(editor.cursors.primary_position(), (0, 2));
         assert!(editor.cursors.primary().anchor.is_none());
     }
+
+    #[test]
+    fn test_delete_word_left_and_right() {
+        let mut editor = CodeEditor::new("let foo_bar = 1;", "rs");
+        editor.cursors.primary_mut().position = (0, 11);
+
+        let _ = editor.update(&Message::DeleteWordLeft(WordUnit::SubWord));
+        assert_eq!(editor.buffer.line(0), "let foo_ = 1;");
+
+        let _ = editor.update(&Message::DeleteWordLeft(WordUnit::Word));
+        assert_eq!(editor.buffer.line(0), "let  = 1;");
+        assert_eq!(editor.cursors.primary_position(), (0, 4));
+
+        let _ = editor.update(&Message::DeleteWordRight(WordUnit::Word));
+        assert_eq!(editor.buffer.line(0), "let  1;");
+    }
+
+    #[test]
+    fn test_delete_word_undoes_all_cursors_at_once() {
+        let mut editor = CodeEditor::new("one two\nthree four", "txt");
+        editor.cursors.primary_mut().position = (0, 7);
+        editor.cursors.add_cursor((1, 10));
+
+        let _ = editor.update(&Message::DeleteWordLeft(WordUnit::Word));
+        assert_eq!(editor.content(), "one \nthree ");
+
+        let _ = editor.update(&Message::Undo);
+        assert_eq!(editor.content(), "one two\nthree four");
+    }
+
+    #[test]
+    fn test_delete_word_at_document_start_is_a_no_op() {
+        let mut editor = CodeEditor::new("hello", "txt");
+        editor.cursors.primary_mut().position = (0, 0);
+
+        let _ = editor.update(&Message::DeleteWordLeft(WordUnit::Word));
+        assert_eq!(editor.content(), "hello");
+        assert!(editor.cursors.primary().anchor.is_none());
+    }
+
+    #[test]
+    fn test_delete_word_stops_at_line_breaks() {
+        let mut editor = CodeEditor::new("foo  \n\n  bar", "txt");
+        editor.cursors.primary_mut().position = (2, 2);
+
+        let _ = editor.update(&Message::DeleteWordLeft(WordUnit::Word));
+        assert_eq!(editor.content(), "foo  \n\nbar");
+
+        let _ = editor.update(&Message::DeleteWordLeft(WordUnit::Word));
+        assert_eq!(editor.content(), "foo  \nbar");
+        assert_eq!(editor.cursors.primary_position(), (1, 0));
+
+        editor.cursors.primary_mut().position = (0, 3);
+        let _ = editor.update(&Message::DeleteWordRight(WordUnit::Word));
+        assert_eq!(editor.content(), "foo\nbar");
+    }
 }
@@ -2,6 +2,9 @@
 
 use iced::Task;
 
+use crate::canvas_editor::features::color_picker;
+use crate::canvas_editor::features::command_palette::PaletteMode;
+use crate::canvas_editor::features::linked_editing::LinkedEdit;
 use crate::canvas_editor::{CodeEditor, Message};
 
 impl CodeEditor {
@@ -30,24 +33,57 @@ impl CodeEditor {
     /// assert_eq!(editor.content(), "hello");
     /// ```
     pub fn update(&mut self, message: &Message) -> Task<Message> {
+        // Typed Vim keys go through the key mappings first, and re-enter
+        // here once resolved.
+        if let Some(key) = self.vim_mapped_key(message) {
+            return self.handle_vim_typed_key(key);
+        }
         // Capture the topmost active line before any edit mutates the buffer,
         // so `finish_edit_operation` can truncate the highlight cache precisely.
         self.pre_edit_line = self.min_active_line();
         self.pre_edit_last_line = self.max_active_line();
         self.capture_lsp_edit_snapshot(message);
-        match message {
+        // Anything but the picker's own messages (and passive ones such as
+        // ticks) ends a color-picker session before it is handled.
+        if self.color_picker_state.is_open()
+            && !color_picker::keeps_color_picker_open(message)
+        {
+            self.commit_color_picker();
+        }
+        self.record_vim_insert_input(message);
+        let padded = self.prepare_column_selection(message);
+        let task = match message {
             // Text input operations
+            Message::CharacterInput(ch) if self.vim_replacing() => {
+                self.handle_vim_replace_input(*ch)
+            }
             Message::CharacterInput(ch) if self.vim_accepts_insert_input() => {
-                self.handle_character_input_msg(*ch)
+                self.with_linked_editing(LinkedEdit::Insert(*ch), |editor| {
+                    editor.handle_character_input_msg(*ch)
+                })
             }
             Message::CharacterInput(_) => Task::none(),
-            Message::VimKey(ch) => self.handle_vim_key_msg(*ch),
+            Message::VimKey(ch) => {
+                if *ch == '\u{1b}' {
+                    self.leave_snippet();
+                }
+                self.handle_vim_key_msg(*ch)
+            }
+            Message::VimClipboardPaste(text) => {
+                self.handle_vim_clipboard_paste_msg(text)
+            }
+            Message::VimCommandHistory(older) => {
+                self.handle_vim_command_history_msg(*older)
+            }
             Message::ToggleVimMode => {
                 self.set_vim_enabled(!self.vim_enabled);
                 Task::none()
             }
             Message::Tab if self.vim_accepts_insert_input() => {
-                self.handle_tab()
+                match self.expand_snippet_prefix() {
+                    Some(task) => task,
+                    None => self.handle_tab(),
+                }
             }
             Message::Enter if self.vim_accepts_insert_input() => {
                 self.handle_enter()
@@ -55,19 +91,50 @@ impl CodeEditor {
             Message::Tab | Message::Enter => Task::none(),
 
             // Deletion operations
-            Message::Backspace if self.vim_accepts_insert_input() => {
-                self.handle_backspace()
+            Message::Backspace | Message::Delete
+                if self.vim_accepts_insert_input()
+                    && let Some(selection) = self.column_selection =>
+            {
+                let forward = matches!(message, Message::Delete);
+                self.handle_column_delete(selection, forward)
+            }
+            Message::Backspace if self.vim_replacing() => {
+                self.handle_vim_replace_backspace()
+            }
+            Message::Backspace if self.vim_accepts_insert_input() => self
+                .with_linked_editing(LinkedEdit::Backspace, |editor| {
+                    editor.handle_backspace()
+                }),
+            Message::Delete if self.vim_accepts_insert_input() => self
+                .with_linked_editing(LinkedEdit::Delete, |editor| {
+                    editor.handle_delete()
+                }),
+            Message::DeleteWordLeft(unit)
+                if self.vim_accepts_insert_input() =>
+            {
+                self.handle_delete_word(false, *unit)
             }
-            Message::Delete if self.vim_accepts_insert_input() => {
-                self.handle_delete()
+            Message::DeleteWordRight(unit)
+                if self.vim_accepts_insert_input() =>
+            {
+                self.handle_delete_word(true, *unit)
             }
-            Message::Backspace | Message::Delete => Task::none(),
+            Message::Backspace
+            | Message::Delete
+            | Message::DeleteWordLeft(_)
+            | Message::DeleteWordRight(_) => Task::none(),
             Message::DeleteSelection => self.handle_delete_selection(),
 
             // Navigation operations
             Message::ArrowKey(direction, shift) => {
                 self.handle_arrow_key(*direction, *shift)
             }
+            Message::WordLeft(unit, shift) => {
+                self.handle_word_move(false, *unit, *shift)
+            }
+            Message::WordRight(unit, shift) => {
+                self.handle_word_move(true, *unit, *shift)
+            }
             Message::Home(shift) => self.handle_home(*shift),
             Message::End(shift) => self.handle_end(*shift),
             Message::CtrlHome => self.handle_ctrl_home(),
@@ -75,6 +142,8 @@ impl CodeEditor {
             Message::GotoPosition(line, col) => {
                 self.handle_goto_position(*line, *col)
             }
+            Message::NavigateBack => self.handle_navigate_msg(false),
+            Message::NavigateForward => self.handle_navigate_msg(true),
             Message::OpenGotoLine => self.handle_open_goto_line_msg(),
             Message::CloseGotoLine => self.handle_close_goto_line_msg(),
             Message::GotoLineChanged(query) => {
@@ -82,8 +151,10 @@ impl CodeEditor {
             }
             Message::SubmitGotoLine => self.handle_submit_goto_line_msg(),
             Message::OpenCommandPalette => {
-                self.handle_open_command_palette_msg()
+                self.handle_open_command_palette_msg(PaletteMode::Commands)
             }
+            Message::OpenClipboardHistory => self
+                .handle_open_command_palette_msg(PaletteMode::ClipboardHistory),
             Message::CloseCommandPalette => {
                 self.handle_close_command_palette_msg()
             }
@@ -178,9 +249,36 @@ impl CodeEditor {
             // Currently, this returns `Task::none()` as the actual navigation logic
             // is delegated to the `LspClient` implementation or handled elsewhere.
             Message::JumpClick(_point) => Task::none(),
+            Message::LinkActivated { .. } => Task::none(),
+
+            // Color picker
+            Message::OpenColorPicker(line, start_col) => {
+                self.handle_open_color_picker_msg(*line, *start_col)
+            }
+            Message::CloseColorPicker => self.handle_close_color_picker_msg(),
+            Message::ColorPickerSaturationValue(saturation, value) => self
+                .handle_color_picker_saturation_value_msg(*saturation, *value),
+            Message::ColorPickerHue(hue) => {
+                self.handle_color_picker_hue_msg(*hue)
+            }
+            Message::ColorPickerAlpha(alpha) => {
+                self.handle_color_picker_alpha_msg(*alpha)
+            }
+            Message::ColorPickerHexChanged(input) => {
+                self.handle_color_picker_hex_changed_msg(input)
+            }
+            Message::ColorPickerPresentation(index) => {
+                self.handle_color_picker_presentation_msg(*index)
+            }
 
             // Multi-cursor operations
             Message::AltClick(point) => self.handle_alt_click_msg(*point),
+            Message::ColumnSelectClick(point) => {
+                self.handle_column_select_click_msg(*point)
+            }
+            Message::ColumnSelect(direction) => {
+                self.handle_column_select_msg(*direction)
+            }
             Message::AddCursorAbove => self.handle_add_cursor_above_msg(),
             Message::AddCursorBelow => self.handle_add_cursor_below_msg(),
             Message::SelectNextOccurrence => {
@@ -202,6 +300,14 @@ impl CodeEditor {
                 self.unfold_all();
                 Task::none()
             }
+            Message::ToggleBreakpoint(line) => {
+                self.toggle_breakpoint(*line);
+                Task::none()
+            }
+            Message::ToggleBreakpointAtCursor => {
+                self.toggle_breakpoint_at_cursor();
+                Task::none()
+            }
 
             // Line manipulation operations
             Message::MoveLineUp => self.move_lines(false),
@@ -209,7 +315,65 @@ impl CodeEditor {
             Message::DuplicateLineUp => self.duplicate_lines(false),
             Message::DuplicateLineDown => self.duplicate_lines(true),
             Message::ToggleComment => self.toggle_comment(),
+            Message::ToggleBlockComment => self.toggle_block_comment(),
+            Message::ReindentLines => self.reindent_lines(),
+            Message::SortLines(order) => self.handle_sort_lines_msg(*order),
+            Message::ReverseLines => self.handle_reverse_lines_msg(),
+            Message::RemoveDuplicateLines => {
+                self.handle_remove_duplicate_lines_msg()
+            }
+            Message::JoinLines => self.handle_join_lines_msg(),
+            Message::TransposeCharacters => {
+                self.handle_transpose_characters_msg()
+            }
+            Message::ConvertCase(case) => self.handle_convert_case_msg(*case),
+            Message::TrimTrailingWhitespace => {
+                self.handle_trim_trailing_whitespace_msg()
+            }
+            Message::IncrementNumber(delta) => {
+                self.handle_increment_number_msg(*delta)
+            }
+            Message::IndentLines => self.handle_indent_lines_msg(),
+            Message::OutdentLines => self.handle_outdent_lines_msg(),
+            Message::ConvertIndentationToSpaces => {
+                self.handle_convert_indentation_to_spaces_msg()
+            }
+            Message::ConvertIndentationToTabs => {
+                self.handle_convert_indentation_to_tabs_msg()
+            }
+
+            // Document highlight navigation
+            Message::NextDocumentHighlight => {
+                self.handle_document_highlight_navigation(true)
+            }
+            Message::PreviousDocumentHighlight => {
+                self.handle_document_highlight_navigation(false)
+            }
+            Message::ExpandSelection => self.handle_expand_selection_msg(),
+            Message::ShrinkSelection => self.handle_shrink_selection_msg(),
+
+            // Snippets
+            Message::InsertSnippet(body) => self.insert_snippet(body),
+            Message::NextSnippetTabstop => {
+                self.handle_snippet_tabstop_msg(true)
+            }
+            Message::PreviousSnippetTabstop => {
+                self.handle_snippet_tabstop_msg(false)
+            }
+            Message::SelectSnippetChoice(index) => {
+                self.handle_select_snippet_choice_msg(*index)
+            }
+            Message::LeaveSnippet => {
+                self.leave_snippet();
+                Task::none()
+            }
+        };
+        // Close the group holding the column-selection padding, unless the
+        // handler keeps it open for a run of typing.
+        if padded && !self.is_grouping {
+            self.history.end_group();
         }
+        task
     }
 }
 
@@ -0,0 +1,259 @@
+//! Block indentation handlers: indenting and outdenting the selected lines,
+//! and converting the document's indentation between spaces and tabs.
+//!
+//! These reuse the line blocks of the transformations in [`super::transform`],
+//! so each command is one undo step across every cursor.
+
+use iced::Task;
+
+use super::transform::Scope;
+use crate::canvas_editor::editing::cursor_set::Cursor;
+use crate::canvas_editor::editing::indentation;
+use crate::canvas_editor::{CodeEditor, IndentStyle, Message, TAB_WIDTH};
+
+/// Moves the column of every cursor end inside `lines` with `map`, which
+/// receives the line, the column and whether the cursor selects.
+///
+/// Selection ends past the block (at column 0 of the next line) stay put.
+fn map_columns<F>(lines: &[String], cursors: &mut [Cursor], map: F)
+where
+    F: Fn(usize, usize, bool) -> usize,
+{
+    for cursor in cursors.iter_mut() {
+        let selecting = cursor.has_selection();
+        let ends =
+            std::iter::once(&mut cursor.position).chain(cursor.anchor.as_mut());
+        for (line, col) in ends {
+            if *line < lines.len() {
+                *col = map(*line, *col, selecting);
+            }
+        }
+    }
+}
+
+impl CodeEditor {
+    /// Returns whether any cursor's selection spans more than one line, the
+    /// case in which Tab and Shift+Tab indent and outdent instead of typing.
+    pub(crate) fn has_multi_line_selection(&self) -> bool {
+        self.cursors.iter().any(|cursor| {
+            cursor.selection_range().is_some_and(|(from, to)| from.0 != to.0)
+        })
+    }
+
+    /// Indents every selected line, or each cursor's line, by one level of
+    /// the editor's [`IndentStyle`].
+    ///
+    /// Empty lines stay empty. A selection starting at column 0 keeps
+    /// starting there, so it still covers the whole lines.
+    ///
+    /// # Returns
+    ///
+    /// A `Task<Message>` that scrolls to keep the cursor visible
+    pub(crate) fn handle_indent_lines_msg(&mut self) -> Task<Message> {
+        let style = self.indent_style;
+        let blocks = self.line_blocks(Scope::Lines);
+        self.rewrite_line_blocks(blocks, |lines, cursors| {
+            let (new_lines, added): (Vec<String>, Vec<usize>) = lines
+                .iter()
+                .map(|line| indentation::indent_line(line, style))
+                .unzip();
+            map_columns(lines, cursors, |line, col, selecting| {
+                if col == 0 && selecting { 0 } else { col + added[line] }
+            });
+            new_lines
+        })
+    }
+
+    /// Removes one level of indentation from every selected line, or each
+    /// cursor's line.
+    ///
+    /// # Returns
+    ///
+    /// A `Task<Message>` that scrolls to keep the cursor visible
+    pub(crate) fn handle_outdent_lines_msg(&mut self) -> Task<Message> {
+        let style = self.indent_style;
+        let blocks = self.line_blocks(Scope::Lines);
+        self.rewrite_line_blocks(blocks, |lines, cursors| {
+            let (new_lines, removed): (Vec<String>, Vec<usize>) = lines
+                .iter()
+                .map(|line| indentation::outdent_line(line, style))
+                .unzip();
+            map_columns(lines, cursors, |line, col, _| {
+                col.saturating_sub(removed[line])
+            });
+            new_lines
+        })
+    }
+
+    /// Rewrites the indentation of the whole document in `style`, then makes
+    /// `style` the editor's indentation style.
+    ///
+    /// # Returns
+    ///
+    /// A `Task<Message>` that scrolls to keep the cursor visible
+    fn convert_document_indentation(
+        &mut self,
+        style: IndentStyle,
+    ) -> Task<Message> {
+        self.indent_style = style;
+        let blocks = self.line_blocks(Scope::Document);
+        self.rewrite_line_blocks(blocks, |lines, cursors| {
+            let (new_lines, lengths): (Vec<String>, Vec<(usize, usize)>) =
+                lines
+                    .iter()
+                    .map(|line| {
+                        let (new_line, old_len, new_len) =
+                            indentation::convert_indentation(line, style);
+                        (new_line, (old_len, new_len))
+                    })
+                    .unzip();
+            map_columns(lines, cursors, |line, col, _| {
+                let (old_len, new_len) = lengths[line];
+                if col >= old_len {
+                    col - old_len + new_len
+                } else {
+                    col.min(new_len)
+                }
+            });
+            new_lines
+        })
+    }
+
+    /// Converts the document's indentation to spaces, keeping its width.
+    ///
+    /// The editor keeps its space width, or switches to spaces as wide as a
+    /// tab when it indented with tabs.
+    ///
+    /// # Returns
+    ///
+    /// A `Task<Message>` that scrolls to keep the cursor visible
+    pub(crate) fn handle_convert_indentation_to_spaces_msg(
+        &mut self,
+    ) -> Task<Message> {
+        let style = match self.indent_style {
+            IndentStyle::Spaces(n) => IndentStyle::Spaces(n),
+            IndentStyle::Tab => IndentStyle::Spaces(TAB_WIDTH as u8),
+        };
+        self.convert_document_indentation(style)
+    }
+
+    /// Converts the document's indentation to tabs, keeping any remainder
+    /// narrower than a tab as spaces, and makes the editor indent with tabs.
+    ///
+    /// # Returns
+    ///
+    /// A `Task<Message>` that scrolls to keep the cursor visible
+    pub(crate) fn handle_convert_indentation_to_tabs_msg(
+        &mut self,
+    ) -> Task<Message> {
+        self.convert_document_indentation(IndentStyle::Tab)
+    }
+
+    /// Switches to the indentation style detected in the buffer, when
+    /// detection is enabled and the buffer is indented at all.
+    pub(crate) fn apply_detected_indent_style(&mut self) {
+        if !self.detect_indentation {
+            return;
+        }
+        if let Some(style) =
+            indentation::detect_indent_style(&self.buffer.to_string())
+        {
+            self.indent_style = style;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn select(
+        editor: &mut CodeEditor,
+        anchor: (usize, usize),
+        to: (usize, usize),
+    ) {
+        editor.cursors.set_single(to);
+        editor.cursors.primary_mut().anchor = Some(anchor);
+    }
+
+    #[test]
+    fn test_indent_lines_keeps_the_selection_on_whole_lines() {
+        let mut editor = CodeEditor::new("a\n\nb\nc", "txt");
+        select(&mut editor, (0, 0), (3, 0));
+
+        let _ = editor.update(&Message::IndentLines);
+
+        assert_eq!(editor.content(), "    a\n\n    b\nc");
+        assert_eq!(editor.cursors.primary().anchor, Some((0, 0)));
+        assert_eq!(editor.cursors.primary_position(), (3, 0));
+
+        let _ = editor.update(&Message::Undo);
+        assert_eq!(editor.content(), "a\n\nb\nc");
+    }
+
+    #[test]
+    fn test_outdent_lines_removes_one_level_per_line() {
+        let mut editor = CodeEditor::new("      a\n\tb\n  c", "txt");
+        editor.set_indent_style(IndentStyle::Spaces(4));
+        select(&mut editor, (0, 7), (2, 3));
+
+        let _ = editor.update(&Message::OutdentLines);
+
+        assert_eq!(editor.content(), "  a\nb\nc");
+        assert_eq!(editor.cursors.primary().anchor, Some((0, 3)));
+        assert_eq!(editor.cursors.primary_position(), (2, 1));
+    }
+
+    #[test]
+    fn test_indent_lines_with_tabs_at_each_cursor() {
+        let mut editor = CodeEditor::new("a\nb\nc", "txt");
+        editor.set_indent_style(IndentStyle::Tab);
+        editor.cursors.set_single((0, 1));
+        editor.cursors.add_cursor((2, 0));
+
+        let _ = editor.update(&Message::IndentLines);
+
+        assert_eq!(editor.content(), "\ta\nb\n\tc");
+        let positions: Vec<_> =
+            editor.cursors.iter().map(|cursor| cursor.position).collect();
+        assert_eq!(positions, vec![(0, 2), (2, 1)]);
+    }
+
+    #[test]
+    fn test_convert_indentation_round_trips_and_sets_the_style() {
+        let text = "fn a() {\n    if b {\n      c();\n    }\n}";
+        let mut editor = CodeEditor::new(text, "rs");
+        editor.cursors.set_single((2, 7));
+
+        let _ = editor.update(&Message::ConvertIndentationToTabs);
+        assert_eq!(editor.content(), "fn a() {\n\tif b {\n\t  c();\n\t}\n}");
+        assert_eq!(editor.indent_style(), IndentStyle::Tab);
+        assert_eq!(editor.cursors.primary_position(), (2, 4));
+
+        let _ = editor.update(&Message::ConvertIndentationToSpaces);
+        assert_eq!(editor.content(), text);
+        assert_eq!(editor.indent_style(), IndentStyle::Spaces(4));
+        assert_eq!(editor.cursors.primary_position(), (2, 7));
+
+        let _ = editor.update(&Message::Undo);
+        assert_eq!(editor.content(), "fn a() {\n\tif b {\n\t  c();\n\t}\n}");
+    }
+
+    #[test]
+    fn test_new_and_reset_detect_the_indentation() {
+        let mut editor = CodeEditor::new("a:\n  b: 1\n", "yaml");
+        assert_eq!(editor.indent_style(), IndentStyle::Spaces(2));
+
+        let _ = editor.reset("fn a() {\n\tb();\n}");
+        assert_eq!(editor.indent_style(), IndentStyle::Tab);
+
+        // Content without indentation keeps the current style.
+        let _ = editor.reset("plain");
+        assert_eq!(editor.indent_style(), IndentStyle::Tab);
+
+        editor.set_detect_indentation(false);
+        editor.set_indent_style(IndentStyle::Spaces(8));
+        let _ = editor.reset("a:\n  b: 1\n");
+        assert_eq!(editor.indent_style(), IndentStyle::Spaces(8));
+    }
+}
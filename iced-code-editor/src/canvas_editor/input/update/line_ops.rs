@@ -3,8 +3,8 @@
 use iced::Task;
 
 use crate::canvas_editor::editing::command::{
-    Command, DuplicateLinesCommand, MoveLinesCommand, ToggleCommentCommand,
-    line_comment_token,
+    Command, DuplicateLinesCommand, MoveLinesCommand,
+    ToggleBlockCommentCommand, ToggleCommentCommand,
 };
 use crate::canvas_editor::{CodeEditor, Message};
 
@@ -15,7 +15,7 @@ impl CodeEditor {
     /// spans. A selection that ends at column 0 of a line does not include that
     /// trailing line (VS Code convention). Without a selection, the range is the
     /// single line the cursor sits on.
-    fn primary_line_range(&self) -> (usize, usize) {
+    pub(crate) fn primary_line_range(&self) -> (usize, usize) {
         let primary = self.cursors.primary();
         match primary.selection_range() {
             Some((sel_start, sel_end)) => {
@@ -132,14 +132,14 @@ impl CodeEditor {
         self.end_grouping_if_active();
         self.cursors.remove_all_but_primary();
 
-        let Some(token) = line_comment_token(&self.syntax) else {
+        let Some(token) = self.comment_tokens().line else {
             return Task::none();
         };
 
         let (start, end) = self.primary_line_range();
         let pos = self.cursors.primary_position();
         let mut cmd =
-            ToggleCommentCommand::new(&self.buffer, start, end, token, pos);
+            ToggleCommentCommand::new(&self.buffer, start, end, &token, pos);
         if cmd.is_noop() {
             return Task::none();
         }
@@ -158,11 +158,77 @@ impl CodeEditor {
         self.finish_edit_operation();
         self.scroll_to_cursor()
     }
+
+    /// Wraps the primary selection in block-comment tokens, or unwraps it
+    /// when it already is a block comment (Shift+Alt+A).
+    ///
+    /// Without a selection the current line is toggled, and a blank line gets
+    /// an empty comment with the cursor inside. Secondary cursors are
+    /// collapsed onto the primary one. After the toggle the selection covers
+    /// the toggled text, so a second toggle restores it. The operation is a
+    /// no-op when the active syntax has no block-comment tokens.
+    ///
+    /// # Returns
+    ///
+    /// A `Task<Message>` that scrolls to keep the cursor visible
+    pub(crate) fn toggle_block_comment(&mut self) -> Task<Message> {
+        self.end_grouping_if_active();
+        self.cursors.remove_all_but_primary();
+
+        let Some((open, close)) = self.comment_tokens().block else {
+            return Task::none();
+        };
+
+        let primary = self.cursors.primary();
+        let pos = primary.position;
+        let selection = primary
+            .selection_range()
+            .filter(|(start, end)| start != end)
+            .map(|range| (range, primary.anchor.is_some_and(|a| a > pos)));
+        // Without a selection the whole line is toggled; on a blank line
+        // the empty comment goes where the cursor is.
+        let line_range = if self.buffer.line(pos.0).trim().is_empty() {
+            (pos, pos)
+        } else {
+            ((pos.0, 0), (pos.0, self.buffer.line_len(pos.0)))
+        };
+        let (start, end) = selection.map_or(line_range, |(range, _)| range);
+        let mut cmd = ToggleBlockCommentCommand::new(
+            &self.buffer,
+            start,
+            end,
+            &open,
+            &close,
+            pos,
+        );
+
+        let mut cursor_pos = pos;
+        cmd.execute(&mut self.buffer, &mut cursor_pos);
+        let range = cmd.range_after();
+        let primary = self.cursors.primary_mut();
+        match selection {
+            Some((_, backward)) => {
+                let (anchor, position) =
+                    if backward { (range.1, range.0) } else { range };
+                primary.anchor = Some(anchor);
+                primary.position = position;
+            }
+            None => {
+                primary.anchor = None;
+                primary.position = cursor_pos;
+            }
+        }
+        self.history.push(Box::new(cmd));
+
+        self.finish_edit_operation();
+        self.scroll_to_cursor()
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::CommentTokens;
 
     #[test]
     fn test_toggle_comment_selection() {
@@ -201,6 +267,58 @@ mod tests {
         assert_eq!(editor.cursors.primary_position(), (0, 8));
     }
 
+    #[test]
+    fn test_toggle_block_comment_selection_round_trip() {
+        let mut editor = CodeEditor::new("let x = a + b;", "rs");
+        // Backward selection of `a + b`.
+        editor.cursors.primary_mut().anchor = Some((0, 13));
+        editor.cursors.primary_mut().position = (0, 8);
+
+        let _ = editor.update(&Message::ToggleBlockComment);
+        assert_eq!(editor.buffer.line(0), "let x = /* a + b */;");
+        // The selection now covers the comment, keeping its direction.
+        assert_eq!(editor.cursors.primary().anchor, Some((0, 19)));
+        assert_eq!(editor.cursors.primary_position(), (0, 8));
+
+        let _ = editor.update(&Message::ToggleBlockComment);
+        assert_eq!(editor.buffer.line(0), "let x = a + b;");
+
+        let _ = editor.update(&Message::Undo);
+        assert_eq!(editor.buffer.line(0), "let x = /* a + b */;");
+    }
+
+    #[test]
+    fn test_toggle_block_comment_current_line() {
+        let mut editor = CodeEditor::new("  <div>\n\n</p>", "html");
+        editor.cursors.primary_mut().position = (0, 4);
+
+        let _ = editor.update(&Message::ToggleBlockComment);
+        assert_eq!(editor.buffer.line(0), "  <!-- <div> -->");
+        assert_eq!(editor.cursors.primary_position(), (0, 9));
+        assert_eq!(editor.cursors.primary().anchor, None);
+
+        // A blank line gets an empty comment with the cursor inside.
+        editor.cursors.primary_mut().position = (1, 0);
+        let _ = editor.update(&Message::ToggleBlockComment);
+        assert_eq!(editor.buffer.line(1), "<!--  -->");
+        assert_eq!(editor.cursors.primary_position(), (1, 5));
+    }
+
+    #[test]
+    fn test_toggle_block_comment_noop_without_tokens() {
+        let mut editor = CodeEditor::new("x = 1", "py");
+        let _ = editor.update(&Message::ToggleBlockComment);
+        assert_eq!(editor.buffer.line(0), "x = 1");
+    }
+
+    #[test]
+    fn test_toggle_comment_uses_registered_tokens() {
+        let mut editor = CodeEditor::new("x", "ini");
+        editor.set_comment_tokens("ini", CommentTokens::new().with_line(";"));
+        let _ = editor.update(&Message::ToggleComment);
+        assert_eq!(editor.buffer.line(0), "; x");
+    }
+
     // =========================================================================
     // Line move / duplicate
     // =========================================================================
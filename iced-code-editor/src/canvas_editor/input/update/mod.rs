@@ -128,6 +128,18 @@ impl CodeEditor {
             .truncate_from(self.pre_edit_line.saturating_sub(1));
         self.content_cache.clear();
         self.overlay_cache.clear();
+        // Snippet tabstops follow the edit, or the session ends.
+        self.track_snippet_edit();
+        // Occurrence, selection-range, linked-range, link and color positions
+        // are stale once the text moves under them.
+        self.document_highlight_state.clear();
+        self.selection_range_state.clear();
+        self.linked_editing_state.ranges.clear();
+        self.document_link_state.mark_edited();
+        self.document_color_state.mark_edited();
+        // Breakpoints stay on their statements as lines come and go above.
+        self.shift_breakpoints_after_edit();
+        self.shift_marks_after_edit();
         self.enqueue_incremental_lsp_change();
     }
 
@@ -175,6 +187,8 @@ impl CodeEditor {
                 | Message::Backspace
                 | Message::Delete
                 | Message::DeleteSelection
+                | Message::DeleteWordLeft(_)
+                | Message::DeleteWordRight(_)
                 | Message::Paste(_)
                 | Message::ImeCommit(_)
                 | Message::MoveLineUp
@@ -182,10 +196,31 @@ impl CodeEditor {
                 | Message::DuplicateLineUp
                 | Message::DuplicateLineDown
                 | Message::ToggleComment
+                | Message::ToggleBlockComment
+                | Message::ReindentLines
+                | Message::InsertSnippet(_)
+                | Message::SelectSnippetChoice(_)
         );
+        // The line and text transformations and the indentation commands may
+        // rewrite the whole document (sorting without a selection, converting
+        // indentation), so they resend all of it.
         let is_global_edit = matches!(
             message,
-            Message::Undo | Message::Redo | Message::ReplaceAll
+            Message::Undo
+                | Message::Redo
+                | Message::ReplaceAll
+                | Message::SortLines(_)
+                | Message::ReverseLines
+                | Message::RemoveDuplicateLines
+                | Message::JoinLines
+                | Message::TransposeCharacters
+                | Message::ConvertCase(_)
+                | Message::TrimTrailingWhitespace
+                | Message::IncrementNumber(_)
+                | Message::IndentLines
+                | Message::OutdentLines
+                | Message::ConvertIndentationToSpaces
+                | Message::ConvertIndentationToTabs
         );
         let is_replace_next = matches!(message, Message::ReplaceNext);
         if !is_local_edit && !is_global_edit && !is_replace_next {
@@ -281,13 +316,15 @@ impl CodeEditor {
     /// or when starting a new type of operation that shouldn't be grouped
     /// with previous operations.
     pub(crate) fn end_grouping_if_active(&mut self) {
-        if self.is_grouping {
+        // A Vim `.` replay keeps its group open until it has finished, so
+        // the whole replayed change undoes as one step.
+        if self.is_grouping && !self.vim_state.replaying() {
             self.history.end_group();
             self.is_grouping = false;
         }
     }
 
-    fn keep_vim_insert_group(&self) -> bool {
+    pub(crate) fn keep_vim_insert_group(&self) -> bool {
         self.vim_enabled
             && self.vim_state.mode() == VimMode::Insert
             && self.is_grouping
@@ -331,12 +368,14 @@ mod deletion;
 mod dispatch;
 mod focus_ime;
 mod history_ops;
+mod indentation;
 mod line_ops;
 mod mouse;
 mod multi_cursor;
 mod navigation;
 mod scroll_timer;
 mod text_input;
+mod transform;
 
 #[cfg(test)]
 mod tests {
@@ -76,7 +76,9 @@ impl CodeEditor {
         &mut self,
         point: iced::Point,
     ) -> Task<Message> {
-        if self.is_dragging {
+        if self.is_dragging && self.column_selection.is_some() {
+            self.drag_column_selection(point);
+        } else if self.is_dragging {
             let before_pos = self.cursors.primary_position();
             self.handle_mouse_drag(point);
             if self.cursors.primary_position() != before_pos {
@@ -1,8 +1,10 @@
-//! Arrow-key, Home/End, Ctrl+Home/End, Page Up/Down, and goto-position message handlers.
+//! Arrow-key, word-wise, Home/End, Ctrl+Home/End, Page Up/Down, and goto-position message handlers.
 
 use iced::Task;
 
-use crate::canvas_editor::{ArrowDirection, CodeEditor, Message, VimMode};
+use crate::canvas_editor::{
+    ArrowDirection, CodeEditor, Message, VimMode, WordUnit,
+};
 
 impl CodeEditor {
     pub(crate) fn vim_accepts_insert_input(&self) -> bool {
@@ -44,6 +46,43 @@ impl CodeEditor {
         self.scroll_to_cursor()
     }
 
+    /// Handles Ctrl+Left/Right: moves every cursor to the previous word
+    /// start or past the next word end.
+    ///
+    /// # Arguments
+    ///
+    /// * `forward` - `true` for Ctrl+Right, `false` for Ctrl+Left
+    /// * `unit` - Whether to stop at sub-word boundaries
+    /// * `shift_pressed` - Whether Shift is held (for selection)
+    ///
+    /// # Returns
+    ///
+    /// A `Task<Message>` that scrolls to keep the cursor visible
+    pub(crate) fn handle_word_move(
+        &mut self,
+        forward: bool,
+        unit: WordUnit,
+        shift_pressed: bool,
+    ) -> Task<Message> {
+        self.end_grouping_if_active();
+
+        if !shift_pressed {
+            self.clear_selection();
+        }
+        let targets = self.word_targets(forward, unit);
+        for (cursor, target) in
+            self.cursors.as_mut_slice().iter_mut().zip(targets)
+        {
+            if shift_pressed && cursor.anchor.is_none() {
+                cursor.set_anchor();
+            }
+            cursor.position = target;
+        }
+        self.cursors.sort_and_merge();
+        self.finish_navigation_operation();
+        self.scroll_to_cursor()
+    }
+
     /// Handles Home key press.
     ///
     /// Moves the cursor to the start of the current line.
@@ -164,7 +203,8 @@ impl CodeEditor {
         self.scroll_to_cursor()
     }
 
-    /// Handles direct navigation to an explicit logical position.
+    /// Handles direct navigation to an explicit logical position, recording
+    /// the position it leaves in the jumplist.
     ///
     /// # Arguments
     ///
@@ -181,6 +221,7 @@ impl CodeEditor {
     ) -> Task<Message> {
         // End grouping on navigation command
         self.end_grouping_if_active();
+        self.push_jump();
         self.set_cursor(line, col)
     }
 }
@@ -324,4 +365,35 @@ mod tests {
         let _ = editor.update(&Message::Undo);
         assert_eq!(editor.buffer.line(0), "hello");
     }
+
+    #[test]
+    fn test_word_move_moves_every_cursor() {
+        let mut editor = CodeEditor::new("foo bar\nbaz qux", "txt");
+        editor.cursors.primary_mut().position = (0, 0);
+        editor.cursors.add_cursor((1, 0));
+
+        let _ = editor.update(&Message::WordRight(WordUnit::Word, false));
+        let positions: Vec<_> =
+            editor.cursors.iter().map(|cursor| cursor.position).collect();
+        assert_eq!(positions, vec![(0, 3), (1, 3)]);
+
+        let _ = editor.update(&Message::WordLeft(WordUnit::Word, false));
+        let positions: Vec<_> =
+            editor.cursors.iter().map(|cursor| cursor.position).collect();
+        assert_eq!(positions, vec![(0, 0), (1, 0)]);
+    }
+
+    #[test]
+    fn test_word_move_with_shift_extends_selection() {
+        let mut editor = CodeEditor::new("foo bar baz", "txt");
+        editor.cursors.primary_mut().position = (0, 0);
+
+        let _ = editor.update(&Message::WordRight(WordUnit::Word, true));
+        let _ = editor.update(&Message::WordRight(WordUnit::Word, true));
+        assert_eq!(editor.get_selected_text(), Some("foo bar".to_string()));
+
+        let _ = editor.update(&Message::WordRight(WordUnit::Word, false));
+        assert!(!editor.cursors.primary().has_selection());
+        assert_eq!(editor.cursors.primary_position(), (0, 11));
+    }
 }
@@ -11,11 +11,13 @@ use crate::canvas_editor::{CURSOR_BLINK_INTERVAL, CodeEditor, Message};
 impl CodeEditor {
     /// Handles cursor blink tick event.
     ///
-    /// Updates cursor visibility for blinking animation.
+    /// Updates cursor visibility for blinking animation, lets the
+    /// document-highlight debounce notice a cursor that has come to rest,
+    /// and runs Vim keys whose mapping timeout has passed.
     ///
     /// # Returns
     ///
-    /// A `Task<Message>` (currently Task::none())
+    /// The task of any Vim keys run by the mapping timeout
     pub(crate) fn handle_tick_msg(&mut self) -> Task<Message> {
         // Handle cursor blinking only if editor has focus
         if self.has_focus()
@@ -31,7 +33,11 @@ impl CodeEditor {
             self.show_cursor = false;
         }
 
-        Task::none()
+        self.refresh_document_highlights();
+        self.refresh_document_links();
+        self.refresh_document_colors();
+
+        self.flush_vim_typeahead()
     }
 
     /// Handles viewport scrolled event.
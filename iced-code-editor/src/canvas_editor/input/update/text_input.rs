@@ -137,6 +137,7 @@ impl CodeEditor {
             }
         }
 
+        self.outdent_typed_closers();
         self.finish_edit_operation();
 
         // Auto-trigger LSP completion for identifier characters and trigger characters
@@ -145,6 +146,13 @@ impl CodeEditor {
             self.lsp_request_completion();
         }
 
+        // Opening a call or moving to the next argument refreshes the
+        // parameter hints.
+        if ch == '(' || ch == ',' {
+            self.lsp_flush_pending_changes();
+            self.lsp_request_signature_help();
+        }
+
         self.scroll_to_cursor()
     }
 
@@ -280,7 +288,8 @@ impl CodeEditor {
         }
     }
 
-    /// Handles Tab key press (inserts 4 spaces).
+    /// Handles Tab key press (inserts one level of the editor's
+    /// [`IndentStyle`] at each cursor).
     ///
     /// # Returns
     ///
@@ -291,8 +300,8 @@ impl CodeEditor {
 
         // A plain click leaves a zero-length anchor in place (see
         // `handle_enter`); clear it so it isn't mistaken for a real selection
-        // by a later edit. Tab only reaches here when there is no real
-        // selection to indent (see `IndentLines`).
+        // by a later edit. Tab only reaches here when no selection spans
+        // several lines; those are indented instead (see `IndentLines`).
         self.clear_selection();
 
         // Multi-cursor: process in descending document order
@@ -393,46 +402,89 @@ impl CodeEditor {
             self.clear_selection();
         }
 
-        // Multi-cursor: process in descending document order
+        // Multi-cursor: process in descending document order. Edits only
+        // touch text at or after each cursor, so every indentation can be
+        // planned up front from the unmodified lines above it.
         let order = self.cursors.descending_order();
+        let plans: Vec<(usize, String, Option<String>)> = order
+            .iter()
+            .map(|&idx| {
+                let pos = self.cursors.as_slice()[idx].position;
+                let (indent, closing) = self.newline_indent(pos);
+                (idx, indent, closing)
+            })
+            .collect();
+
+        // Splitting `{|}` inserts two lines; keep them one undo step.
+        let splits = plans.iter().any(|(_, _, closing)| closing.is_some());
+        if splits {
+            self.ensure_grouping_started();
+        }
 
-        for &idx in &order {
+        for (idx, indent, closing) in plans {
             let pos = self.cursors.as_slice()[idx].position;
-
-            // Copy leading whitespace of the current line to the new line (if enabled)
-            let indent: String = if self.auto_indent_enabled {
-                self.buffer
-                    .line(pos.0)
-                    .chars()
-                    .take_while(|c| c.is_whitespace())
-                    .collect()
+            if let Some(closing) = closing {
+                self.insert_newline_at_cursor(idx, pos, indent.clone());
+                let inner = self.cursors.as_slice()[idx].position;
+                self.insert_newline_at_cursor(idx, inner, closing);
+                self.cursors.as_mut_slice()[idx].position = inner;
             } else {
-                String::new()
-            };
-            let indent_len = indent.chars().count();
-
-            let mut cmd =
-                InsertNewlineCommand::with_indent(pos.0, pos.1, pos, indent);
-            let mut cursor_pos = pos;
-            cmd.execute(&mut self.buffer, &mut cursor_pos);
-            self.cursors.as_mut_slice()[idx].position = cursor_pos;
-            adjust_other_cursors(
-                self.cursors.as_mut_slice(),
-                idx,
-                pos.0,
-                pos.1,
-                EditType::InsertNewline { indent_len },
-            );
-            self.history.push(Box::new(cmd));
+                self.insert_newline_at_cursor(idx, pos, indent);
+            }
         }
 
-        if replaces_selection && !keep_vim_group {
+        if (replaces_selection || splits) && !keep_vim_group {
             self.end_grouping_if_active();
         }
 
         self.finish_edit_operation();
         self.scroll_to_cursor()
     }
+
+    /// Returns the indentation of the line Enter creates at `pos` and, when
+    /// Enter splits a `{|}` pair, of the line the closing text moves to.
+    ///
+    /// Follows the syntax's indentation rules when it has some, and copies
+    /// the leading whitespace of the current line otherwise. Without
+    /// auto-indentation the new line starts at column 0.
+    fn newline_indent(&self, pos: (usize, usize)) -> (String, Option<String>) {
+        if !self.auto_indent_enabled {
+            return (String::new(), None);
+        }
+        self.rule_based_newline_indent(pos.0, pos.1).unwrap_or_else(|| {
+            let indent = self
+                .buffer
+                .line(pos.0)
+                .chars()
+                .take_while(|c| c.is_whitespace())
+                .collect();
+            (indent, None)
+        })
+    }
+
+    /// Splits the line at `pos` for cursor `idx`, starting the new line with
+    /// `indent`, and adjusts every other cursor.
+    fn insert_newline_at_cursor(
+        &mut self,
+        idx: usize,
+        pos: (usize, usize),
+        indent: String,
+    ) {
+        let indent_len = indent.chars().count();
+        let mut cmd =
+            InsertNewlineCommand::with_indent(pos.0, pos.1, pos, indent);
+        let mut cursor_pos = pos;
+        cmd.execute(&mut self.buffer, &mut cursor_pos);
+        self.cursors.as_mut_slice()[idx].position = cursor_pos;
+        adjust_other_cursors(
+            self.cursors.as_mut_slice(),
+            idx,
+            pos.0,
+            pos.1,
+            EditType::InsertNewline { indent_len },
+        );
+        self.history.push(Box::new(cmd));
+    }
 }
 
 #[cfg(test)]
@@ -0,0 +1,702 @@
+//! Line and text transformation handlers: sorting, reversing,
+//! de-duplicating, joining and trimming lines, transposing characters,
+//! converting case, and incrementing numbers.
+//!
+//! Every transformation works on blocks of whole lines. The cursors are
+//! grouped into blocks first — overlapping ones merge — then each block is
+//! rewritten by a pure function from [`transform`] and replaced with a
+//! [`ReplaceLinesCommand`]. All blocks go into one [`CompositeCommand`], so
+//! the whole transformation undoes as a single step, like
+//! [`MoveLinesCommand`](crate::canvas_editor::editing::command::MoveLinesCommand).
+
+use iced::Task;
+
+use crate::canvas_editor::editing::command::{
+    Command, CompositeCommand, ReplaceLinesCommand,
+};
+use crate::canvas_editor::editing::cursor_set::Cursor;
+use crate::canvas_editor::editing::transform;
+use crate::canvas_editor::{CodeEditor, Message, SortOrder, TextCase};
+
+/// Which lines a cursor hands to a transformation.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub(super) enum Scope {
+    /// The lines of the selection, leaving out a last line the selection
+    /// only reaches at column 0, or the cursor's line.
+    Lines,
+    /// The lines of the selection, leaving out a last line the selection
+    /// only reaches at column 0. A lone cursor without a selection covers the
+    /// whole document.
+    LinesOrDocument,
+    /// The lines of the selection, or the cursor's line and the next one.
+    JoinedLines,
+    /// Every line the selection touches, or the cursor's line.
+    Text,
+    /// The whole document, whatever the cursors cover.
+    Document,
+}
+
+/// A block of whole lines rewritten in one piece, with the cursors inside it.
+pub(super) struct LineBlock {
+    /// First line of the block.
+    start: usize,
+    /// Last line of the block (inclusive).
+    end: usize,
+    /// The cursors that asked for the block, with whether each is primary.
+    cursors: Vec<(Cursor, bool)>,
+}
+
+/// A replacement of the character range `[start, end)` of a block's text,
+/// where lines are joined with `\n`.
+struct RangeEdit {
+    start: usize,
+    end: usize,
+    text: String,
+}
+
+impl RangeEdit {
+    /// Returns how far the edit moves the text after it.
+    fn delta(&self) -> isize {
+        self.text.chars().count() as isize - (self.end - self.start) as isize
+    }
+}
+
+/// Returns the character offset of a block-local `(line, col)` position in
+/// the block's lines joined with `\n`.
+fn block_offset(lines: &[String], position: (usize, usize)) -> usize {
+    lines
+        .iter()
+        .take(position.0)
+        .map(|line| line.chars().count() + 1)
+        .sum::<usize>()
+        + position.1
+}
+
+/// Returns the block-local `(line, col)` position of a character offset in
+/// `text`.
+fn block_position(text: &str, offset: usize) -> (usize, usize) {
+    let mut position = (0, 0);
+    for ch in text.chars().take(offset) {
+        if ch == '\n' {
+            position = (position.0 + 1, 0);
+        } else {
+            position.1 += 1;
+        }
+    }
+    position
+}
+
+/// Applies `edits`, sorted and non-overlapping, to `text`.
+fn replace_ranges(text: &str, edits: &[RangeEdit]) -> String {
+    let chars: Vec<char> = text.chars().collect();
+    let mut result = String::with_capacity(text.len());
+    let mut next = 0;
+    for edit in edits {
+        result.extend(&chars[next..edit.start]);
+        result.push_str(&edit.text);
+        next = edit.end;
+    }
+    result.extend(&chars[next..]);
+    result
+}
+
+/// Carries an offset across `edits`: offsets before an edit stay, offsets
+/// after it move with the text, and offsets inside it keep their distance
+/// from its start as far as the new text reaches.
+fn map_offset(offset: usize, edits: &[RangeEdit]) -> usize {
+    let mut shift = 0isize;
+    for edit in edits {
+        if offset < edit.start
+            || (offset == edit.start && edit.start < edit.end)
+        {
+            break;
+        }
+        if offset >= edit.end {
+            shift += edit.delta();
+            continue;
+        }
+        let new_start = edit.start.saturating_add_signed(shift);
+        let new_len = edit.text.chars().count();
+        return new_start + (offset - edit.start).min(new_len);
+    }
+    offset.saturating_add_signed(shift)
+}
+
+/// Sorts `edits` and drops the ones overlapping an earlier edit, so two
+/// cursors in one word convert it once.
+fn disjoint(mut edits: Vec<RangeEdit>) -> Vec<RangeEdit> {
+    edits.sort_by_key(|edit| (edit.start, edit.end));
+    let mut kept: Vec<RangeEdit> = Vec::with_capacity(edits.len());
+    for edit in edits {
+        if kept.last().is_none_or(|last| last.end <= edit.start) {
+            kept.push(edit);
+        }
+    }
+    kept
+}
+
+/// Rewrites a block's text through `edits` and carries the block's cursors
+/// along.
+fn apply_range_edits(
+    lines: &[String],
+    cursors: &mut [Cursor],
+    edits: Vec<RangeEdit>,
+) -> Vec<String> {
+    let edits = disjoint(edits);
+    let text = lines.join("\n");
+    let new_text = replace_ranges(&text, &edits);
+    let carry = |position: (usize, usize)| {
+        block_position(
+            &new_text,
+            map_offset(block_offset(lines, position), &edits),
+        )
+    };
+    for cursor in cursors.iter_mut() {
+        cursor.position = carry(cursor.position);
+        cursor.anchor = cursor.anchor.map(carry);
+    }
+    new_text.split('\n').map(str::to_string).collect()
+}
+
+/// Puts each selecting cursor's selection around the rewritten lines, and
+/// clamps the others into them. Used by the transformations that reorder or
+/// drop lines, after which columns inside the block mean nothing.
+fn select_rewritten_lines(
+    old_len: usize,
+    new_lines: &[String],
+    cursors: &mut [Cursor],
+) {
+    let last = new_lines.len() - 1;
+    let block_end = (last, new_lines[last].chars().count());
+    for cursor in cursors.iter_mut() {
+        let Some((_, end)) = cursor.selection_range() else {
+            let line = cursor.position.0.min(last);
+            let col = cursor.position.1.min(new_lines[line].chars().count());
+            cursor.position = (line, col);
+            cursor.anchor = None;
+            continue;
+        };
+        // A selection ending at column 0 past the block keeps that end.
+        let end = if end.0 >= old_len {
+            (new_lines.len() + end.0 - old_len, 0)
+        } else {
+            block_end
+        };
+        let start = (0, 0);
+        let forward = cursor.anchor.is_some_and(|a| a <= cursor.position);
+        let (anchor, position) =
+            if forward { (start, end) } else { (end, start) };
+        cursor.anchor = Some(anchor);
+        cursor.position = position;
+    }
+}
+
+impl CodeEditor {
+    /// Groups the cursors into line blocks for a transformation of `scope`,
+    /// in document order.
+    pub(super) fn line_blocks(&self, scope: Scope) -> Vec<LineBlock> {
+        let last_line = self.buffer.line_count().saturating_sub(1);
+        let primary = self.cursors.primary_position();
+        let whole_document = scope == Scope::Document
+            || (scope == Scope::LinesOrDocument
+                && !self.cursors.is_multi()
+                && !self.cursors.primary().has_selection());
+        let mut blocks: Vec<LineBlock> = Vec::new();
+        for cursor in self.cursors.iter() {
+            let (start, mut end) = match cursor.selection_range() {
+                _ if whole_document => (0, last_line),
+                Some((from, to))
+                    if scope != Scope::Text && to.1 == 0 && to.0 > from.0 =>
+                {
+                    (from.0, to.0 - 1)
+                }
+                Some((from, to)) => (from.0, to.0),
+                None => (cursor.position.0, cursor.position.0),
+            };
+            if scope == Scope::JoinedLines && start == end {
+                end = (end + 1).min(last_line);
+            }
+            let entry = (cursor.clone(), cursor.position == primary);
+            if let Some(previous) = blocks.last_mut()
+                && start <= previous.end
+            {
+                previous.end = end.max(previous.end);
+                previous.cursors.push(entry);
+                continue;
+            }
+            blocks.push(LineBlock { start, end, cursors: vec![entry] });
+        }
+        blocks
+    }
+
+    /// Rewrites every block with `rewrite` as one undoable edit, then puts
+    /// the cursors where `rewrite` left them.
+    ///
+    /// `rewrite` receives a block's lines and its cursors in block-local
+    /// coordinates (line 0 is the block's first line), returns the new lines
+    /// and moves the cursors in place, to coordinates local to the new lines.
+    /// A selection may end just past the block, at column 0 of the line after
+    /// it; `rewrite` moves such an end along with the block's last line.
+    ///
+    /// # Returns
+    ///
+    /// A `Task<Message>` that scrolls to keep the cursor visible, or
+    /// `Task::none()` when no block changed
+    pub(super) fn rewrite_line_blocks<F>(
+        &mut self,
+        blocks: Vec<LineBlock>,
+        mut rewrite: F,
+    ) -> Task<Message>
+    where
+        F: FnMut(&[String], &mut [Cursor]) -> Vec<String>,
+    {
+        self.end_grouping_if_active();
+
+        let mut shift = 0isize;
+        let mut rewritten = Vec::with_capacity(blocks.len());
+        let mut cursors = Vec::new();
+        for block in blocks {
+            let old_len = block.end - block.start + 1;
+            let lines: Vec<String> = (block.start..=block.end)
+                .map(|line| self.buffer.line(line).to_string())
+                .collect();
+            let to_local =
+                |(line, col): (usize, usize)| (line - block.start, col);
+            let mut local: Vec<Cursor> = block
+                .cursors
+                .iter()
+                .map(|(cursor, _)| Cursor {
+                    position: to_local(cursor.position),
+                    anchor: cursor.anchor.map(to_local),
+                })
+                .collect();
+            let new_lines = rewrite(&lines, &mut local);
+            let new_start = block.start.saturating_add_signed(shift);
+            let to_global =
+                |(line, col): (usize, usize)| (new_start + line, col);
+            for (cursor, (_, primary)) in local.iter().zip(&block.cursors) {
+                let cursor = Cursor {
+                    position: to_global(cursor.position),
+                    anchor: cursor.anchor.map(to_global),
+                };
+                cursors.push((cursor, *primary));
+            }
+            shift += new_lines.len() as isize - old_len as isize;
+            rewritten.push((block.start, old_len, new_lines));
+        }
+
+        let primary_before = self.cursors.primary_position();
+        let primary_after = cursors
+            .iter()
+            .find(|(_, primary)| *primary)
+            .map_or(primary_before, |(cursor, _)| cursor.position);
+        let mut composite = CompositeCommand::new();
+        let mut first_line = usize::MAX;
+        // Bottom-up, so each replacement leaves the lines above it in place.
+        for (start, old_len, new_lines) in rewritten.into_iter().rev() {
+            let mut cmd = ReplaceLinesCommand::new(
+                &self.buffer,
+                start,
+                old_len,
+                new_lines,
+                primary_before,
+                primary_after,
+            );
+            if cmd.is_noop() {
+                continue;
+            }
+            let mut cursor_pos = primary_before;
+            cmd.execute(&mut self.buffer, &mut cursor_pos);
+            composite.add(Box::new(cmd));
+            first_line = start;
+        }
+        if composite.is_empty() {
+            return Task::none();
+        }
+        self.history.push(Box::new(composite));
+        self.place_cursors(cursors);
+
+        self.pre_edit_line = self.pre_edit_line.min(first_line);
+        self.finish_edit_operation();
+        self.scroll_to_cursor()
+    }
+
+    /// Replaces the cursor set with `cursors`, keeping the one flagged
+    /// primary as the primary cursor.
+    fn place_cursors(&mut self, mut cursors: Vec<(Cursor, bool)>) {
+        // The last cursor added becomes primary.
+        cursors.sort_by_key(|(_, primary)| *primary);
+        let mut cursors = cursors.into_iter().map(|(cursor, _)| cursor);
+        let Some(first) = cursors.next() else {
+            return;
+        };
+        self.cursors.set_single(first.position);
+        *self.cursors.primary_mut() = first;
+        for cursor in cursors {
+            self.cursors.add_cursor_with_selection(cursor);
+        }
+    }
+
+    /// Sorts the selected lines, or the whole document when a lone cursor has
+    /// no selection.
+    pub(crate) fn handle_sort_lines_msg(
+        &mut self,
+        order: SortOrder,
+    ) -> Task<Message> {
+        let blocks = self.line_blocks(Scope::LinesOrDocument);
+        self.rewrite_line_blocks(blocks, |lines, cursors| {
+            let sorted = transform::sort_lines(lines, order);
+            select_rewritten_lines(lines.len(), &sorted, cursors);
+            sorted
+        })
+    }
+
+    /// Reverses the selected lines, or the whole document when a lone cursor
+    /// has no selection.
+    pub(crate) fn handle_reverse_lines_msg(&mut self) -> Task<Message> {
+        let blocks = self.line_blocks(Scope::LinesOrDocument);
+        self.rewrite_line_blocks(blocks, |lines, cursors| {
+            let reversed: Vec<String> = lines.iter().rev().cloned().collect();
+            select_rewritten_lines(lines.len(), &reversed, cursors);
+            reversed
+        })
+    }
+
+    /// Removes repeated lines from the selection, or from the whole document
+    /// when a lone cursor has no selection.
+    pub(crate) fn handle_remove_duplicate_lines_msg(
+        &mut self,
+    ) -> Task<Message> {
+        let blocks = self.line_blocks(Scope::LinesOrDocument);
+        self.rewrite_line_blocks(blocks, |lines, cursors| {
+            let unique = transform::remove_duplicate_lines(lines);
+            select_rewritten_lines(lines.len(), &unique, cursors);
+            unique
+        })
+    }
+
+    /// Removes trailing whitespace from the selected lines, or from the whole
+    /// document when a lone cursor has no selection.
+    pub(crate) fn handle_trim_trailing_whitespace_msg(
+        &mut self,
+    ) -> Task<Message> {
+        let blocks = self.line_blocks(Scope::LinesOrDocument);
+        self.rewrite_line_blocks(blocks, |lines, cursors| {
+            let trimmed: Vec<String> =
+                lines.iter().map(|line| line.trim_end().to_string()).collect();
+            let clamp = |(line, col): (usize, usize)| {
+                let len = trimmed.get(line).map_or(0, |l| l.chars().count());
+                (line, col.min(len))
+            };
+            for cursor in cursors.iter_mut() {
+                cursor.position = clamp(cursor.position);
+                cursor.anchor = cursor.anchor.map(clamp);
+            }
+            trimmed
+        })
+    }
+
+    /// Joins the selected lines, or each cursor's line with the next one.
+    ///
+    /// A cursor without a selection lands where the lines were joined; a
+    /// selection keeps covering the same text.
+    pub(crate) fn handle_join_lines_msg(&mut self) -> Task<Message> {
+        let blocks = self.line_blocks(Scope::JoinedLines);
+        self.rewrite_line_blocks(blocks, |lines, cursors| {
+            if lines.len() < 2 {
+                return lines.to_vec();
+            }
+            let (joined, layout) = transform::join_lines(lines);
+            let joined_len = joined.chars().count();
+            let seam = lines[0].trim_end().chars().count();
+            let carry = |(line, col): (usize, usize)| match layout.get(line) {
+                Some(&(base, dropped)) => {
+                    (0, (base + col.saturating_sub(dropped)).min(joined_len))
+                }
+                None => (line + 1 - lines.len(), col),
+            };
+            for cursor in cursors.iter_mut() {
+                if cursor.has_selection() {
+                    cursor.position = carry(cursor.position);
+                    cursor.anchor = cursor.anchor.map(carry);
+                } else {
+                    cursor.position = (0, seam);
+                    cursor.anchor = None;
+                }
+            }
+            vec![joined]
+        })
+    }
+
+    /// Swaps the characters around every cursor without a selection.
+    pub(crate) fn handle_transpose_characters_msg(&mut self) -> Task<Message> {
+        let blocks = self.line_blocks(Scope::Text);
+        self.rewrite_line_blocks(blocks, |lines, cursors| {
+            let mut lines = lines.to_vec();
+            for cursor in cursors.iter_mut() {
+                if cursor.has_selection() {
+                    continue;
+                }
+                let (line, col) = cursor.position;
+                if let Some((text, col)) =
+                    transform::transpose(&lines[line], col)
+                {
+                    lines[line] = text;
+                    cursor.position = (line, col);
+                    cursor.anchor = None;
+                }
+            }
+            lines
+        })
+    }
+
+    /// Converts every selection, or the word under every cursor, to `case`.
+    ///
+    /// Selections keep covering the converted text.
+    pub(crate) fn handle_convert_case_msg(
+        &mut self,
+        case: TextCase,
+    ) -> Task<Message> {
+        let blocks = self.line_blocks(Scope::Text);
+        self.rewrite_line_blocks(blocks, |lines, cursors| {
+            let text = lines.join("\n");
+            let chars: Vec<char> = text.chars().collect();
+            let edits = cursors
+                .iter()
+                .filter_map(|cursor| {
+                    let (start, end) = match cursor.selection_range() {
+                        Some((from, to)) => {
+                            (block_offset(lines, from), block_offset(lines, to))
+                        }
+                        None => {
+                            let (line, col) = cursor.position;
+                            let content = &lines[line];
+                            (
+                                block_offset(
+                                    lines,
+                                    (
+                                        line,
+                                        Self::word_start_in_line(content, col),
+                                    ),
+                                ),
+                                block_offset(
+                                    lines,
+                                    (
+                                        line,
+                                        Self::word_end_in_line(content, col),
+                                    ),
+                                ),
+                            )
+                        }
+                    };
+                    let old: String = chars[start..end].iter().collect();
+                    let text = transform::convert_case(&old, case);
+                    (start < end).then_some(RangeEdit { start, end, text })
+                })
+                .collect();
+            apply_range_edits(lines, cursors, edits)
+        })
+    }
+
+    /// Adds `delta` to the number under or after every cursor, leaving each
+    /// cursor at the end of its new number.
+    pub(crate) fn handle_increment_number_msg(
+        &mut self,
+        delta: i64,
+    ) -> Task<Message> {
+        let blocks = self.line_blocks(Scope::Text);
+        self.rewrite_line_blocks(blocks, |lines, cursors| {
+            let mut edits = Vec::new();
+            let mut ends = Vec::new();
+            for (index, cursor) in cursors.iter().enumerate() {
+                let (line, col) = cursor
+                    .selection_range()
+                    .map_or(cursor.position, |(start, _)| start);
+                if let Some((start, end, text)) =
+                    transform::increment_number(&lines[line], col, delta)
+                {
+                    let start = block_offset(lines, (line, start));
+                    let end = block_offset(lines, (line, end));
+                    ends.push((index, end));
+                    edits.push(RangeEdit { start, end, text });
+                }
+            }
+            let edits = disjoint(edits);
+            let ends: Vec<(usize, usize)> = ends
+                .into_iter()
+                .map(|(index, end)| (index, map_offset(end, &edits)))
+                .collect();
+            let new_lines = apply_range_edits(lines, cursors, edits);
+            let new_text = new_lines.join("\n");
+            for (index, end) in ends {
+                cursors[index].position = block_position(&new_text, end);
+                cursors[index].anchor = None;
+            }
+            new_lines
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn select(
+        editor: &mut CodeEditor,
+        anchor: (usize, usize),
+        to: (usize, usize),
+    ) {
+        editor.cursors.set_single(to);
+        editor.cursors.primary_mut().anchor = Some(anchor);
+    }
+
+    #[test]
+    fn test_sort_without_selection_sorts_the_document_and_undoes_once() {
+        let mut editor = CodeEditor::new("c\na\nb", "txt");
+        editor.cursors.set_single((2, 1));
+
+        let _ = editor.update(&Message::SortLines(SortOrder::Ascending));
+        assert_eq!(editor.content(), "a\nb\nc");
+        assert_eq!(editor.cursors.primary_position(), (2, 1));
+
+        let _ = editor.update(&Message::Undo);
+        assert_eq!(editor.content(), "c\na\nb");
+    }
+
+    #[test]
+    fn test_sort_selection_keeps_the_lines_selected() {
+        let mut editor = CodeEditor::new("z\nc\nb\na\nz", "txt");
+        // Lines 1..=3; the selection ends at column 0 of line 4.
+        select(&mut editor, (1, 1), (4, 0));
+
+        let _ = editor.update(&Message::SortLines(SortOrder::Descending));
+
+        assert_eq!(editor.content(), "z\nc\nb\na\nz");
+        let _ = editor.update(&Message::ReverseLines);
+        assert_eq!(editor.content(), "z\na\nb\nc\nz");
+        assert_eq!(editor.cursors.primary().anchor, Some((1, 0)));
+        assert_eq!(editor.cursors.primary_position(), (4, 0));
+    }
+
+    #[test]
+    fn test_each_cursor_transforms_its_own_block() {
+        let mut editor = CodeEditor::new("b\na\n--\nd\nc", "txt");
+        select(&mut editor, (0, 0), (1, 1));
+        editor.cursors.add_cursor_with_selection(Cursor {
+            position: (4, 1),
+            anchor: Some((3, 0)),
+        });
+
+        let _ = editor.update(&Message::SortLines(SortOrder::Ascending));
+        assert_eq!(editor.content(), "a\nb\n--\nc\nd");
+        assert_eq!(editor.cursors.len(), 2);
+
+        let _ = editor.update(&Message::Undo);
+        assert_eq!(editor.content(), "b\na\n--\nd\nc");
+    }
+
+    #[test]
+    fn test_remove_duplicate_lines_shrinks_the_block() {
+        let mut editor = CodeEditor::new("a\nb\na\nb\ntail", "txt");
+        select(&mut editor, (0, 0), (3, 1));
+
+        let _ = editor.update(&Message::RemoveDuplicateLines);
+
+        assert_eq!(editor.content(), "a\nb\ntail");
+        assert_eq!(editor.cursors.primary().anchor, Some((0, 0)));
+        assert_eq!(editor.cursors.primary_position(), (1, 1));
+    }
+
+    #[test]
+    fn test_join_lines_per_cursor() {
+        let mut editor = CodeEditor::new("a {\n    b\n}\nc\n  d", "txt");
+        editor.cursors.set_single((0, 1));
+        editor.cursors.add_cursor((3, 0));
+
+        let _ = editor.update(&Message::JoinLines);
+
+        assert_eq!(editor.content(), "a { b\n}\nc d");
+        let positions: Vec<_> =
+            editor.cursors.iter().map(|cursor| cursor.position).collect();
+        assert_eq!(positions, vec![(0, 3), (2, 1)]);
+    }
+
+    #[test]
+    fn test_join_lines_selection_keeps_covering_the_text() {
+        let mut editor = CodeEditor::new("one\n  two\n  three", "txt");
+        select(&mut editor, (0, 1), (2, 4));
+
+        let _ = editor.update(&Message::JoinLines);
+
+        assert_eq!(editor.content(), "one two three");
+        assert_eq!(editor.cursors.primary().anchor, Some((0, 1)));
+        assert_eq!(editor.cursors.primary_position(), (0, 10));
+    }
+
+    #[test]
+    fn test_transpose_characters_at_every_cursor() {
+        let mut editor = CodeEditor::new("ab cd\nxy", "txt");
+        editor.cursors.set_single((0, 1));
+        editor.cursors.add_cursor((0, 4));
+        editor.cursors.add_cursor((1, 2));
+
+        let _ = editor.update(&Message::TransposeCharacters);
+
+        assert_eq!(editor.content(), "ba dc\nyx");
+    }
+
+    #[test]
+    fn test_convert_case_selection_and_word_under_cursor() {
+        let mut editor = CodeEditor::new("let maxWidth = min_height;", "rs");
+        select(&mut editor, (0, 4), (0, 12));
+        editor.cursors.add_cursor((0, 17));
+
+        let _ = editor.update(&Message::ConvertCase(TextCase::Kebab));
+
+        assert_eq!(editor.content(), "let max-width = min-height;");
+        let cursors: Vec<_> = editor.cursors.iter().cloned().collect();
+        assert_eq!(cursors[0].anchor, Some((0, 4)));
+        assert_eq!(cursors[0].position, (0, 13));
+        assert_eq!(cursors[1].position, (0, 18));
+
+        let _ = editor.update(&Message::Undo);
+        assert_eq!(editor.content(), "let maxWidth = min_height;");
+    }
+
+    #[test]
+    fn test_trim_trailing_whitespace_clamps_cursors() {
+        let mut editor = CodeEditor::new("a  \nb\t\nc", "txt");
+        editor.cursors.set_single((0, 3));
+
+        let _ = editor.update(&Message::TrimTrailingWhitespace);
+
+        assert_eq!(editor.content(), "a\nb\nc");
+        assert_eq!(editor.cursors.primary_position(), (0, 1));
+    }
+
+    #[test]
+    fn test_increment_number_under_each_cursor() {
+        let mut editor = CodeEditor::new("x = 9; y = 10;\nz = -1", "txt");
+        editor.cursors.set_single((0, 0));
+        editor.cursors.add_cursor((0, 7));
+        editor.cursors.add_cursor((1, 0));
+
+        let _ = editor.update(&Message::IncrementNumber(1));
+        assert_eq!(editor.content(), "x = 10; y = 11;\nz = 0");
+        let positions: Vec<_> =
+            editor.cursors.iter().map(|cursor| cursor.position).collect();
+        assert_eq!(positions, vec![(0, 6), (0, 14), (1, 5)]);
+
+        let _ = editor.update(&Message::IncrementNumber(-2));
+        assert_eq!(editor.content(), "x = 8; y = 9;\nz = -2");
+    }
+
+    #[test]
+    fn test_no_change_records_no_history() {
+        let mut editor = CodeEditor::new("a\nb", "txt");
+
+        let _ = editor.update(&Message::SortLines(SortOrder::Ascending));
+
+        assert!(!editor.can_undo());
+    }
+}
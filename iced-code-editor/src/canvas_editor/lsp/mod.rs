@@ -1,5 +1,12 @@
 //! Minimal LSP types and helpers used by the editor.
 
+use std::future::Future;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+use iced::Task;
+use iced::futures::channel::oneshot;
+
 pub(crate) mod sync;
 
 #[cfg(all(feature = "lsp-process", not(target_arch = "wasm32")))]
@@ -103,6 +110,237 @@ pub struct LspRange {
     pub end: LspPosition,
 }
 
+/// A completion item received from the LSP server.
+///
+/// `detail` and `documentation` are optional on the wire; when present, the
+/// `lsp-process` overlay shows them in a panel next to the completion menu
+/// while the item is selected. `documentation` is already normalized to
+/// Markdown, whatever `MarkupContent` kind the server sent.
+///
+/// An item may insert something other than its label. When `is_snippet` is
+/// set (`insertTextFormat = 2` on the wire), the text is in snippet syntax:
+/// insert it with [`Message::InsertSnippet`](crate::Message::InsertSnippet)
+/// rather than pasting it.
+///
+/// # Examples
+///
+/// ```
+/// use iced_code_editor::LspCompletionItem;
+///
+/// // A bare label converts directly, for hosts with no extra metadata.
+/// let item = LspCompletionItem::from("println!");
+/// assert_eq!(item.label, "println!");
+/// assert!(item.documentation.is_none());
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LspCompletionItem {
+    /// Text shown in the menu and inserted on confirm.
+    pub label: String,
+    /// Short detail, typically the item's signature or type.
+    pub detail: Option<String>,
+    /// Markdown documentation for the item.
+    pub documentation: Option<String>,
+    /// Text to insert instead of the label, from `insertText` or the
+    /// item's text edit.
+    pub insert_text: Option<String>,
+    /// Whether `insert_text` is a snippet.
+    pub is_snippet: bool,
+}
+
+impl LspCompletionItem {
+    /// Returns the text confirming the item inserts: `insert_text` when
+    /// there is one, the label otherwise.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use iced_code_editor::LspCompletionItem;
+    ///
+    /// let item = LspCompletionItem {
+    ///     insert_text: Some("push(${1:value})".to_string()),
+    ///     is_snippet: true,
+    ///     ..LspCompletionItem::from("push")
+    /// };
+    /// assert_eq!(item.text_to_insert(), "push(${1:value})");
+    /// ```
+    pub fn text_to_insert(&self) -> &str {
+        self.insert_text.as_deref().unwrap_or(&self.label)
+    }
+}
+
+impl From<String> for LspCompletionItem {
+    fn from(label: String) -> Self {
+        Self {
+            label,
+            detail: None,
+            documentation: None,
+            insert_text: None,
+            is_snippet: false,
+        }
+    }
+}
+
+impl From<&str> for LspCompletionItem {
+    fn from(label: &str) -> Self {
+        Self::from(label.to_string())
+    }
+}
+
+/// How a document highlight uses the symbol it marks.
+///
+/// Mirrors LSP's `DocumentHighlightKind`. The editor paints
+/// [`Write`](Self::Write) occurrences with
+/// [`Style::document_highlight_write_color`] and every other kind with
+/// [`Style::document_highlight_read_color`].
+///
+/// [`Style::document_highlight_write_color`]: crate::theme::Style::document_highlight_write_color
+/// [`Style::document_highlight_read_color`]: crate::theme::Style::document_highlight_read_color
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum LspDocumentHighlightKind {
+    /// A textual occurrence, neither a read nor a write.
+    #[default]
+    Text,
+    /// Read access to the symbol, such as using a variable's value.
+    Read,
+    /// Write access to the symbol, such as assigning to a variable.
+    Write,
+}
+
+/// One occurrence of the symbol under the cursor.
+///
+/// This is the payload of a `textDocument/documentHighlight` response; pass a
+/// batch of them to [`CodeEditor::set_lsp_document_highlights`]. As on the
+/// wire, `range` counts characters in UTF-16 code units.
+///
+/// # Example
+///
+/// ```
+/// use iced_code_editor::{
+///     LspDocumentHighlight, LspDocumentHighlightKind, LspPosition, LspRange,
+/// };
+///
+/// // `x` being assigned in `let x = 1;`.
+/// let highlight = LspDocumentHighlight {
+///     range: LspRange {
+///         start: LspPosition { line: 0, character: 4 },
+///         end: LspPosition { line: 0, character: 5 },
+///     },
+///     kind: LspDocumentHighlightKind::Write,
+/// };
+/// assert_eq!(highlight.kind, LspDocumentHighlightKind::Write);
+/// ```
+///
+/// [`CodeEditor::set_lsp_document_highlights`]: crate::CodeEditor::set_lsp_document_highlights
+#[derive(Debug, Clone, Copy)]
+pub struct LspDocumentHighlight {
+    /// Range of the occurrence.
+    pub range: LspRange,
+    /// How the occurrence uses the symbol.
+    pub kind: LspDocumentHighlightKind,
+}
+
+/// A link found in the document by a language server.
+///
+/// This is the payload of a `textDocument/documentLink` response; pass a
+/// batch of them to [`CodeEditor::set_lsp_document_links`]. As on the wire,
+/// `range` counts characters in UTF-16 code units. Links the server would
+/// only resolve later (those sent without a target) are not represented.
+///
+/// # Example
+///
+/// ```
+/// use iced_code_editor::{LspDocumentLink, LspPosition, LspRange};
+///
+/// // `"serde"` in `use serde;`, linking to the crate's documentation.
+/// let link = LspDocumentLink {
+///     range: LspRange {
+///         start: LspPosition { line: 0, character: 4 },
+///         end: LspPosition { line: 0, character: 9 },
+///     },
+///     target: "https://docs.rs/serde".to_string(),
+/// };
+/// assert!(link.target.starts_with("https://"));
+/// ```
+///
+/// [`CodeEditor::set_lsp_document_links`]: crate::CodeEditor::set_lsp_document_links
+#[derive(Debug, Clone)]
+pub struct LspDocumentLink {
+    /// Range of the linked text.
+    pub range: LspRange,
+    /// URI the link points to.
+    pub target: String,
+}
+
+/// A color found in the document by a language server.
+///
+/// This is the payload of a `textDocument/documentColor` response; pass a
+/// batch of them to [`CodeEditor::set_lsp_document_colors`]. As on the wire,
+/// `range` counts characters in UTF-16 code units.
+///
+/// # Example
+///
+/// ```
+/// use iced::Color;
+/// use iced_code_editor::{LspDocumentColor, LspPosition, LspRange};
+///
+/// // `Color::RED` in `let c = Color::RED;`.
+/// let color = LspDocumentColor {
+///     range: LspRange {
+///         start: LspPosition { line: 0, character: 8 },
+///         end: LspPosition { line: 0, character: 18 },
+///     },
+///     color: Color::from_rgb(1.0, 0.0, 0.0),
+/// };
+/// assert_eq!(color.range.start.character, 8);
+/// ```
+///
+/// [`CodeEditor::set_lsp_document_colors`]: crate::CodeEditor::set_lsp_document_colors
+#[derive(Debug, Clone, Copy)]
+pub struct LspDocumentColor {
+    /// Range of the color's source text.
+    pub range: LspRange,
+    /// The color the text denotes.
+    pub color: iced::Color,
+}
+
+/// One way of writing a color, offered by a language server.
+///
+/// This is an entry of a `textDocument/colorPresentation` response; pass a
+/// batch of them to [`CodeEditor::set_lsp_color_presentations`]. The text to
+/// insert is `text_edit`'s when the server sends one, and `label` otherwise.
+///
+/// # Example
+///
+/// ```
+/// use iced_code_editor::LspColorPresentation;
+///
+/// let presentation = LspColorPresentation {
+///     label: "#ff0000".to_string(),
+///     text_edit: None,
+/// };
+/// assert_eq!(presentation.text(), "#ff0000");
+/// ```
+///
+/// [`CodeEditor::set_lsp_color_presentations`]: crate::CodeEditor::set_lsp_color_presentations
+#[derive(Debug, Clone)]
+pub struct LspColorPresentation {
+    /// Label shown for this presentation.
+    pub label: String,
+    /// Edit that writes the color this way, if the server provides one.
+    pub text_edit: Option<LspTextChange>,
+}
+
+impl LspColorPresentation {
+    /// Returns the text this presentation writes.
+    ///
+    /// # Returns
+    ///
+    /// The text of `text_edit` when present, `label` otherwise
+    pub fn text(&self) -> &str {
+        self.text_edit.as_ref().map_or(&self.label, |edit| &edit.text)
+    }
+}
+
 /// A text change described by a range replacement.
 ///
 /// An insertion is an empty `range` with non-empty `text`; a deletion is a
@@ -131,13 +369,126 @@ pub struct LspTextChange {
     pub text: String,
 }
 
+/// A location in a document, such as the target of a definition request.
+///
+/// # Example
+///
+/// ```
+/// use iced_code_editor::{LspLocation, LspPosition, LspRange};
+///
+/// let location = LspLocation {
+///     uri: "file:///tmp/lib.rs".to_string(),
+///     range: LspRange {
+///         start: LspPosition { line: 4, character: 3 },
+///         end: LspPosition { line: 4, character: 8 },
+///     },
+/// };
+/// assert_eq!(location.range.start.line, 4);
+/// ```
+#[derive(Debug, Clone)]
+pub struct LspLocation {
+    /// Document URI.
+    pub uri: String,
+    /// Range within that document, in UTF-16 code units as sent by the
+    /// server.
+    pub range: LspRange,
+}
+
+/// A handle to the reply of one LSP request.
+///
+/// Returned by [`LspClient::hover`], [`LspClient::completion`] and
+/// [`LspClient::definition`] (and the matching `CodeEditor::lsp_*` methods).
+/// It is a future: await it, or turn it into a [`Task`] tagged with the
+/// editor that sent the request with [`Self::into_task`]. It resolves to
+/// `None` when the server sent nothing usable, when the request was
+/// abandoned, or when the client delivers its replies some other way.
+///
+/// # Example
+///
+/// ```
+/// use iced::futures::FutureExt;
+/// use iced_code_editor::LspRequest;
+///
+/// let (reply, request) = LspRequest::channel();
+/// let _ = reply.send("fn main()".to_string());
+/// assert_eq!(request.now_or_never(), Some(Some("fn main()".to_string())));
+///
+/// let unanswered = LspRequest::<String>::none();
+/// assert_eq!(unanswered.now_or_never(), Some(None));
+/// ```
+#[derive(Debug)]
+#[must_use = "an LSP request handle does nothing unless awaited or run as a task"]
+pub struct LspRequest<T> {
+    /// Receiving half of the reply, or `None` when no reply will come.
+    receiver: Option<oneshot::Receiver<T>>,
+}
+
+impl<T> LspRequest<T> {
+    /// Creates a handle together with the sender its reply goes through.
+    ///
+    /// Dropping the sender without sending resolves the handle to `None`.
+    pub fn channel() -> (oneshot::Sender<T>, Self) {
+        let (sender, receiver) = oneshot::channel();
+        (sender, Self { receiver: Some(receiver) })
+    }
+
+    /// Creates a handle that resolves to `None` straight away.
+    pub fn none() -> Self {
+        Self { receiver: None }
+    }
+}
+
+impl<T: Send + 'static> LspRequest<T> {
+    /// Turns the handle into a [`Task`] that yields the reply tagged with
+    /// `editor`.
+    ///
+    /// # Arguments
+    ///
+    /// * `editor` - Id of the editor that sent the request, as the host
+    ///   tells its editors apart
+    pub fn into_task<Id: Send + 'static>(
+        self,
+        editor: Id,
+    ) -> Task<LspEditorReply<Id, T>> {
+        Task::future(
+            async move { LspEditorReply { editor, result: self.await } },
+        )
+    }
+}
+
+impl<T> Future for LspRequest<T> {
+    type Output = Option<T>;
+
+    fn poll(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Self::Output> {
+        match self.receiver.as_mut() {
+            Some(receiver) => Pin::new(receiver).poll(cx).map(Result::ok),
+            None => Poll::Ready(None),
+        }
+    }
+}
+
+/// The reply to an [`LspRequest`], together with the editor that sent it.
+#[derive(Debug, Clone)]
+pub struct LspEditorReply<Id, T> {
+    /// Id passed to [`LspRequest::into_task`].
+    pub editor: Id,
+    /// The reply, or `None` when there is none.
+    pub result: Option<T>,
+}
+
 /// LSP client hooks invoked by the editor.
 ///
 /// Every method has a no-op default, so an implementation only overrides the
 /// notifications and requests it actually cares about. Attach one with
 /// [`CodeEditor::attach_lsp`]; the editor never blocks on a response, so
 /// request results come back through whatever channel the implementation
-/// chooses.
+/// chooses. Hover, completion and definition can also answer through an
+/// [`LspRequest`] handle: [`Self::hover`], [`Self::completion`] and
+/// [`Self::definition`] return one, and by default send the fire-and-forget
+/// request and return a handle that resolves to `None`.
 ///
 /// # Example
 ///
@@ -196,6 +547,75 @@ pub trait LspClient {
         _position: LspPosition,
     ) {
     }
+    /// Requests signature help for the call surrounding the given position.
+    ///
+    /// The editor calls this after the user types `(` or `,`, so the client
+    /// can show which parameter of the enclosing call is being filled in.
+    fn request_signature_help(
+        &mut self,
+        _document: &LspDocument,
+        _position: LspPosition,
+    ) {
+    }
+    /// Requests every occurrence of the symbol at the given position.
+    ///
+    /// The editor calls this once the cursor has rested on an identifier;
+    /// hand the server's answer back with
+    /// [`CodeEditor::set_lsp_document_highlights`](crate::CodeEditor::set_lsp_document_highlights).
+    fn request_document_highlight(
+        &mut self,
+        _document: &LspDocument,
+        _position: LspPosition,
+    ) {
+    }
+    /// Requests the chain of enclosing ranges around the given position.
+    ///
+    /// The editor calls this when Expand Selection is used with a server
+    /// attached; hand the server's answer back with
+    /// [`CodeEditor::set_lsp_selection_ranges`](crate::CodeEditor::set_lsp_selection_ranges).
+    fn request_selection_range(
+        &mut self,
+        _document: &LspDocument,
+        _position: LspPosition,
+    ) {
+    }
+    /// Requests the ranges that must be edited together with the one at the
+    /// given position, such as an HTML tag name and its closing tag.
+    ///
+    /// The editor calls this once the cursor has rested; hand the server's
+    /// answer back with
+    /// [`CodeEditor::set_lsp_linked_editing_ranges`](crate::CodeEditor::set_lsp_linked_editing_ranges).
+    fn request_linked_editing_range(
+        &mut self,
+        _document: &LspDocument,
+        _position: LspPosition,
+    ) {
+    }
+    /// Requests the links found in the whole document.
+    ///
+    /// The editor calls this after the buffer has stopped changing; hand the
+    /// server's answer back with
+    /// [`CodeEditor::set_lsp_document_links`](crate::CodeEditor::set_lsp_document_links).
+    fn request_document_links(&mut self, _document: &LspDocument) {}
+    /// Requests the colors found in the whole document.
+    ///
+    /// The editor calls this after the buffer has stopped changing, while
+    /// color previews are enabled; hand the server's answer back with
+    /// [`CodeEditor::set_lsp_document_colors`](crate::CodeEditor::set_lsp_document_colors).
+    fn request_document_colors(&mut self, _document: &LspDocument) {}
+    /// Requests the ways `color` can be written in place of `range`.
+    ///
+    /// The editor calls this while the color picker edits a color the server
+    /// reported; hand the server's answer back with
+    /// [`CodeEditor::set_lsp_color_presentations`](crate::CodeEditor::set_lsp_color_presentations).
+    /// Like every position the editor sends, `range` counts characters.
+    fn request_color_presentations(
+        &mut self,
+        _document: &LspDocument,
+        _color: iced::Color,
+        _range: LspRange,
+    ) {
+    }
     /// Requests the definition location(s) for the symbol at the given position.
     ///
     /// This method is called when the user triggers a "Go to Definition" action
@@ -207,6 +627,34 @@ pub trait LspClient {
         _position: LspPosition,
     ) {
     }
+    /// Requests hover information and returns a handle to the reply.
+    fn hover(
+        &mut self,
+        document: &LspDocument,
+        position: LspPosition,
+    ) -> LspRequest<String> {
+        self.request_hover(document, position);
+        LspRequest::none()
+    }
+    /// Requests completion items and returns a handle to the reply.
+    fn completion(
+        &mut self,
+        document: &LspDocument,
+        position: LspPosition,
+    ) -> LspRequest<Vec<LspCompletionItem>> {
+        self.request_completion(document, position);
+        LspRequest::none()
+    }
+    /// Requests the definition of the symbol at the given position and
+    /// returns a handle to the reply.
+    fn definition(
+        &mut self,
+        document: &LspDocument,
+        position: LspPosition,
+    ) -> LspRequest<LspLocation> {
+        self.request_definition(document, position);
+        LspRequest::none()
+    }
 }
 
 /// Computes a minimal text change between two snapshots.
@@ -295,10 +743,35 @@ fn position_for_char_index(text: &str, target_index: usize) -> LspPosition {
     LspPosition { line, character }
 }
 
+/// Converts a UTF-16 column, as language servers count them, into a
+/// character column of `line`.
+///
+/// A column past the end of the line, or one that splits a surrogate pair,
+/// resolves to the nearest character boundary at or before it.
+pub(crate) fn char_column(line: &str, utf16_column: u32) -> usize {
+    let mut units = 0u32;
+    for (index, ch) in line.chars().enumerate() {
+        let next = units.saturating_add(ch.len_utf16() as u32);
+        if next > utf16_column {
+            return index;
+        }
+        units = next;
+    }
+    line.chars().count()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_char_column_counts_utf16_units() {
+        // '😀' is two UTF-16 units but one character.
+        assert_eq!(char_column("😀ab", 2), 1);
+        assert_eq!(char_column("😀ab", 3), 2);
+        assert_eq!(char_column("ab", 10), 2);
+    }
+
     #[test]
     fn test_compute_text_change_none_when_equal() {
         let change = compute_text_change("abc", "abc");
@@ -0,0 +1,348 @@
+//! Delivery of LSP events into an iced application.
+//!
+//! A client built with [`LspProcessClient::new_with_server`] reports through a
+//! `std::sync::mpsc` channel that the host has to poll on a timer, and the
+//! events carry nothing that says which editor asked for them. An
+//! [`LspEventHub`] removes both chores: every client it spawns is bound to the
+//! id of the editor it serves, every event that client emits comes back as an
+//! [`LspEditorEvent`] tagged with that id, and the events arrive as the output
+//! of one long-lived [`Task`] instead of through a polled receiver.
+//! [`CodeEditor::apply_lsp_event`] then folds the replies that belong to the
+//! editor itself back into it, leaving only the host's own concerns — hover,
+//! completion, jumps, progress — to route.
+//!
+//! Hover, completion and definition need not go through the stream at all:
+//! [`CodeEditor::lsp_hover_at_position`], [`CodeEditor::lsp_completion`] and
+//! [`CodeEditor::lsp_definition`] return an
+//! [`LspRequest`](crate::LspRequest) whose
+//! [`into_task`](crate::LspRequest::into_task) resolves to the typed reply,
+//! tagged with the editor's id.
+
+use std::sync::mpsc;
+use std::thread;
+
+use iced::Task;
+use iced::futures::channel::mpsc as async_mpsc;
+
+use super::{LspEvent, LspProcessClient};
+use crate::canvas_editor::{CodeEditor, Message};
+
+/// An [`LspEvent`] together with the editor whose client emitted it.
+///
+/// Replies to a request (hover, completion, highlights, …) answer a request
+/// that editor sent; notifications (progress, logs) come from the server the
+/// editor is attached to.
+#[derive(Debug, Clone)]
+pub struct LspEditorEvent<Id> {
+    /// Id the client was spawned for with [`LspEventHub::spawn`].
+    pub editor: Id,
+    /// The event itself.
+    pub event: LspEvent,
+}
+
+/// Spawns LSP clients bound to editor ids and streams their events as an iced
+/// [`Task`].
+///
+/// `Id` is whatever the host uses to tell its editors apart, typically a tab
+/// id. The hub is cheap to clone; the event stream ends once the hub and every
+/// client it spawned have been dropped.
+///
+/// # Examples
+///
+/// ```no_run
+/// use iced::Task;
+/// use iced_code_editor::{
+///     CodeEditor, LspDocument, LspEditorEvent, LspEditorReply, LspEventHub,
+///     LspPosition, Message as EditorMessage,
+/// };
+///
+/// #[derive(Debug, Clone)]
+/// enum Message {
+///     Editor(usize, EditorMessage),
+///     Lsp(LspEditorEvent<usize>),
+///     Hover(usize, LspPosition),
+///     HoverReply(LspEditorReply<usize, String>),
+/// }
+///
+/// struct App {
+///     editors: Vec<CodeEditor>,
+///     hub: LspEventHub<usize>,
+/// }
+///
+/// fn boot() -> (App, Task<Message>) {
+///     let (hub, events) = LspEventHub::new();
+///     let mut editor = CodeEditor::new("print('hi')", "lua");
+///     if let Ok(client) =
+///         hub.spawn(0, "file:///tmp/project", "lua-language-server")
+///     {
+///         editor.attach_lsp(
+///             Box::new(client),
+///             LspDocument::new("file:///tmp/project/main.lua", "lua"),
+///         );
+///     }
+///     (App { editors: vec![editor], hub }, events.map(Message::Lsp))
+/// }
+///
+/// fn update(app: &mut App, message: Message) -> Task<Message> {
+///     match message {
+///         Message::Lsp(LspEditorEvent { editor: id, event }) => {
+///             let Some(editor) = app.editors.get_mut(id) else {
+///                 return Task::none();
+///             };
+///             match editor.apply_lsp_event(&event) {
+///                 Some(task) => task.map(move |e| Message::Editor(id, e)),
+///                 // Hover, completion, definition, progress and logs are
+///                 // the host's to present.
+///                 None => Task::none(),
+///             }
+///         }
+///         Message::Hover(id, position) => app
+///             .editors
+///             .get_mut(id)
+///             .and_then(|editor| editor.lsp_hover_at_position(position))
+///             .map_or_else(Task::none, |request| {
+///                 request.into_task(id).map(Message::HoverReply)
+///             }),
+///         Message::HoverReply(LspEditorReply { editor, result }) => {
+///             // Show `result` in a tooltip over editor `editor`.
+///             drop((editor, result));
+///             Task::none()
+///         }
+///         Message::Editor(id, message) => app
+///             .editors
+///             .get_mut(id)
+///             .map_or_else(Task::none, |editor| editor.update(&message))
+///             .map(move |e| Message::Editor(id, e)),
+///     }
+/// }
+/// ```
+#[derive(Debug, Clone)]
+pub struct LspEventHub<Id> {
+    /// Sending half of the stream the hub's [`Task`] reads.
+    sender: async_mpsc::UnboundedSender<LspEditorEvent<Id>>,
+}
+
+impl<Id: Clone + Send + 'static> LspEventHub<Id> {
+    /// Creates a hub and the [`Task`] that yields its events.
+    ///
+    /// Return the task from the application's boot or update function (mapped
+    /// to the host's message type); it keeps producing an [`LspEditorEvent`]
+    /// for every event of every client spawned through the hub.
+    pub fn new() -> (Self, Task<LspEditorEvent<Id>>) {
+        let (hub, receiver) = Self::channel();
+        (hub, Task::run(receiver, |event| event))
+    }
+
+    /// Creates a hub and the raw receiving half of its stream.
+    fn channel() -> (Self, async_mpsc::UnboundedReceiver<LspEditorEvent<Id>>) {
+        let (sender, receiver) = async_mpsc::unbounded();
+        (Self { sender }, receiver)
+    }
+
+    /// Spawns an LSP client for `editor`, whose events the hub tags with it.
+    ///
+    /// # Arguments
+    ///
+    /// * `editor` — id of the editor the client is attached to
+    /// * `root_uri` — the root URI of the workspace
+    /// * `server_key` — key identifying the LSP server (e.g. `"gopls"`)
+    ///
+    /// # Errors
+    ///
+    /// Returns the same errors as [`LspProcessClient::new_with_server`].
+    pub fn spawn(
+        &self,
+        editor: Id,
+        root_uri: &str,
+        server_key: &str,
+    ) -> Result<LspProcessClient, String> {
+        LspProcessClient::new_with_server(
+            root_uri,
+            self.sender_for(editor),
+            server_key,
+        )
+    }
+
+    /// Returns a sender whose events reach the hub's stream tagged with
+    /// `editor`.
+    ///
+    /// A forwarding thread moves events from the client's threads onto the
+    /// stream; it exits once every clone of the returned sender is dropped,
+    /// that is once the client is gone.
+    fn sender_for(&self, editor: Id) -> mpsc::Sender<LspEvent> {
+        let (events, receiver) = mpsc::channel();
+        let hub = self.sender.clone();
+        thread::spawn(move || {
+            for event in receiver {
+                let tagged = LspEditorEvent { editor: editor.clone(), event };
+                if hub.unbounded_send(tagged).is_err() {
+                    break;
+                }
+            }
+        });
+        events
+    }
+}
+
+impl CodeEditor {
+    /// Applies an LSP event that answers one of this editor's own requests.
+    ///
+    /// Document highlights, selection ranges, linked editing ranges, document
+    /// links, document colors and color presentations are handed to the
+    /// matching `set_lsp_*` setter, and the returned task carries whatever
+    /// messages that setter produces. Every other event is left to the host,
+    /// which owns the overlays, navigation and logs, and yields `None`.
+    ///
+    /// # Arguments
+    ///
+    /// * `event` - An event emitted by this editor's client
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use iced_code_editor::{CodeEditor, LspEvent};
+    ///
+    /// let mut editor = CodeEditor::new("let x = 1;", "rs");
+    /// let links = LspEvent::DocumentLinks { links: Vec::new() };
+    /// assert!(editor.apply_lsp_event(&links).is_some());
+    ///
+    /// let hover = LspEvent::Hover { text: "x: i32".to_string() };
+    /// assert!(editor.apply_lsp_event(&hover).is_none());
+    /// ```
+    pub fn apply_lsp_event(
+        &mut self,
+        event: &LspEvent,
+    ) -> Option<Task<Message>> {
+        match event {
+            LspEvent::DocumentHighlight { highlights } => {
+                self.set_lsp_document_highlights(highlights);
+            }
+            LspEvent::SelectionRange { ranges } => {
+                return Some(self.set_lsp_selection_ranges(ranges));
+            }
+            LspEvent::LinkedEditingRange { ranges } => {
+                self.set_lsp_linked_editing_ranges(ranges);
+            }
+            LspEvent::DocumentLinks { links } => {
+                self.set_lsp_document_links(links);
+            }
+            LspEvent::DocumentColors { colors } => {
+                self.set_lsp_document_colors(colors);
+            }
+            LspEvent::ColorPresentations { presentations } => {
+                self.set_lsp_color_presentations(presentations);
+            }
+            LspEvent::Hover { .. }
+            | LspEvent::Completion { .. }
+            | LspEvent::SignatureHelp { .. }
+            | LspEvent::Definition { .. }
+            | LspEvent::Progress { .. }
+            | LspEvent::Log { .. } => return None,
+        }
+        Some(Task::none())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::thread;
+    use std::time::{Duration, Instant};
+
+    use super::{LspEditorEvent, LspEventHub};
+    use crate::canvas_editor::CodeEditor;
+    use crate::canvas_editor::lsp::process::LspEvent;
+    use crate::canvas_editor::lsp::{LspPosition, LspRange};
+
+    /// Waits for the next item of the hub's stream: `Some(event)`, or `None`
+    /// once the stream has ended.
+    fn next_event(
+        receiver: &mut iced::futures::channel::mpsc::UnboundedReceiver<
+            LspEditorEvent<u32>,
+        >,
+    ) -> Option<LspEditorEvent<u32>> {
+        let deadline = Instant::now() + Duration::from_secs(5);
+        loop {
+            match receiver.try_next() {
+                Ok(item) => return item,
+                Err(_) if Instant::now() < deadline => {
+                    thread::sleep(Duration::from_millis(5));
+                }
+                Err(_) => return None,
+            }
+        }
+    }
+
+    /// Returns a log event carrying `message`.
+    fn log(message: &str) -> LspEvent {
+        LspEvent::Log {
+            server_key: "gopls".to_string(),
+            message: message.to_string(),
+        }
+    }
+
+    #[test]
+    #[allow(clippy::unwrap_used)]
+    fn test_events_are_tagged_with_their_editor() {
+        let (hub, mut receiver) = LspEventHub::<u32>::channel();
+        let first = hub.sender_for(1);
+        let second = hub.sender_for(2);
+
+        second.send(log("from two")).unwrap();
+        first.send(log("from one")).unwrap();
+
+        let mut received: Vec<(u32, String)> = (0..2)
+            .filter_map(|_| next_event(&mut receiver))
+            .map(|tagged| match tagged.event {
+                LspEvent::Log { message, .. } => (tagged.editor, message),
+                other => (tagged.editor, format!("{other:?}")),
+            })
+            .collect();
+        received.sort();
+
+        assert_eq!(
+            received,
+            [(1, "from one".to_string()), (2, "from two".to_string())]
+        );
+    }
+
+    #[test]
+    #[allow(clippy::unwrap_used)]
+    fn test_stream_ends_once_hub_and_clients_are_gone() {
+        let (hub, mut receiver) = LspEventHub::<u32>::channel();
+        let client = hub.sender_for(7);
+        drop(hub);
+
+        // The clients outlive the hub that spawned them.
+        client.send(log("still here")).unwrap();
+        assert_eq!(next_event(&mut receiver).map(|e| e.editor), Some(7));
+
+        drop(client);
+        assert!(next_event(&mut receiver).is_none());
+    }
+
+    #[test]
+    fn test_apply_lsp_event_keeps_host_events_for_the_host() {
+        let mut editor = CodeEditor::new("fn main() {}", "rs");
+        let range = LspRange {
+            start: LspPosition { line: 0, character: 3 },
+            end: LspPosition { line: 0, character: 7 },
+        };
+
+        assert!(
+            editor
+                .apply_lsp_event(&LspEvent::LinkedEditingRange {
+                    ranges: vec![range],
+                })
+                .is_some()
+        );
+        for event in [
+            LspEvent::Hover { text: "fn main()".to_string() },
+            LspEvent::Completion { items: Vec::new() },
+            LspEvent::SignatureHelp { help: None },
+            LspEvent::Definition { uri: "file:///a.rs".to_string(), range },
+            log("hello"),
+        ] {
+            assert!(editor.apply_lsp_event(&event).is_none(), "{event:?}");
+        }
+    }
+}
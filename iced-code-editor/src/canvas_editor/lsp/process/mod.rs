@@ -5,13 +5,16 @@
 //!
 //! Enable with the `lsp-process` Cargo feature. Not available on WASM targets.
 //!
-//! The transport itself lives in [`protocol`]: framing, bounded reads, message
-//! dispatch, and response parsing. [`text_model`] holds the per-document mirror
-//! used for UTF-16 position translation, and [`pending`] tracks in-flight
-//! requests. What remains here is the process lifecycle and the [`LspClient`]
-//! implementation the editor talks to.
+//! The transport itself lives in [`protocol`]: message dispatch and response
+//! parsing, over the `Content-Length` framing shared with the debug adapter
+//! client. [`text_model`] holds the per-document mirror used for UTF-16
+//! position translation, and [`pending`] tracks in-flight requests. [`hub`]
+//! spawns clients bound to editor ids and delivers their events to an iced
+//! application as a `Task`. What remains here is the process lifecycle and the
+//! [`LspClient`] implementation the editor talks to.
 
 pub mod config;
+pub mod hub;
 pub mod overlay;
 
 mod pending;
@@ -22,14 +25,20 @@ use self::config::{
     LspCommand, ensure_rust_analyzer_config, lsp_server_config,
     resolve_lsp_command,
 };
-use self::pending::{LspRequestKind, PendingRequest, evict_expired_requests};
+use self::pending::{
+    LspReplySender, LspRequestKind, PendingRequest, evict_expired_requests,
+};
 use self::protocol::{
-    frame_message, handle_client_response, handle_server_notification,
-    handle_server_request, read_log_line, read_message,
+    handle_client_response, handle_server_notification, handle_server_request,
 };
 use self::text_model::{DocumentState, TextModel, apply_changes_to_document};
+use crate::canvas_editor::framing::{
+    frame_message, read_log_line, read_message,
+};
 use crate::canvas_editor::lsp::{
-    LspClient, LspDocument, LspPosition, LspRange, LspTextChange,
+    LspClient, LspColorPresentation, LspCompletionItem, LspDocument,
+    LspDocumentColor, LspDocumentHighlight, LspDocumentLink, LspLocation,
+    LspPosition, LspRange, LspRequest, LspTextChange,
 };
 use serde_json::json;
 use std::collections::HashMap;
@@ -45,10 +54,14 @@ use std::time::Instant;
 
 /// Events that can be sent from the LSP client to the application.
 ///
-/// Receive these by polling the `mpsc::Receiver` you pass to
-/// [`LspProcessClient::new_with_server`]. Requests are fire-and-forget, so
-/// every server reply arrives here rather than as a return value — drain the
-/// receiver on a timer and fold the results into the application state.
+/// Requests are fire-and-forget, so every server reply arrives as an event
+/// rather than as a return value — except hover, completion and definition
+/// requests sent through [`LspClient::hover`] and friends, whose replies go
+/// to the returned [`LspRequest`] handle instead. Clients spawned through an
+/// [`LspEventHub`](hub::LspEventHub) deliver them to an iced `Task`, tagged
+/// with the editor that asked; a client built with
+/// [`LspProcessClient::new_with_server`] sends them to the `mpsc::Receiver`
+/// you pass it, to be drained on a timer.
 ///
 /// # Examples
 ///
@@ -66,12 +79,22 @@ use std::time::Instant;
 ///     match event {
 ///         LspEvent::Hover { text } => assert_eq!(text, "fn main()"),
 ///         LspEvent::Completion { items } => drop(items),
+///         LspEvent::SignatureHelp { help } => drop(help),
+///         LspEvent::DocumentHighlight { highlights } => drop(highlights),
+///         LspEvent::SelectionRange { ranges } => drop(ranges),
+///         LspEvent::LinkedEditingRange { ranges } => drop(ranges),
+///         LspEvent::DocumentLinks { links } => drop(links),
+///         LspEvent::DocumentColors { colors } => drop(colors),
+///         LspEvent::ColorPresentations { presentations } => {
+///             drop(presentations)
+///         }
 ///         LspEvent::Definition { uri, .. } => drop(uri),
 ///         LspEvent::Progress { done, .. } => drop(done),
 ///         LspEvent::Log { message, .. } => drop(message),
 ///     }
 /// }
 /// ```
+#[derive(Debug, Clone)]
 pub enum LspEvent {
     /// Hover information received from the LSP server.
     Hover {
@@ -80,8 +103,55 @@ pub enum LspEvent {
     },
     /// Completion items received from the LSP server.
     Completion {
-        /// List of completion label strings.
-        items: Vec<String>,
+        /// Completion items, in server order.
+        items: Vec<LspCompletionItem>,
+    },
+    /// Signature help received from the LSP server.
+    SignatureHelp {
+        /// The active signature, or `None` when the server has nothing to
+        /// show — the popup should close.
+        help: Option<LspSignatureHelp>,
+    },
+    /// Occurrences of the symbol under the cursor received from the LSP
+    /// server; pass them to
+    /// [`CodeEditor::set_lsp_document_highlights`](crate::CodeEditor::set_lsp_document_highlights).
+    DocumentHighlight {
+        /// Highlighted ranges, in UTF-16 code units as sent by the server.
+        highlights: Vec<LspDocumentHighlight>,
+    },
+    /// Ranges enclosing the position of a selection-range request, innermost
+    /// first; pass them to
+    /// [`CodeEditor::set_lsp_selection_ranges`](crate::CodeEditor::set_lsp_selection_ranges).
+    /// Empty when the server has none or rejected the request.
+    SelectionRange {
+        /// Enclosing ranges, in UTF-16 code units as sent by the server.
+        ranges: Vec<LspRange>,
+    },
+    /// Ranges that must be edited together with the one at the requested
+    /// position; pass them to
+    /// [`CodeEditor::set_lsp_linked_editing_ranges`](crate::CodeEditor::set_lsp_linked_editing_ranges).
+    LinkedEditingRange {
+        /// Linked ranges, in UTF-16 code units as sent by the server.
+        ranges: Vec<LspRange>,
+    },
+    /// Links found in a document by the LSP server; pass them to
+    /// [`CodeEditor::set_lsp_document_links`](crate::CodeEditor::set_lsp_document_links).
+    DocumentLinks {
+        /// Links with a target, in UTF-16 code units as sent by the server.
+        links: Vec<LspDocumentLink>,
+    },
+    /// Colors found in a document by the LSP server; pass them to
+    /// [`CodeEditor::set_lsp_document_colors`](crate::CodeEditor::set_lsp_document_colors).
+    DocumentColors {
+        /// Colors, in UTF-16 code units as sent by the server.
+        colors: Vec<LspDocumentColor>,
+    },
+    /// Ways of writing the color picked in the editor's color picker; pass
+    /// them to
+    /// [`CodeEditor::set_lsp_color_presentations`](crate::CodeEditor::set_lsp_color_presentations).
+    ColorPresentations {
+        /// Presentations, in server order.
+        presentations: Vec<LspColorPresentation>,
     },
     /// Definition location received from the LSP server.
     Definition {
@@ -114,6 +184,36 @@ pub enum LspEvent {
     },
 }
 
+/// The active signature of an LSP signature-help response.
+///
+/// Only the signature the server marks active is kept: the popup shows one
+/// signature at a time, with the parameter under the cursor emphasized.
+///
+/// # Examples
+///
+/// ```
+/// use iced_code_editor::LspSignatureHelp;
+///
+/// let help = LspSignatureHelp {
+///     label: "fn add(a: i32, b: i32) -> i32".to_string(),
+///     documentation: None,
+///     active_parameter: Some((15, 21)),
+/// };
+/// let (start, end) = help.active_parameter.unwrap_or_default();
+/// let parameter: String =
+///     help.label.chars().skip(start).take(end - start).collect();
+/// assert_eq!(parameter, "b: i32");
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LspSignatureHelp {
+    /// The full signature label, e.g. `fn add(a: i32, b: i32) -> i32`.
+    pub label: String,
+    /// Markdown documentation for the signature or the active parameter.
+    pub documentation: Option<String>,
+    /// Char range `(start, end)` of the active parameter within `label`.
+    pub active_parameter: Option<(usize, usize)>,
+}
+
 // =============================================================================
 // LSP Process Client - Main client implementation
 // =============================================================================
@@ -122,6 +222,9 @@ pub enum LspEvent {
 ///
 /// Manages the lifecycle of the server process and handles all communication.
 /// Implements [`LspClient`] so it can be plugged directly into a [`CodeEditor`].
+/// In an iced application, prefer spawning it with
+/// [`LspEventHub::spawn`](hub::LspEventHub::spawn), which delivers its events
+/// without a polled channel.
 ///
 /// # Examples
 ///
@@ -340,6 +443,39 @@ impl LspProcessClient {
                             "dynamicRegistration": false,
                             "willSave": false,
                             "didSave": true
+                        },
+                        "hover": {
+                            "contentFormat": ["markdown", "plaintext"]
+                        },
+                        "completion": {
+                            "completionItem": {
+                                "documentationFormat": ["markdown", "plaintext"],
+                                "snippetSupport": true
+                            }
+                        },
+                        "signatureHelp": {
+                            "signatureInformation": {
+                                "documentationFormat": ["markdown", "plaintext"],
+                                "parameterInformation": {
+                                    "labelOffsetSupport": true
+                                }
+                            }
+                        },
+                        "documentHighlight": {
+                            "dynamicRegistration": false
+                        },
+                        "selectionRange": {
+                            "dynamicRegistration": false
+                        },
+                        "linkedEditingRange": {
+                            "dynamicRegistration": false
+                        },
+                        "documentLink": {
+                            "dynamicRegistration": false,
+                            "tooltipSupport": false
+                        },
+                        "colorProvider": {
+                            "dynamicRegistration": false
                         }
                     },
                     "window": {
@@ -375,6 +511,124 @@ impl LspProcessClient {
         }
     }
 
+    /// Sends a `textDocument/hover` request, whose reply goes to `reply`
+    /// when given and out as an [`LspEvent::Hover`] otherwise.
+    fn send_hover(
+        &self,
+        document: &LspDocument,
+        position: LspPosition,
+        reply: Option<LspReplySender>,
+    ) {
+        let docs = self.documents.lock().unwrap_or_else(|e| e.into_inner());
+        let Some(state) = docs.get(&document.uri) else { return };
+        let pos = state.text.to_utf16_position(position);
+
+        let id = self.next_id();
+        {
+            let mut pending =
+                self.pending_requests.lock().unwrap_or_else(|e| e.into_inner());
+            evict_expired_requests(&mut pending);
+            pending.insert(
+                id,
+                PendingRequest {
+                    kind: LspRequestKind::Hover,
+                    reply,
+                    requested_at: Instant::now(),
+                },
+            );
+        }
+
+        let msg = json!({
+            "jsonrpc": "2.0",
+            "id": id,
+            "method": "textDocument/hover",
+            "params": {
+                "textDocument": { "uri": document.uri },
+                "position": { "line": pos.line, "character": pos.character }
+            }
+        });
+        self.send_message(&msg);
+    }
+
+    /// Sends a `textDocument/completion` request, whose reply goes to
+    /// `reply` when given and out as an [`LspEvent::Completion`] otherwise.
+    fn send_completion(
+        &self,
+        document: &LspDocument,
+        position: LspPosition,
+        reply: Option<LspReplySender>,
+    ) {
+        let docs = self.documents.lock().unwrap_or_else(|e| e.into_inner());
+        let Some(state) = docs.get(&document.uri) else { return };
+        let pos = state.text.to_utf16_position(position);
+
+        let id = self.next_id();
+        {
+            let mut pending =
+                self.pending_requests.lock().unwrap_or_else(|e| e.into_inner());
+            evict_expired_requests(&mut pending);
+            pending.insert(
+                id,
+                PendingRequest {
+                    kind: LspRequestKind::Completion,
+                    reply,
+                    requested_at: Instant::now(),
+                },
+            );
+        }
+
+        let msg = json!({
+            "jsonrpc": "2.0",
+            "id": id,
+            "method": "textDocument/completion",
+            "params": {
+                "textDocument": { "uri": document.uri },
+                "position": { "line": pos.line, "character": pos.character },
+                "context": { "triggerKind": 1 }
+            }
+        });
+        self.send_message(&msg);
+    }
+
+    /// Sends a `textDocument/definition` request, whose reply goes to
+    /// `reply` when given and out as an [`LspEvent::Definition`] otherwise.
+    fn send_definition(
+        &self,
+        document: &LspDocument,
+        position: LspPosition,
+        reply: Option<LspReplySender>,
+    ) {
+        let docs = self.documents.lock().unwrap_or_else(|e| e.into_inner());
+        let Some(state) = docs.get(&document.uri) else { return };
+        let pos = state.text.to_utf16_position(position);
+
+        let id = self.next_id();
+        {
+            let mut pending =
+                self.pending_requests.lock().unwrap_or_else(|e| e.into_inner());
+            evict_expired_requests(&mut pending);
+            pending.insert(
+                id,
+                PendingRequest {
+                    kind: LspRequestKind::Definition,
+                    reply,
+                    requested_at: Instant::now(),
+                },
+            );
+        }
+
+        let msg = json!({
+            "jsonrpc": "2.0",
+            "id": id,
+            "method": "textDocument/definition",
+            "params": {
+                "textDocument": { "uri": document.uri },
+                "position": { "line": pos.line, "character": pos.character }
+            }
+        });
+        self.send_message(&msg);
+    }
+
     /// Applies text changes to a document and converts them to JSON format.
     ///
     /// Also converts positions to UTF-16 as required by LSP.
@@ -517,7 +771,11 @@ impl LspClient for LspProcessClient {
         self.send_message(&msg);
     }
 
-    fn request_hover(&mut self, document: &LspDocument, position: LspPosition) {
+    fn request_signature_help(
+        &mut self,
+        document: &LspDocument,
+        position: LspPosition,
+    ) {
         let docs = self.documents.lock().unwrap_or_else(|e| e.into_inner());
         let Some(state) = docs.get(&document.uri) else { return };
         let pos = state.text.to_utf16_position(position);
@@ -530,7 +788,8 @@ impl LspClient for LspProcessClient {
             pending.insert(
                 id,
                 PendingRequest {
-                    kind: LspRequestKind::Hover,
+                    kind: LspRequestKind::SignatureHelp,
+                    reply: None,
                     requested_at: Instant::now(),
                 },
             );
@@ -539,7 +798,7 @@ impl LspClient for LspProcessClient {
         let msg = json!({
             "jsonrpc": "2.0",
             "id": id,
-            "method": "textDocument/hover",
+            "method": "textDocument/signatureHelp",
             "params": {
                 "textDocument": { "uri": document.uri },
                 "position": { "line": pos.line, "character": pos.character }
@@ -548,7 +807,7 @@ impl LspClient for LspProcessClient {
         self.send_message(&msg);
     }
 
-    fn request_completion(
+    fn request_document_highlight(
         &mut self,
         document: &LspDocument,
         position: LspPosition,
@@ -565,7 +824,8 @@ impl LspClient for LspProcessClient {
             pending.insert(
                 id,
                 PendingRequest {
-                    kind: LspRequestKind::Completion,
+                    kind: LspRequestKind::DocumentHighlight,
+                    reply: None,
                     requested_at: Instant::now(),
                 },
             );
@@ -574,17 +834,16 @@ impl LspClient for LspProcessClient {
         let msg = json!({
             "jsonrpc": "2.0",
             "id": id,
-            "method": "textDocument/completion",
+            "method": "textDocument/documentHighlight",
             "params": {
                 "textDocument": { "uri": document.uri },
-                "position": { "line": pos.line, "character": pos.character },
-                "context": { "triggerKind": 1 }
+                "position": { "line": pos.line, "character": pos.character }
             }
         });
         self.send_message(&msg);
     }
 
-    fn request_definition(
+    fn request_selection_range(
         &mut self,
         document: &LspDocument,
         position: LspPosition,
@@ -601,7 +860,8 @@ impl LspClient for LspProcessClient {
             pending.insert(
                 id,
                 PendingRequest {
-                    kind: LspRequestKind::Definition,
+                    kind: LspRequestKind::SelectionRange,
+                    reply: None,
                     requested_at: Instant::now(),
                 },
             );
@@ -610,7 +870,45 @@ impl LspClient for LspProcessClient {
         let msg = json!({
             "jsonrpc": "2.0",
             "id": id,
-            "method": "textDocument/definition",
+            "method": "textDocument/selectionRange",
+            "params": {
+                "textDocument": { "uri": document.uri },
+                "positions": [
+                    { "line": pos.line, "character": pos.character }
+                ]
+            }
+        });
+        self.send_message(&msg);
+    }
+
+    fn request_linked_editing_range(
+        &mut self,
+        document: &LspDocument,
+        position: LspPosition,
+    ) {
+        let docs = self.documents.lock().unwrap_or_else(|e| e.into_inner());
+        let Some(state) = docs.get(&document.uri) else { return };
+        let pos = state.text.to_utf16_position(position);
+
+        let id = self.next_id();
+        {
+            let mut pending =
+                self.pending_requests.lock().unwrap_or_else(|e| e.into_inner());
+            evict_expired_requests(&mut pending);
+            pending.insert(
+                id,
+                PendingRequest {
+                    kind: LspRequestKind::LinkedEditingRange,
+                    reply: None,
+                    requested_at: Instant::now(),
+                },
+            );
+        }
+
+        let msg = json!({
+            "jsonrpc": "2.0",
+            "id": id,
+            "method": "textDocument/linkedEditingRange",
             "params": {
                 "textDocument": { "uri": document.uri },
                 "position": { "line": pos.line, "character": pos.character }
@@ -618,6 +916,165 @@ impl LspClient for LspProcessClient {
         });
         self.send_message(&msg);
     }
+
+    fn request_document_links(&mut self, document: &LspDocument) {
+        let id = self.next_id();
+        {
+            let mut pending =
+                self.pending_requests.lock().unwrap_or_else(|e| e.into_inner());
+            evict_expired_requests(&mut pending);
+            pending.insert(
+                id,
+                PendingRequest {
+                    kind: LspRequestKind::DocumentLink,
+                    reply: None,
+                    requested_at: Instant::now(),
+                },
+            );
+        }
+
+        let msg = json!({
+            "jsonrpc": "2.0",
+            "id": id,
+            "method": "textDocument/documentLink",
+            "params": {
+                "textDocument": { "uri": document.uri }
+            }
+        });
+        self.send_message(&msg);
+    }
+
+    fn request_document_colors(&mut self, document: &LspDocument) {
+        let id = self.next_id();
+        {
+            let mut pending =
+                self.pending_requests.lock().unwrap_or_else(|e| e.into_inner());
+            evict_expired_requests(&mut pending);
+            pending.insert(
+                id,
+                PendingRequest {
+                    kind: LspRequestKind::DocumentColor,
+                    reply: None,
+                    requested_at: Instant::now(),
+                },
+            );
+        }
+
+        let msg = json!({
+            "jsonrpc": "2.0",
+            "id": id,
+            "method": "textDocument/documentColor",
+            "params": {
+                "textDocument": { "uri": document.uri }
+            }
+        });
+        self.send_message(&msg);
+    }
+
+    fn request_color_presentations(
+        &mut self,
+        document: &LspDocument,
+        color: iced::Color,
+        range: LspRange,
+    ) {
+        let docs = self.documents.lock().unwrap_or_else(|e| e.into_inner());
+        let Some(state) = docs.get(&document.uri) else { return };
+        let start = state.text.to_utf16_position(range.start);
+        let end = state.text.to_utf16_position(range.end);
+
+        let id = self.next_id();
+        {
+            let mut pending =
+                self.pending_requests.lock().unwrap_or_else(|e| e.into_inner());
+            evict_expired_requests(&mut pending);
+            pending.insert(
+                id,
+                PendingRequest {
+                    kind: LspRequestKind::ColorPresentation,
+                    reply: None,
+                    requested_at: Instant::now(),
+                },
+            );
+        }
+
+        let msg = json!({
+            "jsonrpc": "2.0",
+            "id": id,
+            "method": "textDocument/colorPresentation",
+            "params": {
+                "textDocument": { "uri": document.uri },
+                "color": {
+                    "red": color.r,
+                    "green": color.g,
+                    "blue": color.b,
+                    "alpha": color.a
+                },
+                "range": {
+                    "start": { "line": start.line, "character": start.character },
+                    "end": { "line": end.line, "character": end.character }
+                }
+            }
+        });
+        self.send_message(&msg);
+    }
+
+    fn request_hover(&mut self, document: &LspDocument, position: LspPosition) {
+        self.send_hover(document, position, None);
+    }
+
+    fn request_completion(
+        &mut self,
+        document: &LspDocument,
+        position: LspPosition,
+    ) {
+        self.send_completion(document, position, None);
+    }
+
+    fn request_definition(
+        &mut self,
+        document: &LspDocument,
+        position: LspPosition,
+    ) {
+        self.send_definition(document, position, None);
+    }
+
+    fn hover(
+        &mut self,
+        document: &LspDocument,
+        position: LspPosition,
+    ) -> LspRequest<String> {
+        let (reply, request) = LspRequest::channel();
+        self.send_hover(document, position, Some(LspReplySender::Hover(reply)));
+        request
+    }
+
+    fn completion(
+        &mut self,
+        document: &LspDocument,
+        position: LspPosition,
+    ) -> LspRequest<Vec<LspCompletionItem>> {
+        let (reply, request) = LspRequest::channel();
+        self.send_completion(
+            document,
+            position,
+            Some(LspReplySender::Completion(reply)),
+        );
+        request
+    }
+
+    fn definition(
+        &mut self,
+        document: &LspDocument,
+        position: LspPosition,
+    ) -> LspRequest<LspLocation> {
+        let (reply, request) = LspRequest::channel();
+        self.send_definition(
+            document,
+            position,
+            Some(LspReplySender::Definition(reply)),
+        );
+        request
+    }
 }
 
 // =============================================================================
@@ -860,6 +1317,63 @@ mod tests {
         assert!(client.pending_requests.lock().unwrap().is_empty());
     }
 
+    #[test]
+    #[allow(clippy::expect_used, clippy::assertions_on_result_states)]
+    fn test_hover_handle_receives_the_reply_instead_of_an_event() {
+        use iced::futures::FutureExt;
+
+        let (mut client, writer_rx, events_rx) = test_client();
+        let doc = document("file:///a.rs");
+        client.did_open(&doc, "hello");
+        writer_rx.try_recv().expect("drain didOpen");
+
+        let request = client.hover(&doc, LspPosition { line: 0, character: 2 });
+        let bytes = writer_rx.try_recv().expect("hover request sent");
+        let id = decode_sent(&bytes)["id"].as_u64().expect("id present");
+
+        let reply = json!({ "id": id, "result": { "contents": "hello: str" } });
+        handle_client_response(
+            id,
+            &reply,
+            &client.pending_requests,
+            &client.events,
+        );
+
+        assert_eq!(request.now_or_never(), Some(Some("hello: str".into())));
+        assert!(events_rx.try_recv().is_err());
+    }
+
+    #[test]
+    #[allow(clippy::expect_used)]
+    fn test_definition_handle_resolves_to_none_without_a_location() {
+        use iced::futures::FutureExt;
+
+        let (mut client, writer_rx, _events_rx) = test_client();
+        let doc = document("file:///a.rs");
+        client.did_open(&doc, "hello");
+        writer_rx.try_recv().expect("drain didOpen");
+
+        let missing = client.definition(
+            &document("file:///missing.rs"),
+            LspPosition { line: 0, character: 0 },
+        );
+        assert!(missing.now_or_never().is_some_and(|reply| reply.is_none()));
+
+        let request =
+            client.definition(&doc, LspPosition { line: 0, character: 1 });
+        let bytes = writer_rx.try_recv().expect("definition request sent");
+        let id = decode_sent(&bytes)["id"].as_u64().expect("id present");
+        let reply = json!({ "id": id, "result": null });
+        handle_client_response(
+            id,
+            &reply,
+            &client.pending_requests,
+            &client.events,
+        );
+
+        assert!(request.now_or_never().is_some_and(|reply| reply.is_none()));
+    }
+
     #[test]
     #[allow(clippy::expect_used, clippy::unwrap_used, clippy::panic)]
     fn test_request_completion_registers_pending_and_sends_request() {
@@ -882,6 +1396,167 @@ mod tests {
         }
     }
 
+    #[test]
+    #[allow(clippy::expect_used, clippy::unwrap_used, clippy::panic)]
+    fn test_request_signature_help_registers_pending_and_sends_request() {
+        let (mut client, writer_rx, _events_rx) = test_client();
+        let doc = document("file:///a.rs");
+        client.did_open(&doc, "f(a, b)");
+        writer_rx.try_recv().expect("drain didOpen");
+
+        client.request_signature_help(
+            &doc,
+            LspPosition { line: 0, character: 4 },
+        );
+
+        let bytes = writer_rx.try_recv().expect("signature help request sent");
+        let value = decode_sent(&bytes);
+        assert_eq!(value["method"], "textDocument/signatureHelp");
+        assert_eq!(value["params"]["position"]["character"], 4);
+        let id = value["id"].as_u64().expect("id present");
+
+        let pending = client.pending_requests.lock().unwrap();
+        match pending.get(&id).map(|p| &p.kind) {
+            Some(LspRequestKind::SignatureHelp) => {}
+            _ => panic!("expected a pending SignatureHelp request"),
+        }
+    }
+
+    #[test]
+    #[allow(clippy::expect_used, clippy::unwrap_used, clippy::panic)]
+    fn test_request_document_highlight_registers_pending_and_sends_request() {
+        let (mut client, writer_rx, _events_rx) = test_client();
+        let doc = document("file:///a.rs");
+        client.did_open(&doc, "😀 x");
+        writer_rx.try_recv().expect("drain didOpen");
+
+        client.request_document_highlight(
+            &doc,
+            LspPosition { line: 0, character: 2 },
+        );
+
+        let bytes = writer_rx.try_recv().expect("highlight request sent");
+        let value = decode_sent(&bytes);
+        assert_eq!(value["method"], "textDocument/documentHighlight");
+        // The emoji is one character but two UTF-16 code units.
+        assert_eq!(value["params"]["position"]["character"], 3);
+        let id = value["id"].as_u64().expect("id present");
+
+        let pending = client.pending_requests.lock().unwrap();
+        match pending.get(&id).map(|p| &p.kind) {
+            Some(LspRequestKind::DocumentHighlight) => {}
+            _ => panic!("expected a pending DocumentHighlight request"),
+        }
+    }
+
+    #[test]
+    #[allow(clippy::expect_used, clippy::unwrap_used, clippy::panic)]
+    fn test_request_selection_range_sends_positions_array() {
+        let (mut client, writer_rx, _events_rx) = test_client();
+        let doc = document("file:///a.rs");
+        client.did_open(&doc, "😀 x");
+        writer_rx.try_recv().expect("drain didOpen");
+
+        client.request_selection_range(
+            &doc,
+            LspPosition { line: 0, character: 2 },
+        );
+
+        let bytes = writer_rx.try_recv().expect("selection range request sent");
+        let value = decode_sent(&bytes);
+        assert_eq!(value["method"], "textDocument/selectionRange");
+        assert_eq!(value["params"]["positions"][0]["character"], 3);
+        let id = value["id"].as_u64().expect("id present");
+
+        let pending = client.pending_requests.lock().unwrap();
+        match pending.get(&id).map(|p| &p.kind) {
+            Some(LspRequestKind::SelectionRange) => {}
+            _ => panic!("expected a pending SelectionRange request"),
+        }
+    }
+
+    #[test]
+    #[allow(clippy::expect_used, clippy::unwrap_used, clippy::panic)]
+    fn test_request_linked_editing_range_registers_pending_and_sends_request() {
+        let (mut client, writer_rx, _events_rx) = test_client();
+        let doc = document("file:///a.html");
+        client.did_open(&doc, "<p></p>");
+        writer_rx.try_recv().expect("drain didOpen");
+
+        client.request_linked_editing_range(
+            &doc,
+            LspPosition { line: 0, character: 1 },
+        );
+
+        let bytes = writer_rx.try_recv().expect("linked editing request sent");
+        let value = decode_sent(&bytes);
+        assert_eq!(value["method"], "textDocument/linkedEditingRange");
+        assert_eq!(value["params"]["position"]["character"], 1);
+        let id = value["id"].as_u64().expect("id present");
+
+        let pending = client.pending_requests.lock().unwrap();
+        match pending.get(&id).map(|p| &p.kind) {
+            Some(LspRequestKind::LinkedEditingRange) => {}
+            _ => panic!("expected a pending LinkedEditingRange request"),
+        }
+    }
+
+    #[test]
+    #[allow(clippy::expect_used, clippy::unwrap_used, clippy::panic)]
+    fn test_request_color_presentations_sends_utf16_range_and_color() {
+        let (mut client, writer_rx, _events_rx) = test_client();
+        let doc = document("file:///a.css");
+        // The emoji takes two UTF-16 code units.
+        client.did_open(&doc, "/* \u{1f600} */ a { color: #ff0000; }");
+        writer_rx.try_recv().expect("drain didOpen");
+
+        client.request_document_colors(&doc);
+        let bytes = writer_rx.try_recv().expect("document color request sent");
+        assert_eq!(decode_sent(&bytes)["method"], "textDocument/documentColor");
+
+        let range = LspRange {
+            start: LspPosition { line: 0, character: 19 },
+            end: LspPosition { line: 0, character: 26 },
+        };
+        client.request_color_presentations(&doc, iced::Color::WHITE, range);
+
+        let bytes = writer_rx.try_recv().expect("presentation request sent");
+        let value = decode_sent(&bytes);
+        assert_eq!(value["method"], "textDocument/colorPresentation");
+        assert_eq!(value["params"]["color"]["alpha"], 1.0);
+        assert_eq!(value["params"]["range"]["start"]["character"], 20);
+        let id = value["id"].as_u64().expect("id present");
+
+        let pending = client.pending_requests.lock().unwrap();
+        match pending.get(&id).map(|p| &p.kind) {
+            Some(LspRequestKind::ColorPresentation) => {}
+            _ => panic!("expected a pending ColorPresentation request"),
+        }
+    }
+
+    #[test]
+    #[allow(clippy::expect_used, clippy::unwrap_used, clippy::panic)]
+    fn test_request_document_links_registers_pending_and_sends_request() {
+        let (mut client, writer_rx, _events_rx) = test_client();
+        let doc = document("file:///a.rs");
+        client.did_open(&doc, "// https://example.com");
+        writer_rx.try_recv().expect("drain didOpen");
+
+        client.request_document_links(&doc);
+
+        let bytes = writer_rx.try_recv().expect("document link request sent");
+        let value = decode_sent(&bytes);
+        assert_eq!(value["method"], "textDocument/documentLink");
+        assert_eq!(value["params"]["textDocument"]["uri"], "file:///a.rs");
+        let id = value["id"].as_u64().expect("id present");
+
+        let pending = client.pending_requests.lock().unwrap();
+        match pending.get(&id).map(|p| &p.kind) {
+            Some(LspRequestKind::DocumentLink) => {}
+            _ => panic!("expected a pending DocumentLink request"),
+        }
+    }
+
     #[test]
     #[allow(clippy::expect_used, clippy::unwrap_used, clippy::panic)]
     fn test_request_definition_registers_pending_and_sends_request() {
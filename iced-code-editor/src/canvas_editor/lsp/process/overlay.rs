@@ -1,14 +1,29 @@
-//! LSP overlay UI components for displaying hover tooltips and completion menus.
+//! LSP overlay UI components for displaying hover tooltips, completion menus
+//! and signature help.
 //!
 //! Provides [`LspOverlayState`] for storing overlay display state and
 //! [`view_lsp_overlay`] for rendering it on top of a [`CodeEditor`].
+//!
+//! All popup text is Markdown. Fenced code blocks are highlighted with the
+//! editor's own syntect pipeline: a block tagged with a language uses that
+//! language, and an untagged block (such as a completion item's detail) uses
+//! the editor's.
+
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::rc::Rc;
 
+use super::{LspCompletionItem, LspSignatureHelp};
 use crate::CodeEditor;
+use crate::canvas_editor::render::syntax::highlight_snippet;
+use iced::widget::text::Span;
 use iced::widget::{
-    Id, Space, button, column, container, markdown, mouse_area, row,
-    scrollable, stack, text,
+    Id, Space, button, column, container, markdown, mouse_area, rich_text, row,
+    scrollable, span, stack, text,
+};
+use iced::{
+    Background, Border, Color, Element, Font, Length, Point, Shadow, Theme,
 };
-use iced::{Background, Border, Color, Element, Length, Point, Shadow, Theme};
 
 /// Maximum number of completion items shown at once in the menu.
 const MAX_COMPLETION_ITEMS: usize = 8;
@@ -20,8 +35,20 @@ const COMPLETION_HEADER_HEIGHT: f32 = 24.0;
 const COMPLETION_PADDING: f32 = 4.0;
 /// Maximum width in pixels of the completion menu.
 const COMPLETION_MENU_WIDTH: f32 = 250.0;
+/// Width in pixels of the documentation panel beside the completion menu.
+const COMPLETION_DOC_WIDTH: f32 = 320.0;
 /// Border radius in pixels applied to scrollable rail and scroller borders.
 const SCROLLABLE_BORDER_RADIUS: f32 = 4.0;
+/// Maximum number of text lines a hover tooltip shows before scrolling.
+const MAX_HOVER_LINES: f32 = 10.0;
+/// Maximum number of documentation lines the signature popup shows before
+/// scrolling.
+const MAX_SIGNATURE_DOC_LINES: f32 = 6.0;
+/// Padding in pixels inside hover and signature popups.
+const POPUP_PADDING: f32 = 8.0;
+
+/// Highlighted lines of a fenced code block, keyed by `(language, code)`.
+type CodeBlockCache = HashMap<(String, String), Rc<Vec<Vec<(Color, String)>>>>;
 
 /// State for the LSP overlay display (hover tooltips and completion menus).
 ///
@@ -62,6 +89,28 @@ pub struct LspOverlayState {
     pub completion_suppressed: bool,
     /// Screen position of the completion menu anchor.
     pub completion_position: Option<Point>,
+    /// Markdown documentation for each entry of `all_completions`, by
+    /// index, built from the item's detail and documentation. Items sharing
+    /// a label, such as overloads, each keep their own.
+    pub completion_docs: Vec<Option<String>>,
+    /// Parsed markdown for the selected completion item's documentation.
+    pub completion_doc_items: Vec<markdown::Item>,
+    /// The items behind `all_completions`, by index (see
+    /// [`Self::selected_completion_item`]).
+    completion_entries: Vec<LspCompletionItem>,
+    /// For each entry of `completion_items`, its index in
+    /// `all_completions`.
+    completion_matches: Vec<usize>,
+    /// The signature help currently shown, if any.
+    pub signature_help: Option<LspSignatureHelp>,
+    /// Parsed markdown derived from the signature help's documentation.
+    pub signature_items: Vec<markdown::Item>,
+    /// Screen position of the signature help anchor (the call's cursor).
+    pub signature_position: Option<Point>,
+    /// Highlighted fenced code blocks, so a popup is tokenized once rather
+    /// than on every frame it is drawn. Cleared whenever popup content is
+    /// replaced.
+    code_blocks: RefCell<CodeBlockCache>,
 }
 
 impl LspOverlayState {
@@ -90,6 +139,14 @@ impl LspOverlayState {
             completion_selected: 0,
             completion_suppressed: false,
             completion_position: None,
+            completion_docs: Vec::new(),
+            completion_doc_items: Vec::new(),
+            completion_entries: Vec::new(),
+            completion_matches: Vec::new(),
+            signature_help: None,
+            signature_items: Vec::new(),
+            signature_position: None,
+            code_blocks: RefCell::new(HashMap::new()),
         }
     }
 
@@ -113,7 +170,9 @@ impl LspOverlayState {
 
     /// Displays a hover tooltip with the given text.
     ///
-    /// Parses the text as markdown and marks the tooltip as visible.
+    /// Parses the text as markdown and marks the tooltip as visible. Fenced
+    /// code blocks are highlighted with their tagged language, or with the
+    /// editor's when untagged.
     ///
     /// # Example
     ///
@@ -126,7 +185,8 @@ impl LspOverlayState {
     /// assert!(state.hover_text.is_some());
     /// ```
     pub fn show_hover(&mut self, text: String) {
-        self.hover_items = iced::widget::markdown::parse(&text).collect();
+        self.code_blocks.borrow_mut().clear();
+        self.hover_items = markdown::parse(&text).collect();
         self.hover_text = Some(text);
         self.hover_visible = true;
     }
@@ -156,7 +216,9 @@ impl LspOverlayState {
 
     /// Sets the completion items and their display position.
     ///
-    /// Resets the selection to index 0 and applies the current filter.
+    /// Resets the selection to index 0 and applies the current filter. Takes
+    /// plain labels or [`LspCompletionItem`]s; an item's detail and
+    /// documentation are shown beside the menu while it is selected.
     ///
     /// # Example
     ///
@@ -171,8 +233,20 @@ impl LspOverlayState {
     /// );
     /// assert_eq!(state.completion_items.len(), 2);
     /// ```
-    pub fn set_completions(&mut self, items: Vec<String>, position: Point) {
-        self.all_completions = items;
+    pub fn set_completions(
+        &mut self,
+        items: Vec<impl Into<LspCompletionItem>>,
+        position: Point,
+    ) {
+        self.code_blocks.borrow_mut().clear();
+        self.completion_entries = items.into_iter().map(Into::into).collect();
+        self.completion_docs =
+            self.completion_entries.iter().map(completion_markdown).collect();
+        self.all_completions = self
+            .completion_entries
+            .iter()
+            .map(|item| item.label.clone())
+            .collect();
         self.completion_selected = 0;
         self.completion_position = Some(position);
         self.filter_completions();
@@ -198,6 +272,10 @@ impl LspOverlayState {
         self.completion_filter.clear();
         self.completion_visible = false;
         self.completion_suppressed = false;
+        self.completion_docs.clear();
+        self.completion_doc_items.clear();
+        self.completion_entries.clear();
+        self.completion_matches.clear();
     }
 
     /// Filters `all_completions` into `completion_items` using `completion_filter`.
@@ -221,21 +299,26 @@ impl LspOverlayState {
     /// ```
     pub fn filter_completions(&mut self) {
         let filter = self.completion_filter.to_lowercase();
-        if filter.is_empty() {
-            self.completion_items = self.all_completions.clone();
-        } else {
-            self.completion_items = self
-                .all_completions
-                .iter()
-                .filter(|item| item.to_lowercase().contains(&filter))
-                .cloned()
-                .collect();
-        }
+        self.completion_matches = self
+            .all_completions
+            .iter()
+            .enumerate()
+            .filter(|(_, item)| {
+                filter.is_empty() || item.to_lowercase().contains(&filter)
+            })
+            .map(|(index, _)| index)
+            .collect();
+        self.completion_items = self
+            .completion_matches
+            .iter()
+            .map(|&index| self.all_completions[index].clone())
+            .collect();
         self.completion_visible = !self.completion_items.is_empty();
         if self.completion_selected >= self.completion_items.len() {
             self.completion_selected =
                 self.completion_items.len().saturating_sub(1);
         }
+        self.refresh_completion_doc();
     }
 
     /// Navigates through the completion list by `delta` steps, wrapping at boundaries.
@@ -264,6 +347,17 @@ impl LspOverlayState {
         let current = self.completion_selected as i32;
         self.completion_selected =
             ((current + delta).rem_euclid(len as i32)) as usize;
+        self.refresh_completion_doc();
+    }
+
+    /// Re-parses the documentation shown for the selected completion item.
+    fn refresh_completion_doc(&mut self) {
+        self.completion_doc_items = self
+            .completion_matches
+            .get(self.completion_selected)
+            .and_then(|&index| self.completion_docs.get(index)?.as_ref())
+            .map(|doc| markdown::parse(doc).collect())
+            .unwrap_or_default();
     }
 
     /// Returns the currently selected completion item, if any.
@@ -282,6 +376,123 @@ impl LspOverlayState {
         self.completion_items.get(self.completion_selected).map(String::as_str)
     }
 
+    /// Returns the currently selected completion item itself, which tells
+    /// apart items that share a label.
+    ///
+    /// Use it on confirm to insert [`LspCompletionItem::text_to_insert`],
+    /// as a snippet when [`LspCompletionItem::is_snippet`] is set.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use iced::Point;
+    /// use iced_code_editor::{LspCompletionItem, LspOverlayState};
+    ///
+    /// let overload = |detail: &str| LspCompletionItem {
+    ///     detail: Some(detail.to_string()),
+    ///     ..LspCompletionItem::from("new")
+    /// };
+    /// let mut state = LspOverlayState::new();
+    /// state.set_completions(
+    ///     vec![overload("fn new() -> Self"), overload("fn new(n: usize) -> Self")],
+    ///     Point::ORIGIN,
+    /// );
+    /// state.navigate(1);
+    ///
+    /// let detail = state
+    ///     .selected_completion_item()
+    ///     .and_then(|item| item.detail.as_deref());
+    /// assert_eq!(detail, Some("fn new(n: usize) -> Self"));
+    /// ```
+    pub fn selected_completion_item(&self) -> Option<&LspCompletionItem> {
+        self.completion_matches
+            .get(self.completion_selected)
+            .and_then(|&index| self.completion_entries.get(index))
+    }
+
+    /// Displays signature help anchored at `position`.
+    ///
+    /// `None` — what the server sends once the cursor leaves the call —
+    /// closes the popup instead.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use iced::Point;
+    /// use iced_code_editor::{LspOverlayState, LspSignatureHelp};
+    ///
+    /// let mut state = LspOverlayState::new();
+    /// let help = LspSignatureHelp {
+    ///     label: "fn add(a: i32, b: i32)".to_string(),
+    ///     documentation: Some("Adds *two* numbers.".to_string()),
+    ///     active_parameter: Some((7, 13)),
+    /// };
+    /// state.show_signature_help(Some(help), Point::ORIGIN);
+    /// assert!(state.signature_help.is_some());
+    ///
+    /// state.show_signature_help(None, Point::ORIGIN);
+    /// assert!(state.signature_help.is_none());
+    /// ```
+    pub fn show_signature_help(
+        &mut self,
+        help: Option<LspSignatureHelp>,
+        position: Point,
+    ) {
+        let Some(help) = help else {
+            self.clear_signature_help();
+            return;
+        };
+        self.code_blocks.borrow_mut().clear();
+        self.signature_items = help
+            .documentation
+            .as_deref()
+            .map(|doc| markdown::parse(doc).collect())
+            .unwrap_or_default();
+        self.signature_help = Some(help);
+        self.signature_position = Some(position);
+    }
+
+    /// Clears all signature-help state.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use iced::Point;
+    /// use iced_code_editor::{LspOverlayState, LspSignatureHelp};
+    ///
+    /// let mut state = LspOverlayState::new();
+    /// let help = LspSignatureHelp {
+    ///     label: "fn f()".to_string(),
+    ///     documentation: None,
+    ///     active_parameter: None,
+    /// };
+    /// state.show_signature_help(Some(help), Point::ORIGIN);
+    /// state.clear_signature_help();
+    /// assert!(state.signature_help.is_none());
+    /// assert!(state.signature_position.is_none());
+    /// ```
+    pub fn clear_signature_help(&mut self) {
+        self.signature_help = None;
+        self.signature_items.clear();
+        self.signature_position = None;
+    }
+
+    /// Returns the highlighted lines of a fenced code block, tokenizing it on
+    /// first use.
+    fn highlighted_code_block(
+        &self,
+        language: &str,
+        code: &str,
+    ) -> Rc<Vec<Vec<(Color, String)>>> {
+        let key = (language.to_string(), code.to_string());
+        Rc::clone(
+            self.code_blocks
+                .borrow_mut()
+                .entry(key)
+                .or_insert_with(|| Rc::new(highlight_snippet(code, language))),
+        )
+    }
+
     /// Returns the vertical scroll offset in pixels to keep the selected
     /// completion item visible when navigating with the keyboard.
     ///
@@ -366,14 +577,199 @@ pub enum LspOverlayMessage {
     CompletionNavigateDown,
     /// Confirm the currently highlighted completion item (e.g., Enter key).
     CompletionConfirm,
+    /// A link in a hover, documentation, or signature popup was clicked.
+    ///
+    /// Carries the link target exactly as written in the markdown.
+    LinkClicked(String),
+}
+
+/// Builds the markdown shown beside the completion menu for `item`.
+///
+/// The detail comes first as an untagged fenced block, so it is highlighted
+/// in the editor's language. Returns `None` when the item has neither a
+/// detail nor documentation.
+fn completion_markdown(item: &LspCompletionItem) -> Option<String> {
+    let detail =
+        item.detail.as_deref().map(|detail| format!("```\n{detail}\n```"));
+    match (detail, item.documentation.as_deref()) {
+        (Some(detail), Some(doc)) => Some(format!("{detail}\n\n{doc}")),
+        (Some(detail), None) => Some(detail),
+        (None, doc) => doc.map(String::from),
+    }
+}
+
+/// Estimates the rendered size of markdown `text`, for sizing a popup.
+///
+/// Returns the widest line in pixels and the height in editor lines. Fence
+/// markers are not drawn and are skipped; code inside a fence is measured as
+/// the monospace text it is drawn as. Prose is measured once its inline
+/// markup is removed, and heading lines are scaled to their heading size.
+/// Prose is drawn proportionally, so this slightly overestimates — a popup a
+/// little too wide beats one that clips.
+fn measure_markdown(editor: &CodeEditor, text: &str) -> (f32, f32) {
+    let mut in_fence = false;
+    let mut width = 0.0_f32;
+    let mut lines = 0.0_f32;
+    for line in text.lines() {
+        let trimmed = line.trim_start();
+        if trimmed.starts_with("```") || trimmed.starts_with("~~~") {
+            in_fence = !in_fence;
+            continue;
+        }
+        let (scale, measured) = if in_fence {
+            (1.0, editor.measure_text_width(line))
+        } else {
+            let (scale, content) = heading_scale(trimmed);
+            (scale, editor.measure_text_width(&strip_inline_markup(content)))
+        };
+        width = width.max(measured * scale);
+        lines += scale;
+    }
+    (width, lines.max(1.0))
+}
+
+/// Splits a markdown heading line into its size relative to body text and
+/// its content, using the ratios of `markdown::Settings::with_text_size`.
+///
+/// Lines that are not headings are returned unchanged at scale 1.
+fn heading_scale(line: &str) -> (f32, &str) {
+    let level = line.chars().take_while(|c| *c == '#').count();
+    let rest = &line[level..];
+    if level == 0 || level > 6 || !(rest.is_empty() || rest.starts_with(' ')) {
+        return (1.0, line);
+    }
+    let scale = match level {
+        1 => 2.0,
+        2 => 1.75,
+        3 => 1.5,
+        4 => 1.25,
+        _ => 1.0,
+    };
+    (scale, rest.trim())
+}
+
+/// Removes inline markdown markup that is not drawn as text: escapes,
+/// emphasis asterisks, code backticks, link brackets and link targets.
+fn strip_inline_markup(line: &str) -> String {
+    let mut out = String::with_capacity(line.len());
+    let mut chars = line.chars().peekable();
+    while let Some(ch) = chars.next() {
+        match ch {
+            '\\' => out.extend(chars.next()),
+            '*' | '`' | '[' => {}
+            ']' if chars.peek() == Some(&'(') => {
+                for skipped in chars.by_ref() {
+                    if skipped == ')' {
+                        break;
+                    }
+                }
+            }
+            ']' => {}
+            _ => out.push(ch),
+        }
+    }
+    out
 }
 
-/// Measures the maximum pixel width of any line in the given text.
-fn measure_hover_width(editor: &CodeEditor, text: &str) -> f32 {
-    text.lines().map(|line| editor.measure_text_width(line)).fold(0.0, f32::max)
+/// Splits a signature label around its active parameter.
+///
+/// Returns `(before, parameter, after)`; `parameter` is empty when no
+/// parameter is active or its range does not fit the label.
+fn split_signature_label(help: &LspSignatureHelp) -> (String, String, String) {
+    let chars: Vec<char> = help.label.chars().collect();
+    match help.active_parameter {
+        Some((start, end)) if start <= end && end <= chars.len() => (
+            chars[..start].iter().collect(),
+            chars[start..end].iter().collect(),
+            chars[end..].iter().collect(),
+        ),
+        _ => (help.label.clone(), String::new(), String::new()),
+    }
 }
 
-/// Renders LSP overlay elements (hover tooltip and completion menu) on top of a [`CodeEditor`].
+/// Markdown viewer for the LSP popups.
+///
+/// Fenced code blocks are drawn in the editor's font and highlighted with the
+/// editor's syntect pipeline instead of iced's default plain rendering; links
+/// produce [`LspOverlayMessage::LinkClicked`].
+struct PopupViewer<'a> {
+    /// Owner of the highlighted code block cache.
+    state: &'a LspOverlayState,
+    /// Language used for code blocks without a language tag.
+    language: &'a str,
+    /// Font code blocks are drawn in.
+    font: Font,
+}
+
+impl<'a> markdown::Viewer<'a, LspOverlayMessage> for PopupViewer<'a> {
+    fn on_link_click(url: markdown::Uri) -> LspOverlayMessage {
+        LspOverlayMessage::LinkClicked(url)
+    }
+
+    fn code_block(
+        &self,
+        settings: markdown::Settings,
+        language: Option<&'a str>,
+        code: &'a str,
+        _lines: &'a [markdown::Text],
+    ) -> Element<'a, LspOverlayMessage> {
+        // Fence info strings may carry attributes after the language, as in
+        // "rust,ignore" or "python title=x".
+        let language = language
+            .and_then(|info| info.split([',', ' ']).next())
+            .filter(|tag| !tag.is_empty())
+            .unwrap_or(self.language);
+        let highlighted = self.state.highlighted_code_block(language, code);
+
+        let lines = highlighted.iter().map(|spans| {
+            let spans: Vec<Span<'a, (), Font>> = if spans.is_empty() {
+                // Keep blank lines at full height.
+                vec![span(" ")]
+            } else {
+                spans
+                    .iter()
+                    .map(|(color, text)| span(text.clone()).color(*color))
+                    .collect()
+            };
+            rich_text(spans).font(self.font).size(settings.text_size).into()
+        });
+
+        container(
+            scrollable(
+                container(column(lines)).padding(settings.code_size / 2),
+            )
+            .direction(scrollable::Direction::Horizontal(
+                scrollable::Scrollbar::default()
+                    .width(settings.code_size / 4)
+                    .scroller_width(settings.code_size / 4),
+            )),
+        )
+        .width(Length::Fill)
+        .style(container::dark)
+        .into()
+    }
+}
+
+/// What every popup needs to render markdown: the viewer, its settings, the
+/// theme, and the mapping into the application's message type.
+struct PopupContext<'a, M> {
+    viewer: PopupViewer<'a>,
+    settings: markdown::Settings,
+    theme: &'a Theme,
+    map: Rc<dyn Fn(LspOverlayMessage) -> M + 'a>,
+}
+
+impl<'a, M: 'a> PopupContext<'a, M> {
+    /// Renders parsed markdown `items` with the popup viewer.
+    fn markdown(&self, items: &'a [markdown::Item]) -> Element<'a, M> {
+        let map = Rc::clone(&self.map);
+        markdown::view_with(items, self.settings, &self.viewer)
+            .map(move |message| map(message))
+    }
+}
+
+/// Renders LSP overlay elements (hover tooltip, completion menu and signature
+/// help) on top of a [`CodeEditor`].
 ///
 /// Returns an [`Element`] containing the overlays positioned relative to the editor viewport.
 /// The function maps [`LspOverlayMessage`] values to the application message type `M` via `f`.
@@ -381,7 +777,8 @@ fn measure_hover_width(editor: &CodeEditor, text: &str) -> f32 {
 /// # Arguments
 ///
 /// * `state` — current overlay display state
-/// * `editor` — the editor this overlay is associated with (used for viewport measurements)
+/// * `editor` — the editor this overlay is associated with (used for viewport
+///   measurements, and for the font and language of untagged code blocks)
 /// * `theme` — the active Iced theme for styling
 /// * `font_size` — font size in points, used for markdown rendering
 /// * `line_height` — line height in pixels, used for vertical positioning
@@ -431,14 +828,28 @@ pub fn view_lsp_overlay<'a, M: Clone + 'a>(
         .map(|i| f(LspOverlayMessage::CompletionSelected(i)))
         .collect();
 
+    let popup = PopupContext {
+        viewer: PopupViewer {
+            state,
+            language: editor.syntax(),
+            font: editor.font,
+        },
+        settings: markdown::Settings::with_text_size(
+            font_size,
+            markdown::Style::from_palette(theme.palette()),
+        ),
+        theme,
+        map: Rc::new(f),
+    };
+
     let mut has_overlay = false;
 
     // Build the hover tooltip layer
     let hover_layer: Element<'a, M> = build_hover_layer(
         state,
         editor,
-        theme,
-        (font_size, line_height),
+        &popup,
+        line_height,
         msg_hover_entered,
         msg_hover_exited,
         &mut has_overlay,
@@ -448,12 +859,22 @@ pub fn view_lsp_overlay<'a, M: Clone + 'a>(
     let completion_layer: Element<'a, M> = build_completion_layer(
         state,
         editor,
+        &popup,
         line_height,
         msg_completion_closed,
         msg_completion_selected,
         &mut has_overlay,
     );
 
+    // Build the signature help layer
+    let signature_layer: Element<'a, M> = build_signature_layer(
+        state,
+        editor,
+        &popup,
+        line_height,
+        &mut has_overlay,
+    );
+
     if !has_overlay {
         return container(
             Space::new().width(Length::Shrink).height(Length::Shrink),
@@ -465,21 +886,21 @@ pub fn view_lsp_overlay<'a, M: Clone + 'a>(
         .width(Length::Fill)
         .height(Length::Fill);
 
-    // Hover appears on top of completion
-    stack![base, completion_layer, hover_layer].into()
+    // Hover appears on top of signature help, which appears on top of
+    // completion
+    stack![base, completion_layer, signature_layer, hover_layer].into()
 }
 
 /// Builds the hover tooltip layer.
 fn build_hover_layer<'a, M: Clone + 'a>(
     state: &'a LspOverlayState,
     editor: &'a CodeEditor,
-    theme: &'a Theme,
-    text_metrics: (f32, f32),
+    popup: &PopupContext<'a, M>,
+    line_height: f32,
     msg_entered: M,
     msg_exited: M,
     has_overlay: &mut bool,
 ) -> Element<'a, M> {
-    let (font_size, line_height) = text_metrics;
     if !state.hover_visible {
         return empty_overlay();
     }
@@ -490,15 +911,14 @@ fn build_hover_layer<'a, M: Clone + 'a>(
         return empty_overlay();
     };
 
-    let line_count = hover.lines().count().max(1);
-    let visible_lines = line_count.min(10);
-    let hover_padding = 8.0;
-    let scroll_height = line_height * visible_lines as f32
+    let (max_line_width, line_count) = measure_markdown(editor, hover);
+    let visible_lines = line_count.min(MAX_HOVER_LINES);
+    let hover_padding = POPUP_PADDING;
+    let scroll_height = line_height * visible_lines
         + (line_height * 0.75).max(10.0)
         + hover_padding * 2.0;
 
     let viewport_width = editor.viewport_width();
-    let max_line_width = measure_hover_width(editor, hover);
     let max_width = (viewport_width - 24.0).max(0.0);
     let content_max_width = if max_width > hover_padding * 2.0 {
         max_width - hover_padding * 2.0
@@ -512,61 +932,21 @@ fn build_hover_layer<'a, M: Clone + 'a>(
     };
     let hover_width = content_width + hover_padding * 2.0;
 
-    let palette = theme.palette();
-    let markdown_settings = markdown::Settings::with_text_size(
-        font_size,
-        markdown::Style::from_palette(palette),
-    );
-
-    let entered_for_map = msg_entered.clone();
     let entered_for_enter = msg_entered.clone();
     let entered_for_move = msg_entered;
 
     let hover_content = scrollable(
-        container(
-            markdown::view(&state.hover_items, markdown_settings)
-                .map(move |_| entered_for_map.clone()),
-        )
-        .width(Length::Fixed(hover_width))
-        .padding(hover_padding),
+        container(popup.markdown(&state.hover_items))
+            .width(Length::Fixed(hover_width))
+            .padding(hover_padding),
     )
     .height(Length::Fixed(scroll_height))
     .width(Length::Fixed(hover_width))
-    .style(|theme: &Theme, _status| {
-        let palette = theme.extended_palette();
-        scrollable::Style {
-            container: container::Style {
-                background: Some(Background::Color(Color::TRANSPARENT)),
-                ..container::Style::default()
-            },
-            vertical_rail: lsp_scrollable_rail(palette),
-            horizontal_rail: lsp_scrollable_rail(palette),
-            gap: None,
-            auto_scroll: scrollable::AutoScroll {
-                background: Color::TRANSPARENT.into(),
-                border: Border::default(),
-                shadow: Shadow::default(),
-                icon: Color::TRANSPARENT,
-            },
-        }
-    });
+    .style(transparent_scrollable_style);
 
     let hover_box = container(column![hover_content])
         .width(Length::Shrink)
-        .style(|theme: &Theme| {
-            let palette = theme.extended_palette();
-            container::Style {
-                background: Some(iced::Background::Color(
-                    palette.background.weak.color,
-                )),
-                border: iced::Border {
-                    color: palette.primary.weak.color,
-                    width: 1.0,
-                    radius: 6.0.into(),
-                },
-                ..Default::default()
-            }
-        });
+        .style(popup_box_style);
 
     let hover_box: Element<'_, M> = mouse_area(hover_box)
         .on_enter(entered_for_enter)
@@ -621,6 +1001,7 @@ fn build_hover_layer<'a, M: Clone + 'a>(
 fn build_completion_layer<'a, M: Clone + 'a>(
     state: &'a LspOverlayState,
     editor: &'a CodeEditor,
+    popup: &PopupContext<'a, M>,
     line_height: f32,
     msg_closed: M,
     msg_selected: Vec<M>,
@@ -699,31 +1080,31 @@ fn build_completion_layer<'a, M: Clone + 'a>(
         .height(Length::Fixed(menu_height))
         .width(Length::Fixed(menu_width))
         .id(Id::new("completion_scrollable"))
-        .style(|theme: &Theme, _status| {
-            let palette = theme.extended_palette();
-            scrollable::Style {
-                container: container::Style {
-                    background: Some(iced::Background::Color(
-                        palette.background.weak.color,
-                    )),
-                    border: iced::Border {
-                        color: palette.primary.weak.color,
-                        width: 1.0,
-                        radius: SCROLLABLE_BORDER_RADIUS.into(),
-                    },
-                    ..Default::default()
-                },
-                vertical_rail: lsp_scrollable_rail(palette),
-                horizontal_rail: lsp_scrollable_rail(palette),
-                gap: None,
-                auto_scroll: scrollable::AutoScroll {
-                    background: Color::TRANSPARENT.into(),
-                    border: Border::default(),
-                    shadow: Shadow::default(),
-                    icon: Color::TRANSPARENT,
-                },
-            }
-        });
+        .style(bordered_scrollable_style);
+
+    // Documentation for the selected item sits beside the menu: to the
+    // right when it fits, else to the left, else it is left out.
+    let doc_panel = (!state.completion_doc_items.is_empty()).then(|| {
+        scrollable(
+            container(popup.markdown(&state.completion_doc_items))
+                .width(Length::Fixed(COMPLETION_DOC_WIDTH))
+                .padding(POPUP_PADDING),
+        )
+        .height(Length::Fixed(menu_height))
+        .width(Length::Fixed(COMPLETION_DOC_WIDTH))
+        .style(bordered_scrollable_style)
+    });
+    let doc_span = COMPLETION_DOC_WIDTH + COMPLETION_PADDING;
+    let (offset_x, menu_row) = match doc_panel {
+        Some(panel) if offset_x + menu_width + doc_span <= viewport_width => {
+            (offset_x, row![completion_box, panel])
+        }
+        Some(panel) if offset_x >= doc_span => {
+            (offset_x - doc_span, row![panel, completion_box])
+        }
+        _ => (offset_x, row![completion_box]),
+    };
+    let menu_row = menu_row.spacing(COMPLETION_PADDING);
 
     *has_overlay = true;
 
@@ -740,7 +1121,7 @@ fn build_completion_layer<'a, M: Clone + 'a>(
     let completion_content = container(
         column![
             Space::new().height(Length::Fixed(offset_y)),
-            row![Space::new().width(Length::Fixed(offset_x)), completion_box]
+            row![Space::new().width(Length::Fixed(offset_x)), menu_row]
         ]
         .spacing(0)
         .width(Length::Fill)
@@ -752,6 +1133,167 @@ fn build_completion_layer<'a, M: Clone + 'a>(
     stack![click_outside, completion_content].into()
 }
 
+/// Builds the signature help layer.
+///
+/// Shows the active signature above the call's line (below it when there is
+/// no room), with the active parameter emphasized and any documentation
+/// rendered as markdown underneath.
+fn build_signature_layer<'a, M: Clone + 'a>(
+    state: &'a LspOverlayState,
+    editor: &'a CodeEditor,
+    popup: &PopupContext<'a, M>,
+    line_height: f32,
+    has_overlay: &mut bool,
+) -> Element<'a, M> {
+    let Some(help) = state.signature_help.as_ref() else {
+        return empty_overlay();
+    };
+
+    let (doc_width, doc_lines) = help
+        .documentation
+        .as_deref()
+        .map_or((0.0, 0.0), |doc| measure_markdown(editor, doc));
+    let has_docs = !state.signature_items.is_empty();
+    let doc_height = if has_docs {
+        line_height * doc_lines.min(MAX_SIGNATURE_DOC_LINES)
+            + (line_height * 0.75).max(10.0)
+    } else {
+        0.0
+    };
+    let popup_height = line_height + doc_height + POPUP_PADDING * 2.0;
+
+    let viewport_width = editor.viewport_width();
+    let content_width = editor
+        .measure_text_width(&help.label)
+        .max(doc_width)
+        .min((viewport_width - 24.0 - POPUP_PADDING * 2.0).max(0.0));
+    let popup_width = content_width + POPUP_PADDING * 2.0;
+
+    let (before, parameter, after) = split_signature_label(help);
+    let palette = popup.theme.extended_palette();
+    let spans: Vec<Span<'a, (), Font>> = vec![
+        span(before),
+        span(parameter)
+            .font(Font { weight: iced::font::Weight::Bold, ..editor.font })
+            .color(palette.primary.strong.color)
+            .underline(true),
+        span(after),
+    ];
+    let label = rich_text(spans)
+        .font(editor.font)
+        .size(popup.settings.text_size)
+        .width(Length::Fill);
+
+    let mut content = column![label].spacing(POPUP_PADDING / 2.0);
+    if has_docs {
+        content = content.push(
+            scrollable(popup.markdown(&state.signature_items))
+                .height(Length::Fixed(doc_height))
+                .style(transparent_scrollable_style),
+        );
+    }
+    let signature_box = container(content)
+        .width(Length::Fixed(popup_width))
+        .padding(POPUP_PADDING)
+        .style(popup_box_style);
+
+    let anchor = state.signature_position.unwrap_or(Point::new(4.0, 4.0));
+    let anchor_y = (anchor.y - editor.viewport_scroll()).max(0.0);
+    let gap = 1.0;
+    let offset_y = if anchor_y >= popup_height + gap {
+        anchor_y - popup_height - gap
+    } else {
+        (anchor_y + line_height + gap).min(editor.viewport_height())
+    };
+    let offset_x = anchor.x.min(viewport_width - popup_width - 4.0).max(0.0);
+
+    *has_overlay = true;
+
+    container(
+        column![
+            Space::new().height(Length::Fixed(offset_y)),
+            row![Space::new().width(Length::Fixed(offset_x)), signature_box]
+        ]
+        .spacing(0)
+        .width(Length::Fill)
+        .height(Length::Fill),
+    )
+    .width(Length::Fill)
+    .height(Length::Fill)
+    .into()
+}
+
+/// Style of the completion menu and its documentation panel: a framed,
+/// scrollable list on the popup background.
+fn bordered_scrollable_style(
+    theme: &Theme,
+    _status: scrollable::Status,
+) -> scrollable::Style {
+    let palette = theme.extended_palette();
+    scrollable::Style {
+        container: container::Style {
+            background: Some(iced::Background::Color(
+                palette.background.weak.color,
+            )),
+            border: iced::Border {
+                color: palette.primary.weak.color,
+                width: 1.0,
+                radius: SCROLLABLE_BORDER_RADIUS.into(),
+            },
+            ..Default::default()
+        },
+        vertical_rail: lsp_scrollable_rail(palette),
+        horizontal_rail: lsp_scrollable_rail(palette),
+        gap: None,
+        auto_scroll: scrollable::AutoScroll {
+            background: Color::TRANSPARENT.into(),
+            border: Border::default(),
+            shadow: Shadow::default(),
+            icon: Color::TRANSPARENT,
+        },
+    }
+}
+
+/// Style of a popup's scrollable body: transparent, so the framing
+/// [`popup_box_style`] container shows through, with the shared rails.
+fn transparent_scrollable_style(
+    theme: &Theme,
+    _status: scrollable::Status,
+) -> scrollable::Style {
+    let palette = theme.extended_palette();
+    scrollable::Style {
+        container: container::Style {
+            background: Some(Background::Color(Color::TRANSPARENT)),
+            ..container::Style::default()
+        },
+        vertical_rail: lsp_scrollable_rail(palette),
+        horizontal_rail: lsp_scrollable_rail(palette),
+        gap: None,
+        auto_scroll: scrollable::AutoScroll {
+            background: Color::TRANSPARENT.into(),
+            border: Border::default(),
+            shadow: Shadow::default(),
+            icon: Color::TRANSPARENT,
+        },
+    }
+}
+
+/// Style of the box framing the hover and signature popups.
+fn popup_box_style(theme: &Theme) -> container::Style {
+    let palette = theme.extended_palette();
+    container::Style {
+        background: Some(iced::Background::Color(
+            palette.background.weak.color,
+        )),
+        border: iced::Border {
+            color: palette.primary.weak.color,
+            width: 1.0,
+            radius: 6.0.into(),
+        },
+        ..Default::default()
+    }
+}
+
 /// Creates the scrollable rail style used in LSP overlay panels.
 ///
 /// Both the hover tooltip and the completion menu share the same rail appearance:
@@ -903,4 +1445,165 @@ mod tests {
         state.navigate(1);
         assert_eq!(state.selected_item(), Some("second"));
     }
+
+    #[test]
+    fn test_set_completions_tracks_documentation_of_selected_item() {
+        let mut state = LspOverlayState::new();
+        state.set_completions(
+            vec![
+                LspCompletionItem {
+                    label: "push".to_string(),
+                    detail: Some("fn push(&mut self, value: T)".to_string()),
+                    documentation: Some("Appends an element.".to_string()),
+                    ..LspCompletionItem::from("push")
+                },
+                LspCompletionItem::from("pop"),
+            ],
+            Point::ORIGIN,
+        );
+        assert!(!state.completion_doc_items.is_empty());
+
+        state.navigate(1);
+        assert!(state.completion_doc_items.is_empty());
+
+        // Filtering the documented item back to the top shows its docs again.
+        state.completion_filter = "pu".to_string();
+        state.filter_completions();
+        assert_eq!(state.selected_item(), Some("push"));
+        assert!(!state.completion_doc_items.is_empty());
+
+        state.clear_completions();
+        assert!(state.completion_docs.is_empty());
+        assert!(state.completion_doc_items.is_empty());
+    }
+
+    #[test]
+    fn test_items_sharing_a_label_keep_their_own_documentation() {
+        let overload = |documentation: &str| LspCompletionItem {
+            documentation: Some(documentation.to_string()),
+            ..LspCompletionItem::from("new")
+        };
+        let mut state = LspOverlayState::new();
+        state.set_completions(
+            vec![
+                overload("Creates an empty list."),
+                LspCompletionItem::from("next"),
+                overload("Creates a list with room for `n` items."),
+            ],
+            Point::ORIGIN,
+        );
+        let shown = |state: &LspOverlayState| {
+            state
+                .completion_matches
+                .get(state.completion_selected)
+                .and_then(|&index| state.completion_docs[index].clone())
+        };
+        assert_eq!(shown(&state).as_deref(), Some("Creates an empty list."));
+
+        state.completion_filter = "new".to_string();
+        state.filter_completions();
+        state.navigate(1);
+        assert_eq!(
+            shown(&state).as_deref(),
+            Some("Creates a list with room for `n` items.")
+        );
+        assert_eq!(
+            state
+                .selected_completion_item()
+                .and_then(|item| item.documentation.as_deref()),
+            Some("Creates a list with room for `n` items.")
+        );
+    }
+
+    #[test]
+    fn test_completion_markdown_fences_detail_without_language() {
+        let item = LspCompletionItem {
+            label: "len".to_string(),
+            detail: Some("fn len(&self) -> usize".to_string()),
+            documentation: Some("Returns the *length*.".to_string()),
+            ..LspCompletionItem::from("len")
+        };
+        assert_eq!(
+            completion_markdown(&item).as_deref(),
+            Some("```\nfn len(&self) -> usize\n```\n\nReturns the *length*.")
+        );
+        assert_eq!(completion_markdown(&LspCompletionItem::from("x")), None);
+    }
+
+    #[test]
+    fn test_show_signature_help_none_closes_popup() {
+        let mut state = LspOverlayState::new();
+        let help = LspSignatureHelp {
+            label: "fn f(a: u8)".to_string(),
+            documentation: Some("Docs".to_string()),
+            active_parameter: Some((5, 10)),
+        };
+        state.show_signature_help(Some(help), Point::new(3.0, 4.0));
+        assert_eq!(state.signature_position, Some(Point::new(3.0, 4.0)));
+        assert!(!state.signature_items.is_empty());
+
+        state.show_signature_help(None, Point::ORIGIN);
+        assert!(state.signature_help.is_none());
+        assert!(state.signature_items.is_empty());
+        assert!(state.signature_position.is_none());
+    }
+
+    #[test]
+    fn test_split_signature_label_around_active_parameter() {
+        let mut help = LspSignatureHelp {
+            label: "fn é(a: u8, b: u8)".to_string(),
+            documentation: None,
+            active_parameter: Some((12, 17)),
+        };
+        assert_eq!(
+            split_signature_label(&help),
+            ("fn é(a: u8, ".to_string(), "b: u8".to_string(), ")".to_string())
+        );
+
+        // An out-of-range parameter leaves the label whole.
+        help.active_parameter = Some((12, 99));
+        assert_eq!(
+            split_signature_label(&help),
+            (help.label.clone(), String::new(), String::new())
+        );
+    }
+
+    #[test]
+    fn test_strip_inline_markup_keeps_only_rendered_text() {
+        assert_eq!(
+            strip_inline_markup("**bold** `code` [link](https://x.y) a\\_b"),
+            "bold code link a_b"
+        );
+    }
+
+    #[test]
+    fn test_heading_scale_matches_markdown_settings() {
+        assert_eq!(heading_scale("# Title"), (2.0, "Title"));
+        assert_eq!(heading_scale("### Sub"), (1.5, "Sub"));
+        assert_eq!(heading_scale("#hashtag"), (1.0, "#hashtag"));
+        assert_eq!(heading_scale("plain"), (1.0, "plain"));
+    }
+
+    #[test]
+    fn test_measure_markdown_skips_fences_and_markup() {
+        let editor = CodeEditor::new("", "rs");
+        let (width, lines) =
+            measure_markdown(&editor, "```rust\nfn main()\n```\n**ab**");
+        assert!((lines - 2.0).abs() < f32::EPSILON);
+        // The widest drawn line is the code, not the fence or the markup.
+        assert!((width - editor.measure_text_width("fn main()")).abs() < 0.01);
+    }
+
+    #[test]
+    fn test_highlighted_code_block_is_tokenized_once() {
+        let state = LspOverlayState::new();
+        let first = state.highlighted_code_block("rs", "let x = 1;");
+        let second = state.highlighted_code_block("rs", "let x = 1;");
+        assert!(Rc::ptr_eq(&first, &second));
+
+        let mut state = state;
+        state.show_hover("new".to_string());
+        let third = state.highlighted_code_block("rs", "let x = 1;");
+        assert!(!Rc::ptr_eq(&first, &third));
+    }
 }
@@ -8,6 +8,10 @@
 use std::collections::HashMap;
 use std::time::{Duration, Instant};
 
+use iced::futures::channel::oneshot;
+
+use crate::canvas_editor::lsp::{LspCompletionItem, LspLocation};
+
 /// Enumeration of LSP request types that we track for response handling.
 pub(super) enum LspRequestKind {
     /// Hover request — shows type information and documentation
@@ -16,12 +20,41 @@ pub(super) enum LspRequestKind {
     Completion,
     /// Definition request — go to definition
     Definition,
+    /// Signature help request — parameter hints for the call being typed
+    SignatureHelp,
+    /// Document highlight request — occurrences of the symbol under the cursor
+    DocumentHighlight,
+    /// Selection range request — enclosing ranges for Expand Selection
+    SelectionRange,
+    /// Linked editing range request — ranges renamed together, like tag pairs
+    LinkedEditingRange,
+    /// Document link request — URLs and paths the server found in a document
+    DocumentLink,
+    /// Document color request — color literals the server found in a document
+    DocumentColor,
+    /// Color presentation request — ways of writing a picked color
+    ColorPresentation,
+}
+
+/// Where the reply to a request sent through an
+/// [`LspRequest`](crate::canvas_editor::lsp::LspRequest) handle goes instead
+/// of the event channel.
+pub(super) enum LspReplySender {
+    /// Reply to a hover request.
+    Hover(oneshot::Sender<String>),
+    /// Reply to a completion request.
+    Completion(oneshot::Sender<Vec<LspCompletionItem>>),
+    /// Reply to a definition request.
+    Definition(oneshot::Sender<LspLocation>),
 }
 
 /// A request awaiting a server response, tracked with the time it was sent.
 pub(super) struct PendingRequest {
     /// Which kind of request this is, used to route the eventual response.
     pub(super) kind: LspRequestKind,
+    /// The handle waiting for the reply, if the request was sent through
+    /// one; otherwise the reply goes out as an `LspEvent`.
+    pub(super) reply: Option<LspReplySender>,
     /// When the request was sent, used by [`evict_expired_requests`] to
     /// drop it if the server never responds.
     pub(super) requested_at: Instant,
@@ -58,7 +91,7 @@ mod tests {
 
     /// Builds a [`PendingRequest`] of `kind`, sent "now" for test purposes.
     fn pending_request(kind: LspRequestKind) -> PendingRequest {
-        PendingRequest { kind, requested_at: Instant::now() }
+        PendingRequest { kind, reply: None, requested_at: Instant::now() }
     }
 
     #[test]
@@ -68,6 +101,7 @@ mod tests {
             1u64,
             PendingRequest {
                 kind: LspRequestKind::Hover,
+                reply: None,
                 requested_at: Instant::now()
                     - PENDING_REQUEST_TIMEOUT
                     - Duration::from_secs(1),
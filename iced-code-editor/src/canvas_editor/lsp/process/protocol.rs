@@ -1,23 +1,27 @@
 //! Wire format for the LSP stdio transport.
 //!
-//! Everything between the raw byte stream and the editor-facing
-//! [`LspEvent`](super::LspEvent): `Content-Length` framing, the bounded reads
-//! that keep a malformed or hostile server from exhausting memory, dispatch of
-//! incoming JSON-RPC messages, and parsing of the three response shapes the
-//! editor asks for.
+//! Everything between a framed message body and the editor-facing
+//! [`LspEvent`](super::LspEvent): dispatch of incoming JSON-RPC messages, and
+//! parsing of the response shapes the editor asks for. The `Content-Length`
+//! framing itself is shared with the debug adapter client.
 //!
 //! These are free functions with no client state: the reader thread owns the
 //! stream and calls into here, so nothing in this module needs to know the
 //! process exists.
 
 use std::collections::HashMap;
-use std::io::{BufRead, Read};
 use std::sync::{Arc, Mutex, mpsc};
 
 use serde_json::json;
 
-use super::pending::{LspRequestKind, PendingRequest};
-use super::{LspEvent, LspPosition, LspRange};
+use super::pending::{LspReplySender, LspRequestKind, PendingRequest};
+use super::{
+    LspColorPresentation, LspCompletionItem, LspDocumentColor,
+    LspDocumentHighlight, LspDocumentLink, LspEvent, LspPosition, LspRange,
+    LspSignatureHelp, LspTextChange,
+};
+use crate::canvas_editor::framing::frame_message;
+use crate::canvas_editor::lsp::{LspDocumentHighlightKind, LspLocation};
 
 /// JSON-RPC method name for server-push progress notifications.
 const METHOD_PROGRESS: &str = "$/progress";
@@ -25,201 +29,6 @@ const METHOD_PROGRESS: &str = "$/progress";
 const METHOD_WORK_DONE_PROGRESS_CREATE: &str = "window/workDoneProgress/create";
 /// Progress `kind` value that signals the end of a work-done sequence.
 const PROGRESS_KIND_END: &str = "end";
-/// Maximum accepted `Content-Length` for a single LSP frame (64 MiB).
-///
-/// The body length is attacker-controlled input: it comes from the language
-/// server process, whose binary is resolved through `PATH` or an environment
-/// variable. Without a cap, a malformed or hostile server announcing a
-/// multi-gigabyte frame would force an unbounded allocation and take the
-/// editor down. Real LSP payloads stay orders of magnitude below this.
-const MAX_MESSAGE_BYTES: usize = 64 * 1024 * 1024;
-/// Maximum accepted length for a single header line (8 KiB).
-///
-/// `BufRead::read_line` grows its buffer until it finds a newline, so a server
-/// that opens a header line and never terminates it would force an unbounded
-/// allocation — the same hazard [`MAX_MESSAGE_BYTES`] guards on the body, on
-/// the path that reaches it. Real LSP headers are a few dozen bytes.
-const MAX_HEADER_LINE_BYTES: u64 = 8 * 1024;
-/// Maximum accepted number of header lines before the blank separator.
-///
-/// Each line is individually bounded by [`MAX_HEADER_LINE_BYTES`], so an
-/// endless stream of well-formed headers is not a memory hazard — but without
-/// a cap the reader would spin on it forever and never yield a message. Real
-/// LSP frames send one or two headers.
-const MAX_HEADER_LINES: usize = 64;
-/// Maximum bytes kept from a single language-server stderr line (8 KiB).
-///
-/// `BufRead::lines` grows its buffer until it finds a newline, so one
-/// unterminated line from a chatty or hostile server would grow without bound
-/// — the same hazard [`MAX_HEADER_LINE_BYTES`] guards on the protocol stream.
-/// Diagnostics are free-form text rather than framed protocol, so an oversized
-/// line is truncated and reading resumes at the next line, instead of tearing
-/// down the stream the way an unparseable frame must.
-const MAX_LOG_LINE_BYTES: u64 = 8 * 1024;
-/// Maximum bytes discarded while resynchronising after an oversized log line.
-///
-/// Bounds the *time* spent skipping, where [`MAX_LOG_LINE_BYTES`] bounds the
-/// memory kept: a line that never ends has no newline to find, so an
-/// unbudgeted skip would never return. See [`skip_to_newline`].
-const MAX_LOG_SKIP_BYTES: u64 = 1024 * 1024;
-
-/// Reads one `Content-Length`-framed message body from `reader`.
-///
-/// Returns `None` when the stream ends, when the body cannot be read in full,
-/// when the announced frame exceeds [`MAX_MESSAGE_BYTES`], or when the header
-/// block breaches [`MAX_HEADER_LINE_BYTES`] or [`MAX_HEADER_LINES`]. None of
-/// these can be skipped reliably — the framing is exactly what is not
-/// trustworthy — so the caller must stop reading the stream rather than try to
-/// resynchronise mid-message.
-///
-/// Header lines other than `Content-Length` are ignored. A header block
-/// carrying no `Content-Length` yields an empty body, which the caller then
-/// discards as invalid JSON.
-pub(super) fn read_message(reader: &mut impl BufRead) -> Option<Vec<u8>> {
-    let mut content_length: Option<usize> = None;
-    let mut line = String::new();
-
-    for _ in 0..MAX_HEADER_LINES {
-        line.clear();
-        // Read through a `take` rather than calling `read_line` directly:
-        // `read_line` grows `line` until it finds a newline, so a server that
-        // never sends one would force an unbounded allocation here — the same
-        // hazard `MAX_MESSAGE_BYTES` guards on the body, one step earlier.
-        // Called through UFCS so `Self` is `&mut R`: written as a method call,
-        // the probe derefs to `R` and `take` moves the reader out from under
-        // the borrow.
-        let read = Read::take(&mut *reader, MAX_HEADER_LINE_BYTES)
-            .read_line(&mut line)
-            .ok()?;
-
-        // A zero-length read means end of stream, not an empty header line.
-        if read == 0 {
-            return None;
-        }
-
-        // Hitting the cap with no newline means the line was oversized. The
-        // remainder is still queued in the stream with no way to tell where
-        // the next frame begins, so give up instead of resynchronising.
-        if !line.ends_with('\n') {
-            return None;
-        }
-
-        let trimmed = line.trim();
-        if trimmed.is_empty() {
-            return read_body(reader, content_length);
-        }
-        if let Some(value) = trimmed.strip_prefix("Content-Length:")
-            && let Ok(len) = value.trim().parse::<usize>()
-        {
-            content_length = Some(len);
-        }
-    }
-
-    // The header block never terminated. Each line is individually bounded, so
-    // this is not a memory hazard, but without a cap the loop would spin on a
-    // server emitting headers forever and never yield a message.
-    None
-}
-
-/// Reads one line of language-server stderr, truncated to
-/// [`MAX_LOG_LINE_BYTES`].
-///
-/// Returns `None` at end of stream. A line longer than the cap is cut at the
-/// cap, marked with a trailing `…`, and the remainder discarded (without being
-/// buffered) up to the next newline, so the following lines still start on a
-/// real boundary.
-///
-/// Invalid UTF-8 is replaced rather than treated as an error: server
-/// diagnostics are not worth tearing the log stream down over.
-pub(super) fn read_log_line(reader: &mut impl BufRead) -> Option<String> {
-    let mut buf = Vec::new();
-    // Bounded like the protocol header lines, and for the same reason; see
-    // `read_message`.
-    let read = Read::take(&mut *reader, MAX_LOG_LINE_BYTES)
-        .read_until(b'\n', &mut buf)
-        .ok()?;
-    if read == 0 {
-        return None;
-    }
-
-    let truncated = !buf.ends_with(b"\n");
-    let mut line = String::from_utf8_lossy(&buf).into_owned();
-    if truncated {
-        skip_to_newline(reader);
-        line.push('…');
-    }
-    Some(line)
-}
-
-/// Advances `reader` past the next newline, discarding at most
-/// [`MAX_LOG_SKIP_BYTES`] on the way.
-///
-/// Used to resynchronise after an oversized log line. Works through
-/// `fill_buf`/`consume` rather than `read_until` so the skipped bytes are
-/// never buffered — otherwise recovering from an unbounded line would itself
-/// need unbounded memory.
-///
-/// The byte budget matters as much as the buffering: a server emitting a line
-/// that never ends has no newline to find, and an unbudgeted skip would spin
-/// in this loop for as long as it kept talking. Giving up instead lets the
-/// next [`read_log_line`] surface the following chunk as its own truncated
-/// line, so the reader always makes forward progress.
-fn skip_to_newline(reader: &mut impl BufRead) {
-    let mut skipped: u64 = 0;
-    while skipped < MAX_LOG_SKIP_BYTES {
-        let Ok(available) = reader.fill_buf() else { return };
-        if available.is_empty() {
-            return;
-        }
-        if let Some(index) = available.iter().position(|byte| *byte == b'\n') {
-            reader.consume(index + 1);
-            return;
-        }
-        let len = available.len();
-        reader.consume(len);
-        skipped = skipped.saturating_add(len as u64);
-    }
-}
-
-/// Reads the body announced by a header block's `Content-Length`.
-///
-/// A missing `Content-Length` is treated as a zero-length body; see
-/// [`read_message`].
-fn read_body(
-    reader: &mut impl BufRead,
-    content_length: Option<usize>,
-) -> Option<Vec<u8>> {
-    let len = content_length.unwrap_or(0);
-    if len > MAX_MESSAGE_BYTES {
-        return None;
-    }
-
-    let mut buf = vec![0u8; len];
-    if reader.read_exact(&mut buf).is_err() {
-        return None;
-    }
-    Some(buf)
-}
-
-/// Frames a JSON-RPC value as `Content-Length: N\r\n\r\n<body>` bytes, ready
-/// to be written to an LSP server's stdin.
-///
-/// Returns `None` if `value` cannot be serialized to JSON.
-///
-/// # Examples
-///
-/// ```text
-/// let framed = frame_message(&serde_json::json!({"jsonrpc": "2.0"})).unwrap();
-/// assert!(framed.starts_with(b"Content-Length: "));
-/// ```
-pub(super) fn frame_message(value: &serde_json::Value) -> Option<Vec<u8>> {
-    let data = serde_json::to_vec(value).ok()?;
-    let mut framed =
-        format!("Content-Length: {}\r\n\r\n", data.len()).into_bytes();
-    framed.extend_from_slice(&data);
-    Some(framed)
-}
-
 /// Handles an LSP server request that requires a JSON-RPC response.
 ///
 /// Currently handles `window/workDoneProgress/create` by replying with a null
@@ -244,21 +53,62 @@ pub(super) fn handle_server_request(
 /// Dispatches a server response to the appropriate pending request handler.
 ///
 /// Looks up the request kind by `id`, parses the result, and emits a
-/// [`LspEvent::Hover`], [`LspEvent::Completion`], or [`LspEvent::Definition`].
+/// [`LspEvent::Hover`], [`LspEvent::Completion`], [`LspEvent::SignatureHelp`],
+/// [`LspEvent::DocumentHighlight`], [`LspEvent::SelectionRange`],
+/// [`LspEvent::LinkedEditingRange`], or [`LspEvent::Definition`] — or, for a
+/// request sent through an
+/// [`LspRequest`](crate::canvas_editor::lsp::LspRequest) handle, hands the
+/// parsed reply to that handle instead.
 pub(super) fn handle_client_response(
     id: u64,
     value: &serde_json::Value,
     pending: &Arc<Mutex<HashMap<u64, PendingRequest>>>,
     events: &mpsc::Sender<LspEvent>,
 ) {
-    let kind = {
+    let entry = {
         let mut map = pending.lock().unwrap_or_else(|e| e.into_inner());
-        map.remove(&id).map(|entry| entry.kind)
+        map.remove(&id)
     };
 
-    let Some(kind) = kind else { return };
+    let Some(PendingRequest { kind, reply, .. }) = entry else { return };
     let result = value.get("result").unwrap_or(&serde_json::Value::Null);
 
+    // A request sent through a handle is answered through it, and only
+    // through it; a handle dropped unanswered resolves to `None`.
+    match (kind, reply) {
+        (LspRequestKind::Hover, Some(LspReplySender::Hover(reply))) => {
+            if let Some(text) = parse_hover_text(result) {
+                let _ = reply.send(text);
+            }
+        }
+        (
+            LspRequestKind::Completion,
+            Some(LspReplySender::Completion(reply)),
+        ) => {
+            if value.get("error").is_none() {
+                let _ = reply.send(parse_completion_items(result));
+            }
+        }
+        (
+            LspRequestKind::Definition,
+            Some(LspReplySender::Definition(reply)),
+        ) => {
+            if let Some((uri, range)) = parse_definition_location(result) {
+                let _ = reply.send(LspLocation { uri, range });
+            }
+        }
+        (kind, _) => send_response_event(&kind, value, result, events),
+    }
+}
+
+/// Emits the [`LspEvent`] answering a request of `kind` that was not sent
+/// through a handle.
+fn send_response_event(
+    kind: &LspRequestKind,
+    value: &serde_json::Value,
+    result: &serde_json::Value,
+    events: &mpsc::Sender<LspEvent>,
+) {
     match kind {
         LspRequestKind::Hover => {
             let text = parse_hover_text(result).unwrap_or_default();
@@ -270,6 +120,54 @@ pub(super) fn handle_client_response(
                 let _ = events.send(LspEvent::Completion { items });
             }
         }
+        LspRequestKind::SignatureHelp => {
+            let help = parse_signature_help(result);
+            let _ = events.send(LspEvent::SignatureHelp { help });
+        }
+        LspRequestKind::DocumentHighlight => {
+            // An error reply (e.g. a server without the capability) sends
+            // nothing, leaving the editor's lexical highlights in place; a
+            // null result means "no symbol here" and clears them.
+            if value.get("error").is_none() {
+                let highlights = parse_document_highlights(result);
+                let _ = events.send(LspEvent::DocumentHighlight { highlights });
+            }
+        }
+        LspRequestKind::SelectionRange => {
+            // Always answer, even on error: the editor holds back the
+            // expansion until it hears back, and an empty list tells it to
+            // use its own ranges instead.
+            let ranges = parse_selection_ranges(result);
+            let _ = events.send(LspEvent::SelectionRange { ranges });
+        }
+        LspRequestKind::LinkedEditingRange => {
+            if value.get("error").is_none() {
+                let ranges = parse_linked_editing_ranges(result);
+                let _ = events.send(LspEvent::LinkedEditingRange { ranges });
+            }
+        }
+        LspRequestKind::DocumentLink => {
+            // On error the editor keeps its lexical links; there is nothing
+            // to replace them with.
+            if value.get("error").is_none() {
+                let links = parse_document_links(result);
+                let _ = events.send(LspEvent::DocumentLinks { links });
+            }
+        }
+        LspRequestKind::DocumentColor => {
+            // On error the editor keeps its lexical swatches.
+            if value.get("error").is_none() {
+                let colors = parse_document_colors(result);
+                let _ = events.send(LspEvent::DocumentColors { colors });
+            }
+        }
+        LspRequestKind::ColorPresentation => {
+            if value.get("error").is_none() {
+                let presentations = parse_color_presentations(result);
+                let _ =
+                    events.send(LspEvent::ColorPresentations { presentations });
+            }
+        }
         LspRequestKind::Definition => {
             if let Some((uri, range)) = parse_definition_location(result) {
                 let _ = events.send(LspEvent::Definition { uri, range });
@@ -327,7 +225,7 @@ pub(super) fn handle_server_notification(
 // LSP Response Parsing Functions
 // =============================================================================
 
-/// Parses hover text from an LSP hover response.
+/// Parses hover text from an LSP hover response, as Markdown.
 fn parse_hover_text(result: &serde_json::Value) -> Option<String> {
     let contents = result.get("contents")?;
     hover_text_from_contents(contents)
@@ -335,26 +233,74 @@ fn parse_hover_text(result: &serde_json::Value) -> Option<String> {
 
 /// Recursively extracts hover text from various content formats.
 ///
-/// Handles strings, arrays, and objects with a `"value"` field.
+/// Handles the three shapes `Hover.contents` may take — a `MarkedString`, a
+/// `MarkedString[]`, or a `MarkupContent` — and normalizes each to Markdown,
+/// so the overlay has a single format to render. Array entries become
+/// separate paragraphs.
 fn hover_text_from_contents(value: &serde_json::Value) -> Option<String> {
     match value {
-        serde_json::Value::String(text) => Some(text.clone()),
         serde_json::Value::Array(items) => {
             let parts: Vec<String> =
                 items.iter().filter_map(hover_text_from_contents).collect();
-            if parts.is_empty() { None } else { Some(parts.join("\n")) }
-        }
-        serde_json::Value::Object(map) => {
-            map.get("value").and_then(|v| v.as_str()).map(String::from)
+            if parts.is_empty() { None } else { Some(parts.join("\n\n")) }
         }
-        _ => None,
+        _ => markup_to_markdown(value),
     }
 }
 
+/// Converts a `MarkupContent`, `MarkedString`, or plain string to Markdown.
+///
+/// - A plain string is already Markdown (a deprecated `MarkedString`).
+/// - `{ language, value }` is a `MarkedString` code snippet and becomes a
+///   fenced block tagged with its language, so it is highlighted like the
+///   editor highlights that language.
+/// - `{ kind: "plaintext", value }` is escaped, so characters such as `*` or
+///   `_` in a doc comment are shown verbatim rather than read as emphasis.
+/// - `{ kind: "markdown", value }` is passed through.
+fn markup_to_markdown(value: &serde_json::Value) -> Option<String> {
+    if let Some(text) = value.as_str() {
+        return Some(text.to_string());
+    }
+    let text = value.get("value")?.as_str()?;
+    if let Some(language) = value.get("language").and_then(|l| l.as_str()) {
+        return Some(format!("```{language}\n{text}\n```"));
+    }
+    match value.get("kind").and_then(|k| k.as_str()) {
+        Some("plaintext") => Some(escape_markdown(text)),
+        _ => Some(text.to_string()),
+    }
+}
+
+/// Escapes `text` so Markdown renders it verbatim, line breaks included.
+fn escape_markdown(text: &str) -> String {
+    const SPECIAL: &str = "\\`*_{}[]()<>#+-.!|~";
+    text.lines()
+        .map(|line| {
+            let mut escaped = String::with_capacity(line.len());
+            for ch in line.chars() {
+                if SPECIAL.contains(ch) {
+                    escaped.push('\\');
+                }
+                escaped.push(ch);
+            }
+            escaped
+        })
+        .collect::<Vec<_>>()
+        // Two trailing spaces make a hard line break, so plain text keeps
+        // its line structure instead of reflowing into one paragraph.
+        .join("  \n")
+}
+
 /// Parses completion items from an LSP completion response.
 ///
 /// Handles both array responses and object responses with an `"items"` field.
-fn parse_completion_items(result: &serde_json::Value) -> Vec<String> {
+/// Items without a label are skipped; `documentation` is normalized to
+/// Markdown with [`markup_to_markdown`]. The text to insert comes from the
+/// item's text edit (plain or insert/replace), falling back to `insertText`;
+/// the edit's range is not kept.
+fn parse_completion_items(
+    result: &serde_json::Value,
+) -> Vec<LspCompletionItem> {
     let mut items = Vec::new();
 
     if let Some(array) = result.as_array() {
@@ -365,11 +311,116 @@ fn parse_completion_items(result: &serde_json::Value) -> Vec<String> {
 
     items
         .iter()
-        .filter_map(|item| item.get("label").and_then(|v| v.as_str()))
-        .map(String::from)
+        .filter_map(|item| {
+            let label = item.get("label")?.as_str()?.to_string();
+            let detail = item
+                .get("detail")
+                .and_then(|d| d.as_str())
+                .filter(|d| !d.trim().is_empty())
+                .map(String::from);
+            let documentation = item
+                .get("documentation")
+                .and_then(markup_to_markdown)
+                .filter(|d| !d.trim().is_empty());
+            let insert_text = item
+                .pointer("/textEdit/newText")
+                .or_else(|| item.get("insertText"))
+                .and_then(|text| text.as_str())
+                .map(String::from);
+            let is_snippet = insert_text.is_some()
+                && item.get("insertTextFormat").and_then(|f| f.as_u64())
+                    == Some(2);
+            Some(LspCompletionItem {
+                label,
+                detail,
+                documentation,
+                insert_text,
+                is_snippet,
+            })
+        })
         .collect()
 }
 
+/// Parses the active signature from an LSP signature-help response.
+///
+/// Returns `None` for a `null` result or one with no signatures. The active
+/// parameter may be given per signature or for the whole response (the
+/// per-signature value wins), and its label either as a substring of the
+/// signature label or as a UTF-16 `[start, end)` offset pair; both are
+/// resolved to a char range within the label.
+fn parse_signature_help(
+    result: &serde_json::Value,
+) -> Option<LspSignatureHelp> {
+    let signatures = result.get("signatures")?.as_array()?;
+    let active_signature = result
+        .get("activeSignature")
+        .and_then(|v| v.as_u64())
+        .and_then(|index| usize::try_from(index).ok())
+        .filter(|index| *index < signatures.len())
+        .unwrap_or(0);
+    let signature = signatures.get(active_signature)?;
+    let label = signature.get("label")?.as_str()?.to_string();
+
+    let active_index = signature
+        .get("activeParameter")
+        .or_else(|| result.get("activeParameter"))
+        .and_then(|v| v.as_u64())
+        .and_then(|index| usize::try_from(index).ok());
+    let parameter = active_index
+        .and_then(|index| signature.get("parameters")?.as_array()?.get(index));
+    let active_parameter =
+        parameter.and_then(|p| parameter_char_range(&label, p.get("label")?));
+
+    let parameter_doc = parameter
+        .and_then(|p| p.get("documentation"))
+        .and_then(markup_to_markdown);
+    let signature_doc =
+        signature.get("documentation").and_then(markup_to_markdown);
+    let documentation = match (parameter_doc, signature_doc) {
+        (Some(parameter), Some(signature)) => {
+            Some(format!("{parameter}\n\n{signature}"))
+        }
+        (parameter, signature) => parameter.or(signature),
+    }
+    .filter(|doc| !doc.trim().is_empty());
+
+    Some(LspSignatureHelp { label, documentation, active_parameter })
+}
+
+/// Resolves a `ParameterInformation.label` to a char range within `label`.
+fn parameter_char_range(
+    label: &str,
+    parameter: &serde_json::Value,
+) -> Option<(usize, usize)> {
+    if let Some(text) = parameter.as_str() {
+        let byte_start = label.find(text)?;
+        let start = label[..byte_start].chars().count();
+        return Some((start, start + text.chars().count()));
+    }
+    let offsets = parameter.as_array()?;
+    let start = utf16_to_char_offset(label, offsets.first()?.as_u64()?)?;
+    let end = utf16_to_char_offset(label, offsets.get(1)?.as_u64()?)?;
+    (start <= end).then_some((start, end))
+}
+
+/// Converts a UTF-16 code unit offset into `text` to a char offset.
+///
+/// Returns `None` when the offset lies past the end of `text` or inside a
+/// surrogate pair.
+fn utf16_to_char_offset(text: &str, utf16_offset: u64) -> Option<usize> {
+    let mut units = 0u64;
+    for (index, ch) in text.chars().enumerate() {
+        if units == utf16_offset {
+            return Some(index);
+        }
+        if units > utf16_offset {
+            return None;
+        }
+        units += ch.len_utf16() as u64;
+    }
+    (units == utf16_offset).then(|| text.chars().count())
+}
+
 /// Parses a JSON-RPC `Range` object (`{start: {line, character}, end: {...}}`)
 /// into an [`LspRange`].
 ///
@@ -390,6 +441,123 @@ fn extract_range(range_val: &serde_json::Value) -> Option<LspRange> {
     })
 }
 
+/// Parses a `DocumentHighlight[] | null` response.
+///
+/// Entries without a valid range are skipped; a missing or unknown `kind`
+/// defaults to [`LspDocumentHighlightKind::Text`], as the specification says.
+fn parse_document_highlights(
+    result: &serde_json::Value,
+) -> Vec<LspDocumentHighlight> {
+    let Some(array) = result.as_array() else { return Vec::new() };
+    array
+        .iter()
+        .filter_map(|entry| {
+            let range = extract_range(entry.get("range")?)?;
+            let kind = match entry.get("kind").and_then(|k| k.as_u64()) {
+                Some(2) => LspDocumentHighlightKind::Read,
+                Some(3) => LspDocumentHighlightKind::Write,
+                _ => LspDocumentHighlightKind::Text,
+            };
+            Some(LspDocumentHighlight { range, kind })
+        })
+        .collect()
+}
+
+/// Parses a `SelectionRange[] | null` response into the chain of the first
+/// (and only requested) position, innermost range first.
+///
+/// The chain is followed through `parent` links and stops at the first
+/// entry without a valid range.
+fn parse_selection_ranges(result: &serde_json::Value) -> Vec<LspRange> {
+    let mut ranges = Vec::new();
+    let mut next = result.as_array().and_then(|array| array.first());
+    while let Some(entry) = next {
+        let Some(range) = entry.get("range").and_then(extract_range) else {
+            break;
+        };
+        ranges.push(range);
+        next = entry.get("parent");
+    }
+    ranges
+}
+
+/// Parses a `LinkedEditingRanges | null` response.
+///
+/// The optional `wordPattern` is ignored; entries without a valid range are
+/// skipped.
+fn parse_linked_editing_ranges(result: &serde_json::Value) -> Vec<LspRange> {
+    let Some(array) = result.get("ranges").and_then(|r| r.as_array()) else {
+        return Vec::new();
+    };
+    array.iter().filter_map(extract_range).collect()
+}
+
+/// Parses a `DocumentLink[] | null` response.
+///
+/// Entries without a `target` would need a `documentLink/resolve` round trip
+/// before they lead anywhere, and are skipped along with those without a
+/// valid range.
+fn parse_document_links(result: &serde_json::Value) -> Vec<LspDocumentLink> {
+    let Some(array) = result.as_array() else { return Vec::new() };
+    array
+        .iter()
+        .filter_map(|entry| {
+            let range = extract_range(entry.get("range")?)?;
+            let target = entry.get("target")?.as_str()?.to_string();
+            Some(LspDocumentLink { range, target })
+        })
+        .collect()
+}
+
+/// Parses a `ColorInformation[]` response.
+///
+/// Entries without a valid range or with a missing channel are skipped.
+fn parse_document_colors(result: &serde_json::Value) -> Vec<LspDocumentColor> {
+    let Some(array) = result.as_array() else { return Vec::new() };
+    array
+        .iter()
+        .filter_map(|entry| {
+            let range = extract_range(entry.get("range")?)?;
+            let color = entry.get("color")?;
+            let channel = |name: &str| {
+                color.get(name)?.as_f64().map(|value| value as f32)
+            };
+            Some(LspDocumentColor {
+                range,
+                color: iced::Color::from_rgba(
+                    channel("red")?,
+                    channel("green")?,
+                    channel("blue")?,
+                    channel("alpha")?,
+                ),
+            })
+        })
+        .collect()
+}
+
+/// Parses a `ColorPresentation[]` response.
+///
+/// `additionalTextEdits` are ignored: the picker only rewrites the color
+/// itself.
+fn parse_color_presentations(
+    result: &serde_json::Value,
+) -> Vec<LspColorPresentation> {
+    let Some(array) = result.as_array() else { return Vec::new() };
+    array
+        .iter()
+        .filter_map(|entry| {
+            let label = entry.get("label")?.as_str()?.to_string();
+            let text_edit = entry.get("textEdit").and_then(|edit| {
+                Some(LspTextChange {
+                    range: extract_range(edit.get("range")?)?,
+                    text: edit.get("newText")?.as_str()?.to_string(),
+                })
+            });
+            Some(LspColorPresentation { label, text_edit })
+        })
+        .collect()
+}
+
 /// Extracts `(uri, range)` from an LSP `Location` object.
 fn extract_location(loc: &serde_json::Value) -> Option<(String, LspRange)> {
     let uri = loc.get("uri")?.as_str()?.to_string();
@@ -442,7 +610,6 @@ mod tests {
     // existing per-test allows in `update.rs` and `selection.rs`.
     use super::*;
     use crate::canvas_editor::lsp::LspPosition;
-    use std::io::BufReader;
     use std::time::Instant;
 
     /// Returns the JSON body of a `Content-Length`-framed message taken from
@@ -459,217 +626,7 @@ mod tests {
 
     /// Builds a [`PendingRequest`] of `kind`, sent "now" for test purposes.
     fn pending_request(kind: LspRequestKind) -> PendingRequest {
-        PendingRequest { kind, requested_at: Instant::now() }
-    }
-
-    // -------------------------------------------------------------------------
-    // read_message
-    // -------------------------------------------------------------------------
-
-    #[test]
-    fn test_read_message_reads_framed_body() {
-        let mut stream = std::io::Cursor::new(
-            b"Content-Length: 9\r\n\r\n{\"id\":42}".to_vec(),
-        );
-        assert_eq!(
-            read_message(&mut stream).as_deref(),
-            Some(&b"{\"id\":42}"[..])
-        );
-    }
-
-    #[test]
-    fn test_read_message_ignores_other_headers() {
-        let mut stream = std::io::Cursor::new(
-            b"Content-Type: application/vscode-jsonrpc\r\nContent-Length: 2\r\n\r\n{}"
-                .to_vec(),
-        );
-        assert_eq!(read_message(&mut stream).as_deref(), Some(&b"{}"[..]));
-    }
-
-    #[test]
-    fn test_read_message_reads_consecutive_frames() {
-        let mut stream = std::io::Cursor::new(
-            b"Content-Length: 2\r\n\r\n{}Content-Length: 4\r\n\r\n[1,]"
-                .to_vec(),
-        );
-        assert_eq!(read_message(&mut stream).as_deref(), Some(&b"{}"[..]));
-        assert_eq!(read_message(&mut stream).as_deref(), Some(&b"[1,]"[..]));
-        assert!(read_message(&mut stream).is_none(), "stream is exhausted");
-    }
-
-    #[test]
-    fn test_read_message_rejects_oversized_frame() {
-        // The announced length exceeds MAX_MESSAGE_BYTES: the frame must be
-        // refused without allocating it.
-        let header =
-            format!("Content-Length: {}\r\n\r\n", MAX_MESSAGE_BYTES + 1);
-        let mut stream = std::io::Cursor::new(header.into_bytes());
-        assert!(read_message(&mut stream).is_none());
-    }
-
-    #[test]
-    fn test_read_message_rejects_truncated_body() {
-        let mut stream =
-            std::io::Cursor::new(b"Content-Length: 10\r\n\r\nabc".to_vec());
-        assert!(read_message(&mut stream).is_none());
-    }
-
-    #[test]
-    fn test_read_message_ends_on_empty_stream() {
-        let mut stream = std::io::Cursor::new(Vec::new());
-        assert!(read_message(&mut stream).is_none());
-    }
-
-    #[test]
-    fn test_read_message_without_content_length_yields_empty_body() {
-        // No Content-Length: the caller gets an empty body and discards it as
-        // invalid JSON, then keeps reading.
-        let mut stream = std::io::Cursor::new(b"X-Unknown: 1\r\n\r\n".to_vec());
-        assert_eq!(read_message(&mut stream).as_deref(), Some(&b""[..]));
-    }
-
-    /// A reader that serves an endless run of `a` with no newline ever,
-    /// standing in for a server that opens a header line and never closes it.
-    ///
-    /// It counts what it served, which is what makes the bound observable: a
-    /// finite `Cursor` would end the line at EOF and so pass even with no cap
-    /// at all.
-    struct EndlessHeaderReader {
-        served: std::rc::Rc<std::cell::Cell<usize>>,
-    }
-
-    impl Read for EndlessHeaderReader {
-        fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
-            buf.fill(b'a');
-            self.served.set(self.served.get() + buf.len());
-            Ok(buf.len())
-        }
-    }
-
-    #[test]
-    fn test_read_message_stops_reading_an_unterminated_header_line() {
-        let served = std::rc::Rc::new(std::cell::Cell::new(0));
-        let mut stream = BufReader::new(EndlessHeaderReader {
-            served: std::rc::Rc::clone(&served),
-        });
-
-        assert!(read_message(&mut stream).is_none());
-
-        // The real property: the reader gave up near the cap instead of
-        // growing its buffer for as long as the server kept talking. Without
-        // the cap this test would not fail, it would never return.
-        let cap = MAX_HEADER_LINE_BYTES as usize;
-        assert!(
-            served.get() <= cap * 4,
-            "read {} bytes for one header line, cap is {cap}",
-            served.get()
-        );
-    }
-
-    #[test]
-    fn test_read_message_rejects_a_header_block_that_never_ends() {
-        // Well-formed headers, but no blank separator line ever arrives. Each
-        // line is individually bounded, so this is a livelock rather than a
-        // memory hazard — the line cap alone would not stop it.
-        let flood = "X-Pad: 1\r\n".repeat(MAX_HEADER_LINES + 10);
-        let mut stream = std::io::Cursor::new(flood.into_bytes());
-
-        assert!(read_message(&mut stream).is_none());
-    }
-
-    #[test]
-    fn test_read_log_line_reads_lines_in_order() {
-        let mut reader =
-            BufReader::new(std::io::Cursor::new(b"first\nsecond\n".to_vec()));
-
-        assert_eq!(read_log_line(&mut reader).as_deref(), Some("first\n"));
-        assert_eq!(read_log_line(&mut reader).as_deref(), Some("second\n"));
-        assert_eq!(read_log_line(&mut reader), None, "stream is exhausted");
-    }
-
-    #[test]
-    fn test_read_log_line_truncates_an_oversized_line_and_resyncs() {
-        let cap = MAX_LOG_LINE_BYTES as usize;
-        let flood = "a".repeat(cap * 3);
-        let stream = format!("{flood}\nnext\n");
-        let mut reader =
-            BufReader::new(std::io::Cursor::new(stream.into_bytes()));
-
-        let line = read_log_line(&mut reader);
-        assert!(
-            line.as_ref().is_some_and(|line| line.len() <= cap + 8),
-            "the kept prefix must be bounded by the cap"
-        );
-        assert!(
-            line.as_ref().is_some_and(|line| line.ends_with('…')),
-            "a truncated line must say so"
-        );
-
-        // The remainder was skipped rather than buffered, so the next line
-        // still starts on a real boundary instead of mid-flood.
-        assert_eq!(read_log_line(&mut reader).as_deref(), Some("next\n"));
-    }
-
-    #[test]
-    fn test_read_log_line_replaces_invalid_utf8_instead_of_ending_the_stream() {
-        // Server diagnostics are not worth tearing the log stream down over:
-        // a bad byte must not cost us every later line.
-        let mut reader = BufReader::new(std::io::Cursor::new(
-            b"bad \xff byte\nnext\n".to_vec(),
-        ));
-
-        let line = read_log_line(&mut reader);
-        assert!(
-            line.as_ref().is_some_and(|line| line.contains('\u{fffd}')),
-            "the invalid byte should become a replacement character, got {line:?}"
-        );
-        assert_eq!(read_log_line(&mut reader).as_deref(), Some("next\n"));
-    }
-
-    #[test]
-    fn test_read_log_line_stops_reading_an_unterminated_line() {
-        let served = std::rc::Rc::new(std::cell::Cell::new(0));
-        let mut reader = BufReader::new(EndlessHeaderReader {
-            served: std::rc::Rc::clone(&served),
-        });
-
-        // The line never ends, so the skip-ahead never finds a newline either;
-        // both must stay bounded rather than buffering the whole flood.
-        let line = read_log_line(&mut reader);
-        let cap = MAX_LOG_LINE_BYTES as usize;
-        assert!(line.is_some_and(|line| line.len() <= cap + 8));
-    }
-
-    #[test]
-    fn test_read_message_accepts_headers_just_inside_the_caps() {
-        // The caps must not reject legitimate traffic: a long-but-bounded
-        // header line, and more headers than a real server sends but fewer
-        // than the limit, still produce the framed body.
-        let padding = "x".repeat(MAX_HEADER_LINE_BYTES as usize / 2);
-        let mut frame = format!("X-Pad: {padding}\r\n");
-        for _ in 0..MAX_HEADER_LINES - 3 {
-            frame.push_str("X-Small: 1\r\n");
-        }
-        frame.push_str("Content-Length: 2\r\n\r\n{}");
-        let mut stream = std::io::Cursor::new(frame.into_bytes());
-
-        assert_eq!(read_message(&mut stream).as_deref(), Some(&b"{}"[..]));
-    }
-
-    // -------------------------------------------------------------------------
-    // frame_message
-    // -------------------------------------------------------------------------
-
-    #[test]
-    #[allow(clippy::unwrap_used)]
-    fn test_frame_message_builds_content_length_header() {
-        let value = serde_json::json!({"jsonrpc": "2.0", "id": 1});
-        let data = serde_json::to_vec(&value).unwrap();
-        let framed = frame_message(&value).unwrap();
-
-        let expected_header = format!("Content-Length: {}\r\n\r\n", data.len());
-        assert!(framed.starts_with(expected_header.as_bytes()));
-        assert_eq!(&framed[expected_header.len()..], data.as_slice());
+        PendingRequest { kind, reply: None, requested_at: Instant::now() }
     }
 
     // -------------------------------------------------------------------------
@@ -817,12 +774,380 @@ mod tests {
 
         match events_rx.try_recv().expect("expected a Completion event") {
             LspEvent::Completion { items } => {
-                assert_eq!(items, vec!["foo", "bar"]);
+                assert_eq!(
+                    items,
+                    vec![LspCompletionItem::from("foo"), "bar".into()]
+                );
             }
             _ => panic!("expected LspEvent::Completion"),
         }
     }
 
+    #[test]
+    fn test_parse_completion_items_reads_snippet_insert_text() {
+        let items = parse_completion_items(&serde_json::json!([
+            {
+                "label": "push",
+                "insertText": "push(${1:value})",
+                "insertTextFormat": 2
+            },
+            {
+                "label": "len",
+                "insertText": "ignored",
+                "textEdit": {
+                    "range": {
+                        "start": { "line": 0, "character": 0 },
+                        "end": { "line": 0, "character": 1 }
+                    },
+                    "newText": "len()"
+                }
+            },
+            { "label": "pop" }
+        ]));
+
+        assert_eq!(items[0].text_to_insert(), "push(${1:value})");
+        assert!(items[0].is_snippet);
+        assert_eq!(items[1].text_to_insert(), "len()");
+        assert!(!items[1].is_snippet);
+        assert_eq!(items[2], LspCompletionItem::from("pop"));
+    }
+
+    #[test]
+    #[allow(clippy::expect_used, clippy::panic, clippy::unwrap_used)]
+    fn test_handle_client_response_signature_help() {
+        let (events_tx, events_rx) = mpsc::channel::<LspEvent>();
+        let pending = Arc::new(Mutex::new(HashMap::new()));
+        pending
+            .lock()
+            .unwrap()
+            .insert(4u64, pending_request(LspRequestKind::SignatureHelp));
+
+        let value = serde_json::json!({ "id": 4, "result": null });
+        handle_client_response(4, &value, &pending, &events_tx);
+
+        // A null result still produces an event, so the popup can close.
+        match events_rx.try_recv().expect("expected a SignatureHelp event") {
+            LspEvent::SignatureHelp { help } => assert!(help.is_none()),
+            _ => panic!("expected LspEvent::SignatureHelp"),
+        }
+    }
+
+    #[test]
+    #[allow(clippy::expect_used, clippy::panic, clippy::unwrap_used)]
+    fn test_handle_client_response_document_highlight() {
+        let (events_tx, events_rx) = mpsc::channel::<LspEvent>();
+        let pending = Arc::new(Mutex::new(HashMap::new()));
+        pending
+            .lock()
+            .unwrap()
+            .insert(5u64, pending_request(LspRequestKind::DocumentHighlight));
+
+        let value = serde_json::json!({
+            "id": 5,
+            "result": [
+                {
+                    "range": {
+                        "start": { "line": 0, "character": 4 },
+                        "end": { "line": 0, "character": 5 }
+                    },
+                    "kind": 3
+                },
+                {
+                    "range": {
+                        "start": { "line": 1, "character": 0 },
+                        "end": { "line": 1, "character": 1 }
+                    },
+                    "kind": 2
+                },
+                {
+                    "range": {
+                        "start": { "line": 2, "character": 0 },
+                        "end": { "line": 2, "character": 1 }
+                    }
+                },
+                { "kind": 1 }
+            ]
+        });
+        handle_client_response(5, &value, &pending, &events_tx);
+
+        match events_rx.try_recv().expect("expected a DocumentHighlight event")
+        {
+            LspEvent::DocumentHighlight { highlights } => {
+                let kinds: Vec<_> = highlights.iter().map(|h| h.kind).collect();
+                assert_eq!(
+                    kinds,
+                    [
+                        LspDocumentHighlightKind::Write,
+                        LspDocumentHighlightKind::Read,
+                        LspDocumentHighlightKind::Text,
+                    ]
+                );
+                assert_eq!(highlights[0].range.start.character, 4);
+            }
+            _ => panic!("expected LspEvent::DocumentHighlight"),
+        }
+    }
+
+    #[test]
+    #[allow(clippy::unwrap_used)]
+    fn test_handle_client_response_document_highlight_error_sends_nothing() {
+        let (events_tx, events_rx) = mpsc::channel::<LspEvent>();
+        let pending = Arc::new(Mutex::new(HashMap::new()));
+        pending
+            .lock()
+            .unwrap()
+            .insert(6u64, pending_request(LspRequestKind::DocumentHighlight));
+
+        let value = serde_json::json!({
+            "id": 6,
+            "error": { "code": -32601, "message": "Method not found" }
+        });
+        handle_client_response(6, &value, &pending, &events_tx);
+
+        assert!(matches!(events_rx.try_recv(), Err(mpsc::TryRecvError::Empty)));
+    }
+
+    #[test]
+    #[allow(clippy::expect_used, clippy::panic, clippy::unwrap_used)]
+    fn test_handle_client_response_selection_range_flattens_parents() {
+        let (events_tx, events_rx) = mpsc::channel::<LspEvent>();
+        let pending = Arc::new(Mutex::new(HashMap::new()));
+        pending
+            .lock()
+            .unwrap()
+            .insert(7u64, pending_request(LspRequestKind::SelectionRange));
+
+        let range = |start: u64, end: u64| {
+            serde_json::json!({
+                "start": { "line": 0, "character": start },
+                "end": { "line": 0, "character": end }
+            })
+        };
+        let value = serde_json::json!({
+            "id": 7,
+            "result": [{
+                "range": range(4, 9),
+                "parent": {
+                    "range": range(0, 20),
+                    "parent": { "range": range(0, 30) }
+                }
+            }]
+        });
+        handle_client_response(7, &value, &pending, &events_tx);
+
+        match events_rx.try_recv().expect("expected a SelectionRange event") {
+            LspEvent::SelectionRange { ranges } => {
+                let ends: Vec<u32> =
+                    ranges.iter().map(|r| r.end.character).collect();
+                assert_eq!(ends, [9, 20, 30]);
+            }
+            _ => panic!("expected LspEvent::SelectionRange"),
+        }
+    }
+
+    #[test]
+    #[allow(clippy::expect_used, clippy::panic, clippy::unwrap_used)]
+    fn test_handle_client_response_selection_range_error_sends_empty_list() {
+        let (events_tx, events_rx) = mpsc::channel::<LspEvent>();
+        let pending = Arc::new(Mutex::new(HashMap::new()));
+        pending
+            .lock()
+            .unwrap()
+            .insert(8u64, pending_request(LspRequestKind::SelectionRange));
+
+        let value = serde_json::json!({
+            "id": 8,
+            "error": { "code": -32601, "message": "Method not found" }
+        });
+        handle_client_response(8, &value, &pending, &events_tx);
+
+        match events_rx.try_recv().expect("expected a SelectionRange event") {
+            LspEvent::SelectionRange { ranges } => assert!(ranges.is_empty()),
+            _ => panic!("expected LspEvent::SelectionRange"),
+        }
+    }
+
+    #[test]
+    #[allow(clippy::expect_used, clippy::panic, clippy::unwrap_used)]
+    fn test_handle_client_response_linked_editing_range() {
+        let (events_tx, events_rx) = mpsc::channel::<LspEvent>();
+        let pending = Arc::new(Mutex::new(HashMap::new()));
+        pending
+            .lock()
+            .unwrap()
+            .insert(9u64, pending_request(LspRequestKind::LinkedEditingRange));
+
+        let value = serde_json::json!({
+            "id": 9,
+            "result": {
+                "ranges": [
+                    {
+                        "start": { "line": 0, "character": 1 },
+                        "end": { "line": 0, "character": 4 }
+                    },
+                    {
+                        "start": { "line": 2, "character": 2 },
+                        "end": { "line": 2, "character": 5 }
+                    }
+                ],
+                "wordPattern": "[a-z]+"
+            }
+        });
+        handle_client_response(9, &value, &pending, &events_tx);
+
+        match events_rx.try_recv().expect("expected a LinkedEditingRange event")
+        {
+            LspEvent::LinkedEditingRange { ranges } => {
+                assert_eq!(ranges.len(), 2);
+                assert_eq!(ranges[1].start.line, 2);
+            }
+            _ => panic!("expected LspEvent::LinkedEditingRange"),
+        }
+    }
+
+    #[test]
+    #[allow(clippy::expect_used, clippy::panic, clippy::unwrap_used)]
+    fn test_handle_client_response_document_links_skip_unresolved_entries() {
+        let (events_tx, events_rx) = mpsc::channel::<LspEvent>();
+        let pending = Arc::new(Mutex::new(HashMap::new()));
+        pending
+            .lock()
+            .unwrap()
+            .insert(10u64, pending_request(LspRequestKind::DocumentLink));
+
+        let value = serde_json::json!({
+            "id": 10,
+            "result": [
+                {
+                    "range": {
+                        "start": { "line": 0, "character": 4 },
+                        "end": { "line": 0, "character": 9 }
+                    },
+                    "target": "https://docs.rs/serde"
+                },
+                {
+                    "range": {
+                        "start": { "line": 1, "character": 0 },
+                        "end": { "line": 1, "character": 3 }
+                    },
+                    "data": { "resolve": "later" }
+                }
+            ]
+        });
+        handle_client_response(10, &value, &pending, &events_tx);
+
+        match events_rx.try_recv().expect("expected a DocumentLinks event") {
+            LspEvent::DocumentLinks { links } => {
+                assert_eq!(links.len(), 1);
+                assert_eq!(links[0].target, "https://docs.rs/serde");
+                assert_eq!(links[0].range.end.character, 9);
+            }
+            _ => panic!("expected LspEvent::DocumentLinks"),
+        }
+    }
+
+    #[test]
+    #[allow(clippy::expect_used, clippy::panic, clippy::unwrap_used)]
+    fn test_handle_client_response_document_colors() {
+        let (events_tx, events_rx) = mpsc::channel::<LspEvent>();
+        let pending = Arc::new(Mutex::new(HashMap::new()));
+        pending
+            .lock()
+            .unwrap()
+            .insert(12u64, pending_request(LspRequestKind::DocumentColor));
+
+        let value = serde_json::json!({
+            "id": 12,
+            "result": [
+                {
+                    "range": {
+                        "start": { "line": 2, "character": 8 },
+                        "end": { "line": 2, "character": 18 }
+                    },
+                    "color": { "red": 1.0, "green": 0.0, "blue": 0.0, "alpha": 1.0 }
+                },
+                {
+                    "range": {
+                        "start": { "line": 3, "character": 0 },
+                        "end": { "line": 3, "character": 3 }
+                    },
+                    "color": { "red": 1.0 }
+                }
+            ]
+        });
+        handle_client_response(12, &value, &pending, &events_tx);
+
+        match events_rx.try_recv().expect("expected a DocumentColors event") {
+            LspEvent::DocumentColors { colors } => {
+                assert_eq!(colors.len(), 1);
+                assert_eq!(colors[0].range.start.line, 2);
+                assert_eq!(colors[0].color.into_rgba8(), [255, 0, 0, 255]);
+            }
+            _ => panic!("expected LspEvent::DocumentColors"),
+        }
+    }
+
+    #[test]
+    #[allow(clippy::expect_used, clippy::panic, clippy::unwrap_used)]
+    fn test_handle_client_response_color_presentations() {
+        let (events_tx, events_rx) = mpsc::channel::<LspEvent>();
+        let pending = Arc::new(Mutex::new(HashMap::new()));
+        pending
+            .lock()
+            .unwrap()
+            .insert(13u64, pending_request(LspRequestKind::ColorPresentation));
+
+        let value = serde_json::json!({
+            "id": 13,
+            "result": [
+                { "label": "Color::BLUE" },
+                {
+                    "label": "rgb",
+                    "textEdit": {
+                        "range": {
+                            "start": { "line": 0, "character": 8 },
+                            "end": { "line": 0, "character": 18 }
+                        },
+                        "newText": "Color::from_rgb(0.0, 0.0, 1.0)"
+                    }
+                }
+            ]
+        });
+        handle_client_response(13, &value, &pending, &events_tx);
+
+        match events_rx.try_recv().expect("expected a ColorPresentations event")
+        {
+            LspEvent::ColorPresentations { presentations } => {
+                assert_eq!(presentations.len(), 2);
+                assert_eq!(presentations[0].text(), "Color::BLUE");
+                assert_eq!(
+                    presentations[1].text(),
+                    "Color::from_rgb(0.0, 0.0, 1.0)"
+                );
+            }
+            _ => panic!("expected LspEvent::ColorPresentations"),
+        }
+    }
+
+    #[test]
+    #[allow(clippy::unwrap_used)]
+    fn test_handle_client_response_document_links_error_sends_nothing() {
+        let (events_tx, events_rx) = mpsc::channel::<LspEvent>();
+        let pending = Arc::new(Mutex::new(HashMap::new()));
+        pending
+            .lock()
+            .unwrap()
+            .insert(11u64, pending_request(LspRequestKind::DocumentLink));
+
+        let value = serde_json::json!({
+            "id": 11,
+            "error": { "code": -32601, "message": "unsupported" }
+        });
+        handle_client_response(11, &value, &pending, &events_tx);
+
+        assert!(matches!(events_rx.try_recv(), Err(mpsc::TryRecvError::Empty)));
+    }
+
     #[test]
     #[allow(clippy::expect_used, clippy::panic, clippy::unwrap_used)]
     fn test_handle_client_response_definition() {
@@ -932,6 +1257,123 @@ mod tests {
             &events_tx,
             "server",
         );
-        assert!(events_rx.try_recv().is_err());
+        assert!(matches!(events_rx.try_recv(), Err(mpsc::TryRecvError::Empty)));
+    }
+
+    // -------------------------------------------------------------------------
+    // Markup and response parsing
+    // -------------------------------------------------------------------------
+
+    #[test]
+    fn test_hover_marked_string_with_language_becomes_fenced_block() {
+        let result = serde_json::json!({
+            "contents": { "language": "rust", "value": "fn main()" }
+        });
+        assert_eq!(
+            parse_hover_text(&result).as_deref(),
+            Some("```rust\nfn main()\n```")
+        );
+    }
+
+    #[test]
+    fn test_hover_marked_string_array_joins_as_paragraphs() {
+        let result = serde_json::json!({
+            "contents": [
+                { "language": "rust", "value": "struct Foo" },
+                "A *foo*."
+            ]
+        });
+        assert_eq!(
+            parse_hover_text(&result).as_deref(),
+            Some("```rust\nstruct Foo\n```\n\nA *foo*.")
+        );
+    }
+
+    #[test]
+    fn test_hover_markup_content_markdown_passes_through() {
+        let result = serde_json::json!({
+            "contents": { "kind": "markdown", "value": "# Title\n**bold**" }
+        });
+        assert_eq!(
+            parse_hover_text(&result).as_deref(),
+            Some("# Title\n**bold**")
+        );
+    }
+
+    #[test]
+    fn test_hover_markup_content_plaintext_is_escaped() {
+        let result = serde_json::json!({
+            "contents": { "kind": "plaintext", "value": "a_b * c\nnext" }
+        });
+        assert_eq!(
+            parse_hover_text(&result).as_deref(),
+            Some("a\\_b \\* c  \nnext")
+        );
+    }
+
+    #[test]
+    fn test_parse_completion_items_reads_detail_and_documentation() {
+        let result = serde_json::json!([
+            {
+                "label": "push",
+                "detail": "fn(&mut self, T)",
+                "documentation": { "kind": "markdown", "value": "Appends." }
+            },
+            { "label": "pop", "documentation": "Removes the last." },
+            { "detail": "no label" }
+        ]);
+        let items = parse_completion_items(&result);
+        assert_eq!(items.len(), 2);
+        assert_eq!(items[0].detail.as_deref(), Some("fn(&mut self, T)"));
+        assert_eq!(items[0].documentation.as_deref(), Some("Appends."));
+        assert_eq!(items[1].label, "pop");
+        assert_eq!(items[1].detail, None);
+        assert_eq!(
+            items[1].documentation.as_deref(),
+            Some("Removes the last.")
+        );
+    }
+
+    #[test]
+    #[allow(clippy::expect_used)]
+    fn test_parse_signature_help_resolves_string_parameter_label() {
+        let result = serde_json::json!({
+            "signatures": [{
+                "label": "fn add(a: i32, b: i32) -> i32",
+                "documentation": "Adds.",
+                "parameters": [
+                    { "label": "a: i32" },
+                    { "label": "b: i32", "documentation": "The addend." }
+                ]
+            }],
+            "activeSignature": 0,
+            "activeParameter": 1
+        });
+        let help = parse_signature_help(&result).expect("a signature");
+        assert_eq!(help.label, "fn add(a: i32, b: i32) -> i32");
+        assert_eq!(help.active_parameter, Some((15, 21)));
+        assert_eq!(help.documentation.as_deref(), Some("The addend.\n\nAdds."));
+    }
+
+    #[test]
+    #[allow(clippy::expect_used)]
+    fn test_parse_signature_help_resolves_utf16_offsets() {
+        // "é" is one UTF-16 unit but two bytes; "𝕏" is two UTF-16 units.
+        let result = serde_json::json!({
+            "signatures": [{
+                "label": "f(𝕏, é)",
+                "parameters": [{ "label": [2, 4] }, { "label": [6, 7] }],
+                "activeParameter": 1
+            }]
+        });
+        let help = parse_signature_help(&result).expect("a signature");
+        assert_eq!(help.active_parameter, Some((5, 6)));
+    }
+
+    #[test]
+    fn test_parse_signature_help_without_signatures_is_none() {
+        let result = serde_json::json!({ "signatures": [] });
+        assert!(parse_signature_help(&result).is_none());
+        assert!(parse_signature_help(&serde_json::Value::Null).is_none());
     }
 }
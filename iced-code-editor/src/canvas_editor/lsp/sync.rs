@@ -1,6 +1,8 @@
 //! LSP synchronization for [`CodeEditor`]: attaching/detaching a client,
 //! tracking buffer edits as queued `didChange` notifications, and issuing
-//! hover/completion/definition requests.
+//! hover/completion/signature-help/document-highlight/selection-range/
+//! linked-editing/definition requests, the hover, completion and definition
+//! ones optionally through an [`LspRequest`](lsp::LspRequest) handle.
 
 use crate::buffer::TextBuffer;
 use crate::canvas_editor::CodeEditor;
@@ -120,6 +122,10 @@ impl CodeEditor {
         self.update_lsp_synced_extent();
         self.lsp_edit_snapshot = None;
         self.lsp_pending_changes.clear();
+        // Links and colors from the previous document do not apply to this
+        // one, and this one has not been asked for its own yet.
+        self.document_link_state.clear();
+        self.document_color_state.clear();
     }
 
     /// Runs `f` with the attached LSP client and document, if both are
@@ -194,6 +200,8 @@ impl CodeEditor {
         self.lsp_synced_last_line_len = 0;
         self.lsp_edit_snapshot = None;
         self.lsp_pending_changes.clear();
+        self.document_link_state.clear();
+        self.document_color_state.clear();
     }
 
     /// Sends a `did_save` notification with the current buffer contents.
@@ -319,6 +327,66 @@ impl CodeEditor {
         .is_some()
     }
 
+    /// Requests hover information at an explicit LSP position and returns a
+    /// handle to the reply.
+    ///
+    /// Unlike [`Self::lsp_request_hover_at_position`], the reply comes back
+    /// through the returned [`LspRequest`](lsp::LspRequest) rather than the
+    /// client's event channel; turn it into a task with
+    /// [`LspRequest::into_task`](lsp::LspRequest::into_task) to get it tagged
+    /// with this editor's id.
+    ///
+    /// # Arguments
+    ///
+    /// * `position` - The zero-based document position to query
+    ///
+    /// # Returns
+    ///
+    /// The handle, or `None` if no client is attached
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use iced::futures::FutureExt;
+    /// use iced_code_editor::{
+    ///     CodeEditor, LspClient, LspDocument, LspPosition, LspRequest,
+    /// };
+    ///
+    /// /// Answers every hover at once with the position asked about.
+    /// struct EchoClient;
+    ///
+    /// impl LspClient for EchoClient {
+    ///     fn hover(
+    ///         &mut self,
+    ///         _document: &LspDocument,
+    ///         position: LspPosition,
+    ///     ) -> LspRequest<String> {
+    ///         let (reply, request) = LspRequest::channel();
+    ///         let _ = reply.send(format!("{}:{}", position.line, position.character));
+    ///         request
+    ///     }
+    /// }
+    ///
+    /// let mut editor = CodeEditor::new("fn main() {}", "rs");
+    /// let position = LspPosition { line: 0, character: 3 };
+    /// assert!(editor.lsp_hover_at_position(position).is_none());
+    ///
+    /// editor.attach_lsp(
+    ///     Box::new(EchoClient),
+    ///     LspDocument::new("file:///tmp/main.rs", "rust"),
+    /// );
+    /// let reply = editor
+    ///     .lsp_hover_at_position(position)
+    ///     .and_then(|request| request.now_or_never());
+    /// assert_eq!(reply, Some(Some("0:3".to_string())));
+    /// ```
+    pub fn lsp_hover_at_position(
+        &mut self,
+        position: lsp::LspPosition,
+    ) -> Option<lsp::LspRequest<String>> {
+        self.with_lsp(|client, document| client.hover(document, position))
+    }
+
     /// Requests hover information at an explicit LSP position.
     ///
     /// Unlike [`Self::lsp_request_hover_at`], this skips point-to-position
@@ -478,6 +546,287 @@ impl CodeEditor {
         });
     }
 
+    /// Requests completion items at the current cursor position and returns
+    /// a handle to the reply.
+    ///
+    /// Unlike [`Self::lsp_request_completion`], the items come back through
+    /// the returned [`LspRequest`](lsp::LspRequest) rather than the client's
+    /// event channel.
+    ///
+    /// # Returns
+    ///
+    /// The handle, or `None` if no client is attached
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use iced_code_editor::CodeEditor;
+    ///
+    /// let mut editor = CodeEditor::new("fn main() {}", "rs");
+    ///
+    /// // With no client attached there is nothing to send.
+    /// assert!(editor.lsp_completion().is_none());
+    /// ```
+    pub fn lsp_completion(
+        &mut self,
+    ) -> Option<lsp::LspRequest<Vec<lsp::LspCompletionItem>>> {
+        let position = self.lsp_position_from_cursor();
+        self.with_lsp(|client, document| client.completion(document, position))
+    }
+
+    /// Requests signature help at the current cursor position.
+    ///
+    /// The editor sends this itself after `(` or `,` is typed; call it
+    /// directly to re-query, e.g. when the cursor moves inside an argument
+    /// list. Fire-and-forget like the other requests, and a no-op when no
+    /// client is attached.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use std::cell::RefCell;
+    /// use std::rc::Rc;
+    ///
+    /// use iced_code_editor::{CodeEditor, LspClient, LspDocument, LspPosition};
+    ///
+    /// /// Records where signature help was requested.
+    /// struct SignatureClient(Rc<RefCell<Vec<LspPosition>>>);
+    ///
+    /// impl LspClient for SignatureClient {
+    ///     fn request_signature_help(&mut self, _document: &LspDocument, position: LspPosition) {
+    ///         self.0.borrow_mut().push(position);
+    ///     }
+    /// }
+    ///
+    /// let requests = Rc::new(RefCell::new(Vec::new()));
+    /// let mut editor = CodeEditor::new("add(", "rs");
+    /// editor.attach_lsp(
+    ///     Box::new(SignatureClient(Rc::clone(&requests))),
+    ///     LspDocument::new("file:///tmp/main.rs", "rust"),
+    /// );
+    ///
+    /// editor.lsp_request_signature_help();
+    /// assert_eq!(requests.borrow().len(), 1);
+    /// ```
+    pub fn lsp_request_signature_help(&mut self) {
+        let position = self.lsp_position_from_cursor();
+        self.with_lsp(|client, document| {
+            client.request_signature_help(document, position);
+        });
+    }
+
+    /// Requests the occurrences of the symbol under the cursor.
+    ///
+    /// The editor sends this on its own once the cursor rests on an
+    /// identifier (see [`Self::set_document_highlight_enabled`]); calling it
+    /// directly is only needed to refresh highlights on demand. Hand the
+    /// reply to [`Self::set_lsp_document_highlights`].
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use std::cell::RefCell;
+    /// use std::rc::Rc;
+    ///
+    /// use iced_code_editor::{CodeEditor, LspClient, LspDocument, LspPosition};
+    ///
+    /// /// Records the position each document-highlight request asked about.
+    /// struct HighlightClient(Rc<RefCell<Vec<LspPosition>>>);
+    ///
+    /// impl LspClient for HighlightClient {
+    ///     fn request_document_highlight(&mut self, _document: &LspDocument, position: LspPosition) {
+    ///         self.0.borrow_mut().push(position);
+    ///     }
+    /// }
+    ///
+    /// let requests = Rc::new(RefCell::new(Vec::new()));
+    /// let mut editor = CodeEditor::new("let x = x;", "rs");
+    /// editor.attach_lsp(
+    ///     Box::new(HighlightClient(Rc::clone(&requests))),
+    ///     LspDocument::new("file:///tmp/main.rs", "rust"),
+    /// );
+    ///
+    /// editor.lsp_request_document_highlight();
+    /// assert_eq!(requests.borrow().len(), 1);
+    /// ```
+    pub fn lsp_request_document_highlight(&mut self) {
+        let position = self.lsp_position_from_cursor();
+        self.with_lsp(|client, document| {
+            client.request_document_highlight(document, position);
+        });
+    }
+
+    /// Requests the selection ranges enclosing the cursor.
+    ///
+    /// [`Message::ExpandSelection`] sends this itself the first time it is
+    /// used at a position; hand the reply to
+    /// [`Self::set_lsp_selection_ranges`].
+    ///
+    /// [`Message::ExpandSelection`]: crate::Message::ExpandSelection
+    ///
+    /// # Returns
+    ///
+    /// `true` if a client is attached and the request was sent, `false`
+    /// otherwise
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use iced_code_editor::CodeEditor;
+    ///
+    /// let mut editor = CodeEditor::new("fn main() {}", "rs");
+    ///
+    /// // With no client attached there is nothing to send.
+    /// assert!(!editor.lsp_request_selection_range());
+    /// ```
+    pub fn lsp_request_selection_range(&mut self) -> bool {
+        let position = self.lsp_position_from_cursor();
+        self.with_lsp(|client, document| {
+            client.request_selection_range(document, position);
+        })
+        .is_some()
+    }
+
+    /// Requests the ranges linked to the one under the cursor.
+    ///
+    /// The editor sends this on its own once the cursor rests (see
+    /// [`Self::set_linked_editing_enabled`]); hand the reply to
+    /// [`Self::set_lsp_linked_editing_ranges`].
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use std::cell::RefCell;
+    /// use std::rc::Rc;
+    ///
+    /// use iced_code_editor::{CodeEditor, LspClient, LspDocument, LspPosition};
+    ///
+    /// /// Counts linked-editing requests.
+    /// struct LinkedClient(Rc<RefCell<usize>>);
+    ///
+    /// impl LspClient for LinkedClient {
+    ///     fn request_linked_editing_range(&mut self, _document: &LspDocument, _position: LspPosition) {
+    ///         *self.0.borrow_mut() += 1;
+    ///     }
+    /// }
+    ///
+    /// let requests = Rc::new(RefCell::new(0));
+    /// let mut editor = CodeEditor::new("<div></div>", "html");
+    /// editor.attach_lsp(
+    ///     Box::new(LinkedClient(Rc::clone(&requests))),
+    ///     LspDocument::new("file:///tmp/index.html", "html"),
+    /// );
+    ///
+    /// editor.lsp_request_linked_editing_range();
+    /// assert_eq!(*requests.borrow(), 1);
+    /// ```
+    pub fn lsp_request_linked_editing_range(&mut self) {
+        let position = self.lsp_position_from_cursor();
+        self.with_lsp(|client, document| {
+            client.request_linked_editing_range(document, position);
+        });
+    }
+
+    /// Requests the links found in the document.
+    ///
+    /// The editor sends this on its own once the buffer has stopped changing
+    /// (see [`Self::set_document_links_enabled`]); hand the reply to
+    /// [`Self::set_lsp_document_links`].
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use std::cell::RefCell;
+    /// use std::rc::Rc;
+    ///
+    /// use iced_code_editor::{CodeEditor, LspClient, LspDocument};
+    ///
+    /// /// Counts document-link requests.
+    /// struct LinkClient(Rc<RefCell<usize>>);
+    ///
+    /// impl LspClient for LinkClient {
+    ///     fn request_document_links(&mut self, _document: &LspDocument) {
+    ///         *self.0.borrow_mut() += 1;
+    ///     }
+    /// }
+    ///
+    /// let requests = Rc::new(RefCell::new(0));
+    /// let mut editor = CodeEditor::new("// see https://example.com", "rs");
+    /// editor.attach_lsp(
+    ///     Box::new(LinkClient(Rc::clone(&requests))),
+    ///     LspDocument::new("file:///tmp/main.rs", "rust"),
+    /// );
+    ///
+    /// editor.lsp_request_document_links();
+    /// assert_eq!(*requests.borrow(), 1);
+    /// ```
+    pub fn lsp_request_document_links(&mut self) {
+        self.document_link_state.requested = Some(self.buffer_revision);
+        self.with_lsp(|client, document| {
+            client.request_document_links(document);
+        });
+    }
+
+    /// Requests the colors found in the document.
+    ///
+    /// The editor sends this on its own once the buffer has stopped changing
+    /// (see [`Self::set_show_color_previews`]); hand the reply to
+    /// [`Self::set_lsp_document_colors`].
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use std::cell::RefCell;
+    /// use std::rc::Rc;
+    ///
+    /// use iced_code_editor::{CodeEditor, LspClient, LspDocument};
+    ///
+    /// /// Counts document-color requests.
+    /// struct ColorClient(Rc<RefCell<usize>>);
+    ///
+    /// impl LspClient for ColorClient {
+    ///     fn request_document_colors(&mut self, _document: &LspDocument) {
+    ///         *self.0.borrow_mut() += 1;
+    ///     }
+    /// }
+    ///
+    /// let requests = Rc::new(RefCell::new(0));
+    /// let mut editor = CodeEditor::new("a { color: red; }", "css");
+    /// editor.attach_lsp(
+    ///     Box::new(ColorClient(Rc::clone(&requests))),
+    ///     LspDocument::new("file:///tmp/style.css", "css"),
+    /// );
+    ///
+    /// editor.lsp_request_document_colors();
+    /// assert_eq!(*requests.borrow(), 1);
+    /// ```
+    pub fn lsp_request_document_colors(&mut self) {
+        self.document_color_state.requested = Some(self.buffer_revision);
+        self.with_lsp(|client, document| {
+            client.request_document_colors(document);
+        });
+    }
+
+    /// Requests the ways `color` can be written over the given single-line
+    /// range, whose columns count characters.
+    pub(crate) fn lsp_request_color_presentations(
+        &mut self,
+        color: iced::Color,
+        (line, start_col, end_col): (usize, usize, usize),
+    ) {
+        let position = |col: usize| lsp::LspPosition {
+            line: u32::try_from(line).unwrap_or(u32::MAX),
+            character: u32::try_from(col).unwrap_or(u32::MAX),
+        };
+        let range = lsp::LspRange {
+            start: position(start_col),
+            end: position(end_col),
+        };
+        self.with_lsp(|client, document| {
+            client.request_color_presentations(document, color, range);
+        });
+    }
+
     /// Flushes pending LSP text changes to the attached client.
     ///
     /// This increments the document version and sends `did_change` with all
@@ -570,6 +919,20 @@ impl CodeEditor {
         lsp::LspPosition { line, character }
     }
 
+    /// Converts a position received from a language server into buffer
+    /// coordinates.
+    ///
+    /// Servers count columns in UTF-16 code units, the editor in characters;
+    /// a line past the end of the buffer clamps to the last line.
+    pub(crate) fn position_from_lsp(
+        &self,
+        position: lsp::LspPosition,
+    ) -> (usize, usize) {
+        let last_line = self.buffer.line_count().saturating_sub(1);
+        let line = (position.line as usize).min(last_line);
+        (line, lsp::char_column(self.buffer.line(line), position.character))
+    }
+
     /// Converts a canvas point into an LSP position, if it hits the buffer.
     fn lsp_position_from_point(
         &self,
@@ -729,7 +1092,10 @@ impl CodeEditor {
     /// and delegates the request to the active `LspClient`, if one is attached.
     ///
     /// Fire-and-forget: the resolved location arrives through the client
-    /// implementation, and the host decides whether to follow it.
+    /// implementation, and the host decides whether to follow it. The cursor
+    /// position is recorded in the jumplist first, so
+    /// [`Message::NavigateBack`](crate::Message::NavigateBack) returns to it
+    /// after the jump.
     ///
     /// # Example
     ///
@@ -760,15 +1126,54 @@ impl CodeEditor {
     /// ```
     pub fn lsp_request_definition(&mut self) {
         let position = self.lsp_position_from_cursor();
-        self.with_lsp(|client, document| {
+        let sent = self.with_lsp(|client, document| {
             client.request_definition(document, position);
         });
+        if sent.is_some() {
+            self.push_jump();
+        }
+    }
+
+    /// Requests the definition of the symbol at the current cursor position
+    /// and returns a handle to the reply.
+    ///
+    /// Unlike [`Self::lsp_request_definition`], the location comes back
+    /// through the returned [`LspRequest`](lsp::LspRequest) rather than the
+    /// client's event channel. The cursor position is recorded in the
+    /// jumplist all the same.
+    ///
+    /// # Returns
+    ///
+    /// The handle, or `None` if no client is attached
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use iced_code_editor::CodeEditor;
+    ///
+    /// let mut editor = CodeEditor::new("fn main() {}", "rs");
+    ///
+    /// // With no client attached there is nothing to send.
+    /// assert!(editor.lsp_definition().is_none());
+    /// ```
+    pub fn lsp_definition(
+        &mut self,
+    ) -> Option<lsp::LspRequest<lsp::LspLocation>> {
+        let position = self.lsp_position_from_cursor();
+        let request = self
+            .with_lsp(|client, document| client.definition(document, position));
+        if request.is_some() {
+            self.push_jump();
+        }
+        request
     }
 
     /// Initiates a "Go to Definition" request for the symbol at the specified screen coordinates.
     ///
     /// This is typically used for mouse interactions (e.g., Ctrl+Click). It first resolves
     /// the screen coordinates to a text position and then sends the request.
+    /// Like [`Self::lsp_request_definition`], it records the cursor position
+    /// in the jumplist.
     ///
     /// # Arguments
     ///
@@ -794,10 +1199,15 @@ impl CodeEditor {
         let Some(position) = self.lsp_position_from_point(point) else {
             return false;
         };
-        self.with_lsp(|client, document| {
-            client.request_definition(document, position);
-        })
-        .is_some()
+        let sent = self
+            .with_lsp(|client, document| {
+                client.request_definition(document, position);
+            })
+            .is_some();
+        if sent {
+            self.push_jump();
+        }
+        sent
     }
 }
 
@@ -868,6 +1278,32 @@ mod tests {
         ) {
             self.calls.borrow_mut().push("request_hover".to_string());
         }
+        fn request_signature_help(
+            &mut self,
+            _document: &lsp::LspDocument,
+            _position: lsp::LspPosition,
+        ) {
+            self.calls.borrow_mut().push("request_signature_help".to_string());
+        }
+        fn request_document_highlight(
+            &mut self,
+            _document: &lsp::LspDocument,
+            _position: lsp::LspPosition,
+        ) {
+            self.calls
+                .borrow_mut()
+                .push("request_document_highlight".to_string());
+        }
+        fn request_selection_range(
+            &mut self,
+            _document: &lsp::LspDocument,
+            _position: lsp::LspPosition,
+        ) {
+            self.calls.borrow_mut().push("request_selection_range".to_string());
+        }
+        fn request_document_links(&mut self, _document: &lsp::LspDocument) {
+            self.calls.borrow_mut().push("request_document_links".to_string());
+        }
         fn did_change(
             &mut self,
             _document: &lsp::LspDocument,
@@ -935,6 +1371,112 @@ mod tests {
         assert!(editor.lsp_shadow_text.is_empty());
     }
 
+    #[test]
+    fn test_typing_open_paren_or_comma_requests_signature_help() {
+        let calls = Rc::new(RefCell::new(Vec::new()));
+        let client = RecordingLspClient { calls: Rc::clone(&calls) };
+        let mut editor = CodeEditor::new("add", "rs");
+        editor.attach_lsp(
+            Box::new(client),
+            lsp::LspDocument::new("file:///test.rs", "rust"),
+        );
+        editor.request_focus();
+        editor.has_canvas_focus = true;
+        editor.focus_locked = false;
+        editor.cursors.primary_mut().position = (0, 3);
+
+        let count = |calls: &Rc<RefCell<Vec<String>>>| {
+            calls
+                .borrow()
+                .iter()
+                .filter(|call| *call == "request_signature_help")
+                .count()
+        };
+
+        let _ = editor.update(&Message::CharacterInput('('));
+        assert_eq!(count(&calls), 1);
+        let _ = editor.update(&Message::CharacterInput('1'));
+        assert_eq!(count(&calls), 1);
+        let _ = editor.update(&Message::CharacterInput(','));
+        assert_eq!(count(&calls), 2);
+    }
+
+    #[test]
+    fn test_document_highlight_navigation_asks_the_server() {
+        let calls = Rc::new(RefCell::new(Vec::new()));
+        let client = RecordingLspClient { calls: Rc::clone(&calls) };
+        let mut editor = CodeEditor::new("foo(foo)", "rs");
+        editor.attach_lsp(
+            Box::new(client),
+            lsp::LspDocument::new("file:///test.rs", "rust"),
+        );
+
+        let _ = editor.update(&Message::NextDocumentHighlight);
+        assert!(
+            calls
+                .borrow()
+                .iter()
+                .any(|call| call == "request_document_highlight")
+        );
+    }
+
+    #[test]
+    fn test_expand_selection_waits_for_the_server() {
+        let calls = Rc::new(RefCell::new(Vec::new()));
+        let client = RecordingLspClient { calls: Rc::clone(&calls) };
+        let mut editor = CodeEditor::new("foo(bar)", "rs");
+        editor.attach_lsp(
+            Box::new(client),
+            lsp::LspDocument::new("file:///test.rs", "rust"),
+        );
+        editor.cursors.set_single((0, 5));
+
+        let _ = editor.update(&Message::ExpandSelection);
+        let _ = editor.update(&Message::ExpandSelection);
+        assert!(calls.borrow().iter().any(|c| c == "request_selection_range"));
+        assert!(editor.cursors.primary().selection_range().is_none());
+
+        // Both queued expansions apply once the reply arrives.
+        let at = |character| lsp::LspPosition { line: 0, character };
+        let _ = editor.set_lsp_selection_ranges(&[
+            lsp::LspRange { start: at(4), end: at(7) },
+            lsp::LspRange { start: at(0), end: at(8) },
+        ]);
+        assert_eq!(
+            editor.cursors.primary().selection_range(),
+            Some(((0, 0), (0, 8)))
+        );
+    }
+
+    #[test]
+    fn test_document_links_are_requested_once_the_buffer_rests() {
+        let calls = Rc::new(RefCell::new(Vec::new()));
+        let client = RecordingLspClient { calls: Rc::clone(&calls) };
+        let mut editor = CodeEditor::new("see ./README.md", "rs");
+        editor.attach_lsp(
+            Box::new(client),
+            lsp::LspDocument::new("file:///test.rs", "rust"),
+        );
+        let requests = |calls: &Rc<RefCell<Vec<String>>>| {
+            calls
+                .borrow()
+                .iter()
+                .filter(|call| *call == "request_document_links")
+                .count()
+        };
+
+        // Asked once for the opened document, not on every tick.
+        let _ = editor.update(&Message::Tick);
+        let _ = editor.update(&Message::Tick);
+        assert_eq!(requests(&calls), 1);
+
+        // An edit waits out the debounce before asking again.
+        editor.cursors.set_single((0, 0));
+        let _ = editor.update(&Message::CharacterInput('x'));
+        let _ = editor.update(&Message::Tick);
+        assert_eq!(requests(&calls), 1);
+    }
+
     #[test]
     fn test_open_lsp_document_stamps_version_and_sends_did_open() {
         let calls = Rc::new(RefCell::new(Vec::new()));
@@ -18,6 +18,9 @@ pub(crate) const GUTTER_WIDTH: f32 = 45.0;
 /// Width in pixels of the fold margin (chevron column) added to the gutter when
 /// code folding is enabled.
 pub(crate) const FOLD_MARGIN_WIDTH: f32 = 14.0;
+/// Width in pixels of the breakpoint margin added at the left edge of the
+/// gutter when breakpoints are enabled.
+pub(crate) const BREAKPOINT_MARGIN_WIDTH: f32 = 16.0;
 pub(crate) const CURSOR_BLINK_INTERVAL: std::time::Duration =
     std::time::Duration::from_millis(530);
 
@@ -473,13 +476,29 @@ impl CodeEditor {
         self
     }
 
-    /// Returns the total gutter width, including the line-number area and the
-    /// fold margin.
+    /// Returns the total gutter width, including the breakpoint margin, the
+    /// line-number area and the fold margin.
     ///
     /// The fold margin is added when folding is enabled, independently of line
     /// numbers, so fold chevrons remain clickable even without line numbers.
+    /// The breakpoint margin likewise depends only on breakpoints being
+    /// enabled.
     pub(crate) fn gutter_width(&self) -> f32 {
-        self.line_number_gutter_width() + self.fold_margin_width()
+        self.breakpoint_margin_width()
+            + self.line_number_gutter_width()
+            + self.fold_margin_width()
+    }
+
+    /// Returns the width of the breakpoint margin at the left edge of the
+    /// gutter, or `0.0` when breakpoints are disabled.
+    pub(crate) fn breakpoint_margin_width(&self) -> f32 {
+        if self.breakpoints_enabled { BREAKPOINT_MARGIN_WIDTH } else { 0.0 }
+    }
+
+    /// Returns the x coordinate where the fold margin starts, right after the
+    /// breakpoint margin and the line-number area.
+    pub(crate) fn fold_margin_start(&self) -> f32 {
+        self.breakpoint_margin_width() + self.line_number_gutter_width()
     }
 
     /// Returns the width of the line-number area (excluding the fold margin).
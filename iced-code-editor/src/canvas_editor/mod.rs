@@ -6,7 +6,7 @@
 use iced::widget::operation::{RelativeOffset, snap_to};
 use iced::widget::{Id, canvas};
 use std::cell::{Cell, RefCell};
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 use std::ops::Range;
 use std::rc::Rc;
 use std::sync::atomic::{AtomicU64, Ordering};
@@ -19,12 +19,18 @@ use crate::theme::Style;
 use caches::{
     BracketDepthCache, HighlightCache, MaxContentWidthCache, VisualLinesCache,
 };
+use editing::cursor::DEFAULT_WORD_SEPARATORS;
 use editing::cursor_set;
 pub use editing::history::CommandHistory;
-use features::{command_palette, folding, goto_line, search, vim};
+use features::{
+    breakpoints, clipboard_history, color_picker, column_selection,
+    command_palette, comment_tokens, document_color, document_highlight,
+    document_link, folding, goto_line, indent_rules, linked_editing, marks,
+    search, selection_range, snippet, vim,
+};
 use metrics::{
     CACHE_WINDOW_MARGIN_MULTIPLIER, CHAR_WIDTH, CURSOR_BLINK_INTERVAL,
-    FONT_SIZE, GUTTER_WIDTH, HIGHLIGHT_LINES_PER_FRAME, LINE_HEIGHT, TAB_WIDTH,
+    FONT_SIZE, HIGHLIGHT_LINES_PER_FRAME, LINE_HEIGHT, TAB_WIDTH,
     compare_floats, indent_width, measure_char_width, measure_text_width,
 };
 
@@ -43,16 +49,22 @@ static FOCUSED_EDITOR_ID: AtomicU64 = AtomicU64::new(0);
 pub mod bench_support;
 mod caches;
 mod config;
+pub mod dap;
 mod editing;
 pub(crate) mod features;
 mod focus;
+#[cfg(all(
+    any(feature = "lsp-process", feature = "dap-process"),
+    not(target_arch = "wasm32")
+))]
+mod framing;
 mod input;
 pub mod lsp;
 mod metrics;
 mod render;
 
 pub use features::context_menu::{ContextMenuEntry, ContextMenuItem};
-pub use features::vim::VimMode;
+pub use features::vim::{VimMapMode, VimMode};
 #[derive(Debug, Clone)]
 pub(crate) struct ImePreedit {
     pub(crate) content: String,
@@ -153,10 +165,23 @@ pub struct CodeEditor {
     pub(crate) wrap_enabled: bool,
     /// Auto-indentation enabled
     pub(crate) auto_indent_enabled: bool,
+    /// Indentation rules set by the host, keyed by lowercase syntax name;
+    /// they take precedence over the built-in ones.
+    pub(crate) indent_rules: HashMap<String, indent_rules::IndentRules>,
+    /// Whether pasted multi-line text is re-indented.
+    pub(crate) reindent_on_paste: bool,
+    /// Comment tokens set by the host, keyed by lowercase syntax name; they
+    /// take precedence over the built-in and grammar-provided ones.
+    pub(crate) comment_tokens: HashMap<String, comment_tokens::CommentTokens>,
+    /// Characters that end a word for word-wise moves and deletions, in
+    /// addition to whitespace.
+    pub(crate) word_separators: String,
     /// Auto-closing of brackets/quotes and surround-selection enabled
     pub(crate) auto_close_brackets: bool,
     /// Indentation style (spaces or tab)
     pub(crate) indent_style: IndentStyle,
+    /// Whether `new` and `reset` pick the indentation style from the content
+    pub(crate) detect_indentation: bool,
     /// Wrap column (None = wrap at viewport width)
     pub(crate) wrap_column: Option<usize>,
     /// Whether code folding (collapse/expand blocks) is enabled.
@@ -171,6 +196,12 @@ pub struct CodeEditor {
     /// Cached foldable regions, keyed by `buffer_revision`.
     pub(crate) foldable_regions_cache:
         RefCell<Option<(u64, Rc<Vec<folding::FoldRegion>>)>>,
+    /// Whether the breakpoint margin is shown and clickable.
+    pub(crate) breakpoints_enabled: bool,
+    /// Breakpoints and the current execution line
+    pub(crate) breakpoint_state: breakpoints::BreakpointState,
+    /// Vim marks and the jumplist shared with navigate back/forward
+    pub(crate) mark_state: marks::MarkState,
     /// Search state
     pub(crate) search_state: search::SearchState,
     /// Custom entries displayed before the built-in context-menu actions.
@@ -189,10 +220,14 @@ pub struct CodeEditor {
     default_command_palette_enabled: bool,
     /// Whether the command palette can be opened at all.
     pub(crate) command_palette_enabled: bool,
+    /// Recent copies and cuts, listed by the clipboard history picker.
+    pub(crate) clipboard_history: clipboard_history::ClipboardHistory,
     /// Whether Vim key handling is enabled for this editor instance.
     vim_enabled: bool,
     /// Per-editor Vim mode, parser prefixes and unnamed register.
     pub(crate) vim_state: vim::VimState,
+    /// Vim key mappings, the leader key and the keys waiting on a mapping.
+    pub(crate) vim_mappings: vim::VimMappings,
     /// Translations for UI text
     pub(crate) translations: Translations,
     /// Whether search/replace functionality is enabled
@@ -205,10 +240,33 @@ pub struct CodeEditor {
     pub(crate) show_indent_guides: bool,
     /// Whether inline color-preview swatches are drawn next to color literals.
     pub(crate) show_color_previews: bool,
+    /// Colors reported by the language server and their request state
+    pub(crate) document_color_state: document_color::DocumentColorState,
+    /// Color picker state
+    pub(crate) color_picker_state: color_picker::ColorPickerState,
     /// Whether the matching-bracket/quote-pair highlight overlay is enabled.
     pub(crate) bracket_match_highlight_enabled: bool,
     /// Whether bracket-pair colorization (rainbow brackets) is enabled.
     pub(crate) bracket_pair_colorization_enabled: bool,
+    /// Whether occurrences of the symbol under the cursor are highlighted
+    pub(crate) document_highlight_enabled: bool,
+    /// Occurrences of the symbol under the cursor and their debounce state
+    pub(crate) document_highlight_state:
+        document_highlight::DocumentHighlightState,
+    /// Expand/Shrink Selection session: replaced selections and server ranges
+    pub(crate) selection_range_state: selection_range::SelectionRangeState,
+    /// Whether edits to an HTML tag name are mirrored into its partner tag
+    pub(crate) linked_editing_enabled: bool,
+    /// Ranges currently edited together
+    pub(crate) linked_editing_state: linked_editing::LinkedEditingState,
+    /// Active column (box) selection, if any
+    pub(crate) column_selection: Option<column_selection::ColumnSelection>,
+    /// Snippet libraries, variables and the tabstop session in progress
+    pub(crate) snippet_state: snippet::SnippetState,
+    /// Whether URLs and file paths can be Ctrl+Clicked
+    pub(crate) document_links_enabled: bool,
+    /// Links reported by the language server and their request state
+    pub(crate) document_link_state: document_link::DocumentLinkState,
     /// Whether LSP support is enabled
     pub(crate) lsp_enabled: bool,
     /// Active LSP client connection, if configured.
@@ -334,6 +392,11 @@ pub enum Message {
     CharacterInput(char),
     /// A printable key interpreted by the Vim state machine.
     VimKey(char),
+    /// Clipboard text read for a Vim `"+p` or `"*p` paste.
+    VimClipboardPaste(String),
+    /// Recall an older (`true`) or newer (`false`) entry of the open Vim
+    /// `:` or `/` command line's history, bound to Up and Down.
+    VimCommandHistory(bool),
     /// Toggle Vim behavior for this editor instance.
     ToggleVimMode,
     /// Requests that the host save this editor's current document.
@@ -344,10 +407,15 @@ pub enum Message {
     Delete,
     /// Enter pressed
     Enter,
-    /// Tab pressed (inserts 4 spaces)
+    /// Tab pressed (inserts one level of indentation at each cursor)
     Tab,
     /// Arrow key pressed (direction, shift_pressed)
     ArrowKey(ArrowDirection, bool),
+    /// Ctrl+Left pressed: move to the previous word start (unit,
+    /// shift_pressed)
+    WordLeft(WordUnit, bool),
+    /// Ctrl+Right pressed: move past the next word end (unit, shift_pressed)
+    WordRight(WordUnit, bool),
     /// Mouse clicked at position
     MouseClick(iced::Point),
     /// Mouse drag for selection
@@ -374,6 +442,10 @@ pub enum Message {
     Paste(String),
     /// Delete selected text (Shift+Delete)
     DeleteSelection,
+    /// Ctrl+Backspace pressed: delete back to the previous word start
+    DeleteWordLeft(WordUnit),
+    /// Ctrl+Delete pressed: delete up to the next word end
+    DeleteWordRight(WordUnit),
     /// Select the complete document
     SelectAll,
     /// Request redraw for cursor blink
@@ -392,6 +464,12 @@ pub enum Message {
     CtrlEnd,
     /// Go to an explicit logical position (line, column), both 0-based.
     GotoPosition(usize, usize),
+    /// Alt+Left, or Ctrl+O in Vim's Normal mode: return to the position
+    /// before the last jump.
+    NavigateBack,
+    /// Alt+Right, or Ctrl+I in Vim's Normal mode: redo a jump undone by
+    /// [`Message::NavigateBack`].
+    NavigateForward,
     /// Open the go-to-line dialog (Cmd/Ctrl+G).
     OpenGotoLine,
     /// Close the go-to-line dialog.
@@ -412,6 +490,9 @@ pub enum Message {
     CommandPaletteSelected(usize),
     /// Run the currently highlighted command palette row.
     SubmitCommandPalette,
+    /// Open the command palette listing the clipboard history
+    /// (Cmd/Ctrl+Shift+V); running a row pastes that entry.
+    OpenClipboardHistory,
     /// A host-registered command palette entry was run, identified by the
     /// `id` it was registered with. The editor never acts on this itself:
     /// handle it in the host application.
@@ -458,6 +539,17 @@ pub enum Message {
     /// on the editor content, intending to jump to the definition of the symbol
     /// under the cursor.
     JumpClick(iced::Point),
+    /// Triggered when the user performs a Ctrl+Click (or Cmd+Click on macOS)
+    /// on a URL or file path, in place of [`Message::JumpClick`].
+    ///
+    /// The editor does not open anything itself: the host decides what the
+    /// target means, such as opening a URL in the browser or a path in a new
+    /// tab.
+    LinkActivated {
+        /// The URL, or the file path as written (possibly followed by
+        /// `:line` or `:line:column`).
+        target: String,
+    },
     /// IME input method opened
     ImeOpened,
     /// IME pre-edit update (content, selection range)
@@ -468,6 +560,11 @@ pub enum Message {
     ImeClosed,
     /// Alt+Click: add a new cursor at the given canvas position
     AltClick(iced::Point),
+    /// Alt+Shift+Click: start a column (box) selection at the given canvas
+    /// position, extended by dragging
+    ColumnSelectClick(iced::Point),
+    /// Ctrl+Alt+Shift+Arrow: extend the column (box) selection
+    ColumnSelect(ArrowDirection),
     /// Ctrl+Alt+Up: add a cursor on the line above the primary cursor
     AddCursorAbove,
     /// Ctrl+Alt+Down: add a cursor on the line below the primary cursor
@@ -492,6 +589,82 @@ pub enum Message {
     DuplicateLineDown,
     /// Ctrl+/: toggle line comments on the current line or primary selection.
     ToggleComment,
+    /// Shift+Alt+A: wrap the primary selection (or the current line) in
+    /// block-comment tokens, or unwrap it when it already is one.
+    ToggleBlockComment,
+    /// Re-indent the current line or the lines of the primary selection
+    /// using the syntax's indentation rules.
+    ReindentLines,
+    /// Sort the selected lines, or the whole document without a selection.
+    SortLines(SortOrder),
+    /// Reverse the order of the selected lines, or of the whole document
+    /// without a selection.
+    ReverseLines,
+    /// Remove repeated lines from the selection, or from the whole document
+    /// without a selection, keeping the first occurrence of each.
+    RemoveDuplicateLines,
+    /// Join the selected lines, or each cursor's line with the next one.
+    JoinLines,
+    /// Swap the characters around each cursor.
+    TransposeCharacters,
+    /// Convert each selection, or the word under each cursor, to a case.
+    ConvertCase(TextCase),
+    /// Remove trailing whitespace from the selected lines, or from the whole
+    /// document without a selection.
+    TrimTrailingWhitespace,
+    /// Add the given amount to the number under or after each cursor
+    /// (negative to decrement).
+    IncrementNumber(i64),
+    /// Indent the selected lines, or each cursor's line, by one level
+    /// (Tab with a multi-line selection).
+    IndentLines,
+    /// Outdent the selected lines, or each cursor's line, by one level
+    /// (Shift+Tab with a multi-line selection).
+    OutdentLines,
+    /// Convert the whole document's indentation to spaces.
+    ConvertIndentationToSpaces,
+    /// Convert the whole document's indentation to tabs.
+    ConvertIndentationToTabs,
+    /// F7: move to the next occurrence of the symbol under the cursor.
+    NextDocumentHighlight,
+    /// Shift+F7: move to the previous occurrence of the symbol under the cursor.
+    PreviousDocumentHighlight,
+    /// Shift+Alt+Right: grow the selection to the next enclosing syntactic range.
+    ExpandSelection,
+    /// Shift+Alt+Left: restore the selection the last expansion replaced.
+    ShrinkSelection,
+    /// Click on a color-preview swatch: open the color picker on the literal
+    /// starting at the given line and column.
+    OpenColorPicker(usize, usize),
+    /// Close the color picker, keeping the picked color as one undoable edit.
+    CloseColorPicker,
+    /// Color picker square dragged to the given saturation and value
+    /// (`0.0..=1.0`).
+    ColorPickerSaturationValue(f32, f32),
+    /// Color picker hue bar dragged to the given hue, in degrees.
+    ColorPickerHue(f32),
+    /// Color picker alpha bar dragged to the given opacity (`0.0..=1.0`).
+    ColorPickerAlpha(f32),
+    /// Color picker hex input changed.
+    ColorPickerHexChanged(String),
+    /// Color picker: rewrite the color with the language server's
+    /// presentation at the given index.
+    ColorPickerPresentation(usize),
+    /// Click in the breakpoint margin: toggle the breakpoint on the given
+    /// logical line.
+    ToggleBreakpoint(usize),
+    /// F9: toggle the breakpoint on the cursor line.
+    ToggleBreakpointAtCursor,
+    /// Insert a snippet, in LSP/TextMate snippet syntax, at the cursor.
+    InsertSnippet(String),
+    /// Tab in a snippet session: select the next tabstop.
+    NextSnippetTabstop,
+    /// Shift+Tab in a snippet session: select the previous tabstop.
+    PreviousSnippetTabstop,
+    /// Replace the current choice tabstop with its option at the given index.
+    SelectSnippetChoice(usize),
+    /// Escape in a snippet session: stop tracking its tabstops.
+    LeaveSnippet,
 }
 
 /// Indentation style used when pressing the Tab key.
@@ -582,6 +755,80 @@ pub enum ArrowDirection {
     Right,
 }
 
+/// How far a word-wise move or deletion reaches.
+///
+/// # Example
+///
+/// ```
+/// use iced_code_editor::{CodeEditor, Message, WordUnit};
+///
+/// let mut editor = CodeEditor::new("parseHtml()", "rs");
+///
+/// // Alt+Ctrl+Right stops inside camelCase and snake_case names.
+/// let _ = editor.update(&Message::WordRight(WordUnit::SubWord, false));
+/// assert_eq!(editor.cursor_position(), (0, 5));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WordUnit {
+    /// A whole run of word characters or of punctuation (Ctrl+Arrow).
+    Word,
+    /// A part of a camelCase or snake_case name (Ctrl+Alt+Arrow).
+    SubWord,
+}
+
+/// The order [`Message::SortLines`] puts lines in.
+///
+/// # Example
+///
+/// ```
+/// use iced_code_editor::{CodeEditor, Message, SortOrder};
+///
+/// let mut editor = CodeEditor::new("file10\nfile9\nfile1", "txt");
+///
+/// // Without a selection the whole document is sorted.
+/// let _ = editor.update(&Message::SortLines(SortOrder::Natural));
+/// assert_eq!(editor.content(), "file1\nfile9\nfile10");
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SortOrder {
+    /// Character by character, `A` before `a`.
+    Ascending,
+    /// The reverse of [`SortOrder::Ascending`].
+    Descending,
+    /// Numbers by value and letters regardless of case (`file9` before
+    /// `file10`).
+    Natural,
+}
+
+/// The case [`Message::ConvertCase`] rewrites text to.
+///
+/// # Example
+///
+/// ```
+/// use iced_code_editor::{CodeEditor, Message, TextCase};
+///
+/// let mut editor = CodeEditor::new("maxWidth", "rs");
+///
+/// // Without a selection the word under the cursor is converted.
+/// let _ = editor.update(&Message::ConvertCase(TextCase::Snake));
+/// assert_eq!(editor.content(), "max_width");
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TextCase {
+    /// `UPPER CASE`.
+    Upper,
+    /// `lower case`.
+    Lower,
+    /// `Title Case`: the first letter of every word capitalized.
+    Title,
+    /// `snake_case`.
+    Snake,
+    /// `camelCase`.
+    Camel,
+    /// `kebab-case`.
+    Kebab,
+}
+
 impl CodeEditor {
     /// Creates a new canvas-based text editor.
     ///
@@ -638,13 +885,21 @@ impl CodeEditor {
             is_grouping: false,
             wrap_enabled: true,
             auto_indent_enabled: true,
+            indent_rules: HashMap::new(),
+            reindent_on_paste: false,
+            comment_tokens: HashMap::new(),
+            word_separators: DEFAULT_WORD_SEPARATORS.to_string(),
             auto_close_brackets: true,
             indent_style: IndentStyle::Spaces(4),
+            detect_indentation: true,
             wrap_column: None,
             folding_enabled: true,
             collapsed_folds: HashSet::new(),
             fold_revision: 0,
             foldable_regions_cache: RefCell::new(None),
+            breakpoints_enabled: false,
+            breakpoint_state: breakpoints::BreakpointState::default(),
+            mark_state: marks::MarkState::default(),
             search_state: search::SearchState::new(),
             custom_context_menu_entries: Vec::new(),
             default_context_menu_enabled: true,
@@ -654,16 +909,31 @@ impl CodeEditor {
             custom_command_palette_entries: Vec::new(),
             default_command_palette_enabled: true,
             command_palette_enabled: true,
+            clipboard_history: clipboard_history::ClipboardHistory::default(),
             vim_enabled: false,
             vim_state: vim::VimState::default(),
+            vim_mappings: vim::VimMappings::default(),
             translations: Translations::default(),
             search_replace_enabled: true,
             line_numbers_enabled: true,
             show_whitespace: true,
             show_indent_guides: true,
             show_color_previews: true,
+            document_color_state: document_color::DocumentColorState::default(),
+            color_picker_state: color_picker::ColorPickerState::default(),
             bracket_match_highlight_enabled: true,
             bracket_pair_colorization_enabled: true,
+            document_highlight_enabled: true,
+            document_highlight_state:
+                document_highlight::DocumentHighlightState::default(),
+            selection_range_state:
+                selection_range::SelectionRangeState::default(),
+            linked_editing_enabled: true,
+            linked_editing_state: linked_editing::LinkedEditingState::default(),
+            column_selection: None,
+            snippet_state: snippet::SnippetState::default(),
+            document_links_enabled: true,
+            document_link_state: document_link::DocumentLinkState::default(),
             lsp_enabled: true,
             lsp_client: None,
             lsp_document: None,
@@ -702,6 +972,7 @@ impl CodeEditor {
 
         // Perform initial character dimension calculation
         editor.recalculate_char_dimensions(false);
+        editor.apply_detected_indent_style();
 
         editor
     }
@@ -764,8 +1035,21 @@ impl CodeEditor {
     /// ```
     pub fn reset(&mut self, content: &str) -> iced::Task<Message> {
         self.buffer = TextBuffer::new(content);
+        self.apply_detected_indent_style();
         self.cursors.set_single((0, 0));
         self.vim_state.reset();
+        self.vim_mappings.clear_typeahead();
+        self.document_highlight_state.clear();
+        self.selection_range_state.clear();
+        self.linked_editing_state.ranges.clear();
+        self.column_selection = None;
+        self.snippet_state.session = None;
+        self.document_link_state.clear();
+        self.document_color_state.clear();
+        self.color_picker_state.target = None;
+        self.clear_breakpoints();
+        self.breakpoint_state.execution_line = None;
+        self.mark_state = marks::MarkState::default();
         self.horizontal_scroll_offset = 0.0;
         self.is_dragging = false;
         self.viewport_scroll = 0.0;
@@ -4,18 +4,13 @@ use iced::mouse;
 use iced::widget::canvas::{self, Action, Geometry};
 use iced::{Event, Rectangle, Theme, keyboard};
 use std::rc::Rc;
-use std::sync::OnceLock;
-use syntect::highlighting::ThemeSet;
-use syntect::parsing::SyntaxSet;
 
+use super::syntax;
 use super::text::RenderContext;
 use super::wrapping::VisualLine;
 use crate::canvas_editor::features::color_preview;
 use crate::canvas_editor::{CodeEditor, HIGHLIGHT_LINES_PER_FRAME, Message};
 
-static SYNTAX_SET: OnceLock<SyntaxSet> = OnceLock::new();
-static THEME_SET: OnceLock<ThemeSet> = OnceLock::new();
-
 impl canvas::Program<Message> for CodeEditor {
     type State = ();
 
@@ -82,36 +77,10 @@ impl canvas::Program<Message> for CodeEditor {
                 // blocking the UI while parsing every preceding line.
                 self.highlight_lines_remaining.set(HIGHLIGHT_LINES_PER_FRAME);
 
-                // syntect initialization is relatively expensive; keep it global.
-                let syntax_set = SYNTAX_SET.get_or_init(|| {
-                    #[cfg(feature = "two-face")]
-                    {
-                        two_face::syntax::extra_newlines()
-                    }
-                    #[cfg(not(feature = "two-face"))]
-                    {
-                        SyntaxSet::load_defaults_newlines()
-                    }
-                });
-                let theme_set = THEME_SET.get_or_init(ThemeSet::load_defaults);
-                let syntax_theme = theme_set
-                    .themes
-                    .get("base16-ocean.dark")
-                    .or_else(|| theme_set.themes.values().next());
-
-                // Normalize common language aliases/extensions used by consumers.
-                let syntax_ref = match self.syntax.as_str() {
-                    "python" => syntax_set.find_syntax_by_extension("py"),
-                    "rust" => syntax_set.find_syntax_by_extension("rs"),
-                    "javascript" => syntax_set.find_syntax_by_extension("js"),
-                    "htm" => syntax_set.find_syntax_by_extension("html"),
-                    "svg" => syntax_set.find_syntax_by_extension("xml"),
-                    "markdown" => syntax_set.find_syntax_by_extension("md"),
-                    "text" => Some(syntax_set.find_syntax_plain_text()),
-                    _ => syntax_set
-                        .find_syntax_by_extension(self.syntax.as_str()),
-                }
-                .or(Some(syntax_set.find_syntax_plain_text()));
+                let syntax_set = syntax::syntax_set();
+                let syntax_theme = syntax::syntax_theme();
+                let syntax_ref =
+                    Some(syntax::find_syntax(syntax_set, self.syntax.as_str()));
 
                 let ctx = RenderContext {
                     visual_lines: visual_lines_for_content.as_ref(),
@@ -162,7 +131,6 @@ impl canvas::Program<Message> for CodeEditor {
                         self.draw_bracket_pair_colors(f, &ctx, visual_line, y);
                         self.draw_color_swatches(
                             f,
-                            &ctx,
                             visual_line,
                             y,
                             &mut color_literals,
@@ -212,6 +180,12 @@ impl canvas::Program<Message> for CodeEditor {
                     .take(end_idx.saturating_sub(start_idx))
                 {
                     let y = idx as f32 * self.line_height;
+                    self.draw_execution_line_highlight(
+                        frame,
+                        &ctx,
+                        visual_line,
+                        y,
+                    );
                     self.draw_current_line_highlight(
                         frame,
                         &ctx,
@@ -220,6 +194,7 @@ impl canvas::Program<Message> for CodeEditor {
                     );
                 }
 
+                self.draw_document_highlights(frame, &ctx);
                 self.draw_search_highlights(frame, &ctx, start_idx, end_idx);
                 self.draw_matching_bracket_highlight(frame, &ctx);
                 self.draw_selection_highlight(frame, &ctx);
@@ -302,7 +277,9 @@ impl canvas::Program<Message> for CodeEditor {
             return mouse::Interaction::default();
         };
 
-        if self.fold_header_at_point(position).is_some() {
+        if self.breakpoint_line_at_point(position).is_some()
+            || self.fold_header_at_point(position).is_some()
+        {
             mouse::Interaction::Pointer
         } else if position.x >= self.gutter_width() {
             mouse::Interaction::Text
@@ -1,5 +1,5 @@
-//! Gutter rendering: line numbers, wrap indicators, and fold chevrons for
-//! [`CodeEditor`].
+//! Gutter rendering: breakpoint markers, line numbers, wrap indicators, and
+//! fold chevrons for [`CodeEditor`].
 
 use iced::Point;
 use iced::widget::canvas;
@@ -10,7 +10,8 @@ use crate::canvas_editor::features::folding;
 use crate::canvas_editor::{CodeEditor, measure_text_width};
 
 impl CodeEditor {
-    /// Draws line numbers and wrap indicators in the gutter area.
+    /// Draws breakpoint markers, line numbers and wrap indicators in the
+    /// gutter area.
     ///
     /// # Arguments
     ///
@@ -25,10 +26,16 @@ impl CodeEditor {
         visual_line: &VisualLine,
         y: f32,
     ) {
-        // The line-number area is the left part of the gutter; the fold margin
-        // (when folding is enabled) is the right strip adjacent to the text.
+        // The breakpoint margin (when enabled) is the leftmost strip of the
+        // gutter, followed by the line-number area; the fold margin (when
+        // folding is enabled) is the right strip adjacent to the text.
+        let number_area_start = self.breakpoint_margin_width();
         let number_area_width = self.line_number_gutter_width();
 
+        if self.breakpoints_enabled && visual_line.is_first_segment() {
+            self.draw_breakpoint_marker(frame, visual_line.logical_line, y);
+        }
+
         if self.line_numbers_enabled {
             if visual_line.is_first_segment() {
                 // Draw line number for first segment, centered in the number area.
@@ -39,7 +46,8 @@ impl CodeEditor {
                     ctx.full_char_width,
                     ctx.char_width,
                 );
-                let x_pos = (number_area_width - text_width) / 2.0;
+                let x_pos =
+                    number_area_start + (number_area_width - text_width) / 2.0;
                 frame.fill_text(canvas::Text {
                     content: line_num_text,
                     position: Point::new(x_pos, y + 2.0),
@@ -52,7 +60,10 @@ impl CodeEditor {
                 // Draw wrap indicator for continuation lines.
                 frame.fill_text(canvas::Text {
                     content: "↪".to_string(),
-                    position: Point::new(number_area_width - 20.0, y + 2.0),
+                    position: Point::new(
+                        number_area_start + number_area_width - 20.0,
+                        y + 2.0,
+                    ),
                     color: self.style.line_number_color,
                     size: ctx.font_size.into(),
                     font: ctx.font,
@@ -61,7 +72,13 @@ impl CodeEditor {
             }
         }
 
-        self.draw_fold_chevron(frame, ctx, visual_line, y, number_area_width);
+        self.draw_fold_chevron(
+            frame,
+            ctx,
+            visual_line,
+            y,
+            self.fold_margin_start(),
+        );
     }
 
     /// Draws the fold chevron in the fold margin for a foldable header line.
@@ -75,14 +92,14 @@ impl CodeEditor {
     /// * `ctx` - Rendering context containing metrics
     /// * `visual_line` - The visual line to render
     /// * `y` - Y position for rendering
-    /// * `number_area_width` - Width of the line-number area (start of the fold margin)
+    /// * `margin_start` - X coordinate where the fold margin starts
     fn draw_fold_chevron(
         &self,
         frame: &mut canvas::Frame,
         ctx: &RenderContext,
         visual_line: &VisualLine,
         y: f32,
-        number_area_width: f32,
+        margin_start: f32,
     ) {
         if !self.folding_enabled || !visual_line.is_first_segment() {
             return;
@@ -101,7 +118,7 @@ impl CodeEditor {
         };
         frame.fill_text(canvas::Text {
             content: chevron.to_string(),
-            position: Point::new(number_area_width + 1.0, y + 2.0),
+            position: Point::new(margin_start + 1.0, y + 2.0),
             color: self.style.line_number_color,
             size: ctx.font_size.into(),
             font: ctx.font,
@@ -5,6 +5,7 @@
 mod canvas;
 pub(crate) mod gutter;
 pub(crate) mod overlays;
+pub(crate) mod syntax;
 pub(crate) mod text;
 pub(crate) mod view;
 pub(crate) mod wrapping;
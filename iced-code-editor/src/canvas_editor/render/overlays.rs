@@ -1,5 +1,5 @@
 //! Overlay-layer rendering: current-line highlight, search/selection/bracket
-//! highlights, and cursor/caret drawing for [`CodeEditor`].
+//! and document highlights, and cursor/caret drawing for [`CodeEditor`].
 
 use iced::mouse;
 use iced::widget::canvas;
@@ -7,8 +7,10 @@ use iced::{Color, Point, Rectangle, Size};
 
 use super::text::{RenderContext, calculate_segment_geometry};
 use super::wrapping::{VisualLine, WrappingCalculator};
+use crate::canvas_editor::features::document_link::LineLinks;
 use crate::canvas_editor::features::vim::VimMode;
 use crate::canvas_editor::features::{bracket_match, search};
+use crate::canvas_editor::lsp::LspDocumentHighlightKind;
 use crate::canvas_editor::{
     CodeEditor, measure_char_width, measure_text_width,
 };
@@ -39,6 +41,33 @@ impl CodeEditor {
         }
     }
 
+    /// Draws the highlight of the line where the debuggee is stopped.
+    ///
+    /// Covers every visual segment of the line set with
+    /// [`CodeEditor::set_execution_line`], beneath the current-line highlight.
+    ///
+    /// # Arguments
+    ///
+    /// * `frame` - The canvas frame to draw on
+    /// * `ctx` - Rendering context containing visual lines and metrics
+    /// * `visual_line` - The visual line to check
+    /// * `y` - Y position for rendering
+    pub(super) fn draw_execution_line_highlight(
+        &self,
+        frame: &mut canvas::Frame,
+        ctx: &RenderContext,
+        visual_line: &VisualLine,
+        y: f32,
+    ) {
+        if self.execution_line() == Some(visual_line.logical_line) {
+            frame.fill_rectangle(
+                Point::new(ctx.gutter_width, y),
+                Size::new(ctx.bounds_width - ctx.gutter_width, ctx.line_height),
+                self.style.execution_line_highlight,
+            );
+        }
+    }
+
     /// Fills a single highlight rectangle for a column range within one visual
     /// line.
     ///
@@ -204,23 +233,26 @@ impl CodeEditor {
         }
     }
 
-    /// Draws the selection highlight for a single cursor range.
+    /// Fills the background of a buffer range, which may span wrapped
+    /// segments and several logical lines.
+    ///
+    /// Used for selections and document highlights.
     ///
     /// # Arguments
     ///
     /// * `frame` - The canvas frame to draw on
     /// * `ctx` - Rendering context containing visual lines and metrics
-    /// * `start` - Selection start (line, col)
-    /// * `end` - Selection end (line, col), must be >= start
-    fn draw_single_selection(
+    /// * `start` - Range start (line, col)
+    /// * `end` - Range end (line, col), must be >= start
+    /// * `color` - Fill color of the range
+    fn draw_range_highlight(
         &self,
         frame: &mut canvas::Frame,
         ctx: &RenderContext,
         start: (usize, usize),
         end: (usize, usize),
+        color: Color,
     ) {
-        let selection_color = self.style.selection_color;
-
         if start.0 == end.0 {
             // Single line selection - need to handle wrapped segments
             let start_visual = WrappingCalculator::logical_to_visual(
@@ -244,7 +276,7 @@ impl CodeEditor {
                         start_v,
                         vl,
                         (start.1, end.1),
-                        selection_color,
+                        color,
                     );
                 } else {
                     // Selection spans multiple visual lines (same logical line)
@@ -269,7 +301,7 @@ impl CodeEditor {
                             v_idx,
                             vl,
                             (sel_start_col, sel_end_col),
-                            selection_color,
+                            color,
                         );
                     }
                 }
@@ -315,13 +347,49 @@ impl CodeEditor {
                         v_idx,
                         vl,
                         (sel_start_col, sel_end_col),
-                        selection_color,
+                        color,
                     );
                 }
             }
         }
     }
 
+    /// Draws the occurrences of the symbol under the cursor, write accesses
+    /// in their own color.
+    ///
+    /// # Arguments
+    ///
+    /// * `frame` - The canvas frame to draw on
+    /// * `ctx` - Rendering context containing visual lines and metrics
+    pub(super) fn draw_document_highlights(
+        &self,
+        frame: &mut canvas::Frame,
+        ctx: &RenderContext,
+    ) {
+        if !self.document_highlight_enabled {
+            return;
+        }
+
+        for occurrence in &self.document_highlight_state.occurrences {
+            let color = match occurrence.kind {
+                LspDocumentHighlightKind::Write => {
+                    self.style.document_highlight_write_color
+                }
+                LspDocumentHighlightKind::Read
+                | LspDocumentHighlightKind::Text => {
+                    self.style.document_highlight_read_color
+                }
+            };
+            self.draw_range_highlight(
+                frame,
+                ctx,
+                occurrence.start,
+                occurrence.end,
+                color,
+            );
+        }
+    }
+
     /// Draws a highlight around the bracket or quote pair touching the
     /// primary cursor, if any.
     ///
@@ -381,9 +449,71 @@ impl CodeEditor {
             if let Some((start, end)) = cursor.selection_range()
                 && start != end
             {
-                self.draw_single_selection(frame, ctx, start, end);
+                self.draw_range_highlight(
+                    frame,
+                    ctx,
+                    start,
+                    end,
+                    self.style.selection_color,
+                );
             }
         }
+        self.draw_column_selection_virtual_space(frame, ctx);
+    }
+
+    /// Draws the part of a column selection that lies past the end of
+    /// shorter lines, which no cursor's selection covers.
+    ///
+    /// # Arguments
+    ///
+    /// * `frame` - The canvas frame to draw on
+    /// * `ctx` - Rendering context containing visual lines and metrics
+    fn draw_column_selection_virtual_space(
+        &self,
+        frame: &mut canvas::Frame,
+        ctx: &RenderContext,
+    ) {
+        let Some(selection) = self.column_selection else {
+            return;
+        };
+        for line in selection.lines() {
+            let line_len = self.buffer.line_len(line);
+            let line_end = self.visual_column(line, line_len);
+            if selection.right() <= line_end {
+                continue;
+            }
+            let Some(visual_idx) = WrappingCalculator::logical_to_visual(
+                ctx.visual_lines,
+                line,
+                line_len,
+            ) else {
+                continue;
+            };
+            let vl = &ctx.visual_lines[visual_idx];
+            let (end_x, _) = calculate_segment_geometry(
+                self.buffer.line(line),
+                vl.start_col,
+                line_len,
+                line_len,
+                ctx.gutter_width + 5.0,
+                ctx.full_char_width,
+                ctx.char_width,
+            );
+            let start = selection.left().max(line_end) - line_end;
+            let end = selection.right() - line_end;
+            frame.fill_rectangle(
+                Point::new(
+                    end_x - ctx.horizontal_scroll_offset
+                        + start as f32 * ctx.char_width,
+                    visual_idx as f32 * ctx.line_height + 2.0,
+                ),
+                Size::new(
+                    (end - start) as f32 * ctx.char_width,
+                    ctx.line_height - 4.0,
+                ),
+                self.style.selection_color,
+            );
+        }
     }
 
     /// Draws the cursor (normal caret or IME preedit cursor).
@@ -618,7 +748,10 @@ impl CodeEditor {
                 ctx.full_char_width,
                 ctx.char_width,
             );
-            let cursor_x = cursor_x_content - ctx.horizontal_scroll_offset;
+            // A column-selection caret past the end of its line is drawn
+            // in virtual space, lined up with the others.
+            let cursor_x = cursor_x_content - ctx.horizontal_scroll_offset
+                + self.column_virtual_space(position) as f32 * ctx.char_width;
             let cursor_y = cursor_visual as f32 * ctx.line_height;
 
             let cursor_size = self.cursor_size_for_position(position);
@@ -635,7 +768,8 @@ impl CodeEditor {
         }
     }
 
-    /// Draws underlines for jumpable links when modifier is held.
+    /// Draws underlines for jumpable links when modifier is held: the URL or
+    /// file path under the mouse, or else the word under it.
     pub(super) fn draw_jump_link_highlight(
         &self,
         frame: &mut canvas::Frame,
@@ -659,8 +793,19 @@ impl CodeEditor {
         if let Some((line, col)) = self.calculate_cursor_from_point(point) {
             let line_content = self.buffer.line(line);
 
-            let start_col = Self::word_start_in_line(line_content, col);
-            let end_col = Self::word_end_in_line(line_content, col);
+            // A link is underlined whole; anywhere else, the word is what a
+            // click would jump from.
+            let (start_col, end_col) = self
+                .document_link_at(&mut LineLinks::default(), line, col)
+                .map_or_else(
+                    || {
+                        (
+                            Self::word_start_in_line(line_content, col),
+                            Self::word_end_in_line(line_content, col),
+                        )
+                    },
+                    |link| (link.start_col, link.end_col),
+                );
 
             if start_col >= end_col {
                 return;
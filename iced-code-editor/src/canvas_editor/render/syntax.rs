@@ -0,0 +1,198 @@
+//! The editor's syntect pipeline, shared by the canvas and the popups.
+//!
+//! Owns the process-wide syntax and theme sets, resolves the language names
+//! hosts pass to [`CodeEditor::new`](crate::CodeEditor::new) to a syntect
+//! syntax, and highlights standalone snippets (such as the fenced code blocks
+//! of an LSP hover) with the same grammar and colors the buffer is drawn with.
+
+use iced::Color;
+use std::sync::OnceLock;
+#[cfg(all(feature = "lsp-process", not(target_arch = "wasm32")))]
+use syntect::easy::HighlightLines;
+use syntect::highlighting::{Style, Theme, ThemeSet};
+use syntect::parsing::{SyntaxReference, SyntaxSet};
+
+/// Name of the syntect theme the editor highlights with.
+const SYNTAX_THEME_NAME: &str = "base16-ocean.dark";
+
+static SYNTAX_SET: OnceLock<SyntaxSet> = OnceLock::new();
+static THEME_SET: OnceLock<ThemeSet> = OnceLock::new();
+
+/// Returns the global syntax set, loading it on first use.
+///
+/// syntect initialization is relatively expensive, so it happens once per
+/// process rather than per editor or per frame.
+pub(crate) fn syntax_set() -> &'static SyntaxSet {
+    SYNTAX_SET.get_or_init(|| {
+        #[cfg(feature = "two-face")]
+        {
+            two_face::syntax::extra_newlines()
+        }
+        #[cfg(not(feature = "two-face"))]
+        {
+            SyntaxSet::load_defaults_newlines()
+        }
+    })
+}
+
+/// Returns the highlighting theme, loading the theme set on first use.
+///
+/// Falls back to any bundled theme if the preferred one is missing, and to
+/// `None` only if the set is empty.
+pub(crate) fn syntax_theme() -> Option<&'static Theme> {
+    let theme_set = THEME_SET.get_or_init(ThemeSet::load_defaults);
+    theme_set
+        .themes
+        .get(SYNTAX_THEME_NAME)
+        .or_else(|| theme_set.themes.values().next())
+}
+
+/// Resolves a language name or file extension to a syntax in `syntax_set`.
+///
+/// Accepts the same names as [`CodeEditor::new`](crate::CodeEditor::new):
+/// extensions (`"rs"`, `"py"`), the common aliases hosts tend to use
+/// (`"rust"`, `"python"`), and — for Markdown fence tags such as `"Lua"` —
+/// syntect's own case-insensitive syntax names. Anything unknown resolves to
+/// plain text, so the result is always usable.
+pub(crate) fn find_syntax<'s>(
+    syntax_set: &'s SyntaxSet,
+    name: &str,
+) -> &'s SyntaxReference {
+    match name {
+        "python" => syntax_set.find_syntax_by_extension("py"),
+        "rust" => syntax_set.find_syntax_by_extension("rs"),
+        "javascript" => syntax_set.find_syntax_by_extension("js"),
+        "htm" => syntax_set.find_syntax_by_extension("html"),
+        "svg" => syntax_set.find_syntax_by_extension("xml"),
+        "markdown" => syntax_set.find_syntax_by_extension("md"),
+        "text" => Some(syntax_set.find_syntax_plain_text()),
+        _ => syntax_set
+            .find_syntax_by_extension(name)
+            .or_else(|| syntax_set.find_syntax_by_token(name)),
+    }
+    .unwrap_or_else(|| syntax_set.find_syntax_plain_text())
+}
+
+/// Converts a syntect highlight [`Style`] into an iced [`Color`].
+///
+/// Only the foreground color is used; alpha is left fully opaque.
+///
+/// # Arguments
+///
+/// * `style` - The syntect style whose foreground color is converted.
+pub(crate) fn color_from_style(style: Style) -> Color {
+    Color::from_rgb(
+        f32::from(style.foreground.r) / 255.0,
+        f32::from(style.foreground.g) / 255.0,
+        f32::from(style.foreground.b) / 255.0,
+    )
+}
+
+/// Highlights a standalone multi-line snippet as `language`.
+///
+/// Lines are tokenized in order with one highlighter, so constructs spanning
+/// several lines (block comments, raw strings) keep their color across the
+/// whole snippet, exactly as they would in the buffer. Unlike the canvas this
+/// does no caching: snippets are small and highlighted once per popup.
+///
+/// # Arguments
+///
+/// * `code` - The snippet, with `\n` line separators.
+/// * `language` - A language name or extension, resolved by [`find_syntax`].
+///
+/// # Returns
+///
+/// One entry per line of `code`, each holding the line's colored spans in
+/// order. A line with no text yields an empty span list.
+#[cfg(all(feature = "lsp-process", not(target_arch = "wasm32")))]
+pub(crate) fn highlight_snippet(
+    code: &str,
+    language: &str,
+) -> Vec<Vec<(Color, String)>> {
+    let syntax_set = syntax_set();
+    let syntax = find_syntax(syntax_set, language);
+    let Some(theme) = syntax_theme() else {
+        return code
+            .lines()
+            .map(|line| vec![(Color::WHITE, line.to_string())])
+            .collect();
+    };
+
+    let mut highlighter = HighlightLines::new(syntax, theme);
+    code.lines()
+        .map(|line| {
+            // syntect's `_newlines` syntaxes expect a trailing '\n' for
+            // correct end-of-line context handling.
+            let line = format!("{line}\n");
+            highlighter
+                .highlight_line(&line, syntax_set)
+                .unwrap_or_else(|_| vec![(Style::default(), line.as_str())])
+                .into_iter()
+                .filter_map(|(style, text)| {
+                    let text = text.strip_suffix('\n').unwrap_or(text);
+                    (!text.is_empty())
+                        .then(|| (color_from_style(style), text.to_string()))
+                })
+                .collect()
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_find_syntax_resolves_aliases_extensions_and_names() {
+        let syntax_set = syntax_set();
+        assert_eq!(find_syntax(syntax_set, "rust").name, "Rust");
+        assert_eq!(find_syntax(syntax_set, "rs").name, "Rust");
+        assert_eq!(find_syntax(syntax_set, "python").name, "Python");
+        // Fence tags are often a capitalized syntax name, not an extension.
+        assert_eq!(find_syntax(syntax_set, "Lua").name, "Lua");
+    }
+
+    #[test]
+    fn test_find_syntax_falls_back_to_plain_text() {
+        let syntax_set = syntax_set();
+        assert_eq!(
+            find_syntax(syntax_set, "no-such-language").name,
+            syntax_set.find_syntax_plain_text().name
+        );
+    }
+
+    #[cfg(all(feature = "lsp-process", not(target_arch = "wasm32")))]
+    #[test]
+    fn test_highlight_snippet_keeps_line_structure_and_text() {
+        let lines = highlight_snippet("fn main() {\n\n    let x = 1;\n}", "rs");
+        assert_eq!(lines.len(), 4);
+        assert!(lines[1].is_empty(), "an empty line has no spans");
+
+        let text: Vec<String> = lines
+            .iter()
+            .map(|spans| spans.iter().map(|(_, s)| s.as_str()).collect())
+            .collect();
+        assert_eq!(text, ["fn main() {", "", "    let x = 1;", "}"]);
+    }
+
+    #[cfg(all(feature = "lsp-process", not(target_arch = "wasm32")))]
+    #[test]
+    fn test_highlight_snippet_colors_tokens_differently() {
+        let lines = highlight_snippet("let x = \"s\";", "rs");
+        let colors: Vec<Color> = lines[0].iter().map(|(c, _)| *c).collect();
+        assert!(
+            colors.windows(2).any(|pair| pair[0] != pair[1]),
+            "a keyword and a string literal should not share one color"
+        );
+    }
+
+    #[cfg(all(feature = "lsp-process", not(target_arch = "wasm32")))]
+    #[test]
+    fn test_highlight_snippet_carries_state_across_lines() {
+        // The second line sits inside a block comment opened on the first,
+        // so it must be colored like the comment, not like code.
+        let lines = highlight_snippet("/* open\nlet x = 1; */", "rs");
+        let comment_color = lines[0][0].0;
+        assert!(lines[1].iter().all(|(color, _)| *color == comment_color));
+    }
+}
@@ -2,7 +2,7 @@
 //! bracket-pair colorization for [`CodeEditor`].
 
 use iced::widget::canvas;
-use iced::{Color, Point, Size};
+use iced::{Color, Point, Rectangle, Size};
 use std::borrow::Cow;
 use std::rc::Rc;
 use syntect::easy::HighlightLines;
@@ -13,6 +13,7 @@ use syntect::parsing::{ParseState, ScopeStack, SyntaxSet};
 
 use crate::buffer::text_utils::char_range_to_byte_range;
 
+use super::syntax::color_from_style;
 use super::wrapping::VisualLine;
 use crate::canvas_editor::IndentStyle;
 use crate::canvas_editor::features::{
@@ -156,21 +157,6 @@ fn split_whitespace_segments(text: &str) -> Vec<(bool, &str)> {
     result
 }
 
-/// Converts a syntect highlight [`Style`] into an iced [`Color`].
-///
-/// Only the foreground color is used; alpha is left fully opaque.
-///
-/// # Arguments
-///
-/// * `style` - The syntect style whose foreground color is converted.
-fn color_from_style(style: Style) -> Color {
-    Color::from_rgb(
-        f32::from(style.foreground.r) / 255.0,
-        f32::from(style.foreground.g) / 255.0,
-        f32::from(style.foreground.b) / 255.0,
-    )
-}
-
 /// Tokenizes a full logical line into colored spans using syntect.
 ///
 /// The returned spans cover the entire line in order, each pairing an iced
@@ -577,10 +563,48 @@ impl CodeEditor {
         }
     }
 
+    /// Returns the bounds of the swatch drawn after a color literal ending at
+    /// `end_col`, in canvas coordinates.
+    ///
+    /// Shared by the renderer and by the click hit-test that opens the color
+    /// picker, so a click lands exactly where the square is drawn.
+    ///
+    /// # Arguments
+    ///
+    /// * `visual_line` - The visual line holding the literal's last character
+    /// * `y` - Y position of that visual line
+    /// * `end_col` - Column one past the literal's last character
+    pub(crate) fn color_swatch_bounds(
+        &self,
+        visual_line: &VisualLine,
+        y: f32,
+        end_col: usize,
+    ) -> Rectangle {
+        let side = (self.line_height * SWATCH_SIZE_RATIO).floor().max(1.0);
+        let (x, _width) = calculate_segment_geometry(
+            self.buffer.line(visual_line.logical_line),
+            visual_line.start_col,
+            end_col,
+            end_col,
+            self.gutter_width() + 5.0,
+            self.full_char_width,
+            self.char_width,
+        );
+
+        Rectangle::new(
+            Point::new(
+                x - self.horizontal_scroll_offset + SWATCH_GAP,
+                y + (self.line_height - side) / 2.0,
+            ),
+            Size::new(side, side),
+        )
+    }
+
     /// Draws the inline color-preview swatches for `visual_line`.
     ///
-    /// Every color literal found on the logical line gets a small square,
-    /// filled with the color it denotes, drawn just after it.
+    /// Every color literal found on the logical line — lexical, or reported
+    /// by the language server — gets a small square, filled with the color it
+    /// denotes, drawn just after it.
     /// The square is framed so that a color close to the editor background
     /// stays visible, and translucent colors are drawn over a background-filled
     /// square so their opacity reads correctly. No-op when the feature is
@@ -599,14 +623,12 @@ impl CodeEditor {
     /// # Arguments
     ///
     /// * `frame` - The canvas frame to draw on
-    /// * `ctx` - Rendering context containing visual lines and metrics
     /// * `visual_line` - The visual line to render
     /// * `y` - Y position for rendering
     /// * `literals` - Per-draw-pass memo of the logical lines already scanned
     pub(super) fn draw_color_swatches(
         &self,
         frame: &mut canvas::Frame,
-        ctx: &RenderContext,
         visual_line: &VisualLine,
         y: f32,
         literals: &mut color_preview::LineLiterals,
@@ -615,38 +637,26 @@ impl CodeEditor {
             return;
         }
 
-        let line_content = self.buffer.line(visual_line.logical_line);
-        let side = (ctx.line_height * SWATCH_SIZE_RATIO).floor().max(1.0);
-        let inner_side = (side - 2.0 * SWATCH_BORDER_WIDTH).max(1.0);
-
-        for literal in literals.get(&self.buffer, visual_line.logical_line) {
+        for placed in self.placed_colors(literals, visual_line.logical_line) {
+            let literal = placed.literal;
             if literal.end_col <= visual_line.start_col
                 || literal.end_col > visual_line.end_col
             {
                 continue;
             }
 
-            let (x, _width) = calculate_segment_geometry(
-                line_content,
-                visual_line.start_col,
-                literal.end_col,
-                literal.end_col,
-                ctx.gutter_width + 5.0,
-                ctx.full_char_width,
-                ctx.char_width,
-            );
-            let border_position = Point::new(
-                x - ctx.horizontal_scroll_offset + SWATCH_GAP,
-                y + (ctx.line_height - side) / 2.0,
-            );
+            let bounds =
+                self.color_swatch_bounds(visual_line, y, literal.end_col);
+            let inner_side =
+                (bounds.width - 2.0 * SWATCH_BORDER_WIDTH).max(1.0);
             let inner_position = Point::new(
-                border_position.x + SWATCH_BORDER_WIDTH,
-                border_position.y + SWATCH_BORDER_WIDTH,
+                bounds.x + SWATCH_BORDER_WIDTH,
+                bounds.y + SWATCH_BORDER_WIDTH,
             );
 
             frame.fill_rectangle(
-                border_position,
-                Size::new(side, side),
+                bounds.position(),
+                bounds.size(),
                 self.style.gutter_border,
             );
             for color in [self.style.background, literal.color] {
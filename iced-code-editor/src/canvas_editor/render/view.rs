@@ -10,12 +10,14 @@ use iced::{Background, Border, Color, Element, Length, Rectangle, Shadow};
 use iced_aw::ContextMenu;
 
 use super::wrapping::{self, WrappingCalculator};
+use crate::canvas_editor::features::color_picker::dialog as color_picker_dialog;
 use crate::canvas_editor::features::command_palette::dialog as command_palette_dialog;
 use crate::canvas_editor::features::context_menu;
 use crate::canvas_editor::features::goto_line::dialog as goto_line_dialog;
 use crate::canvas_editor::features::search::dialog as search_dialog;
+use crate::canvas_editor::features::snippet::dialog as snippet_dialog;
 use crate::canvas_editor::input::ime_requester::ImeRequester;
-use crate::canvas_editor::{CodeEditor, GUTTER_WIDTH, Message};
+use crate::canvas_editor::{CodeEditor, Message};
 use std::rc::Rc;
 
 /// Builds the transparent-container scrollable style shared by the canvas
@@ -138,21 +140,25 @@ impl CodeEditor {
         Some(h_scrollable.into())
     }
 
-    /// Creates the gutter background container if line numbers are enabled.
+    /// Creates the gutter background container if line numbers or the
+    /// breakpoint margin are enabled.
     ///
     /// # Returns
     ///
-    /// Some(container) if line numbers are enabled, None otherwise
+    /// Some(container) if line numbers or breakpoints are enabled, None
+    /// otherwise
     fn create_gutter_container(
         &self,
     ) -> Option<container::Container<'_, Message>> {
-        if self.line_numbers_enabled {
+        let width =
+            self.breakpoint_margin_width() + self.line_number_gutter_width();
+        if width > 0.0 {
             let gutter_background = self.style.gutter_background;
             Some(
                 container(
                     Space::new().width(Length::Fill).height(Length::Fill),
                 )
-                .width(Length::Fixed(GUTTER_WIDTH))
+                .width(Length::Fixed(width))
                 .height(Length::Fill)
                 .style(move |_| container::Style {
                     background: Some(Background::Color(gutter_background)),
@@ -184,7 +190,9 @@ impl CodeEditor {
 
     /// Creates the fixed Vim status and command line shown below the editor.
     fn create_vim_status_bar(&self) -> Element<'_, Message> {
-        let (left_text, right_text) = self.vim_state.status_line_text();
+        let (left_text, mut right_text) = self.vim_state.status_line_text();
+        // Keys waiting on a mapping show after the pending command keys.
+        right_text.push_str(&self.vim_mappings.pending_keys());
         let background = self.style.gutter_background;
         let text_color = self.style.text_color;
 
@@ -397,6 +405,44 @@ impl CodeEditor {
             editor_stack = editor_stack.push(positioned_dialog);
         }
 
+        // Add the color picker next to the swatch it was opened from.
+        if let Some(position) = self.color_picker_position() {
+            let picker = color_picker_dialog::view(&self.color_picker_state);
+            let positioned_picker = container(picker)
+                .padding(iced::Padding {
+                    top: position.y,
+                    left: position.x,
+                    ..iced::Padding::ZERO
+                })
+                .width(Length::Fill)
+                .height(Length::Fill);
+
+            editor_stack = editor_stack.push(positioned_picker);
+        }
+
+        // Add the options of a choice tabstop under the caret.
+        if let Some((choices, selected)) = self.snippet_choices()
+            && let Some(position) = self.snippet_choice_position()
+        {
+            let list = snippet_dialog::view(
+                choices,
+                selected,
+                self.font,
+                self.font_size,
+                self.char_width,
+            );
+            let positioned_list = container(list)
+                .padding(iced::Padding {
+                    top: position.y,
+                    left: position.x,
+                    ..iced::Padding::ZERO
+                })
+                .width(Length::Fill)
+                .height(Length::Fill);
+
+            editor_stack = editor_stack.push(positioned_list);
+        }
+
         // Add the command palette in the top center, above every other
         // dialog: opening it closes the others, so it is always innermost.
         if self.command_palette_state.is_open {
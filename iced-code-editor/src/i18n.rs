@@ -591,6 +591,427 @@ impl Translations {
         .into_owned()
     }
 
+    /// Returns the command-palette label for toggling a block comment.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use iced_code_editor::{Language, Translations};
+    ///
+    /// let en = Translations::new(Language::English);
+    /// assert_eq!(
+    ///     en.command_palette_toggle_block_comment(),
+    ///     "Toggle Block Comment"
+    /// );
+    /// ```
+    #[must_use]
+    pub fn command_palette_toggle_block_comment(&self) -> String {
+        rust_i18n::t!(
+            "command_palette.toggle_block_comment",
+            locale = self.language.to_locale()
+        )
+        .into_owned()
+    }
+
+    /// Returns the command-palette label for re-indenting lines.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use iced_code_editor::{Language, Translations};
+    ///
+    /// let en = Translations::new(Language::English);
+    /// assert_eq!(en.command_palette_reindent_lines(), "Reindent Lines");
+    /// ```
+    #[must_use]
+    pub fn command_palette_reindent_lines(&self) -> String {
+        rust_i18n::t!(
+            "command_palette.reindent_lines",
+            locale = self.language.to_locale()
+        )
+        .into_owned()
+    }
+
+    /// Returns the command-palette label for sorting lines in ascending order.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use iced_code_editor::{Language, Translations};
+    ///
+    /// let en = Translations::new(Language::English);
+    /// assert_eq!(en.command_palette_sort_lines_ascending(), "Sort Lines Ascending");
+    /// ```
+    #[must_use]
+    pub fn command_palette_sort_lines_ascending(&self) -> String {
+        rust_i18n::t!(
+            "command_palette.sort_lines_ascending",
+            locale = self.language.to_locale()
+        )
+        .into_owned()
+    }
+
+    /// Returns the command-palette label for sorting lines in descending order.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use iced_code_editor::{Language, Translations};
+    ///
+    /// let en = Translations::new(Language::English);
+    /// assert_eq!(en.command_palette_sort_lines_descending(), "Sort Lines Descending");
+    /// ```
+    #[must_use]
+    pub fn command_palette_sort_lines_descending(&self) -> String {
+        rust_i18n::t!(
+            "command_palette.sort_lines_descending",
+            locale = self.language.to_locale()
+        )
+        .into_owned()
+    }
+
+    /// Returns the command-palette label for sorting lines in natural order.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use iced_code_editor::{Language, Translations};
+    ///
+    /// let en = Translations::new(Language::English);
+    /// assert_eq!(en.command_palette_sort_lines_natural(), "Sort Lines Naturally");
+    /// ```
+    #[must_use]
+    pub fn command_palette_sort_lines_natural(&self) -> String {
+        rust_i18n::t!(
+            "command_palette.sort_lines_natural",
+            locale = self.language.to_locale()
+        )
+        .into_owned()
+    }
+
+    /// Returns the command-palette label for reversing lines.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use iced_code_editor::{Language, Translations};
+    ///
+    /// let en = Translations::new(Language::English);
+    /// assert_eq!(en.command_palette_reverse_lines(), "Reverse Lines");
+    /// ```
+    #[must_use]
+    pub fn command_palette_reverse_lines(&self) -> String {
+        rust_i18n::t!(
+            "command_palette.reverse_lines",
+            locale = self.language.to_locale()
+        )
+        .into_owned()
+    }
+
+    /// Returns the command-palette label for removing duplicate lines.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use iced_code_editor::{Language, Translations};
+    ///
+    /// let en = Translations::new(Language::English);
+    /// assert_eq!(en.command_palette_remove_duplicate_lines(), "Remove Duplicate Lines");
+    /// ```
+    #[must_use]
+    pub fn command_palette_remove_duplicate_lines(&self) -> String {
+        rust_i18n::t!(
+            "command_palette.remove_duplicate_lines",
+            locale = self.language.to_locale()
+        )
+        .into_owned()
+    }
+
+    /// Returns the command-palette label for joining lines.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use iced_code_editor::{Language, Translations};
+    ///
+    /// let en = Translations::new(Language::English);
+    /// assert_eq!(en.command_palette_join_lines(), "Join Lines");
+    /// ```
+    #[must_use]
+    pub fn command_palette_join_lines(&self) -> String {
+        rust_i18n::t!(
+            "command_palette.join_lines",
+            locale = self.language.to_locale()
+        )
+        .into_owned()
+    }
+
+    /// Returns the command-palette label for transposing characters.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use iced_code_editor::{Language, Translations};
+    ///
+    /// let en = Translations::new(Language::English);
+    /// assert_eq!(en.command_palette_transpose_characters(), "Transpose Characters");
+    /// ```
+    #[must_use]
+    pub fn command_palette_transpose_characters(&self) -> String {
+        rust_i18n::t!(
+            "command_palette.transpose_characters",
+            locale = self.language.to_locale()
+        )
+        .into_owned()
+    }
+
+    /// Returns the command-palette label for converting text to uppercase.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use iced_code_editor::{Language, Translations};
+    ///
+    /// let en = Translations::new(Language::English);
+    /// assert_eq!(en.command_palette_upper_case(), "Transform to Uppercase");
+    /// ```
+    #[must_use]
+    pub fn command_palette_upper_case(&self) -> String {
+        rust_i18n::t!(
+            "command_palette.upper_case",
+            locale = self.language.to_locale()
+        )
+        .into_owned()
+    }
+
+    /// Returns the command-palette label for converting text to lowercase.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use iced_code_editor::{Language, Translations};
+    ///
+    /// let en = Translations::new(Language::English);
+    /// assert_eq!(en.command_palette_lower_case(), "Transform to Lowercase");
+    /// ```
+    #[must_use]
+    pub fn command_palette_lower_case(&self) -> String {
+        rust_i18n::t!(
+            "command_palette.lower_case",
+            locale = self.language.to_locale()
+        )
+        .into_owned()
+    }
+
+    /// Returns the command-palette label for converting text to title case.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use iced_code_editor::{Language, Translations};
+    ///
+    /// let en = Translations::new(Language::English);
+    /// assert_eq!(en.command_palette_title_case(), "Transform to Title Case");
+    /// ```
+    #[must_use]
+    pub fn command_palette_title_case(&self) -> String {
+        rust_i18n::t!(
+            "command_palette.title_case",
+            locale = self.language.to_locale()
+        )
+        .into_owned()
+    }
+
+    /// Returns the command-palette label for converting text to snake_case.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use iced_code_editor::{Language, Translations};
+    ///
+    /// let en = Translations::new(Language::English);
+    /// assert_eq!(en.command_palette_snake_case(), "Transform to snake_case");
+    /// ```
+    #[must_use]
+    pub fn command_palette_snake_case(&self) -> String {
+        rust_i18n::t!(
+            "command_palette.snake_case",
+            locale = self.language.to_locale()
+        )
+        .into_owned()
+    }
+
+    /// Returns the command-palette label for converting text to camelCase.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use iced_code_editor::{Language, Translations};
+    ///
+    /// let en = Translations::new(Language::English);
+    /// assert_eq!(en.command_palette_camel_case(), "Transform to camelCase");
+    /// ```
+    #[must_use]
+    pub fn command_palette_camel_case(&self) -> String {
+        rust_i18n::t!(
+            "command_palette.camel_case",
+            locale = self.language.to_locale()
+        )
+        .into_owned()
+    }
+
+    /// Returns the command-palette label for converting text to kebab-case.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use iced_code_editor::{Language, Translations};
+    ///
+    /// let en = Translations::new(Language::English);
+    /// assert_eq!(en.command_palette_kebab_case(), "Transform to kebab-case");
+    /// ```
+    #[must_use]
+    pub fn command_palette_kebab_case(&self) -> String {
+        rust_i18n::t!(
+            "command_palette.kebab_case",
+            locale = self.language.to_locale()
+        )
+        .into_owned()
+    }
+
+    /// Returns the command-palette label for trimming trailing whitespace.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use iced_code_editor::{Language, Translations};
+    ///
+    /// let en = Translations::new(Language::English);
+    /// assert_eq!(en.command_palette_trim_trailing_whitespace(), "Trim Trailing Whitespace");
+    /// ```
+    #[must_use]
+    pub fn command_palette_trim_trailing_whitespace(&self) -> String {
+        rust_i18n::t!(
+            "command_palette.trim_trailing_whitespace",
+            locale = self.language.to_locale()
+        )
+        .into_owned()
+    }
+
+    /// Returns the command-palette label for incrementing numbers.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use iced_code_editor::{Language, Translations};
+    ///
+    /// let en = Translations::new(Language::English);
+    /// assert_eq!(en.command_palette_increment_number(), "Increment Number");
+    /// ```
+    #[must_use]
+    pub fn command_palette_increment_number(&self) -> String {
+        rust_i18n::t!(
+            "command_palette.increment_number",
+            locale = self.language.to_locale()
+        )
+        .into_owned()
+    }
+
+    /// Returns the command-palette label for decrementing numbers.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use iced_code_editor::{Language, Translations};
+    ///
+    /// let en = Translations::new(Language::English);
+    /// assert_eq!(en.command_palette_decrement_number(), "Decrement Number");
+    /// ```
+    #[must_use]
+    pub fn command_palette_decrement_number(&self) -> String {
+        rust_i18n::t!(
+            "command_palette.decrement_number",
+            locale = self.language.to_locale()
+        )
+        .into_owned()
+    }
+
+    /// Returns the command-palette label for indenting lines.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use iced_code_editor::{Language, Translations};
+    ///
+    /// let en = Translations::new(Language::English);
+    /// assert_eq!(en.command_palette_indent_lines(), "Indent Lines");
+    /// ```
+    #[must_use]
+    pub fn command_palette_indent_lines(&self) -> String {
+        rust_i18n::t!(
+            "command_palette.indent_lines",
+            locale = self.language.to_locale()
+        )
+        .into_owned()
+    }
+
+    /// Returns the command-palette label for outdenting lines.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use iced_code_editor::{Language, Translations};
+    ///
+    /// let en = Translations::new(Language::English);
+    /// assert_eq!(en.command_palette_outdent_lines(), "Outdent Lines");
+    /// ```
+    #[must_use]
+    pub fn command_palette_outdent_lines(&self) -> String {
+        rust_i18n::t!(
+            "command_palette.outdent_lines",
+            locale = self.language.to_locale()
+        )
+        .into_owned()
+    }
+
+    /// Returns the command-palette label for converting indentation to spaces.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use iced_code_editor::{Language, Translations};
+    ///
+    /// let en = Translations::new(Language::English);
+    /// assert_eq!(en.command_palette_convert_indentation_to_spaces(), "Convert Indentation to Spaces");
+    /// ```
+    #[must_use]
+    pub fn command_palette_convert_indentation_to_spaces(&self) -> String {
+        rust_i18n::t!(
+            "command_palette.convert_indentation_to_spaces",
+            locale = self.language.to_locale()
+        )
+        .into_owned()
+    }
+
+    /// Returns the command-palette label for converting indentation to tabs.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use iced_code_editor::{Language, Translations};
+    ///
+    /// let en = Translations::new(Language::English);
+    /// assert_eq!(en.command_palette_convert_indentation_to_tabs(), "Convert Indentation to Tabs");
+    /// ```
+    #[must_use]
+    pub fn command_palette_convert_indentation_to_tabs(&self) -> String {
+        rust_i18n::t!(
+            "command_palette.convert_indentation_to_tabs",
+            locale = self.language.to_locale()
+        )
+        .into_owned()
+    }
+
     /// Returns the command-palette label for moving the current line up.
     ///
     /// # Examples
@@ -686,6 +1107,46 @@ impl Translations {
         .into_owned()
     }
 
+    /// Returns the command-palette label for returning to the position
+    /// before the last jump.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use iced_code_editor::{Language, Translations};
+    ///
+    /// let en = Translations::new(Language::English);
+    /// assert_eq!(en.command_palette_navigate_back(), "Go Back");
+    /// ```
+    #[must_use]
+    pub fn command_palette_navigate_back(&self) -> String {
+        rust_i18n::t!(
+            "command_palette.navigate_back",
+            locale = self.language.to_locale()
+        )
+        .into_owned()
+    }
+
+    /// Returns the command-palette label for redoing a jump undone by
+    /// "Go Back".
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use iced_code_editor::{Language, Translations};
+    ///
+    /// let en = Translations::new(Language::English);
+    /// assert_eq!(en.command_palette_navigate_forward(), "Go Forward");
+    /// ```
+    #[must_use]
+    pub fn command_palette_navigate_forward(&self) -> String {
+        rust_i18n::t!(
+            "command_palette.navigate_forward",
+            locale = self.language.to_locale()
+        )
+        .into_owned()
+    }
+
     /// Returns the command-palette label for adding a cursor on the line above.
     ///
     /// # Examples
@@ -743,6 +1204,86 @@ impl Translations {
         .into_owned()
     }
 
+    /// Returns the command-palette label for moving to the next occurrence
+    /// of the symbol under the cursor.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use iced_code_editor::{Language, Translations};
+    ///
+    /// let en = Translations::new(Language::English);
+    /// assert_eq!(en.command_palette_next_highlight(), "Go to Next Highlight");
+    /// ```
+    #[must_use]
+    pub fn command_palette_next_highlight(&self) -> String {
+        rust_i18n::t!(
+            "command_palette.next_highlight",
+            locale = self.language.to_locale()
+        )
+        .into_owned()
+    }
+
+    /// Returns the command-palette label for moving to the previous
+    /// occurrence of the symbol under the cursor.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use iced_code_editor::{Language, Translations};
+    ///
+    /// let en = Translations::new(Language::English);
+    /// assert_eq!(en.command_palette_previous_highlight(), "Go to Previous Highlight");
+    /// ```
+    #[must_use]
+    pub fn command_palette_previous_highlight(&self) -> String {
+        rust_i18n::t!(
+            "command_palette.previous_highlight",
+            locale = self.language.to_locale()
+        )
+        .into_owned()
+    }
+
+    /// Returns the command-palette label for growing the selection to the
+    /// next enclosing syntactic range.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use iced_code_editor::{Language, Translations};
+    ///
+    /// let en = Translations::new(Language::English);
+    /// assert_eq!(en.command_palette_expand_selection(), "Expand Selection");
+    /// ```
+    #[must_use]
+    pub fn command_palette_expand_selection(&self) -> String {
+        rust_i18n::t!(
+            "command_palette.expand_selection",
+            locale = self.language.to_locale()
+        )
+        .into_owned()
+    }
+
+    /// Returns the command-palette label for undoing the last selection
+    /// expansion.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use iced_code_editor::{Language, Translations};
+    ///
+    /// let en = Translations::new(Language::English);
+    /// assert_eq!(en.command_palette_shrink_selection(), "Shrink Selection");
+    /// ```
+    #[must_use]
+    pub fn command_palette_shrink_selection(&self) -> String {
+        rust_i18n::t!(
+            "command_palette.shrink_selection",
+            locale = self.language.to_locale()
+        )
+        .into_owned()
+    }
+
     /// Returns the command-palette label for toggling Vim mode.
     ///
     /// # Examples
@@ -856,6 +1397,71 @@ impl Translations {
         )
         .into_owned()
     }
+
+    /// Returns the command-palette label for opening the clipboard history.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use iced_code_editor::{Language, Translations};
+    ///
+    /// let en = Translations::new(Language::English);
+    /// assert_eq!(en.command_palette_paste_from_history(), "Paste from History");
+    /// ```
+    #[must_use]
+    pub fn command_palette_paste_from_history(&self) -> String {
+        rust_i18n::t!(
+            "command_palette.paste_from_history",
+            locale = self.language.to_locale()
+        )
+        .into_owned()
+    }
+
+    /// Returns the filter input's placeholder while the palette lists the
+    /// clipboard history.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use iced_code_editor::{Language, Translations};
+    ///
+    /// let en = Translations::new(Language::English);
+    /// assert_eq!(
+    ///     en.command_palette_clipboard_placeholder(),
+    ///     "Search clipboard history..."
+    /// );
+    /// ```
+    #[must_use]
+    pub fn command_palette_clipboard_placeholder(&self) -> String {
+        rust_i18n::t!(
+            "command_palette.clipboard_placeholder",
+            locale = self.language.to_locale()
+        )
+        .into_owned()
+    }
+
+    /// Returns the empty-result message while the palette lists the
+    /// clipboard history.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use iced_code_editor::{Language, Translations};
+    ///
+    /// let en = Translations::new(Language::English);
+    /// assert_eq!(
+    ///     en.command_palette_clipboard_no_results(),
+    ///     "No matching clipboard entry"
+    /// );
+    /// ```
+    #[must_use]
+    pub fn command_palette_clipboard_no_results(&self) -> String {
+        rust_i18n::t!(
+            "command_palette.clipboard_no_results",
+            locale = self.language.to_locale()
+        )
+        .into_owned()
+    }
 }
 
 #[cfg(test)]
@@ -1312,20 +1918,51 @@ mod tests {
                 t.command_palette_no_results(),
                 t.command_palette_save(),
                 t.command_palette_toggle_comment(),
+                t.command_palette_toggle_block_comment(),
+                t.command_palette_reindent_lines(),
+                t.command_palette_sort_lines_ascending(),
+                t.command_palette_sort_lines_descending(),
+                t.command_palette_sort_lines_natural(),
+                t.command_palette_reverse_lines(),
+                t.command_palette_remove_duplicate_lines(),
+                t.command_palette_join_lines(),
+                t.command_palette_transpose_characters(),
+                t.command_palette_upper_case(),
+                t.command_palette_lower_case(),
+                t.command_palette_title_case(),
+                t.command_palette_snake_case(),
+                t.command_palette_camel_case(),
+                t.command_palette_kebab_case(),
+                t.command_palette_trim_trailing_whitespace(),
+                t.command_palette_increment_number(),
+                t.command_palette_decrement_number(),
+                t.command_palette_indent_lines(),
+                t.command_palette_outdent_lines(),
+                t.command_palette_convert_indentation_to_spaces(),
+                t.command_palette_convert_indentation_to_tabs(),
                 t.command_palette_move_line_up(),
                 t.command_palette_move_line_down(),
                 t.command_palette_duplicate_line_up(),
                 t.command_palette_duplicate_line_down(),
                 t.command_palette_goto_line(),
+                t.command_palette_navigate_back(),
+                t.command_palette_navigate_forward(),
                 t.command_palette_add_cursor_above(),
                 t.command_palette_add_cursor_below(),
                 t.command_palette_select_next_occurrence(),
+                t.command_palette_next_highlight(),
+                t.command_palette_previous_highlight(),
+                t.command_palette_expand_selection(),
+                t.command_palette_shrink_selection(),
                 t.command_palette_toggle_vim_mode(),
                 t.command_palette_find(),
                 t.command_palette_replace(),
                 t.command_palette_fold_at_cursor(),
                 t.command_palette_fold_all(),
                 t.command_palette_unfold_all(),
+                t.command_palette_paste_from_history(),
+                t.command_palette_clipboard_placeholder(),
+                t.command_palette_clipboard_no_results(),
             ];
 
             for label in labels {
@@ -245,21 +245,40 @@ pub mod theme;
 #[doc(hidden)]
 #[cfg(feature = "bench")]
 pub use canvas_editor::bench_support;
+pub use canvas_editor::dap::{
+    DapBreakpointStatus, DapScope, DapSession, DapSourceBreakpoint,
+    DapStackFrame, DapThread, DapVariable,
+};
+pub use canvas_editor::features::breakpoints::Breakpoint;
+pub use canvas_editor::features::comment_tokens::CommentTokens;
 pub use canvas_editor::features::folding::FoldRegion;
+pub use canvas_editor::features::indent_rules::IndentRules;
+pub use canvas_editor::features::snippet::Snippet;
 /// LSP integration types and traits for editor clients.
 pub use canvas_editor::lsp::{
-    LspClient, LspDocument, LspPosition, LspRange, LspTextChange,
-    compute_text_change,
+    LspClient, LspColorPresentation, LspCompletionItem, LspDocument,
+    LspDocumentColor, LspDocumentHighlight, LspDocumentHighlightKind,
+    LspDocumentLink, LspEditorReply, LspLocation, LspPosition, LspRange,
+    LspRequest, LspTextChange, compute_text_change,
 };
 pub use canvas_editor::{
     ArrowDirection, CodeEditor, CommandHistory, ContextMenuEntry,
-    ContextMenuItem, IndentStyle, Message, VimMode,
+    ContextMenuItem, IndentStyle, Message, SortOrder, TextCase, VimMapMode,
+    VimMode, WordUnit,
 };
 pub use i18n::{Language, Translations};
 pub use theme::{Catalog, Style, StyleFn, from_iced_theme};
 
 #[cfg(all(feature = "lsp-process", not(target_arch = "wasm32")))]
-pub use canvas_editor::lsp::process::{LspEvent, LspProcessClient};
+pub use canvas_editor::lsp::process::{
+    LspEvent, LspProcessClient, LspSignatureHelp,
+};
+
+#[cfg(all(feature = "dap-process", not(target_arch = "wasm32")))]
+pub use canvas_editor::dap::process::{DapEvent, DapProcessClient};
+
+#[cfg(all(feature = "lsp-process", not(target_arch = "wasm32")))]
+pub use canvas_editor::lsp::process::hub::{LspEditorEvent, LspEventHub};
 
 #[cfg(all(feature = "lsp-process", not(target_arch = "wasm32")))]
 pub use canvas_editor::lsp::process::config::{
@@ -56,6 +56,18 @@ pub struct Style {
     pub bracket_match_color: Color,
     /// Background fill color for the IME preedit (composition) region
     pub ime_preedit_background_color: Color,
+    /// Fill color for occurrences of the symbol under the cursor that read
+    /// it (also used for plain textual occurrences)
+    pub document_highlight_read_color: Color,
+    /// Fill color for occurrences of the symbol under the cursor that write
+    /// to it
+    pub document_highlight_write_color: Color,
+    /// Fill color of breakpoint markers in the breakpoint margin
+    pub breakpoint_color: Color,
+    /// Fill color of the arrow marking the line where execution is stopped
+    pub execution_arrow_color: Color,
+    /// Highlight color of the line where execution is stopped
+    pub execution_line_highlight: Color,
 }
 
 /// The theme catalog of a code editor.
@@ -221,6 +233,19 @@ pub fn from_iced_theme(theme: &iced::Theme) -> Style {
     let ime_preedit_background_color =
         Color { r: 1.0, g: 1.0, b: 1.0, a: 0.08 };
 
+    // Occurrences of the symbol under the cursor: neutral gray for reads,
+    // rose for writes, so assignments stand out from uses.
+    let document_highlight_read_color =
+        Color { r: 0.5, g: 0.5, b: 0.5, a: 0.25 };
+    let document_highlight_write_color =
+        Color { r: 0.85, g: 0.35, b: 0.55, a: 0.25 };
+
+    // Debugging: the conventional red breakpoint dot, and amber for the line
+    // where execution is stopped.
+    let breakpoint_color = Color { r: 0.9, g: 0.25, b: 0.25, a: 1.0 };
+    let execution_arrow_color = Color { r: 1.0, g: 0.8, b: 0.0, a: 1.0 };
+    let execution_line_highlight = Color { r: 1.0, g: 0.8, b: 0.0, a: 0.2 };
+
     Style {
         background,
         text_color,
@@ -237,6 +262,11 @@ pub fn from_iced_theme(theme: &iced::Theme) -> Style {
         selection_color,
         bracket_match_color,
         ime_preedit_background_color,
+        document_highlight_read_color,
+        document_highlight_write_color,
+        breakpoint_color,
+        execution_arrow_color,
+        execution_line_highlight,
     }
 }
 
@@ -364,6 +394,15 @@ mod tests {
                     "ime_preedit_background_color",
                     style.ime_preedit_background_color,
                 ),
+                (
+                    "document_highlight_read_color",
+                    style.document_highlight_read_color,
+                ),
+                (
+                    "document_highlight_write_color",
+                    style.document_highlight_write_color,
+                ),
+                ("execution_line_highlight", style.execution_line_highlight),
             ] {
                 assert!(
                     color.r >= 0.0 && color.r <= 1.0,
@@ -387,7 +426,7 @@ mod tests {
 
     #[test]
     fn test_overlay_colors_are_theme_independent() {
-        // The 5 overlay colors are fixed constants (not palette-derived), so
+        // The 8 overlay colors are fixed constants (not palette-derived), so
         // they must be identical across very different themes.
         let light = from_iced_theme(&iced::Theme::Light);
         let dark = from_iced_theme(&iced::Theme::Dark);
@@ -401,6 +440,15 @@ mod tests {
                 light.ime_preedit_background_color,
                 dark.ime_preedit_background_color,
             ),
+            (
+                light.document_highlight_read_color,
+                dark.document_highlight_read_color,
+            ),
+            (
+                light.document_highlight_write_color,
+                dark.document_highlight_write_color,
+            ),
+            (light.execution_line_highlight, dark.execution_line_highlight),
         ] {
             assert!((light_color.r - dark_color.r).abs() < f32::EPSILON);
             assert!((light_color.g - dark_color.g).abs() < f32::EPSILON);